@@ -87,6 +87,7 @@ impl TryFrom<DbEndpoint> for dozer_types::models::api_endpoint::ApiEndpoint {
             index: Some(ApiIndex {
                 primary_key: primary_keys_arr,
             }),
+            json_options: None,
         })
     }
 }
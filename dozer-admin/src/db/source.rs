@@ -70,6 +70,7 @@ fn convert_to_source(
         columns: columns_value,
         connection: Some(connection),
         refresh_config: Some(dozer_types::models::source::RefreshConfig::default()),
+        primary_key_columns: vec![],
     }
 }
 impl Persistable<'_, dozer_types::models::source::Source> for dozer_types::models::source::Source {
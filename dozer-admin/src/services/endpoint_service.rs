@@ -32,6 +32,7 @@ impl EndpointService {
             path: request.path.to_owned(),
             sql: request.sql,
             index: request.index,
+            json_options: None,
         };
         endpoint_info
             .upsert(self.db_pool.to_owned())
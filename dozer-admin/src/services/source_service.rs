@@ -55,6 +55,7 @@ impl SourceService {
                 connection: Some(input_connection),
                 columns: input.columns,
                 refresh_config: Some(dozer_types::models::source::RefreshConfig::default()),
+                primary_key_columns: vec![],
             };
             source_info
                 .upsert(self.db_pool.to_owned())
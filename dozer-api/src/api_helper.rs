@@ -1,13 +1,16 @@
-use crate::auth::Access;
-use crate::errors::{ApiError, AuthError};
+use crate::auth::{Access, EndpointOperation};
+use crate::errors::ApiError;
 use crate::generator::oapi::generator::OpenApiGenerator;
 use crate::PipelineDetails;
-use dozer_cache::cache::{expression::QueryExpression, index};
+use dozer_cache::cache::{
+    check_schema_version, cursor::encode_cursor, expression::QueryExpression, index,
+};
 use dozer_cache::errors::CacheError;
-use dozer_cache::{AccessFilter, CacheReader};
+use dozer_cache::CacheReader;
 use dozer_types::indexmap::IndexMap;
 use dozer_types::json_str_to_field;
-use dozer_types::record_to_map;
+use dozer_types::models::api_endpoint::ApiJsonOptions;
+use dozer_types::record_to_map_with_options;
 use dozer_types::serde_json::Value;
 use dozer_types::types::{Record, Schema};
 use openapiv3::OpenAPI;
@@ -20,25 +23,12 @@ impl<'a> ApiHelper<'a> {
     pub fn new(
         pipeline_details: &'a PipelineDetails,
         access: Option<Access>,
+        operation: Option<EndpointOperation>,
     ) -> Result<Self, ApiError> {
         let access = access.unwrap_or(Access::All);
 
         // Define Access Filter based on token
-        let reader_access = match access {
-            // No access filter.
-            Access::All => AccessFilter {
-                filter: None,
-                fields: vec![],
-            },
-
-            Access::Custom(mut access_filters) => {
-                if let Some(access_filter) = access_filters.remove(&pipeline_details.schema_name) {
-                    access_filter
-                } else {
-                    return Err(ApiError::ApiAuthError(AuthError::InvalidToken));
-                }
-            }
-        };
+        let reader_access = access.authorize(&pipeline_details.schema_name, operation)?;
 
         let reader = CacheReader {
             cache: pipeline_details.cache_endpoint.cache.clone(),
@@ -72,6 +62,13 @@ impl<'a> ApiHelper<'a> {
 
     /// Get a single record by json string as primary key
     pub fn get_record(&self, key: &str) -> Result<IndexMap<String, Value>, CacheError> {
+        let (schema, rec) = self.get_record_with_schema(key)?;
+        record_to_map_with_options(&rec, &schema, &self.json_options())
+            .map_err(CacheError::TypeError)
+    }
+
+    /// Get a single record by json string as primary key, along with the schema of the endpoint
+    pub fn get_record_with_schema(&self, key: &str) -> Result<(Schema, Record), CacheError> {
         let schema = self
             .reader
             .get_schema_and_indexes_by_name(&self.details.schema_name)?
@@ -91,26 +88,30 @@ impl<'a> ApiHelper<'a> {
 
         let key = index::get_primary_key(&[0], &[key]);
         let rec = self.reader.get(&key)?;
+        check_schema_version(&rec, &schema)?;
 
-        record_to_map(&rec, &schema).map_err(CacheError::TypeError)
+        Ok((schema, rec))
     }
 
     pub fn get_records_count(&self, mut exp: QueryExpression) -> Result<usize, CacheError> {
         self.reader.count(&self.details.schema_name, &mut exp)
     }
 
-    /// Get multiple records
-    pub fn get_records_map(
+    /// Get multiple records, and the cursor to pass as `$after` to get the records following them
+    pub fn get_records_page(
         &self,
         exp: QueryExpression,
-    ) -> Result<Vec<IndexMap<String, Value>>, CacheError> {
-        let mut maps = vec![];
+    ) -> Result<(Vec<IndexMap<String, Value>>, Option<String>), CacheError> {
         let (schema, records) = self.get_records(exp)?;
-        for rec in records.iter() {
-            let map = record_to_map(rec, &schema)?;
-            maps.push(map);
-        }
-        Ok(maps)
+        let cursor = records
+            .last()
+            .and_then(|record| encode_cursor(&schema, record));
+        let json_options = self.json_options();
+        let maps = records
+            .iter()
+            .map(|record| record_to_map_with_options(record, &schema, &json_options))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((maps, cursor))
     }
     /// Get multiple records
     pub fn get_records(
@@ -126,6 +127,16 @@ impl<'a> ApiHelper<'a> {
         Ok((schema, records))
     }
 
+    /// How the records of the endpoint are serialized to JSON
+    fn json_options(&self) -> ApiJsonOptions {
+        self.details
+            .cache_endpoint
+            .endpoint
+            .json_options
+            .clone()
+            .unwrap_or_default()
+    }
+
     /// Get schema
     pub fn get_schema(&self) -> Result<Schema, CacheError> {
         let schema = self
@@ -10,6 +10,7 @@ use dozer_types::{
 };
 
 use crate::errors::{ApiError, AuthError};
+use crate::rest::TokenSubject;
 
 use super::{Access, Authorizer};
 
@@ -43,7 +44,7 @@ fn get_secret(req: &HttpRequest) -> Result<&str, AuthError> {
         .ok_or(AuthError::Unauthorized)?;
 
     match api_security {
-        ApiSecurity::Jwt(secret) => Ok(secret.as_str()),
+        ApiSecurity::Jwt(jwt) => Ok(jwt.secret.as_str()),
     }
 }
 pub async fn validate(
@@ -54,8 +55,8 @@ pub async fn validate(
         .app_data::<ApiSecurity>()
         .expect("We only validate bearer tokens if ApiSecurity is set");
     match api_security {
-        ApiSecurity::Jwt(secret) => {
-            let api_auth = Authorizer::new(secret, None, None);
+        ApiSecurity::Jwt(_) => {
+            let api_auth = Authorizer::from(api_security);
             let res = api_auth
                 .validate_token(credentials.token())
                 .map_err(|e| (Error::from(ApiError::ApiAuthError(e))));
@@ -64,6 +65,7 @@ pub async fn validate(
                 Ok(claims) => {
                     // Provide access to all
                     req.extensions_mut().insert(claims.access);
+                    req.extensions_mut().insert(TokenSubject(claims.sub));
                     Ok(req)
                 }
                 Err(e) => Err((e, req)),
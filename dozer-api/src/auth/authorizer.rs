@@ -10,6 +10,8 @@ use super::{Access, Claims};
 
 pub struct Authorizer<'a> {
     secret: &'a [u8],
+    /// Secrets that tokens are still validated with, but not signed with.
+    previous_secrets: Vec<&'a [u8]>,
     aud: &'a str,
     sub: &'a str,
 }
@@ -18,11 +20,18 @@ impl<'a> Authorizer<'a> {
     pub fn new(secret: &'a str, aud: Option<&'a str>, sub: Option<&'a str>) -> Self {
         Self {
             secret: secret.as_bytes(),
+            previous_secrets: vec![],
             aud: aud.unwrap_or("cache_user"),
             sub: sub.unwrap_or("api@dozer.com"),
         }
     }
 
+    /// Also accepts tokens signed with `previous_secrets`, while keys are being rotated.
+    pub fn with_previous_secrets(mut self, previous_secrets: &'a [String]) -> Self {
+        self.previous_secrets = previous_secrets.iter().map(|s| s.as_bytes()).collect();
+        self
+    }
+
     /// Creates exp based on duration provided with a default of 300 seconds
     pub fn get_expiry(dur: Option<Duration>) -> u64 {
         let start = SystemTime::now();
@@ -66,7 +75,17 @@ impl<'a> Authorizer<'a> {
         validation.sub = Some(self.sub.to_owned());
         validation.set_audience(&[self.aud.to_owned()]);
 
-        match decode::<Claims>(token, &DecodingKey::from_secret(self.secret), &validation) {
+        let mut result =
+            decode::<Claims>(token, &DecodingKey::from_secret(self.secret), &validation);
+        // Only a token signed with another secret is worth validating again.
+        for secret in &self.previous_secrets {
+            if !matches!(&result, Err(err) if matches!(err.kind(), ErrorKind::InvalidSignature)) {
+                break;
+            }
+            result = decode::<Claims>(token, &DecodingKey::from_secret(secret), &validation);
+        }
+
+        match result {
             Ok(c) => Ok(c.claims),
             Err(err) => Err(match *err.kind() {
                 ErrorKind::InvalidToken => AuthError::InvalidToken,
@@ -80,7 +99,8 @@ impl<'a> Authorizer<'a> {
 impl<'a> From<&'a ApiSecurity> for Authorizer<'a> {
     fn from(value: &'a ApiSecurity) -> Self {
         match value {
-            ApiSecurity::Jwt(secret) => Authorizer::new(secret, None, None),
+            ApiSecurity::Jwt(jwt) => Authorizer::new(&jwt.secret, None, None)
+                .with_previous_secrets(&jwt.previous_secrets),
         }
     }
 }
@@ -98,4 +118,37 @@ mod tests {
         let token_data = auth_utils.validate_token(&token).unwrap();
         assert_eq!(token_data.access, Access::All, "must be equal");
     }
+
+    #[test]
+    fn validate_token_signed_with_previous_secret() {
+        let old_token = Authorizer::new("old", None, None)
+            .generate_token(Access::All, None)
+            .unwrap();
+        let unknown_token = Authorizer::new("unknown", None, None)
+            .generate_token(Access::All, None)
+            .unwrap();
+
+        let previous_secrets = vec!["older".to_string(), "old".to_string()];
+        let auth_utils =
+            Authorizer::new("new", None, None).with_previous_secrets(&previous_secrets);
+        assert_eq!(
+            auth_utils.validate_token(&old_token).unwrap().access,
+            Access::All
+        );
+        assert!(auth_utils.validate_token(&unknown_token).is_err());
+
+        // New tokens are signed with the current secret only.
+        let new_token = auth_utils.generate_token(Access::All, None).unwrap();
+        assert!(Authorizer::new("new", None, None)
+            .validate_token(&new_token)
+            .is_ok());
+        assert!(Authorizer::new("old", None, None)
+            .validate_token(&new_token)
+            .is_err());
+
+        // Once the window is over, tokens signed with the old secret are rejected.
+        assert!(Authorizer::new("new", None, None)
+            .validate_token(&old_token)
+            .is_err());
+    }
 }
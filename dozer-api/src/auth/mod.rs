@@ -1,4 +1,5 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 use dozer_cache::AccessFilter;
 use dozer_types::serde;
@@ -6,6 +7,9 @@ use serde::{Deserialize, Serialize};
 pub mod api;
 pub mod authorizer;
 pub use authorizer::Authorizer;
+
+use crate::errors::AuthError;
+
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 #[serde(crate = "self::serde")]
 pub struct Claims {
@@ -20,8 +24,82 @@ pub struct Claims {
 pub enum Access {
     /// Access to all indexes
     All,
-    /// (endpoint_name, AccessFilter) Specific permissions to each of the indexes
-    Custom(HashMap<String, AccessFilter>),
+    /// (endpoint_name, EndpointAccess) Specific permissions to each of the indexes
+    Custom(HashMap<String, EndpointAccess>),
+}
+
+impl Access {
+    /// Returns the filter to apply to the records of `endpoint`, if `operation` is permitted on it.
+    ///
+    /// `operation` is `None` for requests that don't read records, like fetching the schema.
+    pub fn authorize(
+        self,
+        endpoint: &str,
+        operation: Option<EndpointOperation>,
+    ) -> Result<AccessFilter, AuthError> {
+        match self {
+            Access::All => Ok(AccessFilter {
+                filter: None,
+                fields: vec![],
+            }),
+            Access::Custom(mut endpoints) => {
+                let endpoint_access = endpoints
+                    .remove(endpoint)
+                    .ok_or_else(|| AuthError::Forbidden(endpoint.to_string(), operation))?;
+                match operation {
+                    Some(operation) if !endpoint_access.operations.contains(&operation) => {
+                        Err(AuthError::Forbidden(endpoint.to_string(), Some(operation)))
+                    }
+                    _ => Ok(endpoint_access.filter),
+                }
+            }
+        }
+    }
+}
+
+/// Permissions of a token on an endpoint.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[serde(crate = "self::serde")]
+pub struct EndpointAccess {
+    /// Restricts the records that can be read.
+    #[serde(flatten)]
+    pub filter: AccessFilter,
+    /// Operations that can be performed. All of them if not specified.
+    #[serde(default = "EndpointOperation::all")]
+    pub operations: HashSet<EndpointOperation>,
+}
+
+/// An operation on the records of an endpoint.
+#[derive(Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Clone, Copy)]
+#[serde(crate = "self::serde", rename_all = "snake_case")]
+pub enum EndpointOperation {
+    List,
+    Get,
+    Query,
+    Count,
+}
+
+impl EndpointOperation {
+    pub fn all() -> HashSet<EndpointOperation> {
+        HashSet::from([
+            EndpointOperation::List,
+            EndpointOperation::Get,
+            EndpointOperation::Query,
+            EndpointOperation::Count,
+        ])
+    }
+}
+
+impl fmt::Display for EndpointOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            EndpointOperation::List => "list",
+            EndpointOperation::Get => "get",
+            EndpointOperation::Query => "query",
+            EndpointOperation::Count => "count",
+        };
+        f.write_str(name)
+    }
 }
 
 #[cfg(test)]
@@ -31,16 +109,20 @@ mod tests {
     use dozer_cache::AccessFilter;
     use dozer_types::serde_json::json;
 
-    use super::Access;
+    use super::{Access, EndpointAccess, EndpointOperation};
+    use crate::errors::AuthError;
 
     #[test]
     fn serialize_access() {
         let mut access_map = HashMap::new();
         access_map.insert(
             "films".to_string(),
-            AccessFilter {
-                filter: None,
-                fields: vec![],
+            EndpointAccess {
+                filter: AccessFilter {
+                    filter: None,
+                    fields: vec![],
+                },
+                operations: EndpointOperation::all(),
             },
         );
         let access = Access::Custom(access_map);
@@ -55,4 +137,28 @@ mod tests {
 
         assert_eq!(de_access.unwrap(), access, "are equal");
     }
+
+    #[test]
+    fn authorize_custom_access() {
+        let access = dozer_types::serde_json::from_value::<Access>(json!({"Custom": {
+            "films": {"filter": null, "fields": [], "operations": ["get", "count"]},
+        }}))
+        .unwrap();
+
+        assert!(access
+            .clone()
+            .authorize("films", Some(EndpointOperation::Get))
+            .is_ok());
+        assert!(access.clone().authorize("films", None).is_ok());
+        assert!(matches!(
+            access
+                .clone()
+                .authorize("films", Some(EndpointOperation::Query)),
+            Err(AuthError::Forbidden(_, Some(EndpointOperation::Query)))
+        ));
+        assert!(matches!(
+            access.authorize("users", Some(EndpointOperation::Get)),
+            Err(AuthError::Forbidden(_, _))
+        ));
+    }
 }
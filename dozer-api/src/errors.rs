@@ -1,5 +1,5 @@
 #![allow(clippy::enum_variant_names)]
-use actix_web::http::header::ContentType;
+use actix_web::http::header::{ContentType, RETRY_AFTER};
 use actix_web::http::StatusCode;
 use actix_web::HttpResponse;
 use dozer_types::thiserror::Error;
@@ -10,6 +10,8 @@ use dozer_types::errors::internal::BoxedError;
 use dozer_types::errors::types::TypeError;
 use prost_reflect::DescriptorError;
 
+use crate::auth::EndpointOperation;
+
 #[derive(Error, Debug)]
 pub enum ApiError {
     #[error("Invalid query provided")]
@@ -32,6 +34,10 @@ pub enum ApiError {
     SchemaIdentifierNotFound,
     #[error(transparent)]
     PortAlreadyInUse(#[from] std::io::Error),
+    #[error("Too many requests, retry after {0} seconds")]
+    TooManyRequests(u64),
+    #[error("Streaming record changes is not enabled. Enable push_events in the config.")]
+    EventsNotEnabled,
 }
 
 impl ApiError {
@@ -77,7 +83,11 @@ impl From<GRPCError> for tonic::Status {
 
 impl From<ApiError> for tonic::Status {
     fn from(input: ApiError) -> Self {
-        tonic::Status::new(tonic::Code::Unknown, input.to_string())
+        let code = match input {
+            ApiError::ApiAuthError(AuthError::Forbidden(_, _)) => tonic::Code::PermissionDenied,
+            _ => tonic::Code::Unknown,
+        };
+        tonic::Status::new(code, input.to_string())
     }
 }
 
@@ -87,6 +97,8 @@ pub enum InitError {
     PipelineNotInitialized,
     #[error("api_security not initialized")]
     SecurityNotInitialized,
+    #[error("Invalid CORS origin {0}, expected scheme://host[:port]")]
+    InvalidCorsOrigin(String),
 }
 
 #[derive(Error, Debug)]
@@ -95,8 +107,6 @@ pub enum GenerationError {
     InternalError(#[from] BoxedError),
     #[error("directory path does not exist")]
     DirPathNotExist,
-    #[error("DozerType to Proto type not supported: {0}")]
-    DozerToProtoTypeNotSupported(String),
     #[error("Missing primary key to query by id: {0}")]
     MissingPrimaryKeyToQueryById(String),
     #[error("Cannot read proto descriptor: {0}")]
@@ -111,22 +121,30 @@ pub enum AuthError {
     InvalidToken,
     #[error("Issuer is invalid")]
     InvalidIssuer,
+    #[error("Token doesn't permit {} on endpoint {0}", .1.map_or("access".to_string(), |operation| operation.to_string()))]
+    Forbidden(String, Option<EndpointOperation>),
     #[error(transparent)]
     InternalError(#[from] BoxedError),
 }
 
 impl actix_web::error::ResponseError for ApiError {
     fn error_response(&self) -> HttpResponse {
-        HttpResponse::build(self.status_code())
-            .insert_header(ContentType::json())
-            .body(self.to_string())
+        let mut res = HttpResponse::build(self.status_code());
+        res.insert_header(ContentType::json());
+        if let ApiError::TooManyRequests(retry_after) = self {
+            res.insert_header((RETRY_AFTER, *retry_after));
+        }
+        res.body(self.to_string())
     }
 
     fn status_code(&self) -> StatusCode {
         match *self {
             ApiError::TypeError(_) => StatusCode::BAD_REQUEST,
+            ApiError::ApiAuthError(AuthError::Forbidden(_, _)) => StatusCode::FORBIDDEN,
             ApiError::ApiAuthError(_) => StatusCode::UNAUTHORIZED,
             ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::EventsNotEnabled => StatusCode::SERVICE_UNAVAILABLE,
             ApiError::ApiGenerationError(_)
             | ApiError::SchemaNotFound(_)
             | ApiError::InvalidQuery(_) => StatusCode::UNPROCESSABLE_ENTITY,
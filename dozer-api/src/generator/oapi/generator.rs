@@ -1,5 +1,6 @@
 use super::utils::{
-    convert_cache_to_oapi_schema, create_contact_info, create_reference_response, create_response,
+    convert_cache_to_oapi_schema, create_contact_info, create_filter_schema,
+    create_query_parameter, create_query_schema, create_reference_response, create_response,
 };
 use crate::errors::GenerationError;
 use dozer_types::indexmap::{self, IndexMap};
@@ -24,6 +25,12 @@ impl OpenApiGenerator {
     fn get_plural_name(&self) -> String {
         format!("{}_array", self.schema_name.to_owned())
     }
+    fn get_filter_name(&self) -> String {
+        format!("{}_filter", self.schema_name)
+    }
+    fn get_query_name(&self) -> String {
+        format!("{}_query", self.schema_name)
+    }
 
     // Generate first secondary_index as an example
     fn generate_query_example(&self) -> Value {
@@ -40,12 +47,13 @@ impl OpenApiGenerator {
                     FieldType::Binary
                     | FieldType::Decimal
                     | FieldType::Timestamp
-                    | FieldType::Bson => Value::Null,
+                    | FieldType::Bson
+                    | FieldType::Json => Value::Null,
 
                     FieldType::Text => Value::from("lorem ipsum".to_string()),
                     FieldType::Date => Value::from("2022-11-24"),
                 };
-                json!({ name: val })
+                json!({ "$filter": { name: val } })
             } else {
                 json!({})
             }
@@ -113,6 +121,26 @@ impl OpenApiGenerator {
                 "This is used when no filter expression or sort is needed.".to_owned(),
             ),
             operation_id: Some(format!("list-{}", self.endpoint.name.to_owned())),
+            parameters: vec![
+                ReferenceOr::Item(create_query_parameter(
+                    "limit".to_owned(),
+                    Some("Maximum number of documents to return".to_owned()),
+                    false,
+                    Type::Integer(Default::default()),
+                )),
+                ReferenceOr::Item(create_query_parameter(
+                    "skip".to_owned(),
+                    Some("Number of documents to skip".to_owned()),
+                    false,
+                    Type::Integer(Default::default()),
+                )),
+                ReferenceOr::Item(create_query_parameter(
+                    "after".to_owned(),
+                    Some("Cursor from the `x-next-cursor` header of the previous page".to_owned()),
+                    false,
+                    Type::String(Default::default()),
+                )),
+            ],
             responses,
             ..Default::default()
         });
@@ -125,7 +153,11 @@ impl OpenApiGenerator {
     fn generate_count_route(&self) -> ReferenceOr<PathItem> {
         let request_body = RequestBody {
             content: indexmap::indexmap! {
-                "application/json".to_owned() => MediaType { example: Some(self.generate_query_example()), ..Default::default() }
+                "application/json".to_owned() => MediaType {
+                    schema: Some(ReferenceOr::ref_(&format!("#/components/schemas/{}", self.get_query_name()))),
+                    example: Some(self.generate_query_example()),
+                    ..Default::default()
+                }
             },
             required: true,
             ..Default::default()
@@ -166,7 +198,11 @@ impl OpenApiGenerator {
     fn generate_query_route(&self) -> ReferenceOr<PathItem> {
         let request_body = RequestBody {
             content: indexmap::indexmap! {
-                "application/json".to_owned() => MediaType { example: Some(self.generate_query_example()), ..Default::default() }
+                "application/json".to_owned() => MediaType {
+                    schema: Some(ReferenceOr::ref_(&format!("#/components/schemas/{}", self.get_query_name()))),
+                    example: Some(self.generate_query_example()),
+                    ..Default::default()
+                }
             },
             required: true,
             ..Default::default()
@@ -212,8 +248,13 @@ impl OpenApiGenerator {
     }
 
     fn generate_component_schema(&self) -> Components {
-        let generated_schema =
-            convert_cache_to_oapi_schema(self.schema.to_owned(), self.schema_name.to_owned());
+        let json_options = self.endpoint.json_options.clone().unwrap_or_default();
+        let generated_schema = convert_cache_to_oapi_schema(
+            self.schema.to_owned(),
+            self.schema_name.to_owned(),
+            &json_options,
+        );
+        let filter_reference_path = format!("#/components/schemas/{}", self.get_filter_name());
 
         let schemas = indexmap::indexmap! {
             self.get_singular_name() => ReferenceOr::Item(generated_schema),
@@ -228,7 +269,9 @@ impl OpenApiGenerator {
                             max_items: None,
                             unique_items: false,
                         })),
-                    })
+                    }),
+            self.get_filter_name() => ReferenceOr::Item(create_filter_schema(&self.schema, &self.schema_name, &filter_reference_path, &json_options)),
+            self.get_query_name() => ReferenceOr::Item(create_query_schema(&self.schema, &self.schema_name, &filter_reference_path)),
         };
 
         Components {
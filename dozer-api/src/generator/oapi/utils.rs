@@ -1,12 +1,14 @@
+use dozer_cache::cache::expression::{Operator, SortDirection};
 use dozer_types::{
     indexmap::{self, IndexMap},
+    models::api_endpoint::ApiJsonOptions,
     types::{FieldType, DATE_FORMAT},
 };
 use openapiv3::{
-    ArrayType, Contact, IntegerFormat, IntegerType, MediaType, NumberFormat, NumberType,
-    ObjectType, Parameter, ParameterData, ParameterSchemaOrContent, PathStyle, ReferenceOr,
-    Response, Schema, SchemaData, SchemaKind, StringFormat, StringType, Type,
-    VariantOrUnknownOrEmpty,
+    AdditionalProperties, AnySchema, ArrayType, Contact, IntegerFormat, IntegerType, MediaType,
+    NumberFormat, NumberType, ObjectType, Parameter, ParameterData, ParameterSchemaOrContent,
+    PathStyle, QueryStyle, ReferenceOr, Response, Schema, SchemaData, SchemaKind, StringFormat,
+    StringType, Type, VariantOrUnknownOrEmpty,
 };
 
 const CONTACT_NAME: &str = "Dozer Team";
@@ -48,6 +50,35 @@ pub fn _create_path_parameter(
     }
 }
 
+pub fn create_query_parameter(
+    name: String,
+    description: Option<String>,
+    required: bool,
+    param_type: Type,
+) -> Parameter {
+    Parameter::Query {
+        parameter_data: ParameterData {
+            name,
+            description,
+            required,
+            format: ParameterSchemaOrContent::Schema(ReferenceOr::Item(Schema {
+                schema_data: SchemaData {
+                    ..Default::default()
+                },
+                schema_kind: SchemaKind::Type(param_type),
+            })),
+            deprecated: None,
+            example: None,
+            examples: IndexMap::new(),
+            explode: None,
+            extensions: IndexMap::new(),
+        },
+        allow_reserved: false,
+        style: QueryStyle::Form,
+        allow_empty_value: None,
+    }
+}
+
 pub fn create_response(description: String, schema: Schema) -> Response {
     Response {
         description,
@@ -71,6 +102,7 @@ pub fn create_reference_response(description: String, schema_reference_path: Str
 pub fn convert_cache_to_oapi_schema(
     cache_schema: dozer_types::types::Schema,
     name: String,
+    json_options: &ApiJsonOptions,
 ) -> Schema {
     let mut properties: IndexMap<String, ReferenceOr<Box<Schema>>> = IndexMap::new();
     let mut required_properties: Vec<String> = Vec::new();
@@ -82,7 +114,7 @@ pub fn convert_cache_to_oapi_schema(
             field.name,
             ReferenceOr::boxed_item(Schema {
                 schema_data: Default::default(),
-                schema_kind: SchemaKind::Type(convert_cache_type_to_schema_type(field.typ)),
+                schema_kind: convert_cache_type_to_schema_kind(field.typ, json_options),
             }),
         );
     }
@@ -100,9 +132,182 @@ pub fn convert_cache_to_oapi_schema(
     }
 }
 
+const FILTER_OPERATORS: [Operator; 8] = [
+    Operator::EQ,
+    Operator::LT,
+    Operator::LTE,
+    Operator::GT,
+    Operator::GTE,
+    Operator::Contains,
+    Operator::MatchesAny,
+    Operator::MatchesAll,
+];
+
+/// Schema of `$filter`. Every field can be compared to a value directly, which means `$eq`, or
+/// with an object of operators. `$and` takes a list of filters referenced by `filter_reference_path`.
+pub fn create_filter_schema(
+    cache_schema: &dozer_types::types::Schema,
+    name: &str,
+    filter_reference_path: &str,
+    json_options: &ApiJsonOptions,
+) -> Schema {
+    let mut properties: IndexMap<String, ReferenceOr<Box<Schema>>> = IndexMap::new();
+    for field in &cache_schema.fields {
+        let value = Schema {
+            schema_data: Default::default(),
+            schema_kind: convert_cache_type_to_schema_kind(field.typ, json_options),
+        };
+        let is_text = matches!(field.typ, FieldType::String | FieldType::Text);
+        let operators = FILTER_OPERATORS
+            .iter()
+            .filter(|operator| is_text || !operator.supported_by_full_text())
+            .map(|operator| {
+                (
+                    operator.to_str().to_string(),
+                    ReferenceOr::boxed_item(value.clone()),
+                )
+            })
+            .collect();
+        properties.insert(
+            field.name.clone(),
+            ReferenceOr::boxed_item(Schema {
+                schema_data: Default::default(),
+                schema_kind: SchemaKind::OneOf {
+                    one_of: vec![
+                        ReferenceOr::Item(value),
+                        ReferenceOr::Item(type_schema(Type::Object(ObjectType {
+                            properties: operators,
+                            ..Default::default()
+                        }))),
+                    ],
+                },
+            }),
+        );
+    }
+    properties.insert(
+        "$and".to_string(),
+        ReferenceOr::boxed_item(type_schema(Type::Array(ArrayType {
+            items: Some(ReferenceOr::ref_(filter_reference_path)),
+            min_items: None,
+            max_items: None,
+            unique_items: false,
+        }))),
+    );
+
+    Schema {
+        schema_data: SchemaData {
+            description: Some(format!("Filter on {}. All the conditions must hold.", name)),
+            ..Default::default()
+        },
+        schema_kind: SchemaKind::Type(Type::Object(ObjectType {
+            properties,
+            additional_properties: Some(AdditionalProperties::Any(false)),
+            ..Default::default()
+        })),
+    }
+}
+
+/// Schema of the request body of the query and count routes.
+pub fn create_query_schema(
+    cache_schema: &dozer_types::types::Schema,
+    name: &str,
+    filter_reference_path: &str,
+) -> Schema {
+    let field_names: Vec<Option<String>> = cache_schema
+        .fields
+        .iter()
+        .map(|field| Some(field.name.clone()))
+        .collect();
+    let direction = || {
+        ReferenceOr::boxed_item(type_schema(Type::String(StringType {
+            enumeration: [SortDirection::Ascending, SortDirection::Descending]
+                .iter()
+                .map(|direction| Some(direction.to_str().to_string()))
+                .collect(),
+            ..Default::default()
+        })))
+    };
+    let sort_by_object = type_schema(Type::Object(ObjectType {
+        properties: cache_schema
+            .fields
+            .iter()
+            .map(|field| (field.name.clone(), direction()))
+            .collect(),
+        additional_properties: Some(AdditionalProperties::Any(false)),
+        ..Default::default()
+    }));
+    let sort_by_array = type_schema(Type::Array(ArrayType {
+        items: Some(ReferenceOr::boxed_item(type_schema(Type::Object(
+            ObjectType {
+                properties: indexmap::indexmap! {
+                    "field".to_string() => ReferenceOr::boxed_item(type_schema(Type::String(StringType {
+                        enumeration: field_names,
+                        ..Default::default()
+                    }))),
+                    "direction".to_string() => direction(),
+                },
+                required: vec!["field".to_string(), "direction".to_string()],
+                ..Default::default()
+            },
+        )))),
+        min_items: None,
+        max_items: None,
+        unique_items: false,
+    }));
+    let non_negative_integer = || {
+        ReferenceOr::boxed_item(type_schema(Type::Integer(IntegerType {
+            minimum: Some(0),
+            ..Default::default()
+        })))
+    };
+
+    Schema {
+        schema_data: SchemaData {
+            description: Some(format!("A query on {}", name)),
+            ..Default::default()
+        },
+        schema_kind: SchemaKind::Type(Type::Object(ObjectType {
+            properties: indexmap::indexmap! {
+                "$filter".to_string() => ReferenceOr::ref_(filter_reference_path),
+                "$order_by".to_string() => ReferenceOr::boxed_item(Schema {
+                    schema_data: Default::default(),
+                    schema_kind: SchemaKind::OneOf {
+                        one_of: vec![ReferenceOr::Item(sort_by_object), ReferenceOr::Item(sort_by_array)],
+                    },
+                }),
+                "$limit".to_string() => non_negative_integer(),
+                "$skip".to_string() => non_negative_integer(),
+                "$after".to_string() => ReferenceOr::boxed_item(type_schema(Type::String(Default::default()))),
+            },
+            additional_properties: Some(AdditionalProperties::Any(false)),
+            ..Default::default()
+        })),
+    }
+}
+
+fn type_schema(schema_type: Type) -> Schema {
+    Schema {
+        schema_data: Default::default(),
+        schema_kind: SchemaKind::Type(schema_type),
+    }
+}
+
 /// Should be consistent with `field_to_json_value`.
-fn convert_cache_type_to_schema_type(field_type: dozer_types::types::FieldType) -> Type {
-    match field_type {
+fn convert_cache_type_to_schema_kind(
+    field_type: dozer_types::types::FieldType,
+    json_options: &ApiJsonOptions,
+) -> SchemaKind {
+    let schema_type = match field_type {
+        FieldType::UInt | FieldType::Int if json_options.ints_as_strings => {
+            Type::String(StringType {
+                format: VariantOrUnknownOrEmpty::Unknown("int64".to_string()),
+                ..Default::default()
+            })
+        }
+        FieldType::Decimal if json_options.decimals_as_numbers => Type::Number(NumberType {
+            format: VariantOrUnknownOrEmpty::Unknown("decimal".to_string()),
+            ..Default::default()
+        }),
         FieldType::UInt | FieldType::Int => Type::Integer(IntegerType {
             format: VariantOrUnknownOrEmpty::Item(IntegerFormat::Int64),
             ..Default::default()
@@ -142,7 +347,10 @@ fn convert_cache_type_to_schema_type(field_type: dozer_types::types::FieldType)
             max_items: None,
             unique_items: false,
         }),
-    }
+        // JSON fields can hold any value
+        FieldType::Json => return SchemaKind::Any(AnySchema::default()),
+    };
+    SchemaKind::Type(schema_type)
 }
 
 fn u8_schema() -> Schema {
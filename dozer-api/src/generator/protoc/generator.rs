@@ -93,7 +93,7 @@ impl<'a> ProtoGenerator<'a> {
                 if field.nullable {
                     result.push_str("optional ");
                 }
-                let proto_type = convert_dozer_type_to_proto_type(field.typ.to_owned()).unwrap();
+                let proto_type = convert_dozer_type_to_proto_type(field.typ);
                 let _ = writeln!(
                     result,
                     "{} {} = {}; ",
@@ -112,24 +112,8 @@ impl<'a> ProtoGenerator<'a> {
     }
 
     pub fn libs_by_type(&self) -> Result<Vec<String>, GenerationError> {
-        let type_need_import_libs = ["google.protobuf.Timestamp"];
-        let mut libs_import: Vec<String> = self
-            .schema
-            .fields
-            .iter()
-            .map(|field| convert_dozer_type_to_proto_type(field.to_owned().typ).unwrap())
-            .filter(|proto_type| -> bool {
-                type_need_import_libs.contains(&proto_type.to_owned().as_str())
-            })
-            .map(|proto_type| match proto_type.as_str() {
-                "google.protobuf.Timestamp" => "google/protobuf/timestamp.proto".to_owned(),
-                _ => "".to_owned(),
-            })
-            .collect();
-        libs_import.push("types.proto".to_owned());
-        libs_import.sort();
-        libs_import.dedup();
-        Ok(libs_import)
+        // Every field type maps to a scalar type, so only the shared types need importing.
+        Ok(vec!["types.proto".to_owned()])
     }
 
     pub fn get_metadata(&self) -> Result<ProtoMetadata, GenerationError> {
@@ -231,20 +215,22 @@ impl<'a> ProtoGenerator<'a> {
     }
 }
 
-fn convert_dozer_type_to_proto_type(field_type: FieldType) -> Result<String, GenerationError> {
+/// Returns the protobuf type of the values that `typed::helper` encodes fields of `field_type` as.
+///
+/// Decimals, timestamps and dates are sent as strings so that no precision is lost.
+fn convert_dozer_type_to_proto_type(field_type: FieldType) -> &'static str {
     match field_type {
-        FieldType::UInt => Ok("uint64".to_owned()),
-        FieldType::Int => Ok("int64".to_owned()),
-        FieldType::Float => Ok("double".to_owned()),
-        FieldType::Boolean => Ok("bool".to_owned()),
-        FieldType::String => Ok("string".to_owned()),
-        FieldType::Decimal => Ok("float64".to_owned()),
-        FieldType::Timestamp => Ok("google.protobuf.Timestamp".to_owned()),
-        FieldType::Date => Ok("string".to_owned()),
-        FieldType::Bson => Ok("google.protobuf.Any".to_owned()),
-        _ => Err(GenerationError::DozerToProtoTypeNotSupported(format!(
-            "{:?}",
-            field_type
-        ))),
+        FieldType::UInt => "uint64",
+        FieldType::Int => "int64",
+        FieldType::Float => "double",
+        FieldType::Boolean => "bool",
+        FieldType::String => "string",
+        FieldType::Text => "string",
+        FieldType::Binary => "bytes",
+        FieldType::Decimal => "string",
+        FieldType::Timestamp => "string",
+        FieldType::Date => "string",
+        FieldType::Bson => "bytes",
+        FieldType::Json => "string",
     }
 }
@@ -1,7 +1,9 @@
 use super::generator::ProtoGenerator;
 use crate::generator::protoc::utils::{create_descriptor_set, get_proto_descriptor};
 use crate::{test_utils, CacheEndpoint, PipelineDetails};
-use dozer_types::models::api_security::ApiSecurity;
+use dozer_types::models::api_security::{ApiSecurity, JwtSecurity};
+use dozer_types::types::{FieldDefinition, FieldType};
+use prost_reflect::Kind;
 use std::collections::HashMap;
 use tempdir::TempDir;
 
@@ -66,7 +68,7 @@ fn test_generate_proto_and_descriptor_with_security() {
     let tmp_dir = TempDir::new("proto_generated").unwrap();
     let tmp_dir_path = tmp_dir.path();
 
-    let api_security = Some(ApiSecurity::Jwt("vDKrSDOrVY".to_owned()));
+    let api_security = Some(ApiSecurity::Jwt(JwtSecurity::new("vDKrSDOrVY".to_owned())));
 
     ProtoGenerator::generate(tmp_dir_path, details, &api_security).unwrap();
 
@@ -86,3 +88,73 @@ fn test_generate_proto_and_descriptor_with_security() {
         "Missing Token response generated with security config"
     );
 }
+
+#[test]
+fn test_generate_proto_with_all_field_types() {
+    let schema_name = "films".to_string();
+    let (mut schema, _) = test_utils::get_schema();
+    let field_types = [
+        FieldType::UInt,
+        FieldType::Int,
+        FieldType::Float,
+        FieldType::Boolean,
+        FieldType::String,
+        FieldType::Text,
+        FieldType::Binary,
+        FieldType::Decimal,
+        FieldType::Timestamp,
+        FieldType::Date,
+        FieldType::Bson,
+        FieldType::Json,
+    ];
+    schema.fields = field_types
+        .iter()
+        .map(|typ| FieldDefinition {
+            name: format!("{:?}", typ).to_lowercase(),
+            typ: *typ,
+            nullable: true,
+        })
+        .collect();
+
+    let endpoint = test_utils::get_endpoint();
+    let details = PipelineDetails {
+        schema_name: schema_name.clone(),
+        cache_endpoint: CacheEndpoint {
+            cache: test_utils::initialize_cache(&schema_name, Some((schema, vec![]))),
+            endpoint: endpoint.clone(),
+        },
+    };
+
+    let tmp_dir = TempDir::new("proto_generated").unwrap();
+    let tmp_dir_path = tmp_dir.path();
+    ProtoGenerator::generate(tmp_dir_path, details, &None).unwrap();
+
+    let descriptor_path = create_descriptor_set(tmp_dir_path, &[endpoint.name]).unwrap();
+    let (_, descriptor) = get_proto_descriptor(&descriptor_path).unwrap();
+
+    let msg = descriptor
+        .get_message_by_name("dozer.generated.films.Film")
+        .unwrap();
+    let kinds: Vec<_> = msg.fields().map(|field| field.kind()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            Kind::Uint64,
+            Kind::Int64,
+            Kind::Double,
+            Kind::Bool,
+            Kind::String,
+            Kind::String,
+            Kind::Bytes,
+            Kind::String,
+            Kind::String,
+            Kind::String,
+            Kind::Bytes,
+        ]
+    );
+    for name in ["ListFilmsResponse", "GetFilmRequest", "GetFilmResponse"] {
+        assert!(descriptor
+            .get_message_by_name(&format!("dozer.generated.films.{}", name))
+            .is_some());
+    }
+}
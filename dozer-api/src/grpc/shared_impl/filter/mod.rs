@@ -13,8 +13,10 @@ pub fn op_satisfies_filter(
     schema: &Schema,
 ) -> bool {
     if let Some(filter) = filter {
-        if op.typ == OperationType::Insert as i32 || op.typ == OperationType::Delete as i32 {
+        if op.typ == OperationType::Insert as i32 {
             record_satisfies_filter(op.new.as_ref().unwrap(), filter, schema)
+        } else if op.typ == OperationType::Delete as i32 {
+            record_satisfies_filter(op.old.as_ref().unwrap(), filter, schema)
         } else if op.typ == OperationType::Update as i32 {
             record_satisfies_filter(op.old.as_ref().unwrap(), filter, schema)
                 || record_satisfies_filter(op.new.as_ref().unwrap(), filter, schema)
@@ -816,8 +816,21 @@ fn test_op_satisfies_filter() {
 
     check(OperationType::Insert, None, &new, Some(&filter1), false);
     check(OperationType::Insert, None, &new, Some(&filter2), true);
-    check(OperationType::Delete, None, &new, Some(&filter1), false);
-    check(OperationType::Delete, None, &new, Some(&filter2), true);
+    // The deleted record is the old one.
+    check(
+        OperationType::Delete,
+        Some(&old),
+        &new,
+        Some(&filter1),
+        true,
+    );
+    check(
+        OperationType::Delete,
+        Some(&old),
+        &new,
+        Some(&filter2),
+        false,
+    );
     check(
         OperationType::Update,
         Some(&old),
@@ -1,4 +1,6 @@
-use dozer_cache::cache::expression::QueryExpression;
+use dozer_cache::cache::cursor::encode_cursor;
+use dozer_cache::cache::expression::{default_limit_for_query, FilterExpression, QueryExpression};
+use dozer_cache::errors::CacheError;
 use dozer_types::log::warn;
 use dozer_types::serde_json;
 use dozer_types::types::{Record, Schema};
@@ -7,7 +9,7 @@ use tokio::sync::broadcast::Receiver;
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Code, Response, Status};
 
-use crate::auth::Access;
+use crate::auth::{Access, EndpointOperation};
 use crate::{api_helper::ApiHelper, PipelineDetails};
 
 use super::internal_grpc::pipeline_response::ApiEvent;
@@ -39,7 +41,7 @@ pub fn count(
     access: Option<Access>,
 ) -> Result<usize, Status> {
     let query = parse_query(query)?;
-    let api_helper = ApiHelper::new(pipeline_details, access)?;
+    let api_helper = ApiHelper::new(pipeline_details, access, Some(EndpointOperation::Count))?;
     api_helper.get_records_count(query).map_err(from_error)
 }
 
@@ -49,23 +51,64 @@ pub fn query(
     access: Option<Access>,
 ) -> Result<(Schema, Vec<Record>), Status> {
     let query = parse_query(query)?;
-    let api_helper = ApiHelper::new(pipeline_details, access)?;
+    let api_helper = ApiHelper::new(pipeline_details, access, Some(EndpointOperation::Query))?;
     let (schema, records) = api_helper.get_records(query).map_err(from_error)?;
     Ok((schema, records))
 }
 
+/// Returns a page of records without filtering them, and the cursor of the last one.
+pub fn list(
+    pipeline_details: &PipelineDetails,
+    limit: Option<usize>,
+    skip: usize,
+    after: Option<String>,
+    access: Option<Access>,
+) -> Result<(Vec<Record>, Option<String>), Status> {
+    let mut query = QueryExpression::new(
+        None,
+        vec![],
+        Some(limit.unwrap_or_else(default_limit_for_query)),
+        skip,
+    );
+    query.after = after;
+    let api_helper = ApiHelper::new(pipeline_details, access, Some(EndpointOperation::List))?;
+    let (schema, records) = api_helper.get_records(query).map_err(|e| match e {
+        CacheError::QueryValidationError(e) => Status::invalid_argument(e.to_string()),
+        e => from_error(e),
+    })?;
+    let cursor = records
+        .last()
+        .and_then(|record| encode_cursor(&schema, record));
+    Ok((records, cursor))
+}
+
+pub fn get(
+    pipeline_details: &PipelineDetails,
+    key: &str,
+    access: Option<Access>,
+) -> Result<Record, Status> {
+    let api_helper = ApiHelper::new(pipeline_details, access, Some(EndpointOperation::Get))?;
+    let (_, record) = api_helper
+        .get_record_with_schema(key)
+        .map_err(|e| Status::not_found(e.to_string()))?;
+    Ok(record)
+}
+
 pub fn on_event<T: Send + 'static>(
     pipeline_details: &PipelineDetails,
     filter: Option<&str>,
-    mut broadcast_receiver: Option<Receiver<PipelineResponse>>,
+    broadcast_receiver: Option<Receiver<PipelineResponse>>,
     access: Option<Access>,
     event_mapper: impl Fn(Operation, String) -> Option<T> + Send + Sync + 'static,
 ) -> Result<Response<ReceiverStream<T>>, Status> {
-    if broadcast_receiver.is_none() {
-        return Err(Status::unavailable(
-            "on_event is not enabled. This is currently an experimental feature. Enable it in the config.",
-        ));
-    }
+    let broadcast_receiver = match broadcast_receiver {
+        Some(broadcast_receiver) => broadcast_receiver,
+        None => {
+            return Err(Status::unavailable(
+                "on_event is not enabled. This is currently an experimental feature. Enable it in the config.",
+            ))
+        }
+    };
 
     let filter = match filter {
         Some(filter) => {
@@ -77,40 +120,57 @@ pub fn on_event<T: Send + 'static>(
         }
         None => None,
     };
-    let api_helper = ApiHelper::new(pipeline_details, access)?;
+    let api_helper = ApiHelper::new(pipeline_details, access, Some(EndpointOperation::Query))?;
     let schema = api_helper
         .get_schema()
         .map_err(|_| Status::invalid_argument(&pipeline_details.cache_endpoint.endpoint.name))?;
 
+    Ok(Response::new(forward_events(
+        schema,
+        filter,
+        broadcast_receiver,
+        event_mapper,
+    )))
+}
+
+/// Forwards the events of `broadcast_receiver` that satisfy `filter` to the returned stream, until
+/// the stream is dropped or the broadcast channel is closed.
+///
+/// Events are handed over one at a time, so a slow consumer doesn't buffer them. Instead it falls
+/// behind the broadcast channel, and the events it missed are skipped with a warning.
+pub fn forward_events<T: Send + 'static>(
+    schema: Schema,
+    filter: Option<FilterExpression>,
+    mut broadcast_receiver: Receiver<PipelineResponse>,
+    event_mapper: impl Fn(Operation, String) -> Option<T> + Send + Sync + 'static,
+) -> ReceiverStream<T> {
     let (tx, rx) = tokio::sync::mpsc::channel(1);
 
     tokio::spawn(async move {
         loop {
-            if let Some(broadcast_receiver) = broadcast_receiver.as_mut() {
-                let event = broadcast_receiver.recv().await;
-                match event {
-                    Ok(event) => {
-                        if let Some(ApiEvent::Op(op)) = event.api_event {
-                            if filter::op_satisfies_filter(&op, filter.as_ref(), &schema) {
-                                if let Some(event) = event_mapper(op, event.endpoint) {
-                                    if (tx.send(event).await).is_err() {
-                                        // receiver dropped
-                                        break;
-                                    }
+            let event = broadcast_receiver.recv().await;
+            match event {
+                Ok(event) => {
+                    if let Some(ApiEvent::Op(op)) = event.api_event {
+                        if filter::op_satisfies_filter(&op, filter.as_ref(), &schema) {
+                            if let Some(event) = event_mapper(op, event.endpoint) {
+                                if (tx.send(event).await).is_err() {
+                                    // receiver dropped
+                                    break;
                                 }
                             }
                         }
                     }
-                    Err(e) => {
-                        warn!("Failed to receive event from broadcast channel: {}", e);
-                        if e == RecvError::Closed {
-                            break;
-                        }
+                }
+                Err(e) => {
+                    warn!("Failed to receive event from broadcast channel: {}", e);
+                    if e == RecvError::Closed {
+                        break;
                     }
                 }
             }
         }
     });
 
-    Ok(Response::new(ReceiverStream::new(rx)))
+    ReceiverStream::new(rx)
 }
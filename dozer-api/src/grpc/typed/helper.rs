@@ -1,5 +1,5 @@
 use crate::grpc::types::{self as GrpcTypes};
-use dozer_types::types::{Field, Record};
+use dozer_types::types::{encode_json, Field, Record};
 use inflector::Inflector;
 use prost_reflect::{DescriptorPool, MessageDescriptor};
 use prost_reflect::{DynamicMessage, Value};
@@ -32,6 +32,26 @@ fn get_response_descriptor(
             desc.get_message_by_name(&query_path)
                 .unwrap_or_else(|| panic!("{}: not found", query_path))
         }
+        "list" => {
+            let list_path = format!(
+                "dozer.generated.{}.List{}Response",
+                endpoint_name.to_lowercase(),
+                endpoint_name.to_pascal_case().to_plural(),
+            );
+
+            desc.get_message_by_name(&list_path)
+                .unwrap_or_else(|| panic!("{}: not found", list_path))
+        }
+        "get" => {
+            let get_path = format!(
+                "dozer.generated.{}.Get{}Response",
+                endpoint_name.to_lowercase(),
+                endpoint_name.to_pascal_case().to_singular(),
+            );
+
+            desc.get_message_by_name(&get_path)
+                .unwrap_or_else(|| panic!("{}: not found", get_path))
+        }
         "on_event" => {
             let query_path = format!(
                 "dozer.generated.{}.{}Event",
@@ -172,6 +192,45 @@ pub fn query_response_to_typed_response(
     TypedResponse::new(msg)
 }
 
+pub fn list_response_to_typed_response(
+    records: Vec<Record>,
+    cursor: Option<String>,
+    desc: &DescriptorPool,
+    endpoint_name: &str,
+) -> TypedResponse {
+    let list_desc = get_response_descriptor(desc, "list", endpoint_name);
+
+    let mut msg = DynamicMessage::new(list_desc);
+
+    let resource_desc = get_resource_desc(desc, endpoint_name);
+    let resources = records
+        .into_iter()
+        .map(|rec| prost_reflect::Value::Message(record_to_pb(rec, &resource_desc)))
+        .collect::<Vec<_>>();
+    msg.set_field_by_name("data", prost_reflect::Value::List(resources));
+    if let Some(cursor) = cursor {
+        msg.set_field_by_name("next_cursor", prost_reflect::Value::String(cursor));
+    }
+    TypedResponse::new(msg)
+}
+
+pub fn get_response_to_typed_response(
+    record: Record,
+    desc: &DescriptorPool,
+    endpoint_name: &str,
+) -> TypedResponse {
+    let get_desc = get_response_descriptor(desc, "get", endpoint_name);
+
+    let mut msg = DynamicMessage::new(get_desc);
+
+    let resource_desc = get_resource_desc(desc, endpoint_name);
+    msg.set_field_by_name(
+        "data",
+        prost_reflect::Value::Message(record_to_pb(record, &resource_desc)),
+    );
+    TypedResponse::new(msg)
+}
+
 pub fn token_response(token: String, desc: &DescriptorPool, endpoint_name: &str) -> TypedResponse {
     let token_desc = get_response_descriptor(desc, "token", endpoint_name);
     let mut msg = DynamicMessage::new(token_desc);
@@ -192,6 +251,7 @@ fn convert_field_to_reflect_value(field: Field) -> prost_reflect::Value {
         Field::Timestamp(n) => Value::String(n.to_rfc3339()),
         Field::Date(n) => Value::String(n.to_string()),
         Field::Bson(n) => Value::Bytes(prost_reflect::bytes::Bytes::from(n)),
+        Field::Json(n) => Value::String(encode_json(&n)),
         Field::Null => panic!("Cannot convert null to protobuf value"),
     }
 }
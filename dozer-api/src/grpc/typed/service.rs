@@ -1,7 +1,8 @@
 use super::{
     codec::TypedCodec,
     helper::{
-        count_response_to_typed_response, on_event_to_typed_response,
+        count_response_to_typed_response, get_response_to_typed_response,
+        list_response_to_typed_response, on_event_to_typed_response,
         query_response_to_typed_response, token_response,
     },
     DynamicMessage, TypedResponse,
@@ -165,6 +166,38 @@ where
                             Ok(res)
                         })
                     }
+                    "list" => {
+                        struct ListService(PipelineDetails, DescriptorPool);
+                        impl tonic::server::UnaryService<DynamicMessage> for ListService {
+                            type Response = TypedResponse;
+                            type Future = future::Ready<Result<Response<TypedResponse>, Status>>;
+                            fn call(&mut self, request: Request<DynamicMessage>) -> Self::Future {
+                                let response = list(request, &self.0, &self.1);
+                                future::ready(response)
+                            }
+                        }
+                        Box::pin(async move {
+                            let method = ListService(pipeline_details, desc);
+                            let res = grpc.unary(method, req).await;
+                            Ok(res)
+                        })
+                    }
+                    "get" => {
+                        struct GetService(PipelineDetails, DescriptorPool);
+                        impl tonic::server::UnaryService<DynamicMessage> for GetService {
+                            type Response = TypedResponse;
+                            type Future = future::Ready<Result<Response<TypedResponse>, Status>>;
+                            fn call(&mut self, request: Request<DynamicMessage>) -> Self::Future {
+                                let response = get(request, &self.0, &self.1);
+                                future::ready(response)
+                            }
+                        }
+                        Box::pin(async move {
+                            let method = GetService(pipeline_details, desc);
+                            let res = grpc.unary(method, req).await;
+                            Ok(res)
+                        })
+                    }
 
                     "on_event" => {
                         struct EventService(
@@ -292,6 +325,76 @@ fn query(
     Ok(Response::new(res))
 }
 
+/// Returns the value of `name` in `message`, or `None` if the optional field isn't set.
+fn get_optional_field<'a>(message: &'a DynamicMessage, name: &str) -> Option<Cow<'a, Value>> {
+    if message.has_field_by_name(name) {
+        message.get_field_by_name(name)
+    } else {
+        None
+    }
+}
+
+fn list(
+    request: Request<DynamicMessage>,
+    pipeline_details: &PipelineDetails,
+    desc: &DescriptorPool,
+) -> Result<Response<TypedResponse>, Status> {
+    let (_, mut extensions, list_request) = request.into_parts();
+    let access = extensions.remove::<Access>();
+
+    let get_u64 = |name: &str| {
+        get_optional_field(&list_request, name)
+            .map(|value| {
+                value.as_u64().ok_or_else(|| {
+                    Status::new(Code::InvalidArgument, format!("{} must be a uint64", name))
+                })
+            })
+            .transpose()
+    };
+    let limit = get_u64("limit")?.map(|limit| limit as usize);
+    let skip = get_u64("skip")?.unwrap_or_default() as usize;
+    let after = get_optional_field(&list_request, "after")
+        .map(|after| {
+            after
+                .as_str()
+                .map(ToString::to_string)
+                .ok_or_else(|| Status::new(Code::InvalidArgument, "after must be a string"))
+        })
+        .transpose()?;
+
+    let (records, cursor) = shared_impl::list(pipeline_details, limit, skip, after, access)?;
+    let res = list_response_to_typed_response(
+        records,
+        cursor,
+        desc,
+        &pipeline_details.cache_endpoint.endpoint.name,
+    );
+    Ok(Response::new(res))
+}
+
+fn get(
+    request: Request<DynamicMessage>,
+    pipeline_details: &PipelineDetails,
+    desc: &DescriptorPool,
+) -> Result<Response<TypedResponse>, Status> {
+    let (_, mut extensions, get_request) = request.into_parts();
+    let access = extensions.remove::<Access>();
+
+    let id = get_request.get_field_by_name("id");
+    let id = id
+        .as_ref()
+        .and_then(|id| id.as_str())
+        .ok_or_else(|| Status::new(Code::InvalidArgument, "id must be a string"))?;
+
+    let record = shared_impl::get(pipeline_details, id, access)?;
+    let res = get_response_to_typed_response(
+        record,
+        desc,
+        &pipeline_details.cache_endpoint.endpoint.name,
+    );
+    Ok(Response::new(res))
+}
+
 fn on_event(
     request: Request<DynamicMessage>,
     pipeline_details: &PipelineDetails,
@@ -9,8 +9,8 @@ use crate::{
             tests::{
                 fake_internal_pipeline_server::start_fake_internal_grpc_pipeline,
                 generated::films::{
-                    films_client::FilmsClient, CountFilmsResponse, FilmEvent, QueryFilmsRequest,
-                    QueryFilmsResponse,
+                    films_client::FilmsClient, CountFilmsResponse, FilmEvent, GetFilmRequest,
+                    ListFilmsRequest, QueryFilmsRequest, QueryFilmsResponse,
                 },
             },
             TypedService,
@@ -20,7 +20,10 @@ use crate::{
 };
 use dozer_cache::cache::expression::{FilterExpression, QueryExpression};
 use dozer_types::{
-    models::{api_config::default_api_config, api_security::ApiSecurity},
+    models::{
+        api_config::default_api_config,
+        api_security::{ApiSecurity, JwtSecurity},
+    },
     types::Schema,
 };
 use futures_util::FutureExt;
@@ -188,7 +191,7 @@ async fn test_grpc_query_with_access_token() {
     let request = QueryFilmsRequest {
         query: Some(dozer_types::serde_json::to_string(&query).unwrap()),
     };
-    let api_security = ApiSecurity::Jwt("DXkzrlnTy6".to_owned());
+    let api_security = ApiSecurity::Jwt(JwtSecurity::new("DXkzrlnTy6".to_owned()));
     let authorizer = Authorizer::from(&api_security);
     let generated_token = authorizer.generate_token(Access::All, None).unwrap();
     let (count_response, query_response) =
@@ -215,7 +218,7 @@ async fn test_grpc_query_with_wrong_access_token() {
     let request = QueryFilmsRequest {
         query: Some(dozer_types::serde_json::to_string(&query).unwrap()),
     };
-    let api_security = ApiSecurity::Jwt("DXkzrlnTy6".to_owned());
+    let api_security = ApiSecurity::Jwt(JwtSecurity::new("DXkzrlnTy6".to_owned()));
     let generated_token = "wrongrandomtoken".to_owned();
     let request_response =
         test_grpc_count_and_query_common(1404, request, Some(api_security), Some(generated_token))
@@ -224,6 +227,81 @@ async fn test_grpc_query_with_wrong_access_token() {
     assert!(request_response.unwrap_err().code() == Code::PermissionDenied);
 }
 
+#[tokio::test]
+async fn test_grpc_list_and_get() {
+    let (sender_shutdown_internal, rx_internal) = oneshot::channel::<()>();
+    let default_pipeline_internal = default_api_config().pipeline_internal.unwrap_or_default();
+    let _jh1 = tokio::spawn(start_fake_internal_grpc_pipeline(
+        default_pipeline_internal.host,
+        default_pipeline_internal.port,
+        rx_internal,
+    ));
+    let (_tx, rx) = oneshot::channel::<()>();
+    let _jh = tokio::spawn(async move {
+        let typed_service = setup_typed_service(None);
+        Server::builder()
+            .add_service(typed_service)
+            .serve_with_shutdown("127.0.0.1:1405".parse().unwrap(), rx.map(drop))
+            .await
+            .unwrap();
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let mut client = FilmsClient::connect("http://127.0.0.1:1405").await.unwrap();
+
+    let request = ListFilmsRequest {
+        limit: Some(1),
+        skip: None,
+        after: None,
+    };
+    let first_page = client
+        .list(Request::new(request))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(first_page.data.len(), 1);
+    assert_eq!(first_page.data[0].film_id, 268);
+
+    let request = ListFilmsRequest {
+        limit: None,
+        skip: None,
+        after: first_page.next_cursor,
+    };
+    let second_page = client
+        .list(Request::new(request))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(second_page.data.len(), 1);
+    assert_eq!(second_page.data[0].film_id, 524);
+
+    let request = ListFilmsRequest {
+        limit: None,
+        skip: None,
+        after: Some("zz".to_string()),
+    };
+    let res = client.list(Request::new(request)).await;
+    assert_eq!(res.unwrap_err().code(), Code::InvalidArgument);
+
+    let request = GetFilmRequest {
+        id: "524".to_string(),
+    };
+    let film = client
+        .get(Request::new(request))
+        .await
+        .unwrap()
+        .into_inner()
+        .data
+        .unwrap();
+    assert_eq!(film, second_page.data[0]);
+
+    let request = GetFilmRequest {
+        id: "1".to_string(),
+    };
+    let res = client.get(Request::new(request)).await;
+    assert_eq!(res.unwrap_err().code(), Code::NotFound);
+    _ = sender_shutdown_internal.send(());
+}
+
 #[tokio::test]
 async fn test_typed_streaming1() {
     let (sender_shutdown_internal, rx_internal) = oneshot::channel::<()>();
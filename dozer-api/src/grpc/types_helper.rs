@@ -1,6 +1,6 @@
 use dozer_types::chrono::SecondsFormat;
 use dozer_types::types::{
-    Field, FieldType, Operation as DozerOperation, Record as DozerRecord, DATE_FORMAT,
+    encode_json, Field, FieldType, Operation as DozerOperation, Record as DozerRecord, DATE_FORMAT,
 };
 
 use crate::grpc::types::{value, Operation, OperationType, Record, Type, Value};
@@ -20,7 +20,7 @@ pub fn map_operation(endpoint_name: String, operation: &DozerOperation) -> Opera
             endpoint_name,
         },
         DozerOperation::Update { old, new } => Operation {
-            typ: OperationType::Insert as i32,
+            typ: OperationType::Update as i32,
             old: Some(map_record(old)),
             new: Some(map_record(new)),
             endpoint_name,
@@ -74,6 +74,9 @@ fn field_to_prost_value(f: Field) -> Value {
         Field::Bson(b) => Value {
             value: Some(value::Value::BytesValue(b)),
         },
+        Field::Json(j) => Value {
+            value: Some(value::Value::StringValue(encode_json(&j))),
+        },
         Field::Null => Value { value: None },
         Field::Date(date) => Value {
             value: Some(value::Value::StringValue(
@@ -95,6 +98,7 @@ pub fn map_field_type_to_pb(typ: FieldType) -> Type {
         FieldType::Decimal => Type::Decimal,
         FieldType::Timestamp => Type::Timestamp,
         FieldType::Bson => Type::Bson,
+        FieldType::Json => Type::Json,
         FieldType::Date => Type::String,
     }
 }
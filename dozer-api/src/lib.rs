@@ -1,5 +1,6 @@
 use dozer_cache::cache::LmdbCache;
 use dozer_types::models::api_endpoint::ApiEndpoint;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 mod api_helper;
 
@@ -21,6 +22,20 @@ pub struct PipelineDetails {
     pub cache_endpoint: CacheEndpoint,
 }
 
+/// Shared flag telling whether the caches have caught up with the sources.
+#[derive(Clone, Debug, Default)]
+pub struct ReadinessGate(Arc<AtomicBool>);
+
+impl ReadinessGate {
+    pub fn is_ready(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    pub fn set_ready(&self, ready: bool) {
+        self.0.store(ready, Ordering::SeqCst);
+    }
+}
+
 // Exports
 pub mod auth;
 pub mod errors;
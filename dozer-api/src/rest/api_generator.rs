@@ -1,21 +1,61 @@
+use actix_web::http::header::{self, HeaderName};
 use actix_web::web::ReqData;
 use actix_web::{web, HttpResponse};
-use dozer_cache::cache::expression::QueryExpression;
+use dozer_cache::cache::expression::{default_limit_for_query, FilterExpression, QueryExpression};
+use dozer_types::indexmap::IndexMap;
 use dozer_types::log::info;
+use dozer_types::serde::{self, Deserialize};
 
 use super::super::api_helper::ApiHelper;
+use super::events::operation_to_event;
 use crate::grpc::health_grpc::health_check_response::ServingStatus;
-use crate::{auth::Access, errors::ApiError, PipelineDetails};
+use crate::grpc::{internal_grpc::PipelineResponse, shared_impl};
+use crate::{
+    auth::{Access, EndpointOperation},
+    errors::ApiError,
+    PipelineDetails, ReadinessGate,
+};
 use dozer_cache::errors::CacheError;
 use dozer_types::serde_json;
 use dozer_types::serde_json::{json, Value};
+use tokio::sync::broadcast::Receiver;
+
+/// Cursor of the last returned record. Pass it as `after` or `$after` to get the next page.
+pub const X_NEXT_CURSOR: HeaderName = HeaderName::from_static("x-next-cursor");
+
+/// Query parameters of the list route.
+#[derive(Debug, Deserialize)]
+#[serde(crate = "self::serde")]
+pub struct ListParams {
+    limit: Option<usize>,
+    #[serde(default)]
+    skip: usize,
+    after: Option<String>,
+}
+
+/// Query parameters of the stream route.
+#[derive(Debug, Deserialize)]
+#[serde(crate = "self::serde")]
+pub struct StreamParams {
+    /// JSON encoded filter expression, like the `$filter` of a query
+    #[serde(rename = "$filter")]
+    filter: Option<String>,
+}
+
+fn page_response((maps, cursor): (Vec<IndexMap<String, Value>>, Option<String>)) -> HttpResponse {
+    let mut res = HttpResponse::Ok();
+    if let Some(cursor) = cursor {
+        res.insert_header((X_NEXT_CURSOR, cursor));
+    }
+    res.json(maps)
+}
 
 /// Generated function to return openapi.yaml documentation.
 pub async fn generate_oapi(
     access: Option<ReqData<Access>>,
     pipeline_details: ReqData<PipelineDetails>,
 ) -> Result<HttpResponse, ApiError> {
-    let helper = ApiHelper::new(&pipeline_details, access.map(|a| a.into_inner()))?;
+    let helper = ApiHelper::new(&pipeline_details, access.map(|a| a.into_inner()), None)?;
 
     helper
         .generate_oapi3()
@@ -28,7 +68,11 @@ pub async fn get(
     pipeline_details: ReqData<PipelineDetails>,
     path: web::Path<String>,
 ) -> Result<HttpResponse, ApiError> {
-    let helper = ApiHelper::new(&pipeline_details, access.map(|a| a.into_inner()))?;
+    let helper = ApiHelper::new(
+        &pipeline_details,
+        access.map(|a| a.into_inner()),
+        Some(EndpointOperation::Get),
+    )?;
     let key = path.as_str();
     helper
         .get_record(key)
@@ -40,13 +84,22 @@ pub async fn get(
 pub async fn list(
     access: Option<ReqData<Access>>,
     pipeline_details: ReqData<PipelineDetails>,
+    params: web::Query<ListParams>,
 ) -> Result<HttpResponse, ApiError> {
-    let helper = ApiHelper::new(&pipeline_details, access.map(|a| a.into_inner()))?;
-    let exp = QueryExpression::new(None, vec![], Some(50), 0);
-    match helper
-        .get_records_map(exp)
-        .map(|maps| HttpResponse::Ok().json(maps))
-    {
+    let helper = ApiHelper::new(
+        &pipeline_details,
+        access.map(|a| a.into_inner()),
+        Some(EndpointOperation::List),
+    )?;
+    let ListParams { limit, skip, after } = params.into_inner();
+    let mut exp = QueryExpression::new(
+        None,
+        vec![],
+        Some(limit.unwrap_or_else(default_limit_for_query)),
+        skip,
+    );
+    exp.after = after;
+    match helper.get_records_page(exp).map(page_response) {
         Ok(res) => Ok(res),
         Err(e) => match e {
             CacheError::QueryError(_) => {
@@ -54,6 +107,7 @@ pub async fn list(
                 info!("No records found.");
                 Ok(HttpResponse::Ok().json(res))
             }
+            CacheError::QueryValidationError(e) => Err(ApiError::InvalidQuery(e)),
             _ => Err(ApiError::InternalError(Box::new(e))),
         },
     }
@@ -66,6 +120,17 @@ pub async fn health_route() -> Result<HttpResponse, ApiError> {
     Ok(HttpResponse::Ok().body(resp))
 }
 
+// Generated get function for readiness check. Ready once the pipeline has caught up with the sources.
+pub async fn ready_route(readiness: web::Data<ReadinessGate>) -> Result<HttpResponse, ApiError> {
+    if readiness.is_ready() {
+        let resp = json!({ "status": ServingStatus::Serving.as_str_name() }).to_string();
+        Ok(HttpResponse::Ok().body(resp))
+    } else {
+        let resp = json!({ "status": ServingStatus::NotServing.as_str_name() }).to_string();
+        Ok(HttpResponse::ServiceUnavailable().body(resp))
+    }
+}
+
 pub async fn count(
     access: Option<ReqData<Access>>,
     pipeline_details: ReqData<PipelineDetails>,
@@ -74,10 +139,15 @@ pub async fn count(
     let query_expression = match query_info {
         Some(query_info) => serde_json::from_value::<QueryExpression>(query_info.0)
             .map_err(ApiError::map_deserialization_error)?,
-        None => QueryExpression::default(),
+        // Count every record, not just the first page of them.
+        None => QueryExpression::new(None, vec![], None, 0),
     };
 
-    let helper = ApiHelper::new(&pipeline_details, access.map(|a| a.into_inner()))?;
+    let helper = ApiHelper::new(
+        &pipeline_details,
+        access.map(|a| a.into_inner()),
+        Some(EndpointOperation::Count),
+    )?;
     helper
         .get_records_count(query_expression)
         .map(|count| HttpResponse::Ok().json(count))
@@ -100,10 +170,14 @@ pub async fn query(
             .map_err(ApiError::map_deserialization_error)?,
         None => QueryExpression::default(),
     };
-    let helper = ApiHelper::new(&pipeline_details, access.map(|a| a.into_inner()))?;
+    let helper = ApiHelper::new(
+        &pipeline_details,
+        access.map(|a| a.into_inner()),
+        Some(EndpointOperation::Query),
+    )?;
     helper
-        .get_records_map(query_expression)
-        .map(|maps| HttpResponse::Ok().json(maps))
+        .get_records_page(query_expression)
+        .map(page_response)
         .map_err(|e| match e {
             CacheError::QueryValidationError(e) => ApiError::InvalidQuery(e),
             CacheError::TypeError(e) => ApiError::TypeError(e),
@@ -111,3 +185,55 @@ pub async fn query(
             e => ApiError::InternalError(Box::new(e)),
         })
 }
+
+/// Streams the changes to the records of the endpoint as server-sent events, from when the request
+/// is made until the client disconnects.
+///
+/// Events are sent as the pipeline commits them and aren't buffered per client. A client that reads
+/// slower than the records change falls behind, and misses the events that the broadcast channel
+/// no longer holds.
+pub async fn stream(
+    access: Option<ReqData<Access>>,
+    pipeline_details: ReqData<PipelineDetails>,
+    event_notifier: Option<web::Data<Receiver<PipelineResponse>>>,
+    params: web::Query<StreamParams>,
+) -> Result<HttpResponse, ApiError> {
+    let event_notifier = event_notifier.ok_or(ApiError::EventsNotEnabled)?;
+    let filter = match params.into_inner().filter {
+        Some(filter) if !filter.is_empty() => Some(
+            serde_json::from_str::<FilterExpression>(&filter)
+                .map_err(ApiError::map_deserialization_error)?,
+        ),
+        _ => None,
+    };
+
+    let helper = ApiHelper::new(
+        &pipeline_details,
+        access.map(|a| a.into_inner()),
+        Some(EndpointOperation::Query),
+    )?;
+    let schema = helper.get_schema().map_err(ApiError::SchemaNotFound)?;
+
+    let endpoint_name = pipeline_details.cache_endpoint.endpoint.name.clone();
+    let json_options = pipeline_details
+        .cache_endpoint
+        .endpoint
+        .json_options
+        .clone()
+        .unwrap_or_default();
+    let event_schema = schema.clone();
+    let events = shared_impl::forward_events(
+        schema,
+        filter,
+        event_notifier.resubscribe(),
+        move |op, endpoint| {
+            (endpoint == endpoint_name)
+                .then(|| operation_to_event(op, &event_schema, &json_options))
+        },
+    );
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .streaming(events))
+}
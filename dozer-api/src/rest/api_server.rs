@@ -1,14 +1,17 @@
 use super::api_generator;
-use crate::errors::ApiError;
-use crate::rest::api_generator::health_route;
+use super::rate_limiter::{RateLimitDecision, RateLimiter, X_RATE_LIMIT_REMAINING};
+use crate::errors::{ApiError, InitError};
+use crate::rest::api_generator::{health_route, ready_route, X_NEXT_CURSOR};
 use crate::{
     auth::api::{auth_route, validate},
-    CacheEndpoint, PipelineDetails,
+    grpc::internal_grpc::PipelineResponse,
+    CacheEndpoint, PipelineDetails, ReadinessGate,
 };
 use actix_cors::Cors;
 use actix_web::{
     body::MessageBody,
     dev::{ServerHandle, Service, ServiceFactory, ServiceRequest, ServiceResponse},
+    http::{header, Method, Uri},
     middleware::{Condition, Logger},
     rt, web, App, HttpMessage, HttpServer,
 };
@@ -18,6 +21,9 @@ use dozer_types::{
     models::api_security::ApiSecurity,
     serde::{self, Deserialize, Serialize},
 };
+use futures_util::future::{ready, Either, TryFutureExt};
+use std::sync::Arc;
+use tokio::sync::broadcast::Receiver;
 use tracing_actix_web::TracingLogger;
 
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
@@ -26,6 +32,53 @@ pub enum CorsOptions {
     Permissive,
     // origins, max_age
     Custom(Vec<String>, usize),
+    /// Only the listed origins, with the methods and headers the generated routes use.
+    AllowList(AllowedOrigins),
+}
+
+impl CorsOptions {
+    /// Allows requests from `origins`, each of which must be of the form `scheme://host[:port]`.
+    pub fn allow_list(origins: Vec<String>) -> Result<Self, InitError> {
+        Ok(CorsOptions::AllowList(origins.try_into()?))
+    }
+}
+
+/// Origins of [`CorsOptions::AllowList`], which can only be built from valid origins.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(crate = "self::serde", try_from = "Vec<String>", into = "Vec<String>")]
+pub struct AllowedOrigins(Vec<String>);
+
+impl TryFrom<Vec<String>> for AllowedOrigins {
+    type Error = InitError;
+
+    fn try_from(origins: Vec<String>) -> Result<Self, Self::Error> {
+        for origin in &origins {
+            if !is_valid_origin(origin) {
+                return Err(InitError::InvalidCorsOrigin(origin.clone()));
+            }
+        }
+        Ok(AllowedOrigins(origins))
+    }
+}
+
+impl From<AllowedOrigins> for Vec<String> {
+    fn from(origins: AllowedOrigins) -> Self {
+        origins.0
+    }
+}
+
+/// Browsers send the `Origin` header without a path or a trailing slash, and actix-cors compares
+/// origins exactly, so anything else would never match.
+fn is_valid_origin(origin: &str) -> bool {
+    match origin.parse::<Uri>() {
+        Ok(uri) => match (uri.scheme_str(), uri.authority()) {
+            (Some(scheme @ ("http" | "https")), Some(authority)) => {
+                format!("{}://{}", scheme, authority) == origin
+            }
+            _ => false,
+        },
+        Err(_) => false,
+    }
 }
 #[derive(Clone)]
 pub struct ApiServer {
@@ -34,6 +87,8 @@ pub struct ApiServer {
     cors: CorsOptions,
     security: Option<ApiSecurity>,
     host: String,
+    readiness: ReadinessGate,
+    rate_limiter: Option<RateLimiter>,
 }
 
 impl Default for ApiServer {
@@ -44,18 +99,26 @@ impl Default for ApiServer {
             cors: CorsOptions::Permissive,
             security: None,
             host: "0.0.0.0".to_owned(),
+            readiness: ReadinessGate::default(),
+            rate_limiter: None,
         }
     }
 }
 
 impl ApiServer {
-    pub fn new(rest_config: ApiRest, security: Option<ApiSecurity>) -> Self {
+    pub fn new(
+        rest_config: ApiRest,
+        security: Option<ApiSecurity>,
+        readiness: ReadinessGate,
+    ) -> Self {
         Self {
             shutdown_timeout: 0,
             port: rest_config.port as u16,
             cors: CorsOptions::Permissive,
             security,
             host: rest_config.host,
+            readiness,
+            rate_limiter: rest_config.rate_limit.as_ref().map(RateLimiter::new),
         }
     }
     fn get_cors(cors: CorsOptions) -> Cors {
@@ -65,6 +128,13 @@ impl ApiServer {
                 .into_iter()
                 .fold(Cors::default(), |cors, origin| cors.allowed_origin(&origin))
                 .max_age(max_age),
+            CorsOptions::AllowList(origins) => origins
+                .0
+                .into_iter()
+                .fold(Cors::default(), |cors, origin| cors.allowed_origin(&origin))
+                .allowed_methods([Method::GET, Method::POST])
+                .allowed_headers([header::AUTHORIZATION, header::CONTENT_TYPE, header::ACCEPT])
+                .expose_headers([X_NEXT_CURSOR, X_RATE_LIMIT_REMAINING, header::RETRY_AFTER]),
         }
     }
 
@@ -72,6 +142,9 @@ impl ApiServer {
         security: Option<ApiSecurity>,
         cors: CorsOptions,
         cache_endpoints: Vec<CacheEndpoint>,
+        readiness: ReadinessGate,
+        rate_limiter: Option<RateLimiter>,
+        event_notifier: Option<Receiver<PipelineResponse>>,
     ) -> App<
         impl ServiceFactory<
             ServiceRequest,
@@ -82,9 +155,14 @@ impl ApiServer {
         >,
     > {
         let mut app = App::new()
+            .app_data(web::Data::new(readiness))
             .wrap(Logger::default())
             .wrap(TracingLogger::default());
 
+        if let Some(event_notifier) = event_notifier {
+            app = app.app_data(web::Data::new(event_notifier));
+        }
+
         let is_auth_configured = if let Some(api_security) = security {
             // Injecting API Security
             app = app.app_data(api_security);
@@ -103,6 +181,8 @@ impl ApiServer {
                 let endpoint = cache_endpoint.endpoint.clone();
                 let scope = endpoint.path.clone();
                 let schema_name = endpoint.name;
+                let rate_limiter = rate_limiter.clone();
+                let rate_limit_key = scope.clone();
                 app.service(
                     web::scope(&scope)
                         // Inject pipeline_details for generated functions
@@ -113,9 +193,31 @@ impl ApiServer {
                             });
                             srv.call(req)
                         })
+                        // Rate limit requests to this endpoint, ahead of any cache access
+                        .wrap_fn(move |req, srv| {
+                            let decision = rate_limiter
+                                .as_ref()
+                                .map(|rate_limiter| rate_limiter.check(&rate_limit_key, &req));
+                            match decision {
+                                Some(RateLimitDecision::Exceeded { retry_after }) => {
+                                    let res =
+                                        req.error_response(ApiError::TooManyRequests(retry_after));
+                                    Either::Left(ready(Ok(res.map_into_right_body())))
+                                }
+                                decision => Either::Right(srv.call(req).map_ok(move |mut res| {
+                                    if let Some(RateLimitDecision::Allowed { remaining }) = decision
+                                    {
+                                        res.headers_mut()
+                                            .insert(X_RATE_LIMIT_REMAINING, remaining.into());
+                                    }
+                                    res.map_into_left_body()
+                                })),
+                            }
+                        })
                         .route("/count", web::post().to(api_generator::count))
                         .route("/query", web::post().to(api_generator::query))
                         .route("/oapi", web::post().to(api_generator::generate_oapi))
+                        .route("/stream", web::get().to(api_generator::stream))
                         .route("/{id}", web::get().to(api_generator::get))
                         .route("/", web::get().to(api_generator::list))
                         .route("", web::get().to(api_generator::list)),
@@ -125,6 +227,8 @@ impl ApiServer {
             .route("/auth/token", web::post().to(auth_route))
             // Attach health route
             .route("/health", web::get().to(health_route))
+            // Attach readiness route
+            .route("/ready", web::get().to(ready_route))
             // Wrap Api Validator
             .wrap(auth_middleware)
             // Wrap CORS around api validator. Required to return the right headers.
@@ -135,6 +239,7 @@ impl ApiServer {
         &self,
         cache_endpoints: Vec<CacheEndpoint>,
         tx: Sender<ServerHandle>,
+        event_notifier: Option<Receiver<PipelineResponse>>,
     ) -> Result<(), ApiError> {
         info!(
             "Starting Rest Api Server on http://{}:{} with security: {}",
@@ -148,12 +253,19 @@ impl ApiServer {
         );
         let cors = self.cors.clone();
         let security = self.security.clone();
+        let readiness = self.readiness.clone();
+        let rate_limiter = self.rate_limiter.clone();
+        // Every worker subscribes to the events on its own.
+        let event_notifier = event_notifier.map(Arc::new);
         let address = format!("{}:{}", self.host.to_owned(), self.port.to_owned());
         let server = HttpServer::new(move || {
             ApiServer::create_app_entry(
                 security.to_owned(),
                 cors.to_owned(),
                 cache_endpoints.clone(),
+                readiness.clone(),
+                rate_limiter.clone(),
+                event_notifier.as_ref().map(|r| r.resubscribe()),
             )
         })
         .bind(address.to_owned())
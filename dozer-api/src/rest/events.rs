@@ -0,0 +1,97 @@
+use std::str::FromStr;
+
+use actix_web::web::Bytes;
+use dozer_types::errors::types::TypeError;
+use dozer_types::helper::record_to_map_with_options;
+use dozer_types::models::api_endpoint::ApiJsonOptions;
+use dozer_types::ordered_float::OrderedFloat;
+use dozer_types::rust_decimal::Decimal;
+use dozer_types::serde_json::{self, Map, Value as JsonValue};
+use dozer_types::types::{Field, FieldType, Record as DozerRecord, Schema};
+
+use crate::grpc::types::{value, Operation, OperationType, Record, Value};
+
+/// Formats `op` as a server-sent event named after the operation type, whose data is a JSON object
+/// with the `old` and `new` records keyed by field name, serialized like the other endpoints with
+/// `options`.
+pub fn operation_to_event(
+    op: Operation,
+    schema: &Schema,
+    options: &ApiJsonOptions,
+) -> Result<Bytes, TypeError> {
+    let name = match OperationType::from_i32(op.typ) {
+        Some(OperationType::Insert) => "insert",
+        Some(OperationType::Delete) => "delete",
+        Some(OperationType::Update) => "update",
+        None => "unknown",
+    };
+    let mut data = Map::new();
+    if let Some(old) = op.old {
+        data.insert("old".to_string(), record_to_json(old, schema, options)?);
+    }
+    if let Some(new) = op.new {
+        data.insert("new".to_string(), record_to_json(new, schema, options)?);
+    }
+    Ok(Bytes::from(format!(
+        "event: {}\ndata: {}\n\n",
+        name,
+        JsonValue::Object(data)
+    )))
+}
+
+fn record_to_json(
+    record: Record,
+    schema: &Schema,
+    options: &ApiJsonOptions,
+) -> Result<JsonValue, TypeError> {
+    let values = schema
+        .fields
+        .iter()
+        .zip(record.values)
+        .map(|(field, value)| value_to_field(value, field.typ))
+        .collect();
+    let map = record_to_map_with_options(&DozerRecord::new(None, values, None), schema, options)?;
+    Ok(JsonValue::Object(map.into_iter().collect()))
+}
+
+/// Maps `value` back to the field of type `typ` it was made from, see `map_record`.
+fn value_to_field(value: Value, typ: FieldType) -> Field {
+    match (value.value, typ) {
+        (Some(value::Value::UintValue(n)), _) => Field::UInt(n),
+        (Some(value::Value::IntValue(n)), _) => Field::Int(n),
+        (Some(value::Value::FloatValue(n)), _) => Field::Float(OrderedFloat(n as f64)),
+        (Some(value::Value::DoubleValue(n)), _) => Field::Float(OrderedFloat(n)),
+        (Some(value::Value::BoolValue(b)), _) => Field::Boolean(b),
+        (Some(value::Value::StringValue(s)), FieldType::Decimal) => match Decimal::from_str(&s) {
+            Ok(n) => Field::Decimal(n),
+            Err(_) => Field::String(s),
+        },
+        (Some(value::Value::StringValue(s)), FieldType::Json) => match serde_json::from_str(&s) {
+            Ok(j) => Field::Json(j),
+            Err(_) => Field::String(s),
+        },
+        (Some(value::Value::StringValue(s)), _) => Field::String(s),
+        (Some(value::Value::BytesValue(b)), FieldType::Bson) => Field::Bson(b),
+        (Some(value::Value::BytesValue(b)), _) => Field::Binary(b),
+        (Some(value::Value::ArrayValue(array)), _) => Field::Json(JsonValue::Array(
+            array.array_value.into_iter().map(value_to_json).collect(),
+        )),
+        (None, _) => Field::Null,
+    }
+}
+
+fn value_to_json(value: Value) -> JsonValue {
+    match value.value {
+        Some(value::Value::UintValue(n)) => JsonValue::from(n),
+        Some(value::Value::IntValue(n)) => JsonValue::from(n),
+        Some(value::Value::FloatValue(n)) => JsonValue::from(n),
+        Some(value::Value::DoubleValue(n)) => JsonValue::from(n),
+        Some(value::Value::BoolValue(b)) => JsonValue::from(b),
+        Some(value::Value::StringValue(s)) => JsonValue::from(s),
+        Some(value::Value::BytesValue(b)) => JsonValue::from(b),
+        Some(value::Value::ArrayValue(array)) => {
+            JsonValue::Array(array.array_value.into_iter().map(value_to_json).collect())
+        }
+        None => JsonValue::Null,
+    }
+}
@@ -1,7 +1,10 @@
 // Exports
 mod api_generator;
 mod api_server;
+mod events;
+mod rate_limiter;
 pub use api_server::ApiServer;
+pub use rate_limiter::{RateLimiter, TokenSubject};
 
 #[cfg(test)]
 mod tests;
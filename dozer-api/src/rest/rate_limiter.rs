@@ -0,0 +1,168 @@
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use actix_web::{dev::ServiceRequest, http::header::HeaderName, HttpMessage};
+use dozer_types::{models::api_config::ApiRateLimit, parking_lot::Mutex};
+
+pub const X_RATE_LIMIT_REMAINING: HeaderName = HeaderName::from_static("x-ratelimit-remaining");
+
+/// Subject of the bearer token a request was authenticated with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenSubject(pub String);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitDecision {
+    Allowed {
+        remaining: u32,
+    },
+    /// Seconds until the window resets, rounded up.
+    Exceeded {
+        retry_after: u64,
+    },
+}
+
+/// Fixed window request counter, shared by all the workers of the server.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    max_requests: u32,
+    window: Duration,
+    per_token: bool,
+    windows: Arc<Mutex<Windows>>,
+}
+
+/// Start and request count of the current window of each key.
+#[derive(Debug)]
+struct Windows {
+    counters: HashMap<String, (Instant, u32)>,
+    /// Expired windows are removed at most once per window, so that keys of clients which stopped
+    /// sending requests don't accumulate.
+    last_eviction: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(config: &ApiRateLimit) -> Self {
+        Self {
+            max_requests: config.max_requests,
+            window: Duration::from_secs(config.window_secs),
+            per_token: config.per_token,
+            windows: Arc::new(Mutex::new(Windows {
+                counters: HashMap::new(),
+                last_eviction: Instant::now(),
+            })),
+        }
+    }
+
+    /// Counts a request against `endpoint` for the client that sent `req`.
+    pub fn check(&self, endpoint: &str, req: &ServiceRequest) -> RateLimitDecision {
+        let client = self.get_client_key(req);
+        self.acquire(&format!("{endpoint}|{client}"), Instant::now())
+    }
+
+    fn get_client_key(&self, req: &ServiceRequest) -> String {
+        if self.per_token {
+            if let Some(subject) = req.extensions().get::<TokenSubject>() {
+                return format!("sub:{}", subject.0);
+            }
+        }
+        let ip = req
+            .connection_info()
+            .realip_remote_addr()
+            .unwrap_or("unknown")
+            .to_owned();
+        format!("ip:{ip}")
+    }
+
+    fn acquire(&self, key: &str, now: Instant) -> RateLimitDecision {
+        let mut windows = self.windows.lock();
+        if now.duration_since(windows.last_eviction) >= self.window {
+            windows
+                .counters
+                .retain(|_, (start, _)| now.duration_since(*start) < self.window);
+            windows.last_eviction = now;
+        }
+        let (start, count) = windows.counters.entry(key.to_owned()).or_insert((now, 0));
+        if now.duration_since(*start) >= self.window {
+            *start = now;
+            *count = 0;
+        }
+        if *count >= self.max_requests {
+            let retry_after = self.window - now.duration_since(*start);
+            RateLimitDecision::Exceeded {
+                retry_after: retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0),
+            }
+        } else {
+            *count += 1;
+            RateLimitDecision::Allowed {
+                remaining: self.max_requests - *count,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use dozer_types::models::api_config::ApiRateLimit;
+
+    use super::{RateLimitDecision, RateLimiter};
+
+    #[test]
+    fn test_fixed_window() {
+        let limiter = RateLimiter::new(&ApiRateLimit {
+            max_requests: 2,
+            window_secs: 10,
+            per_token: false,
+        });
+        let now = Instant::now();
+        assert_eq!(
+            limiter.acquire("films|ip:1", now),
+            RateLimitDecision::Allowed { remaining: 1 }
+        );
+        assert_eq!(
+            limiter.acquire("films|ip:1", now),
+            RateLimitDecision::Allowed { remaining: 0 }
+        );
+        assert_eq!(
+            limiter.acquire("films|ip:1", now + Duration::from_secs(4)),
+            RateLimitDecision::Exceeded { retry_after: 6 }
+        );
+        // Other endpoints and clients are counted separately
+        assert_eq!(
+            limiter.acquire("users|ip:1", now),
+            RateLimitDecision::Allowed { remaining: 1 }
+        );
+        assert_eq!(
+            limiter.acquire("films|ip:2", now),
+            RateLimitDecision::Allowed { remaining: 1 }
+        );
+        // The window resets
+        assert_eq!(
+            limiter.acquire("films|ip:1", now + Duration::from_secs(10)),
+            RateLimitDecision::Allowed { remaining: 1 }
+        );
+    }
+
+    #[test]
+    fn test_expired_windows_are_evicted() {
+        let limiter = RateLimiter::new(&ApiRateLimit {
+            max_requests: 2,
+            window_secs: 10,
+            per_token: false,
+        });
+        let now = Instant::now();
+        limiter.acquire("films|ip:1", now);
+        limiter.acquire("films|ip:2", now + Duration::from_secs(5));
+        assert_eq!(limiter.windows.lock().counters.len(), 2);
+
+        // Only the window of ip:1 expired
+        limiter.acquire("films|ip:3", now + Duration::from_secs(12));
+        let windows = limiter.windows.lock();
+        let mut keys: Vec<&String> = windows.counters.keys().collect();
+        keys.sort();
+        assert_eq!(keys, vec!["films|ip:2", "films|ip:3"]);
+    }
+}
@@ -1,14 +1,14 @@
 use super::super::api_server::{ApiServer, CorsOptions};
 use crate::{
     auth::{Access, Authorizer},
-    test_utils, CacheEndpoint,
+    test_utils, CacheEndpoint, ReadinessGate,
 };
 use actix_web::{body::MessageBody, dev::ServiceResponse};
 use dozer_types::{
-    models::api_security::ApiSecurity,
+    models::api_security::{ApiSecurity, JwtSecurity},
     serde,
     serde::{Deserialize, Serialize},
-    serde_json::{json, Value},
+    serde_json::{self, json, Value},
 };
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -49,16 +49,73 @@ async fn verify_token_test() {
     assert!(res.status().is_success());
 
     // With ApiSecurity but no token
-    let res = check_status(Some(ApiSecurity::Jwt(secret.to_string())), None).await;
+    let res = check_status(
+        Some(ApiSecurity::Jwt(JwtSecurity::new(secret.to_string()))),
+        None,
+    )
+    .await;
     assert_eq!(res.status().as_u16(), 401, "Should be unauthorized.");
 
     let auth = Authorizer::new(secret, None, None);
     let token = auth.generate_token(Access::All, None).unwrap();
 
-    let res = check_status(Some(ApiSecurity::Jwt("secret".to_string())), Some(token)).await;
+    let res = check_status(
+        Some(ApiSecurity::Jwt(JwtSecurity::new("secret".to_string()))),
+        Some(token),
+    )
+    .await;
     assert!(res.status().is_success());
 }
 
+#[actix_web::test]
+async fn verify_token_signed_with_previous_secret() {
+    let old_token = Authorizer::new("old_secret", None, None)
+        .generate_token(Access::All, None)
+        .unwrap();
+
+    // While rotating, tokens signed with the previous secret are still accepted.
+    let rotating = ApiSecurity::Jwt(JwtSecurity {
+        secret: "new_secret".to_string(),
+        previous_secrets: vec!["old_secret".to_string()],
+    });
+    let res = check_status(Some(rotating), Some(old_token.clone())).await;
+    assert!(res.status().is_success());
+
+    // Once the previous secret is dropped, they aren't.
+    let rotated = ApiSecurity::Jwt(JwtSecurity::new("new_secret".to_string()));
+    let res = check_status(Some(rotated), Some(old_token)).await;
+    assert_eq!(res.status().as_u16(), 401, "Should be unauthorized.");
+}
+
+#[actix_web::test]
+async fn verify_endpoint_scoped_token() {
+    let secret = "secret";
+    let security = ApiSecurity::Jwt(JwtSecurity::new(secret.to_string()));
+    let auth = Authorizer::new(secret, None, None);
+    let scoped_token = |access: Value| {
+        auth.generate_token(serde_json::from_value(access).unwrap(), None)
+            .unwrap()
+    };
+
+    let list_token = scoped_token(json!({"Custom":{"films":{
+        "filter":null,"fields":[],"operations":["list"]
+    }}}));
+    let res = check_status(Some(security.clone()), Some(list_token)).await;
+    assert!(res.status().is_success());
+
+    let count_token = scoped_token(json!({"Custom":{"films":{
+        "filter":null,"fields":[],"operations":["count"]
+    }}}));
+    let res = check_status(Some(security.clone()), Some(count_token)).await;
+    assert_eq!(res.status().as_u16(), 403, "Should be forbidden.");
+
+    let other_endpoint_token = scoped_token(json!({"Custom":{"users":{
+        "filter":null,"fields":[]
+    }}}));
+    let res = check_status(Some(security), Some(other_endpoint_token)).await;
+    assert_eq!(res.status().as_u16(), 403, "Should be forbidden.");
+}
+
 async fn check_status(
     security: Option<ApiSecurity>,
     token: Option<String>,
@@ -73,6 +130,9 @@ async fn check_status(
             cache,
             endpoint: endpoint.clone(),
         }],
+        ReadinessGate::default(),
+        None,
+        None,
     );
     let app = actix_web::test::init_service(api_server).await;
 
@@ -96,9 +156,12 @@ async fn _call_auth_token_api(
     let schema_name = endpoint.name.clone();
     let cache = test_utils::initialize_cache(&schema_name, None);
     let api_server = ApiServer::create_app_entry(
-        Some(ApiSecurity::Jwt(secret)),
+        Some(ApiSecurity::Jwt(JwtSecurity::new(secret))),
         CorsOptions::Permissive,
         vec![CacheEndpoint { cache, endpoint }],
+        ReadinessGate::default(),
+        None,
+        None,
     );
     let app = actix_web::test::init_service(api_server).await;
 
@@ -0,0 +1,54 @@
+use dozer_types::models::api_endpoint::ApiJsonOptions;
+use dozer_types::rust_decimal::Decimal;
+use dozer_types::serde_json::{self, json, Value};
+use dozer_types::types::{Field, FieldDefinition, FieldType, Operation, Record, Schema};
+
+use crate::grpc::types_helper::map_operation;
+use crate::rest::events::operation_to_event;
+
+fn get_data(event: &[u8]) -> Value {
+    let event = std::str::from_utf8(event).unwrap();
+    let data = event
+        .lines()
+        .find_map(|line| line.strip_prefix("data: "))
+        .unwrap();
+    serde_json::from_str(data).unwrap()
+}
+
+#[test]
+fn test_operation_to_event_with_json_options() {
+    let schema = Schema {
+        identifier: None,
+        fields: vec![
+            FieldDefinition::new("id".to_string(), FieldType::Int, false),
+            FieldDefinition::new("price".to_string(), FieldType::Decimal, false),
+        ],
+        primary_index: vec![0],
+    };
+    let op = map_operation(
+        "prices".to_string(),
+        &Operation::Insert {
+            new: Record::new(
+                None,
+                vec![Field::Int(1), Field::Decimal(Decimal::new(12345, 2))],
+                None,
+            ),
+        },
+    );
+
+    let event = operation_to_event(op.clone(), &schema, &ApiJsonOptions::default()).unwrap();
+    assert_eq!(
+        get_data(&event),
+        json!({"new": {"id": 1, "price": "123.45"}})
+    );
+
+    let options = ApiJsonOptions {
+        decimals_as_numbers: true,
+        ints_as_strings: true,
+    };
+    let event = operation_to_event(op, &schema, &options).unwrap();
+    assert_eq!(
+        get_data(&event),
+        json!({"new": {"id": "1", "price": 123.45}})
+    );
+}
@@ -1,6 +1,18 @@
+use super::super::api_generator::X_NEXT_CURSOR;
 use super::super::api_server::{ApiServer, CorsOptions};
-use crate::{generator::oapi::generator::OpenApiGenerator, test_utils, CacheEndpoint};
-use dozer_types::serde_json::{json, Value};
+use super::super::RateLimiter;
+use crate::grpc::internal_grpc::{pipeline_response::ApiEvent, PipelineResponse};
+use crate::grpc::types_helper::map_operation;
+use crate::{
+    generator::oapi::generator::OpenApiGenerator, test_utils, CacheEndpoint, ReadinessGate,
+};
+use actix_web::http::header;
+use dozer_types::{
+    models::{api_config::ApiRateLimit, api_endpoint::ApiJsonOptions},
+    serde_json::{self, json, Value},
+    types::Operation,
+};
+use tokio::sync::broadcast;
 
 #[test]
 fn test_generate_oapi() {
@@ -17,6 +29,43 @@ fn test_generate_oapi() {
     let generated = oapi_generator.generate_oas3().unwrap();
 
     assert_eq!(generated.paths.paths.len(), 4, " paths must be generated");
+
+    let generated = serde_json::to_value(&generated).unwrap();
+    let schemas = &generated["components"]["schemas"];
+    let query = &schemas["films_query"]["properties"];
+    for property in ["$filter", "$order_by", "$limit", "$skip", "$after"] {
+        assert!(
+            query.get(property).is_some(),
+            "{} must be documented",
+            property
+        );
+    }
+    for route in ["/films/query", "/films/count"] {
+        assert_eq!(
+            generated["paths"][route]["post"]["requestBody"]["content"]["application/json"]
+                ["schema"]["$ref"],
+            "#/components/schemas/films_query"
+        );
+    }
+
+    let filter = &schemas["films_filter"]["properties"];
+    assert_eq!(
+        filter["$and"]["items"]["$ref"],
+        "#/components/schemas/films_filter"
+    );
+    let film_id = &filter["film_id"]["oneOf"];
+    assert_eq!(film_id[0]["type"], "integer");
+    let operators = film_id[1]["properties"].as_object().unwrap();
+    for operator in ["$eq", "$lt", "$lte", "$gt", "$gte"] {
+        assert_eq!(operators[operator]["type"], "integer");
+    }
+    assert!(!operators.contains_key("$contains"));
+    let description = filter["description"]["oneOf"][1]["properties"]
+        .as_object()
+        .unwrap();
+    for operator in ["$contains", "$matches_any", "$matches_all"] {
+        assert_eq!(description[operator]["type"], "string");
+    }
 }
 
 #[actix_web::test]
@@ -32,6 +81,9 @@ async fn list_route() {
             cache,
             endpoint: endpoint.clone(),
         }],
+        ReadinessGate::default(),
+        None,
+        None,
     );
     let app = actix_web::test::init_service(api_server).await;
 
@@ -46,6 +98,106 @@ async fn list_route() {
     assert!(!body.as_array().unwrap().is_empty(), "Must return records");
 }
 
+#[actix_web::test]
+async fn list_route_with_json_options() {
+    let mut endpoint = test_utils::get_endpoint();
+    endpoint.json_options = Some(ApiJsonOptions {
+        decimals_as_numbers: true,
+        ints_as_strings: true,
+    });
+    let mut schema_name = endpoint.to_owned().path;
+    schema_name.remove(0);
+    let cache = test_utils::initialize_cache(&schema_name, None);
+    let api_server = ApiServer::create_app_entry(
+        None,
+        CorsOptions::Permissive,
+        vec![CacheEndpoint {
+            cache,
+            endpoint: endpoint.clone(),
+        }],
+        ReadinessGate::default(),
+        None,
+        None,
+    );
+    let app = actix_web::test::init_service(api_server).await;
+
+    let req = actix_web::test::TestRequest::get()
+        .uri(&endpoint.path)
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+    let body: Value = actix_web::test::read_body_json(res).await;
+    for record in body.as_array().unwrap() {
+        assert!(record["film_id"].is_string());
+    }
+
+    let req = actix_web::test::TestRequest::post()
+        .uri(&format!("{}/oapi", endpoint.path))
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+    let body: Value = actix_web::test::read_body_json(res).await;
+    let film_id = &body["components"]["schemas"]["films"]["properties"]["film_id"];
+    assert_eq!(film_id["type"], "string");
+    assert_eq!(film_id["format"], "int64");
+}
+
+#[actix_web::test]
+async fn list_route_with_cursor() {
+    let endpoint = test_utils::get_endpoint();
+    let mut schema_name = endpoint.to_owned().path;
+    schema_name.remove(0);
+    let cache = test_utils::initialize_cache(&schema_name, None);
+    let api_server = ApiServer::create_app_entry(
+        None,
+        CorsOptions::Permissive,
+        vec![CacheEndpoint {
+            cache,
+            endpoint: endpoint.clone(),
+        }],
+        ReadinessGate::default(),
+        None,
+        None,
+    );
+    let app = actix_web::test::init_service(api_server).await;
+
+    let req = actix_web::test::TestRequest::get()
+        .uri(&format!("{}?limit=10", endpoint.path))
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+    let cursor = res
+        .headers()
+        .get(X_NEXT_CURSOR)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    let first_page: Value = actix_web::test::read_body_json(res).await;
+    assert_eq!(first_page.as_array().unwrap().len(), 10);
+
+    let req = actix_web::test::TestRequest::get()
+        .uri(&format!("{}?limit=10&after={}", endpoint.path, cursor))
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+    let second_page: Value = actix_web::test::read_body_json(res).await;
+
+    let req = actix_web::test::TestRequest::get()
+        .uri(&format!("{}?limit=10&skip=10", endpoint.path))
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    let skipped_page: Value = actix_web::test::read_body_json(res).await;
+    assert_eq!(second_page.as_array().unwrap().len(), 10);
+    assert_eq!(second_page, skipped_page);
+
+    let req = actix_web::test::TestRequest::get()
+        .uri(&format!("{}?after=zz", endpoint.path))
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert!(res.status().is_client_error());
+}
+
 #[actix_web::test]
 async fn count_and_query_route() {
     let endpoint = test_utils::get_endpoint();
@@ -59,6 +211,9 @@ async fn count_and_query_route() {
             cache,
             endpoint: endpoint.clone(),
         }],
+        ReadinessGate::default(),
+        None,
+        None,
     );
     let app = actix_web::test::init_service(api_server).await;
 
@@ -73,6 +228,19 @@ async fn count_and_query_route() {
     let body: Value = actix_web::test::read_body_json(res).await;
     assert_eq!(body.as_u64().unwrap(), 1);
 
+    // Without a body, every record is counted.
+    let req = actix_web::test::TestRequest::post()
+        .uri(&format!("{}/count", endpoint.path))
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+
+    let body: Value = actix_web::test::read_body_json(res).await;
+    assert_eq!(
+        body.as_u64().unwrap() as usize,
+        test_utils::get_films().len()
+    );
+
     let req = actix_web::test::TestRequest::post()
         .uri(&format!("{}/query", endpoint.path))
         .set_json(&filter)
@@ -97,6 +265,9 @@ async fn get_route() {
             cache,
             endpoint: endpoint.clone(),
         }],
+        ReadinessGate::default(),
+        None,
+        None,
     );
     let app = actix_web::test::init_service(api_server).await;
     let req = actix_web::test::TestRequest::get()
@@ -115,3 +286,235 @@ async fn get_route() {
         "Must be equal"
     );
 }
+
+#[actix_web::test]
+async fn ready_route() {
+    let endpoint = test_utils::get_endpoint();
+    let mut schema_name = endpoint.to_owned().path;
+    schema_name.remove(0);
+    let cache = test_utils::initialize_cache(&schema_name, None);
+    let readiness = ReadinessGate::default();
+    let api_server = ApiServer::create_app_entry(
+        None,
+        CorsOptions::Permissive,
+        vec![CacheEndpoint { cache, endpoint }],
+        readiness.clone(),
+        None,
+        None,
+    );
+    let app = actix_web::test::init_service(api_server).await;
+
+    let req = actix_web::test::TestRequest::get()
+        .uri("/ready")
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert_eq!(res.status().as_u16(), 503);
+
+    readiness.set_ready(true);
+    let req = actix_web::test::TestRequest::get()
+        .uri("/ready")
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+}
+
+#[actix_web::test]
+async fn rate_limited_route() {
+    let endpoint = test_utils::get_endpoint();
+    let mut schema_name = endpoint.to_owned().path;
+    schema_name.remove(0);
+    let cache = test_utils::initialize_cache(&schema_name, None);
+    let rate_limiter = RateLimiter::new(&ApiRateLimit {
+        max_requests: 2,
+        window_secs: 60,
+        per_token: false,
+    });
+    let api_server = ApiServer::create_app_entry(
+        None,
+        CorsOptions::Permissive,
+        vec![CacheEndpoint {
+            cache,
+            endpoint: endpoint.clone(),
+        }],
+        ReadinessGate::default(),
+        Some(rate_limiter),
+        None,
+    );
+    let app = actix_web::test::init_service(api_server).await;
+
+    for remaining in ["1", "0"] {
+        let req = actix_web::test::TestRequest::get()
+            .uri(&endpoint.path)
+            .to_request();
+        let res = actix_web::test::call_service(&app, req).await;
+        assert!(res.status().is_success());
+        assert_eq!(
+            res.headers().get("x-ratelimit-remaining").unwrap(),
+            remaining
+        );
+    }
+
+    let req = actix_web::test::TestRequest::get()
+        .uri(&endpoint.path)
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert_eq!(res.status().as_u16(), 429);
+    assert!(res.headers().contains_key("retry-after"));
+
+    // Routes outside of the endpoints are not limited
+    let req = actix_web::test::TestRequest::get()
+        .uri("/health")
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+}
+
+#[test]
+fn cors_allow_list_validates_origins() {
+    assert!(CorsOptions::allow_list(vec![
+        "https://app.example.com".to_string(),
+        "http://localhost:3000".to_string(),
+    ])
+    .is_ok());
+    for origin in [
+        "app.example.com",
+        "https://app.example.com/",
+        "https://app.example.com/path",
+        "ftp://app.example.com",
+    ] {
+        assert!(
+            CorsOptions::allow_list(vec![origin.to_string()]).is_err(),
+            "{} must be rejected",
+            origin
+        );
+    }
+}
+
+#[test]
+fn cors_allow_list_validates_deserialized_origins() {
+    let valid: CorsOptions =
+        serde_json::from_value(json!({"AllowList": ["https://app.example.com"]})).unwrap();
+    assert_eq!(
+        valid,
+        CorsOptions::allow_list(vec!["https://app.example.com".to_string()]).unwrap()
+    );
+    assert!(
+        serde_json::from_value::<CorsOptions>(json!({"AllowList": ["app.example.com"]})).is_err()
+    );
+}
+
+#[actix_web::test]
+async fn cors_allow_list() {
+    let endpoint = test_utils::get_endpoint();
+    let cache = test_utils::initialize_cache(&endpoint.name, None);
+    let api_server = ApiServer::create_app_entry(
+        None,
+        CorsOptions::allow_list(vec!["https://app.example.com".to_string()]).unwrap(),
+        vec![CacheEndpoint {
+            cache,
+            endpoint: endpoint.clone(),
+        }],
+        ReadinessGate::default(),
+        None,
+        None,
+    );
+    let app = actix_web::test::init_service(api_server).await;
+
+    let req = actix_web::test::TestRequest::get()
+        .uri(&endpoint.path)
+        .insert_header((header::ORIGIN, "https://app.example.com"))
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+    assert_eq!(
+        res.headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .unwrap(),
+        "https://app.example.com"
+    );
+
+    let req = actix_web::test::TestRequest::get()
+        .uri(&endpoint.path)
+        .insert_header((header::ORIGIN, "https://evil.example.com"))
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert!(res
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .is_none());
+}
+
+#[actix_web::test]
+async fn stream_route() {
+    let endpoint = test_utils::get_endpoint();
+    let (schema, _) = test_utils::get_schema();
+    let cache = test_utils::initialize_cache(&endpoint.name, None);
+    let (tx, rx) = broadcast::channel(16);
+    let api_server = ApiServer::create_app_entry(
+        None,
+        CorsOptions::Permissive,
+        vec![CacheEndpoint {
+            cache,
+            endpoint: endpoint.clone(),
+        }],
+        ReadinessGate::default(),
+        None,
+        Some(rx),
+    );
+    let app = actix_web::test::init_service(api_server).await;
+
+    // $filter={"film_id": 524}
+    let req = actix_web::test::TestRequest::get()
+        .uri(&format!(
+            "{}/stream?$filter=%7B%22film_id%22%3A524%7D",
+            endpoint.path
+        ))
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+    assert_eq!(
+        res.headers().get(header::CONTENT_TYPE).unwrap(),
+        "text/event-stream"
+    );
+
+    for record in test_utils::get_sample_records(schema) {
+        let op = map_operation(endpoint.name.clone(), &Operation::Insert { new: record });
+        tx.send(PipelineResponse {
+            endpoint: endpoint.name.clone(),
+            api_event: Some(ApiEvent::Op(op)),
+        })
+        .unwrap();
+    }
+    // Closing the channel ends the stream.
+    drop(tx);
+
+    let body = actix_web::body::to_bytes(res.into_body()).await.unwrap();
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    assert_eq!(body.matches("event: insert\n").count(), 1);
+    assert!(body.contains("\"film_id\":524"));
+    assert!(!body.contains("\"film_id\":268"));
+}
+
+#[actix_web::test]
+async fn stream_route_without_events() {
+    let endpoint = test_utils::get_endpoint();
+    let cache = test_utils::initialize_cache(&endpoint.name, None);
+    let api_server = ApiServer::create_app_entry(
+        None,
+        CorsOptions::Permissive,
+        vec![CacheEndpoint {
+            cache,
+            endpoint: endpoint.clone(),
+        }],
+        ReadinessGate::default(),
+        None,
+        None,
+    );
+    let app = actix_web::test::init_service(api_server).await;
+
+    let req = actix_web::test::TestRequest::get()
+        .uri(&format!("{}/stream", endpoint.path))
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert_eq!(res.status().as_u16(), 503);
+}
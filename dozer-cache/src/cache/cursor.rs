@@ -0,0 +1,72 @@
+//! Opaque cursors used to page through query results.
+//!
+//! A cursor encodes the primary key of the last record of a page. Passing it as `$after` returns
+//! the records inserted after that one, so records inserted or deleted between two pages don't
+//! shift the next page the way `$skip` does.
+
+use dozer_types::types::{Record, Schema};
+
+use super::index::get_primary_key;
+use crate::errors::QueryValidationError;
+
+/// Returns the cursor pointing after `record`, or `None` if `schema` has no primary key.
+pub fn encode_cursor(schema: &Schema, record: &Record) -> Option<String> {
+    if schema.primary_index.is_empty() {
+        return None;
+    }
+    let key = get_primary_key(&schema.primary_index, &record.values);
+    Some(key.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// Returns the primary key encoded in `cursor`.
+pub fn decode_cursor(cursor: &str) -> Result<Vec<u8>, QueryValidationError> {
+    let invalid = || QueryValidationError::InvalidCursor(cursor.to_string());
+    if cursor.len() % 2 != 0 {
+        return Err(invalid());
+    }
+    (0..cursor.len())
+        .step_by(2)
+        .map(|i| {
+            cursor
+                .get(i..i + 2)
+                .filter(|byte| byte.chars().all(|c| c.is_ascii_hexdigit()))
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(invalid)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dozer_types::types::{Field, FieldDefinition, FieldType};
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let mut schema = Schema::empty();
+        schema.field(
+            FieldDefinition::new("id".to_string(), FieldType::Int, false),
+            true,
+        );
+        let record = Record::new(None, vec![Field::Int(42)], None);
+
+        let cursor = encode_cursor(&schema, &record).unwrap();
+        assert_eq!(
+            decode_cursor(&cursor).unwrap(),
+            get_primary_key(&schema.primary_index, &record.values)
+        );
+
+        schema.primary_index.clear();
+        assert_eq!(encode_cursor(&schema, &record), None);
+    }
+
+    #[test]
+    fn test_decode_invalid_cursor() {
+        for cursor in ["0", "zz", "+1", "é0"] {
+            assert!(matches!(
+                decode_cursor(cursor),
+                Err(QueryValidationError::InvalidCursor(_))
+            ));
+        }
+    }
+}
@@ -17,6 +17,9 @@ pub struct QueryExpression {
     pub limit: Option<usize>,
     #[serde(rename = "$skip", default)]
     pub skip: usize,
+    /// Cursor of the last record of the previous page, see [`crate::cache::cursor`].
+    #[serde(rename = "$after", default)]
+    pub after: Option<String>,
 }
 pub fn default_limit_for_query() -> usize {
     50
@@ -28,6 +31,7 @@ impl Default for QueryExpression {
             order_by: Default::default(),
             limit: Some(default_limit_for_query()),
             skip: Default::default(),
+            after: None,
         }
     }
 }
@@ -44,6 +48,7 @@ impl QueryExpression {
             order_by: SortOptions(order_by),
             limit,
             skip,
+            after: None,
         }
     }
 }
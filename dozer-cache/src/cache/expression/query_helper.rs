@@ -58,13 +58,22 @@ pub fn simple_expression(
     validate_field_name(&key)?;
     match value {
         Value::Object(pairs) => {
-            validate_query(pairs.len() <= 1, MoreThanOneStmt)?;
-            let (inner_key, scalar_value) = pairs.into_iter().next().ok_or(EmptyObjectAsValue)?;
-            let operator: Operator =
-                Operator::convert_str(&inner_key).ok_or(UnidentifiedOperator(inner_key))?;
+            validate_query(!pairs.is_empty(), EmptyObjectAsValue)?;
+            // Several operators on a field, like `{"$gt": 1, "$lt": 10}`, must all hold.
+            let mut expressions = pairs
+                .into_iter()
+                .map(|(inner_key, scalar_value)| {
+                    let operator: Operator =
+                        Operator::convert_str(&inner_key).ok_or(UnidentifiedOperator(inner_key))?;
+                    construct_simple_expression(key.clone(), operator, scalar_value)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
 
-            let expression = construct_simple_expression(key, operator, scalar_value)?;
-            Ok(expression)
+            if expressions.len() == 1 {
+                Ok(expressions.remove(0))
+            } else {
+                Ok(FilterExpression::And(expressions))
+            }
         }
         Value::Number(_) | Value::String(_) | Value::Bool(_) | Value::Null => {
             let expression = construct_simple_expression(key, Operator::EQ, value)?;
@@ -99,3 +108,15 @@ pub fn sort_option(key: String, value: Value) -> Result<SortOption, QueryValidat
     let direction = SortDirection::convert_str(&direction).ok_or(UnidentifiedOrder(direction))?;
     Ok(SortOption::new(key, direction))
 }
+
+/// Parses a sort option of the array form of `$order_by`, like `{"field": "a", "direction": "asc"}`.
+pub fn sort_option_from_object(value: Value) -> Result<SortOption, QueryValidationError> {
+    if let Value::Object(object) = &value {
+        if let (2, Some(Value::String(field)), Some(direction)) =
+            (object.len(), object.get("field"), object.get("direction"))
+        {
+            return sort_option(field.clone(), direction.clone());
+        }
+    }
+    Err(InvalidSortOption(value))
+}
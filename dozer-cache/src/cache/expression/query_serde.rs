@@ -5,7 +5,9 @@ use dozer_types::serde::ser::{self, Serialize, SerializeMap, Serializer};
 use dozer_types::serde_json::Value;
 use dozer_types::{serde, serde_json};
 
-use crate::cache::expression::query_helper::{and_expression, simple_expression, sort_option};
+use crate::cache::expression::query_helper::{
+    and_expression, simple_expression, sort_option, sort_option_from_object,
+};
 
 use super::super::expression::FilterExpression;
 use super::{Operator, SortOptions};
@@ -95,7 +97,9 @@ impl<'de> Deserialize<'de> for SortOptions {
             type Value = SortOptions;
 
             fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                formatter.write_str("map from field name to sort direction")
+                formatter.write_str(
+                    "map from field name to sort direction, or array of field and direction objects",
+                )
             }
 
             fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
@@ -111,8 +115,21 @@ impl<'de> Deserialize<'de> for SortOptions {
                 }
                 Ok(SortOptions(sort_options))
             }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let mut sort_options = vec![];
+                while let Some(value) = seq.next_element::<Value>()? {
+                    let sort_option = sort_option_from_object(value)
+                        .map_err(|err| de::Error::custom(err.to_string()))?;
+                    sort_options.push(sort_option);
+                }
+                Ok(SortOptions(sort_options))
+            }
         }
-        deserializer.deserialize_map(SortOptionsVisitor {})
+        deserializer.deserialize_any(SortOptionsVisitor {})
     }
 }
 
@@ -136,6 +136,16 @@ fn test_filter_query_deserialize_complex() {
         ]),
     );
 
+    // Several operators on the same field
+    test_deserialize_filter(
+        json!({"film_id":  {"$gt": 100, "$lt": 200}}),
+        FilterExpression::And(vec![
+            FilterExpression::Simple("film_id".to_string(), Operator::GT, Value::from(100)),
+            FilterExpression::Simple("film_id".to_string(), Operator::LT, Value::from(200)),
+        ]),
+    );
+    test_deserialize_filter_error(json!({"a":  {"$gt": 1, "lt": 2}}));
+
     test_deserialize_filter_error(json!({"$and": [{"a":  {"$lt": 1}}]}));
     test_deserialize_filter_error(json!({"$and": []}));
     test_deserialize_filter_error(json!({"$and": {}}));
@@ -172,6 +182,24 @@ fn test_sort_options_query_deserialize() {
     test_deserialize_sort_options_error(json!({"a": []}));
     test_deserialize_sort_options_error(json!({"a": {}}));
     test_deserialize_sort_options_error(json!({"-": "asc"}));
+
+    // The array form keeps the order of the fields.
+    test_deserialize_sort_options(json!([]), vec![]);
+    test_deserialize_sort_options(
+        json!([{"field": "b", "direction": "asc"}, {"field": "a", "direction": "desc"}]),
+        vec![
+            SortOption::new("b".into(), Ascending),
+            SortOption::new("a".into(), Descending),
+        ],
+    );
+
+    test_deserialize_sort_options_error(json!(["a"]));
+    test_deserialize_sort_options_error(json!([{"field": "a"}]));
+    test_deserialize_sort_options_error(json!([{"field": 1, "direction": "asc"}]));
+    test_deserialize_sort_options_error(json!([{"field": "a", "direction": "up"}]));
+    test_deserialize_sort_options_error(
+        json!([{"field": "a", "direction": "asc", "nulls": "first"}]),
+    );
 }
 
 #[test]
@@ -1,6 +1,9 @@
+use std::borrow::Cow;
 use std::cmp::Ordering;
 
+use dozer_types::serde_json::Value as JsonValue;
 use dozer_types::types::{FieldBorrow, IndexDefinition, Record};
+use unicode_segmentation::UnicodeSegmentation;
 
 pub trait CacheIndex {
     // Builds one index based on index definition and record
@@ -42,6 +45,31 @@ pub fn get_secondary_index(fields: &[&Field], is_single_field_index: bool) -> Ve
     }
 }
 
+/// Returns the values a single field index stores for `field`.
+///
+/// JSON arrays, held by a `Json` or a `Bson` field, are indexed by each of their distinct elements as a
+/// `Field::Json`, so that `$contains` on one element is an index lookup. An empty array isn't indexed at all.
+/// Every other field is indexed as is.
+pub fn get_index_elements(field: &Field) -> Vec<Cow<Field>> {
+    let elements = match field {
+        Field::Json(JsonValue::Array(elements)) => elements.clone(),
+        Field::Bson(_) => match field.to_json() {
+            Some(JsonValue::Array(elements)) => elements,
+            _ => return vec![Cow::Borrowed(field)],
+        },
+        _ => return vec![Cow::Borrowed(field)],
+    };
+
+    let mut fields: Vec<Cow<Field>> = vec![];
+    for element in elements {
+        let element = Field::Json(element);
+        if !fields.iter().any(|field| **field == element) {
+            fields.push(Cow::Owned(element));
+        }
+    }
+    fields
+}
+
 pub fn compare_composite_secondary_index(a: &[u8], b: &[u8]) -> Result<Ordering, CompareError> {
     let mut a = CompositeSecondaryIndexKey::new(a);
     let mut b = CompositeSecondaryIndexKey::new(b);
@@ -62,6 +90,11 @@ pub fn compare_composite_secondary_index(a: &[u8], b: &[u8]) -> Result<Ordering,
     })
 }
 
+/// Splits `string` into the tokens a full text index stores.
+pub fn get_full_text_tokens(string: &str) -> impl Iterator<Item = &str> {
+    string.unicode_words()
+}
+
 pub fn get_full_text_secondary_index(token: &str) -> Vec<u8> {
     token.as_bytes().to_vec()
 }
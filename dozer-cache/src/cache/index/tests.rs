@@ -1,8 +1,9 @@
-use dozer_types::types::field_test_cases;
+use dozer_types::serde_json::json;
+use dozer_types::types::{field_test_cases, Field};
 
 use crate::cache::index::{get_composite_secondary_index, CompositeSecondaryIndexKey};
 
-use super::get_full_text_secondary_index;
+use super::{get_full_text_secondary_index, get_index_elements};
 
 #[test]
 fn test_get_full_text_secondary_index() {
@@ -41,3 +42,38 @@ fn composite_key_encode_preserves_prefix() {
         }
     }
 }
+
+#[test]
+fn test_get_index_elements() {
+    let elements = |field: &Field| {
+        get_index_elements(field)
+            .into_iter()
+            .map(|element| element.into_owned())
+            .collect::<Vec<_>>()
+    };
+
+    for field in [
+        Field::Int(1),
+        Field::String("a".into()),
+        Field::Null,
+        Field::Json(json!({"a": [1]})),
+        Field::Bson(b"not json".to_vec()),
+    ] {
+        assert_eq!(elements(&field), vec![field.clone()]);
+    }
+
+    // Arrays are indexed by their distinct elements.
+    assert_eq!(
+        elements(&Field::Json(json!(["a", 1, "a", null]))),
+        vec![
+            Field::Json(json!("a")),
+            Field::Json(json!(1)),
+            Field::Json(json!(null))
+        ]
+    );
+    assert_eq!(
+        elements(&Field::Bson(b"[1, [2]]".to_vec())),
+        vec![Field::Json(json!(1)), Field::Json(json!([2]))]
+    );
+    assert!(elements(&Field::Json(json!([]))).is_empty());
+}
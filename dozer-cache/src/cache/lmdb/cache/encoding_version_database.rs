@@ -0,0 +1,141 @@
+use dozer_types::types::FIELD_ENCODING_VERSION;
+use lmdb::{Cursor, Database, DatabaseFlags, Environment, Transaction, WriteFlags};
+
+use crate::errors::CacheError;
+
+const DB_NAME: &str = "encoding_version";
+const VERSION_KEY: &[u8] = b"field";
+
+/// Version of the field encoding of the keys and indexes of the cache, see
+/// [`FIELD_ENCODING_VERSION`].
+#[derive(Debug, Clone, Copy)]
+pub struct EncodingVersionDatabase(Database);
+
+impl EncodingVersionDatabase {
+    /// Opens the database, recording the current version in a new cache. Fails if the cache was
+    /// written with another version, as its keys would be decoded and sorted wrongly.
+    ///
+    /// Must be called before the other databases are created, to tell a new cache from one
+    /// written before the version was recorded.
+    pub fn new(env: &Environment, create_if_not_exist: bool) -> Result<Self, CacheError> {
+        match env.open_db(Some(DB_NAME)) {
+            Ok(db) => {
+                let txn = env.begin_ro_txn().map_err(internal_err)?;
+                let version = match txn.get(db, &VERSION_KEY) {
+                    Ok(version) => u32::from_be_bytes(
+                        version
+                            .try_into()
+                            .expect("The version must be a u32 in this database"),
+                    ),
+                    Err(lmdb::Error::NotFound) => 1,
+                    Err(e) => return Err(internal_err(e)),
+                };
+                txn.commit().map_err(internal_err)?;
+                check_version(version)?;
+                Ok(Self(db))
+            }
+            Err(lmdb::Error::NotFound) if create_if_not_exist && is_empty(env)? => {
+                let db = env
+                    .create_db(Some(DB_NAME), DatabaseFlags::empty())
+                    .map_err(internal_err)?;
+                let mut txn = env.begin_rw_txn().map_err(internal_err)?;
+                txn.put(
+                    db,
+                    &VERSION_KEY,
+                    &FIELD_ENCODING_VERSION.to_be_bytes(),
+                    WriteFlags::empty(),
+                )
+                .map_err(internal_err)?;
+                txn.commit().map_err(internal_err)?;
+                Ok(Self(db))
+            }
+            Err(lmdb::Error::NotFound) => Err(CacheError::IncompatibleEncodingVersion(
+                1,
+                FIELD_ENCODING_VERSION,
+            )),
+            Err(e) => Err(internal_err(e)),
+        }
+    }
+}
+
+fn check_version(version: u32) -> Result<(), CacheError> {
+    if version == FIELD_ENCODING_VERSION {
+        Ok(())
+    } else {
+        Err(CacheError::IncompatibleEncodingVersion(
+            version,
+            FIELD_ENCODING_VERSION,
+        ))
+    }
+}
+
+/// Whether `env` has no database yet. Named databases are keys of the unnamed one.
+fn is_empty(env: &Environment) -> Result<bool, CacheError> {
+    let db = env.open_db(None).map_err(internal_err)?;
+    let txn = env.begin_ro_txn().map_err(internal_err)?;
+    let is_empty = {
+        let mut cursor = txn.open_ro_cursor(db).map_err(internal_err)?;
+        cursor.iter_start().next().is_none()
+    };
+    txn.commit().map_err(internal_err)?;
+    Ok(is_empty)
+}
+
+fn internal_err(e: lmdb::Error) -> CacheError {
+    CacheError::InternalError(Box::new(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::{
+        lmdb::utils::{init_db, init_env, DatabaseCreateOptions},
+        CacheOptions,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_encoding_version_database() {
+        let env = init_env(&CacheOptions::default()).unwrap();
+        EncodingVersionDatabase::new(&env, true).unwrap();
+        // Reopening a cache of the current version
+        EncodingVersionDatabase::new(&env, true).unwrap();
+        EncodingVersionDatabase::new(&env, false).unwrap();
+
+        let db = env.open_db(Some(DB_NAME)).unwrap();
+        let mut txn = env.begin_rw_txn().unwrap();
+        txn.put(db, &VERSION_KEY, &1_u32.to_be_bytes(), WriteFlags::empty())
+            .unwrap();
+        txn.commit().unwrap();
+        assert!(matches!(
+            EncodingVersionDatabase::new(&env, true),
+            Err(CacheError::IncompatibleEncodingVersion(
+                1,
+                FIELD_ENCODING_VERSION
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_cache_without_encoding_version() {
+        // A cache written before the version was recorded
+        let env = init_env(&CacheOptions::default()).unwrap();
+        init_db(
+            &env,
+            Some("records"),
+            Some(DatabaseCreateOptions {
+                allow_dup: false,
+                fixed_length_key: true,
+            }),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            EncodingVersionDatabase::new(&env, true),
+            Err(CacheError::IncompatibleEncodingVersion(
+                1,
+                FIELD_ENCODING_VERSION
+            ))
+        ));
+    }
+}
@@ -8,7 +8,7 @@ use lmdb::{Environment, RoTransaction, RwTransaction, Transaction};
 use dozer_types::types::{IndexDefinition, Record};
 use dozer_types::types::{Schema, SchemaIdentifier};
 
-use super::super::Cache;
+use super::super::{check_schema_version, Cache};
 use super::indexer::Indexer;
 use super::query::handler::LmdbQueryHandler;
 use super::{utils, CacheOptions, CacheOptionsKind};
@@ -16,12 +16,16 @@ use crate::cache::expression::QueryExpression;
 use crate::cache::index::get_primary_key;
 use crate::errors::CacheError;
 
+mod encoding_version_database;
 mod id_database;
+mod record_count_database;
 mod record_database;
 mod schema_database;
 mod secondary_index_database;
 
+use encoding_version_database::EncodingVersionDatabase;
 pub use id_database::IdDatabase;
+pub use record_count_database::RecordCountDatabase;
 pub use record_database::RecordDatabase;
 use schema_database::SchemaDatabase;
 use secondary_index_database::SecondaryIndexDatabase;
@@ -33,6 +37,7 @@ pub struct LmdbCache {
     env: Environment,
     db: RecordDatabase,
     id: IdDatabase,
+    record_count: RecordCountDatabase,
     secondary_indexes: Arc<RwLock<SecondaryIndexDatabases>>,
     schema_db: SchemaDatabase,
     cache_options: CacheOptions,
@@ -50,8 +55,10 @@ impl LmdbCache {
 
         // Create or open must have databases.
         let create_if_not_exist = matches!(cache_options.kind, CacheOptionsKind::Write(_));
+        EncodingVersionDatabase::new(&env, create_if_not_exist)?;
         let db = RecordDatabase::new(&env, create_if_not_exist)?;
         let id = IdDatabase::new(&env, create_if_not_exist)?;
+        let record_count = RecordCountDatabase::new(&env, create_if_not_exist)?;
         let schema_db = SchemaDatabase::new(&env, create_if_not_exist)?;
 
         // Open existing secondary index databases.
@@ -72,6 +79,7 @@ impl LmdbCache {
             env,
             db,
             id,
+            record_count,
             secondary_indexes: Arc::new(RwLock::new(secondary_indexe_databases)),
             schema_db,
             cache_options,
@@ -85,6 +93,7 @@ impl LmdbCache {
         schema: &Schema,
         secondary_indexes: &[IndexDefinition],
     ) -> Result<(), CacheError> {
+        check_schema_version(record, schema)?;
         let id = if schema.primary_index.is_empty() {
             self.id.get_or_generate(txn, None)?
         } else {
@@ -92,6 +101,7 @@ impl LmdbCache {
             self.id.get_or_generate(txn, Some(&primary_key))?
         };
         self.db.insert(txn, id, record)?;
+        self.record_count.increment(txn, get_schema_id(schema)?)?;
 
         let indexer = Indexer {
             secondary_indexes: self.secondary_indexes.clone(),
@@ -127,6 +137,7 @@ impl LmdbCache {
     ) -> Result<(), CacheError> {
         let id = self.id.get(txn, key)?;
         self.db.delete(txn, id)?;
+        self.record_count.decrement(txn, get_schema_id(schema)?)?;
 
         let indexer = Indexer {
             secondary_indexes: self.secondary_indexes.clone(),
@@ -197,12 +208,15 @@ impl Cache for LmdbCache {
 
         let handler = LmdbQueryHandler::new(
             self.db,
+            self.id,
+            self.record_count,
             self.secondary_indexes.clone(),
             &txn,
             &schema,
             &secondary_indexes,
             query,
             self.cache_options.common.intersection_chunk_size,
+            self.cache_options.common.max_in_memory_sort_records,
         );
         handler.count()
     }
@@ -216,14 +230,20 @@ impl Cache for LmdbCache {
 
         let handler = LmdbQueryHandler::new(
             self.db,
+            self.id,
+            self.record_count,
             self.secondary_indexes.clone(),
             &txn,
             &schema,
             &secondary_indexes,
             query,
             self.cache_options.common.intersection_chunk_size,
+            self.cache_options.common.max_in_memory_sort_records,
         );
         let records = handler.query()?;
+        for record in &records {
+            check_schema_version(record, &schema)?;
+        }
         Ok(records)
     }
 
@@ -297,6 +317,12 @@ impl Cache for LmdbCache {
     }
 }
 
+fn get_schema_id(schema: &Schema) -> Result<SchemaIdentifier, CacheError> {
+    schema
+        .identifier
+        .ok_or(CacheError::SchemaIdentifierNotFound)
+}
+
 /// Methods for testing.
 #[cfg(test)]
 mod tests {
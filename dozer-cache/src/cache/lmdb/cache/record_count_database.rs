@@ -0,0 +1,116 @@
+use dozer_types::types::SchemaIdentifier;
+use lmdb::{Database, Environment, RwTransaction, Transaction, WriteFlags};
+
+use crate::{
+    cache::lmdb::utils::{self, DatabaseCreateOptions},
+    errors::{CacheError, QueryError},
+};
+
+/// Number of records of every schema, so counting them doesn't need a scan.
+#[derive(Debug, Clone, Copy)]
+pub struct RecordCountDatabase(Database);
+
+impl RecordCountDatabase {
+    pub fn new(env: &Environment, create_if_not_exist: bool) -> Result<Self, CacheError> {
+        let options = if create_if_not_exist {
+            Some(DatabaseCreateOptions {
+                allow_dup: false,
+                fixed_length_key: false,
+            })
+        } else {
+            None
+        };
+        let db = utils::init_db(env, Some("record_counts"), options)?;
+        Ok(Self(db))
+    }
+
+    pub fn get<T: Transaction>(
+        &self,
+        txn: &T,
+        schema_id: SchemaIdentifier,
+    ) -> Result<usize, CacheError> {
+        match txn.get(self.0, &get_count_key(schema_id)) {
+            Ok(count) => Ok(u64::from_be_bytes(
+                count
+                    .try_into()
+                    .expect("All values must be u64 counts in this database"),
+            ) as usize),
+            Err(lmdb::Error::NotFound) => Ok(0),
+            Err(e) => Err(CacheError::QueryError(QueryError::GetValue(e))),
+        }
+    }
+
+    pub fn increment(
+        &self,
+        txn: &mut RwTransaction,
+        schema_id: SchemaIdentifier,
+    ) -> Result<(), CacheError> {
+        let count = self.get(txn, schema_id)?;
+        self.put(txn, schema_id, count + 1)
+    }
+
+    pub fn decrement(
+        &self,
+        txn: &mut RwTransaction,
+        schema_id: SchemaIdentifier,
+    ) -> Result<(), CacheError> {
+        let count = self.get(txn, schema_id)?;
+        self.put(txn, schema_id, count.saturating_sub(1))
+    }
+
+    fn put(
+        &self,
+        txn: &mut RwTransaction,
+        schema_id: SchemaIdentifier,
+        count: usize,
+    ) -> Result<(), CacheError> {
+        txn.put(
+            self.0,
+            &get_count_key(schema_id),
+            &(count as u64).to_be_bytes(),
+            WriteFlags::empty(),
+        )
+        .map_err(|e| CacheError::QueryError(QueryError::InsertValue(e)))
+    }
+}
+
+fn get_count_key(schema_id: SchemaIdentifier) -> [u8; 6] {
+    let mut key = [0; 6];
+    key[..4].copy_from_slice(&schema_id.id.to_be_bytes());
+    key[4..].copy_from_slice(&schema_id.version.to_be_bytes());
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::{lmdb::utils::init_env, CacheOptions};
+
+    use super::*;
+
+    #[test]
+    fn test_record_count_database() {
+        let env = init_env(&CacheOptions::default()).unwrap();
+        let writer = RecordCountDatabase::new(&env, true).unwrap();
+        let reader = RecordCountDatabase::new(&env, false).unwrap();
+        let schema_1 = SchemaIdentifier { id: 1, version: 1 };
+        let schema_2 = SchemaIdentifier { id: 2, version: 1 };
+
+        let mut txn = env.begin_rw_txn().unwrap();
+        writer.increment(&mut txn, schema_1).unwrap();
+        writer.increment(&mut txn, schema_1).unwrap();
+        writer.increment(&mut txn, schema_2).unwrap();
+        writer.decrement(&mut txn, schema_2).unwrap();
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        assert_eq!(reader.get(&txn, schema_1).unwrap(), 2);
+        assert_eq!(reader.get(&txn, schema_2).unwrap(), 0);
+        assert_eq!(
+            reader
+                .get(&txn, SchemaIdentifier { id: 3, version: 1 })
+                .unwrap(),
+            0
+        );
+        txn.commit().unwrap();
+    }
+}
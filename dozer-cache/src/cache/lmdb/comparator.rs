@@ -240,6 +240,7 @@ mod tests {
             Field::Timestamp(DateTime::from(Utc.timestamp_millis(1))),
             Field::Date(NaiveDate::from_ymd(2020, 1, 2)),
             Field::Bson(vec![255]),
+            Field::Json(dozer_types::serde_json::json!({ "a": 1 })),
         ];
         for a in test_cases.iter() {
             check(a);
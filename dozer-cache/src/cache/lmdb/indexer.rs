@@ -5,7 +5,6 @@ use dozer_types::{
 };
 use lmdb::{RwTransaction, Transaction};
 use std::sync::Arc;
-use unicode_segmentation::UnicodeSegmentation;
 
 use crate::cache::index::{self, get_full_text_secondary_index};
 
@@ -43,8 +42,11 @@ impl Indexer {
 
             match index {
                 IndexDefinition::SortedInverted(fields) => {
-                    let secondary_key = Self::_build_index_sorted_inverted(fields, &record.values);
-                    db.insert(&mut txn, &secondary_key, id)?;
+                    for secondary_key in
+                        Self::_build_indices_sorted_inverted(fields, &record.values)
+                    {
+                        db.insert(&mut txn, &secondary_key, id)?;
+                    }
                 }
                 IndexDefinition::FullText(field_index) => {
                     for secondary_key in
@@ -80,8 +82,11 @@ impl Indexer {
 
             match index {
                 IndexDefinition::SortedInverted(fields) => {
-                    let secondary_key = Self::_build_index_sorted_inverted(fields, &record.values);
-                    db.delete(txn, &secondary_key, id)?;
+                    for secondary_key in
+                        Self::_build_indices_sorted_inverted(fields, &record.values)
+                    {
+                        db.delete(txn, &secondary_key, id)?;
+                    }
                 }
                 IndexDefinition::FullText(field_index) => {
                     for secondary_key in
@@ -96,14 +101,22 @@ impl Indexer {
         Ok(())
     }
 
-    fn _build_index_sorted_inverted(fields: &[usize], values: &[Field]) -> Vec<u8> {
+    fn _build_indices_sorted_inverted(fields: &[usize], values: &[Field]) -> Vec<Vec<u8>> {
         let values = fields
             .iter()
             .copied()
             .filter_map(|index| (values.get(index)))
             .collect::<Vec<_>>();
         // `values.len() == 1` criteria must be kept the same with `comparator.rs`.
-        index::get_secondary_index(&values, values.len() == 1)
+        if values.len() == 1 {
+            // A multi-valued field gets one index entry per element.
+            index::get_index_elements(values[0])
+                .into_iter()
+                .map(|element| index::get_secondary_index(&[&element], true))
+                .collect()
+        } else {
+            vec![index::get_secondary_index(&values, false)]
+        }
     }
 
     fn _build_indices_full_text(
@@ -122,8 +135,7 @@ impl Indexer {
             return Err(CacheError::IndexError(IndexError::FieldIndexOutOfRange));
         };
 
-        Ok(string
-            .unicode_words()
+        Ok(index::get_full_text_tokens(string)
             .map(get_full_text_secondary_index)
             .collect())
     }
@@ -135,6 +147,8 @@ mod tests {
         lmdb::tests::utils as lmdb_utils, lmdb::CacheOptions, test_utils, Cache, LmdbCache,
     };
 
+    use dozer_types::serde_json::json;
+
     use super::*;
 
     #[test]
@@ -188,6 +202,70 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_build_indices_sorted_inverted() {
+        let values = [Field::Int(1), Field::String("a".into())];
+        assert_eq!(
+            Indexer::_build_indices_sorted_inverted(&[1], &values),
+            vec![index::get_secondary_index(&[&values[1]], true)]
+        );
+        assert_eq!(
+            Indexer::_build_indices_sorted_inverted(&[0, 1], &values),
+            vec![index::get_secondary_index(&[&values[0], &values[1]], false)]
+        );
+
+        // An array gets one entry per distinct element, unless it's part of a composite index.
+        let values = [Field::Int(1), Field::Json(json!(["a", "b", "a"]))];
+        assert_eq!(
+            Indexer::_build_indices_sorted_inverted(&[1], &values),
+            vec![
+                index::get_secondary_index(&[&Field::Json(json!("a"))], true),
+                index::get_secondary_index(&[&Field::Json(json!("b"))], true),
+            ]
+        );
+        assert_eq!(
+            Indexer::_build_indices_sorted_inverted(&[0, 1], &values),
+            vec![index::get_secondary_index(&[&values[0], &values[1]], false)]
+        );
+    }
+
+    #[test]
+    fn test_array_indexes_are_deleted() {
+        let cache = LmdbCache::new(CacheOptions::default()).unwrap();
+        let (schema, secondary_indexes) = test_utils::schema_json_array();
+
+        cache
+            .insert_schema("sample", &schema, &secondary_indexes)
+            .unwrap();
+
+        for (id, tags) in [(1, json!(["a", "b", "a"])), (2, json!([])), (3, json!("c"))] {
+            cache
+                .insert(&Record::new(
+                    schema.identifier,
+                    vec![Field::Int(id), Field::Json(tags)],
+                    None,
+                ))
+                .unwrap();
+        }
+
+        let indexes = lmdb_utils::get_indexes(&cache);
+        assert_eq!(indexes[0].len(), 3);
+        // "a" and "b", nothing for the empty array, and "c".
+        assert_eq!(indexes[1].len(), 3);
+
+        for id in [1i64, 2, 3] {
+            cache.delete(&Field::Int(id).encode()).unwrap();
+        }
+        assert_eq!(
+            lmdb_utils::get_indexes(&cache)
+                .into_iter()
+                .flatten()
+                .count(),
+            0,
+            "Must delete every element index"
+        );
+    }
+
     #[test]
     fn test_build_indices_full_text() {
         let field_index = 0;
@@ -40,6 +40,9 @@ pub struct CacheCommonOptions {
     /// The chunk size when calculating intersection of index queries.
     pub intersection_chunk_size: usize,
 
+    /// The maximum number of records a query can sort in memory, when no index matches its `$order_by`.
+    pub max_in_memory_sort_records: usize,
+
     // Provide a path where db will be created. If nothing is provided, will default to a temp location.
     pub path: Option<PathBuf>,
 }
@@ -50,6 +53,7 @@ impl Default for CacheCommonOptions {
             max_readers: 1000,
             max_db_size: 1000,
             intersection_chunk_size: 100,
+            max_in_memory_sort_records: 100_000,
             path: None,
         }
     }
@@ -2,15 +2,16 @@ use std::{cmp::Ordering, sync::Arc};
 
 use super::iterator::{CacheIterator, KeyEndpoint};
 use crate::cache::{
+    cursor::decode_cursor,
     expression::{Operator, QueryExpression, SortDirection},
     index,
     lmdb::{
-        cache::{RecordDatabase, SecondaryIndexDatabases},
+        cache::{IdDatabase, RecordCountDatabase, RecordDatabase, SecondaryIndexDatabases},
         query::intersection::intersection,
     },
     plan::{IndexScan, IndexScanKind, Plan, QueryPlanner, SortedInvertedRangeQuery},
 };
-use crate::errors::{CacheError, IndexError};
+use crate::errors::{CacheError, IndexError, PlanError, QueryError, QueryValidationError};
 use dozer_types::{
     bincode,
     parking_lot::RwLock,
@@ -21,68 +22,171 @@ use lmdb::RoTransaction;
 
 pub struct LmdbQueryHandler<'a> {
     db: RecordDatabase,
+    id_db: IdDatabase,
+    record_count_db: RecordCountDatabase,
     secondary_index_databases: Arc<RwLock<SecondaryIndexDatabases>>,
     txn: &'a RoTransaction<'a>,
     schema: &'a Schema,
     secondary_indexes: &'a [IndexDefinition],
     query: &'a QueryExpression,
     intersection_chunk_size: usize,
+    max_in_memory_sort_records: usize,
 }
 impl<'a> LmdbQueryHandler<'a> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         db: RecordDatabase,
+        id_db: IdDatabase,
+        record_count_db: RecordCountDatabase,
         secondary_index_databases: Arc<RwLock<SecondaryIndexDatabases>>,
         txn: &'a RoTransaction,
         schema: &'a Schema,
         secondary_indexes: &'a [IndexDefinition],
         query: &'a QueryExpression,
         intersection_chunk_size: usize,
+        max_in_memory_sort_records: usize,
     ) -> Self {
         Self {
             db,
+            id_db,
+            record_count_db,
             secondary_index_databases,
             txn,
             schema,
             secondary_indexes,
             query,
             intersection_chunk_size,
+            max_in_memory_sort_records,
         }
     }
 
     pub fn count(&self) -> Result<usize, CacheError> {
+        // Without a filter or a cursor, every record of the schema is counted, whatever the order.
+        if self.query.filter.is_none() && self.query.after.is_none() {
+            return self.stored_count();
+        }
         let planner = QueryPlanner::new(self.schema, self.secondary_indexes, self.query);
         let execution = planner.plan()?;
-        match execution {
-            Plan::IndexScans(index_scans) => Ok(self.build_index_scan(index_scans)?.count()),
-            Plan::SeqScan(_) => Ok(self
-                .db
-                .count(self.txn)?
-                .saturating_sub(self.query.skip)
-                .min(self.query.limit.unwrap_or(usize::MAX))),
+        self.count_plan(execution)
+    }
+
+    fn count_plan(&self, plan: Plan) -> Result<usize, CacheError> {
+        match plan {
+            Plan::IndexScans(index_scans) => {
+                Ok(self.paginate(self.build_index_scan(index_scans)?).count())
+            }
+            Plan::SeqScan(_) => {
+                if let Some(after) = self.get_after_id()? {
+                    Ok(self.paginate(self.iterate(Some(after))?).count())
+                } else {
+                    self.stored_count()
+                }
+            }
             Plan::ReturnEmpty => Ok(0),
+            // Sorting doesn't change the number of records.
+            Plan::InMemorySort { plan, .. } => {
+                check_no_cursor(self.query)?;
+                self.count_plan(*plan)
+            }
         }
     }
 
+    /// Returns the number of records of the schema after pagination, without scanning them.
+    fn stored_count(&self) -> Result<usize, CacheError> {
+        let schema_id = self
+            .schema
+            .identifier
+            .ok_or(CacheError::SchemaIdentifierNotFound)?;
+        Ok(self
+            .record_count_db
+            .get(self.txn, schema_id)?
+            .saturating_sub(self.query.skip)
+            .min(self.query.limit.unwrap_or(usize::MAX)))
+    }
+
     pub fn query(&self) -> Result<Vec<Record>, CacheError> {
         let planner = QueryPlanner::new(self.schema, self.secondary_indexes, self.query);
         let execution = planner.plan()?;
         match execution {
             Plan::IndexScans(index_scans) => {
                 let scan = self.build_index_scan(index_scans)?;
-                self.collect_records(scan)
+                self.collect_records(self.paginate(scan))
             }
             Plan::SeqScan(_seq_scan) => self.iterate_and_deserialize(),
             Plan::ReturnEmpty => Ok(vec![]),
+            Plan::InMemorySort { plan, order_by } => {
+                let mut records = self.query_for_sort(*plan)?;
+                // The sort is stable, so records sorting equal stay in id order.
+                records.sort_by(|a, b| compare_records(a, b, &order_by));
+                Ok(self.paginate(records.into_iter()).collect())
+            }
         }
     }
 
     pub fn iterate_and_deserialize(&self) -> Result<Vec<Record>, CacheError> {
+        self.paginate(self.iterate(self.get_after_id()?)?)
+            .map(deserialize_record)
+            .collect()
+    }
+
+    fn iterate(
+        &self,
+        after: Option<u64>,
+    ) -> Result<impl Iterator<Item = &'a [u8]> + '_, CacheError> {
         let cursor = self.db.open_ro_cursor(self.txn)?;
-        CacheIterator::new(cursor, None, SortDirection::Ascending)
-            .skip(self.query.skip)
+        let start = after.map(|id| KeyEndpoint::Excluding(id.to_be_bytes().to_vec()));
+        Ok(CacheIterator::new(cursor, start, SortDirection::Ascending).map(|(_, v)| v))
+    }
+
+    /// Returns all the records of `plan`, failing if there are more than `max_in_memory_sort_records`.
+    fn query_for_sort(&self, plan: Plan) -> Result<Vec<Record>, CacheError> {
+        check_no_cursor(self.query)?;
+        let limit = self.max_in_memory_sort_records.saturating_add(1);
+        let records = match plan {
+            Plan::IndexScans(index_scans) => {
+                self.collect_records(self.build_index_scan(index_scans)?.take(limit))?
+            }
+            Plan::SeqScan(_) => self
+                .iterate(None)?
+                .take(limit)
+                .map(deserialize_record)
+                .collect::<Result<Vec<_>, _>>()?,
+            Plan::ReturnEmpty => vec![],
+            Plan::InMemorySort { .. } => unreachable!("in memory sorts are not nested"),
+        };
+        if records.len() > self.max_in_memory_sort_records {
+            Err(CacheError::InMemorySortLimitExceeded(
+                self.max_in_memory_sort_records,
+            ))
+        } else {
+            Ok(records)
+        }
+    }
+
+    fn paginate<T>(&self, iter: impl Iterator<Item = T>) -> impl Iterator<Item = T> {
+        iter.skip(self.query.skip)
             .take(self.query.limit.unwrap_or(usize::MAX))
-            .map(|(_, v)| bincode::deserialize(v).map_err(CacheError::map_deserialization_error))
-            .collect()
+    }
+
+    /// Returns the id of the record the `$after` cursor points to.
+    ///
+    /// Ids are never reused, so the cursor stays valid after its record is deleted.
+    fn get_after_id(&self) -> Result<Option<u64>, CacheError> {
+        let cursor = if let Some(cursor) = &self.query.after {
+            cursor
+        } else {
+            return Ok(None);
+        };
+        let key = decode_cursor(cursor)?;
+        match self.id_db.get(self.txn, &key) {
+            Ok(id) => Ok(Some(u64::from_be_bytes(id))),
+            Err(CacheError::QueryError(QueryError::GetValue(lmdb::Error::NotFound))) => {
+                Err(CacheError::QueryValidationError(
+                    QueryValidationError::InvalidCursor(cursor.clone()),
+                ))
+            }
+            Err(e) => Err(e),
+        }
     }
 
     fn build_index_scan(
@@ -93,6 +197,14 @@ impl<'a> LmdbQueryHandler<'a> {
             !index_scans.is_empty(),
             "Planner should not generate empty index scan"
         );
+        let after = self.get_after_id()?;
+        if after.is_some()
+            && index_scans
+                .iter()
+                .any(|index_scan| !is_sorted_by_id(index_scan))
+        {
+            return Err(CacheError::PlanError(PlanError::CursorNotSupported));
+        }
         let full_sacan = if index_scans.len() == 1 {
             // The fast path, without intersection calculation.
             Either::Left(self.query_with_secondary_index(&index_scans[0])?)
@@ -109,9 +221,9 @@ impl<'a> LmdbQueryHandler<'a> {
                 intersection(iterators, self.intersection_chunk_size).map(|id| id.to_be_bytes()),
             )
         };
+        // Ids come in ascending order, so the records after the cursor are a suffix.
         Ok(full_sacan
-            .skip(self.query.skip)
-            .take(self.query.limit.unwrap_or(usize::MAX)))
+            .skip_while(move |id| after.map_or(false, |after| u64::from_be_bytes(*id) <= after)))
     }
 
     fn query_with_secondary_index(
@@ -162,6 +274,43 @@ impl<'a> LmdbQueryHandler<'a> {
     }
 }
 
+fn deserialize_record(bytes: &[u8]) -> Result<Record, CacheError> {
+    bincode::deserialize(bytes).map_err(CacheError::map_deserialization_error)
+}
+
+/// The order of sorted records doesn't depend on ids, so they can't be paged with a cursor.
+fn check_no_cursor(query: &QueryExpression) -> Result<(), CacheError> {
+    if query.after.is_some() {
+        Err(CacheError::PlanError(PlanError::CursorNotSupported))
+    } else {
+        Ok(())
+    }
+}
+
+/// Orders records the way a sorted inverted index does, `null` being greater than any other value.
+fn compare_records(a: &Record, b: &Record, order_by: &[(usize, SortDirection)]) -> Ordering {
+    for (field_index, direction) in order_by {
+        let ordering = a.values[*field_index].cmp(&b.values[*field_index]);
+        let ordering = match direction {
+            SortDirection::Ascending => ordering,
+            SortDirection::Descending => ordering.reverse(),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Whether `index_scan` returns ids in ascending order, which is the case when it only matches
+/// keys equal to a single value.
+fn is_sorted_by_id(index_scan: &IndexScan) -> bool {
+    match &index_scan.kind {
+        IndexScanKind::SortedInverted { range_query, .. } => range_query.is_none(),
+        IndexScanKind::FullText { .. } => true,
+    }
+}
+
 #[derive(Debug)]
 struct RangeSpec {
     start: Option<KeyEndpoint>,
@@ -184,7 +333,7 @@ fn get_range_spec(
                 is_single_field_sorted_inverted,
             );
             // There're 3 cases:
-            // 1. Range query with operator.
+            // 1. Range query with operator, and optionally an upper bound.
             // 2. Range query without operator (only order by).
             // 3. No range query.
             Ok(if let Some(range_query) = range_query {
@@ -192,38 +341,65 @@ fn get_range_spec(
                     Some((operator, _)) => {
                         // Here we respond to case 1, examples are `a = 1 && b > 2` or `b < 2`.
                         let comparison_key = comparison_key.expect("here's at least a range query");
-                        let null_key = build_sorted_inverted_comparision_key(
-                            eq_filters,
-                            Some(&SortedInvertedRangeQuery {
-                                field_index: range_query.field_index,
-                                operator_and_value: Some((operator, Field::Null)),
-                                sort_direction: range_query.sort_direction,
-                            }),
-                            is_single_field_sorted_inverted,
-                        )
-                        .expect("we provided a range query");
-                        get_key_interval_from_range_query(
-                            comparison_key,
-                            null_key,
-                            operator,
-                            range_query.sort_direction,
-                        )
-                    }
-                    None => {
-                        // Here we respond to case 2, examples are `a = 1 && b asc` or `b desc`.
-                        if let Some(comparison_key) = comparison_key {
-                            // This is the case like `a = 1 && b asc`. The comparison key is only built from `a = 1`.
-                            // We use `a = 1 && b = null` as a sentinel, using the invariant that `null` is greater than anything.
+                        if let Some((upper_operator, upper_value)) = &range_query.upper_bound {
+                            // Examples are `a = 1 && b > 2 && b < 5` or `b >= 2 && b <= 5`.
+                            let upper_key = build_sorted_inverted_comparision_key(
+                                eq_filters,
+                                Some(&SortedInvertedRangeQuery {
+                                    field_index: range_query.field_index,
+                                    operator_and_value: Some((
+                                        *upper_operator,
+                                        upper_value.clone(),
+                                    )),
+                                    upper_bound: None,
+                                    secondary_sort_fields: vec![],
+                                    sort_direction: range_query.sort_direction,
+                                }),
+                                is_single_field_sorted_inverted,
+                            )
+                            .expect("we provided a range query");
+                            get_key_interval_from_bounds(
+                                (comparison_key, operator),
+                                (upper_key, *upper_operator),
+                                range_query.sort_direction,
+                            )
+                        } else {
                             let null_key = build_sorted_inverted_comparision_key(
                                 eq_filters,
                                 Some(&SortedInvertedRangeQuery {
                                     field_index: range_query.field_index,
-                                    operator_and_value: Some((Operator::LT, Field::Null)),
+                                    operator_and_value: Some((operator, Field::Null)),
+                                    upper_bound: None,
+                                    secondary_sort_fields: vec![],
                                     sort_direction: range_query.sort_direction,
                                 }),
                                 is_single_field_sorted_inverted,
                             )
                             .expect("we provided a range query");
+                            get_key_interval_from_range_query(
+                                comparison_key,
+                                null_key,
+                                operator,
+                                range_query.sort_direction,
+                            )
+                        }
+                    }
+                    None => {
+                        // Here we respond to case 2, examples are `a = 1 && b asc` or `b desc`.
+                        if let Some(comparison_key) = comparison_key {
+                            // This is the case like `a = 1 && b asc`. The comparison key is only built from `a = 1`.
+                            // We use `a = 1 && b = null` as a sentinel, using the invariant that `null` is greater than anything.
+                            // With secondary sort fields, like `a = 1 && b asc && c asc`, the sentinel is `a = 1 && b = null && c = null`.
+                            let nulls =
+                                vec![Field::Null; 1 + range_query.secondary_sort_fields.len()];
+                            let null_key = index::get_secondary_index(
+                                &eq_filters
+                                    .iter()
+                                    .map(|(_, value)| value)
+                                    .chain(&nulls)
+                                    .collect::<Vec<_>>(),
+                                is_single_field_sorted_inverted,
+                            );
                             match range_query.sort_direction {
                                 SortDirection::Ascending => RangeSpec {
                                     start: Some(KeyEndpoint::Excluding(comparison_key)),
@@ -299,6 +475,36 @@ fn build_sorted_inverted_comparision_key(
     }
 }
 
+/// Returns the keys between a lower bound (`GT` or `GTE`) and an upper bound (`LT` or `LTE`).
+fn get_key_interval_from_bounds(
+    (lower_key, lower_operator): (Vec<u8>, Operator),
+    (upper_key, upper_operator): (Vec<u8>, Operator),
+    sort_direction: SortDirection,
+) -> RangeSpec {
+    let lower = match lower_operator {
+        Operator::GT => KeyEndpoint::Excluding(lower_key),
+        Operator::GTE => KeyEndpoint::Including(lower_key),
+        other => panic!("operator {:?} is not a lower bound", other),
+    };
+    let upper = match upper_operator {
+        Operator::LT => KeyEndpoint::Excluding(upper_key),
+        Operator::LTE => KeyEndpoint::Including(upper_key),
+        other => panic!("operator {:?} is not an upper bound", other),
+    };
+    match sort_direction {
+        SortDirection::Ascending => RangeSpec {
+            start: Some(lower),
+            end: Some(upper),
+            direction: SortDirection::Ascending,
+        },
+        SortDirection::Descending => RangeSpec {
+            start: Some(upper),
+            end: Some(lower),
+            direction: SortDirection::Descending,
+        },
+    }
+}
+
 /// Here we use the invariant that `null` is greater than anything.
 fn get_key_interval_from_range_query(
     comparison_key: Vec<u8>,
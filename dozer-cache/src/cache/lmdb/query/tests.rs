@@ -1,4 +1,5 @@
 use crate::cache::{
+    cursor::encode_cursor,
     expression::{self, FilterExpression, QueryExpression},
     lmdb::{cache::LmdbCache, tests::utils, CacheOptions},
     test_utils, Cache,
@@ -123,8 +124,27 @@ fn query_secondary_vars() {
         &cache,
     );
 
-    // No compound index for a,c
-    test_query_err(json!({"$filter":{ "a": 1, "c": 521}}), &cache);
+    // No compound index for a,c, so both indexes are intersected
+    test_query_record(
+        json!({"$filter":{ "a": 1, "c": 521}}),
+        vec![(1, "yuri".to_string(), 521)],
+        &schema,
+        &cache,
+    );
+    test_query_record(
+        json!({"$filter":{ "b": "mega", "c": 521}}),
+        vec![(2, "mega".to_string(), 521)],
+        &schema,
+        &cache,
+    );
+
+    // Intersection loses the sort order, and there's no compound index for a,c, so it's sorted in memory
+    test_query_record(
+        json!({"$filter":{ "a": 1 }, "$order_by": { "c": "desc" }}),
+        vec![(1, "yuri".to_string(), 521)],
+        &schema,
+        &cache,
+    );
 
     test_query(
         json!({
@@ -181,6 +201,73 @@ fn query_secondary_vars() {
         &schema,
         &cache,
     );
+
+    // Bounded range tests
+    test_query(
+        json!({"$filter":{ "c": {"$gt": 521, "$lt": 527}}}),
+        3,
+        &cache,
+    );
+
+    test_query(
+        json!({"$filter":{ "c": {"$gte": 521, "$lte": 524}}}),
+        4,
+        &cache,
+    );
+
+    test_query(
+        json!({"$filter":{ "c": {"$gt": 528, "$lt": 600}}}),
+        0,
+        &cache,
+    );
+
+    test_query(
+        json!({"$filter":{ "c": {"$gt": 527, "$lt": 521}}}),
+        0,
+        &cache,
+    );
+
+    test_query(json!({"$filter":{ "a": {"$gte": 2, "$lt": 5}}}), 3, &cache);
+
+    test_query(
+        json!({"$filter":{ "$and": [{"a": {"$gt": 5}}, {"a": {"$lte": 7}}]}}),
+        2,
+        &cache,
+    );
+
+    test_query_record(
+        json!({
+            "$filter":{ "c": {"$gte": 523, "$lt": 527}},
+            "$order_by": { "c": "asc" }
+        }),
+        vec![
+            (3, "james".to_string(), 523),
+            (4, "james".to_string(), 524),
+            (5, "steff".to_string(), 526),
+        ],
+        &schema,
+        &cache,
+    );
+
+    test_query_record(
+        json!({
+            "$filter":{ "c": {"$gt": 523, "$lte": 527}},
+            "$order_by": { "c": "desc" }
+        }),
+        vec![
+            (6, "mega".to_string(), 527),
+            (5, "steff".to_string(), 526),
+            (4, "james".to_string(), 524),
+        ],
+        &schema,
+        &cache,
+    );
+
+    // Only one field can be queried by range
+    test_query_err(
+        json!({"$filter":{ "a": {"$gt": 1}, "c": {"$lt": 600}}}),
+        &cache,
+    );
 }
 
 #[test]
@@ -247,6 +334,345 @@ fn query_secondary_multi_indices() {
     );
 }
 
+#[test]
+fn query_full_text() {
+    let cache = LmdbCache::new(CacheOptions::default()).unwrap();
+    let (schema, seconary_indexes) = test_utils::schema_multi_indices();
+
+    cache
+        .insert_schema("sample", &schema, &seconary_indexes)
+        .unwrap();
+
+    for (id, text) in [
+        (1, "apple ball cake dance"),
+        (2, "ball cake dance egg"),
+        (3, "cake dance egg fish"),
+        (4, "dance egg fish glove"),
+        (5, "egg fish glove heart"),
+    ] {
+        cache
+            .insert(&Record {
+                schema_id: schema.identifier,
+                values: vec![Field::Int(id), Field::String(text.into())],
+                version: None,
+            })
+            .unwrap();
+    }
+
+    let query_ids = |query: Value| {
+        let query = serde_json::from_value::<QueryExpression>(query).unwrap();
+        let records = cache.query("sample", &query).unwrap();
+        assert_eq!(cache.count("sample", &query).unwrap(), records.len());
+        records
+            .into_iter()
+            .map(|record| record.values[0].clone())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        query_ids(json!({"$filter": {"text": {"$contains": "cake"}}})),
+        vec![Field::Int(1), Field::Int(2), Field::Int(3)]
+    );
+    // Every token must be present
+    assert_eq!(
+        query_ids(json!({"$filter": {"text": {"$contains": "egg fish"}}})),
+        vec![Field::Int(3), Field::Int(4), Field::Int(5)]
+    );
+    assert_eq!(
+        query_ids(json!({"$filter": {"text": {"$contains": "fish, apple"}}})),
+        vec![]
+    );
+    assert_eq!(
+        query_ids(json!({"$filter": {"text": {"$contains": "  "}}})),
+        vec![]
+    );
+
+    // `id` has no full text index
+    let query =
+        serde_json::from_value::<QueryExpression>(json!({"$filter": {"id": {"$contains": 1}}}))
+            .unwrap();
+    assert!(matches!(
+        cache.query("sample", &query).unwrap_err(),
+        crate::errors::CacheError::PlanError(crate::errors::PlanError::FullTextIndexNotFound(_))
+    ));
+}
+
+#[test]
+fn query_array_contains() {
+    let cache = LmdbCache::new(CacheOptions::default()).unwrap();
+    let (schema, seconary_indexes) = test_utils::schema_json_array();
+
+    cache
+        .insert_schema("sample", &schema, &seconary_indexes)
+        .unwrap();
+
+    for (id, tags) in [
+        (1, Field::Json(json!(["rust", "db"]))),
+        (2, Field::Json(json!(["db", 1]))),
+        (3, Field::Json(json!([]))),
+        (4, Field::Null),
+        (5, Field::Json(json!(["rust"]))),
+    ] {
+        cache
+            .insert(&Record {
+                schema_id: schema.identifier,
+                values: vec![Field::Int(id), tags],
+                version: None,
+            })
+            .unwrap();
+    }
+
+    let query_ids = |query: Value| {
+        let query = serde_json::from_value::<QueryExpression>(query).unwrap();
+        let records = cache.query("sample", &query).unwrap();
+        assert_eq!(cache.count("sample", &query).unwrap(), records.len());
+        records
+            .into_iter()
+            .map(|record| record.values[0].clone())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        query_ids(json!({"$filter": {"tags": {"$contains": "rust"}}})),
+        vec![Field::Int(1), Field::Int(5)]
+    );
+    assert_eq!(
+        query_ids(json!({"$filter": {"tags": {"$contains": "db"}}})),
+        vec![Field::Int(1), Field::Int(2)]
+    );
+    assert_eq!(
+        query_ids(json!({"$filter": {"tags": {"$contains": 1}}})),
+        vec![Field::Int(2)]
+    );
+    assert_eq!(
+        query_ids(json!({"$filter": {"tags": {"$contains": "go"}}})),
+        vec![]
+    );
+    assert_eq!(
+        query_ids(json!({"$filter": {"$and": [{"id": 2}, {"tags": {"$contains": "db"}}]}})),
+        vec![Field::Int(2)]
+    );
+
+    // Removing an element removes the record from the element's results.
+    cache
+        .update(
+            &Field::Int(1).encode(),
+            &Record {
+                schema_id: schema.identifier,
+                values: vec![Field::Int(1), Field::Json(json!(["db"]))],
+                version: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        query_ids(json!({"$filter": {"tags": {"$contains": "rust"}}})),
+        vec![Field::Int(5)]
+    );
+}
+
+#[test]
+fn query_with_cursor() {
+    let cache = LmdbCache::new(CacheOptions::default()).unwrap();
+    let (schema, seconary_indexes) = test_utils::schema_1();
+    cache
+        .insert_schema("sample", &schema, &seconary_indexes)
+        .unwrap();
+
+    let record = |a: i64| {
+        let b = if a % 2 == 0 { "even" } else { "odd" };
+        Record::new(
+            schema.identifier,
+            vec![Field::Int(a), Field::String(b.to_string()), Field::Int(a)],
+            None,
+        )
+    };
+    for a in 0..50 {
+        cache.insert(&record(a)).unwrap();
+    }
+
+    // Returns the `a` values of the page and the cursor to the next one.
+    let get_page = |filter: Option<Value>, after: Option<String>| {
+        let mut query = QueryExpression::new(None, vec![], Some(10), 0);
+        if let Some(filter) = filter {
+            query.filter = Some(serde_json::from_value(filter).unwrap());
+        }
+        query.after = after;
+        let records = cache.query("sample", &query).unwrap();
+        assert_eq!(cache.count("sample", &query).unwrap(), records.len());
+        let cursor = records
+            .last()
+            .map(|record| encode_cursor(&schema, record).unwrap());
+        let values = records
+            .into_iter()
+            .map(|record| record.values[0].clone())
+            .collect::<Vec<_>>();
+        (values, cursor)
+    };
+    let ints = |range: std::ops::Range<i64>| range.map(Field::Int).collect::<Vec<_>>();
+
+    let mut cursor = None;
+    for page in 0..5 {
+        let (values, next) = get_page(None, cursor);
+        assert_eq!(values, ints(page * 10..page * 10 + 10));
+        cursor = next;
+    }
+    assert_eq!(get_page(None, cursor), (vec![], None));
+
+    // Pages don't drift when records are deleted or inserted in between.
+    let (_, cursor) = get_page(None, None);
+    let cursor = cursor.unwrap();
+    cache.delete(&Field::Int(3).encode()).unwrap();
+    cache.delete(&Field::Int(9).encode()).unwrap();
+    cache.insert(&record(100)).unwrap();
+    assert_eq!(get_page(None, Some(cursor.clone())).0, ints(10..20));
+
+    // The cursor of a deleted record is still valid.
+    let (values, _) = get_page(None, encode_cursor(&schema, &record(9)));
+    assert_eq!(values, ints(10..20));
+
+    // Index scans.
+    let filter = json!({"b": "even"});
+    let mut cursor = None;
+    let mut values = vec![];
+    loop {
+        let (page, next) = get_page(Some(filter.clone()), cursor);
+        if page.is_empty() {
+            break;
+        }
+        values.extend(page);
+        cursor = next;
+    }
+    // Ids follow insertion order, so the record inserted last comes last.
+    let expected = (0..50).step_by(2).chain([100]).map(Field::Int);
+    assert_eq!(values, expected.collect::<Vec<_>>());
+
+    // Cursors need results in insertion order.
+    let query = serde_json::from_value::<QueryExpression>(
+        json!({"$order_by": {"c": "desc"}, "$after": cursor}),
+    )
+    .unwrap();
+    assert!(matches!(
+        cache.query("sample", &query).unwrap_err(),
+        crate::errors::CacheError::PlanError(crate::errors::PlanError::CursorNotSupported)
+    ));
+
+    let query = serde_json::from_value::<QueryExpression>(json!({ "$after": "00" })).unwrap();
+    assert!(matches!(
+        cache.query("sample", &query).unwrap_err(),
+        crate::errors::CacheError::QueryValidationError(
+            crate::errors::QueryValidationError::InvalidCursor(_)
+        )
+    ));
+}
+
+#[test]
+fn query_with_multi_field_order_by() {
+    let (mut schema, seconary_indexes) = test_utils::schema_1();
+    // `a` is not unique here, so the whole record is the primary key.
+    schema.primary_index = vec![0, 1, 2];
+
+    let items = vec![
+        (1, Some("yuri".to_string()), Some(521)),
+        (2, Some("mega".to_string()), Some(521)),
+        (1, Some("james".to_string()), Some(523)),
+        (3, None, Some(524)),
+        (2, Some("ava".to_string()), None),
+    ];
+    let record = |index: usize| {
+        let (a, b, c) = items[index].clone();
+        (a, b.unwrap_or_default(), c.unwrap_or_default())
+    };
+    let create_cache = |max_in_memory_sort_records| {
+        let mut options = CacheOptions::default();
+        options.common.max_in_memory_sort_records = max_in_memory_sort_records;
+        let cache = LmdbCache::new(options).unwrap();
+        cache
+            .insert_schema("sample", &schema, &seconary_indexes)
+            .unwrap();
+        for val in items.clone() {
+            utils::insert_rec_1(&cache, &schema, val);
+        }
+        cache
+    };
+    let cache = create_cache(10);
+
+    let query_records = |query: Value| {
+        let query = serde_json::from_value::<QueryExpression>(query).unwrap();
+        let records = cache.query("sample", &query).unwrap();
+        assert_eq!(cache.count("sample", &query).unwrap(), records.len());
+        records
+            .into_iter()
+            .map(|record| {
+                let field = |index: usize| match &record.values[index] {
+                    Field::Int(value) => *value,
+                    Field::Null => 0,
+                    other => panic!("unexpected field {:?}", other),
+                };
+                let b = match &record.values[1] {
+                    Field::String(value) => value.clone(),
+                    Field::Null => String::new(),
+                    other => panic!("unexpected field {:?}", other),
+                };
+                (field(0), b, field(2))
+            })
+            .collect::<Vec<_>>()
+    };
+    let expected = |indexes: &[usize]| indexes.iter().map(|i| record(*i)).collect::<Vec<_>>();
+
+    // Answered by the composite index on `a` and `b`, `null` sorts last.
+    assert_eq!(
+        query_records(json!({"$order_by": [
+            {"field": "a", "direction": "asc"},
+            {"field": "b", "direction": "asc"}
+        ]})),
+        expected(&[2, 0, 4, 1, 3])
+    );
+    assert_eq!(
+        query_records(json!({"$order_by": [
+            {"field": "a", "direction": "desc"},
+            {"field": "b", "direction": "desc"}
+        ]})),
+        expected(&[3, 1, 4, 0, 2])
+    );
+
+    // Sorted in memory.
+    assert_eq!(
+        query_records(json!({"$order_by": [
+            {"field": "a", "direction": "asc"},
+            {"field": "b", "direction": "desc"}
+        ]})),
+        expected(&[0, 2, 1, 4, 3])
+    );
+    assert_eq!(
+        query_records(json!({"$order_by": [
+            {"field": "c", "direction": "asc"},
+            {"field": "a", "direction": "desc"}
+        ]})),
+        expected(&[1, 0, 2, 3, 4])
+    );
+    assert_eq!(
+        query_records(json!({
+            "$filter": {"c": 521},
+            "$order_by": [{"field": "b", "direction": "asc"}, {"field": "a", "direction": "asc"}],
+            "$skip": 1,
+            "$limit": 1
+        })),
+        expected(&[0])
+    );
+
+    // Too many records to sort in memory.
+    let cache = create_cache(4);
+    let query = serde_json::from_value::<QueryExpression>(json!({"$order_by": [
+        {"field": "a", "direction": "asc"},
+        {"field": "b", "direction": "desc"}
+    ]}))
+    .unwrap();
+    assert!(matches!(
+        cache.query("sample", &query).unwrap_err(),
+        crate::errors::CacheError::InMemorySortLimitExceeded(4)
+    ));
+}
+
 fn test_query_err(query: Value, cache: &LmdbCache) {
     let query = serde_json::from_value::<QueryExpression>(query).unwrap();
     let count_result = cache.count("sample", &query);
@@ -4,9 +4,10 @@ use crate::cache::{
     lmdb::CacheOptions,
     test_utils, Cache,
 };
+use crate::errors::CacheError;
 use dozer_types::{
     serde_json::Value,
-    types::{Field, IndexDefinition, Record, Schema},
+    types::{Field, FieldDefinition, FieldType, IndexDefinition, Record, Schema, SchemaIdentifier},
 };
 
 use super::super::cache::LmdbCache;
@@ -91,6 +92,103 @@ fn insert_and_update_record() {
     cache.update(&key, &foo).unwrap();
 }
 
+#[test]
+fn count_after_inserts_and_deletes() {
+    let (cache, schema, secondary_indexes) = _setup();
+    let (other_schema, other_secondary_indexes) = test_utils::schema_1();
+    cache
+        .insert_schema("docs", &schema, &secondary_indexes)
+        .unwrap();
+    cache
+        .insert_schema("other", &other_schema, &other_secondary_indexes)
+        .unwrap();
+    let all = QueryExpression::new(None, vec![], None, 0);
+
+    let records: Vec<Record> = ["foo", "bar", "baz"]
+        .into_iter()
+        .map(|val| Record::new(schema.identifier, vec![Field::String(val.into())], None))
+        .collect();
+    for (count, record) in records.iter().enumerate() {
+        cache.insert(record).unwrap();
+        assert_eq!(cache.count("docs", &all).unwrap(), count + 1);
+    }
+    let other = Record::new(
+        other_schema.identifier,
+        vec![Field::Int(1), Field::Null, Field::Null],
+        None,
+    );
+    cache.insert(&other).unwrap();
+    assert_eq!(cache.count("docs", &all).unwrap(), 3);
+    assert_eq!(cache.count("other", &all).unwrap(), 1);
+
+    // Updating a record doesn't change the count.
+    let key = index::get_primary_key(&schema.primary_index, &records[0].values);
+    cache.update(&key, &records[0]).unwrap();
+    assert_eq!(cache.count("docs", &all).unwrap(), 3);
+
+    // Pagination still applies.
+    let page = QueryExpression::new(None, vec![], Some(1), 0);
+    assert_eq!(cache.count("docs", &page).unwrap(), 1);
+    let skipped = QueryExpression::new(None, vec![], None, 2);
+    assert_eq!(cache.count("docs", &skipped).unwrap(), 1);
+
+    for (count, record) in records.iter().enumerate() {
+        let key = index::get_primary_key(&schema.primary_index, &record.values);
+        cache.delete(&key).unwrap();
+        assert_eq!(
+            cache.count("docs", &all).unwrap(),
+            records.len() - count - 1
+        );
+    }
+    assert_eq!(cache.count("other", &all).unwrap(), 1);
+
+    // Deleted records can be inserted again.
+    cache.insert(&records[1]).unwrap();
+    assert_eq!(cache.count("docs", &all).unwrap(), 1);
+    assert_eq!(cache.query("docs", &all).unwrap(), vec![records[1].clone()]);
+}
+
+#[test]
+fn reject_stale_schema_version() {
+    let (cache, schema, secondary_indexes) = _setup();
+    cache
+        .insert_schema("docs", &schema, &secondary_indexes)
+        .unwrap();
+    let record = Record::new(schema.identifier, vec![Field::String("foo".into())], None);
+    cache.insert(&record).unwrap();
+
+    // A column is added to the schema
+    let mut new_schema = schema.clone();
+    new_schema.fields.push(FieldDefinition::new(
+        "bar".to_string(),
+        FieldType::String,
+        true,
+    ));
+    let schema_id = schema.identifier.unwrap();
+    new_schema.identifier = Some(SchemaIdentifier {
+        id: schema_id.id,
+        version: schema_id.version + 1,
+    });
+    cache
+        .insert_schema("new_docs", &new_schema, &secondary_indexes)
+        .unwrap();
+
+    // Records of the previous version can't be read with the new one
+    let all = QueryExpression::new(None, vec![], None, 0);
+    assert!(matches!(
+        cache.query("new_docs", &all),
+        Err(CacheError::StaleSchemaVersion(id, 1, 2)) if id == schema_id.id
+    ));
+
+    // Nor written
+    let mut txn = cache.begin_rw_txn().unwrap();
+    let stale = Record::new(schema.identifier, vec![Field::String("baz".into())], None);
+    assert!(matches!(
+        cache.insert_with_txn(&mut txn, &stale, &new_schema, &secondary_indexes),
+        Err(CacheError::StaleSchemaVersion(..))
+    ));
+}
+
 fn insert_and_query_record_impl(
     cache: LmdbCache,
     schema: Schema,
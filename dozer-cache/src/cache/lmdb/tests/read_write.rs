@@ -17,6 +17,7 @@ fn read_and_write() {
             max_db_size: 100,
             path: Some(path.clone()),
             intersection_chunk_size: 1,
+            ..Default::default()
         },
         kind: CacheOptionsKind::Write(CacheWriteOptions {
             max_size: 1024 * 1024,
@@ -6,10 +6,30 @@ pub use self::lmdb::{
 };
 use crate::errors::CacheError;
 use dozer_types::types::{IndexDefinition, Record, Schema, SchemaIdentifier};
+pub mod cursor;
 pub mod expression;
 pub mod index;
 mod plan;
 pub mod test_utils;
+
+/// Checks that a record stamped with another version of `schema` isn't read or written with it.
+/// Such a record was written before the fields of the schema changed, and its values don't match
+/// them anymore. Records stamped with another schema or not stamped at all aren't checked.
+pub fn check_schema_version(record: &Record, schema: &Schema) -> Result<(), CacheError> {
+    match (record.schema_id, schema.identifier) {
+        (Some(record_id), Some(schema_id))
+            if record_id.id == schema_id.id && record_id.version != schema_id.version =>
+        {
+            Err(CacheError::StaleSchemaVersion(
+                schema_id.id,
+                record_id.version,
+                schema_id.version,
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
 pub trait Cache {
     // Schema Operations
     fn insert_schema(
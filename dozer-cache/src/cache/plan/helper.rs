@@ -22,10 +22,14 @@ pub enum RangeQueryKind {
     Filter {
         operator: Operator,
         value: Field,
+        /// Set if the range is bounded on both sides, `operator` and `value` are then the lower bound.
+        upper_bound: Option<(Operator, Field)>,
         sort_direction: Option<SortDirection>,
     },
     OrderBy {
         sort_direction: SortDirection,
+        /// Fields to sort by after `field_index`, in the same direction.
+        secondary_sort_fields: Vec<usize>,
     },
 }
 
@@ -53,16 +57,64 @@ pub fn get_all_indexes(
         );
         Either::Left(std::iter::once(full_text_scans))
     } else {
+        // Prefer a single sorted inverted index covering all the filters. Otherwise, fall back to intersecting one
+        // index per filter, which returns ids in ascending order, so it's not an option if the range query sorts.
+        let can_intersect = eq_filters.len() + usize::from(range_query.is_some()) > 1
+            && !matches!(
+                range_query,
+                Some(RangeQuery {
+                    kind: RangeQueryKind::OrderBy { .. }
+                        | RangeQueryKind::Filter {
+                            sort_direction: Some(_),
+                            ..
+                        },
+                    ..
+                })
+            );
+        let intersected_scans = if can_intersect {
+            Either::Left(get_intersected_sorted_inverted_scans(
+                eq_filters.clone(),
+                range_query.clone(),
+            ))
+        } else {
+            Either::Right(std::iter::empty())
+        };
         Either::Right(
-            get_sorted_inverted_scans(eq_filters, range_query).map(move |scan| {
-                let mut scans = full_text_scans.clone();
-                scans.push(scan);
-                scans
-            }),
+            get_sorted_inverted_scans(eq_filters, range_query)
+                .map(|scan| vec![scan])
+                .chain(intersected_scans)
+                .map(move |sorted_inverted_scans| {
+                    let mut scans = full_text_scans.clone();
+                    scans.extend(sorted_inverted_scans);
+                    scans
+                }),
         )
     }
 }
 
+fn get_intersected_sorted_inverted_scans(
+    eq_filters: Vec<(usize, Field)>,
+    range_query: Option<RangeQuery>,
+) -> impl Iterator<Item = Vec<IndexScanKind>> {
+    let eq_scans = eq_filters
+        .into_iter()
+        .map(|eq_filter| IndexScanKind::SortedInverted {
+            eq_filters: vec![eq_filter],
+            range_query: None,
+        })
+        .collect::<Vec<_>>();
+    get_option_sorted_inverted_range_queries(range_query).map(move |range_query| {
+        let mut scans = eq_scans.clone();
+        if let Some(range_query) = range_query {
+            scans.push(IndexScanKind::SortedInverted {
+                eq_filters: vec![],
+                range_query: Some(range_query),
+            });
+        }
+        scans
+    })
+}
+
 fn get_sorted_inverted_scans(
     eq_filters: Vec<(usize, Field)>,
     range_query: Option<RangeQuery>,
@@ -121,23 +173,29 @@ fn get_sorted_inverted_range_queries(
         RangeQueryKind::Filter {
             operator,
             value,
+            upper_bound,
             sort_direction,
         } => Either::Left(
             get_sort_directions(sort_direction).map(move |sort_direction| {
                 SortedInvertedRangeQuery {
                     field_index: range_query.field_index,
                     operator_and_value: Some((operator, value.clone())),
+                    upper_bound: upper_bound.clone(),
+                    secondary_sort_fields: vec![],
                     sort_direction,
                 }
             }),
         ),
-        RangeQueryKind::OrderBy { sort_direction } => {
-            Either::Right(std::iter::once(SortedInvertedRangeQuery {
-                field_index: range_query.field_index,
-                operator_and_value: None,
-                sort_direction,
-            }))
-        }
+        RangeQueryKind::OrderBy {
+            sort_direction,
+            secondary_sort_fields,
+        } => Either::Right(std::iter::once(SortedInvertedRangeQuery {
+            field_index: range_query.field_index,
+            operator_and_value: None,
+            upper_bound: None,
+            secondary_sort_fields,
+            sort_direction,
+        })),
     }
 }
 
@@ -190,12 +248,47 @@ fn test_get_all_indexes() {
         }]],
     );
 
+    // Multiple `Eq`, intersected if no composite index exists.
+    let filter_a = IndexFilter::new(0, Operator::EQ, Field::Int(1));
+    let filter_b = IndexFilter::new(1, Operator::EQ, Field::Int(2));
+    check(
+        vec![(filter_a.clone(), None), (filter_b.clone(), None)],
+        None,
+        vec![
+            vec![IndexScanKind::SortedInverted {
+                eq_filters: vec![
+                    (filter_a.field_index, filter_a.val.clone()),
+                    (filter_b.field_index, filter_b.val.clone()),
+                ],
+                range_query: None,
+            }],
+            vec![IndexScanKind::SortedInverted {
+                eq_filters: vec![
+                    (filter_b.field_index, filter_b.val.clone()),
+                    (filter_a.field_index, filter_a.val.clone()),
+                ],
+                range_query: None,
+            }],
+            vec![
+                IndexScanKind::SortedInverted {
+                    eq_filters: vec![(filter_a.field_index, filter_a.val)],
+                    range_query: None,
+                },
+                IndexScanKind::SortedInverted {
+                    eq_filters: vec![(filter_b.field_index, filter_b.val)],
+                    range_query: None,
+                },
+            ],
+        ],
+    );
+
     // Only order by.
     let direction = SortDirection::Ascending;
     let range_query = RangeQuery::new(
         0,
         RangeQueryKind::OrderBy {
             sort_direction: direction,
+            secondary_sort_fields: vec![],
         },
     );
     check(
@@ -206,6 +299,8 @@ fn test_get_all_indexes() {
             range_query: Some(SortedInvertedRangeQuery {
                 field_index: range_query.field_index,
                 operator_and_value: None,
+                upper_bound: None,
+                secondary_sort_fields: vec![],
                 sort_direction: direction,
             }),
         }]],
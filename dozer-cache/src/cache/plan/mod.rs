@@ -13,6 +13,11 @@ pub enum Plan {
     IndexScans(Vec<IndexScan>),
     SeqScan(SeqScan),
     ReturnEmpty,
+    /// No index returns the records in the requested order, so the records of `plan` are sorted in memory.
+    InMemorySort {
+        plan: Box<Plan>,
+        order_by: Vec<(usize, SortDirection)>,
+    },
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct IndexScan {
@@ -37,6 +42,11 @@ pub struct SortedInvertedRangeQuery {
     pub field_index: usize,
     pub sort_direction: SortDirection,
     pub operator_and_value: Option<(Operator, Field)>,
+    /// Set if the range is bounded on both sides, `operator_and_value` is then the lower bound.
+    pub upper_bound: Option<(Operator, Field)>,
+    /// Fields ordering the records with the same value of `field_index`, in `sort_direction` too.
+    /// Only set when there's no `operator_and_value`.
+    pub secondary_sort_fields: Vec<usize>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
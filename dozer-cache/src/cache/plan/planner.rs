@@ -1,4 +1,5 @@
 use crate::cache::expression::{FilterExpression, Operator, QueryExpression, SortDirection};
+use crate::cache::index;
 use crate::errors::PlanError;
 use dozer_types::json_value_to_field;
 use dozer_types::types::{Field, FieldDefinition, Schema};
@@ -26,22 +27,43 @@ impl<'a> QueryPlanner<'a> {
         }
     }
 
+    /// Plans the query as a set of index scans whose results are intersected.
+    ///
+    /// Full text filters always scan their own index. Sorted inverted filters use one index covering all of them
+    /// if there is one, or one index per filter. A filter without a matching index fails with `MatchingIndexNotFound`.
+    ///
+    /// `$order_by` is answered by a sorted inverted index on the filtered and sorted fields if there is one, which is
+    /// scanned in reverse for descending sorts. Otherwise the matching records are sorted in memory.
     pub fn plan(&self) -> Result<Plan, PlanError> {
         // Collect all the filters.
-        // TODO: Handle filters like And([a > 0, a < 10]).
         let mut filters = vec![];
         if let Some(expression) = &self.query.filter {
             collect_filters(self.schema, expression, &mut filters)?;
         }
 
+        // Full text filters can only be answered by a full text index on their field.
+        for (filter, _) in &filters {
+            if filter.op.supported_by_full_text()
+                && !self
+                    .secondary_indexes
+                    .contains(&IndexDefinition::FullText(filter.field_index))
+            {
+                return Err(PlanError::FullTextIndexNotFound(
+                    self.schema.fields[filter.field_index].name.clone(),
+                ));
+            }
+        }
+
         // Filter the sort options.
         // TODO: Handle duplicate fields.
+        let mut all_order_by = vec![];
         let mut order_by = vec![];
         for order in &self.query.order_by.0 {
             // Find the field index.
             let (field_index, _, _) =
                 get_field_index_and_type(&order.field_name, &self.schema.fields)
                     .ok_or(PlanError::FieldNotFound(order.field_name.clone()))?;
+            all_order_by.push((field_index, order.direction));
             // If the field is already in a filter supported by `SortedInverted`, mark the corresponding filter.
             if seen_in_sorted_inverted_filter(field_index, order.direction, &mut filters)? {
                 continue;
@@ -65,8 +87,37 @@ impl<'a> QueryPlanner<'a> {
             return Ok(Plan::ReturnEmpty);
         }
 
-        // Find the range query, can be a range filter or a sort option.
-        let range_query = find_range_query(&mut filters, &order_by)?;
+        match self.plan_index_scans(filters.clone(), &order_by) {
+            Err(PlanError::RangeQueryLimit | PlanError::MatchingIndexNotFound)
+                if !all_order_by.is_empty() =>
+            {
+                // Plan the filters alone and sort their result.
+                for filter in &mut filters {
+                    filter.1 = None;
+                }
+                let plan = if filters.is_empty() {
+                    Plan::SeqScan(SeqScan {
+                        direction: SortDirection::Ascending,
+                    })
+                } else {
+                    self.plan_index_scans(filters, &[])?
+                };
+                Ok(Plan::InMemorySort {
+                    plan: Box::new(plan),
+                    order_by: all_order_by,
+                })
+            }
+            result => result,
+        }
+    }
+
+    fn plan_index_scans(
+        &self,
+        mut filters: Vec<(IndexFilter, Option<SortDirection>)>,
+        order_by: &[(usize, SortDirection)],
+    ) -> Result<Plan, PlanError> {
+        // Find the range query, can be a range filter or the sort options.
+        let range_query = find_range_query(&mut filters, order_by)?;
 
         // Generate some index scans that can answer this query, lazily.
         let all_index_scans = helper::get_all_indexes(filters, range_query);
@@ -104,8 +155,41 @@ fn collect_filters(
             let (field_index, field_type, nullable) =
                 get_field_index_and_type(field_name, &schema.fields)
                     .ok_or(PlanError::FieldNotFound(field_name.clone()))?;
+            if *operator == Operator::Contains
+                && matches!(field_type, FieldType::Json | FieldType::Bson)
+            {
+                // Arrays are indexed by each of their elements, so the element is looked up in a sorted inverted index.
+                filters.push((
+                    IndexFilter::new(field_index, Operator::EQ, Field::Json(value.clone())),
+                    None,
+                ));
+                return Ok(());
+            }
             let field = json_value_to_field(value.clone(), field_type, nullable)?;
-            filters.push((IndexFilter::new(field_index, *operator, field), None));
+            match (operator, &field) {
+                // Records must contain every token of the query, each token is looked up in the full text index.
+                (Operator::Contains | Operator::MatchesAll, Field::String(string)) => {
+                    let mut tokens = index::get_full_text_tokens(string).peekable();
+                    if tokens.peek().is_none() {
+                        // Nothing is indexed for a string without tokens, so it matches no record.
+                        filters.push((
+                            IndexFilter::new(field_index, Operator::Contains, field.clone()),
+                            None,
+                        ));
+                    }
+                    for token in tokens {
+                        filters.push((
+                            IndexFilter::new(
+                                field_index,
+                                Operator::Contains,
+                                Field::String(token.to_string()),
+                            ),
+                            None,
+                        ));
+                    }
+                }
+                _ => filters.push((IndexFilter::new(field_index, *operator, field), None)),
+            }
         }
         FilterExpression::And(expressions) => {
             for expression in expressions {
@@ -141,37 +225,38 @@ fn seen_in_sorted_inverted_filter(
     Ok(false)
 }
 
+/// Finds the only range query, which is either the range filters or the sort options.
+///
+/// There can be two range filters if they are a lower bound and an upper bound of the same field, like
+/// `a > 0 && a < 10`. There can be several sort options if they all have the same direction, they are answered by
+/// a composite index on the sorted fields.
 fn find_range_query(
     filters: &mut Vec<(IndexFilter, Option<SortDirection>)>,
     order_by: &[(usize, SortDirection)],
 ) -> Result<Option<RangeQuery>, PlanError> {
-    let mut num_range_ops = 0;
-    let mut range_filter_index = None;
-    for (i, filter) in filters.iter().enumerate() {
-        if filter.0.op.is_range_operator() {
-            num_range_ops += 1;
-            range_filter_index = Some(i);
-        }
-    }
-    num_range_ops += order_by.len();
-    if num_range_ops > 1 {
+    let (range_filters, other_filters): (Vec<_>, Vec<_>) = filters
+        .drain(..)
+        .partition(|filter| filter.0.op.is_range_operator());
+    *filters = other_filters;
+
+    if !range_filters.is_empty() && !order_by.is_empty() {
         return Err(PlanError::RangeQueryLimit);
     }
-    Ok(if let Some(range_filter_index) = range_filter_index {
-        let filter = filters.remove(range_filter_index);
-        Some(RangeQuery::new(
-            filter.0.field_index,
-            RangeQueryKind::Filter {
-                operator: filter.0.op,
-                value: filter.0.val,
-                sort_direction: filter.1,
-            },
-        ))
-    } else if let Some((field_index, sort_direction)) = order_by.first() {
+    Ok(if !range_filters.is_empty() {
+        Some(get_range_query_from_filters(range_filters)?)
+    } else if let Some(((field_index, sort_direction), rest)) = order_by.split_first() {
+        if rest
+            .iter()
+            .any(|(_, direction)| direction != sort_direction)
+        {
+            // A scan of an index returns all fields in the same direction.
+            return Err(PlanError::RangeQueryLimit);
+        }
         Some(RangeQuery::new(
             *field_index,
             RangeQueryKind::OrderBy {
                 sort_direction: *sort_direction,
+                secondary_sort_fields: rest.iter().map(|(field_index, _)| *field_index).collect(),
             },
         ))
     } else {
@@ -179,6 +264,56 @@ fn find_range_query(
     })
 }
 
+fn get_range_query_from_filters(
+    mut range_filters: Vec<(IndexFilter, Option<SortDirection>)>,
+) -> Result<RangeQuery, PlanError> {
+    let (lower, upper) = match range_filters.len() {
+        1 => {
+            let filter = range_filters.remove(0);
+            return Ok(RangeQuery::new(
+                filter.0.field_index,
+                RangeQueryKind::Filter {
+                    operator: filter.0.op,
+                    value: filter.0.val,
+                    upper_bound: None,
+                    sort_direction: filter.1,
+                },
+            ));
+        }
+        2 => {
+            let second = range_filters.remove(1);
+            let first = range_filters.remove(0);
+            if is_lower_bound(first.0.op) {
+                (first, second)
+            } else {
+                (second, first)
+            }
+        }
+        _ => return Err(PlanError::RangeQueryLimit),
+    };
+
+    if lower.0.field_index != upper.0.field_index
+        || !is_lower_bound(lower.0.op)
+        || is_lower_bound(upper.0.op)
+    {
+        return Err(PlanError::RangeQueryLimit);
+    }
+    Ok(RangeQuery::new(
+        lower.0.field_index,
+        RangeQueryKind::Filter {
+            operator: lower.0.op,
+            value: lower.0.val,
+            upper_bound: Some((upper.0.op, upper.0.val)),
+            // Only the first filter of a field is marked with the sort direction.
+            sort_direction: lower.1.or(upper.1),
+        },
+    ))
+}
+
+fn is_lower_bound(operator: Operator) -> bool {
+    matches!(operator, Operator::GT | Operator::GTE)
+}
+
 impl IndexScanKind {
     fn is_supported_by_index(&self, index: &IndexDefinition) -> bool {
         match (self, index) {
@@ -200,13 +335,9 @@ impl IndexScanKind {
                     return false;
                 }
                 if let Some(range_query) = range_query {
-                    if fields.len() != eq_filters.len() + 1 {
-                        return false;
-                    }
-                    let last_field = fields
-                        .last()
-                        .expect("We've checked `fields.len()` is at least 1");
-                    range_query.field_index == *last_field
+                    let range_fields = std::iter::once(&range_query.field_index)
+                        .chain(&range_query.secondary_sort_fields);
+                    fields[eq_filters.len()..].iter().eq(range_fields)
                 } else {
                     fields.len() == eq_filters.len()
                 }
@@ -272,6 +403,8 @@ mod tests {
                             field_index: index,
                             sort_direction: SortDirection::Ascending,
                             operator_and_value: None,
+                            upper_bound: None,
+                            secondary_sort_fields: vec![],
                         })
                     }
                     .is_supported_by_index(&IndexDefinition::SortedInverted(index)),
@@ -1,10 +1,11 @@
 use super::{Plan, QueryPlanner};
 use crate::cache::{
     expression::{self, FilterExpression, Operator, QueryExpression, SortDirection, SortOption},
-    plan::{IndexScanKind, SortedInvertedRangeQuery},
+    plan::{IndexScanKind, SeqScan, SortedInvertedRangeQuery},
     test_utils,
 };
 
+use crate::errors::PlanError;
 use dozer_types::{serde_json::Value, types::Field};
 
 #[test]
@@ -105,6 +106,8 @@ fn test_generate_plan_range_query_and_order_by() {
                         field_index: 2,
                         sort_direction: SortDirection::Descending,
                         operator_and_value: Some((expression::Operator::GT, 1.into())),
+                        upper_bound: None,
+                        secondary_sort_fields: vec![],
                     })
                 );
             }
@@ -132,3 +135,185 @@ fn test_generate_plan_empty() {
     let planner = QueryPlanner::new(&schema, &secondary_indexes, &query);
     assert!(matches!(planner.plan().unwrap(), Plan::ReturnEmpty));
 }
+
+#[test]
+fn test_generate_plan_intersection() {
+    let (schema, secondary_indexes) = test_utils::schema_1();
+
+    // There's no composite index on `a` and `c`.
+    let filter = FilterExpression::And(vec![
+        FilterExpression::Simple("a".to_string(), expression::Operator::EQ, Value::from(1)),
+        FilterExpression::Simple("c".to_string(), expression::Operator::EQ, Value::from(2)),
+    ]);
+    let query = QueryExpression::new(Some(filter), vec![], Some(10), 0);
+    let planner = QueryPlanner::new(&schema, &secondary_indexes, &query);
+    if let Plan::IndexScans(index_scans) = planner.plan().unwrap() {
+        assert_eq!(
+            index_scans
+                .iter()
+                .map(|index_scan| index_scan.index_id)
+                .collect::<Vec<_>>(),
+            vec![0, 2]
+        );
+        assert_eq!(
+            index_scans[1].kind,
+            IndexScanKind::SortedInverted {
+                eq_filters: vec![(2, Field::Int(2))],
+                range_query: None
+            }
+        );
+    } else {
+        panic!("IndexScan expected")
+    }
+
+    // Sorting can't be answered by an intersection, so the filter result is sorted in memory.
+    let filter =
+        FilterExpression::Simple("a".to_string(), expression::Operator::EQ, Value::from(1));
+    let query = QueryExpression::new(
+        Some(filter),
+        vec![SortOption {
+            field_name: "c".into(),
+            direction: SortDirection::Descending,
+        }],
+        Some(10),
+        0,
+    );
+    let planner = QueryPlanner::new(&schema, &secondary_indexes, &query);
+    if let Plan::InMemorySort { plan, order_by } = planner.plan().unwrap() {
+        assert!(matches!(*plan, Plan::IndexScans(_)));
+        assert_eq!(order_by, vec![(2, SortDirection::Descending)]);
+    } else {
+        panic!("InMemorySort expected")
+    }
+}
+
+#[test]
+fn test_generate_plan_multi_field_order_by() {
+    let (schema, secondary_indexes) = test_utils::schema_1();
+    let plan = |order_by: &[(&str, SortDirection)]| {
+        let order_by = order_by
+            .iter()
+            .map(|(field_name, direction)| SortOption::new(field_name.to_string(), *direction))
+            .collect();
+        let query = QueryExpression::new(None, order_by, Some(10), 0);
+        QueryPlanner::new(&schema, &secondary_indexes, &query)
+            .plan()
+            .unwrap()
+    };
+
+    // The composite index on `a` and `b` is scanned in both directions.
+    for direction in [SortDirection::Ascending, SortDirection::Descending] {
+        if let Plan::IndexScans(index_scans) = plan(&[("a", direction), ("b", direction)]) {
+            assert_eq!(index_scans.len(), 1);
+            assert_eq!(index_scans[0].index_id, 3);
+            assert_eq!(
+                index_scans[0].kind,
+                IndexScanKind::SortedInverted {
+                    eq_filters: vec![],
+                    range_query: Some(SortedInvertedRangeQuery {
+                        field_index: 0,
+                        sort_direction: direction,
+                        operator_and_value: None,
+                        upper_bound: None,
+                        secondary_sort_fields: vec![1],
+                    })
+                }
+            );
+        } else {
+            panic!("IndexScan expected")
+        }
+    }
+
+    // Mixed directions, or fields without a composite index, are sorted in memory.
+    let order_by = [
+        ("a", SortDirection::Ascending),
+        ("b", SortDirection::Descending),
+    ];
+    assert_eq!(
+        plan(&order_by),
+        Plan::InMemorySort {
+            plan: Box::new(Plan::SeqScan(SeqScan {
+                direction: SortDirection::Ascending
+            })),
+            order_by: vec![
+                (0, SortDirection::Ascending),
+                (1, SortDirection::Descending)
+            ],
+        }
+    );
+    assert!(matches!(
+        plan(&[
+            ("b", SortDirection::Ascending),
+            ("a", SortDirection::Ascending)
+        ]),
+        Plan::InMemorySort { .. }
+    ));
+}
+
+#[test]
+fn test_generate_plan_bounded_range_query() {
+    let (schema, secondary_indexes) = test_utils::schema_1();
+
+    let filter = FilterExpression::And(vec![
+        FilterExpression::Simple("c".into(), Operator::LT, 200.into()),
+        FilterExpression::Simple("c".into(), Operator::GT, 100.into()),
+    ]);
+    let query = QueryExpression::new(Some(filter), vec![], Some(10), 0);
+    let planner = QueryPlanner::new(&schema, &secondary_indexes, &query);
+    if let Plan::IndexScans(index_scans) = planner.plan().unwrap() {
+        assert_eq!(index_scans.len(), 1);
+        assert_eq!(index_scans[0].index_id, 2);
+        assert_eq!(
+            index_scans[0].kind,
+            IndexScanKind::SortedInverted {
+                eq_filters: vec![],
+                range_query: Some(SortedInvertedRangeQuery {
+                    field_index: 2,
+                    sort_direction: SortDirection::Ascending,
+                    operator_and_value: Some((Operator::GT, 100.into())),
+                    upper_bound: Some((Operator::LT, 200.into())),
+                    secondary_sort_fields: vec![],
+                })
+            }
+        );
+    } else {
+        panic!("IndexScan expected")
+    }
+
+    let plan_error = |filter| {
+        let query = QueryExpression::new(Some(filter), vec![], Some(10), 0);
+        QueryPlanner::new(&schema, &secondary_indexes, &query)
+            .plan()
+            .unwrap_err()
+    };
+    // Two lower bounds.
+    assert!(matches!(
+        plan_error(FilterExpression::And(vec![
+            FilterExpression::Simple("c".into(), Operator::GT, 100.into()),
+            FilterExpression::Simple("c".into(), Operator::GTE, 200.into()),
+        ])),
+        PlanError::RangeQueryLimit
+    ));
+    // Bounds on different fields.
+    assert!(matches!(
+        plan_error(FilterExpression::And(vec![
+            FilterExpression::Simple("a".into(), Operator::GT, 100.into()),
+            FilterExpression::Simple("c".into(), Operator::LT, 200.into()),
+        ])),
+        PlanError::RangeQueryLimit
+    ));
+
+    // No sorted inverted index on `text`.
+    let (schema, secondary_indexes) = test_utils::schema_multi_indices();
+    let filter = FilterExpression::And(vec![
+        FilterExpression::Simple("text".into(), Operator::GT, "a".into()),
+        FilterExpression::Simple("text".into(), Operator::LT, "b".into()),
+    ]);
+    let query = QueryExpression::new(Some(filter), vec![], Some(10), 0);
+    assert!(matches!(
+        QueryPlanner::new(&schema, &secondary_indexes, &query)
+            .plan()
+            .unwrap_err(),
+        PlanError::MatchingIndexNotFound
+    ));
+}
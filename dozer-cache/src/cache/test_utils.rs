@@ -103,3 +103,28 @@ pub fn schema_multi_indices() -> (Schema, Vec<IndexDefinition>) {
         ],
     )
 }
+
+pub fn schema_json_array() -> (Schema, Vec<IndexDefinition>) {
+    (
+        Schema {
+            identifier: Some(SchemaIdentifier { id: 5, version: 1 }),
+            fields: vec![
+                FieldDefinition {
+                    name: "id".to_string(),
+                    typ: dozer_types::types::FieldType::Int,
+                    nullable: false,
+                },
+                FieldDefinition {
+                    name: "tags".to_string(),
+                    typ: dozer_types::types::FieldType::Json,
+                    nullable: true,
+                },
+            ],
+            primary_index: vec![0],
+        },
+        vec![
+            IndexDefinition::SortedInverted(vec![0]),
+            IndexDefinition::SortedInverted(vec![1]),
+        ],
+    )
+}
@@ -23,10 +23,16 @@ pub enum CacheError {
     TypeError(#[from] TypeError),
     #[error("Schema Identifier is not present")]
     SchemaIdentifierNotFound,
+    #[error("Record was written with version {1} of schema {0}, which is now at version {2}")]
+    StaleSchemaVersion(u32, u16, u16),
     #[error("Path not initialized for Cache Reader")]
     PathNotInitialized,
     #[error("Secondary index database is not found")]
     SecondaryIndexDatabaseNotFound,
+    #[error("Cannot sort more than {0} records in memory, add an index matching $order_by")]
+    InMemorySortLimitExceeded(usize),
+    #[error("Cache was written with field encoding version {0}, but version {1} is required. Delete the cache to rebuild it")]
+    IncompatibleEncodingVersion(u32, u32),
 }
 
 impl CacheError {
@@ -122,6 +128,12 @@ pub enum QueryValidationError {
 
     #[error("unidentified order {0}")]
     UnidentifiedOrder(String),
+
+    #[error("sort option must be an object with field and direction: {0}")]
+    InvalidSortOption(Value),
+
+    #[error("invalid cursor {0}")]
+    InvalidCursor(String),
 }
 
 #[derive(Error, Debug)]
@@ -138,6 +150,10 @@ pub enum PlanError {
     RangeQueryLimit,
     #[error("Matching index not found")]
     MatchingIndexNotFound,
+    #[error("Field {0:?} has no full text index")]
+    FullTextIndexNotFound(String),
+    #[error("Cursors cannot be used with range queries or $order_by")]
+    CursorNotSupported,
 }
 
 pub fn validate_query(
@@ -3,6 +3,7 @@
 pub mod app;
 pub mod appsource;
 pub mod channels;
+pub mod commit_tuner;
 pub mod dag;
 mod dag_metadata;
 pub mod dag_schemas;
@@ -11,8 +12,10 @@ pub mod errors;
 pub mod executor;
 mod executor_utils;
 pub mod forwarder;
+pub mod metrics;
 pub mod node;
 pub mod record_store;
+pub mod validation;
 
 #[cfg(test)]
 mod tests;
@@ -146,7 +146,7 @@ impl App {
             .get(entry_points.iter().map(|e| e.0.clone()).collect())?;
 
         for mapping in &mappings {
-            let node_handle = NodeHandle::new(None, mapping.source.connection.clone());
+            let node_handle = NodeHandle::new(None, mapping.source.name.clone());
             dag.add_node(
                 NodeType::Source(mapping.source.source.clone()),
                 node_handle.clone(),
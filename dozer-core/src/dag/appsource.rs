@@ -10,6 +10,8 @@ use std::sync::Arc;
 #[derive(Clone)]
 pub struct AppSource {
     pub connection: String,
+    /// Name of the source node, the connection's name unless set with [`with_name`](Self::with_name).
+    pub name: String,
     pub source: Arc<dyn SourceFactory>,
     pub mappings: HashMap<String, PortHandle>,
 }
@@ -21,11 +23,19 @@ impl AppSource {
         mappings: HashMap<String, PortHandle>,
     ) -> Self {
         Self {
+            name: connection.clone(),
             connection,
             source,
             mappings,
         }
     }
+
+    /// Names the source node `name`, for connections ingested by several sources. Tables are
+    /// still qualified with the connection's name.
+    pub fn with_name(mut self, name: String) -> Self {
+        self.name = name;
+        self
+    }
 }
 
 #[derive(Clone, Hash, Eq, PartialEq, Debug)]
@@ -75,8 +85,8 @@ impl Default for AppSourceManager {
 
 impl AppSourceManager {
     pub fn add(&mut self, src: AppSource) -> Result<(), ExecutionError> {
-        if self.sources.iter().any(|s| s.connection == src.connection) {
-            return Err(AppSourceConnectionAlreadyExists(src.connection));
+        if self.sources.iter().any(|s| s.name == src.name) {
+            return Err(AppSourceConnectionAlreadyExists(src.name));
         }
 
         self.sources.push(src);
@@ -12,8 +12,33 @@ pub trait SourceChannelForwarder: Send + Sync {
         op: Operation,
         port: PortHandle,
     ) -> Result<(), ExecutionError>;
+
+    /// Sends `(txid, seq_in_tx, op, port)` operations, in order. Equivalent to calling
+    /// [`send`](Self::send) for each of them, but the whole batch crosses the channel to the
+    /// source listener at once, which saves synchronization for high volume sources.
+    fn send_batch(
+        &mut self,
+        ops: Vec<(u64, u64, Operation, PortHandle)>,
+    ) -> Result<(), ExecutionError> {
+        for (txid, seq_in_tx, op, port) in ops {
+            self.send(txid, seq_in_tx, op, port)?;
+        }
+        Ok(())
+    }
+
+    /// Reports that the source has no pending operations up to (`txid`, `seq_in_tx`).
+    /// Lets the checkpoint advance while the source is idle.
+    fn heartbeat(&mut self, _txid: u64, _seq_in_tx: u64) -> Result<(), ExecutionError> {
+        Ok(())
+    }
 }
 
 pub trait ProcessorChannelForwarder {
+    /// Forwards `op` to the nodes connected to output `port`.
+    ///
+    /// Can be called any number of times while processing an operation, including none. All the
+    /// operations forwarded belong to the epoch of the operation being processed: the epoch is
+    /// only committed downstream after [`Processor::process`](crate::dag::node::Processor::process)
+    /// returns, so a processor can fan out an operation without any commit accounting.
     fn send(&mut self, op: Operation, port: PortHandle) -> Result<(), ExecutionError>;
 }
@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+/// Channels fuller than this make the tuner shrink the commit size.
+const HIGH_CHANNEL_PRESSURE: f64 = 0.8;
+/// Channels must be emptier than this for the tuner to grow the commit size.
+const LOW_CHANNEL_PRESSURE: f64 = 0.5;
+
+/// Bounds of the adaptive commit size.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitSizeTuning {
+    pub min_commit_sz: u32,
+    pub max_commit_sz: u32,
+    /// Commits slower than this shrink the commit size.
+    pub target_commit_latency: Duration,
+}
+
+impl Default for CommitSizeTuning {
+    fn default() -> Self {
+        Self {
+            min_commit_sz: 1_000,
+            max_commit_sz: 100_000,
+            target_commit_latency: Duration::from_millis(100),
+        }
+    }
+}
+
+/// Adjusts the commit size of a source after every commit.
+///
+/// The commit size is halved when a commit is slower than the target or downstream channels are
+/// close to full. It grows by a quarter when a full batch committed in less than half the target
+/// while downstream channels had room.
+#[derive(Debug)]
+pub(crate) struct CommitSizeTuner {
+    tuning: CommitSizeTuning,
+    commit_sz: u32,
+}
+
+impl CommitSizeTuner {
+    pub fn new(tuning: CommitSizeTuning, initial_commit_sz: u32) -> Self {
+        let mut tuner = Self {
+            tuning,
+            commit_sz: initial_commit_sz,
+        };
+        tuner.commit_sz = tuner.bound(initial_commit_sz);
+        tuner
+    }
+
+    pub fn get_commit_sz(&self) -> u32 {
+        self.commit_sz
+    }
+
+    /// - `batch_full`: The commit was triggered by reaching the commit size.
+    /// - `latency`: Time spent closing the epoch and sending the commit.
+    /// - `channel_pressure`: Fill ratio of the fullest downstream channel.
+    pub fn on_commit(&mut self, batch_full: bool, latency: Duration, channel_pressure: f64) {
+        let commit_sz = if latency > self.tuning.target_commit_latency
+            || channel_pressure >= HIGH_CHANNEL_PRESSURE
+        {
+            self.commit_sz / 2
+        } else if batch_full
+            && latency <= self.tuning.target_commit_latency / 2
+            && channel_pressure < LOW_CHANNEL_PRESSURE
+        {
+            self.commit_sz.saturating_add((self.commit_sz / 4).max(1))
+        } else {
+            self.commit_sz
+        };
+        self.commit_sz = self.bound(commit_sz);
+    }
+
+    fn bound(&self, commit_sz: u32) -> u32 {
+        commit_sz
+            .max(self.tuning.min_commit_sz)
+            .min(self.tuning.max_commit_sz)
+            .max(1)
+    }
+}
@@ -2,15 +2,15 @@ use crate::dag::dag::{Dag, Edge, NodeType};
 use crate::dag::dag_schemas::NodeSchemas;
 use crate::dag::errors::ExecutionError;
 use crate::dag::errors::ExecutionError::{
-    InvalidCheckpointState, InvalidNodeHandle, MetadataAlreadyExists,
+    IncompatibleEncodingVersion, InvalidCheckpointState, InvalidNodeHandle, MetadataAlreadyExists,
 };
+use crate::dag::executor_utils::partition_env_name;
 use crate::dag::node::{NodeHandle, PortHandle};
-use crate::storage::common::Seek;
 use crate::storage::errors::StorageError;
 use crate::storage::errors::StorageError::{DeserializationError, SerializationError};
 use crate::storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
 use dozer_types::bincode;
-use dozer_types::types::Schema;
+use dozer_types::types::{Schema, FIELD_ENCODING_VERSION};
 use std::collections::{HashMap, HashSet};
 
 use std::path::Path;
@@ -19,12 +19,23 @@ pub(crate) const METADATA_DB_NAME: &str = "__META__";
 pub(crate) const SOURCE_ID_IDENTIFIER: u8 = 0_u8;
 pub(crate) const OUTPUT_SCHEMA_IDENTIFIER: u8 = 1_u8;
 pub(crate) const INPUT_SCHEMA_IDENTIFIER: u8 = 2_u8;
+pub(crate) const ENCODING_VERSION_IDENTIFIER: u8 = 3_u8;
 
 pub(crate) enum Consistency {
     FullyConsistent((u64, u64)),
     PartiallyConsistent(HashMap<(u64, u64), Vec<NodeHandle>>),
 }
 
+/// Removes the environment of `node`, and those of its other instances if it's a partitioned processor.
+fn remove_node_envs(path: &Path, node: &NodeHandle) {
+    LmdbEnvironmentManager::remove(path, format!("{}", node).as_str());
+    let mut instance = 1;
+    while LmdbEnvironmentManager::exists(path, &partition_env_name(node, instance)) {
+        LmdbEnvironmentManager::remove(path, &partition_env_name(node, instance));
+        instance += 1;
+    }
+}
+
 struct DependencyTreeNode {
     pub handle: NodeHandle,
     pub children: Vec<DependencyTreeNode>,
@@ -76,22 +87,56 @@ impl<'a> DagMetadataManager<'a> {
         })
     }
 
+    /// Reads the metadata of `node`, or returns `None` if it has none. Unlike [`new`](Self::new),
+    /// doesn't delete the metadata if it can't be read.
+    pub(crate) fn read_node_metadata(
+        path: &Path,
+        node: &NodeHandle,
+    ) -> Result<Option<DagMetadata>, ExecutionError> {
+        if !LmdbEnvironmentManager::exists(path, format!("{}", node).as_str()) {
+            return Ok(None);
+        }
+        Self::get_node_checkpoint_metadata(path, node).map(Some)
+    }
+
     fn get_node_checkpoint_metadata(
         path: &Path,
         name: &NodeHandle,
     ) -> Result<DagMetadata, ExecutionError> {
-        if !LmdbEnvironmentManager::exists(path, format!("{}", name).as_str()) {
-            return Err(InvalidCheckpointState(name.clone()));
+        Self::get_env_checkpoint_metadata(path, name, &partition_env_name(name, 0))
+    }
+
+    /// Reads the metadata of `node`, checking that the other `parallelism - 1` instances of a
+    /// partitioned processor have committed the same positions. Instances commit one after the
+    /// other, so a failure can leave them at different positions, from which the node can't resume.
+    fn get_partitioned_node_checkpoint_metadata(
+        path: &Path,
+        node: &NodeHandle,
+        parallelism: usize,
+    ) -> Result<DagMetadata, ExecutionError> {
+        let metadata = Self::get_node_checkpoint_metadata(path, node)?;
+        for instance in 1..parallelism {
+            let instance_metadata =
+                Self::get_env_checkpoint_metadata(path, node, &partition_env_name(node, instance))?;
+            if instance_metadata.commits != metadata.commits {
+                return Err(InvalidCheckpointState(node.clone()));
+            }
         }
+        Ok(metadata)
+    }
 
-        let mut env = LmdbEnvironmentManager::create(path, format!("{}", name).as_str())?;
-        let db = env.open_database(METADATA_DB_NAME, false)?;
-        let txn = env.create_txn()?;
-        let txn = SharedTransaction::try_unwrap(txn)
-            .expect("We just created this `SharedTransaction`. It's not shared.");
+    fn get_env_checkpoint_metadata(
+        path: &Path,
+        name: &NodeHandle,
+        env_name: &str,
+    ) -> Result<DagMetadata, ExecutionError> {
+        if !LmdbEnvironmentManager::exists(path, env_name) {
+            return Err(InvalidCheckpointState(name.clone()));
+        }
 
-        let cur = txn.open_ro_cursor(db)?;
-        if !cur.first()? {
+        // Only read, so that validating a checkpoint never creates or modifies it
+        let entries = LmdbEnvironmentManager::read_database(path, env_name, METADATA_DB_NAME)?;
+        if entries.is_empty() {
             return Err(ExecutionError::InternalDatabaseError(
                 StorageError::InvalidRecord,
             ));
@@ -100,11 +145,11 @@ impl<'a> DagMetadataManager<'a> {
         let mut map = HashMap::<NodeHandle, (u64, u64)>::new();
         let mut input_schemas: HashMap<PortHandle, Schema> = HashMap::new();
         let mut output_schemas: HashMap<PortHandle, Schema> = HashMap::new();
+        // State written before the version was recorded
+        let mut encoding_version = 1;
 
-        loop {
-            let value = cur.read()?.ok_or(ExecutionError::InternalDatabaseError(
-                StorageError::InvalidRecord,
-            ))?;
+        for (key, value) in &entries {
+            let value = (key.as_slice(), value.as_slice());
             match value.0[0] {
                 SOURCE_ID_IDENTIFIER => {
                     let handle: NodeHandle = NodeHandle::from_bytes(&value.0[1..]);
@@ -138,15 +183,26 @@ impl<'a> DagMetadataManager<'a> {
                         })?;
                     input_schemas.insert(handle, schema);
                 }
+                ENCODING_VERSION_IDENTIFIER => {
+                    encoding_version = u32::from_be_bytes(value.1.try_into().map_err(|_e| {
+                        ExecutionError::InternalDatabaseError(StorageError::InvalidRecord)
+                    })?);
+                }
                 _ => {
                     return Err(ExecutionError::InternalDatabaseError(
                         StorageError::InvalidRecord,
                     ))
                 }
             }
-            if !cur.next()? {
-                break;
-            }
+        }
+
+        // The keys of the record stores would be decoded and sorted wrongly, the node is rebuilt
+        if encoding_version != FIELD_ENCODING_VERSION {
+            return Err(IncompatibleEncodingVersion(
+                name.clone(),
+                encoding_version,
+                FIELD_ENCODING_VERSION,
+            ));
         }
 
         Ok(DagMetadata {
@@ -162,11 +218,19 @@ impl<'a> DagMetadataManager<'a> {
     ) -> Result<HashMap<NodeHandle, DagMetadata>, ExecutionError> {
         let mut all = HashMap::<NodeHandle, DagMetadata>::new();
         for node in &dag.nodes {
-            match DagMetadataManager::get_node_checkpoint_metadata(path, node.0) {
+            let parallelism = match node.1 {
+                NodeType::Processor(factory) => factory.parallelism(),
+                _ => 1,
+            };
+            match DagMetadataManager::get_partitioned_node_checkpoint_metadata(
+                path,
+                node.0,
+                parallelism,
+            ) {
                 Ok(r) => {
                     all.insert(node.0.clone(), r);
                 }
-                Err(_e) => LmdbEnvironmentManager::remove(path, format!("{}", node.0).as_str()),
+                Err(_e) => remove_node_envs(path, node.0),
             }
         }
         Ok(all)
@@ -202,6 +266,22 @@ impl<'a> DagMetadataManager<'a> {
         }
     }
 
+    fn collect_dependent_nodes(tree_node: &DependencyTreeNode, nodes: &mut HashSet<NodeHandle>) {
+        nodes.insert(tree_node.handle.clone());
+        for child in &tree_node.children {
+            Self::collect_dependent_nodes(child, nodes);
+        }
+    }
+
+    /// Returns `source` and every node it feeds, directly or not.
+    pub(crate) fn get_dependent_nodes(&self, source: &NodeHandle) -> HashSet<NodeHandle> {
+        let mut nodes = HashSet::new();
+        if let Some(tree) = self.deps_trees.get(source) {
+            Self::collect_dependent_nodes(tree, &mut nodes);
+        }
+        nodes
+    }
+
     fn get_sources_for_namespace(&self, ns: u16) -> HashSet<NodeHandle> {
         let mut handles = HashSet::<NodeHandle>::new();
         for (src_handle, src_node) in self.deps_trees.iter() {
@@ -249,74 +329,85 @@ impl<'a> DagMetadataManager<'a> {
         r
     }
 
-    pub(crate) fn delete_metadata(&self) {
-        for node in &self.dag.nodes {
-            LmdbEnvironmentManager::remove(self.path, format!("{}", node.0).as_str());
-        }
+    pub(crate) fn delete_node_metadata(&self, node: &NodeHandle) {
+        remove_node_envs(self.path, node);
+    }
+
+    /// Returns the metadata loaded for `node`, if it had any.
+    pub(crate) fn get_node_metadata(&self, node: &NodeHandle) -> Option<&DagMetadata> {
+        self.metadata.get(node)
     }
 
-    pub(crate) fn get_metadata(&self) -> Result<HashMap<NodeHandle, DagMetadata>, ExecutionError> {
-        let mut all_meta = HashMap::<NodeHandle, DagMetadata>::new();
-        for node in &self.dag.nodes {
-            let metadata = Self::get_node_checkpoint_metadata(self.path, node.0)?;
-            all_meta.insert(node.0.clone(), metadata);
+    /// Initializes the metadata of the nodes which don't have any, leaving the others untouched.
+    pub(crate) fn init_missing_metadata(
+        &self,
+        schemas: &HashMap<NodeHandle, NodeSchemas>,
+    ) -> Result<(), ExecutionError> {
+        for node in self.dag.nodes.keys() {
+            if !LmdbEnvironmentManager::exists(self.path, format!("{}", node).as_str()) {
+                self.init_node_metadata(node, schemas)?;
+            }
         }
-        Ok(all_meta)
+        Ok(())
     }
 
-    pub(crate) fn init_metadata(
+    fn init_node_metadata(
         &self,
+        node: &NodeHandle,
         schemas: &HashMap<NodeHandle, NodeSchemas>,
     ) -> Result<(), ExecutionError> {
-        for node in &self.dag.nodes {
-            let curr_node_schema = schemas
-                .get(node.0)
-                .ok_or_else(|| InvalidNodeHandle(node.0.clone()))?;
+        let curr_node_schema = schemas
+            .get(node)
+            .ok_or_else(|| InvalidNodeHandle(node.clone()))?;
 
-            if LmdbEnvironmentManager::exists(self.path, format!("{}", node.0).as_str()) {
-                return Err(MetadataAlreadyExists(node.0.clone()));
-            }
+        if LmdbEnvironmentManager::exists(self.path, format!("{}", node).as_str()) {
+            return Err(MetadataAlreadyExists(node.clone()));
+        }
 
-            let mut env =
-                LmdbEnvironmentManager::create(self.path, format!("{}", node.0).as_str())?;
-            let db = env.open_database(METADATA_DB_NAME, false)?;
-            let txn = env.create_txn()?;
-            let mut txn = SharedTransaction::try_unwrap(txn)
-                .expect("We just created this `SharedTransaction`. It's not shared.");
-
-            for (handle, schema) in curr_node_schema.output_schemas.iter() {
-                let mut key: Vec<u8> = vec![OUTPUT_SCHEMA_IDENTIFIER];
-                key.extend(handle.to_be_bytes());
-                let value = bincode::serialize(schema).map_err(|e| SerializationError {
-                    typ: "Schema".to_string(),
-                    reason: Box::new(e),
-                })?;
-                txn.put(db, &key, &value)?;
-            }
+        let mut env = LmdbEnvironmentManager::create(self.path, format!("{}", node).as_str())?;
+        let db = env.open_database(METADATA_DB_NAME, false)?;
+        let txn = env.create_txn()?;
+        let mut txn = SharedTransaction::try_unwrap(txn)
+            .expect("We just created this `SharedTransaction`. It's not shared.");
 
-            for (handle, schema) in curr_node_schema.input_schemas.iter() {
-                let mut key: Vec<u8> = vec![INPUT_SCHEMA_IDENTIFIER];
-                key.extend(handle.to_be_bytes());
-                let value = bincode::serialize(schema).map_err(|e| SerializationError {
-                    typ: "Schema".to_string(),
-                    reason: Box::new(e),
-                })?;
-                txn.put(db, &key, &value)?;
-            }
+        for (handle, schema) in curr_node_schema.output_schemas.iter() {
+            let mut key: Vec<u8> = vec![OUTPUT_SCHEMA_IDENTIFIER];
+            key.extend(handle.to_be_bytes());
+            let value = bincode::serialize(schema).map_err(|e| SerializationError {
+                typ: "Schema".to_string(),
+                reason: Box::new(e),
+            })?;
+            txn.put(db, &key, &value)?;
+        }
 
-            for (source, _factory) in &self.dag.get_sources() {
-                let mut key: Vec<u8> = vec![SOURCE_ID_IDENTIFIER];
-                key.extend(source.to_bytes());
+        for (handle, schema) in curr_node_schema.input_schemas.iter() {
+            let mut key: Vec<u8> = vec![INPUT_SCHEMA_IDENTIFIER];
+            key.extend(handle.to_be_bytes());
+            let value = bincode::serialize(schema).map_err(|e| SerializationError {
+                typ: "Schema".to_string(),
+                reason: Box::new(e),
+            })?;
+            txn.put(db, &key, &value)?;
+        }
 
-                let mut value: Vec<u8> = Vec::with_capacity(16);
-                value.extend(0_u64.to_be_bytes());
-                value.extend(0_u64.to_be_bytes());
+        txn.put(
+            db,
+            &[ENCODING_VERSION_IDENTIFIER],
+            &FIELD_ENCODING_VERSION.to_be_bytes(),
+        )?;
 
-                txn.put(db, &key, &value)?;
-            }
+        for (source, _factory) in &self.dag.get_sources() {
+            let mut key: Vec<u8> = vec![SOURCE_ID_IDENTIFIER];
+            key.extend(source.to_bytes());
+
+            let mut value: Vec<u8> = Vec::with_capacity(16);
+            value.extend(0_u64.to_be_bytes());
+            value.extend(0_u64.to_be_bytes());
 
-            txn.commit_and_renew()?;
+            txn.put(db, &key, &value)?;
         }
+
+        txn.commit_and_renew()?;
         Ok(())
     }
 }
@@ -199,9 +199,48 @@ impl<'a> DagSchemaManager<'a> {
             Self::fill_node_output_schemas(dag, source, &mut schemas)?;
         }
 
+        Self::validate_record_lookups(dag)?;
+
         Ok(Self { dag, schemas })
     }
 
+    /// Checks that every input port from which records are looked up is fed by a stateful port.
+    /// A stateless port keeps no records, so lookups would silently find nothing.
+    fn validate_record_lookups(dag: &Dag) -> Result<(), ExecutionError> {
+        for edge in &dag.edges {
+            let to_node = dag
+                .nodes
+                .get(&edge.to.node)
+                .ok_or_else(|| InvalidNodeHandle(edge.to.node.clone()))?;
+            let requires_lookup = match to_node {
+                NodeType::Source(_) => false,
+                NodeType::Processor(proc) => proc.requires_record_lookup(&edge.to.port),
+                NodeType::Sink(sink) => sink.requires_record_lookup(&edge.to.port),
+            };
+            if !requires_lookup {
+                continue;
+            }
+
+            let from_node = dag
+                .nodes
+                .get(&edge.from.node)
+                .ok_or_else(|| InvalidNodeHandle(edge.from.node.clone()))?;
+            let from_port = Self::get_node_output_ports(from_node)?
+                .into_iter()
+                .find(|port| port.handle == edge.from.port)
+                .ok_or(ExecutionError::InvalidPortHandle(edge.from.port))?;
+            if matches!(from_port.typ, OutputPortType::Stateless) {
+                return Err(ExecutionError::RecordLookupOnStatelessPort(
+                    edge.to.node.clone(),
+                    edge.to.port,
+                    edge.from.node.clone(),
+                    edge.from.port,
+                ));
+            }
+        }
+        Ok(())
+    }
+
     pub fn get_node_input_schemas(
         &self,
         handle: &NodeHandle,
@@ -1,10 +1,8 @@
 use crate::dag::node::NodeHandle;
-use dozer_types::parking_lot::Mutex;
+use dozer_types::parking_lot::{Mutex, RwLock};
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
-use std::sync::{Arc, Barrier};
-use std::thread::sleep;
-use std::time::Duration;
+use std::sync::Arc;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Epoch {
@@ -36,6 +34,53 @@ impl Display for Epoch {
     }
 }
 
+/// Positions of every source that have been committed by all sinks of the DAG.
+///
+/// Sinks report each epoch they commit. A source position is only considered committed once
+/// every registered sink has committed an epoch at or past it.
+#[derive(Debug, Clone, Default)]
+pub struct CommitWatermark {
+    sinks: Arc<RwLock<HashMap<NodeHandle, HashMap<NodeHandle, (u64, u64)>>>>,
+}
+
+impl CommitWatermark {
+    pub(crate) fn register_sink(&self, sink: NodeHandle) {
+        self.sinks.write().entry(sink).or_default();
+    }
+
+    pub(crate) fn update(&self, sink: &NodeHandle, epoch: &Epoch) {
+        let mut sinks = self.sinks.write();
+        let committed = sinks.entry(sink.clone()).or_default();
+        for (source, position) in &epoch.details {
+            committed.insert(source.clone(), *position);
+        }
+    }
+
+    /// Returns the position of `source` committed by all sinks, or `None` if some sink has not
+    /// committed anything from `source` yet.
+    pub fn get(&self, source: &NodeHandle) -> Option<(u64, u64)> {
+        let sinks = self.sinks.read();
+        if sinks.is_empty() {
+            return None;
+        }
+        let mut watermark: Option<(u64, u64)> = None;
+        for committed in sinks.values() {
+            let position = *committed.get(source)?;
+            watermark = Some(match watermark {
+                Some(current) if current <= position => current,
+                _ => position,
+            });
+        }
+        watermark
+    }
+
+    /// Returns `true` if all sinks have committed `source` up to at least `target`.
+    pub fn has_reached(&self, source: &NodeHandle, target: (u64, u64)) -> bool {
+        self.get(source)
+            .map_or(false, |position| position >= target)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ClosingEpoch {
     pub id: u64,
@@ -53,104 +98,64 @@ impl ClosingEpoch {
     }
 }
 
-#[derive(Debug)]
-enum EpochManagerState {
-    Closing {
-        epoch: Epoch,
-        should_terminate: bool,
-        barrier: Arc<Barrier>,
-    },
-    Closed {
-        epoch: ClosingEpoch,
-        num_participant_confirmations: usize,
-    },
+/// The last epoch closed by a source, and the position of the source it covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceWatermark {
+    pub epoch_id: u64,
+    pub position: (u64, u64),
 }
 
+/// Numbers the epochs of every source.
+///
+/// Each source closes its own epochs, numbered from 0, without waiting for the other sources, and
+/// its low-watermark is the last epoch it closed. Nodes fed by several sources align the commits
+/// of their inputs by epoch id, so a slow source only holds back the nodes downstream of it. The
+/// epoch committed by every source is the minimum of their watermarks.
 #[derive(Debug)]
-pub(crate) struct EpochManager {
-    num_participants: usize,
-    state: Mutex<EpochManagerState>,
+pub struct EpochManager {
+    watermarks: Mutex<HashMap<NodeHandle, Option<SourceWatermark>>>,
 }
 
 impl EpochManager {
-    pub fn new(num_participants: usize) -> Self {
+    pub fn new(sources: impl IntoIterator<Item = NodeHandle>) -> Self {
         Self {
-            num_participants,
-            state: Mutex::new(EpochManagerState::Closing {
-                epoch: Epoch::new(0, HashMap::new()),
-                should_terminate: true,
-                barrier: Arc::new(Barrier::new(num_participants)),
-            }),
+            watermarks: Mutex::new(sources.into_iter().map(|source| (source, None)).collect()),
         }
     }
 
-    pub fn wait_for_epoch_close(
+    /// Closes the next epoch of `source`, which covers the source up to `position`.
+    pub fn close_epoch(
         &self,
-        participant: NodeHandle,
-        txn_id_and_seq_number: (u64, u64),
-        request_termination: bool,
+        source: &NodeHandle,
+        position: (u64, u64),
+        terminating: bool,
     ) -> ClosingEpoch {
-        let barrier = loop {
-            let mut state = self.state.lock();
-            match &mut *state {
-                EpochManagerState::Closing {
-                    epoch,
-                    should_terminate,
-                    barrier,
-                } => {
-                    epoch.details.insert(participant, txn_id_and_seq_number);
-                    // If anyone doesn't want to terminate, we don't terminate.
-                    *should_terminate = *should_terminate && request_termination;
-                    break barrier.clone();
-                }
-                EpochManagerState::Closed { .. } => {
-                    // This thread wants to close a new epoch while some other thread hasn't got confirmation of last epoch closing.
-                    // Just release the lock and put this thread to sleep.
-                    drop(state);
-                    sleep(Duration::from_millis(1));
-                }
-            }
-        };
-
-        barrier.wait();
-
-        let mut state = self.state.lock();
-        if let EpochManagerState::Closing {
-            epoch,
-            should_terminate,
-            ..
-        } = &mut *state
-        {
-            // This thread is the first one in this critical area.
-            debug_assert!(epoch.details.len() == self.num_participants);
-            let closing_epoch =
-                ClosingEpoch::new(epoch.id, epoch.details.clone(), *should_terminate);
-            *state = EpochManagerState::Closed {
-                epoch: closing_epoch,
-                num_participant_confirmations: 0,
-            };
-        }
+        let mut watermarks = self.watermarks.lock();
+        let watermark = watermarks.entry(source.clone()).or_default();
+        let id = watermark.map_or(0, |watermark| watermark.epoch_id + 1);
+        *watermark = Some(SourceWatermark {
+            epoch_id: id,
+            position,
+        });
+        ClosingEpoch::new(
+            id,
+            [(source.clone(), position)].into_iter().collect(),
+            terminating,
+        )
+    }
 
-        match &mut *state {
-            EpochManagerState::Closed {
-                epoch,
-                num_participant_confirmations,
-            } => {
-                *num_participant_confirmations += 1;
-                let closing_epoch = epoch.clone();
-                if *num_participant_confirmations == self.num_participants {
-                    // This thread is the last one in this critical area.
-                    *state = EpochManagerState::Closing {
-                        epoch: Epoch::new(closing_epoch.id + 1, HashMap::new()),
-                        should_terminate: true,
-                        barrier: Arc::new(Barrier::new(self.num_participants)),
-                    };
-                }
-                closing_epoch
-            }
-            EpochManagerState::Closing { .. } => {
-                unreachable!("We just modified `EpochManagerstate` to `Closed`")
-            }
-        }
+    /// Returns the last epoch closed by `source`, or `None` if it hasn't closed any.
+    pub fn watermark(&self, source: &NodeHandle) -> Option<SourceWatermark> {
+        self.watermarks.lock().get(source).copied().flatten()
+    }
+
+    /// Returns the last epoch closed by every source, or `None` if some source hasn't closed any.
+    pub fn global_watermark(&self) -> Option<u64> {
+        self.watermarks
+            .lock()
+            .values()
+            .map(|watermark| watermark.map(|watermark| watermark.epoch_id))
+            .min()
+            .flatten()
     }
 }
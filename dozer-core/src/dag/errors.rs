@@ -2,6 +2,7 @@
 use crate::dag::appsource::AppSourceId;
 use crate::dag::node::{NodeHandle, PortHandle};
 use crate::storage::errors::StorageError;
+use crossbeam::channel::RecvError;
 use dozer_types::errors::internal::BoxedError;
 use dozer_types::errors::types::TypeError;
 use dozer_types::thiserror;
@@ -37,6 +38,8 @@ pub enum ExecutionError {
     InvalidCheckpointState(NodeHandle),
     #[error("Already exists: {0}")]
     MetadataAlreadyExists(NodeHandle),
+    #[error("State of node {0} was written with field encoding version {1}, expected {2}")]
+    IncompatibleEncodingVersion(NodeHandle, u32, u32),
     #[error("Incompatible schemas")]
     IncompatibleSchemas(),
     #[error("Channel disconnected")]
@@ -45,6 +48,20 @@ pub enum ExecutionError {
     CannotSpawnWorkerThread(#[from] std::io::Error),
     #[error("Internal thread panicked")]
     InternalThreadPanic,
+    #[error("Node {node} failed: {source}")]
+    NodeFailed {
+        node: NodeHandle,
+        #[source]
+        source: Box<ExecutionError>,
+    },
+    #[error("Node {node} panicked: {message}")]
+    NodePanicked { node: NodeHandle, message: String },
+    #[error("Processor {0} runs in parallel but its input port {1} has no partition key")]
+    MissingPartitionKey(NodeHandle, PortHandle),
+    #[error("Processor {0} runs in parallel but its output port {1} is stateful")]
+    StatefulPartitionedOutput(NodeHandle, PortHandle),
+    #[error("Node {0} looks up records on input port {1} but output port {3} of {2} is stateless")]
+    RecordLookupOnStatelessPort(NodeHandle, PortHandle, NodeHandle, PortHandle),
     #[error("Invalid source identifier {0}")]
     InvalidSourceIdentifier(AppSourceId),
     #[error("Ambiguous source identifier {0}")]
@@ -61,6 +78,8 @@ pub enum ExecutionError {
     UnsupportedDeleteOperation(String),
     #[error("Invalid AppSource connection {0}. Already exists.")]
     AppSourceConnectionAlreadyExists(String),
+    #[error("Schema of source table {0} changed. Restart the pipeline to apply it")]
+    SourceSchemaChanged(String),
     #[error("Failed to get primary key for `{0}`")]
     FailedToGetPrimaryKey(String),
     #[error("Got mismatching primary key for `{endpoint_name}`. Expected: `{expected:?}`, got: `{actual:?}`")]
@@ -69,6 +88,8 @@ pub enum ExecutionError {
         expected: Vec<String>,
         actual: Vec<String>,
     },
+    #[error("Failed to process the record: {0}")]
+    RecordProcessingError(#[source] BoxedError),
 
     // Error forwarders
     #[error(transparent)]
@@ -95,6 +116,49 @@ pub enum ExecutionError {
     ProcessorReceiverError(usize, #[source] BoxedError),
 }
 
+impl ExecutionError {
+    /// Returns `true` if the error is caused by the operation being processed, so the pipeline
+    /// can skip that operation and go on. These are:
+    ///
+    /// - [`InvalidOperation`](Self::InvalidOperation), [`FieldNotFound`](Self::FieldNotFound),
+    ///   [`RecordNotFound`](Self::RecordNotFound),
+    ///   [`UnsupportedUpdateOperation`](Self::UnsupportedUpdateOperation),
+    ///   [`UnsupportedDeleteOperation`](Self::UnsupportedDeleteOperation),
+    ///   [`FailedToGetPrimaryKey`](Self::FailedToGetPrimaryKey) and
+    ///   [`MismatchPrimaryKey`](Self::MismatchPrimaryKey),
+    /// - [`InternalTypeError`](Self::InternalTypeError),
+    /// - [`RecordProcessingError`](Self::RecordProcessingError), which processors return when they
+    ///   can't compute the output of a record.
+    ///
+    /// Every other error, in particular storage and channel errors and opaque
+    /// [`InternalError`](Self::InternalError)s, is fatal.
+    pub fn is_recoverable(&self) -> bool {
+        match self {
+            ExecutionError::InvalidOperation(_)
+            | ExecutionError::FieldNotFound(_)
+            | ExecutionError::RecordNotFound()
+            | ExecutionError::UnsupportedUpdateOperation(_)
+            | ExecutionError::UnsupportedDeleteOperation(_)
+            | ExecutionError::FailedToGetPrimaryKey(_)
+            | ExecutionError::MismatchPrimaryKey { .. }
+            | ExecutionError::InternalTypeError(_)
+            | ExecutionError::RecordProcessingError(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the error is caused by another node going away, either downstream
+    /// ([`ChannelDisconnected`](Self::ChannelDisconnected)) or upstream (an
+    /// [`InternalError`](Self::InternalError) wrapping a [`RecvError`]).
+    pub fn is_disconnection(&self) -> bool {
+        match self {
+            ExecutionError::ChannelDisconnected => true,
+            ExecutionError::InternalError(e) => e.is::<RecvError>(),
+            _ => false,
+        }
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum SinkError {
     #[error("Failed to initialize schema in Sink: {0}")]
@@ -1,15 +1,19 @@
 #![allow(clippy::type_complexity)]
 
+use crate::dag::commit_tuner::CommitSizeTuning;
 use crate::dag::dag::Dag;
 use crate::dag::dag_metadata::{Consistency, DagMetadata, DagMetadataManager};
 use crate::dag::dag_schemas::{DagSchemaManager, NodeSchemas};
 use crate::dag::errors::ExecutionError;
-use crate::dag::errors::ExecutionError::{
-    IncompatibleSchemas, InconsistentCheckpointMetadata, InvalidNodeHandle,
+use crate::dag::errors::ExecutionError::IncompatibleSchemas;
+use crate::dag::executor_utils::{index_edges, partition_env_name};
+use crate::dag::metrics::{DagMetrics, NodeCounters, NodeMetrics};
+use crate::dag::node::{
+    NodeHandle, OutputPortType, PortHandle, ProcessorFactory, SinkFactory, SourceFactory,
+    SourceStopper,
 };
-use crate::dag::executor_utils::index_edges;
-use crate::dag::node::{NodeHandle, PortHandle, ProcessorFactory, SinkFactory, SourceFactory};
 use crate::dag::record_store::RecordReader;
+use crate::dag::validation::{NodeValidation, SchemaDiff, ValidationReport};
 use crate::storage::common::Database;
 use crate::storage::lmdb_storage::LmdbEnvironmentManager;
 
@@ -17,11 +21,11 @@ use crossbeam::channel::{bounded, Receiver, Sender};
 use dozer_types::parking_lot::RwLock;
 use dozer_types::types::{Operation, Record};
 
-use crate::dag::epoch::{Epoch, EpochManager};
+use crate::dag::epoch::{CommitWatermark, Epoch, EpochManager};
+use std::any::Any;
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
-use std::panic::panic_any;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Barrier};
@@ -32,16 +36,38 @@ use std::time::Duration;
 #[derive(Clone)]
 pub struct ExecutorOptions {
     pub commit_sz: u32,
+    /// Overrides `commit_sz` for the given sources. Every handle must be a source of the DAG.
+    pub source_commit_sz: HashMap<NodeHandle, u32>,
+    /// Lets the commit size adapt at runtime within these bounds. `None` keeps `commit_sz` fixed.
+    pub commit_sz_tuning: Option<CommitSizeTuning>,
     pub channel_buffer_sz: usize,
     pub commit_time_threshold: Duration,
+    /// Interval at which idle sources commit empty epochs to advance their checkpoint. `None`, the default, disables heartbeats.
+    pub heartbeat_interval: Option<Duration>,
+    /// Logs a warning when a node waits longer than this for room in a full output channel. `None` disables the warning.
+    pub full_channel_warning: Option<Duration>,
+    /// Receives the operations a processor failed to process, with the processor and the error,
+    /// when the error is [recoverable](ExecutionError::is_recoverable). The state writes and
+    /// output of the failed operation are rolled back, and the processor goes on with the next
+    /// operation. `None` makes every processor error stop the pipeline.
+    pub dead_letter: Option<Sender<(NodeHandle, Operation, ExecutionError)>>,
+    /// Keeps the state of every node in memory instead of LMDB. Nothing is written to disk, so
+    /// the pipeline can't resume from a checkpoint and every source starts from the beginning.
+    pub in_memory: bool,
 }
 
 impl Default for ExecutorOptions {
     fn default() -> Self {
         Self {
             commit_sz: 10_000,
+            source_commit_sz: HashMap::new(),
+            commit_sz_tuning: None,
             channel_buffer_sz: 20_000,
             commit_time_threshold: Duration::from_millis(50),
+            heartbeat_interval: None,
+            full_channel_warning: None,
+            dead_letter: None,
+            in_memory: false,
         }
     }
 }
@@ -97,12 +123,15 @@ impl StorageMetadata {
 
 mod name;
 mod node;
+mod partition;
 mod processor_node;
 mod receiver_loop;
 mod sink_node;
 mod source_node;
 
+use name::Name;
 use node::Node;
+use partition::{PartitionMerger, PartitionRouter};
 use processor_node::ProcessorNode;
 use sink_node::SinkNode;
 
@@ -116,23 +145,75 @@ pub struct DagExecutor<'a> {
     path: PathBuf,
     options: ExecutorOptions,
     running: Arc<AtomicBool>,
+    /// Set when stopping, to let sources forward the operations they already sent.
+    draining: Arc<AtomicBool>,
+    /// Stoppers of the running sources, registered by their sender threads.
+    source_stoppers: Arc<RwLock<Vec<Arc<dyn SourceStopper>>>>,
     consistency_metadata: HashMap<NodeHandle, (u64, u64)>,
+    watermark: CommitWatermark,
+    metrics: DagMetrics,
 }
 
 impl<'a> DagExecutor<'a> {
+    /// Returns the position each source restarts from.
+    ///
+    /// A source whose nodes didn't all commit the same position restarts from the beginning,
+    /// and the metadata of every node it feeds is deleted. Deleting a node also discards what
+    /// other sources sent to it, so those sources restart from the beginning as well. The other
+    /// sources keep their checkpoint.
     fn check_consistency(
         dag: &'a Dag,
         path: &Path,
     ) -> Result<HashMap<NodeHandle, (u64, u64)>, ExecutionError> {
-        let mut r: HashMap<NodeHandle, (u64, u64)> = HashMap::new();
         let meta = DagMetadataManager::new(dag, path)?;
         let chk = meta.get_checkpoint_consistency();
-        for (handle, _factory) in &dag.get_sources() {
+        let sources: HashMap<NodeHandle, HashSet<NodeHandle>> = dag
+            .get_sources()
+            .into_iter()
+            .map(|(handle, _factory)| {
+                let nodes = meta.get_dependent_nodes(&handle);
+                (handle, nodes)
+            })
+            .collect();
+
+        let mut reset: HashSet<NodeHandle> = sources
+            .keys()
+            .filter(|handle| !matches!(chk.get(handle), Some(Consistency::FullyConsistent(_))))
+            .cloned()
+            .collect();
+        let mut reset_nodes: HashSet<NodeHandle> = HashSet::new();
+        loop {
+            reset_nodes.extend(
+                reset
+                    .iter()
+                    .flat_map(|handle| sources[handle].iter().cloned()),
+            );
+            let shared: Vec<NodeHandle> = sources
+                .iter()
+                .filter(|(handle, nodes)| {
+                    !reset.contains(*handle) && !nodes.is_disjoint(&reset_nodes)
+                })
+                .map(|(handle, _nodes)| handle.clone())
+                .collect();
+            if shared.is_empty() {
+                break;
+            }
+            reset.extend(shared);
+        }
+
+        for node in &reset_nodes {
+            meta.delete_node_metadata(node);
+        }
+
+        let mut r: HashMap<NodeHandle, (u64, u64)> = HashMap::new();
+        for handle in sources.keys() {
             match chk.get(handle) {
-                Some(Consistency::FullyConsistent(c)) => {
+                Some(Consistency::FullyConsistent(c)) if !reset.contains(handle) => {
                     r.insert(handle.clone(), *c);
                 }
-                _ => return Err(InconsistentCheckpointMetadata),
+                _ => {
+                    r.insert(handle.clone(), (0, 0));
+                }
             }
         }
         Ok(r)
@@ -144,21 +225,20 @@ impl<'a> DagExecutor<'a> {
         options: ExecutorOptions,
         running: Arc<AtomicBool>,
     ) -> Result<Self, ExecutionError> {
-        //
-
-        let consistency_metadata: HashMap<NodeHandle, (u64, u64)> =
-            match Self::check_consistency(dag, path) {
-                Ok(c) => c,
-                Err(_) => {
-                    DagMetadataManager::new(dag, path)?.delete_metadata();
-                    dag.get_sources()
-                        .iter()
-                        .map(|e| (e.0.clone(), (0_u64, 0_u64)))
-                        .collect()
-                }
-            };
-
-        let schemas = Self::load_or_init_schema(dag, path)?;
+        let (schemas, consistency_metadata) = if options.in_memory {
+            let schemas = DagSchemaManager::new(dag)?.get_all_schemas().clone();
+            let start = dag
+                .get_sources()
+                .into_iter()
+                .map(|(handle, _factory)| (handle, (0, 0)))
+                .collect();
+            (schemas, start)
+        } else {
+            let schemas = Self::load_schemas(dag, path)?;
+            let consistency_metadata = Self::check_consistency(dag, path)?;
+            DagMetadataManager::new(dag, path)?.init_missing_metadata(&schemas)?;
+            (schemas, consistency_metadata)
+        };
 
         Ok(Self {
             dag,
@@ -173,73 +253,91 @@ impl<'a> DagExecutor<'a> {
             join_handles: HashMap::new(),
             options,
             running,
+            draining: Arc::new(AtomicBool::new(false)),
+            source_stoppers: Arc::new(RwLock::new(vec![])),
             consistency_metadata,
+            watermark: CommitWatermark::default(),
+            metrics: DagMetrics::default(),
         })
     }
 
+    /// Directory of the environments of the nodes, `None` if their state is kept in memory.
+    fn state_path(&self) -> Option<PathBuf> {
+        (!self.options.in_memory).then(|| self.path.clone())
+    }
+
+    /// Returns a handle to the source positions committed by the sinks of this DAG.
+    pub fn get_watermark(&self) -> CommitWatermark {
+        self.watermark.clone()
+    }
+
+    /// Returns a handle to the runtime counters of the nodes of this DAG, which can be read
+    /// from another thread while the executor runs.
+    pub fn get_metrics(&self) -> DagMetrics {
+        self.metrics.clone()
+    }
+
+    /// Returns the current counters of every started node.
+    pub fn metrics(&self) -> HashMap<NodeHandle, NodeMetrics> {
+        self.metrics.get()
+    }
+
     pub fn validate(dag: &'a Dag, path: &Path) -> Result<(), ExecutionError> {
-        Self::load_or_init_schema(dag, path).map(|_| ())
+        let schemas = Self::load_schemas(dag, path)?;
+        DagMetadataManager::new(dag, path)?.init_missing_metadata(&schemas)
     }
 
-    fn validate_schemas(
+    /// Checks the schemas of `dag` against the metadata in `path`, like [`validate`](Self::validate)
+    /// but without deleting or initializing any metadata, and reports what each node would do.
+    pub fn validate_readonly(dag: &Dag, path: &Path) -> Result<ValidationReport, ExecutionError> {
+        let schema_manager = DagSchemaManager::new(dag)?;
+        let mut report = ValidationReport::default();
+        for (handle, current) in schema_manager.get_all_schemas() {
+            let validation = match DagMetadataManager::read_node_metadata(path, handle) {
+                Ok(None) => NodeValidation::New,
+                Ok(Some(existing)) => {
+                    let diffs = SchemaDiff::between(current, &existing);
+                    if diffs.is_empty() {
+                        NodeValidation::Compatible
+                    } else {
+                        NodeValidation::Incompatible(diffs)
+                    }
+                }
+                Err(e) => NodeValidation::Unreadable(e.to_string()),
+            };
+            report.nodes.insert(handle.clone(), validation);
+        }
+        Ok(report)
+    }
+
+    pub(crate) fn validate_schemas(
         current: &NodeSchemas,
         existing: &DagMetadata,
     ) -> Result<(), ExecutionError> {
-        if existing.output_schemas.len() != current.output_schemas.len() {
-            return Err(IncompatibleSchemas());
-        }
-        for (port, schema) in &current.output_schemas {
-            let other_schema = existing
-                .output_schemas
-                .get(port)
-                .ok_or(IncompatibleSchemas())?;
-            if schema != other_schema {
-                return Err(IncompatibleSchemas());
-            }
-        }
-        if existing.input_schemas.len() != current.input_schemas.len() {
-            return Err(IncompatibleSchemas());
-        }
-        for (port, schema) in &current.output_schemas {
-            let other_schema = existing
-                .output_schemas
-                .get(port)
-                .ok_or(IncompatibleSchemas())?;
-            if schema != other_schema {
-                return Err(IncompatibleSchemas());
-            }
+        if SchemaDiff::between(current, existing).is_empty() {
+            Ok(())
+        } else {
+            Err(IncompatibleSchemas())
         }
-        Ok(())
     }
 
-    fn load_or_init_schema(
+    /// Returns the schemas of the DAG, deleting the metadata of the nodes whose schemas changed
+    /// since it was written.
+    fn load_schemas(
         dag: &'a Dag,
         path: &Path,
     ) -> Result<HashMap<NodeHandle, NodeSchemas>, ExecutionError> {
         let schema_manager = DagSchemaManager::new(dag)?;
         let meta_manager = DagMetadataManager::new(dag, path)?;
 
-        let compatible = match meta_manager.get_metadata() {
-            Ok(existing_schemas) => {
-                for (handle, current) in schema_manager.get_all_schemas() {
-                    let existing = existing_schemas
-                        .get(handle)
-                        .ok_or_else(|| InvalidNodeHandle(handle.clone()))?;
-                    Self::validate_schemas(current, existing)?;
+        for (handle, current) in schema_manager.get_all_schemas() {
+            if let Some(existing) = meta_manager.get_node_metadata(handle) {
+                if Self::validate_schemas(current, existing).is_err() {
+                    meta_manager.delete_node_metadata(handle);
                 }
-                Ok(schema_manager.get_all_schemas().clone())
-            }
-            Err(_) => Err(IncompatibleSchemas()),
-        };
-
-        match compatible {
-            Ok(schema) => Ok(schema),
-            Err(_) => {
-                meta_manager.delete_metadata();
-                meta_manager.init_metadata(schema_manager.get_all_schemas())?;
-                Ok(schema_manager.get_all_schemas().clone())
             }
         }
+        Ok(schema_manager.get_all_schemas().clone())
     }
 
     fn start_source(
@@ -257,11 +355,17 @@ impl<'a> DagExecutor<'a> {
             .get(&handle)
             .ok_or_else(|| ExecutionError::InvalidNodeHandle(handle.clone()))?;
         let output_ports = src_factory.get_output_ports()?;
+        let counters = self.metrics.register_node(
+            handle.clone(),
+            &[],
+            &output_ports.iter().map(|p| p.handle).collect::<Vec<_>>(),
+        );
 
         let st_node_handle = handle.clone();
         let output_schemas = schemas.output_schemas.clone();
         let running = self.running.clone();
         let running_source = running.clone();
+        let source_stoppers = self.source_stoppers.clone();
         let source_fn = move |handle: NodeHandle| -> Result<(), ExecutionError> {
             let sender = SourceSenderNode::new(
                 handle,
@@ -270,6 +374,7 @@ impl<'a> DagExecutor<'a> {
                 start_seq,
                 sender,
                 running,
+                &source_stoppers,
             )?;
             sender.run()
         };
@@ -285,30 +390,44 @@ impl<'a> DagExecutor<'a> {
             })?;
 
         let timeout = self.options.commit_time_threshold;
-        let base_path = self.path.clone();
+        let base_path = self.state_path();
         let record_readers = self.record_stores.clone();
         let edges = self.dag.edges.clone();
         let running = self.running.clone();
         let running_listener = running.clone();
-        let commit_sz = self.options.commit_sz;
+        let draining = self.draining.clone();
+        let commit_sz = self
+            .options
+            .source_commit_sz
+            .get(&handle)
+            .copied()
+            .unwrap_or(self.options.commit_sz);
+        let commit_sz_tuning = self.options.commit_sz_tuning.clone();
         let max_duration_between_commits = self.options.commit_time_threshold;
+        let heartbeat_interval = self.options.heartbeat_interval;
+        let full_channel_warning = self.options.full_channel_warning;
         let output_schemas = schemas.output_schemas.clone();
         let source_fn = move |handle: NodeHandle| -> Result<(), ExecutionError> {
             let listener = SourceListenerNode::new(
                 handle,
                 receiver,
                 timeout,
-                &base_path,
+                base_path.as_deref(),
                 &output_ports,
                 record_readers,
                 senders,
                 &edges,
                 running,
+                draining,
                 commit_sz,
+                commit_sz_tuning,
                 max_duration_between_commits,
+                heartbeat_interval,
                 epoch_manager,
                 output_schemas,
                 start_seq,
+                counters,
+                full_channel_warning,
             )?;
             start_barrier.wait();
             listener.run()
@@ -332,31 +451,186 @@ impl<'a> DagExecutor<'a> {
         receivers: HashMap<PortHandle, Vec<Receiver<ExecutorOperation>>>,
         schemas: &NodeSchemas,
     ) -> Result<JoinHandle<()>, ExecutionError> {
-        let base_path = self.path.clone();
+        let counters = self.metrics.register_node(
+            handle.clone(),
+            &proc_factory.get_input_ports(),
+            &proc_factory
+                .get_output_ports()
+                .iter()
+                .map(|p| p.handle)
+                .collect::<Vec<_>>(),
+        );
+        if proc_factory.parallelism() > 1 {
+            return self.start_partitioned_processor(
+                handle,
+                proc_factory,
+                senders,
+                receivers,
+                schemas,
+                counters,
+            );
+        }
+        self.spawn_processor_instance(
+            handle.to_string(),
+            handle,
+            0,
+            proc_factory,
+            senders,
+            receivers,
+            schemas,
+            counters,
+        )
+    }
+
+    /// Runs `parallelism` instances of the processor. Every input channel goes through a
+    /// [`PartitionRouter`] dispatching the records to the instances by their partition key, and
+    /// every output port through a [`PartitionMerger`] merging what the instances send. The
+    /// returned thread stops the DAG when one of these threads fails, waits for all of them and
+    /// fails with the first failure.
+    fn start_partitioned_processor(
+        &self,
+        handle: NodeHandle,
+        proc_factory: Arc<dyn ProcessorFactory>,
+        senders: HashMap<PortHandle, Vec<Sender<ExecutorOperation>>>,
+        receivers: HashMap<PortHandle, Vec<Receiver<ExecutorOperation>>>,
+        schemas: &NodeSchemas,
+        counters: Arc<NodeCounters>,
+    ) -> Result<JoinHandle<()>, ExecutionError> {
+        let parallelism = proc_factory.parallelism();
+        if let Some(port) = proc_factory
+            .get_output_ports()
+            .into_iter()
+            .find(|port| !matches!(port.typ, OutputPortType::Stateless))
+        {
+            return Err(ExecutionError::StatefulPartitionedOutput(
+                handle,
+                port.handle,
+            ));
+        }
+
+        let mut instance_receivers: Vec<HashMap<PortHandle, Vec<Receiver<ExecutorOperation>>>> =
+            vec![HashMap::new(); parallelism];
+        let mut routers = vec![];
+        for (port, port_receivers) in receivers {
+            let key = proc_factory
+                .partition_by(&port)
+                .ok_or_else(|| ExecutionError::MissingPartitionKey(handle.clone(), port))?;
+            for receiver in port_receivers {
+                let (router_senders, router_receivers): (Vec<_>, Vec<_>) = (0..parallelism)
+                    .map(|_| bounded(self.options.channel_buffer_sz))
+                    .unzip();
+                for (instance, router_receiver) in
+                    instance_receivers.iter_mut().zip(router_receivers)
+                {
+                    instance.entry(port).or_default().push(router_receiver);
+                }
+                routers.push(PartitionRouter::new(
+                    handle.clone(),
+                    port,
+                    key.clone(),
+                    receiver,
+                    router_senders,
+                ));
+            }
+        }
+
+        let mut instance_senders = vec![HashMap::new(); parallelism];
+        let mut mergers = vec![];
+        for (port, port_senders) in senders {
+            let (merger_senders, merger_receivers): (Vec<_>, Vec<_>) = (0..parallelism)
+                .map(|_| bounded(self.options.channel_buffer_sz))
+                .unzip();
+            for (instance, merger_sender) in instance_senders.iter_mut().zip(merger_senders) {
+                instance.insert(port, vec![merger_sender]);
+            }
+            mergers.push(PartitionMerger::new(
+                handle.clone(),
+                port,
+                merger_receivers,
+                port_senders,
+            ));
+        }
+
+        let mut threads = vec![];
+        for (instance, (senders, receivers)) in instance_senders
+            .into_iter()
+            .zip(instance_receivers)
+            .enumerate()
+        {
+            threads.push(self.spawn_processor_instance(
+                partition_env_name(&handle, instance),
+                handle.clone(),
+                instance,
+                proc_factory.clone(),
+                senders,
+                receivers,
+                schemas,
+                counters.clone(),
+            )?);
+        }
+        for router in routers {
+            threads.push(spawn_node(
+                router.name().into_owned(),
+                self.running.clone(),
+                || router.run(),
+            )?);
+        }
+        for merger in mergers {
+            threads.push(spawn_node(
+                merger.name().into_owned(),
+                self.running.clone(),
+                || merger.run(),
+            )?);
+        }
+
+        let running = self.running.clone();
+        let source_stoppers = self.source_stoppers.clone();
+        let stop = move || {
+            running.store(false, Ordering::SeqCst);
+            for stopper in source_stoppers.read().iter() {
+                stopper.stop();
+            }
+        };
+        Ok(Builder::new()
+            .name(handle.to_string())
+            .spawn(move || join_partitioned_processor(threads, stop))?)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn spawn_processor_instance(
+        &self,
+        thread_name: String,
+        handle: NodeHandle,
+        instance: usize,
+        proc_factory: Arc<dyn ProcessorFactory>,
+        senders: HashMap<PortHandle, Vec<Sender<ExecutorOperation>>>,
+        receivers: HashMap<PortHandle, Vec<Receiver<ExecutorOperation>>>,
+        schemas: &NodeSchemas,
+        counters: Arc<NodeCounters>,
+    ) -> Result<JoinHandle<()>, ExecutionError> {
+        let base_path = self.state_path();
         let record_readers = self.record_stores.clone();
         let edges = self.dag.edges.clone();
         let schemas = schemas.clone();
-        let running = self.running.clone();
-        let processor_fn = move |handle: NodeHandle| -> Result<(), ExecutionError> {
+        let full_channel_warning = self.options.full_channel_warning;
+        let dead_letter = self.options.dead_letter.clone();
+        spawn_node(thread_name, self.running.clone(), move || {
             let processor = ProcessorNode::new(
                 handle,
+                instance,
                 &*proc_factory,
-                &base_path,
+                base_path.as_deref(),
                 record_readers,
                 receivers,
                 senders,
                 &edges,
-                schemas.clone(),
+                schemas,
+                counters,
+                full_channel_warning,
+                dead_letter,
             )?;
             processor.run()
-        };
-        Ok(Builder::new().name(handle.to_string()).spawn(move || {
-            if let Err(e) = processor_fn(handle) {
-                if running.load(Ordering::Relaxed) {
-                    std::panic::panic_any(e);
-                }
-            }
-        })?)
+        })
     }
 
     pub fn start_sink(
@@ -366,28 +640,39 @@ impl<'a> DagExecutor<'a> {
         receivers: HashMap<PortHandle, Vec<Receiver<ExecutorOperation>>>,
         schemas: &NodeSchemas,
     ) -> Result<JoinHandle<()>, ExecutionError> {
-        let base_path = self.path.clone();
+        let base_path = self.state_path();
         let record_readers = self.record_stores.clone();
         let input_schemas = schemas.input_schemas.clone();
+        let watermark = self.watermark.clone();
+        watermark.register_sink(handle.clone());
+        let running = self.running.clone();
+        let counters =
+            self.metrics
+                .register_node(handle.clone(), &snk_factory.get_input_ports(), &[]);
         let snk_fn = move |handle| -> Result<(), ExecutionError> {
             let sink = SinkNode::new(
                 handle,
                 &*snk_factory,
-                &base_path,
+                base_path.as_deref(),
                 record_readers,
                 receivers,
                 input_schemas,
+                watermark,
+                counters,
             )?;
             sink.run()
         };
-        Ok(Builder::new().name(handle.to_string()).spawn(|| {
+        Ok(Builder::new().name(handle.to_string()).spawn(move || {
             if let Err(e) = snk_fn(handle) {
-                std::panic::panic_any(e);
+                if running.load(Ordering::Relaxed) {
+                    std::panic::panic_any(e);
+                }
             }
         })?)
     }
 
     pub fn start(&mut self) -> Result<(), ExecutionError> {
+        self.validate_source_commit_sz()?;
         let (mut senders, mut receivers) = index_edges(self.dag, self.options.channel_buffer_sz);
 
         for (handle, factory) in self.dag.get_sinks() {
@@ -421,10 +706,10 @@ impl<'a> DagExecutor<'a> {
             self.join_handles.insert(handle.clone(), join_handle);
         }
 
-        let epoch_manager: Arc<EpochManager> =
-            Arc::new(EpochManager::new(self.dag.get_sources().len()));
-
         let sources = self.dag.get_sources();
+        let epoch_manager: Arc<EpochManager> = Arc::new(EpochManager::new(
+            sources.iter().map(|(handle, _factory)| handle.clone()),
+        ));
         let start_barrier = Arc::new(Barrier::new(sources.len()));
 
         for (handle, factory) in sources {
@@ -445,29 +730,157 @@ impl<'a> DagExecutor<'a> {
         Ok(())
     }
 
+    fn validate_source_commit_sz(&self) -> Result<(), ExecutionError> {
+        let sources = self.dag.get_sources();
+        for handle in self.options.source_commit_sz.keys() {
+            if !sources.iter().any(|(source, _)| source == handle) {
+                return Err(ExecutionError::InvalidNodeHandle(handle.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Stops the DAG, and the sources through their [`stopper`](crate::dag::node::Source::stopper).
     pub fn stop(&self) {
         self.running.store(false, Ordering::SeqCst);
+        for stopper in self.source_stoppers.read().iter() {
+            stopper.stop();
+        }
     }
 
+    /// Stops the sources like [`stop`](Self::stop), but waits for the operations they already
+    /// sent to go through the DAG. They are committed by the sinks in a final epoch before the
+    /// threads exit. A source without a stopper is waited for until it returns from `start`.
+    pub fn stop_and_drain(self) -> Result<(), ExecutionError> {
+        self.draining.store(true, Ordering::SeqCst);
+        self.stop();
+        self.join()
+    }
+
+    /// Waits for all the nodes to finish.
+    ///
+    /// If a node fails, stops the other nodes, waits for them to finish and returns an error
+    /// naming the first failing node. Nodes fail in turn when their neighbours go away, so a node
+    /// that failed on its own is preferred.
     pub fn join(mut self) -> Result<(), ExecutionError> {
         let handles: Vec<NodeHandle> = self.join_handles.iter().map(|e| e.0.clone()).collect();
 
+        let mut failure: Option<ExecutionError> = None;
         loop {
             for handle in &handles {
                 if let Entry::Occupied(entry) = self.join_handles.entry(handle.clone()) {
                     if entry.get().is_finished() {
-                        if let Err(e) = entry.remove().join() {
-                            panic_any(e);
+                        if let Err(payload) = entry.remove().join() {
+                            let error = node_error(handle.clone(), payload);
+                            let is_preferred = match &failure {
+                                None => {
+                                    self.stop();
+                                    true
+                                }
+                                Some(first) => {
+                                    is_node_disconnection(first) && !is_node_disconnection(&error)
+                                }
+                            };
+                            if is_preferred {
+                                failure = Some(error);
+                            }
                         }
                     }
                 }
             }
 
             if self.join_handles.is_empty() {
-                return Ok(());
+                return failure.map_or(Ok(()), Err);
             }
 
             thread::sleep(Duration::from_millis(250));
         }
     }
 }
+
+/// Runs `node_fn` in a thread named `name`. An error is raised as a panic, unless the DAG was
+/// stopped.
+fn spawn_node<F>(
+    name: String,
+    running: Arc<AtomicBool>,
+    node_fn: F,
+) -> Result<JoinHandle<()>, ExecutionError>
+where
+    F: FnOnce() -> Result<(), ExecutionError> + Send + 'static,
+{
+    Ok(Builder::new().name(name).spawn(move || {
+        if let Err(e) = node_fn() {
+            if running.load(Ordering::Relaxed) {
+                std::panic::panic_any(e);
+            }
+        }
+    })?)
+}
+
+/// Waits for all the threads of a partitioned processor. When one fails, calls `stop` so that the
+/// others finish too, then raises the panic of the first one failing, preferring a failure which
+/// isn't a disconnection, like [`DagExecutor::join`].
+fn join_partitioned_processor(mut threads: Vec<JoinHandle<()>>, stop: impl Fn()) {
+    let mut failure: Option<Box<dyn Any + Send>> = None;
+    while !threads.is_empty() {
+        let (finished, running): (Vec<_>, Vec<_>) =
+            threads.into_iter().partition(|thread| thread.is_finished());
+        threads = running;
+
+        for payload in finished
+            .into_iter()
+            .filter_map(|thread| thread.join().err())
+        {
+            let is_preferred = match &failure {
+                None => {
+                    stop();
+                    true
+                }
+                Some(first) => {
+                    is_disconnection(first.as_ref()) && !is_disconnection(payload.as_ref())
+                }
+            };
+            if is_preferred {
+                failure = Some(payload);
+            }
+        }
+
+        if !threads.is_empty() {
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+    if let Some(payload) = failure {
+        std::panic::resume_unwind(payload);
+    }
+}
+
+fn is_node_disconnection(error: &ExecutionError) -> bool {
+    match error {
+        ExecutionError::NodeFailed { source, .. } => source.is_disconnection(),
+        _ => false,
+    }
+}
+
+fn is_disconnection(payload: &(dyn Any + Send)) -> bool {
+    payload
+        .downcast_ref::<ExecutionError>()
+        .map_or(false, ExecutionError::is_disconnection)
+}
+
+/// Names the node whose thread failed. Nodes panic with the `ExecutionError` they returned, other
+/// panics usually carry a message.
+fn node_error(node: NodeHandle, payload: Box<dyn Any + Send>) -> ExecutionError {
+    match payload.downcast::<ExecutionError>() {
+        Ok(source) => ExecutionError::NodeFailed { node, source },
+        Err(payload) => {
+            let message = if let Some(message) = payload.downcast_ref::<&str>() {
+                message.to_string()
+            } else if let Some(message) = payload.downcast_ref::<String>() {
+                message.clone()
+            } else {
+                "unknown panic payload".to_string()
+            };
+            ExecutionError::NodePanicked { node, message }
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use std::borrow::Cow;
+
+use crossbeam::channel::{Receiver, Sender};
+use dozer_types::internal_err;
+use dozer_types::types::{Operation, Record};
+
+use crate::dag::{
+    epoch::Epoch,
+    errors::ExecutionError::{self, ChannelDisconnected, InternalError},
+    executor::ExecutorOperation,
+    node::{NodeHandle, PortHandle},
+};
+
+use super::{name::Name, node::Node, receiver_loop::ReceiverLoop};
+
+/// Returns the instance of a partitioned processor which processes `record`.
+///
+/// The key is hashed with FNV-1a, which doesn't change between builds, so that a record goes to
+/// the same instance, and finds the same state, after a restart.
+pub fn partition_of(record: &Record, key: &[usize], instances: usize) -> usize {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for index in key {
+        for byte in record.values[*index].encode() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    (hash % instances as u64) as usize
+}
+
+/// Distributes the operations of one input channel of a partitioned processor to its instances.
+///
+/// Data goes to the instance chosen by the partition key. An update whose key changes becomes a
+/// delete for the instance of the old record and an insert for the instance of the new one.
+/// Commits and terminate go to every instance.
+#[derive(Debug)]
+pub struct PartitionRouter {
+    /// Node handle of the partitioned processor.
+    node_handle: NodeHandle,
+    /// Input port of the channel.
+    port: PortHandle,
+    /// Indexes of the fields deciding the instance of a record.
+    key: Vec<usize>,
+    /// The input channel.
+    receiver: Receiver<ExecutorOperation>,
+    /// Input channels of the instances, indexed by instance.
+    senders: Vec<Sender<ExecutorOperation>>,
+}
+
+impl PartitionRouter {
+    pub fn new(
+        node_handle: NodeHandle,
+        port: PortHandle,
+        key: Vec<usize>,
+        receiver: Receiver<ExecutorOperation>,
+        senders: Vec<Sender<ExecutorOperation>>,
+    ) -> Self {
+        Self {
+            node_handle,
+            port,
+            key,
+            receiver,
+            senders,
+        }
+    }
+
+    fn partition_of(&self, record: &Record) -> usize {
+        partition_of(record, &self.key, self.senders.len())
+    }
+
+    fn send(&self, instance: usize, op: ExecutorOperation) -> Result<(), ExecutionError> {
+        self.senders[instance]
+            .send(op)
+            .map_err(|_| ChannelDisconnected)
+    }
+
+    fn broadcast(&self, op: ExecutorOperation) -> Result<(), ExecutionError> {
+        for sender in &self.senders {
+            sender.send(op.clone()).map_err(|_| ChannelDisconnected)?;
+        }
+        Ok(())
+    }
+}
+
+impl Name for PartitionRouter {
+    fn name(&self) -> Cow<str> {
+        Cow::Owned(format!("{}:{}", self.node_handle, self.port))
+    }
+}
+
+impl Node for PartitionRouter {
+    fn run(self) -> Result<(), ExecutionError> {
+        loop {
+            match internal_err!(self.receiver.recv())? {
+                ExecutorOperation::Insert { new } => {
+                    self.send(self.partition_of(&new), ExecutorOperation::Insert { new })?
+                }
+                ExecutorOperation::Delete { old } => {
+                    self.send(self.partition_of(&old), ExecutorOperation::Delete { old })?
+                }
+                ExecutorOperation::Update { old, new } => {
+                    let old_instance = self.partition_of(&old);
+                    let new_instance = self.partition_of(&new);
+                    if old_instance == new_instance {
+                        self.send(old_instance, ExecutorOperation::Update { old, new })?;
+                    } else {
+                        self.send(old_instance, ExecutorOperation::Delete { old })?;
+                        self.send(new_instance, ExecutorOperation::Insert { new })?;
+                    }
+                }
+                ExecutorOperation::Commit { epoch } => {
+                    self.broadcast(ExecutorOperation::Commit { epoch })?
+                }
+                ExecutorOperation::Terminate => {
+                    return self.broadcast(ExecutorOperation::Terminate);
+                }
+            }
+        }
+    }
+}
+
+/// Merges what the instances of a partitioned processor send on one output port.
+///
+/// An epoch is committed downstream once every instance committed it, so the downstream nodes
+/// see a single processor.
+#[derive(Debug)]
+pub struct PartitionMerger {
+    /// Node handle of the partitioned processor.
+    node_handle: NodeHandle,
+    /// Output port of the processor.
+    port: PortHandle,
+    /// Output channels of the instances, indexed by instance.
+    receivers: Vec<Receiver<ExecutorOperation>>,
+    /// Output channels of the processor on this port.
+    senders: Vec<Sender<ExecutorOperation>>,
+}
+
+impl PartitionMerger {
+    pub fn new(
+        node_handle: NodeHandle,
+        port: PortHandle,
+        receivers: Vec<Receiver<ExecutorOperation>>,
+        senders: Vec<Sender<ExecutorOperation>>,
+    ) -> Self {
+        Self {
+            node_handle,
+            port,
+            receivers,
+            senders,
+        }
+    }
+
+    fn broadcast(&self, op: ExecutorOperation) -> Result<(), ExecutionError> {
+        for sender in &self.senders {
+            sender.send(op.clone()).map_err(|_| ChannelDisconnected)?;
+        }
+        Ok(())
+    }
+}
+
+impl Name for PartitionMerger {
+    fn name(&self) -> Cow<str> {
+        Cow::Owned(format!("{}:{}", self.node_handle, self.port))
+    }
+}
+
+impl ReceiverLoop for PartitionMerger {
+    fn receivers(&mut self) -> Vec<Receiver<ExecutorOperation>> {
+        std::mem::take(&mut self.receivers)
+    }
+
+    fn receiver_name(&self, index: usize) -> Cow<str> {
+        Cow::Owned(format!("instance {}", index))
+    }
+
+    fn on_op(&mut self, _index: usize, op: Operation) -> Result<(), ExecutionError> {
+        self.broadcast(ExecutorOperation::from_operation(op))
+    }
+
+    fn on_commit(&mut self, epoch: &Epoch) -> Result<(), ExecutionError> {
+        self.broadcast(ExecutorOperation::Commit {
+            epoch: epoch.clone(),
+        })
+    }
+
+    fn on_terminate(&mut self) -> Result<(), ExecutionError> {
+        self.broadcast(ExecutorOperation::Terminate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossbeam::channel::unbounded;
+    use dozer_types::types::Field;
+
+    use super::*;
+
+    fn record(key: i64, value: &str) -> Record {
+        Record::new(
+            None,
+            vec![Field::Int(key), Field::String(value.to_string())],
+            None,
+        )
+    }
+
+    #[test]
+    fn test_partition_of_depends_on_key_only() {
+        let key = vec![0];
+        for n in 0..100 {
+            assert_eq!(
+                partition_of(&record(n, "a"), &key, 4),
+                partition_of(&record(n, "b"), &key, 4)
+            );
+        }
+        let instances = (0..100)
+            .map(|n| partition_of(&record(n, "a"), &key, 4))
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(instances.len(), 4);
+    }
+
+    #[test]
+    fn test_router_splits_updates_changing_partition() {
+        let key = vec![0];
+        let (old, new) = (0..100)
+            .map(|n| (record(0, "a"), record(n, "a")))
+            .find(|(old, new)| partition_of(old, &key, 2) != partition_of(new, &key, 2))
+            .unwrap();
+        let old_instance = partition_of(&old, &key, 2);
+
+        let (input, receiver) = unbounded();
+        let (senders, receivers): (Vec<_>, Vec<_>) = (0..2).map(|_| unbounded()).unzip();
+        let router = PartitionRouter::new(
+            NodeHandle::new(None, "proc".to_string()),
+            0,
+            key,
+            receiver,
+            senders,
+        );
+        input
+            .send(ExecutorOperation::Update {
+                old: old.clone(),
+                new: new.clone(),
+            })
+            .unwrap();
+        input.send(ExecutorOperation::Terminate).unwrap();
+        router.run().unwrap();
+
+        assert_eq!(
+            receivers[old_instance].try_iter().collect::<Vec<_>>(),
+            vec![
+                ExecutorOperation::Delete { old },
+                ExecutorOperation::Terminate
+            ]
+        );
+        assert_eq!(
+            receivers[1 - old_instance].try_iter().collect::<Vec<_>>(),
+            vec![
+                ExecutorOperation::Insert { new },
+                ExecutorOperation::Terminate
+            ]
+        );
+    }
+}
@@ -1,18 +1,22 @@
-use std::{borrow::Cow, collections::HashMap, mem::swap, path::Path, sync::Arc};
+use std::{borrow::Cow, collections::HashMap, mem::swap, path::Path, sync::Arc, time::Duration};
 
 use crossbeam::channel::{Receiver, Sender};
+use dozer_types::internal_err;
+use dozer_types::log::warn;
 use dozer_types::parking_lot::RwLock;
+use dozer_types::types::Operation;
 
 use crate::{
     dag::{
         dag::Edge,
         dag_schemas::NodeSchemas,
-        errors::ExecutionError,
+        errors::ExecutionError::{self, InternalError},
         executor_utils::{
             build_receivers_lists, create_ports_databases_and_fill_downstream_record_readers,
-            init_component,
+            init_component, partition_env_name,
         },
         forwarder::{ProcessorChannelManager, StateWriter},
+        metrics::NodeCounters,
         node::{NodeHandle, PortHandle, Processor, ProcessorFactory},
         record_store::RecordReader,
     },
@@ -38,35 +42,51 @@ pub struct ProcessorNode {
     master_tx: SharedTransaction,
     /// This node's output channel manager, for forwarding data, writing metadata and writing port state.
     channel_manager: ProcessorChannelManager,
+    /// Runtime metrics of this node.
+    counters: Arc<NodeCounters>,
+    /// Receives the operations which failed with a recoverable error. `None` makes every error fatal.
+    dead_letter: Option<Sender<(NodeHandle, Operation, ExecutionError)>>,
 }
 
 impl ProcessorNode {
     /// # Arguments
     ///
     /// - `node_handle`: Node handle in description DAG.
+    /// - `instance`: Index of this instance if the processor is partitioned, 0 otherwise.
     /// - `processor_factory`: Processor factory in description DAG.
-    /// - `base_path`: Base path of persisted data for the last execution of the description DAG.
+    /// - `base_path`: Base path of persisted data for the last execution of the description DAG. `None` keeps the state in memory.
     /// - `record_readers`: Record readers of all stateful ports.
     /// - `receivers`: Input channels to this processor.
     /// - `senders`: Output channels from this processor.
     /// - `edges`: All edges in the description DAG, used for creating record readers for input ports which is connected to this processor's stateful output ports.
     /// - `node_schemas`: Input and output data schemas.
+    /// - `counters`: Runtime metrics of this node.
+    /// - `full_channel_warning`: Time after which a send blocked on a full output channel is logged. `None` disables the warning.
+    /// - `dead_letter`: Channel receiving the operations which failed with a recoverable error. `None` makes every error fatal.
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         node_handle: NodeHandle,
+        instance: usize,
         processor_factory: &dyn ProcessorFactory,
-        base_path: &Path,
+        base_path: Option<&Path>,
         record_readers: Arc<RwLock<HashMap<NodeHandle, HashMap<PortHandle, RecordReader>>>>,
         receivers: HashMap<PortHandle, Vec<Receiver<ExecutorOperation>>>,
         senders: HashMap<PortHandle, Vec<Sender<ExecutorOperation>>>,
         edges: &[Edge],
         node_schemas: NodeSchemas,
+        counters: Arc<NodeCounters>,
+        full_channel_warning: Option<Duration>,
+        dead_letter: Option<Sender<(NodeHandle, Operation, ExecutionError)>>,
     ) -> Result<Self, ExecutionError> {
         let mut processor = processor_factory.build(
             node_schemas.input_schemas.clone(),
             node_schemas.output_schemas.clone(),
         )?;
-        let state_meta = init_component(&node_handle, base_path, |e| processor.init(e))?;
+        let state_meta = init_component(
+            &partition_env_name(&node_handle, instance),
+            base_path,
+            |e| processor.init(e),
+        )?;
 
         let (master_tx, port_databases) =
             create_ports_databases_and_fill_downstream_record_readers(
@@ -87,6 +107,8 @@ impl ProcessorNode {
                 node_schemas.output_schemas,
             )?,
             true,
+            counters.clone(),
+            full_channel_warning,
         );
 
         Ok(Self {
@@ -97,6 +119,8 @@ impl ProcessorNode {
             record_readers,
             master_tx,
             channel_manager,
+            counters,
+            dead_letter,
         })
     }
 }
@@ -118,26 +142,65 @@ impl ReceiverLoop for ProcessorNode {
         Cow::Owned(self.port_handles[index].to_string())
     }
 
-    fn on_op(
-        &mut self,
-        index: usize,
-        op: dozer_types::types::Operation,
-    ) -> Result<(), ExecutionError> {
+    fn on_receive(&mut self, index: usize, queue_depth: usize) {
+        self.counters
+            .on_input(self.port_handles[index], queue_depth);
+    }
+
+    fn on_op(&mut self, index: usize, op: Operation) -> Result<(), ExecutionError> {
         let record_readers = self.record_readers.read();
         let reader = record_readers
             .get(&self.node_handle)
             .ok_or_else(|| ExecutionError::InvalidNodeHandle(self.node_handle.clone()))?;
 
-        self.processor.process(
-            self.port_handles[index],
-            op,
-            &mut self.channel_manager,
-            &self.master_tx,
-            reader,
-        )
+        let dead_letter = match &self.dead_letter {
+            Some(dead_letter) => dead_letter,
+            None => {
+                return self.processor.process(
+                    self.port_handles[index],
+                    op,
+                    &mut self.channel_manager,
+                    &self.master_tx,
+                    reader,
+                )
+            }
+        };
+
+        // The writes and output of a failed operation are rolled back, so that skipping it leaves
+        // no partial result in the state or downstream.
+        let failed_op = op.clone();
+        let savepoint = self.master_tx.write().savepoint();
+        self.channel_manager.begin_buffering();
+        let result = self
+            .processor
+            .process(
+                self.port_handles[index],
+                op,
+                &mut self.channel_manager,
+                &self.master_tx,
+                reader,
+            )
+            .and_then(|()| self.channel_manager.flush());
+        match result {
+            Err(e) if e.is_recoverable() => {
+                self.channel_manager.discard();
+                self.master_tx.write().rollback_to(savepoint)?;
+                warn!(
+                    "[{}] Sending failed operation to dead letter: {}",
+                    self.node_handle, e
+                );
+                internal_err!(dead_letter.send((self.node_handle.clone(), failed_op, e)))
+            }
+            result => {
+                self.channel_manager.discard();
+                result
+            }
+        }
     }
 
     fn on_commit(&mut self, epoch: &crate::dag::epoch::Epoch) -> Result<(), ExecutionError> {
+        self.processor
+            .flush(&mut self.channel_manager, &self.master_tx)?;
         self.processor.commit(epoch, &self.master_tx)?;
         self.channel_manager.store_and_send_commit(epoch)
     }
@@ -43,6 +43,8 @@ pub trait ReceiverLoop: Name {
     fn receivers(&mut self) -> Vec<Receiver<ExecutorOperation>>;
     /// Returns the name of the receiver at `index`. Used for logging.
     fn receiver_name(&self, index: usize) -> Cow<str>;
+    /// Called when an operation is received at `index`, with `queue_depth` operations left behind it.
+    fn on_receive(&mut self, _index: usize, _queue_depth: usize) {}
     /// Responds to `op` from the receiver at `index`.
     fn on_op(&mut self, index: usize, op: Operation) -> Result<(), ExecutionError>;
     /// Responds to `commit` of `epoch`.
@@ -63,6 +65,7 @@ pub trait ReceiverLoop: Name {
             let index = sel.ready();
             match internal_err!(receivers[index].recv().map(map_executor_operation))? {
                 MappedExecutorOperation::Data { op } => {
+                    self.on_receive(index, receivers[index].len());
                     self.on_op(index, op)?;
                 }
                 MappedExecutorOperation::Commit { epoch } => {
@@ -70,13 +73,6 @@ pub trait ReceiverLoop: Name {
                     commits_received += 1;
                     sel.remove(index);
                     common_epoch.details.extend(epoch.details);
-
-                    if commits_received == receivers.len() {
-                        self.on_commit(&common_epoch)?;
-                        common_epoch = Epoch::new(common_epoch.id + 1, HashMap::new());
-                        commits_received = 0;
-                        sel = init_select(&receivers);
-                    }
                 }
                 MappedExecutorOperation::Terminate => {
                     port_states[index] = InputPortState::Terminated;
@@ -93,6 +89,24 @@ pub trait ReceiverLoop: Name {
                     }
                 }
             }
+
+            // Sources close their epochs independently, and may terminate while the others go
+            // on, so an epoch is committed once every receiver still open has committed it.
+            let num_open = port_states
+                .iter()
+                .filter(|v| *v == &InputPortState::Open)
+                .count();
+            if commits_received > 0 && commits_received == num_open {
+                self.on_commit(&common_epoch)?;
+                common_epoch = Epoch::new(common_epoch.id + 1, HashMap::new());
+                commits_received = 0;
+                sel = init_select(&receivers);
+                for (index, state) in port_states.iter().enumerate() {
+                    if state == &InputPortState::Terminated {
+                        sel.remove(index);
+                    }
+                }
+            }
         }
     }
 }
@@ -272,6 +286,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn receiver_loop_commits_open_receivers_after_terminate() {
+        let (mut test_loop, senders) = TestReceiverLoop::new(2);
+        let mut details = HashMap::new();
+        details.insert(NodeHandle::new(None, "0".to_string()), (0, 0));
+        let mut epoch0 = Epoch::new(0, details);
+        let mut details = HashMap::new();
+        details.insert(NodeHandle::new(None, "1".to_string()), (0, 0));
+        let epoch1 = Epoch::new(0, details);
+        senders[0]
+            .send(ExecutorOperation::Commit {
+                epoch: epoch0.clone(),
+            })
+            .unwrap();
+        senders[0].send(ExecutorOperation::Terminate).unwrap();
+        senders[1]
+            .send(ExecutorOperation::Commit {
+                epoch: epoch1.clone(),
+            })
+            .unwrap();
+        // Receiver 1 is ahead, and goes on after receiver 0 terminated.
+        let mut epoch1_next = epoch1.clone();
+        epoch1_next.id = 1;
+        senders[1]
+            .send(ExecutorOperation::Commit {
+                epoch: epoch1_next.clone(),
+            })
+            .unwrap();
+        senders[1].send(ExecutorOperation::Terminate).unwrap();
+        test_loop.receiver_loop().unwrap();
+
+        epoch0.details.extend(epoch1.details);
+        assert_eq!(test_loop.commits, vec![epoch0, epoch1_next]);
+        assert_eq!(test_loop.num_termations, 1);
+    }
+
     #[test]
     #[should_panic]
     fn receiver_loop_panics_on_inconsistent_commit_epoch() {
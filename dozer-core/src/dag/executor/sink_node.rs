@@ -6,10 +6,11 @@ use dozer_types::{parking_lot::RwLock, types::Schema};
 
 use crate::{
     dag::{
-        epoch::Epoch,
+        epoch::{CommitWatermark, Epoch},
         errors::ExecutionError,
         executor_utils::{build_receivers_lists, init_component},
         forwarder::StateWriter,
+        metrics::NodeCounters,
         node::{NodeHandle, PortHandle, Sink, SinkFactory},
         record_store::RecordReader,
     },
@@ -35,6 +36,10 @@ pub struct SinkNode {
     master_tx: SharedTransaction,
     /// This node's state writer, for writing metadata and port state.
     state_writer: StateWriter,
+    /// Committed source positions shared with the executor.
+    watermark: CommitWatermark,
+    /// Runtime metrics of this node.
+    counters: Arc<NodeCounters>,
 }
 
 impl SinkNode {
@@ -42,20 +47,25 @@ impl SinkNode {
     ///
     /// - `node_handle`: Node handle in description DAG.
     /// - `sink_factory`: Sink factory in description DAG.
-    /// - `base_path`: Base path of persisted data for the last execution of the description DAG.
+    /// - `base_path`: Base path of persisted data for the last execution of the description DAG. `None` keeps the state in memory.
     /// - `record_readers`: Record readers of all stateful ports.
     /// - `receivers`: Input channels to this sink.
     /// - `input_schemas`: Input data schemas.
+    /// - `watermark`: Committed source positions, updated on every commit.
+    /// - `counters`: Runtime metrics of this node.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         node_handle: NodeHandle,
         sink_factory: &dyn SinkFactory,
-        base_path: &Path,
+        base_path: Option<&Path>,
         record_readers: Arc<RwLock<HashMap<NodeHandle, HashMap<PortHandle, RecordReader>>>>,
         receivers: HashMap<PortHandle, Vec<Receiver<ExecutorOperation>>>,
         input_schemas: HashMap<PortHandle, Schema>,
+        watermark: CommitWatermark,
+        counters: Arc<NodeCounters>,
     ) -> Result<Self, ExecutionError> {
         let mut sink = sink_factory.build(input_schemas)?;
-        let state_meta = init_component(&node_handle, base_path, |e| sink.init(e))?;
+        let state_meta = init_component(&node_handle.to_string(), base_path, |e| sink.init(e))?;
         let master_tx = state_meta.env.create_txn()?;
         let state_writer = StateWriter::new(
             state_meta.meta_db,
@@ -72,6 +82,8 @@ impl SinkNode {
             record_readers,
             master_tx,
             state_writer,
+            watermark,
+            counters,
         })
     }
 }
@@ -93,6 +105,11 @@ impl ReceiverLoop for SinkNode {
         Cow::Owned(self.port_handles[index].to_string())
     }
 
+    fn on_receive(&mut self, index: usize, queue_depth: usize) {
+        self.counters
+            .on_input(self.port_handles[index], queue_depth);
+    }
+
     fn on_op(
         &mut self,
         index: usize,
@@ -109,7 +126,9 @@ impl ReceiverLoop for SinkNode {
     fn on_commit(&mut self, epoch: &Epoch) -> Result<(), ExecutionError> {
         debug!("[{}] Checkpointing - {}", self.node_handle, epoch);
         self.sink.commit(epoch, &self.master_tx)?;
-        self.state_writer.store_commit_info(epoch)
+        self.state_writer.store_commit_info(epoch)?;
+        self.watermark.update(&self.node_handle, epoch);
+        Ok(())
     }
 
     fn on_terminate(&mut self) -> Result<(), ExecutionError> {
@@ -18,24 +18,35 @@ use dozer_types::{
 
 use crate::dag::{
     channels::SourceChannelForwarder,
+    commit_tuner::CommitSizeTuning,
     dag::Edge,
     epoch::EpochManager,
     errors::ExecutionError::{self, InternalError},
     executor_utils::{create_ports_databases_and_fill_downstream_record_readers, init_component},
     forwarder::{SourceChannelManager, StateWriter},
-    node::{NodeHandle, OutputPortDef, PortHandle, Source, SourceFactory},
+    metrics::NodeCounters,
+    node::{NodeHandle, OutputPortDef, PortHandle, Source, SourceFactory, SourceStopper},
     record_store::RecordReader,
 };
 
 use super::{node::Node, ExecutorOperation};
 
+/// Data sent from a source sender to its listener.
+#[derive(Debug)]
+pub(crate) enum SourceMessage {
+    Operation(PortHandle, u64, u64, Operation),
+    /// `(txid, seq_in_tx, op, port)` operations, in order.
+    Batch(Vec<(u64, u64, Operation, PortHandle)>),
+    Heartbeat(u64, u64),
+}
+
 #[derive(Debug)]
 struct InternalChannelSourceForwarder {
-    sender: Sender<(PortHandle, u64, u64, Operation)>,
+    sender: Sender<SourceMessage>,
 }
 
 impl InternalChannelSourceForwarder {
-    pub fn new(sender: Sender<(PortHandle, u64, u64, Operation)>) -> Self {
+    pub fn new(sender: Sender<SourceMessage>) -> Self {
         Self { sender }
     }
 }
@@ -48,7 +59,23 @@ impl SourceChannelForwarder for InternalChannelSourceForwarder {
         op: Operation,
         port: PortHandle,
     ) -> Result<(), ExecutionError> {
-        internal_err!(self.sender.send((port, txid, seq_in_tx, op)))
+        internal_err!(self
+            .sender
+            .send(SourceMessage::Operation(port, txid, seq_in_tx, op)))
+    }
+
+    fn send_batch(
+        &mut self,
+        ops: Vec<(u64, u64, Operation, PortHandle)>,
+    ) -> Result<(), ExecutionError> {
+        if ops.is_empty() {
+            return Ok(());
+        }
+        internal_err!(self.sender.send(SourceMessage::Batch(ops)))
+    }
+
+    fn heartbeat(&mut self, txid: u64, seq_in_tx: u64) -> Result<(), ExecutionError> {
+        internal_err!(self.sender.send(SourceMessage::Heartbeat(txid, seq_in_tx)))
     }
 }
 
@@ -76,15 +103,20 @@ impl SourceSenderNode {
     /// - `last_checkpoint`: Last checkpointed output of this source.
     /// - `sender`: Channel to send data to.
     /// - `running`: If the execution DAG should still be running.
+    /// - `source_stoppers`: Stoppers called when the execution DAG stops, where the source's is added.
     pub fn new(
         node_handle: NodeHandle,
         source_factory: &dyn SourceFactory,
         output_schemas: HashMap<PortHandle, Schema>,
         last_checkpoint: (u64, u64),
-        sender: Sender<(PortHandle, u64, u64, Operation)>,
+        sender: Sender<SourceMessage>,
         running: Arc<AtomicBool>,
+        source_stoppers: &RwLock<Vec<Arc<dyn SourceStopper>>>,
     ) -> Result<Self, ExecutionError> {
         let source = source_factory.build(output_schemas)?;
+        if let Some(stopper) = source.stopper() {
+            source_stoppers.write().push(stopper);
+        }
         let forwarder = InternalChannelSourceForwarder::new(sender);
         Ok(Self {
             node_handle,
@@ -98,9 +130,13 @@ impl SourceSenderNode {
 
 impl Node for SourceSenderNode {
     fn run(mut self) -> Result<(), ExecutionError> {
-        let result = self
-            .source
-            .start(&mut self.forwarder, Some(self.last_checkpoint));
+        // The DAG may have stopped before the stopper was registered
+        let result = if self.running.load(Ordering::SeqCst) {
+            self.source
+                .start(&mut self.forwarder, Some(self.last_checkpoint))
+        } else {
+            Ok(())
+        };
         self.running.store(false, Ordering::SeqCst);
         debug!("[{}-sender] Quit", self.node_handle);
         result
@@ -113,12 +149,14 @@ pub struct SourceListenerNode {
     /// Node handle in description DAG.
     node_handle: NodeHandle,
     /// Output from corresponding source sender.
-    receiver: Receiver<(PortHandle, u64, u64, Operation)>,
+    receiver: Receiver<SourceMessage>,
     /// Receiving timeout.
     timeout: Duration,
     /// If the execution DAG should be running. Used for determining if a `terminate` message should be sent.
     running: Arc<AtomicBool>,
-    /// This node's output channel manager, for communicating to other sources to coordinate terminate and commit, forwarding data, writing metadata and writing port state.
+    /// If the operations already sent by the source sender should be forwarded before terminating.
+    draining: Arc<AtomicBool>,
+    /// This node's output channel manager, for closing epochs, forwarding data, writing metadata and writing port state.
     channel_manager: SourceChannelManager,
 }
 
@@ -128,32 +166,45 @@ impl SourceListenerNode {
     /// - `node_handle`: Node handle in description DAG.
     /// - `receiver`: Channel that the data comes in.
     /// - `timeout`: `Listener timeout. After this timeout, listener will check if commit or terminate need to happen.
-    /// - `base_path`: Base path of persisted data for the last execution of the description DAG.
+    /// - `base_path`: Base path of persisted data for the last execution of the description DAG. `None` keeps the state in memory.
     /// - `output_ports`: Output port definition of the source in description DAG.
     /// - `record_readers`: Record readers of all stateful ports.
     /// - `senders`: Output channels from this processor.
     /// - `edges`: All edges in the description DAG, used for creating record readers for input ports which is connected to this processor's stateful output ports.
     /// - `running`: If the execution DAG should still be running.
-    /// - `epoch_manager`: Used for coordinating commit and terminate between sources. Shared by all sources.
+    /// - `draining`: If the source sender should quit before this node terminates.
+    /// - `commit_sz`: Number of operations after which a commit is triggered.
+    /// - `commit_sz_tuning`: Bounds within which `commit_sz` adapts to commit latency. `None` keeps it fixed.
+    /// - `max_duration_between_commits`: Time after which a commit is triggered.
+    /// - `heartbeat_interval`: Minimum time between commits triggered by source heartbeats. `None` ignores heartbeats.
+    /// - `epoch_manager`: Numbers the epochs of every source. Shared by all sources.
     /// - `output_schemas`: Output data schemas.
+    /// - `start_seq`: Last checkpointed output of this source.
+    /// - `counters`: Runtime metrics of this node.
+    /// - `full_channel_warning`: Time after which a send blocked on a full output channel is logged. `None` disables the warning.
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         node_handle: NodeHandle,
-        receiver: Receiver<(PortHandle, u64, u64, Operation)>,
+        receiver: Receiver<SourceMessage>,
         timeout: Duration,
-        base_path: &Path,
+        base_path: Option<&Path>,
         output_ports: &[OutputPortDef],
         record_readers: Arc<RwLock<HashMap<NodeHandle, HashMap<PortHandle, RecordReader>>>>,
         senders: HashMap<PortHandle, Vec<Sender<ExecutorOperation>>>,
         edges: &[Edge],
         running: Arc<AtomicBool>,
+        draining: Arc<AtomicBool>,
         commit_sz: u32,
+        commit_sz_tuning: Option<CommitSizeTuning>,
         max_duration_between_commits: Duration,
+        heartbeat_interval: Option<Duration>,
         epoch_manager: Arc<EpochManager>,
         output_schemas: HashMap<PortHandle, Schema>,
         start_seq: (u64, u64),
+        counters: Arc<NodeCounters>,
+        full_channel_warning: Option<Duration>,
     ) -> Result<Self, ExecutionError> {
-        let state_meta = init_component(&node_handle, base_path, |_| Ok(()))?;
+        let state_meta = init_component(&node_handle.to_string(), base_path, |_| Ok(()))?;
         let (master_tx, port_databases) =
             create_ports_databases_and_fill_downstream_record_readers(
                 &node_handle,
@@ -173,33 +224,46 @@ impl SourceListenerNode {
             )?,
             true,
             commit_sz,
+            commit_sz_tuning,
             max_duration_between_commits,
+            heartbeat_interval,
             epoch_manager,
             start_seq,
+            counters,
+            full_channel_warning,
         );
         Ok(Self {
             node_handle,
             receiver,
             timeout,
             running,
+            draining,
             channel_manager,
         })
     }
 }
 
 impl SourceListenerNode {
-    /// Returns if the node should terminate.
+    /// Returns if the node should terminate. `sender_quit` tells if the source sender has quit and all its data was received.
     fn send_and_trigger_commit_if_needed(
         &mut self,
-        data: Option<(PortHandle, u64, u64, Operation)>,
+        data: Option<SourceMessage>,
+        sender_quit: bool,
     ) -> Result<bool, ExecutionError> {
-        // First check if termination was requested.
-        let terminating = !self.running.load(Ordering::SeqCst);
+        // First check if termination was requested. When draining, wait for the sender to quit.
+        let terminating = !self.running.load(Ordering::SeqCst)
+            && (sender_quit || !self.draining.load(Ordering::SeqCst));
         // If this commit was not requested with termination at the start, we shouldn't terminate either.
         let terminating = match data {
-            Some((port, txid, seq_in_tx, op)) => self
+            Some(SourceMessage::Operation(port, txid, seq_in_tx, op)) => self
                 .channel_manager
                 .send_and_trigger_commit_if_needed(txid, seq_in_tx, op, port, terminating)?,
+            Some(SourceMessage::Batch(ops)) => self
+                .channel_manager
+                .send_batch_and_trigger_commit_if_needed(ops, terminating)?,
+            Some(SourceMessage::Heartbeat(txid, seq_in_tx)) => self
+                .channel_manager
+                .heartbeat_and_trigger_commit_if_needed(txid, seq_in_tx, terminating)?,
             None => self.channel_manager.trigger_commit_if_needed(terminating)?,
         };
         if terminating {
@@ -213,18 +277,21 @@ impl SourceListenerNode {
 impl Node for SourceListenerNode {
     fn run(mut self) -> Result<(), ExecutionError> {
         loop {
-            match self.receiver.recv_timeout(self.timeout) {
+            // Wake up when a commit is due, so an idle source doesn't hold back a partial batch.
+            let timeout = self.timeout.min(self.channel_manager.time_until_commit());
+            match self.receiver.recv_timeout(timeout) {
                 Ok(data) => {
-                    if self.send_and_trigger_commit_if_needed(Some(data))? {
+                    if self.send_and_trigger_commit_if_needed(Some(data), false)? {
                         return Ok(());
                     }
                 }
                 Err(e) => {
-                    if self.send_and_trigger_commit_if_needed(None)? {
+                    let sender_quit = e == RecvTimeoutError::Disconnected;
+                    if self.send_and_trigger_commit_if_needed(None, sender_quit)? {
                         return Ok(());
                     }
                     // Channel disconnected but running flag not set to false, the source sender must have panicked.
-                    if self.running.load(Ordering::SeqCst) && e == RecvTimeoutError::Disconnected {
+                    if self.running.load(Ordering::SeqCst) && sender_quit {
                         return Err(ExecutionError::ChannelDisconnected);
                     }
                 }
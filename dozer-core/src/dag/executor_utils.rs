@@ -9,7 +9,7 @@ use crate::dag::record_store::RecordReader;
 use crate::storage::common::Database;
 use crate::storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
 use crossbeam::channel::{bounded, Receiver, Select, Sender};
-use dozer_types::types::{Operation, Schema};
+use dozer_types::types::{KeyMode, Operation, Schema};
 use std::collections::HashMap;
 use std::path::Path;
 
@@ -25,18 +25,31 @@ impl StorageMetadata {
 }
 
 pub(crate) fn init_component<F>(
-    node_handle: &NodeHandle,
-    base_path: &Path,
+    env_name: &str,
+    base_path: Option<&Path>,
     mut init_f: F,
 ) -> Result<StorageMetadata, ExecutionError>
 where
     F: FnMut(&mut LmdbEnvironmentManager) -> Result<(), ExecutionError>,
 {
-    let mut env = LmdbEnvironmentManager::create(base_path, format!("{}", node_handle).as_str())?;
+    let mut env = match base_path {
+        Some(base_path) => LmdbEnvironmentManager::create(base_path, env_name)?,
+        None => LmdbEnvironmentManager::create_in_memory(),
+    };
     let db = env.open_database(METADATA_DB_NAME, false)?;
     init_f(&mut env)?;
     Ok(StorageMetadata::new(env, db))
 }
+
+/// Name of the environment of `instance` of a partitioned processor. The first instance uses the
+/// environment of the node, so that its checkpoint is found like any other node's.
+pub(crate) fn partition_env_name(handle: &NodeHandle, instance: usize) -> String {
+    match instance {
+        0 => handle.to_string(),
+        _ => format!("{}_part{}", handle, instance),
+    }
+}
+
 #[inline]
 pub(crate) fn init_select(receivers: &Vec<Receiver<ExecutorOperation>>) -> Select {
     let mut sel = Select::new();
@@ -201,13 +214,17 @@ pub(crate) fn create_ports_databases_and_fill_downstream_record_readers(
 
     for (state_options, port) in port_databases.iter().zip(output_ports.iter()) {
         if let Some(state_options) = state_options {
+            let key_mode = match state_options.typ {
+                OutputPortType::StatefulWithPrimaryKeyLookup { key_mode, .. } => key_mode,
+                _ => KeyMode::Position,
+            };
             for endpoint in get_inputs_for_output(edges, handle, &port.handle) {
                 record_stores
                     .get_mut(&endpoint.node)
                     .expect("Record store HashMap must be created for every node upfront")
                     .insert(
                         endpoint.port,
-                        RecordReader::new(master_tx.clone(), state_options.db),
+                        RecordReader::new(master_tx.clone(), state_options.db, key_mode),
                     );
             }
         }
@@ -1,19 +1,20 @@
 #![allow(clippy::too_many_arguments)]
 use crate::dag::channels::ProcessorChannelForwarder;
+use crate::dag::commit_tuner::{CommitSizeTuner, CommitSizeTuning};
 use crate::dag::dag_metadata::SOURCE_ID_IDENTIFIER;
 use crate::dag::epoch::{Epoch, EpochManager};
 use crate::dag::errors::ExecutionError;
-use crate::dag::errors::ExecutionError::{InternalError, InvalidPortHandle};
+use crate::dag::errors::ExecutionError::{ChannelDisconnected, InvalidPortHandle};
 use crate::dag::executor::ExecutorOperation;
 use crate::dag::executor_utils::StateOptions;
+use crate::dag::metrics::NodeCounters;
 use crate::dag::node::{NodeHandle, PortHandle};
 use crate::dag::record_store::{RecordWriter, RecordWriterUtils};
 use crate::storage::common::Database;
 
 use crate::storage::lmdb_storage::SharedTransaction;
-use crossbeam::channel::Sender;
-use dozer_types::internal_err;
-use dozer_types::log::debug;
+use crossbeam::channel::{SendTimeoutError, Sender, TrySendError};
+use dozer_types::log::{debug, warn};
 use dozer_types::types::{Operation, Schema};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -60,6 +61,18 @@ impl StateWriter {
         }
     }
 
+    fn store_ops(
+        &mut self,
+        ops: Vec<Operation>,
+        port: &PortHandle,
+    ) -> Result<Vec<Operation>, ExecutionError> {
+        if let Some(writer) = self.record_writers.get_mut(port) {
+            writer.write_batch(ops, &self.tx)
+        } else {
+            Ok(ops)
+        }
+    }
+
     pub fn store_commit_info(&mut self, epoch_details: &Epoch) -> Result<(), ExecutionError> {
         //
         for (source, (txid, seq_in_tx)) in &epoch_details.details {
@@ -85,15 +98,47 @@ struct ChannelManager {
     senders: HashMap<PortHandle, Vec<Sender<ExecutorOperation>>>,
     state_writer: StateWriter,
     stateful: bool,
+    counters: Arc<NodeCounters>,
+    full_channel_warning: Option<Duration>,
 }
 
 impl ChannelManager {
     #[inline]
-    fn send_op(&mut self, mut op: Operation, port_id: PortHandle) -> Result<(), ExecutionError> {
+    fn send_op(&mut self, op: Operation, port_id: PortHandle) -> Result<(), ExecutionError> {
+        let op = self.store_op(op, port_id)?;
+        self.send_stored_op(op, port_id)
+    }
+
+    /// Writes `op` to the state of `port_id`, if the node is stateful.
+    #[inline]
+    fn store_op(
+        &mut self,
+        op: Operation,
+        port_id: PortHandle,
+    ) -> Result<Operation, ExecutionError> {
         if self.stateful {
-            op = self.state_writer.store_op(op, &port_id)?;
+            self.state_writer.store_op(op, &port_id)
+        } else {
+            Ok(op)
         }
+    }
 
+    /// Writes `ops` to the state of `port_id` at once, if the node is stateful.
+    fn store_ops(
+        &mut self,
+        ops: Vec<Operation>,
+        port_id: PortHandle,
+    ) -> Result<Vec<Operation>, ExecutionError> {
+        if self.stateful {
+            self.state_writer.store_ops(ops, &port_id)
+        } else {
+            Ok(ops)
+        }
+    }
+
+    /// Sends `op`, as returned by [`store_op`](Self::store_op), to the receivers of `port_id`.
+    #[inline]
+    fn send_stored_op(&mut self, op: Operation, port_id: PortHandle) -> Result<(), ExecutionError> {
         let senders = self
             .senders
             .get(&port_id)
@@ -107,33 +152,82 @@ impl ChannelManager {
 
         if let Some((last_sender, senders)) = senders.split_last() {
             for sender in senders {
-                internal_err!(sender.send(exec_op.clone()))?;
+                self.send_or_wait(sender, exec_op.clone(), port_id)?;
             }
-            internal_err!(last_sender.send(exec_op))?;
+            self.send_or_wait(last_sender, exec_op, port_id)?;
         }
+        self.counters.on_output(port_id);
 
         Ok(())
     }
 
+    /// Sends `op`, recording how long it waits if the channel is full. Fails with
+    /// [`ChannelDisconnected`] if the receiving node is gone.
+    fn send_or_wait(
+        &self,
+        sender: &Sender<ExecutorOperation>,
+        op: ExecutorOperation,
+        port_id: PortHandle,
+    ) -> Result<(), ExecutionError> {
+        let op = match sender.try_send(op) {
+            Ok(()) => return Ok(()),
+            Err(TrySendError::Full(op)) => op,
+            Err(TrySendError::Disconnected(_)) => return Err(ChannelDisconnected),
+        };
+
+        let blocked_since = Instant::now();
+        let result = match self.full_channel_warning {
+            Some(threshold) => match sender.send_timeout(op, threshold) {
+                Err(SendTimeoutError::Timeout(op)) => {
+                    warn!(
+                        "[{}] Output port {} has been full for more than {:?}",
+                        self.owner, port_id, threshold
+                    );
+                    sender.send(op).map_err(|_| ChannelDisconnected)
+                }
+                result => result.map_err(|_| ChannelDisconnected),
+            },
+            None => sender.send(op).map_err(|_| ChannelDisconnected),
+        };
+        self.counters.on_blocked(port_id, blocked_since.elapsed());
+        result
+    }
+
     fn send_terminate(&self) -> Result<(), ExecutionError> {
-        for senders in self.senders.values() {
+        for (port_id, senders) in &self.senders {
             for sender in senders {
-                internal_err!(sender.send(ExecutorOperation::Terminate))?;
+                self.send_or_wait(sender, ExecutorOperation::Terminate, *port_id)?;
             }
         }
 
         Ok(())
     }
 
+    /// Fill ratio of the fullest bounded output channel.
+    fn get_channel_pressure(&self) -> f64 {
+        self.senders
+            .values()
+            .flatten()
+            .filter_map(|sender| match sender.capacity() {
+                Some(capacity) if capacity > 0 => Some(sender.len() as f64 / capacity as f64),
+                _ => None,
+            })
+            .fold(0.0, f64::max)
+    }
+
     fn store_and_send_commit(&mut self, epoch: &Epoch) -> Result<(), ExecutionError> {
         debug!("[{}] Checkpointing - {}", self.owner, &epoch);
         self.state_writer.store_commit_info(epoch)?;
 
-        for senders in &self.senders {
-            for sender in senders.1 {
-                internal_err!(sender.send(ExecutorOperation::Commit {
-                    epoch: epoch.clone()
-                }))?;
+        for (port_id, senders) in &self.senders {
+            for sender in senders {
+                self.send_or_wait(
+                    sender,
+                    ExecutorOperation::Commit {
+                        epoch: epoch.clone(),
+                    },
+                    *port_id,
+                )?;
             }
         }
 
@@ -144,12 +238,16 @@ impl ChannelManager {
         senders: HashMap<PortHandle, Vec<Sender<ExecutorOperation>>>,
         state_writer: StateWriter,
         stateful: bool,
+        counters: Arc<NodeCounters>,
+        full_channel_warning: Option<Duration>,
     ) -> Self {
         Self {
             owner,
             senders,
             state_writer,
             stateful,
+            counters,
+            full_channel_warning,
         }
     }
 }
@@ -161,9 +259,12 @@ pub(crate) struct SourceChannelManager {
     curr_txid: u64,
     curr_seq_in_tx: u64,
     commit_sz: u32,
+    commit_sz_tuner: Option<CommitSizeTuner>,
     num_uncommited_ops: u32,
     max_duration_between_commits: Duration,
     last_commit_instant: Instant,
+    heartbeat_interval: Option<Duration>,
+    last_heartbeat_commit_instant: Instant,
     epoch_manager: Arc<EpochManager>,
 }
 
@@ -174,19 +275,33 @@ impl SourceChannelManager {
         state_writer: StateWriter,
         stateful: bool,
         commit_sz: u32,
+        commit_sz_tuning: Option<CommitSizeTuning>,
         max_duration_between_commits: Duration,
+        heartbeat_interval: Option<Duration>,
         epoch_manager: Arc<EpochManager>,
         start_seq: (u64, u64),
+        counters: Arc<NodeCounters>,
+        full_channel_warning: Option<Duration>,
     ) -> Self {
         Self {
-            manager: ChannelManager::new(owner.clone(), senders, state_writer, stateful),
+            manager: ChannelManager::new(
+                owner.clone(),
+                senders,
+                state_writer,
+                stateful,
+                counters,
+                full_channel_warning,
+            ),
             curr_txid: start_seq.0,
             curr_seq_in_tx: start_seq.1,
             source_handle: owner,
             commit_sz,
+            commit_sz_tuner: commit_sz_tuning.map(|tuning| CommitSizeTuner::new(tuning, commit_sz)),
             num_uncommited_ops: 0,
             max_duration_between_commits,
             last_commit_instant: Instant::now(),
+            heartbeat_interval,
+            last_heartbeat_commit_instant: Instant::now(),
             epoch_manager,
         }
     }
@@ -196,26 +311,51 @@ impl SourceChannelManager {
             || self.last_commit_instant.elapsed() >= self.max_duration_between_commits
     }
 
+    /// Returns the time left until a commit is due, whether data arrives or not.
+    pub fn time_until_commit(&self) -> Duration {
+        self.max_duration_between_commits
+            .saturating_sub(self.last_commit_instant.elapsed())
+    }
+
+    fn should_commit_heartbeat(&self) -> bool {
+        self.heartbeat_interval.map_or(false, |interval| {
+            self.last_heartbeat_commit_instant.elapsed() >= interval
+        })
+    }
+
     pub fn trigger_commit_if_needed(
         &mut self,
         request_termination: bool,
     ) -> Result<bool, ExecutionError> {
         if request_termination || self.should_commit() {
-            let epoch = self.epoch_manager.wait_for_epoch_close(
-                self.source_handle.clone(),
-                (self.curr_txid, self.curr_seq_in_tx),
-                request_termination,
-            );
-            self.manager
-                .store_and_send_commit(&Epoch::new(epoch.id, epoch.details))?;
-            self.num_uncommited_ops = 0;
-            self.last_commit_instant = Instant::now();
-            Ok(epoch.terminating)
+            self.commit(request_termination)
         } else {
             Ok(false)
         }
     }
 
+    fn commit(&mut self, request_termination: bool) -> Result<bool, ExecutionError> {
+        let commit_start = Instant::now();
+        let epoch = self.epoch_manager.close_epoch(
+            &self.source_handle,
+            (self.curr_txid, self.curr_seq_in_tx),
+            request_termination,
+        );
+        self.manager
+            .store_and_send_commit(&Epoch::new(epoch.id, epoch.details))?;
+        if let Some(tuner) = &mut self.commit_sz_tuner {
+            tuner.on_commit(
+                self.num_uncommited_ops >= self.commit_sz,
+                commit_start.elapsed(),
+                self.manager.get_channel_pressure(),
+            );
+            self.commit_sz = tuner.get_commit_sz();
+        }
+        self.num_uncommited_ops = 0;
+        self.last_commit_instant = Instant::now();
+        Ok(epoch.terminating)
+    }
+
     pub fn send_and_trigger_commit_if_needed(
         &mut self,
         txid: u64,
@@ -228,10 +368,81 @@ impl SourceChannelManager {
         self.curr_txid = txid;
         self.curr_seq_in_tx = seq_in_tx;
         self.manager.send_op(op, port)?;
+        self.manager.counters.on_source_op();
         self.num_uncommited_ops += 1;
         self.trigger_commit_if_needed(request_termination)
     }
 
+    /// Sends the operations of a batch, so that each of them advances the source position and
+    /// counts towards the commit size. Consecutive operations to the same port are stored at
+    /// once, up to the commit size, and a commit is only considered after each such run, so a
+    /// commit due to `max_duration_between_commits` may be delayed by one run. Termination is only
+    /// requested after the last operation, so that the whole batch is sent before the epoch closes.
+    pub fn send_batch_and_trigger_commit_if_needed(
+        &mut self,
+        ops: Vec<(u64, u64, Operation, PortHandle)>,
+        request_termination: bool,
+    ) -> Result<bool, ExecutionError> {
+        let mut ops = ops.into_iter().peekable();
+        if ops.peek().is_none() {
+            return self.trigger_commit_if_needed(request_termination);
+        }
+        while let Some((txid, seq_in_tx, op, port)) = ops.next() {
+            let max_run_len = self
+                .commit_sz
+                .saturating_sub(self.num_uncommited_ops)
+                .max(1) as usize;
+            let mut positions = vec![(txid, seq_in_tx)];
+            let mut run = vec![op];
+            while run.len() < max_run_len {
+                match ops.next_if(|(_, _, _, next_port)| *next_port == port) {
+                    Some((txid, seq_in_tx, op, _)) => {
+                        positions.push((txid, seq_in_tx));
+                        run.push(op);
+                    }
+                    None => break,
+                }
+            }
+
+            let run = self.manager.store_ops(run, port)?;
+            for ((txid, seq_in_tx), op) in positions.into_iter().zip(run) {
+                self.curr_txid = txid;
+                self.curr_seq_in_tx = seq_in_tx;
+                self.manager.send_stored_op(op, port)?;
+                self.manager.counters.on_source_op();
+                self.num_uncommited_ops += 1;
+            }
+            let request_termination = request_termination && ops.peek().is_none();
+            if self.trigger_commit_if_needed(request_termination)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Advances the source position without data. If heartbeats are enabled, an empty epoch is committed
+    /// at most once per heartbeat interval, so that the checkpoint follows the source while it is idle.
+    pub fn heartbeat_and_trigger_commit_if_needed(
+        &mut self,
+        txid: u64,
+        seq_in_tx: u64,
+        request_termination: bool,
+    ) -> Result<bool, ExecutionError> {
+        if self.heartbeat_interval.is_none() {
+            return self.trigger_commit_if_needed(request_termination);
+        }
+        if (txid, seq_in_tx) > (self.curr_txid, self.curr_seq_in_tx) {
+            self.curr_txid = txid;
+            self.curr_seq_in_tx = seq_in_tx;
+        }
+        if request_termination || self.should_commit() || self.should_commit_heartbeat() {
+            self.last_heartbeat_commit_instant = Instant::now();
+            self.commit(request_termination)
+        } else {
+            Ok(false)
+        }
+    }
+
     pub fn terminate(&mut self) -> Result<(), ExecutionError> {
         self.manager.send_terminate()
     }
@@ -240,6 +451,8 @@ impl SourceChannelManager {
 #[derive(Debug)]
 pub(crate) struct ProcessorChannelManager {
     manager: ChannelManager,
+    /// Operations sent by the processor while buffering, in order.
+    buffer: Option<Vec<(Operation, PortHandle)>>,
 }
 
 impl ProcessorChannelManager {
@@ -248,10 +461,45 @@ impl ProcessorChannelManager {
         senders: HashMap<PortHandle, Vec<Sender<ExecutorOperation>>>,
         state_writer: StateWriter,
         stateful: bool,
+        counters: Arc<NodeCounters>,
+        full_channel_warning: Option<Duration>,
     ) -> Self {
         Self {
-            manager: ChannelManager::new(owner, senders, state_writer, stateful),
+            manager: ChannelManager::new(
+                owner,
+                senders,
+                state_writer,
+                stateful,
+                counters,
+                full_channel_warning,
+            ),
+            buffer: None,
+        }
+    }
+
+    /// Holds back the operations sent by the processor until [`flush`](Self::flush) or
+    /// [`discard`](Self::discard), so that a failed operation produces no output.
+    pub fn begin_buffering(&mut self) {
+        self.buffer = Some(vec![]);
+    }
+
+    /// Stores all the buffered operations, then sends them. An error while storing leaves
+    /// nothing sent.
+    pub fn flush(&mut self) -> Result<(), ExecutionError> {
+        let buffer = self.buffer.take().unwrap_or_default();
+        let mut stored = Vec::with_capacity(buffer.len());
+        for (op, port) in buffer {
+            stored.push((self.manager.store_op(op, port)?, port));
         }
+        for (op, port) in stored {
+            self.manager.send_stored_op(op, port)?;
+        }
+        Ok(())
+    }
+
+    /// Drops the buffered operations.
+    pub fn discard(&mut self) {
+        self.buffer = None;
     }
 
     pub fn store_and_send_commit(&mut self, epoch: &Epoch) -> Result<(), ExecutionError> {
@@ -265,6 +513,12 @@ impl ProcessorChannelManager {
 
 impl ProcessorChannelForwarder for ProcessorChannelManager {
     fn send(&mut self, op: Operation, port: PortHandle) -> Result<(), ExecutionError> {
-        self.manager.send_op(op, port)
+        match &mut self.buffer {
+            Some(buffer) => {
+                buffer.push((op, port));
+                Ok(())
+            }
+            None => self.manager.send_op(op, port),
+        }
     }
 }
@@ -0,0 +1,149 @@
+use crate::dag::dag::Endpoint;
+use crate::dag::node::{NodeHandle, PortHandle};
+use dozer_types::parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Counters of a node, read from [`DagMetrics`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NodeMetrics {
+    /// Operations sent by a source, or received by a processor or a sink.
+    pub operations: u64,
+    /// Operations received on each input port.
+    pub records_in: HashMap<PortHandle, u64>,
+    /// Operations sent on each output port.
+    pub records_out: HashMap<PortHandle, u64>,
+    /// Operations left in the channels of each input port, the last time the node received one.
+    pub queue_depth: HashMap<PortHandle, u64>,
+    /// Time spent waiting for room in the full channels of each output port.
+    pub blocked_time: HashMap<PortHandle, Duration>,
+}
+
+/// Counters updated by the thread of a node. The ports are known when the node starts, so
+/// updating a counter never takes a lock.
+#[derive(Debug, Default)]
+pub(crate) struct NodeCounters {
+    operations: AtomicU64,
+    records_in: HashMap<PortHandle, AtomicU64>,
+    records_out: HashMap<PortHandle, AtomicU64>,
+    queue_depth: HashMap<PortHandle, AtomicU64>,
+    /// In microseconds
+    blocked_time: HashMap<PortHandle, AtomicU64>,
+}
+
+impl NodeCounters {
+    pub fn new(input_ports: &[PortHandle], output_ports: &[PortHandle]) -> Self {
+        let counters = |ports: &[PortHandle]| {
+            ports
+                .iter()
+                .map(|port| (*port, AtomicU64::new(0)))
+                .collect::<HashMap<PortHandle, AtomicU64>>()
+        };
+        Self {
+            operations: AtomicU64::new(0),
+            records_in: counters(input_ports),
+            records_out: counters(output_ports),
+            queue_depth: counters(input_ports),
+            blocked_time: counters(output_ports),
+        }
+    }
+
+    /// Counts an operation sent by a source.
+    pub fn on_source_op(&self) {
+        self.operations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts an operation received on `port`, with `queue_depth` operations left behind it.
+    pub fn on_input(&self, port: PortHandle, queue_depth: usize) {
+        self.operations.fetch_add(1, Ordering::Relaxed);
+        if let Some(count) = self.records_in.get(&port) {
+            count.fetch_add(1, Ordering::Relaxed);
+        }
+        if let Some(depth) = self.queue_depth.get(&port) {
+            depth.store(queue_depth as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// Counts an operation sent on `port`.
+    pub fn on_output(&self, port: PortHandle) {
+        if let Some(count) = self.records_out.get(&port) {
+            count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Adds the time a send on `port` waited for a full channel.
+    pub fn on_blocked(&self, port: PortHandle, duration: Duration) {
+        if let Some(blocked_time) = self.blocked_time.get(&port) {
+            blocked_time.fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+        }
+    }
+
+    fn get(&self) -> NodeMetrics {
+        let values = |counters: &HashMap<PortHandle, AtomicU64>| {
+            counters
+                .iter()
+                .map(|(port, count)| (*port, count.load(Ordering::Relaxed)))
+                .collect()
+        };
+        NodeMetrics {
+            operations: self.operations.load(Ordering::Relaxed),
+            records_in: values(&self.records_in),
+            records_out: values(&self.records_out),
+            queue_depth: values(&self.queue_depth),
+            blocked_time: self
+                .blocked_time
+                .iter()
+                .map(|(port, micros)| {
+                    (*port, Duration::from_micros(micros.load(Ordering::Relaxed)))
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Runtime counters of all the nodes of a DAG, shared with the executor.
+#[derive(Debug, Clone, Default)]
+pub struct DagMetrics {
+    nodes: Arc<RwLock<HashMap<NodeHandle, Arc<NodeCounters>>>>,
+}
+
+impl DagMetrics {
+    pub(crate) fn register_node(
+        &self,
+        node: NodeHandle,
+        input_ports: &[PortHandle],
+        output_ports: &[PortHandle],
+    ) -> Arc<NodeCounters> {
+        let counters = Arc::new(NodeCounters::new(input_ports, output_ports));
+        self.nodes.write().insert(node, counters.clone());
+        counters
+    }
+
+    /// Returns the current counters of every node.
+    pub fn get(&self) -> HashMap<NodeHandle, NodeMetrics> {
+        self.nodes
+            .read()
+            .iter()
+            .map(|(node, counters)| (node.clone(), counters.get()))
+            .collect()
+    }
+
+    /// Returns the output port which spent the most time blocked on full channels, which is the
+    /// upstream end of the slowest edge. `None` if no send has blocked yet.
+    pub fn get_most_blocked_output(&self) -> Option<(Endpoint, Duration)> {
+        self.get()
+            .into_iter()
+            .flat_map(|(node, metrics)| {
+                metrics
+                    .blocked_time
+                    .into_iter()
+                    .map(move |(port, blocked_time)| {
+                        (Endpoint::new(node.clone(), port), blocked_time)
+                    })
+            })
+            .filter(|(_, blocked_time)| !blocked_time.is_zero())
+            .max_by_key(|(_, blocked_time)| *blocked_time)
+    }
+}
@@ -4,11 +4,12 @@ use crate::dag::errors::ExecutionError;
 use crate::dag::record_store::RecordReader;
 use crate::storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
 
-use dozer_types::types::{Operation, Schema};
+use dozer_types::types::{KeyMode, Operation, Schema};
 use std::collections::HashMap;
 use std::fmt::{Debug, Display, Formatter};
 
 use std::str::from_utf8;
+use std::sync::Arc;
 
 //pub type NodeHandle = String;
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -74,6 +75,7 @@ pub enum OutputPortType {
     StatefulWithPrimaryKeyLookup {
         retr_old_records_for_deletes: bool,
         retr_old_records_for_updates: bool,
+        key_mode: KeyMode,
     },
     AutogenRowKeyLookup,
 }
@@ -118,6 +120,17 @@ pub trait Source: Debug {
         fw: &mut dyn SourceChannelForwarder,
         from: Option<(u64, u64)>,
     ) -> Result<(), ExecutionError>;
+    /// Returns the handle the executor uses to make [`start`](Self::start) return when the DAG
+    /// stops. Sources which may wait for data forever must provide one, or stopping the DAG waits
+    /// for them forever. `None` suits sources which return once their data runs out.
+    fn stopper(&self) -> Option<Arc<dyn SourceStopper>> {
+        None
+    }
+}
+
+/// Makes a running [`Source`] return from [`start`](Source::start). Called from another thread.
+pub trait SourceStopper: Send + Sync + Debug {
+    fn stop(&self);
 }
 
 pub trait ProcessorFactory: Send + Sync + Debug {
@@ -138,10 +151,35 @@ pub trait ProcessorFactory: Send + Sync + Debug {
         input_schemas: HashMap<PortHandle, Schema>,
         output_schemas: HashMap<PortHandle, Schema>,
     ) -> Result<Box<dyn Processor>, ExecutionError>;
+    /// Number of processor instances to run, each in its own thread. Above 1, every input port
+    /// must have a [`partition_by`](Self::partition_by) key and every output port must be stateless.
+    /// Every instance keeps its own state, so changing it requires rebuilding the processor's state.
+    fn parallelism(&self) -> usize {
+        1
+    }
+    /// Indexes of the fields deciding which instance processes a record received on `port`.
+    /// Records with the same values in these fields always go to the same instance.
+    fn partition_by(&self, _port: &PortHandle) -> Option<Vec<usize>> {
+        None
+    }
+    /// Whether the processor looks up records through the [`RecordReader`] of input `port`.
+    /// The output port connected to it must then be stateful.
+    fn requires_record_lookup(&self, _port: &PortHandle) -> bool {
+        false
+    }
 }
 
 pub trait Processor: Debug {
     fn init(&mut self, state: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError>;
+    /// Called at the end of every epoch, before [`commit`](Self::commit), to forward the
+    /// operations the processor held back during the epoch and reset its per-epoch state.
+    fn flush(
+        &mut self,
+        _fw: &mut dyn ProcessorChannelForwarder,
+        _tx: &SharedTransaction,
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
     fn commit(&self, epoch_details: &Epoch, tx: &SharedTransaction) -> Result<(), ExecutionError>;
     fn process(
         &mut self,
@@ -164,6 +202,11 @@ pub trait SinkFactory: Send + Sync + Debug {
         &self,
         input_schemas: HashMap<PortHandle, Schema>,
     ) -> Result<Box<dyn Sink>, ExecutionError>;
+    /// Whether the sink looks up records through the [`RecordReader`] of input `port`.
+    /// The output port connected to it must then be stateful.
+    fn requires_record_lookup(&self, _port: &PortHandle) -> bool {
+        false
+    }
 }
 
 pub trait Sink: Debug {
@@ -1,18 +1,28 @@
 use crate::dag::errors::ExecutionError;
-use crate::dag::errors::ExecutionError::{UnsupportedDeleteOperation, UnsupportedUpdateOperation};
 use crate::dag::node::OutputPortType;
 
-use crate::storage::common::Database;
+use crate::storage::common::{Database, Seek};
 use crate::storage::errors::StorageError;
-use crate::storage::errors::StorageError::SerializationError;
-use crate::storage::lmdb_storage::SharedTransaction;
+use crate::storage::errors::StorageError::{DeserializationError, SerializationError};
+use crate::storage::lmdb_storage::{PutBatchOptions, SharedTransaction};
+use crate::storage::prefix_transaction::PrefixTransaction;
 use dozer_types::bincode;
-use dozer_types::types::{Field, FieldDefinition, FieldType, Operation, Record, Schema};
+use dozer_types::types::{Field, FieldDefinition, FieldType, KeyMode, Operation, Record, Schema};
 use std::fmt::{Debug, Formatter};
 
 pub trait RecordWriter {
     fn write(&mut self, op: Operation, tx: &SharedTransaction)
         -> Result<Operation, ExecutionError>;
+
+    /// Writes `ops`, in order. Equivalent to calling [`write`](Self::write) for each of them, but
+    /// lets writers store a batch at once.
+    fn write_batch(
+        &mut self,
+        ops: Vec<Operation>,
+        tx: &SharedTransaction,
+    ) -> Result<Vec<Operation>, ExecutionError> {
+        ops.into_iter().map(|op| self.write(op, tx)).collect()
+    }
 }
 
 impl Debug for dyn RecordWriter {
@@ -34,12 +44,14 @@ impl RecordWriterUtils {
             OutputPortType::StatefulWithPrimaryKeyLookup {
                 retr_old_records_for_updates,
                 retr_old_records_for_deletes,
+                key_mode,
             } => Ok(Box::new(PrimaryKeyLookupRecordWriter::new(
                 db,
                 meta_db,
                 schema,
                 retr_old_records_for_deletes,
                 retr_old_records_for_updates,
+                key_mode,
             ))),
             OutputPortType::AutogenRowKeyLookup => Ok(Box::new(
                 AutogenRowKeyLookupRecordWriter::new(db, meta_db, schema),
@@ -56,9 +68,10 @@ impl RecordWriterUtils {
 struct PrimaryKeyLookupRecordWriter {
     db: Database,
     meta_db: Database,
-    schema: Schema,
     retr_old_records_for_deletes: bool,
     retr_old_records_for_updates: bool,
+    /// The primary key fields, in the order given by the port's `KeyMode`.
+    key_indexes: Vec<usize>,
 }
 
 impl PrimaryKeyLookupRecordWriter {
@@ -68,27 +81,29 @@ impl PrimaryKeyLookupRecordWriter {
         schema: Schema,
         retr_old_records_for_deletes: bool,
         retr_old_records_for_updates: bool,
+        key_mode: KeyMode,
     ) -> Self {
+        let key_indexes = schema.get_primary_key_indexes(key_mode);
         Self {
             db,
             meta_db,
-            schema,
             retr_old_records_for_deletes,
             retr_old_records_for_updates,
+            key_indexes,
         }
     }
 
-    fn write_record(
-        &self,
-        rec: &Record,
-        schema: &Schema,
-        tx: &SharedTransaction,
-    ) -> Result<(), ExecutionError> {
-        let key = rec.get_key(&schema.primary_index);
+    fn serialize_record(&self, rec: &Record) -> Result<(Vec<u8>, Vec<u8>), ExecutionError> {
+        let key = rec.get_key(&self.key_indexes);
         let value = bincode::serialize(&rec).map_err(|e| SerializationError {
             typ: "Record".to_string(),
             reason: Box::new(e),
         })?;
+        Ok((key, value))
+    }
+
+    fn write_record(&self, rec: &Record, tx: &SharedTransaction) -> Result<(), ExecutionError> {
+        let (key, value) = self.serialize_record(rec)?;
         tx.write().put(self.db, key.as_slice(), value.as_slice())?;
         Ok(())
     }
@@ -105,6 +120,32 @@ impl PrimaryKeyLookupRecordWriter {
         })?;
         Ok(r)
     }
+
+    /// Whether the keys of `items` are sorted and greater than the keys already stored, as when
+    /// loading a snapshot ordered by primary key, so they can be appended.
+    fn can_append(
+        &self,
+        items: &[(&[u8], &[u8])],
+        tx: &SharedTransaction,
+    ) -> Result<bool, StorageError> {
+        if !items.windows(2).all(|pair| pair[0].0 < pair[1].0) {
+            return Ok(false);
+        }
+        let first_key = match items.first() {
+            Some((key, _)) => *key,
+            None => return Ok(false),
+        };
+        let tx = tx.read();
+        let cursor = tx.open_ro_cursor(self.db)?;
+        if !cursor.last()? {
+            return Ok(true);
+        }
+        let after_last = match cursor.read()? {
+            Some((last_key, _)) => last_key < first_key,
+            None => true,
+        };
+        Ok(after_last)
+    }
 }
 
 impl RecordWriter for PrimaryKeyLookupRecordWriter {
@@ -115,11 +156,11 @@ impl RecordWriter for PrimaryKeyLookupRecordWriter {
     ) -> Result<Operation, ExecutionError> {
         match op {
             Operation::Insert { new } => {
-                self.write_record(&new, &self.schema, tx)?;
+                self.write_record(&new, tx)?;
                 Ok(Operation::Insert { new })
             }
             Operation::Delete { mut old } => {
-                let key = old.get_key(&self.schema.primary_index);
+                let key = old.get_key(&self.key_indexes);
                 if self.retr_old_records_for_deletes {
                     old = self.retr_record(&key, tx)?;
                 }
@@ -127,19 +168,51 @@ impl RecordWriter for PrimaryKeyLookupRecordWriter {
                 Ok(Operation::Delete { old })
             }
             Operation::Update { mut old, new } => {
-                let key = old.get_key(&self.schema.primary_index);
+                let key = old.get_key(&self.key_indexes);
                 if self.retr_old_records_for_updates {
                     old = self.retr_record(&key, tx)?;
                 }
-                self.write_record(&new, &self.schema, tx)?;
+                self.write_record(&new, tx)?;
                 Ok(Operation::Update { old, new })
             }
         }
     }
+
+    /// A batch of inserts, like the snapshot of a table, is written with a single
+    /// [`put_batch`](SharedTransaction::put_batch).
+    fn write_batch(
+        &mut self,
+        ops: Vec<Operation>,
+        tx: &SharedTransaction,
+    ) -> Result<Vec<Operation>, ExecutionError> {
+        if !ops.iter().all(|op| matches!(op, Operation::Insert { .. })) {
+            return ops.into_iter().map(|op| self.write(op, tx)).collect();
+        }
+
+        let records = ops
+            .iter()
+            .map(|op| match op {
+                Operation::Insert { new } => self.serialize_record(new),
+                _ => unreachable!("Only inserts are written in one batch"),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let items = records
+            .iter()
+            .map(|(key, value)| (key.as_slice(), value.as_slice()))
+            .collect::<Vec<_>>();
+        let append = self.can_append(&items, tx)?;
+        tx.put_batch(self.db, &items, PutBatchOptions { append })?;
+        Ok(ops)
+    }
 }
 
 const DOZER_ROWID: &str = "_DOZER_ROWID";
 
+/// Writes records under an autogenerated `_DOZER_ROWID`.
+///
+/// Incoming deletes and updates don't carry the rowid, so the writer also indexes the rowids by
+/// a hash of the record values, in the `ROWID_INDEX_DATASET_ID` dataset of `meta_db`. A delete or
+/// an update then applies to the first stored record with the same values.
 #[derive(Debug)]
 pub struct AutogenRowKeyLookupRecordWriter {
     db: Database,
@@ -149,6 +222,7 @@ pub struct AutogenRowKeyLookupRecordWriter {
 
 impl AutogenRowKeyLookupRecordWriter {
     const COUNTER_KEY: u16 = 0_u16;
+    const ROWID_INDEX_DATASET_ID: u32 = 0x0000_0001_u32;
 
     pub fn prepare_schema(mut schema: Schema) -> Schema {
         schema.fields.push(FieldDefinition::new(
@@ -203,6 +277,106 @@ impl AutogenRowKeyLookupRecordWriter {
         )?;
         Ok(curr_counter)
     }
+
+    fn get_indexed_rowids(
+        &self,
+        key: &[u8],
+        tx: &SharedTransaction,
+    ) -> Result<Vec<u64>, StorageError> {
+        let mut tx = tx.write();
+        let ptx = PrefixTransaction::new(&mut tx, Self::ROWID_INDEX_DATASET_ID);
+        match ptx.get(self.meta_db, key)? {
+            Some(v) => bincode::deserialize(v).map_err(|e| DeserializationError {
+                typ: "Vec<u64>".to_string(),
+                reason: Box::new(e),
+            }),
+            None => Ok(vec![]),
+        }
+    }
+
+    fn put_indexed_rowids(
+        &self,
+        key: &[u8],
+        rowids: &[u64],
+        tx: &SharedTransaction,
+    ) -> Result<(), StorageError> {
+        let mut tx = tx.write();
+        let mut ptx = PrefixTransaction::new(&mut tx, Self::ROWID_INDEX_DATASET_ID);
+        if rowids.is_empty() {
+            ptx.del(self.meta_db, key, None)?;
+            return Ok(());
+        }
+        let value = bincode::serialize(rowids).map_err(|e| SerializationError {
+            typ: "Vec<u64>".to_string(),
+            reason: Box::new(e),
+        })?;
+        ptx.put(self.meta_db, key, &value)
+    }
+
+    fn index_rowid(
+        &self,
+        values: &[Field],
+        rowid: u64,
+        tx: &SharedTransaction,
+    ) -> Result<(), StorageError> {
+        let key = get_values_hash(values)?;
+        let mut rowids = self.get_indexed_rowids(&key, tx)?;
+        rowids.push(rowid);
+        self.put_indexed_rowids(&key, &rowids, tx)
+    }
+
+    /// Removes the rowid of the first stored record whose values are `values` from the index,
+    /// and returns that rowid and record.
+    fn unindex_record(
+        &self,
+        values: &[Field],
+        tx: &SharedTransaction,
+    ) -> Result<(u64, Record), ExecutionError> {
+        let key = get_values_hash(values)?;
+        let mut rowids = self.get_indexed_rowids(&key, tx)?;
+        let mut found = None;
+        for (i, rowid) in rowids.iter().enumerate() {
+            let record = self.retr_record(*rowid, tx)?;
+            // Different values can share a hash
+            if record.values[..record.values.len() - 1] == *values {
+                found = Some((i, record));
+                break;
+            }
+        }
+
+        let (i, record) = found.ok_or_else(ExecutionError::RecordNotFound)?;
+        let rowid = rowids.remove(i);
+        self.put_indexed_rowids(&key, &rowids, tx)?;
+        Ok((rowid, record))
+    }
+
+    fn retr_record(&self, rowid: u64, tx: &SharedTransaction) -> Result<Record, ExecutionError> {
+        let tx = tx.read();
+        let curr = tx
+            .get(self.db, &Field::UInt(rowid).encode())?
+            .ok_or_else(ExecutionError::RecordNotFound)?;
+
+        let r: Record = bincode::deserialize(curr).map_err(|e| DeserializationError {
+            typ: "Record".to_string(),
+            reason: Box::new(e),
+        })?;
+        Ok(r)
+    }
+}
+
+/// Hashes the serialized `values` with 64-bit FNV-1a, which is stable across runs.
+fn get_values_hash(values: &[Field]) -> Result<[u8; 8], StorageError> {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let bytes = bincode::serialize(values).map_err(|e| SerializationError {
+        typ: "Vec<Field>".to_string(),
+        reason: Box::new(e),
+    })?;
+    let hash = bytes.iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+    });
+    Ok(hash.to_be_bytes())
 }
 
 impl RecordWriter for AutogenRowKeyLookupRecordWriter {
@@ -220,14 +394,23 @@ impl RecordWriter for AutogenRowKeyLookupRecordWriter {
                         && self.schema.primary_index[0] == new.values.len() - 1
                 );
                 self.write_record(&new, &self.schema, tx)?;
+                self.index_rowid(&new.values[..new.values.len() - 1], ctr, tx)?;
                 Ok(Operation::Insert { new })
             }
-            Operation::Update { .. } => Err(UnsupportedUpdateOperation(
-                "AutogenRowsIdLookupRecordWriter does not support update operations".to_string(),
-            )),
-            Operation::Delete { .. } => Err(UnsupportedDeleteOperation(
-                "AutogenRowsIdLookupRecordWriter does not support delete operations".to_string(),
-            )),
+            Operation::Update { old, mut new } => {
+                // The new values keep the rowid of the old ones
+                let (rowid, old) = self.unindex_record(&old.values, tx)?;
+                self.index_rowid(&new.values, rowid, tx)?;
+                new.values.push(Field::UInt(rowid));
+                self.write_record(&new, &self.schema, tx)?;
+                Ok(Operation::Update { old, new })
+            }
+            Operation::Delete { old } => {
+                let (_rowid, old) = self.unindex_record(&old.values, tx)?;
+                tx.write()
+                    .del(self.db, &old.get_key(&self.schema.primary_index), None)?;
+                Ok(Operation::Delete { old })
+            }
         }
     }
 }
@@ -236,11 +419,17 @@ impl RecordWriter for AutogenRowKeyLookupRecordWriter {
 pub struct RecordReader {
     tx: SharedTransaction,
     db: Database,
+    key_mode: KeyMode,
 }
 
 impl RecordReader {
-    pub fn new(tx: SharedTransaction, db: Database) -> Self {
-        Self { tx, db }
+    pub fn new(tx: SharedTransaction, db: Database, key_mode: KeyMode) -> Self {
+        Self { tx, db, key_mode }
+    }
+
+    /// How the upstream port orders the primary key fields in the keys of its records.
+    pub fn key_mode(&self) -> KeyMode {
+        self.key_mode
     }
 
     pub fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StorageError> {
@@ -249,4 +438,27 @@ impl RecordReader {
             .get(self.db, key)
             .map(|b| b.map(|b| b.to_vec()))
     }
+
+    /// Returns the keys and values of all the records whose key starts with `prefix`, in key order.
+    pub fn scan_prefix(&self, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>, StorageError> {
+        let tx = self.tx.read();
+        let cursor = tx.open_ro_cursor(self.db)?;
+
+        let mut result = vec![];
+        if !cursor.seek_gte(prefix)? {
+            return Ok(result);
+        }
+        loop {
+            match cursor.read()? {
+                Some((key, value)) if key.starts_with(prefix) => {
+                    result.push((key.to_vec(), value.to_vec()))
+                }
+                _ => break,
+            }
+            if !cursor.next()? {
+                break;
+            }
+        }
+        Ok(result)
+    }
 }
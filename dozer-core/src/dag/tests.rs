@@ -5,6 +5,8 @@ mod checkpoint;
 #[cfg(test)]
 mod checkpoint_ns;
 #[cfg(test)]
+mod commit_tuner;
+#[cfg(test)]
 mod common;
 #[cfg(test)]
 mod dag_base_create_errors;
@@ -19,8 +21,12 @@ mod dag_recordreader;
 #[cfg(test)]
 mod dag_schemas;
 #[cfg(test)]
+mod epoch;
+#[cfg(test)]
 mod node;
 #[cfg(test)]
+mod record_store;
+#[cfg(test)]
 mod sinks;
 #[cfg(test)]
 mod sources;
@@ -63,6 +63,34 @@ fn test_apps_sorce_smanager_connection_exists() {
     assert!(r.is_err());
 }
 
+#[test]
+fn test_apps_source_manager_split_connection() {
+    let mut asm = AppSourceManager::new();
+    let app_src = AppSource::new(
+        "conn1".to_string(),
+        Arc::new(NoneSourceFactory {}),
+        vec![("table1".to_string(), 1_u16)].into_iter().collect(),
+    )
+    .with_name("conn1_table1".to_string());
+    asm.add(app_src).unwrap();
+    let app_src = AppSource::new(
+        "conn1".to_string(),
+        Arc::new(NoneSourceFactory {}),
+        vec![("table2".to_string(), 2_u16)].into_iter().collect(),
+    )
+    .with_name("conn1_table2".to_string());
+    asm.add(app_src).unwrap();
+
+    let r = asm
+        .get(vec![AppSourceId::new(
+            "table2".to_string(),
+            Some("conn1".to_string()),
+        )])
+        .unwrap();
+    assert_eq!(r.len(), 1);
+    assert_eq!(r[0].source.name, "conn1_table2");
+}
+
 #[test]
 fn test_apps_sorce_smanager_lookup() {
     let mut asm = AppSourceManager::new();
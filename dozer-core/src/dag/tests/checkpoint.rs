@@ -273,3 +273,105 @@ fn test_checkpoint_consistency_resume() {
         Consistency::FullyConsistent(r) => assert_eq!(r, &(100_000, 0)),
     }
 }
+
+#[test]
+fn test_checkpoint_consistency_reset_per_source() {
+    let mut dag = Dag::new();
+    let latch1 = Arc::new(AtomicBool::new(true));
+    let latch2 = Arc::new(AtomicBool::new(true));
+
+    let source1_handle = NodeHandle::new(Some(1), 1.to_string());
+    let sink1_handle = NodeHandle::new(Some(1), 2.to_string());
+    let source2_handle = NodeHandle::new(Some(1), 3.to_string());
+    let source3_handle = NodeHandle::new(Some(1), 4.to_string());
+    let proc_handle = NodeHandle::new(Some(1), 5.to_string());
+    let sink2_handle = NodeHandle::new(Some(1), 6.to_string());
+
+    // SRC1 -> SINK1 shares no node with SRC2, SRC3 -> PROC -> SINK2
+    dag.add_node(
+        NodeType::Source(Arc::new(GeneratorSourceFactory::new(
+            25_000,
+            latch1.clone(),
+            true,
+        ))),
+        source1_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Sink(Arc::new(CountingSinkFactory::new(25_000, latch1))),
+        sink1_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Source(Arc::new(GeneratorSourceFactory::new(
+            10_000,
+            latch2.clone(),
+            true,
+        ))),
+        source2_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Source(Arc::new(GeneratorSourceFactory::new(
+            10_000,
+            latch2.clone(),
+            true,
+        ))),
+        source3_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Processor(Arc::new(NoopJoinProcessorFactory {})),
+        proc_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Sink(Arc::new(CountingSinkFactory::new(20_000, latch2))),
+        sink2_handle.clone(),
+    );
+
+    chk!(dag.connect(
+        Endpoint::new(source1_handle.clone(), GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(sink1_handle, COUNTING_SINK_INPUT_PORT),
+    ));
+    chk!(dag.connect(
+        Endpoint::new(source2_handle.clone(), GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(proc_handle.clone(), 1),
+    ));
+    chk!(dag.connect(
+        Endpoint::new(source3_handle.clone(), GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(proc_handle.clone(), 2),
+    ));
+    chk!(dag.connect(
+        Endpoint::new(proc_handle, DEFAULT_PORT_HANDLE),
+        Endpoint::new(sink2_handle, COUNTING_SINK_INPUT_PORT),
+    ));
+
+    let tmp_dir = chk!(TempDir::new("test"));
+    let mut executor = chk!(DagExecutor::new(
+        &dag,
+        tmp_dir.path(),
+        ExecutorOptions::default(),
+        Arc::new(AtomicBool::new(true))
+    ));
+    chk!(executor.start());
+    assert!(executor.join().is_ok());
+
+    // SRC2 becomes inconsistent. Resetting it deletes PROC, which SRC3 feeds too.
+    LmdbEnvironmentManager::remove(tmp_dir.path(), format!("{}", source2_handle).as_str());
+    let _executor = chk!(DagExecutor::new(
+        &dag,
+        tmp_dir.path(),
+        ExecutorOptions::default(),
+        Arc::new(AtomicBool::new(true))
+    ));
+
+    let r = chk!(DagMetadataManager::new(&dag, tmp_dir.path()));
+    let c = r.get_checkpoint_consistency();
+    let expected = [
+        (source1_handle, (25_000, 0)),
+        (source2_handle, (0, 0)),
+        (source3_handle, (0, 0)),
+    ];
+    for (handle, seq) in expected {
+        match c.get(&handle).unwrap() {
+            Consistency::PartiallyConsistent(_r) => panic!("Wrong consistency"),
+            Consistency::FullyConsistent(r) => assert_eq!(r, &seq),
+        }
+    }
+}
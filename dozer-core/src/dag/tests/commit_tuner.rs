@@ -0,0 +1,59 @@
+use crate::dag::commit_tuner::{CommitSizeTuner, CommitSizeTuning};
+use std::time::Duration;
+
+fn tuner(initial_commit_sz: u32) -> CommitSizeTuner {
+    CommitSizeTuner::new(
+        CommitSizeTuning {
+            min_commit_sz: 100,
+            max_commit_sz: 1_000,
+            target_commit_latency: Duration::from_millis(100),
+        },
+        initial_commit_sz,
+    )
+}
+
+#[test]
+fn test_commit_sz_tuner_bounds_initial_size() {
+    assert_eq!(tuner(10).get_commit_sz(), 100);
+    assert_eq!(tuner(10_000).get_commit_sz(), 1_000);
+    assert_eq!(tuner(500).get_commit_sz(), 500);
+}
+
+#[test]
+fn test_commit_sz_tuner_shrinks() {
+    let mut tuner = tuner(800);
+
+    // Slow commit
+    tuner.on_commit(true, Duration::from_millis(200), 0.0);
+    assert_eq!(tuner.get_commit_sz(), 400);
+
+    // Downstream channels are almost full
+    tuner.on_commit(true, Duration::from_millis(10), 0.9);
+    assert_eq!(tuner.get_commit_sz(), 200);
+
+    tuner.on_commit(true, Duration::from_millis(200), 0.9);
+    tuner.on_commit(true, Duration::from_millis(200), 0.9);
+    assert_eq!(tuner.get_commit_sz(), 100);
+}
+
+#[test]
+fn test_commit_sz_tuner_grows() {
+    let mut tuner = tuner(400);
+
+    tuner.on_commit(true, Duration::from_millis(10), 0.1);
+    assert_eq!(tuner.get_commit_sz(), 500);
+
+    // Commits triggered by time don't need a bigger batch
+    tuner.on_commit(false, Duration::from_millis(10), 0.1);
+    assert_eq!(tuner.get_commit_sz(), 500);
+
+    // Neither fast nor slow enough to change
+    tuner.on_commit(true, Duration::from_millis(80), 0.1);
+    tuner.on_commit(true, Duration::from_millis(10), 0.6);
+    assert_eq!(tuner.get_commit_sz(), 500);
+
+    for _ in 0..10 {
+        tuner.on_commit(true, Duration::from_millis(10), 0.1);
+    }
+    assert_eq!(tuner.get_commit_sz(), 1_000);
+}
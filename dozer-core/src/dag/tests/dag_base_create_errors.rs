@@ -67,7 +67,6 @@ impl SourceFactory for CreateErrSourceFactory {
 }
 
 #[test]
-#[should_panic]
 fn test_create_src_err() {
     let count: u64 = 1_000_000;
 
@@ -114,7 +113,6 @@ fn test_create_src_err() {
 }
 
 #[test]
-#[should_panic]
 fn test_create_src_panic() {
     let count: u64 = 1_000_000;
 
@@ -220,7 +218,6 @@ impl ProcessorFactory for CreateErrProcessorFactory {
 }
 
 #[test]
-#[should_panic]
 fn test_create_proc_err() {
     let count: u64 = 1_000_000;
 
@@ -271,7 +268,6 @@ fn test_create_proc_err() {
 }
 
 #[test]
-#[should_panic]
 fn test_create_proc_panic() {
     let count: u64 = 1_000_000;
 
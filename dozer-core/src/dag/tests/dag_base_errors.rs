@@ -11,14 +11,18 @@ use crate::dag::record_store::RecordReader;
 use crate::dag::tests::dag_base_run::NoopProcessorFactory;
 use crate::dag::tests::sinks::{CountingSinkFactory, COUNTING_SINK_INPUT_PORT};
 use crate::dag::tests::sources::{GeneratorSourceFactory, GENERATOR_SOURCE_OUTPUT_PORT};
+use crate::storage::common::Database;
 use crate::storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
+use crossbeam::channel::unbounded;
 use dozer_types::types::{Field, FieldDefinition, FieldType, Operation, Record, Schema};
 
 use std::collections::HashMap;
 use std::panic;
 
-use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 
 use crate::dag::epoch::Epoch;
 
@@ -111,7 +115,6 @@ impl Processor for ErrorProcessor {
 }
 
 #[test]
-#[should_panic]
 fn test_run_dag_proc_err_panic() {
     let count: u64 = 1_000_000;
 
@@ -148,7 +151,7 @@ fn test_run_dag_proc_err_panic() {
     ));
 
     chk!(dag.connect(
-        Endpoint::new(proc_handle, DEFAULT_PORT_HANDLE),
+        Endpoint::new(proc_handle.clone(), DEFAULT_PORT_HANDLE),
         Endpoint::new(sink_handle, COUNTING_SINK_INPUT_PORT),
     ));
 
@@ -161,11 +164,237 @@ fn test_run_dag_proc_err_panic() {
     ));
 
     chk!(executor.start());
-    assert!(executor.join().is_err());
+    // The nodes next to the processor fail once it's gone, but the processor is named
+    match executor.join() {
+        Err(ExecutionError::NodePanicked { node, message }) => {
+            assert_eq!(node, proc_handle);
+            assert_eq!(message, "Generated error");
+        }
+        result => panic!("Unexpected join result: {:?}", result),
+    }
+}
+
+#[test]
+fn test_run_dag_proc_err_dead_letter() {
+    let count: u64 = 1_000;
+
+    let mut dag = Dag::new();
+    let latch = Arc::new(AtomicBool::new(true));
+
+    let source_handle = NodeHandle::new(None, 1.to_string());
+    let proc_handle = NodeHandle::new(Some(1), 1.to_string());
+    let sink_handle = NodeHandle::new(Some(1), 2.to_string());
+
+    dag.add_node(
+        NodeType::Source(Arc::new(GeneratorSourceFactory::new(count, latch, false))),
+        source_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Processor(Arc::new(ErrorProcessorFactory {
+            err_on: 500,
+            panic: false,
+        })),
+        proc_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Sink(Arc::new(CountingSinkFactory::new(
+            count - 1,
+            Arc::new(AtomicBool::new(true)),
+        ))),
+        sink_handle.clone(),
+    );
+
+    chk!(dag.connect(
+        Endpoint::new(source_handle, GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(proc_handle.clone(), DEFAULT_PORT_HANDLE),
+    ));
+
+    chk!(dag.connect(
+        Endpoint::new(proc_handle.clone(), DEFAULT_PORT_HANDLE),
+        Endpoint::new(sink_handle.clone(), COUNTING_SINK_INPUT_PORT),
+    ));
+
+    let (dead_letter, dead_letters) = unbounded();
+    let tmp_dir = chk!(TempDir::new("test"));
+    let mut executor = chk!(DagExecutor::new(
+        &dag,
+        tmp_dir.path(),
+        ExecutorOptions {
+            dead_letter: Some(dead_letter),
+            ..Default::default()
+        },
+        Arc::new(AtomicBool::new(true))
+    ));
+
+    chk!(executor.start());
+    let metrics = executor.get_metrics();
+    assert!(executor.join().is_ok());
+
+    // The failed operation is skipped, the others go through
+    let (handle, _op, err) = dead_letters.try_recv().unwrap();
+    assert_eq!(handle, proc_handle);
+    assert!(matches!(err, ExecutionError::InvalidOperation(_)));
+    assert!(dead_letters.try_recv().is_err());
+    assert_eq!(
+        metrics.get()[&sink_handle].records_in[&COUNTING_SINK_INPUT_PORT],
+        count - 1
+    );
+}
+
+// Writes state and sends output before failing, which must all be rolled back
+#[derive(Debug)]
+struct PartialErrorProcessorFactory {
+    err_on: u64,
+}
+
+impl ProcessorFactory for PartialErrorProcessorFactory {
+    fn get_output_schema(
+        &self,
+        _output_port: &PortHandle,
+        input_schemas: &HashMap<PortHandle, Schema>,
+    ) -> Result<Schema, ExecutionError> {
+        Ok(input_schemas.get(&DEFAULT_PORT_HANDLE).unwrap().clone())
+    }
+
+    fn get_input_ports(&self) -> Vec<PortHandle> {
+        vec![DEFAULT_PORT_HANDLE]
+    }
+
+    fn get_output_ports(&self) -> Vec<OutputPortDef> {
+        vec![OutputPortDef::new(
+            DEFAULT_PORT_HANDLE,
+            OutputPortType::Stateless,
+        )]
+    }
+
+    fn prepare(
+        &self,
+        _input_schemas: HashMap<PortHandle, Schema>,
+        _output_schemas: HashMap<PortHandle, Schema>,
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn build(
+        &self,
+        _input_schemas: HashMap<PortHandle, Schema>,
+        _output_schemas: HashMap<PortHandle, Schema>,
+    ) -> Result<Box<dyn Processor>, ExecutionError> {
+        Ok(Box::new(PartialErrorProcessor {
+            err_on: self.err_on,
+            count: 0,
+            db: None,
+        }))
+    }
+}
+
+#[derive(Debug)]
+struct PartialErrorProcessor {
+    err_on: u64,
+    count: u64,
+    db: Option<Database>,
+}
+
+impl Processor for PartialErrorProcessor {
+    fn init(&mut self, state: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+        self.db = Some(state.open_database("partial", false)?);
+        Ok(())
+    }
+
+    fn commit(&self, _epoch: &Epoch, _tx: &SharedTransaction) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn process(
+        &mut self,
+        _from_port: PortHandle,
+        op: Operation,
+        fw: &mut dyn ProcessorChannelForwarder,
+        tx: &SharedTransaction,
+        _reader: &HashMap<PortHandle, RecordReader>,
+    ) -> Result<(), ExecutionError> {
+        let db = self.db.unwrap();
+        if tx.read().get(db, b"failed")?.is_some() {
+            return Err(ExecutionError::InvalidDatabase);
+        }
+
+        self.count += 1;
+        if self.count == self.err_on {
+            tx.write().put(db, b"failed", b"")?;
+            fw.send(op, DEFAULT_PORT_HANDLE)?;
+            return Err(ExecutionError::InvalidOperation("Uknown".to_string()));
+        }
+
+        fw.send(op, DEFAULT_PORT_HANDLE)
+    }
+}
+
+#[test]
+fn test_run_dag_proc_err_dead_letter_rolls_back() {
+    let count: u64 = 1_000;
+
+    let mut dag = Dag::new();
+    let latch = Arc::new(AtomicBool::new(true));
+
+    let source_handle = NodeHandle::new(None, 1.to_string());
+    let proc_handle = NodeHandle::new(Some(1), 1.to_string());
+    let sink_handle = NodeHandle::new(Some(1), 2.to_string());
+
+    dag.add_node(
+        NodeType::Source(Arc::new(GeneratorSourceFactory::new(count, latch, false))),
+        source_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Processor(Arc::new(PartialErrorProcessorFactory { err_on: 500 })),
+        proc_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Sink(Arc::new(CountingSinkFactory::new(
+            count - 1,
+            Arc::new(AtomicBool::new(true)),
+        ))),
+        sink_handle.clone(),
+    );
+
+    chk!(dag.connect(
+        Endpoint::new(source_handle, GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(proc_handle.clone(), DEFAULT_PORT_HANDLE),
+    ));
+
+    chk!(dag.connect(
+        Endpoint::new(proc_handle.clone(), DEFAULT_PORT_HANDLE),
+        Endpoint::new(sink_handle.clone(), COUNTING_SINK_INPUT_PORT),
+    ));
+
+    let (dead_letter, dead_letters) = unbounded();
+    let tmp_dir = chk!(TempDir::new("test"));
+    let mut executor = chk!(DagExecutor::new(
+        &dag,
+        tmp_dir.path(),
+        ExecutorOptions {
+            dead_letter: Some(dead_letter),
+            ..Default::default()
+        },
+        Arc::new(AtomicBool::new(true))
+    ));
+
+    chk!(executor.start());
+    let metrics = executor.get_metrics();
+    // The processor fails fatally if the write of the failed operation is kept
+    assert!(executor.join().is_ok());
+
+    let (handle, _op, err) = dead_letters.try_recv().unwrap();
+    assert_eq!(handle, proc_handle);
+    assert!(matches!(err, ExecutionError::InvalidOperation(_)));
+    assert!(dead_letters.try_recv().is_err());
+    // The output sent before the failure is dropped
+    assert_eq!(
+        metrics.get()[&sink_handle].records_in[&COUNTING_SINK_INPUT_PORT],
+        count - 1
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_run_dag_proc_err_2() {
     let count: u64 = 1_000_000;
 
@@ -214,7 +443,7 @@ fn test_run_dag_proc_err_2() {
     ));
 
     chk!(dag.connect(
-        Endpoint::new(proc_err_handle, DEFAULT_PORT_HANDLE),
+        Endpoint::new(proc_err_handle.clone(), DEFAULT_PORT_HANDLE),
         Endpoint::new(sink_handle, COUNTING_SINK_INPUT_PORT),
     ));
 
@@ -227,11 +456,16 @@ fn test_run_dag_proc_err_2() {
     ));
 
     chk!(executor.start());
-    assert!(executor.join().is_err());
+    match executor.join() {
+        Err(ExecutionError::NodeFailed { node, source }) => {
+            assert_eq!(node, proc_err_handle);
+            assert!(matches!(*source, ExecutionError::InvalidOperation(_)));
+        }
+        result => panic!("Unexpected join result: {:?}", result),
+    }
 }
 
 #[test]
-#[should_panic]
 fn test_run_dag_proc_err_3() {
     let count: u64 = 1_000_000;
 
@@ -509,7 +743,6 @@ impl Sink for ErrSink {
 }
 
 #[test]
-#[should_panic]
 fn test_run_dag_sink_err() {
     let count: u64 = 1_000_000;
 
@@ -556,7 +789,6 @@ fn test_run_dag_sink_err() {
 }
 
 #[test]
-#[should_panic]
 fn test_run_dag_sink_err_panic() {
     let count: u64 = 1_000_000;
 
@@ -601,3 +833,118 @@ fn test_run_dag_sink_err_panic() {
     chk!(executor.start());
     assert!(executor.join().is_err());
 }
+
+#[derive(Debug)]
+struct StopErrSinkFactory {
+    running: Arc<AtomicBool>,
+}
+
+impl SinkFactory for StopErrSinkFactory {
+    fn set_input_schema(
+        &self,
+        _input_schemas: &HashMap<PortHandle, Schema>,
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn get_input_ports(&self) -> Vec<PortHandle> {
+        vec![COUNTING_SINK_INPUT_PORT]
+    }
+
+    fn prepare(&self, _input_schemas: HashMap<PortHandle, Schema>) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn build(
+        &self,
+        _input_schemas: HashMap<PortHandle, Schema>,
+    ) -> Result<Box<dyn Sink>, ExecutionError> {
+        Ok(Box::new(StopErrSink {
+            running: self.running.clone(),
+        }))
+    }
+}
+
+/// Blocks on the first operation until the DAG is stopped, then fails.
+#[derive(Debug)]
+struct StopErrSink {
+    running: Arc<AtomicBool>,
+}
+
+impl Sink for StopErrSink {
+    fn init(&mut self, _state: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn commit(&mut self, _epoch: &Epoch, _tx: &SharedTransaction) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn process(
+        &mut self,
+        _from_port: PortHandle,
+        _op: Operation,
+        _state: &SharedTransaction,
+        _reader: &HashMap<PortHandle, RecordReader>,
+    ) -> Result<(), ExecutionError> {
+        while self.running.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(10));
+        }
+        Err(ExecutionError::InvalidOperation(
+            "Generated error".to_string(),
+        ))
+    }
+}
+
+#[test]
+fn test_run_dag_sink_err_after_stop() {
+    let count: u64 = 1_000_000;
+
+    let mut dag = Dag::new();
+    let latch = Arc::new(AtomicBool::new(true));
+    let running = Arc::new(AtomicBool::new(true));
+
+    let source_handle = NodeHandle::new(None, 1.to_string());
+    let proc_handle = NodeHandle::new(Some(1), 1.to_string());
+    let sink_handle = NodeHandle::new(Some(1), 3.to_string());
+
+    dag.add_node(
+        NodeType::Source(Arc::new(GeneratorSourceFactory::new(count, latch, false))),
+        source_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Processor(Arc::new(NoopProcessorFactory {})),
+        proc_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Sink(Arc::new(StopErrSinkFactory {
+            running: running.clone(),
+        })),
+        sink_handle.clone(),
+    );
+
+    chk!(dag.connect(
+        Endpoint::new(source_handle, GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(proc_handle.clone(), DEFAULT_PORT_HANDLE),
+    ));
+
+    chk!(dag.connect(
+        Endpoint::new(proc_handle, DEFAULT_PORT_HANDLE),
+        Endpoint::new(sink_handle, COUNTING_SINK_INPUT_PORT),
+    ));
+
+    let tmp_dir = chk!(TempDir::new("test"));
+    let mut executor = chk!(DagExecutor::new(
+        &dag,
+        tmp_dir.path(),
+        ExecutorOptions::default(),
+        running
+    ));
+
+    chk!(executor.start());
+
+    thread::sleep(Duration::from_millis(500));
+    executor.stop();
+    // The sink fails because the DAG is stopping, so its error must not propagate as a panic
+    assert!(executor.join().is_ok());
+}
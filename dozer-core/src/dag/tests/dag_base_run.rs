@@ -2,28 +2,33 @@ use crate::chk;
 use crate::dag::channels::ProcessorChannelForwarder;
 use crate::dag::dag::{Dag, Endpoint, NodeType, DEFAULT_PORT_HANDLE};
 use crate::dag::errors::ExecutionError;
-use crate::dag::executor::{DagExecutor, ExecutorOptions};
+use crate::dag::executor::{DagExecutor, ExecutorOperation, ExecutorOptions};
+use crate::dag::forwarder::{ProcessorChannelManager, StateWriter};
+use crate::dag::metrics::DagMetrics;
 use crate::dag::node::{
     NodeHandle, OutputPortDef, OutputPortType, PortHandle, Processor, ProcessorFactory,
 };
 use crate::dag::record_store::RecordReader;
 use crate::dag::tests::sinks::{CountingSinkFactory, COUNTING_SINK_INPUT_PORT};
 use crate::dag::tests::sources::{
-    DualPortGeneratorSourceFactory, GeneratorSourceFactory,
+    BlockingSourceFactory, DualPortGeneratorSourceFactory, GeneratorSourceFactory,
     DUAL_PORT_GENERATOR_SOURCE_OUTPUT_PORT_1, DUAL_PORT_GENERATOR_SOURCE_OUTPUT_PORT_2,
     GENERATOR_SOURCE_OUTPUT_PORT,
 };
 use crate::storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
+use crossbeam::channel::bounded;
+use dozer_types::parking_lot::Mutex;
 use dozer_types::types::{Operation, Schema};
 
-use std::collections::HashMap;
-use std::sync::atomic::AtomicBool;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crate::dag::dag_metadata::{Consistency, DagMetadataManager};
 use crate::dag::epoch::Epoch;
+use crate::dag::executor_utils::partition_env_name;
 use tempdir::TempDir;
 
 #[derive(Debug)]
@@ -125,12 +130,451 @@ fn test_run_dag() {
     );
 
     chk!(dag.connect(
-        Endpoint::new(source_handle, GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(source_handle.clone(), GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(proc_handle.clone(), DEFAULT_PORT_HANDLE),
+    ));
+
+    chk!(dag.connect(
+        Endpoint::new(proc_handle, DEFAULT_PORT_HANDLE),
+        Endpoint::new(sink_handle, COUNTING_SINK_INPUT_PORT),
+    ));
+
+    let tmp_dir = chk!(TempDir::new("test"));
+    let mut executor = chk!(DagExecutor::new(
+        &dag,
+        tmp_dir.path(),
+        ExecutorOptions::default(),
+        Arc::new(AtomicBool::new(true))
+    ));
+
+    let watermark = executor.get_watermark();
+    assert!(!watermark.has_reached(&source_handle, (count, 0)));
+
+    chk!(executor.start());
+    assert!(executor.join().is_ok());
+    assert!(watermark.has_reached(&source_handle, (count, 0)));
+}
+
+/// Runs a source sending `count` operations, in batches of `batch_size` if any, through a
+/// processor into a sink and returns the operations the sink received.
+fn run_generator_dag(count: u64, batch_size: Option<usize>, stateful: bool) -> Vec<Operation> {
+    let mut dag = Dag::new();
+    let latch = Arc::new(AtomicBool::new(true));
+
+    let source_handle = NodeHandle::new(Some(1), 1.to_string());
+    let proc_handle = NodeHandle::new(Some(1), 2.to_string());
+    let sink_handle = NodeHandle::new(Some(1), 3.to_string());
+
+    let source = GeneratorSourceFactory::new(count, latch.clone(), stateful);
+    let source = match batch_size {
+        Some(batch_size) => source.with_batch_size(batch_size),
+        None => source,
+    };
+    dag.add_node(NodeType::Source(Arc::new(source)), source_handle.clone());
+    dag.add_node(
+        NodeType::Processor(Arc::new(NoopProcessorFactory {})),
+        proc_handle.clone(),
+    );
+    let operations = Arc::new(Mutex::new(vec![]));
+    dag.add_node(
+        NodeType::Sink(Arc::new(
+            CountingSinkFactory::new(count, latch).with_operations(operations.clone()),
+        )),
+        sink_handle.clone(),
+    );
+
+    chk!(dag.connect(
+        Endpoint::new(source_handle.clone(), GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(proc_handle.clone(), DEFAULT_PORT_HANDLE),
+    ));
+
+    chk!(dag.connect(
+        Endpoint::new(proc_handle, DEFAULT_PORT_HANDLE),
+        Endpoint::new(sink_handle, COUNTING_SINK_INPUT_PORT),
+    ));
+
+    let tmp_dir = chk!(TempDir::new("test"));
+    let mut executor = chk!(DagExecutor::new(
+        &dag,
+        tmp_dir.path(),
+        ExecutorOptions::default(),
+        Arc::new(AtomicBool::new(true))
+    ));
+    let watermark = executor.get_watermark();
+    let metrics = executor.get_metrics();
+
+    chk!(executor.start());
+    assert!(executor.join().is_ok());
+
+    assert!(watermark.has_reached(&source_handle, (count, 0)));
+    let metrics = metrics.get();
+    assert_eq!(metrics.get(&source_handle).unwrap().operations, count);
+    let operations = operations.lock().clone();
+    operations
+}
+
+#[test]
+fn test_run_dag_batched_source() {
+    let count: u64 = 10_000;
+
+    for stateful in [false, true] {
+        let single = run_generator_dag(count, None, stateful);
+        assert_eq!(single.len(), count as usize);
+        let batched = run_generator_dag(count, Some(1_000), stateful);
+        assert_eq!(batched, single);
+    }
+
+    // The last batch is smaller than the batch size
+    assert_eq!(run_generator_dag(1_500, Some(1_000), false).len(), 1_500);
+}
+
+#[test]
+fn test_run_dag_metrics() {
+    let count: u64 = 1_000;
+
+    let mut dag = Dag::new();
+    let latch = Arc::new(AtomicBool::new(true));
+
+    let source_handle = NodeHandle::new(Some(1), 1.to_string());
+    let proc_handle = NodeHandle::new(Some(1), 2.to_string());
+    let sink_handle = NodeHandle::new(Some(1), 3.to_string());
+
+    dag.add_node(
+        NodeType::Source(Arc::new(GeneratorSourceFactory::new(
+            count,
+            latch.clone(),
+            false,
+        ))),
+        source_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Processor(Arc::new(NoopProcessorFactory {})),
+        proc_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Sink(Arc::new(CountingSinkFactory::new(count, latch))),
+        sink_handle.clone(),
+    );
+
+    chk!(dag.connect(
+        Endpoint::new(source_handle.clone(), GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(proc_handle.clone(), DEFAULT_PORT_HANDLE),
+    ));
+
+    chk!(dag.connect(
+        Endpoint::new(proc_handle.clone(), DEFAULT_PORT_HANDLE),
+        Endpoint::new(sink_handle.clone(), COUNTING_SINK_INPUT_PORT),
+    ));
+
+    let tmp_dir = chk!(TempDir::new("test"));
+    let mut executor = chk!(DagExecutor::new(
+        &dag,
+        tmp_dir.path(),
+        ExecutorOptions::default(),
+        Arc::new(AtomicBool::new(true))
+    ));
+    assert!(executor.metrics().is_empty());
+
+    chk!(executor.start());
+    let metrics = executor.get_metrics();
+    assert_eq!(executor.metrics().len(), 3);
+    assert!(executor.join().is_ok());
+
+    let metrics = metrics.get();
+    let source = metrics.get(&source_handle).unwrap();
+    assert_eq!(source.operations, count);
+    assert!(source.records_in.is_empty());
+    assert_eq!(source.records_out[&GENERATOR_SOURCE_OUTPUT_PORT], count);
+
+    let processor = metrics.get(&proc_handle).unwrap();
+    assert_eq!(processor.operations, count);
+    assert_eq!(processor.records_in[&DEFAULT_PORT_HANDLE], count);
+    assert_eq!(processor.records_out[&DEFAULT_PORT_HANDLE], count);
+    assert!(processor.queue_depth.contains_key(&DEFAULT_PORT_HANDLE));
+
+    let sink = metrics.get(&sink_handle).unwrap();
+    assert_eq!(sink.operations, count);
+    assert_eq!(sink.records_in[&COUNTING_SINK_INPUT_PORT], count);
+    assert!(sink.records_out.is_empty());
+}
+
+/// Records which instance processed each key.
+#[derive(Debug)]
+struct PartitionedProcessorFactory {
+    parallelism: usize,
+    instances: AtomicUsize,
+    seen: Arc<Mutex<HashMap<Vec<u8>, HashSet<usize>>>>,
+}
+
+impl ProcessorFactory for PartitionedProcessorFactory {
+    fn get_output_schema(
+        &self,
+        _output_port: &PortHandle,
+        input_schemas: &HashMap<PortHandle, Schema>,
+    ) -> Result<Schema, ExecutionError> {
+        Ok(input_schemas.get(&DEFAULT_PORT_HANDLE).unwrap().clone())
+    }
+
+    fn get_input_ports(&self) -> Vec<PortHandle> {
+        vec![DEFAULT_PORT_HANDLE]
+    }
+
+    fn get_output_ports(&self) -> Vec<OutputPortDef> {
+        vec![OutputPortDef::new(
+            DEFAULT_PORT_HANDLE,
+            OutputPortType::Stateless,
+        )]
+    }
+
+    fn prepare(
+        &self,
+        _input_schemas: HashMap<PortHandle, Schema>,
+        _output_schemas: HashMap<PortHandle, Schema>,
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn build(
+        &self,
+        _input_schemas: HashMap<PortHandle, Schema>,
+        _output_schemas: HashMap<PortHandle, Schema>,
+    ) -> Result<Box<dyn Processor>, ExecutionError> {
+        Ok(Box::new(PartitionedProcessor {
+            instance: self.instances.fetch_add(1, Ordering::Relaxed),
+            seen: self.seen.clone(),
+        }))
+    }
+
+    fn parallelism(&self) -> usize {
+        self.parallelism
+    }
+
+    fn partition_by(&self, _port: &PortHandle) -> Option<Vec<usize>> {
+        Some(vec![0])
+    }
+}
+
+#[derive(Debug)]
+struct PartitionedProcessor {
+    instance: usize,
+    seen: Arc<Mutex<HashMap<Vec<u8>, HashSet<usize>>>>,
+}
+
+impl Processor for PartitionedProcessor {
+    fn init(&mut self, _state: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn commit(
+        &self,
+        _epoch_details: &Epoch,
+        _tx: &SharedTransaction,
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn process(
+        &mut self,
+        _from_port: PortHandle,
+        op: Operation,
+        fw: &mut dyn ProcessorChannelForwarder,
+        _tx: &SharedTransaction,
+        _reader: &HashMap<PortHandle, RecordReader>,
+    ) -> Result<(), ExecutionError> {
+        if let Operation::Insert { new } = &op {
+            self.seen
+                .lock()
+                .entry(new.values[0].encode())
+                .or_default()
+                .insert(self.instance);
+        }
+        fw.send(op, DEFAULT_PORT_HANDLE)
+    }
+}
+
+#[test]
+fn test_run_dag_partitioned_processor() {
+    let count: u64 = 1_000;
+    let parallelism = 3;
+
+    let mut dag = Dag::new();
+    let latch = Arc::new(AtomicBool::new(true));
+    let seen = Arc::new(Mutex::new(HashMap::new()));
+
+    let source_handle = NodeHandle::new(Some(1), 1.to_string());
+    let proc_handle = NodeHandle::new(Some(1), 2.to_string());
+    let sink_handle = NodeHandle::new(Some(1), 3.to_string());
+
+    dag.add_node(
+        NodeType::Source(Arc::new(GeneratorSourceFactory::new(
+            count,
+            latch.clone(),
+            false,
+        ))),
+        source_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Processor(Arc::new(PartitionedProcessorFactory {
+            parallelism,
+            instances: AtomicUsize::new(0),
+            seen: seen.clone(),
+        })),
+        proc_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Sink(Arc::new(CountingSinkFactory::new(count, latch))),
+        sink_handle.clone(),
+    );
+
+    chk!(dag.connect(
+        Endpoint::new(source_handle.clone(), GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(proc_handle.clone(), DEFAULT_PORT_HANDLE),
+    ));
+
+    chk!(dag.connect(
         Endpoint::new(proc_handle.clone(), DEFAULT_PORT_HANDLE),
+        Endpoint::new(sink_handle.clone(), COUNTING_SINK_INPUT_PORT),
+    ));
+
+    let tmp_dir = chk!(TempDir::new("test"));
+    let mut executor = chk!(DagExecutor::new(
+        &dag,
+        tmp_dir.path(),
+        ExecutorOptions::default(),
+        Arc::new(AtomicBool::new(true))
+    ));
+
+    chk!(executor.start());
+    let metrics = executor.get_metrics();
+    let watermark = executor.get_watermark();
+    assert!(executor.join().is_ok());
+    assert!(watermark.has_reached(&source_handle, (count, 0)));
+
+    let metrics = metrics.get();
+    assert_eq!(
+        metrics[&proc_handle].records_in[&DEFAULT_PORT_HANDLE],
+        count
+    );
+    assert_eq!(
+        metrics[&sink_handle].records_in[&COUNTING_SINK_INPUT_PORT],
+        count
+    );
+
+    let seen = seen.lock();
+    assert_eq!(seen.len() as u64, count);
+    assert!(seen.values().all(|instances| instances.len() == 1));
+    let used: HashSet<usize> = seen.values().flatten().copied().collect();
+    assert_eq!(used.len(), parallelism);
+
+    let metadata = chk!(DagMetadataManager::new(&dag, tmp_dir.path()));
+    assert!(matches!(
+        metadata.get_checkpoint_consistency()[&source_handle],
+        Consistency::FullyConsistent((position, 0)) if position == count
+    ));
+
+    // The state of the processor is lost if one of its instances can't resume
+    LmdbEnvironmentManager::remove(tmp_dir.path(), &partition_env_name(&proc_handle, 2));
+    let metadata = chk!(DagMetadataManager::new(&dag, tmp_dir.path()));
+    assert!(matches!(
+        metadata.get_checkpoint_consistency()[&source_handle],
+        Consistency::PartiallyConsistent(_)
+    ));
+    assert!(!LmdbEnvironmentManager::exists(
+        tmp_dir.path(),
+        &proc_handle.to_string()
+    ));
+}
+
+#[test]
+fn test_run_dag_source_commit_sz() {
+    let count: u64 = 1_000;
+
+    let mut dag = Dag::new();
+    let latch = Arc::new(AtomicBool::new(true));
+
+    let source_handle = NodeHandle::new(Some(1), 1.to_string());
+    let sink_handle = NodeHandle::new(Some(1), 2.to_string());
+
+    dag.add_node(
+        NodeType::Source(Arc::new(GeneratorSourceFactory::new(
+            count,
+            latch.clone(),
+            false,
+        ))),
+        source_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Sink(Arc::new(CountingSinkFactory::new(count, latch))),
+        sink_handle.clone(),
+    );
+
+    chk!(dag.connect(
+        Endpoint::new(source_handle.clone(), GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(sink_handle.clone(), COUNTING_SINK_INPUT_PORT),
     ));
 
+    // Only sources can override the commit size
+    let tmp_dir = chk!(TempDir::new("test"));
+    let mut executor = chk!(DagExecutor::new(
+        &dag,
+        tmp_dir.path(),
+        ExecutorOptions {
+            source_commit_sz: HashMap::from([(sink_handle, 10)]),
+            ..Default::default()
+        },
+        Arc::new(AtomicBool::new(true))
+    ));
+    assert!(matches!(
+        executor.start(),
+        Err(ExecutionError::InvalidNodeHandle(_))
+    ));
+
+    let tmp_dir = chk!(TempDir::new("test"));
+    let mut executor = chk!(DagExecutor::new(
+        &dag,
+        tmp_dir.path(),
+        ExecutorOptions {
+            source_commit_sz: HashMap::from([(source_handle.clone(), 10)]),
+            ..Default::default()
+        },
+        Arc::new(AtomicBool::new(true))
+    ));
+    let watermark = executor.get_watermark();
+    chk!(executor.start());
+    assert!(executor.join().is_ok());
+    assert!(watermark.has_reached(&source_handle, (count, 0)));
+}
+
+#[test]
+fn test_run_dag_commits_idle_source_on_time_threshold() {
+    let count: u64 = 3;
+    let commit_time_threshold = Duration::from_millis(100);
+
+    let mut dag = Dag::new();
+    let source_latch = Arc::new(AtomicBool::new(true));
+
+    let source_handle = NodeHandle::new(Some(1), 1.to_string());
+    let sink_handle = NodeHandle::new(Some(1), 2.to_string());
+
+    dag.add_node(
+        NodeType::Source(Arc::new(GeneratorSourceFactory::new(
+            count,
+            source_latch.clone(),
+            false,
+        ))),
+        source_handle.clone(),
+    );
+    // The sink never stops the source, so only the time threshold can commit the records.
+    dag.add_node(
+        NodeType::Sink(Arc::new(CountingSinkFactory::new(
+            u64::MAX,
+            Arc::new(AtomicBool::new(true)),
+        ))),
+        sink_handle.clone(),
+    );
+
     chk!(dag.connect(
-        Endpoint::new(proc_handle, DEFAULT_PORT_HANDLE),
+        Endpoint::new(source_handle.clone(), GENERATOR_SOURCE_OUTPUT_PORT),
         Endpoint::new(sink_handle, COUNTING_SINK_INPUT_PORT),
     ));
 
@@ -138,12 +582,96 @@ fn test_run_dag() {
     let mut executor = chk!(DagExecutor::new(
         &dag,
         tmp_dir.path(),
-        ExecutorOptions::default(),
+        ExecutorOptions {
+            commit_sz: 1_000,
+            commit_time_threshold,
+            ..Default::default()
+        },
         Arc::new(AtomicBool::new(true))
     ));
-
+    let watermark = executor.get_watermark();
     chk!(executor.start());
+
+    // Leaves room for the DAG to start, and for scheduling delays.
+    let deadline = Instant::now() + commit_time_threshold * 20;
+    while !watermark.has_reached(&source_handle, (count, 0)) && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    let committed = watermark.has_reached(&source_handle, (count, 0));
+
+    source_latch.store(false, Ordering::Relaxed);
     assert!(executor.join().is_ok());
+    assert!(committed);
+}
+
+#[test]
+fn test_most_blocked_output() {
+    let metrics = DagMetrics::default();
+    assert!(metrics.get_most_blocked_output().is_none());
+
+    let source_handle = NodeHandle::new(Some(1), 1.to_string());
+    let proc_handle = NodeHandle::new(Some(1), 2.to_string());
+    let source = metrics.register_node(source_handle, &[], &[GENERATOR_SOURCE_OUTPUT_PORT]);
+    let processor = metrics.register_node(
+        proc_handle.clone(),
+        &[DEFAULT_PORT_HANDLE],
+        &[DEFAULT_PORT_HANDLE],
+    );
+
+    source.on_blocked(GENERATOR_SOURCE_OUTPUT_PORT, Duration::from_millis(10));
+    processor.on_blocked(DEFAULT_PORT_HANDLE, Duration::from_millis(20));
+    processor.on_blocked(DEFAULT_PORT_HANDLE, Duration::from_millis(5));
+
+    assert_eq!(
+        metrics.get_most_blocked_output(),
+        Some((
+            Endpoint::new(proc_handle, DEFAULT_PORT_HANDLE),
+            Duration::from_millis(25)
+        ))
+    );
+}
+
+#[test]
+fn test_commit_and_terminate_count_blocked_time() {
+    let tmp_dir = chk!(TempDir::new("test"));
+    let mut env = chk!(LmdbEnvironmentManager::create(tmp_dir.path(), "test"));
+    let meta_db = chk!(env.open_database("meta", false));
+    let state_writer = chk!(StateWriter::new(
+        meta_db,
+        HashMap::new(),
+        chk!(env.create_txn()),
+        HashMap::new()
+    ));
+
+    let metrics = DagMetrics::default();
+    let handle = NodeHandle::new(Some(1), 1.to_string());
+    let counters = metrics.register_node(handle.clone(), &[], &[DEFAULT_PORT_HANDLE]);
+
+    // The channel is full, and is drained once for the commit and once for the terminate
+    let (sender, receiver) = bounded(1);
+    chk!(sender.send(ExecutorOperation::Terminate));
+    let mut manager = ProcessorChannelManager::new(
+        handle.clone(),
+        [(DEFAULT_PORT_HANDLE, vec![sender])].into_iter().collect(),
+        state_writer,
+        false,
+        counters,
+        None,
+    );
+    let drain = thread::spawn(move || {
+        for _ in 0..3 {
+            thread::sleep(Duration::from_millis(20));
+            receiver.recv().unwrap();
+        }
+    });
+
+    chk!(manager.store_and_send_commit(&Epoch::new(0, HashMap::new())));
+    chk!(manager.send_terminate());
+    drain.join().unwrap();
+
+    let (endpoint, blocked) = metrics.get_most_blocked_output().unwrap();
+    assert_eq!(endpoint, Endpoint::new(handle, DEFAULT_PORT_HANDLE));
+    assert!(blocked >= Duration::from_millis(30));
 }
 
 #[test]
@@ -206,6 +734,127 @@ fn test_run_dag_and_stop() {
     ));
 }
 
+#[test]
+fn test_run_dag_and_stop_and_drain() {
+    let count: u64 = 100_000;
+
+    let mut dag = Dag::new();
+    // The source stops generating when the executor stops
+    let running = Arc::new(AtomicBool::new(true));
+
+    let source_handle = NodeHandle::new(None, 1.to_string());
+    let proc_handle = NodeHandle::new(Some(1), 2.to_string());
+    let sink_handle = NodeHandle::new(Some(1), 3.to_string());
+
+    dag.add_node(
+        NodeType::Source(Arc::new(GeneratorSourceFactory::new(
+            count,
+            running.clone(),
+            false,
+        ))),
+        source_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Processor(Arc::new(NoopProcessorFactory {})),
+        proc_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Sink(Arc::new(CountingSinkFactory::new(
+            count,
+            Arc::new(AtomicBool::new(true)),
+        ))),
+        sink_handle.clone(),
+    );
+
+    chk!(dag.connect(
+        Endpoint::new(source_handle.clone(), GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(proc_handle.clone(), DEFAULT_PORT_HANDLE),
+    ));
+
+    chk!(dag.connect(
+        Endpoint::new(proc_handle, DEFAULT_PORT_HANDLE),
+        Endpoint::new(sink_handle, COUNTING_SINK_INPUT_PORT),
+    ));
+
+    let tmp_dir = chk!(TempDir::new("test"));
+    let mut executor = chk!(DagExecutor::new(
+        &dag,
+        tmp_dir.path(),
+        ExecutorOptions::default(),
+        running
+    ));
+    let watermark = executor.get_watermark();
+
+    chk!(executor.start());
+    // Stop while the source is still sending
+    assert!(executor.stop_and_drain().is_ok());
+
+    // Everything sent before stopping went through the final commit
+    assert!(watermark.has_reached(&source_handle, (count, 0)));
+
+    let r = chk!(DagMetadataManager::new(&dag, tmp_dir.path()));
+    let c = r.get_checkpoint_consistency();
+    assert!(matches!(
+        c.get(&source_handle).unwrap(),
+        Consistency::FullyConsistent(_)
+    ));
+}
+
+#[test]
+fn test_run_dag_and_stop_and_drain_blocking_source() {
+    let count: u64 = 1_000;
+
+    let mut dag = Dag::new();
+
+    let source_handle = NodeHandle::new(None, 1.to_string());
+    let proc_handle = NodeHandle::new(Some(1), 2.to_string());
+    let sink_handle = NodeHandle::new(Some(1), 3.to_string());
+
+    dag.add_node(
+        NodeType::Source(Arc::new(BlockingSourceFactory::new(count))),
+        source_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Processor(Arc::new(NoopProcessorFactory {})),
+        proc_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Sink(Arc::new(CountingSinkFactory::new(
+            count,
+            Arc::new(AtomicBool::new(true)),
+        ))),
+        sink_handle.clone(),
+    );
+
+    chk!(dag.connect(
+        Endpoint::new(source_handle.clone(), GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(proc_handle.clone(), DEFAULT_PORT_HANDLE),
+    ));
+
+    chk!(dag.connect(
+        Endpoint::new(proc_handle, DEFAULT_PORT_HANDLE),
+        Endpoint::new(sink_handle, COUNTING_SINK_INPUT_PORT),
+    ));
+
+    let tmp_dir = chk!(TempDir::new("test"));
+    let mut executor = chk!(DagExecutor::new(
+        &dag,
+        tmp_dir.path(),
+        ExecutorOptions::default(),
+        Arc::new(AtomicBool::new(true))
+    ));
+    let watermark = executor.get_watermark();
+
+    chk!(executor.start());
+    // The source only returns when its stopper is called
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !watermark.has_reached(&source_handle, (count, 0)) {
+        assert!(Instant::now() < deadline, "The source didn't send its data");
+        thread::sleep(Duration::from_millis(50));
+    }
+    assert!(executor.stop_and_drain().is_ok());
+}
+
 #[derive(Debug)]
 pub(crate) struct NoopJoinProcessorFactory {}
 
@@ -407,6 +1056,160 @@ fn test_run_dag_2_sources_stateful() {
     assert!(executor.join().is_ok());
 }
 
+#[test]
+fn test_run_dag_2_sources_skewed() {
+    let fast_count: u64 = 50_000;
+    let slow_count: u64 = 500;
+
+    let mut dag = Dag::new();
+    let latch = Arc::new(AtomicBool::new(true));
+
+    let source1_handle = NodeHandle::new(None, 1.to_string());
+    let source2_handle = NodeHandle::new(None, 2.to_string());
+
+    let proc_handle = NodeHandle::new(Some(1), 1.to_string());
+    let sink_handle = NodeHandle::new(Some(1), 2.to_string());
+
+    dag.add_node(
+        NodeType::Source(Arc::new(GeneratorSourceFactory::new(
+            fast_count,
+            latch.clone(),
+            true,
+        ))),
+        source1_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Source(Arc::new(GeneratorSourceFactory::new(
+            slow_count,
+            latch.clone(),
+            true,
+        ))),
+        source2_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Processor(Arc::new(NoopJoinProcessorFactory {})),
+        proc_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Sink(Arc::new(CountingSinkFactory::new(
+            fast_count + slow_count,
+            latch,
+        ))),
+        sink_handle.clone(),
+    );
+
+    chk!(dag.connect(
+        Endpoint::new(source1_handle.clone(), GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(proc_handle.clone(), 1),
+    ));
+
+    chk!(dag.connect(
+        Endpoint::new(source2_handle.clone(), GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(proc_handle.clone(), 2),
+    ));
+
+    chk!(dag.connect(
+        Endpoint::new(proc_handle, DEFAULT_PORT_HANDLE),
+        Endpoint::new(sink_handle, COUNTING_SINK_INPUT_PORT),
+    ));
+
+    let tmp_dir = chk!(TempDir::new("test"));
+    let mut executor = chk!(DagExecutor::new(
+        &dag,
+        tmp_dir.path(),
+        ExecutorOptions::default(),
+        Arc::new(AtomicBool::new(true))
+    ));
+
+    chk!(executor.start());
+    assert!(executor.join().is_ok());
+
+    // Every node committed all the epochs of both sources, although they closed a different
+    // number of epochs.
+    let r = chk!(DagMetadataManager::new(&dag, tmp_dir.path()));
+    let c = r.get_checkpoint_consistency();
+    assert!(matches!(
+        c.get(&source1_handle).unwrap(),
+        Consistency::FullyConsistent((count, 0)) if *count == fast_count
+    ));
+    assert!(matches!(
+        c.get(&source2_handle).unwrap(),
+        Consistency::FullyConsistent((count, 0)) if *count == slow_count
+    ));
+}
+
+#[test]
+fn test_run_dag_2_sources_stateful_in_memory() {
+    let count: u64 = 1_000;
+
+    let mut dag = Dag::new();
+    let latch = Arc::new(AtomicBool::new(true));
+
+    let source1_handle = NodeHandle::new(None, 1.to_string());
+    let source2_handle = NodeHandle::new(None, 2.to_string());
+
+    let proc_handle = NodeHandle::new(Some(1), 1.to_string());
+    let sink_handle = NodeHandle::new(Some(1), 2.to_string());
+
+    dag.add_node(
+        NodeType::Source(Arc::new(GeneratorSourceFactory::new(
+            count,
+            latch.clone(),
+            true,
+        ))),
+        source1_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Source(Arc::new(GeneratorSourceFactory::new(
+            count,
+            latch.clone(),
+            true,
+        ))),
+        source2_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Processor(Arc::new(NoopJoinProcessorFactory {})),
+        proc_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Sink(Arc::new(CountingSinkFactory::new(count * 2, latch))),
+        sink_handle.clone(),
+    );
+
+    chk!(dag.connect(
+        Endpoint::new(source1_handle.clone(), GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(proc_handle.clone(), 1),
+    ));
+
+    chk!(dag.connect(
+        Endpoint::new(source2_handle.clone(), GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(proc_handle.clone(), 2),
+    ));
+
+    chk!(dag.connect(
+        Endpoint::new(proc_handle, DEFAULT_PORT_HANDLE),
+        Endpoint::new(sink_handle, COUNTING_SINK_INPUT_PORT),
+    ));
+
+    let tmp_dir = chk!(TempDir::new("test"));
+    let mut executor = chk!(DagExecutor::new(
+        &dag,
+        tmp_dir.path(),
+        ExecutorOptions {
+            in_memory: true,
+            ..Default::default()
+        },
+        Arc::new(AtomicBool::new(true))
+    ));
+
+    let watermark = executor.get_watermark();
+    chk!(executor.start());
+    assert!(executor.join().is_ok());
+    assert!(watermark.has_reached(&source1_handle, (count, 0)));
+    assert!(watermark.has_reached(&source2_handle, (count, 0)));
+    assert_eq!(chk!(std::fs::read_dir(tmp_dir.path())).count(), 0);
+}
+
 #[test]
 fn test_run_dag_1_source_2_ports_stateless() {
     let count: u64 = 50_000;
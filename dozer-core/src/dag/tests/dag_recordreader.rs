@@ -12,7 +12,7 @@ use crate::dag::tests::sources::{
     GeneratorSourceFactory, NoPkGeneratorSourceFactory, GENERATOR_SOURCE_OUTPUT_PORT,
 };
 use crate::storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
-use dozer_types::types::{Field, Operation, Schema};
+use dozer_types::types::{Field, KeyMode, Operation, Schema};
 
 use std::collections::HashMap;
 
@@ -62,6 +62,7 @@ impl ProcessorFactory for PassthroughProcessorFactory {
             OutputPortType::StatefulWithPrimaryKeyLookup {
                 retr_old_records_for_deletes: true,
                 retr_old_records_for_updates: true,
+                key_mode: KeyMode::Position,
             },
         )]
     }
@@ -1,11 +1,13 @@
 use crate::dag::dag::{Dag, Endpoint, NodeType, DEFAULT_PORT_HANDLE};
-use crate::dag::dag_schemas::DagSchemaManager;
+use crate::dag::dag_metadata::DagMetadata;
+use crate::dag::dag_schemas::{DagSchemaManager, NodeSchemas};
 use crate::dag::errors::ExecutionError;
 use crate::dag::executor::{DagExecutor, ExecutorOptions};
 use crate::dag::node::{
     NodeHandle, OutputPortDef, OutputPortType, PortHandle, Processor, ProcessorFactory,
     SinkFactory, Source, SourceFactory,
 };
+use crate::dag::validation::{NodeValidation, PortDirection, SchemaDiff};
 
 use dozer_types::types::{FieldDefinition, FieldType, Schema};
 use std::collections::HashMap;
@@ -170,6 +172,37 @@ impl SinkFactory for TestSinkFactory {
     }
 }
 
+#[derive(Debug)]
+struct TestLookupSinkFactory {}
+
+impl SinkFactory for TestLookupSinkFactory {
+    fn set_input_schema(
+        &self,
+        _input_schemas: &HashMap<PortHandle, Schema>,
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn get_input_ports(&self) -> Vec<PortHandle> {
+        vec![DEFAULT_PORT_HANDLE]
+    }
+
+    fn prepare(&self, _input_schemas: HashMap<PortHandle, Schema>) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn build(
+        &self,
+        _input_schemas: HashMap<PortHandle, Schema>,
+    ) -> Result<Box<dyn crate::dag::node::Sink>, ExecutionError> {
+        todo!()
+    }
+
+    fn requires_record_lookup(&self, _port: &PortHandle) -> bool {
+        true
+    }
+}
+
 #[test]
 fn test_extract_dag_schemas() {
     let mut dag = Dag::new();
@@ -244,6 +277,39 @@ fn test_extract_dag_schemas() {
     );
 }
 
+#[test]
+fn test_record_lookup_on_stateless_port() {
+    let mut dag = Dag::new();
+
+    let users_handle = NodeHandle::new(Some(1), 1.to_string());
+    let sink_handle = NodeHandle::new(Some(1), 2.to_string());
+
+    dag.add_node(
+        NodeType::Source(Arc::new(TestUsersSourceFactory {})),
+        users_handle.clone(),
+    );
+    dag.add_node(
+        NodeType::Sink(Arc::new(TestLookupSinkFactory {})),
+        sink_handle.clone(),
+    );
+
+    chk!(dag.connect(
+        Endpoint::new(users_handle.clone(), DEFAULT_PORT_HANDLE),
+        Endpoint::new(sink_handle.clone(), DEFAULT_PORT_HANDLE),
+    ));
+
+    match DagSchemaManager::new(&dag) {
+        Err(ExecutionError::RecordLookupOnStatelessPort(node, port, from, from_port)) => {
+            assert_eq!(node, sink_handle);
+            assert_eq!(port, DEFAULT_PORT_HANDLE);
+            assert_eq!(from, users_handle);
+            assert_eq!(from_port, DEFAULT_PORT_HANDLE);
+        }
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("expected a record lookup error"),
+    }
+}
+
 #[test]
 fn test_init_metadata() {
     let users_handle = NodeHandle::new(Some(1), 1.to_string());
@@ -335,3 +401,128 @@ fn test_init_metadata() {
     );
     assert!(exec.is_err());
 }
+
+#[test]
+fn test_validate_schemas_input_mismatch() {
+    let schema = |field_type: FieldType| {
+        Schema::empty()
+            .field(
+                FieldDefinition::new("user_id".to_string(), field_type, false),
+                true,
+            )
+            .clone()
+    };
+
+    let current = NodeSchemas {
+        input_schemas: HashMap::from([(DEFAULT_PORT_HANDLE, schema(FieldType::String))]),
+        output_schemas: HashMap::from([(DEFAULT_PORT_HANDLE, schema(FieldType::String))]),
+    };
+    let existing = DagMetadata {
+        commits: HashMap::new(),
+        input_schemas: current.input_schemas.clone(),
+        output_schemas: current.output_schemas.clone(),
+    };
+    assert!(DagExecutor::validate_schemas(&current, &existing).is_ok());
+
+    // Only the input schema differs
+    let existing = DagMetadata {
+        commits: HashMap::new(),
+        input_schemas: HashMap::from([(DEFAULT_PORT_HANDLE, schema(FieldType::Int))]),
+        output_schemas: current.output_schemas.clone(),
+    };
+    assert!(matches!(
+        DagExecutor::validate_schemas(&current, &existing),
+        Err(ExecutionError::IncompatibleSchemas())
+    ));
+}
+
+#[test]
+fn test_validate_readonly_reports_schema_diffs() {
+    let users_handle = NodeHandle::new(Some(1), 1.to_string());
+    let countries_handle = NodeHandle::new(Some(1), 2.to_string());
+    let join_handle = NodeHandle::new(Some(1), 3.to_string());
+    let sink_handle = NodeHandle::new(Some(1), 4.to_string());
+
+    let build_dag = |countries_factory: Arc<dyn SourceFactory>| {
+        let mut dag = Dag::new();
+        dag.add_node(
+            NodeType::Source(Arc::new(TestUsersSourceFactory {})),
+            users_handle.clone(),
+        );
+        dag.add_node(
+            NodeType::Source(countries_factory),
+            countries_handle.clone(),
+        );
+        dag.add_node(
+            NodeType::Processor(Arc::new(TestJoinProcessorFactory {})),
+            join_handle.clone(),
+        );
+        dag.add_node(
+            NodeType::Sink(Arc::new(TestSinkFactory {})),
+            sink_handle.clone(),
+        );
+        chk!(dag.connect(
+            Endpoint::new(users_handle.clone(), DEFAULT_PORT_HANDLE),
+            Endpoint::new(join_handle.clone(), 1),
+        ));
+        chk!(dag.connect(
+            Endpoint::new(countries_handle.clone(), DEFAULT_PORT_HANDLE),
+            Endpoint::new(join_handle.clone(), 2),
+        ));
+        chk!(dag.connect(
+            Endpoint::new(join_handle.clone(), DEFAULT_PORT_HANDLE),
+            Endpoint::new(sink_handle.clone(), DEFAULT_PORT_HANDLE),
+        ));
+        dag
+    };
+    let dag = build_dag(Arc::new(TestCountriesSourceFactory {}));
+    let changed_dag = build_dag(Arc::new(TestUsersSourceFactory {}));
+
+    let tmp_dir = chk!(TempDir::new("example"));
+    let report = chk!(DagExecutor::validate_readonly(&dag, tmp_dir.path()));
+    assert!(report
+        .nodes
+        .values()
+        .all(|validation| validation == &NodeValidation::New));
+    assert!(chk!(std::fs::read_dir(tmp_dir.path())).next().is_none());
+
+    chk!(DagExecutor::validate(&dag, tmp_dir.path()));
+    let report = chk!(DagExecutor::validate_readonly(&dag, tmp_dir.path()));
+    assert!(report.is_compatible());
+
+    let report = chk!(DagExecutor::validate_readonly(&changed_dag, tmp_dir.path()));
+    assert!(!report.is_compatible());
+    assert_eq!(report.nodes[&users_handle], NodeValidation::Compatible);
+    match &report.nodes[&countries_handle] {
+        NodeValidation::Incompatible(diffs) => assert!(matches!(
+            diffs.as_slice(),
+            [SchemaDiff::ChangedSchema {
+                direction: PortDirection::Output,
+                port: DEFAULT_PORT_HANDLE,
+                ..
+            }]
+        )),
+        other => panic!("Unexpected validation {:?}", other),
+    }
+    match &report.nodes[&join_handle] {
+        NodeValidation::Incompatible(diffs) => {
+            assert!(diffs.contains(&SchemaDiff::ChangedSchema {
+                direction: PortDirection::Input,
+                port: 2,
+                existing: chk!(
+                    TestCountriesSourceFactory {}.get_output_schema(&DEFAULT_PORT_HANDLE)
+                ),
+                current: chk!(TestUsersSourceFactory {}.get_output_schema(&DEFAULT_PORT_HANDLE)),
+            }))
+        }
+        other => panic!("Unexpected validation {:?}", other),
+    }
+
+    // The metadata of the changed nodes is still there.
+    let report = chk!(DagExecutor::validate_readonly(&dag, tmp_dir.path()));
+    assert!(report.is_compatible());
+    assert!(report
+        .nodes
+        .values()
+        .all(|validation| validation == &NodeValidation::Compatible));
+}
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+use std::thread;
+
+use crate::dag::epoch::{EpochManager, SourceWatermark};
+use crate::dag::node::NodeHandle;
+
+#[test]
+fn test_sources_close_epochs_independently() {
+    let fast = NodeHandle::new(None, "fast".to_string());
+    let slow = NodeHandle::new(None, "slow".to_string());
+    let manager = EpochManager::new([fast.clone(), slow.clone()]);
+    assert_eq!(manager.global_watermark(), None);
+
+    for n in 0..5 {
+        let epoch = manager.close_epoch(&fast, (n, 0), false);
+        assert_eq!(epoch.id, n);
+        assert_eq!(epoch.details.len(), 1);
+        assert_eq!(epoch.details[&fast], (n, 0));
+    }
+    assert_eq!(manager.global_watermark(), None);
+
+    let epoch = manager.close_epoch(&slow, (100, 1), true);
+    assert_eq!(epoch.id, 0);
+    assert!(epoch.terminating);
+
+    assert_eq!(
+        manager.watermark(&fast),
+        Some(SourceWatermark {
+            epoch_id: 4,
+            position: (4, 0)
+        })
+    );
+    assert_eq!(
+        manager.watermark(&slow),
+        Some(SourceWatermark {
+            epoch_id: 0,
+            position: (100, 1)
+        })
+    );
+    assert_eq!(manager.global_watermark(), Some(0));
+}
+
+#[test]
+fn test_fast_source_does_not_wait_for_slow_source() {
+    let fast = NodeHandle::new(None, "fast".to_string());
+    let slow = NodeHandle::new(None, "slow".to_string());
+    let manager = Arc::new(EpochManager::new([fast.clone(), slow.clone()]));
+
+    // The slow source never closes an epoch while the fast one runs.
+    let fast_thread = {
+        let manager = manager.clone();
+        let fast = fast.clone();
+        thread::spawn(move || {
+            for n in 0..1000 {
+                manager.close_epoch(&fast, (n, 0), false);
+            }
+        })
+    };
+    fast_thread.join().unwrap();
+
+    assert_eq!(manager.watermark(&fast).unwrap().epoch_id, 999);
+    assert_eq!(manager.global_watermark(), None);
+
+    manager.close_epoch(&slow, (1, 0), false);
+    assert_eq!(manager.global_watermark(), Some(0));
+}
@@ -0,0 +1,292 @@
+use crate::chk;
+use crate::dag::errors::ExecutionError;
+use crate::dag::node::OutputPortType;
+use crate::dag::record_store::{
+    AutogenRowKeyLookupRecordWriter, RecordReader, RecordWriter, RecordWriterUtils,
+};
+use crate::storage::common::Database;
+use crate::storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
+use dozer_types::types::{Field, FieldDefinition, FieldType, KeyMode, Operation, Record, Schema};
+use tempdir::TempDir;
+
+fn create_autogen_writer(tmp_dir: &TempDir) -> (Box<dyn RecordWriter>, SharedTransaction) {
+    let mut env = chk!(LmdbEnvironmentManager::create(tmp_dir.path(), "test"));
+    let db = chk!(env.open_database("records", false));
+    let meta_db = chk!(env.open_database("records_meta", false));
+    let schema = AutogenRowKeyLookupRecordWriter::prepare_schema(
+        Schema::empty()
+            .field(
+                FieldDefinition::new("name".to_string(), FieldType::String, false),
+                false,
+            )
+            .clone(),
+    );
+    let writer = chk!(RecordWriterUtils::create_writer(
+        OutputPortType::AutogenRowKeyLookup,
+        db,
+        meta_db,
+        schema
+    ));
+    (writer, chk!(env.create_txn()))
+}
+
+fn record(name: &str) -> Record {
+    Record::new(None, vec![Field::String(name.to_string())], None)
+}
+
+fn stored(name: &str, rowid: u64) -> Record {
+    Record::new(
+        None,
+        vec![Field::String(name.to_string()), Field::UInt(rowid)],
+        None,
+    )
+}
+
+#[test]
+fn test_autogen_writer_insert_then_delete() {
+    let tmp_dir = chk!(TempDir::new("test"));
+    let (mut writer, tx) = create_autogen_writer(&tmp_dir);
+
+    for name in ["a", "b", "a"] {
+        chk!(writer.write(Operation::Insert { new: record(name) }, &tx));
+    }
+
+    // Deleting a duplicated row removes one copy at a time
+    assert_eq!(
+        chk!(writer.write(Operation::Delete { old: record("a") }, &tx)),
+        Operation::Delete {
+            old: stored("a", 1)
+        }
+    );
+    assert_eq!(
+        chk!(writer.write(Operation::Delete { old: record("a") }, &tx)),
+        Operation::Delete {
+            old: stored("a", 3)
+        }
+    );
+    assert!(matches!(
+        writer.write(Operation::Delete { old: record("a") }, &tx),
+        Err(ExecutionError::RecordNotFound())
+    ));
+
+    // The row can be inserted again, under a new rowid
+    assert_eq!(
+        chk!(writer.write(Operation::Insert { new: record("a") }, &tx)),
+        Operation::Insert {
+            new: stored("a", 4)
+        }
+    );
+}
+
+#[test]
+fn test_autogen_writer_update() {
+    let tmp_dir = chk!(TempDir::new("test"));
+    let (mut writer, tx) = create_autogen_writer(&tmp_dir);
+
+    chk!(writer.write(Operation::Insert { new: record("a") }, &tx));
+
+    // The updated row keeps its rowid, and is found by its new values
+    assert_eq!(
+        chk!(writer.write(
+            Operation::Update {
+                old: record("a"),
+                new: record("b")
+            },
+            &tx
+        )),
+        Operation::Update {
+            old: stored("a", 1),
+            new: stored("b", 1)
+        }
+    );
+    assert!(matches!(
+        writer.write(Operation::Delete { old: record("a") }, &tx),
+        Err(ExecutionError::RecordNotFound())
+    ));
+    assert_eq!(
+        chk!(writer.write(Operation::Delete { old: record("b") }, &tx)),
+        Operation::Delete {
+            old: stored("b", 1)
+        }
+    );
+}
+
+fn create_primary_key_writer(
+    db: Database,
+    meta_db: Database,
+    schema: Schema,
+    key_mode: KeyMode,
+) -> Box<dyn RecordWriter> {
+    chk!(RecordWriterUtils::create_writer(
+        OutputPortType::StatefulWithPrimaryKeyLookup {
+            retr_old_records_for_deletes: true,
+            retr_old_records_for_updates: true,
+            key_mode,
+        },
+        db,
+        meta_db,
+        schema
+    ))
+}
+
+#[test]
+fn test_field_name_keys_survive_column_reordering() {
+    let tmp_dir = chk!(TempDir::new("test"));
+    let mut env = chk!(LmdbEnvironmentManager::create(tmp_dir.path(), "test"));
+    let db = chk!(env.open_database("records", false));
+    let meta_db = chk!(env.open_database("records_meta", false));
+    let tx = chk!(env.create_txn());
+
+    let id = FieldDefinition::new("id".to_string(), FieldType::Int, false);
+    let region = FieldDefinition::new("region".to_string(), FieldType::String, false);
+    let name = FieldDefinition::new("name".to_string(), FieldType::String, false);
+    let schema = Schema::empty()
+        .field(id.clone(), true)
+        .field(region.clone(), true)
+        .field(name.clone(), false)
+        .clone();
+    let reordered_schema = Schema::empty()
+        .field(region, true)
+        .field(name, false)
+        .field(id, true)
+        .clone();
+
+    let stored = Record::new(
+        None,
+        vec![
+            Field::Int(1),
+            Field::String("eu".to_string()),
+            Field::String("a".to_string()),
+        ],
+        None,
+    );
+    let reordered = Record::new(
+        None,
+        vec![
+            Field::String("eu".to_string()),
+            Field::String("a".to_string()),
+            Field::Int(1),
+        ],
+        None,
+    );
+
+    let mut writer = create_primary_key_writer(db, meta_db, schema, KeyMode::FieldName);
+    chk!(writer.write(
+        Operation::Insert {
+            new: stored.clone()
+        },
+        &tx
+    ));
+
+    // Keyed by position, the reordered primary key doesn't match the stored one
+    let mut writer =
+        create_primary_key_writer(db, meta_db, reordered_schema.clone(), KeyMode::Position);
+    assert!(matches!(
+        writer.write(
+            Operation::Delete {
+                old: reordered.clone()
+            },
+            &tx
+        ),
+        Err(ExecutionError::RecordNotFound())
+    ));
+
+    let mut writer = create_primary_key_writer(db, meta_db, reordered_schema, KeyMode::FieldName);
+    assert_eq!(
+        chk!(writer.write(Operation::Delete { old: reordered }, &tx)),
+        Operation::Delete { old: stored }
+    );
+}
+
+#[test]
+fn test_record_reader_scan_prefix() {
+    let tmp_dir = chk!(TempDir::new("test"));
+    let mut env = chk!(LmdbEnvironmentManager::create(tmp_dir.path(), "test"));
+    let db = chk!(env.open_database("records", false));
+    let tx = chk!(env.create_txn());
+
+    // Order 1 is a prefix of order 10, so the separator marks the boundary
+    let keys = [
+        "order_1/1",
+        "order_1/2",
+        "order_10/1",
+        "order_2/1",
+        "order_2/2",
+    ];
+    for key in keys {
+        chk!(tx
+            .write()
+            .put(db, key.as_bytes(), key.to_uppercase().as_bytes()));
+    }
+
+    let reader = RecordReader::new(tx, db, KeyMode::Position);
+    let scan = |prefix: &str| {
+        chk!(reader.scan_prefix(prefix.as_bytes()))
+            .into_iter()
+            .map(|(key, value)| {
+                (
+                    String::from_utf8(key).unwrap(),
+                    String::from_utf8(value).unwrap(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        scan("order_1/"),
+        vec![
+            ("order_1/1".to_string(), "ORDER_1/1".to_string()),
+            ("order_1/2".to_string(), "ORDER_1/2".to_string()),
+        ]
+    );
+    assert_eq!(scan("order_1").len(), 3);
+    assert_eq!(
+        scan("order_2/"),
+        vec![
+            ("order_2/1".to_string(), "ORDER_2/1".to_string()),
+            ("order_2/2".to_string(), "ORDER_2/2".to_string()),
+        ]
+    );
+    assert_eq!(scan("").len(), keys.len());
+    assert!(scan("order_3/").is_empty());
+    assert!(scan("a").is_empty());
+}
+
+#[test]
+fn test_write_batch_commits_once() {
+    let tmp_dir = chk!(TempDir::new("test"));
+    let mut env = chk!(LmdbEnvironmentManager::create(tmp_dir.path(), "test"));
+    let db = chk!(env.open_database("records", false));
+    let meta_db = chk!(env.open_database("records_meta", false));
+    let tx = chk!(env.create_txn());
+
+    let schema = Schema::empty()
+        .field(
+            FieldDefinition::new("id".to_string(), FieldType::UInt, false),
+            true,
+        )
+        .clone();
+    let mut writer = create_primary_key_writer(db, meta_db, schema, KeyMode::Position);
+    let inserts = |ids: &[u64]| {
+        ids.iter()
+            .map(|id| Operation::Insert {
+                new: Record::new(None, vec![Field::UInt(*id)], None),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let last_txn_id = chk!(tx.read().last_txn_id()).unwrap();
+    // Sorted batches are appended, the others are put key by key
+    for ids in [&[1, 2, 3][..], &[4, 5], &[7, 6], &[0]] {
+        let ops = inserts(ids);
+        assert_eq!(chk!(writer.write_batch(ops.clone(), &tx)), ops);
+    }
+    chk!(tx.write().commit_and_renew());
+    assert_eq!(chk!(tx.read().last_txn_id()).unwrap(), last_txn_id + 1);
+
+    let reader = RecordReader::new(tx, db, KeyMode::Position);
+    for id in 0..8 {
+        let record = Record::new(None, vec![Field::UInt(id)], None);
+        assert!(chk!(reader.get(&record.get_key(&vec![0]))).is_some());
+    }
+}
@@ -6,6 +6,7 @@ use crate::storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
 use dozer_types::types::{Operation, Schema};
 
 use dozer_types::log::info;
+use dozer_types::parking_lot::Mutex;
 use std::collections::HashMap;
 
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -17,6 +18,7 @@ pub(crate) const COUNTING_SINK_INPUT_PORT: PortHandle = 90;
 pub(crate) struct CountingSinkFactory {
     expected: u64,
     running: Arc<AtomicBool>,
+    operations: Option<Arc<Mutex<Vec<Operation>>>>,
 }
 
 impl CountingSinkFactory {
@@ -24,8 +26,15 @@ impl CountingSinkFactory {
         Self {
             expected,
             running: barrier,
+            operations: None,
         }
     }
+
+    /// Also records the operations received, in order, in `operations`.
+    pub fn with_operations(mut self, operations: Arc<Mutex<Vec<Operation>>>) -> Self {
+        self.operations = Some(operations);
+        self
+    }
 }
 
 impl SinkFactory for CountingSinkFactory {
@@ -52,6 +61,7 @@ impl SinkFactory for CountingSinkFactory {
             expected: self.expected,
             current: 0,
             running: self.running.clone(),
+            operations: self.operations.clone(),
         }))
     }
 }
@@ -61,6 +71,7 @@ pub(crate) struct CountingSink {
     expected: u64,
     current: u64,
     running: Arc<AtomicBool>,
+    operations: Option<Arc<Mutex<Vec<Operation>>>>,
 }
 impl Sink for CountingSink {
     fn init(&mut self, _state: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
@@ -85,10 +96,13 @@ impl Sink for CountingSink {
     fn process(
         &mut self,
         _from_port: PortHandle,
-        _op: Operation,
+        op: Operation,
         _state: &SharedTransaction,
         _reader: &HashMap<PortHandle, RecordReader>,
     ) -> Result<(), ExecutionError> {
+        if let Some(operations) = &self.operations {
+            operations.lock().push(op);
+        }
         self.current += 1;
         if self.current == self.expected {
             info!(
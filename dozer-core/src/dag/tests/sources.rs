@@ -1,7 +1,10 @@
 use crate::dag::channels::SourceChannelForwarder;
 use crate::dag::errors::ExecutionError;
-use crate::dag::node::{OutputPortDef, OutputPortType, PortHandle, Source, SourceFactory};
-use dozer_types::types::{Field, FieldDefinition, FieldType, Operation, Record, Schema};
+use crate::dag::node::{
+    OutputPortDef, OutputPortType, PortHandle, Source, SourceFactory, SourceStopper,
+};
+use crossbeam::channel::{bounded, Receiver, Sender};
+use dozer_types::types::{Field, FieldDefinition, FieldType, KeyMode, Operation, Record, Schema};
 
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -17,6 +20,7 @@ pub(crate) struct GeneratorSourceFactory {
     count: u64,
     running: Arc<AtomicBool>,
     stateful: bool,
+    batch_size: Option<usize>,
 }
 
 impl GeneratorSourceFactory {
@@ -25,8 +29,15 @@ impl GeneratorSourceFactory {
             count,
             running: barrier,
             stateful,
+            batch_size: None,
         }
     }
+
+    /// Sends the operations in batches of `batch_size` instead of one by one.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
 }
 
 impl SourceFactory for GeneratorSourceFactory {
@@ -50,6 +61,7 @@ impl SourceFactory for GeneratorSourceFactory {
                 OutputPortType::StatefulWithPrimaryKeyLookup {
                     retr_old_records_for_updates: true,
                     retr_old_records_for_deletes: true,
+                    key_mode: KeyMode::Position,
                 }
             } else {
                 OutputPortType::Stateless
@@ -68,6 +80,7 @@ impl SourceFactory for GeneratorSourceFactory {
         Ok(Box::new(GeneratorSource {
             count: self.count,
             running: self.running.clone(),
+            batch_size: self.batch_size,
         }))
     }
 }
@@ -76,6 +89,7 @@ impl SourceFactory for GeneratorSourceFactory {
 pub(crate) struct GeneratorSource {
     count: u64,
     running: Arc<AtomicBool>,
+    batch_size: Option<usize>,
 }
 
 impl Source for GeneratorSource {
@@ -86,23 +100,29 @@ impl Source for GeneratorSource {
     ) -> Result<(), ExecutionError> {
         let start = from_seq.unwrap().0;
 
+        let mut batch = vec![];
         for n in start + 1..(start + self.count + 1) {
-            fw.send(
-                n,
-                0,
-                Operation::Insert {
-                    new: Record::new(
-                        None,
-                        vec![
-                            Field::String(format!("key_{}", n)),
-                            Field::String(format!("value_{}", n)),
-                        ],
-                        None,
-                    ),
-                },
-                GENERATOR_SOURCE_OUTPUT_PORT,
-            )?;
+            let op = Operation::Insert {
+                new: Record::new(
+                    None,
+                    vec![
+                        Field::String(format!("key_{}", n)),
+                        Field::String(format!("value_{}", n)),
+                    ],
+                    None,
+                ),
+            };
+            match self.batch_size {
+                Some(batch_size) => {
+                    batch.push((n, 0, op, GENERATOR_SOURCE_OUTPUT_PORT));
+                    if batch.len() == batch_size {
+                        fw.send_batch(std::mem::take(&mut batch))?;
+                    }
+                }
+                None => fw.send(n, 0, op, GENERATOR_SOURCE_OUTPUT_PORT)?,
+            }
         }
+        fw.send_batch(batch)?;
 
         loop {
             if !self.running.load(Ordering::Relaxed) {
@@ -115,6 +135,97 @@ impl Source for GeneratorSource {
     }
 }
 
+/// Sends `count` operations like [`GeneratorSource`], then waits for data forever, until its
+/// stopper is called.
+#[derive(Debug)]
+pub(crate) struct BlockingSourceFactory {
+    count: u64,
+}
+
+impl BlockingSourceFactory {
+    pub fn new(count: u64) -> Self {
+        Self { count }
+    }
+}
+
+impl SourceFactory for BlockingSourceFactory {
+    fn get_output_schema(&self, port: &PortHandle) -> Result<Schema, ExecutionError> {
+        GeneratorSourceFactory::new(self.count, Arc::new(AtomicBool::new(true)), false)
+            .get_output_schema(port)
+    }
+
+    fn get_output_ports(&self) -> Result<Vec<OutputPortDef>, ExecutionError> {
+        Ok(vec![OutputPortDef::new(
+            GENERATOR_SOURCE_OUTPUT_PORT,
+            OutputPortType::Stateless,
+        )])
+    }
+
+    fn prepare(&self, _output_schemas: HashMap<PortHandle, Schema>) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn build(
+        &self,
+        _output_schemas: HashMap<PortHandle, Schema>,
+    ) -> Result<Box<dyn Source>, ExecutionError> {
+        let (stop_sender, stop_receiver) = bounded(1);
+        Ok(Box::new(BlockingSource {
+            count: self.count,
+            stop_receiver,
+            stopper: Arc::new(BlockingSourceStopper { stop_sender }),
+        }))
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct BlockingSource {
+    count: u64,
+    stop_receiver: Receiver<()>,
+    stopper: Arc<BlockingSourceStopper>,
+}
+
+#[derive(Debug)]
+struct BlockingSourceStopper {
+    stop_sender: Sender<()>,
+}
+
+impl SourceStopper for BlockingSourceStopper {
+    fn stop(&self) {
+        let _ = self.stop_sender.try_send(());
+    }
+}
+
+impl Source for BlockingSource {
+    fn start(
+        &self,
+        fw: &mut dyn SourceChannelForwarder,
+        from_seq: Option<(u64, u64)>,
+    ) -> Result<(), ExecutionError> {
+        let start = from_seq.unwrap().0;
+        for n in start + 1..(start + self.count + 1) {
+            let op = Operation::Insert {
+                new: Record::new(
+                    None,
+                    vec![
+                        Field::String(format!("key_{}", n)),
+                        Field::String(format!("value_{}", n)),
+                    ],
+                    None,
+                ),
+            };
+            fw.send(n, 0, op, GENERATOR_SOURCE_OUTPUT_PORT)?;
+        }
+
+        let _ = self.stop_receiver.recv();
+        Ok(())
+    }
+
+    fn stopper(&self) -> Option<Arc<dyn SourceStopper>> {
+        Some(self.stopper.clone())
+    }
+}
+
 pub(crate) const DUAL_PORT_GENERATOR_SOURCE_OUTPUT_PORT_1: PortHandle = 1000;
 pub(crate) const DUAL_PORT_GENERATOR_SOURCE_OUTPUT_PORT_2: PortHandle = 2000;
 
@@ -157,6 +268,7 @@ impl SourceFactory for DualPortGeneratorSourceFactory {
                     OutputPortType::StatefulWithPrimaryKeyLookup {
                         retr_old_records_for_updates: true,
                         retr_old_records_for_deletes: true,
+                        key_mode: KeyMode::Position,
                     }
                 } else {
                     OutputPortType::Stateless
@@ -168,6 +280,7 @@ impl SourceFactory for DualPortGeneratorSourceFactory {
                     OutputPortType::StatefulWithPrimaryKeyLookup {
                         retr_old_records_for_updates: true,
                         retr_old_records_for_deletes: true,
+                        key_mode: KeyMode::Position,
                     }
                 } else {
                     OutputPortType::Stateless
@@ -0,0 +1,119 @@
+use std::collections::{BTreeSet, HashMap};
+
+use dozer_types::types::Schema;
+
+use crate::dag::dag_metadata::DagMetadata;
+use crate::dag::dag_schemas::NodeSchemas;
+use crate::dag::node::{NodeHandle, PortHandle};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortDirection {
+    Input,
+    Output,
+}
+
+/// A difference between the schemas of a node and those recorded in its metadata.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaDiff {
+    /// The node has a port which isn't in its metadata.
+    AddedPort {
+        direction: PortDirection,
+        port: PortHandle,
+    },
+    /// The metadata has a port which the node doesn't have anymore.
+    RemovedPort {
+        direction: PortDirection,
+        port: PortHandle,
+    },
+    /// The schema of a port changed.
+    ChangedSchema {
+        direction: PortDirection,
+        port: PortHandle,
+        existing: Schema,
+        current: Schema,
+    },
+}
+
+impl SchemaDiff {
+    /// Returns the differences between the `current` schemas of a node and the `existing` ones,
+    /// ordered by direction and port.
+    pub(crate) fn between(current: &NodeSchemas, existing: &DagMetadata) -> Vec<SchemaDiff> {
+        let mut diffs = vec![];
+        diff_ports(
+            PortDirection::Input,
+            &current.input_schemas,
+            &existing.input_schemas,
+            &mut diffs,
+        );
+        diff_ports(
+            PortDirection::Output,
+            &current.output_schemas,
+            &existing.output_schemas,
+            &mut diffs,
+        );
+        diffs
+    }
+}
+
+fn diff_ports(
+    direction: PortDirection,
+    current: &HashMap<PortHandle, Schema>,
+    existing: &HashMap<PortHandle, Schema>,
+    diffs: &mut Vec<SchemaDiff>,
+) {
+    let ports: BTreeSet<PortHandle> = current.keys().chain(existing.keys()).copied().collect();
+    for port in ports {
+        match (current.get(&port), existing.get(&port)) {
+            (Some(_), None) => diffs.push(SchemaDiff::AddedPort { direction, port }),
+            (None, Some(_)) => diffs.push(SchemaDiff::RemovedPort { direction, port }),
+            (Some(current), Some(existing)) if current != existing => {
+                diffs.push(SchemaDiff::ChangedSchema {
+                    direction,
+                    port,
+                    existing: existing.clone(),
+                    current: current.clone(),
+                })
+            }
+            _ => (),
+        }
+    }
+}
+
+/// The outcome of validating a node against its metadata.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeValidation {
+    /// The node has no metadata yet, it will start from scratch.
+    New,
+    /// The node can resume from its metadata.
+    Compatible,
+    /// The schemas of the node changed. Its metadata and state would be deleted.
+    Incompatible(Vec<SchemaDiff>),
+    /// The metadata of the node can't be read. It would be deleted.
+    Unreadable(String),
+}
+
+/// Compatibility of a DAG with the metadata of its last execution, returned by
+/// [`DagExecutor::validate_readonly`](crate::dag::executor::DagExecutor::validate_readonly).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    pub nodes: HashMap<NodeHandle, NodeValidation>,
+}
+
+impl ValidationReport {
+    /// Returns `true` if no node would lose its metadata.
+    pub fn is_compatible(&self) -> bool {
+        self.nodes.values().all(|validation| {
+            matches!(validation, NodeValidation::New | NodeValidation::Compatible)
+        })
+    }
+
+    /// Returns the nodes which would lose their metadata.
+    pub fn incompatible_nodes(&self) -> impl Iterator<Item = (&NodeHandle, &NodeValidation)> {
+        self.nodes.iter().filter(|(_, validation)| {
+            matches!(
+                validation,
+                NodeValidation::Incompatible(_) | NodeValidation::Unreadable(_)
+            )
+        })
+    }
+}
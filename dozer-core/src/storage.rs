@@ -1,6 +1,7 @@
 pub mod common;
 pub mod errors;
 pub mod lmdb_storage;
+pub mod memory_storage;
 pub mod prefix_transaction;
 
 #[cfg(test)]
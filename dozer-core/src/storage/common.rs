@@ -1,9 +1,51 @@
-pub use lmdb::{Cursor, Database, RwTransaction, Transaction};
-use lmdb_sys::{MDB_GET_CURRENT, MDB_SET, MDB_SET_RANGE};
+pub use lmdb::{Cursor, RwTransaction, Transaction};
+use lmdb_sys::{MDB_GET_CURRENT, MDB_LAST_DUP, MDB_SET, MDB_SET_RANGE};
 
 use crate::storage::errors::StorageError;
 
-pub trait Seek<'txn>: Cursor<'txn> {
+/// A database opened with [`LmdbEnvironmentManager::open_database`](crate::storage::lmdb_storage::LmdbEnvironmentManager::open_database).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Database {
+    Lmdb(lmdb::Database),
+    /// Index of the database in its in-memory environment.
+    Memory(usize),
+}
+
+/// Positions a cursor and reads the entry under it. A database with duplicate keys has an entry
+/// per value.
+pub trait Seek<'txn> {
+    fn seek(&self, key: &[u8]) -> Result<bool, StorageError>;
+
+    fn seek_gte(&self, key: &[u8]) -> Result<bool, StorageError>;
+
+    /// Positions the cursor on the last value of `key`.
+    fn seek_last(&self, key: &[u8]) -> Result<bool, StorageError>;
+
+    /// Positions the cursor on the last entry whose key is less than or equal to `key`, where a
+    /// backward scan of a range ending at `key` starts.
+    fn seek_range_end(&self, key: &[u8]) -> Result<bool, StorageError> {
+        if self.seek_last(key)? {
+            Ok(true)
+        } else if self.seek_gte(key)? {
+            self.prev()
+        } else {
+            self.last()
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn read(&'txn self) -> Result<Option<(&[u8], &[u8])>, StorageError>;
+
+    fn next(&self) -> Result<bool, StorageError>;
+
+    fn prev(&self) -> Result<bool, StorageError>;
+
+    fn first(&self) -> Result<bool, StorageError>;
+
+    fn last(&self) -> Result<bool, StorageError>;
+}
+
+impl<'txn, C: Cursor<'txn>> Seek<'txn> for C {
     fn seek(&self, key: &[u8]) -> Result<bool, StorageError> {
         match self.get(Some(key), None, MDB_SET) {
             Ok(_) => Ok(true),
@@ -20,6 +62,17 @@ pub trait Seek<'txn>: Cursor<'txn> {
         }
     }
 
+    fn seek_last(&self, key: &[u8]) -> Result<bool, StorageError> {
+        if !self.seek(key)? {
+            return Ok(false);
+        }
+        match self.get(None, None, MDB_LAST_DUP) {
+            // A database without duplicate keys has a single value per key.
+            Ok(_) | Err(lmdb::Error::Incompatible) => Ok(true),
+            Err(e) => Err(e.into()),
+        }
+    }
+
     #[allow(clippy::type_complexity)]
     fn read(&'txn self) -> Result<Option<(&[u8], &[u8])>, StorageError> {
         match self.get(None, None, MDB_GET_CURRENT) {
@@ -64,5 +117,3 @@ pub trait Seek<'txn>: Cursor<'txn> {
         }
     }
 }
-
-impl<'txn, C: Cursor<'txn>> Seek<'txn> for C {}
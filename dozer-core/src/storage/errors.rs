@@ -19,6 +19,8 @@ pub enum StorageError {
     InvalidRecord,
     #[error("Invalid database")]
     InvalidDatabase,
+    #[error("Transaction has too many writes to roll back")]
+    TooManyWritesToRollBack,
 
     // Error forwarding
     #[error(transparent)]
@@ -1,23 +1,69 @@
+use crate::storage::common::{Database, Seek};
 use crate::storage::errors::StorageError;
-use crate::storage::errors::StorageError::InternalDbError;
+use crate::storage::errors::StorageError::{
+    InternalDbError, InvalidDatabase, TooManyWritesToRollBack,
+};
+use crate::storage::memory_storage::{MemoryCursor, MemoryEnvironment};
 use dozer_types::parking_lot::RwLock;
 use libc::size_t;
 use lmdb::{
-    Database, DatabaseFlags, Environment, EnvironmentFlags, RoCursor, RwCursor, RwTransaction,
-    Transaction, WriteFlags,
+    Cursor, DatabaseFlags, Environment, EnvironmentFlags, RoCursor, RwTransaction, Transaction,
+    WriteFlags,
+};
+use lmdb_sys::{
+    mdb_env_info, mdb_env_set_mapsize, mdb_env_stat, mdb_set_compare, MDB_cmp_func, MDB_envinfo,
+    MDB_stat, MDB_SUCCESS,
 };
-use lmdb_sys::{mdb_set_compare, MDB_cmp_func, MDB_SUCCESS};
 use std::fs;
+use std::mem::MaybeUninit;
 use std::ops::{Deref, DerefMut};
 use std::path::Path;
 use std::sync::Arc;
 
 const DEFAULT_MAX_DBS: u32 = 256;
 const DEFAULT_MAX_READERS: u32 = 256;
-const DEFAULT_MAX_MAP_SZ: size_t = 1024 * 1024 * 1024;
+const DEFAULT_MAP_SZ: size_t = 1024 * 1024 * 1024;
+const DEFAULT_MAX_MAP_SZ: size_t = 1024 * 1024 * 1024 * 1024;
+/// Bytes of keys and values kept per transaction to replay its writes, see [`LmdbTransaction`].
+const MAX_PENDING_SZ: usize = 64 * 1024 * 1024;
+
+/// Options of an LMDB environment.
+#[derive(Debug, Clone, Copy)]
+pub struct EnvOptions {
+    /// Size of the memory map when the environment is opened.
+    pub map_size: size_t,
+    /// The map doubles whenever it's full, up to this size.
+    pub max_map_size: size_t,
+}
+
+impl Default for EnvOptions {
+    fn default() -> Self {
+        Self {
+            map_size: DEFAULT_MAP_SZ,
+            max_map_size: DEFAULT_MAX_MAP_SZ,
+        }
+    }
+}
+
+/// Options of [`LmdbExclusiveTransaction::put_batch`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PutBatchOptions {
+    /// The caller guarantees the keys are sorted, and greater than the keys already in the
+    /// database, so LMDB can append them without searching. Fails with `MDB_KEYEXIST` otherwise.
+    pub append: bool,
+}
+
+/// A point in a transaction to roll back to, returned by [`LmdbExclusiveTransaction::savepoint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Savepoint(usize);
 
 pub struct LmdbEnvironmentManager {
-    inner: Environment,
+    inner: EnvironmentInner,
+}
+
+enum EnvironmentInner {
+    Lmdb(Environment, EnvOptions),
+    Memory(MemoryEnvironment),
 }
 
 impl LmdbEnvironmentManager {
@@ -32,36 +78,94 @@ impl LmdbEnvironmentManager {
     }
 
     pub fn create(base_path: &Path, name: &str) -> Result<Self, StorageError> {
+        Self::create_with_options(base_path, name, EnvOptions::default())
+    }
+
+    pub fn create_with_options(
+        base_path: &Path,
+        name: &str,
+        options: EnvOptions,
+    ) -> Result<Self, StorageError> {
         let full_path = base_path.join(Path::new(name));
 
         let mut builder = Environment::new();
         builder.set_max_dbs(DEFAULT_MAX_DBS);
-        builder.set_map_size(DEFAULT_MAX_MAP_SZ);
+        builder.set_map_size(options.map_size);
         builder.set_max_readers(DEFAULT_MAX_READERS);
         builder.set_flags(
             EnvironmentFlags::NO_SUB_DIR | EnvironmentFlags::NO_TLS | EnvironmentFlags::NO_LOCK,
         );
 
         let env = builder.open(&full_path).map_err(InternalDbError)?;
-        Ok(LmdbEnvironmentManager { inner: env })
+        Ok(LmdbEnvironmentManager {
+            inner: EnvironmentInner::Lmdb(env, options),
+        })
+    }
+
+    /// Reads all the entries of database `db_name` of the existing environment `name`, without
+    /// creating or modifying anything.
+    pub fn read_database(
+        base_path: &Path,
+        name: &str,
+        db_name: &str,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, StorageError> {
+        let full_path = base_path.join(Path::new(name));
+
+        let mut builder = Environment::new();
+        builder.set_max_dbs(DEFAULT_MAX_DBS);
+        builder.set_max_readers(DEFAULT_MAX_READERS);
+        builder.set_flags(
+            EnvironmentFlags::NO_SUB_DIR
+                | EnvironmentFlags::NO_TLS
+                | EnvironmentFlags::NO_LOCK
+                | EnvironmentFlags::READ_ONLY,
+        );
+
+        let env = builder.open(&full_path).map_err(InternalDbError)?;
+        let db = env.open_db(Some(db_name)).map_err(InternalDbError)?;
+        let txn = env.begin_ro_txn().map_err(InternalDbError)?;
+        let mut cursor = txn.open_ro_cursor(db).map_err(InternalDbError)?;
+        let entries = cursor
+            .iter_start()
+            .map(|entry| entry.map(|(key, value)| (key.to_vec(), value.to_vec())))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(InternalDbError)?;
+        Ok(entries)
+    }
+
+    /// Creates an environment whose databases live in memory, and are lost when it's dropped.
+    pub fn create_in_memory() -> Self {
+        LmdbEnvironmentManager {
+            inner: EnvironmentInner::Memory(MemoryEnvironment::default()),
+        }
     }
 
     pub fn create_txn(self) -> Result<SharedTransaction, StorageError> {
-        Ok(SharedTransaction::new(LmdbExclusiveTransaction::new(
-            self.inner,
-        )?))
+        let inner = match self.inner {
+            EnvironmentInner::Lmdb(env, options) => {
+                LmdbExclusiveTransaction::new(env, options.max_map_size)?
+            }
+            EnvironmentInner::Memory(env) => LmdbExclusiveTransaction {
+                inner: TransactionInner::Memory(env),
+            },
+        };
+        Ok(SharedTransaction::new(inner))
     }
 
     pub fn open_database(&mut self, name: &str, dup_keys: bool) -> Result<Database, StorageError> {
-        let mut flags = DatabaseFlags::default();
-        if dup_keys {
-            flags |= DatabaseFlags::DUP_SORT;
+        match &mut self.inner {
+            EnvironmentInner::Lmdb(env, _) => {
+                let mut flags = DatabaseFlags::default();
+                if dup_keys {
+                    flags |= DatabaseFlags::DUP_SORT;
+                }
+                let db = env.create_db(Some(name), flags).map_err(InternalDbError)?;
+                Ok(Database::Lmdb(db))
+            }
+            EnvironmentInner::Memory(env) => {
+                Ok(Database::Memory(env.open_database(name, dup_keys)))
+            }
         }
-        let db = self
-            .inner
-            .create_db(Some(name), flags)
-            .map_err(InternalDbError)?;
-        Ok(db)
     }
 
     pub fn set_comparator(
@@ -69,14 +173,22 @@ impl LmdbEnvironmentManager {
         db: Database,
         comparator: MDB_cmp_func,
     ) -> Result<(), StorageError> {
-        let txn = self.inner.begin_ro_txn()?;
-        unsafe {
-            assert_eq!(
-                mdb_set_compare(txn.txn(), db.dbi(), comparator),
-                MDB_SUCCESS
-            );
+        match (&mut self.inner, db) {
+            (EnvironmentInner::Lmdb(env, _), Database::Lmdb(db)) => {
+                let txn = env.begin_ro_txn()?;
+                unsafe {
+                    assert_eq!(
+                        mdb_set_compare(txn.txn(), db.dbi(), comparator),
+                        MDB_SUCCESS
+                    );
+                }
+                txn.commit().map_err(InternalDbError)
+            }
+            (EnvironmentInner::Memory(env), Database::Memory(db)) => {
+                env.set_comparator(db, comparator)
+            }
+            _ => Err(InvalidDatabase),
         }
-        txn.commit().map_err(InternalDbError)
     }
 }
 
@@ -94,6 +206,17 @@ impl SharedTransaction {
             .map_err(Self)
     }
 
+    /// Writes many key-value pairs under a single lock.
+    /// See [`LmdbExclusiveTransaction::put_batch`].
+    pub fn put_batch(
+        &self,
+        db: Database,
+        items: &[(&[u8], &[u8])],
+        options: PutBatchOptions,
+    ) -> Result<(), StorageError> {
+        self.write().put_batch(db, items, options)
+    }
+
     pub fn write(&self) -> impl DerefMut<Target = LmdbExclusiveTransaction> + '_ {
         self.0.write()
     }
@@ -112,48 +235,371 @@ unsafe impl Sync for SharedTransaction {}
 
 #[derive(Debug)]
 pub struct LmdbExclusiveTransaction {
-    inner: Option<RwTransaction<'static>>,
+    inner: TransactionInner,
+}
+
+#[derive(Debug)]
+enum TransactionInner {
+    Lmdb(LmdbTransaction),
+    /// Writes to an in-memory environment are visible at once, there is nothing to commit.
+    Memory(MemoryEnvironment),
+}
+
+/// A write that hasn't been committed yet.
+#[derive(Debug)]
+enum PendingWrite {
+    Put {
+        db: lmdb::Database,
+        key: Vec<u8>,
+        value: Vec<u8>,
+        flags: WriteFlags,
+    },
+    Del {
+        db: lmdb::Database,
+        key: Vec<u8>,
+        value: Option<Vec<u8>>,
+    },
+}
+
+impl PendingWrite {
+    fn size(&self) -> usize {
+        match self {
+            PendingWrite::Put { key, value, .. } => key.len() + value.len(),
+            PendingWrite::Del { key, value, .. } => key.len() + value.as_ref().map_or(0, Vec::len),
+        }
+    }
+
+    /// Returns `false` if a delete didn't find anything to delete.
+    fn apply(&self, txn: &mut RwTransaction) -> Result<bool, lmdb::Error> {
+        match self {
+            PendingWrite::Put {
+                db,
+                key,
+                value,
+                flags,
+            } => txn.put(*db, key, value, *flags).map(|_| true),
+            PendingWrite::Del { db, key, value } => match txn.del(*db, key, value.as_deref()) {
+                Ok(()) => Ok(true),
+                Err(lmdb::Error::NotFound) => Ok(false),
+                Err(err) => Err(err),
+            },
+        }
+    }
+}
+
+/// The write transaction of an LMDB environment, which grows the map when it's full.
+///
+/// The map is grown at commits, once it's half used, so that transactions rarely fill it. A
+/// transaction which hit `MDB_MAP_FULL` can only be aborted, so the writes since the last commit
+/// are kept, to be replayed in a new transaction once the map is resized, or to roll back to a
+/// savepoint. Only up to [`MAX_PENDING_SZ`] bytes are kept: past that, filling the map and rolling
+/// back fail until the next commit.
+#[derive(Debug)]
+struct LmdbTransaction {
+    txn: Option<RwTransaction<'static>>,
     env: Environment,
+    max_map_size: size_t,
+    pending: Vec<PendingWrite>,
+    /// Size of `pending`, or `None` if it went past [`MAX_PENDING_SZ`] and was dropped.
+    pending_size: Option<usize>,
 }
 
 const PANIC_MESSAGE: &str =
-    "LmdbExclusiveTransaction cannot be used after `commit_and_renew` fails.";
+    "LmdbExclusiveTransaction cannot be used after `commit_and_renew` or a map resize fails.";
 
-impl LmdbExclusiveTransaction {
-    pub fn new(env: Environment) -> Result<Self, StorageError> {
-        let inner = env.begin_rw_txn()?;
+impl LmdbTransaction {
+    fn begin(env: &Environment) -> Result<RwTransaction<'static>, StorageError> {
+        let txn = env.begin_rw_txn()?;
         // SAFETY:
-        // - `inner` does not reference data in `env`, it only has to be outlived by `env`.
-        // - We never expose `inner` to outside, so no one can observe its `'static` lifetime.
-        // - `inner` is dropped before `env`, guaranteed by `Rust` drop order.
-        let inner =
-            unsafe { std::mem::transmute::<RwTransaction<'_>, RwTransaction<'static>>(inner) };
+        // - `txn` does not reference data in `env`, it only has to be outlived by `env`.
+        // - We never expose `txn` to outside, so no one can observe its `'static` lifetime.
+        // - `txn` is dropped before `env`, guaranteed by `Rust` drop order.
+        Ok(unsafe { std::mem::transmute::<RwTransaction<'_>, RwTransaction<'static>>(txn) })
+    }
+
+    fn txn(&self) -> &RwTransaction<'static> {
+        self.txn.as_ref().expect(PANIC_MESSAGE)
+    }
+
+    fn write(&mut self, write: PendingWrite) -> Result<bool, StorageError> {
+        loop {
+            match write.apply(self.txn.as_mut().expect(PANIC_MESSAGE)) {
+                Err(lmdb::Error::MapFull) => self.grow()?,
+                result => {
+                    let written = result?;
+                    self.log(write);
+                    return Ok(written);
+                }
+            }
+        }
+    }
+
+    fn log(&mut self, write: PendingWrite) {
+        let size = match self.pending_size {
+            Some(size) => size + write.size(),
+            None => return,
+        };
+        if size > MAX_PENDING_SZ {
+            self.pending = vec![];
+            self.pending_size = None;
+        } else {
+            self.pending.push(write);
+            self.pending_size = Some(size);
+        }
+    }
+
+    fn commit_and_renew(&mut self) -> Result<(), StorageError> {
+        loop {
+            match self.txn.take().expect(PANIC_MESSAGE).commit() {
+                Err(lmdb::Error::MapFull) => self.grow()?,
+                result => {
+                    result?;
+                    break;
+                }
+            }
+        }
+        self.pending.clear();
+        self.pending_size = Some(0);
+        self.grow_ahead()?;
+        self.txn = Some(Self::begin(&self.env)?);
+        Ok(())
+    }
+
+    /// Discards the writes made after the first `len` pending writes.
+    fn rollback_to(&mut self, len: usize) -> Result<(), StorageError> {
+        if self.pending_size.is_none() {
+            return Err(TooManyWritesToRollBack);
+        }
+        if len >= self.pending.len() {
+            return Ok(());
+        }
+        self.pending.truncate(len);
+        // LMDB can't undo single writes, so the transaction is replaced by one with only the writes
+        // to keep.
+        if let Some(txn) = self.txn.take() {
+            txn.abort();
+        }
+        self.replay()
+    }
+
+    /// Doubles the map, up to `max_map_size`, and replays the pending writes in a new transaction.
+    fn grow(&mut self) -> Result<(), StorageError> {
+        // LMDB only allows resizing the map when no transaction is active. This is the only
+        // transaction of the environment, and `&mut self` guarantees no cursor borrows it.
+        if let Some(txn) = self.txn.take() {
+            txn.abort();
+        }
+        if self.pending_size.is_none() {
+            return Err(InternalDbError(lmdb::Error::MapFull));
+        }
+
+        let map_size = self.map_size()?;
+        if map_size >= self.max_map_size {
+            return Err(InternalDbError(lmdb::Error::MapFull));
+        }
+        self.set_map_size(map_size.saturating_mul(2).min(self.max_map_size))?;
+        self.replay()
+    }
+
+    /// Doubles the map, up to `max_map_size`, if more than half of it is used. Must be called
+    /// between transactions.
+    fn grow_ahead(&mut self) -> Result<(), StorageError> {
+        let map_size = self.map_size()?;
+        if map_size < self.max_map_size && self.used_size()? > map_size / 2 {
+            self.set_map_size(map_size.saturating_mul(2).min(self.max_map_size))?;
+        }
+        Ok(())
+    }
+
+    fn set_map_size(&mut self, map_size: size_t) -> Result<(), StorageError> {
+        debug_assert!(self.txn.is_none());
+        // SAFETY: No transaction is active, which callers guarantee.
+        let rc = unsafe { mdb_env_set_mapsize(self.env.env(), map_size) };
+        if rc != MDB_SUCCESS {
+            return Err(InternalDbError(lmdb::Error::from_err_code(rc)));
+        }
+        Ok(())
+    }
+
+    /// Replays the pending writes in a new transaction, growing the map if they don't fit.
+    fn replay(&mut self) -> Result<(), StorageError> {
+        let mut txn = Self::begin(&self.env)?;
+        let replayed = self
+            .pending
+            .iter()
+            .try_for_each(|write| write.apply(&mut txn).map(|_| ()));
+        match replayed {
+            Ok(()) => {
+                self.txn = Some(txn);
+                Ok(())
+            }
+            Err(lmdb::Error::MapFull) => {
+                txn.abort();
+                self.grow()
+            }
+            Err(err) => {
+                txn.abort();
+                Err(InternalDbError(err))
+            }
+        }
+    }
+
+    fn env_info(&self) -> Result<MDB_envinfo, StorageError> {
+        let mut info = MaybeUninit::<MDB_envinfo>::uninit();
+        // SAFETY: `mdb_env_info` initializes `info` when it succeeds.
+        let rc = unsafe { mdb_env_info(self.env.env(), info.as_mut_ptr()) };
+        if rc != MDB_SUCCESS {
+            return Err(InternalDbError(lmdb::Error::from_err_code(rc)));
+        }
+        Ok(unsafe { info.assume_init() })
+    }
+
+    fn map_size(&self) -> Result<size_t, StorageError> {
+        Ok(self.env_info()?.me_mapsize)
+    }
+
+    /// Id of the last committed transaction.
+    fn last_txn_id(&self) -> Result<size_t, StorageError> {
+        Ok(self.env_info()?.me_last_txnid)
+    }
+
+    /// Bytes of the map used by the last committed transaction.
+    fn used_size(&self) -> Result<size_t, StorageError> {
+        let info = self.env_info()?;
+        let mut stat = MaybeUninit::<MDB_stat>::uninit();
+        // SAFETY: `mdb_env_stat` initializes `stat` when it succeeds.
+        let rc = unsafe { mdb_env_stat(self.env.env(), stat.as_mut_ptr()) };
+        if rc != MDB_SUCCESS {
+            return Err(InternalDbError(lmdb::Error::from_err_code(rc)));
+        }
+        let stat = unsafe { stat.assume_init() };
+        Ok((info.me_last_pgno + 1).saturating_mul(stat.ms_psize as size_t))
+    }
+}
+
+impl LmdbExclusiveTransaction {
+    pub fn new(env: Environment, max_map_size: size_t) -> Result<Self, StorageError> {
+        let txn = LmdbTransaction::begin(&env)?;
         Ok(Self {
-            inner: Some(inner),
-            env,
+            inner: TransactionInner::Lmdb(LmdbTransaction {
+                txn: Some(txn),
+                env,
+                max_map_size,
+                pending: vec![],
+                pending_size: Some(0),
+            }),
         })
     }
 
     /// If this method fails, following calls to `self` will panic.
     pub fn commit_and_renew(&mut self) -> Result<(), StorageError> {
-        self.inner.take().expect(PANIC_MESSAGE).commit()?;
-        let inner = self.env.begin_rw_txn()?;
-        // SAFETY: Same as `new`.
-        let inner =
-            unsafe { std::mem::transmute::<RwTransaction<'_>, RwTransaction<'static>>(inner) };
-        self.inner = Some(inner);
+        match &mut self.inner {
+            TransactionInner::Lmdb(txn) => txn.commit_and_renew()?,
+            TransactionInner::Memory(env) => env.release_savepoints(),
+        }
         Ok(())
     }
 
+    /// Marks the current state of the transaction, to discard the writes made after it with
+    /// [`rollback_to`](Self::rollback_to). Savepoints are released when the transaction commits.
+    pub fn savepoint(&mut self) -> Savepoint {
+        match &mut self.inner {
+            TransactionInner::Lmdb(txn) => Savepoint(txn.pending.len()),
+            TransactionInner::Memory(env) => Savepoint(env.savepoint()),
+        }
+    }
+
+    /// Discards the writes made after `savepoint`. If that fails, following calls to `self` will
+    /// panic.
+    pub fn rollback_to(&mut self, savepoint: Savepoint) -> Result<(), StorageError> {
+        match &mut self.inner {
+            TransactionInner::Lmdb(txn) => txn.rollback_to(savepoint.0),
+            TransactionInner::Memory(env) => {
+                env.rollback_to(savepoint.0);
+                Ok(())
+            }
+        }
+    }
+
+    /// Size of the memory map, or `None` if the environment is in memory.
+    pub fn map_size(&self) -> Result<Option<usize>, StorageError> {
+        match &self.inner {
+            TransactionInner::Lmdb(txn) => txn.map_size().map(Some),
+            TransactionInner::Memory(_) => Ok(None),
+        }
+    }
+
+    /// Id of the last committed transaction, or `None` if the environment is in memory. Every
+    /// commit increments it.
+    pub fn last_txn_id(&self) -> Result<Option<usize>, StorageError> {
+        match &self.inner {
+            TransactionInner::Lmdb(txn) => txn.last_txn_id().map(Some),
+            TransactionInner::Memory(_) => Ok(None),
+        }
+    }
+
+    /// Grows the map if it's full. If that fails, following calls to `self` will panic.
     #[inline]
     pub fn put(&mut self, db: Database, key: &[u8], value: &[u8]) -> Result<(), StorageError> {
-        self.inner
-            .as_mut()
-            .expect(PANIC_MESSAGE)
-            .put(db, &key, &value, WriteFlags::default())
-            .map_err(InternalDbError)
+        match (&mut self.inner, db) {
+            (TransactionInner::Lmdb(txn), Database::Lmdb(db)) => txn
+                .write(PendingWrite::Put {
+                    db,
+                    key: key.to_vec(),
+                    value: value.to_vec(),
+                    flags: WriteFlags::default(),
+                })
+                .map(|_| ()),
+            (TransactionInner::Memory(env), Database::Memory(db)) => env.put(db, key, value),
+            _ => Err(InvalidDatabase),
+        }
+    }
+
+    /// Writes many key-value pairs, for bulk loads. Either all of them are written or, if one
+    /// fails, none. Grows the map if it's full. If that fails, following calls to `self` will
+    /// panic.
+    pub fn put_batch(
+        &mut self,
+        db: Database,
+        items: &[(&[u8], &[u8])],
+        options: PutBatchOptions,
+    ) -> Result<(), StorageError> {
+        let savepoint = self.savepoint();
+        let result = match (&mut self.inner, db) {
+            (TransactionInner::Lmdb(txn), Database::Lmdb(db)) => {
+                let flags = if options.append {
+                    WriteFlags::APPEND
+                } else {
+                    WriteFlags::default()
+                };
+                items.iter().try_for_each(|(key, value)| {
+                    txn.write(PendingWrite::Put {
+                        db,
+                        key: key.to_vec(),
+                        value: value.to_vec(),
+                        flags,
+                    })
+                    .map(|_| ())
+                })
+            }
+            (TransactionInner::Memory(env), Database::Memory(db)) => {
+                items.iter().try_for_each(|(key, value)| {
+                    if options.append {
+                        env.append(db, key, value)
+                    } else {
+                        env.put(db, key, value)
+                    }
+                })
+            }
+            _ => return Err(InvalidDatabase),
+        };
+        if result.is_err() {
+            self.rollback_to(savepoint)?;
+        }
+        result
     }
 
+    /// Grows the map if it's full. If that fails, following calls to `self` will panic.
     #[inline]
     pub fn del(
         &mut self,
@@ -161,44 +607,109 @@ impl LmdbExclusiveTransaction {
         key: &[u8],
         value: Option<&[u8]>,
     ) -> Result<bool, StorageError> {
-        match self
-            .inner
-            .as_mut()
-            .expect(PANIC_MESSAGE)
-            .del(db, &key, value)
-        {
-            Ok(()) => Ok(true),
-            Err(lmdb::Error::NotFound) => Ok(false),
-            Err(err) => Err(err.into()),
+        match (&mut self.inner, db) {
+            (TransactionInner::Lmdb(txn), Database::Lmdb(db)) => txn.write(PendingWrite::Del {
+                db,
+                key: key.to_vec(),
+                value: value.map(|value| value.to_vec()),
+            }),
+            (TransactionInner::Memory(env), Database::Memory(db)) => env.del(db, key, value),
+            _ => Err(InvalidDatabase),
         }
     }
 
     #[inline]
-    pub fn open_cursor(&mut self, db: Database) -> Result<RwCursor, StorageError> {
-        let cursor = self
-            .inner
-            .as_mut()
-            .expect(PANIC_MESSAGE)
-            .open_rw_cursor(db)?;
-        Ok(cursor)
+    pub fn open_cursor(&mut self, db: Database) -> Result<StorageCursor, StorageError> {
+        self.open_ro_cursor(db)
     }
 
     #[inline]
     pub fn get(&self, db: Database, key: &[u8]) -> Result<Option<&[u8]>, StorageError> {
-        match self.inner.as_ref().expect(PANIC_MESSAGE).get(db, &key) {
-            Ok(value) => Ok(Some(value)),
-            Err(lmdb::Error::NotFound) => Ok(None),
-            Err(err) => Err(err.into()),
+        match (&self.inner, db) {
+            (TransactionInner::Lmdb(txn), Database::Lmdb(db)) => match txn.txn().get(db, &key) {
+                Ok(value) => Ok(Some(value)),
+                Err(lmdb::Error::NotFound) => Ok(None),
+                Err(err) => Err(err.into()),
+            },
+            (TransactionInner::Memory(env), Database::Memory(db)) => env.get(db, key),
+            _ => Err(InvalidDatabase),
         }
     }
 
     #[inline]
-    pub fn open_ro_cursor(&self, db: Database) -> Result<RoCursor, StorageError> {
-        let cursor = self
-            .inner
-            .as_ref()
-            .expect(PANIC_MESSAGE)
-            .open_ro_cursor(db)?;
-        Ok(cursor)
+    pub fn open_ro_cursor(&self, db: Database) -> Result<StorageCursor, StorageError> {
+        match (&self.inner, db) {
+            (TransactionInner::Lmdb(txn), Database::Lmdb(db)) => {
+                Ok(StorageCursor::Lmdb(txn.txn().open_ro_cursor(db)?))
+            }
+            (TransactionInner::Memory(env), Database::Memory(db)) => {
+                Ok(StorageCursor::Memory(env.open_cursor(db)?))
+            }
+            _ => Err(InvalidDatabase),
+        }
+    }
+}
+
+/// A cursor opened by [`LmdbExclusiveTransaction`], on either storage.
+pub enum StorageCursor<'txn> {
+    Lmdb(RoCursor<'txn>),
+    Memory(MemoryCursor<'txn>),
+}
+
+impl<'txn> Seek<'txn> for StorageCursor<'txn> {
+    fn seek(&self, key: &[u8]) -> Result<bool, StorageError> {
+        match self {
+            StorageCursor::Lmdb(cursor) => cursor.seek(key),
+            StorageCursor::Memory(cursor) => cursor.seek(key),
+        }
+    }
+
+    fn seek_gte(&self, key: &[u8]) -> Result<bool, StorageError> {
+        match self {
+            StorageCursor::Lmdb(cursor) => cursor.seek_gte(key),
+            StorageCursor::Memory(cursor) => cursor.seek_gte(key),
+        }
+    }
+
+    fn seek_last(&self, key: &[u8]) -> Result<bool, StorageError> {
+        match self {
+            StorageCursor::Lmdb(cursor) => cursor.seek_last(key),
+            StorageCursor::Memory(cursor) => cursor.seek_last(key),
+        }
+    }
+
+    fn read(&'txn self) -> Result<Option<(&[u8], &[u8])>, StorageError> {
+        match self {
+            StorageCursor::Lmdb(cursor) => cursor.read(),
+            StorageCursor::Memory(cursor) => cursor.read(),
+        }
+    }
+
+    fn next(&self) -> Result<bool, StorageError> {
+        match self {
+            StorageCursor::Lmdb(cursor) => cursor.next(),
+            StorageCursor::Memory(cursor) => cursor.next(),
+        }
+    }
+
+    fn prev(&self) -> Result<bool, StorageError> {
+        match self {
+            StorageCursor::Lmdb(cursor) => cursor.prev(),
+            StorageCursor::Memory(cursor) => cursor.prev(),
+        }
+    }
+
+    fn first(&self) -> Result<bool, StorageError> {
+        match self {
+            StorageCursor::Lmdb(cursor) => cursor.first(),
+            StorageCursor::Memory(cursor) => cursor.first(),
+        }
+    }
+
+    fn last(&self) -> Result<bool, StorageError> {
+        match self {
+            StorageCursor::Lmdb(cursor) => cursor.last(),
+            StorageCursor::Memory(cursor) => cursor.last(),
+        }
     }
 }
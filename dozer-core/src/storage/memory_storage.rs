@@ -0,0 +1,356 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Bound::{Excluded, Unbounded};
+
+use libc::c_void;
+use lmdb_sys::{MDB_cmp_func, MDB_val};
+
+use crate::storage::common::Seek;
+use crate::storage::errors::StorageError;
+
+/// A key-value pair of an in-memory database. Entries are ordered by key, using the comparator
+/// of the database if it has one, then by value, like the duplicates of an LMDB `DUP_SORT` database.
+#[derive(Debug, Clone)]
+struct Entry {
+    key: Vec<u8>,
+    value: Vec<u8>,
+    comparator: MDB_cmp_func,
+}
+
+impl Entry {
+    fn new(key: &[u8], value: &[u8], comparator: MDB_cmp_func) -> Self {
+        Self {
+            key: key.to_vec(),
+            value: value.to_vec(),
+            comparator,
+        }
+    }
+
+    fn has_key(&self, key: &[u8]) -> bool {
+        compare_keys(self.comparator, &self.key, key) == Ordering::Equal
+    }
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare_keys(self.comparator, &self.key, &other.key)
+            .then_with(|| self.value.cmp(&other.value))
+    }
+}
+
+fn compare_keys(comparator: MDB_cmp_func, a: &[u8], b: &[u8]) -> Ordering {
+    match comparator {
+        Some(comparator) => {
+            let a = MDB_val {
+                mv_size: a.len(),
+                mv_data: a.as_ptr() as *mut c_void,
+            };
+            let b = MDB_val {
+                mv_size: b.len(),
+                mv_data: b.as_ptr() as *mut c_void,
+            };
+            // SAFETY: The comparators are the ones LMDB would call with the same values.
+            unsafe { comparator(&a, &b) }.cmp(&0)
+        }
+        None => a.cmp(b),
+    }
+}
+
+#[derive(Debug)]
+struct MemoryDatabase {
+    dup_keys: bool,
+    comparator: MDB_cmp_func,
+    entries: BTreeSet<Entry>,
+}
+
+/// A change to an in-memory database, kept to undo it.
+#[derive(Debug)]
+enum Change {
+    Inserted(usize, Entry),
+    Removed(usize, Entry),
+}
+
+/// The databases of an environment kept in memory, for pipelines which don't need to persist
+/// their state. Follows the semantics of LMDB, including duplicate keys and custom comparators.
+#[derive(Debug, Default)]
+pub struct MemoryEnvironment {
+    names: HashMap<String, usize>,
+    databases: Vec<MemoryDatabase>,
+    /// Changes made since the first savepoint, or `None` if there is no savepoint.
+    changes: Option<Vec<Change>>,
+}
+
+impl MemoryEnvironment {
+    /// Returns the id of the database named `name`, creating it if needed.
+    pub fn open_database(&mut self, name: &str, dup_keys: bool) -> usize {
+        if let Some(id) = self.names.get(name) {
+            return *id;
+        }
+        let id = self.databases.len();
+        self.databases.push(MemoryDatabase {
+            dup_keys,
+            comparator: None,
+            entries: BTreeSet::new(),
+        });
+        self.names.insert(name.to_string(), id);
+        id
+    }
+
+    pub fn set_comparator(
+        &mut self,
+        db: usize,
+        comparator: MDB_cmp_func,
+    ) -> Result<(), StorageError> {
+        let database = self
+            .databases
+            .get_mut(db)
+            .ok_or(StorageError::InvalidDatabase)?;
+        database.comparator = comparator;
+        database.entries = std::mem::take(&mut database.entries)
+            .into_iter()
+            .map(|entry| Entry {
+                comparator,
+                ..entry
+            })
+            .collect();
+        Ok(())
+    }
+
+    /// Starts recording changes, if needed, and returns the position to roll back to.
+    pub fn savepoint(&mut self) -> usize {
+        self.changes.get_or_insert_with(Vec::new).len()
+    }
+
+    /// Undoes the changes recorded after position `savepoint`.
+    pub fn rollback_to(&mut self, savepoint: usize) {
+        let undone = match &mut self.changes {
+            Some(changes) => changes.split_off(savepoint.min(changes.len())),
+            None => return,
+        };
+        for change in undone.into_iter().rev() {
+            match change {
+                Change::Inserted(db, entry) => {
+                    self.databases[db].entries.remove(&entry);
+                }
+                Change::Removed(db, entry) => {
+                    self.databases[db].entries.insert(entry);
+                }
+            }
+        }
+    }
+
+    /// Stops recording changes. Following rollbacks don't undo anything made until now.
+    pub fn release_savepoints(&mut self) {
+        self.changes = None;
+    }
+
+    fn record(&mut self, change: Change) {
+        if let Some(changes) = &mut self.changes {
+            changes.push(change);
+        }
+    }
+
+    fn database(&self, db: usize) -> Result<&MemoryDatabase, StorageError> {
+        self.databases.get(db).ok_or(StorageError::InvalidDatabase)
+    }
+
+    fn database_mut(&mut self, db: usize) -> Result<&mut MemoryDatabase, StorageError> {
+        self.databases
+            .get_mut(db)
+            .ok_or(StorageError::InvalidDatabase)
+    }
+
+    pub fn put(&mut self, db: usize, key: &[u8], value: &[u8]) -> Result<(), StorageError> {
+        let database = self.database_mut(db)?;
+        let entry = Entry::new(key, value, database.comparator);
+        let mut removed = None;
+        if !database.dup_keys {
+            if let Some(existing) = first_with_key(&database.entries, key, database.comparator) {
+                let existing = existing.clone();
+                database.entries.remove(&existing);
+                removed = Some(existing);
+            }
+        }
+        let inserted = database.entries.insert(entry.clone());
+
+        if let Some(removed) = removed {
+            self.record(Change::Removed(db, removed));
+        }
+        if inserted {
+            self.record(Change::Inserted(db, entry));
+        }
+        Ok(())
+    }
+
+    /// Like [`put`](Self::put), but `key` must be greater than the keys already in the database,
+    /// like with LMDB's `MDB_APPEND`. Fails with `MDB_KEYEXIST` otherwise.
+    pub fn append(&mut self, db: usize, key: &[u8], value: &[u8]) -> Result<(), StorageError> {
+        let database = self.database(db)?;
+        if let Some(last) = database.entries.iter().next_back() {
+            if compare_keys(database.comparator, key, &last.key) != Ordering::Greater {
+                return Err(StorageError::InternalDbError(lmdb::Error::KeyExist));
+            }
+        }
+        self.put(db, key, value)
+    }
+
+    /// Deletes `value` of `key`, or all its values if `value` is `None`. Like LMDB, `value` is
+    /// ignored if the database doesn't have duplicate keys.
+    pub fn del(
+        &mut self,
+        db: usize,
+        key: &[u8],
+        value: Option<&[u8]>,
+    ) -> Result<bool, StorageError> {
+        let database = self.database_mut(db)?;
+        let removed: Vec<Entry> = match value {
+            Some(value) if database.dup_keys => database
+                .entries
+                .take(&Entry::new(key, value, database.comparator))
+                .into_iter()
+                .collect(),
+            _ => {
+                let removed: Vec<Entry> = database
+                    .entries
+                    .range(Entry::new(key, &[], database.comparator)..)
+                    .take_while(|entry| entry.has_key(key))
+                    .cloned()
+                    .collect();
+                for entry in &removed {
+                    database.entries.remove(entry);
+                }
+                removed
+            }
+        };
+
+        let found = !removed.is_empty();
+        for entry in removed {
+            self.record(Change::Removed(db, entry));
+        }
+        Ok(found)
+    }
+
+    /// Returns the first value of `key`.
+    pub fn get(&self, db: usize, key: &[u8]) -> Result<Option<&[u8]>, StorageError> {
+        let database = self.database(db)?;
+        Ok(first_with_key(&database.entries, key, database.comparator)
+            .map(|entry| entry.value.as_slice()))
+    }
+
+    pub fn open_cursor(&self, db: usize) -> Result<MemoryCursor, StorageError> {
+        let database = self.database(db)?;
+        Ok(MemoryCursor {
+            entries: &database.entries,
+            comparator: database.comparator,
+            current: RefCell::new(None),
+        })
+    }
+}
+
+fn first_with_key<'a>(
+    entries: &'a BTreeSet<Entry>,
+    key: &[u8],
+    comparator: MDB_cmp_func,
+) -> Option<&'a Entry> {
+    entries
+        .range(Entry::new(key, &[], comparator)..)
+        .next()
+        .filter(|entry| entry.has_key(key))
+}
+
+/// A cursor over an in-memory database. Moves like an LMDB cursor, visiting every value of a key.
+#[derive(Debug)]
+pub struct MemoryCursor<'txn> {
+    entries: &'txn BTreeSet<Entry>,
+    comparator: MDB_cmp_func,
+    /// A copy of the entry under the cursor. Keeping a reference instead would make the cursor
+    /// invariant over `'txn`.
+    current: RefCell<Option<Entry>>,
+}
+
+impl<'txn> MemoryCursor<'txn> {
+    fn current(&self) -> Option<&'txn Entry> {
+        let entries = self.entries;
+        self.current
+            .borrow()
+            .as_ref()
+            .and_then(|current| entries.get(current))
+    }
+
+    fn move_to(&self, entry: Option<&Entry>) -> bool {
+        *self.current.borrow_mut() = entry.cloned();
+        entry.is_some()
+    }
+}
+
+impl<'txn> Seek<'txn> for MemoryCursor<'txn> {
+    fn seek(&self, key: &[u8]) -> Result<bool, StorageError> {
+        Ok(self.move_to(first_with_key(self.entries, key, self.comparator)))
+    }
+
+    fn seek_gte(&self, key: &[u8]) -> Result<bool, StorageError> {
+        Ok(self.move_to(
+            self.entries
+                .range(Entry::new(key, &[], self.comparator)..)
+                .next(),
+        ))
+    }
+
+    fn seek_last(&self, key: &[u8]) -> Result<bool, StorageError> {
+        Ok(self.move_to(
+            self.entries
+                .range(Entry::new(key, &[], self.comparator)..)
+                .take_while(|entry| entry.has_key(key))
+                .last(),
+        ))
+    }
+
+    fn read(&'txn self) -> Result<Option<(&[u8], &[u8])>, StorageError> {
+        Ok(self
+            .current()
+            .map(|entry| (entry.key.as_slice(), entry.value.as_slice())))
+    }
+
+    fn next(&self) -> Result<bool, StorageError> {
+        let next = match self.current() {
+            Some(current) => self.entries.range((Excluded(current), Unbounded)).next(),
+            None => self.entries.first(),
+        };
+        // Like LMDB, the cursor stays on the last entry if there is no next one.
+        Ok(next.is_some() && self.move_to(next))
+    }
+
+    fn prev(&self) -> Result<bool, StorageError> {
+        let prev = match self.current() {
+            Some(current) => self
+                .entries
+                .range((Unbounded, Excluded(current)))
+                .next_back(),
+            None => self.entries.last(),
+        };
+        Ok(prev.is_some() && self.move_to(prev))
+    }
+
+    fn first(&self) -> Result<bool, StorageError> {
+        Ok(self.move_to(self.entries.first()))
+    }
+
+    fn last(&self) -> Result<bool, StorageError> {
+        Ok(self.move_to(self.entries.last()))
+    }
+}
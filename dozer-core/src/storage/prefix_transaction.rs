@@ -1,10 +1,8 @@
-use lmdb::RoCursor;
-
 use crate::storage::common::Database;
 use crate::storage::errors::StorageError;
 
 use super::common::Seek;
-use super::lmdb_storage::LmdbExclusiveTransaction;
+use super::lmdb_storage::{LmdbExclusiveTransaction, StorageCursor};
 
 pub struct PrefixTransaction<'a> {
     prefix: [u8; 4],
@@ -58,11 +56,11 @@ impl<'a> PrefixTransaction<'a> {
 
 pub struct PrefixReaderCursor<'txn> {
     prefix: [u8; 4],
-    inner: RoCursor<'txn>,
+    inner: StorageCursor<'txn>,
 }
 
 impl<'txn> PrefixReaderCursor<'txn> {
-    pub fn new(inner: RoCursor<'txn>, prefix: [u8; 4]) -> Self {
+    pub fn new(inner: StorageCursor<'txn>, prefix: [u8; 4]) -> Self {
         Self { inner, prefix }
     }
 }
@@ -84,6 +82,28 @@ impl<'txn> PrefixReaderCursor<'txn> {
         self.inner.seek(&full_key)
     }
 
+    #[inline]
+    pub fn seek_last(&self, key: &[u8]) -> Result<bool, StorageError> {
+        let mut full_key = Vec::with_capacity(key.len() + self.prefix.len());
+        full_key.extend(self.prefix);
+        full_key.extend(key);
+        self.inner.seek_last(&full_key)
+    }
+
+    #[inline]
+    pub fn seek_range_end(&self, key: &[u8]) -> Result<bool, StorageError> {
+        let mut full_key = Vec::with_capacity(key.len() + self.prefix.len());
+        full_key.extend(self.prefix);
+        full_key.extend(key);
+        if !self.inner.seek_range_end(&full_key)? {
+            return Ok(false);
+        }
+        match self.inner.read()? {
+            Some((key, _val)) => Ok(key[0..4] == self.prefix),
+            None => Ok(false),
+        }
+    }
+
     #[inline]
     #[allow(clippy::type_complexity)]
     pub fn read(&self) -> Result<Option<(&[u8], &[u8])>, StorageError> {
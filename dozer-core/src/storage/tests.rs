@@ -1,4 +1,8 @@
 #[cfg(test)]
+mod lmdb_storage;
+#[cfg(test)]
 mod lmdb_sys;
 #[cfg(test)]
+mod memory_storage;
+#[cfg(test)]
 mod prefix_transaction;
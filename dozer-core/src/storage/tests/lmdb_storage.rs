@@ -0,0 +1,214 @@
+use tempdir::TempDir;
+
+use crate::storage::common::Seek;
+use crate::storage::lmdb_storage::{
+    EnvOptions, LmdbEnvironmentManager, PutBatchOptions, SharedTransaction,
+};
+
+const MAP_SIZE: usize = 1024 * 1024;
+
+fn value(n: u32) -> Vec<u8> {
+    let mut value = vec![0_u8; 1024];
+    value[..4].copy_from_slice(&n.to_be_bytes());
+    value
+}
+
+#[test]
+fn test_map_grows_when_full() {
+    let tmp_dir = TempDir::new("lmdb_storage").unwrap();
+    let mut env = LmdbEnvironmentManager::create_with_options(
+        tmp_dir.path(),
+        "test",
+        EnvOptions {
+            map_size: MAP_SIZE,
+            max_map_size: 64 * MAP_SIZE,
+        },
+    )
+    .unwrap();
+    let db = env.open_database("test_db", false).unwrap();
+    let txn = env.create_txn().unwrap();
+    let mut txn = SharedTransaction::try_unwrap(txn).unwrap();
+
+    // Writes 4 times the initial map size, across a commit.
+    for n in 0..2048_u32 {
+        txn.put(db, &n.to_be_bytes(), &value(n)).unwrap();
+    }
+    txn.commit_and_renew().unwrap();
+    for n in 2048..4096_u32 {
+        txn.put(db, &n.to_be_bytes(), &value(n)).unwrap();
+    }
+    assert!(txn.del(db, &0_u32.to_be_bytes(), None).unwrap());
+    txn.commit_and_renew().unwrap();
+
+    assert!(txn.map_size().unwrap().unwrap() > 4 * MAP_SIZE);
+    assert_eq!(txn.get(db, &0_u32.to_be_bytes()).unwrap(), None);
+    for n in 1..4096_u32 {
+        assert_eq!(
+            txn.get(db, &n.to_be_bytes()).unwrap(),
+            Some(value(n).as_slice())
+        );
+    }
+}
+
+#[test]
+fn test_map_grows_at_commit_when_half_used() {
+    let tmp_dir = TempDir::new("lmdb_storage").unwrap();
+    let mut env = LmdbEnvironmentManager::create_with_options(
+        tmp_dir.path(),
+        "test",
+        EnvOptions {
+            map_size: MAP_SIZE,
+            max_map_size: 64 * MAP_SIZE,
+        },
+    )
+    .unwrap();
+    let db = env.open_database("test_db", false).unwrap();
+    let txn = env.create_txn().unwrap();
+    let mut txn = SharedTransaction::try_unwrap(txn).unwrap();
+
+    // About two thirds of the map, which fit without growing it.
+    for n in 0..500_u32 {
+        txn.put(db, &n.to_be_bytes(), &value(n)).unwrap();
+    }
+    assert_eq!(txn.map_size().unwrap().unwrap(), MAP_SIZE);
+    txn.commit_and_renew().unwrap();
+    assert_eq!(txn.map_size().unwrap().unwrap(), 2 * MAP_SIZE);
+}
+
+#[test]
+fn test_map_does_not_grow_past_max_size() {
+    let tmp_dir = TempDir::new("lmdb_storage").unwrap();
+    let mut env = LmdbEnvironmentManager::create_with_options(
+        tmp_dir.path(),
+        "test",
+        EnvOptions {
+            map_size: MAP_SIZE,
+            max_map_size: 2 * MAP_SIZE,
+        },
+    )
+    .unwrap();
+    let db = env.open_database("test_db", false).unwrap();
+    let txn = env.create_txn().unwrap();
+    let mut txn = SharedTransaction::try_unwrap(txn).unwrap();
+
+    let result = (0..4096_u32).try_for_each(|n| txn.put(db, &n.to_be_bytes(), &value(n)));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_put_batch_loads_rows_in_one_commit() {
+    let tmp_dir = TempDir::new("lmdb_storage").unwrap();
+    let mut env = LmdbEnvironmentManager::create(tmp_dir.path(), "test").unwrap();
+    let db = env.open_database("test_db", false).unwrap();
+    let txn = env.create_txn().unwrap();
+
+    const ROWS: u32 = 100_000;
+    let keys = (0..ROWS).map(|n| n.to_be_bytes()).collect::<Vec<_>>();
+    let items = keys
+        .iter()
+        .map(|key| (key.as_slice(), key.as_slice()))
+        .collect::<Vec<_>>();
+    let last_txn_id = txn.read().last_txn_id().unwrap().unwrap();
+    txn.put_batch(db, &items, PutBatchOptions { append: true })
+        .unwrap();
+    txn.write().commit_and_renew().unwrap();
+    assert_eq!(txn.read().last_txn_id().unwrap().unwrap(), last_txn_id + 1);
+
+    let txn = txn.read();
+    let cursor = txn.open_ro_cursor(db).unwrap();
+    let mut count = 0;
+    let mut found = cursor.first().unwrap();
+    while found {
+        let (key, value) = cursor.read().unwrap().unwrap();
+        assert_eq!(key, (count as u32).to_be_bytes());
+        assert_eq!(key, value);
+        count += 1;
+        found = cursor.next().unwrap();
+    }
+    assert_eq!(count, ROWS as usize);
+}
+
+#[test]
+fn test_put_batch_append_rejects_unsorted_keys() {
+    let tmp_dir = TempDir::new("lmdb_storage").unwrap();
+    let mut env = LmdbEnvironmentManager::create(tmp_dir.path(), "test").unwrap();
+    let db = env.open_database("test_db", false).unwrap();
+    let txn = env.create_txn().unwrap();
+
+    let items: [(&[u8], &[u8]); 2] = [(b"b", b"b"), (b"a", b"a")];
+    assert!(txn
+        .put_batch(db, &items, PutBatchOptions { append: true })
+        .is_err());
+}
+
+#[test]
+fn test_rollback_to_savepoint() {
+    let tmp_dir = TempDir::new("lmdb_storage").unwrap();
+    let mut env = LmdbEnvironmentManager::create(tmp_dir.path(), "test").unwrap();
+    let db = env.open_database("test_db", false).unwrap();
+    let txn = env.create_txn().unwrap();
+    let mut txn = SharedTransaction::try_unwrap(txn).unwrap();
+
+    txn.put(db, b"a", b"1").unwrap();
+    let savepoint = txn.savepoint();
+    txn.put(db, b"a", b"2").unwrap();
+    txn.put(db, b"b", b"2").unwrap();
+    txn.rollback_to(savepoint).unwrap();
+
+    assert_eq!(txn.get(db, b"a").unwrap(), Some(b"1".as_slice()));
+    assert_eq!(txn.get(db, b"b").unwrap(), None);
+
+    // Writes after the rollback are kept
+    txn.put(db, b"c", b"3").unwrap();
+    txn.commit_and_renew().unwrap();
+    assert_eq!(txn.get(db, b"a").unwrap(), Some(b"1".as_slice()));
+    assert_eq!(txn.get(db, b"c").unwrap(), Some(b"3".as_slice()));
+}
+
+#[test]
+fn test_put_batch_is_atomic() {
+    let tmp_dir = TempDir::new("lmdb_storage").unwrap();
+    let mut env = LmdbEnvironmentManager::create(tmp_dir.path(), "test").unwrap();
+    let db = env.open_database("test_db", false).unwrap();
+    let txn = env.create_txn().unwrap();
+    let mut txn = SharedTransaction::try_unwrap(txn).unwrap();
+
+    txn.put(db, b"a", b"1").unwrap();
+    let items: [(&[u8], &[u8]); 3] = [(b"b", b"2"), (b"c", b"2"), (b"a", b"2")];
+    assert!(txn
+        .put_batch(db, &items, PutBatchOptions { append: true })
+        .is_err());
+
+    txn.commit_and_renew().unwrap();
+    assert_eq!(txn.get(db, b"a").unwrap(), Some(b"1".as_slice()));
+    assert_eq!(txn.get(db, b"b").unwrap(), None);
+    assert_eq!(txn.get(db, b"c").unwrap(), None);
+}
+
+#[test]
+fn test_read_database() {
+    let tmp_dir = TempDir::new("lmdb_storage").unwrap();
+    {
+        let mut env = LmdbEnvironmentManager::create(tmp_dir.path(), "test").unwrap();
+        let db = env.open_database("test_db", false).unwrap();
+        let txn = env.create_txn().unwrap();
+        let mut txn = SharedTransaction::try_unwrap(txn).unwrap();
+        txn.put(db, b"b", b"2").unwrap();
+        txn.put(db, b"a", b"1").unwrap();
+        txn.commit_and_renew().unwrap();
+    }
+
+    let entries = LmdbEnvironmentManager::read_database(tmp_dir.path(), "test", "test_db").unwrap();
+    assert_eq!(
+        entries,
+        vec![
+            (b"a".to_vec(), b"1".to_vec()),
+            (b"b".to_vec(), b"2".to_vec())
+        ]
+    );
+
+    // Nothing is created
+    assert!(LmdbEnvironmentManager::read_database(tmp_dir.path(), "test", "other_db").is_err());
+    assert!(LmdbEnvironmentManager::read_database(tmp_dir.path(), "other", "test_db").is_err());
+    assert!(!LmdbEnvironmentManager::exists(tmp_dir.path(), "other"));
+}
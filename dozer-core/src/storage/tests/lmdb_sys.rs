@@ -68,6 +68,64 @@ fn test_cursor_duplicate_keys() {
     }
 }
 
+#[test]
+fn test_cursor_duplicate_keys_reverse() {
+    let tmp_dir = TempDir::new("example").unwrap();
+    if tmp_dir.path().exists() {
+        fs::remove_dir_all(tmp_dir.path()).unwrap();
+    }
+    fs::create_dir(tmp_dir.path()).unwrap();
+
+    let mut builder = Environment::new();
+    builder.set_flags(EnvironmentFlags::NO_SYNC | EnvironmentFlags::WRITE_MAP);
+    builder.set_max_dbs(10);
+    builder.set_map_size(1024 * 1024 * 1024);
+
+    let env = builder.open(tmp_dir.path()).unwrap();
+    let db = env
+        .create_db(Some("test"), DatabaseFlags::DUP_SORT)
+        .unwrap();
+
+    let mut tx = env.begin_rw_txn().unwrap();
+
+    for k in 1..3 {
+        for i in 'a'..'s' {
+            tx.put(
+                db,
+                &format!("key_{}", k).as_bytes(),
+                &format!("val_{}", i).as_bytes(),
+                WriteFlags::default(),
+            )
+            .unwrap();
+        }
+    }
+
+    let cursor = tx.open_ro_cursor(db).unwrap();
+
+    assert!(!cursor.seek_last("key_100".as_bytes()).unwrap());
+    assert!(!cursor.seek_range_end("key_0".as_bytes()).unwrap());
+
+    // "key_15" sorts between "key_1" and "key_2".
+    assert!(cursor.seek_range_end("key_15".as_bytes()).unwrap());
+    let r = cursor.read().unwrap().unwrap();
+    assert_eq!(r, ("key_1".as_bytes(), "val_r".as_bytes()));
+
+    assert!(cursor.seek_range_end("key_3".as_bytes()).unwrap());
+    let r = cursor.read().unwrap().unwrap();
+    assert_eq!(r, ("key_2".as_bytes(), "val_r".as_bytes()));
+
+    assert!(cursor.seek_last("key_2".as_bytes()).unwrap());
+    for k in (1..3).rev() {
+        for i in ('a'..'s').rev() {
+            let r = cursor.read().unwrap().unwrap();
+            assert_eq!(r.0, format!("key_{}", k).as_bytes());
+            assert_eq!(r.1, format!("val_{}", i).as_bytes());
+            let r = cursor.prev().unwrap();
+            assert_eq!(r, k != 1 || i != 'a');
+        }
+    }
+}
+
 fn create_env() -> (Environment, Database) {
     let tmp_dir = TempDir::new("concurrent").unwrap();
     if tmp_dir.path().exists() {
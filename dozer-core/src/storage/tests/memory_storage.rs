@@ -0,0 +1,170 @@
+use crate::storage::common::Seek;
+use crate::storage::lmdb_storage::{LmdbEnvironmentManager, PutBatchOptions, SharedTransaction};
+
+#[test]
+fn test_cursor_duplicate_keys_in_memory() {
+    let mut env = LmdbEnvironmentManager::create_in_memory();
+    let db = env.open_database("test", true).unwrap();
+    let tx = env.create_txn().unwrap();
+    let mut tx = SharedTransaction::try_unwrap(tx).unwrap();
+
+    for k in 1..3 {
+        for i in 'a'..'s' {
+            tx.put(
+                db,
+                format!("key_{}", k).as_bytes(),
+                format!("val_{}", i).as_bytes(),
+            )
+            .unwrap();
+        }
+    }
+
+    let cursor = tx.open_ro_cursor(db).unwrap();
+
+    let r = cursor.seek("key_100".as_bytes()).unwrap();
+    assert!(!r);
+
+    let r = cursor.seek("key_1".as_bytes()).unwrap();
+    assert!(r);
+
+    for i in 'a'..='z' {
+        let r = cursor.read().unwrap().unwrap();
+        if r.0 != "key_1".as_bytes() {
+            break;
+        }
+
+        assert_eq!(r.0, "key_1".as_bytes());
+        assert_eq!(r.1, format!("val_{}", i).as_bytes());
+        let _r = cursor.next().unwrap();
+    }
+
+    for i in 'a'..='z' {
+        let r = cursor.read().unwrap().unwrap();
+        assert_eq!(r.0, "key_2".as_bytes());
+        assert_eq!(r.1, format!("val_{}", i).as_bytes());
+        let r = cursor.next().unwrap();
+
+        if !r {
+            break;
+        }
+    }
+}
+
+#[test]
+fn test_cursor_duplicate_keys_reverse_in_memory() {
+    let mut env = LmdbEnvironmentManager::create_in_memory();
+    let db = env.open_database("test", true).unwrap();
+    let tx = env.create_txn().unwrap();
+    let mut tx = SharedTransaction::try_unwrap(tx).unwrap();
+
+    for k in 1..3 {
+        for i in 'a'..'s' {
+            tx.put(
+                db,
+                format!("key_{}", k).as_bytes(),
+                format!("val_{}", i).as_bytes(),
+            )
+            .unwrap();
+        }
+    }
+
+    let cursor = tx.open_ro_cursor(db).unwrap();
+
+    assert!(!cursor.seek_last("key_100".as_bytes()).unwrap());
+    assert!(!cursor.seek_range_end("key_0".as_bytes()).unwrap());
+
+    // "key_15" sorts between "key_1" and "key_2".
+    assert!(cursor.seek_range_end("key_15".as_bytes()).unwrap());
+    let r = cursor.read().unwrap().unwrap();
+    assert_eq!(r, ("key_1".as_bytes(), "val_r".as_bytes()));
+
+    assert!(cursor.seek_range_end("key_3".as_bytes()).unwrap());
+    let r = cursor.read().unwrap().unwrap();
+    assert_eq!(r, ("key_2".as_bytes(), "val_r".as_bytes()));
+
+    assert!(cursor.seek_last("key_2".as_bytes()).unwrap());
+    for k in (1..3).rev() {
+        for i in ('a'..'s').rev() {
+            let r = cursor.read().unwrap().unwrap();
+            assert_eq!(r.0, format!("key_{}", k).as_bytes());
+            assert_eq!(r.1, format!("val_{}", i).as_bytes());
+            let r = cursor.prev().unwrap();
+            assert_eq!(r, k != 1 || i != 'a');
+        }
+    }
+}
+
+#[test]
+fn test_put_and_del_in_memory() {
+    let mut env = LmdbEnvironmentManager::create_in_memory();
+    let db = env.open_database("test", false).unwrap();
+    let dup_db = env.open_database("test_dup", true).unwrap();
+    let tx = env.create_txn().unwrap();
+    let mut tx = SharedTransaction::try_unwrap(tx).unwrap();
+
+    tx.put(db, b"key", b"val_a").unwrap();
+    tx.put(db, b"key", b"val_b").unwrap();
+    assert_eq!(tx.get(db, b"key").unwrap(), Some(b"val_b".as_slice()));
+    assert!(tx.del(db, b"key", Some(b"val_a")).unwrap());
+    assert_eq!(tx.get(db, b"key").unwrap(), None);
+    assert!(!tx.del(db, b"key", None).unwrap());
+
+    tx.put(dup_db, b"key", b"val_b").unwrap();
+    tx.put(dup_db, b"key", b"val_a").unwrap();
+    tx.put(dup_db, b"key", b"val_a").unwrap();
+    assert_eq!(tx.get(dup_db, b"key").unwrap(), Some(b"val_a".as_slice()));
+    assert!(tx.del(dup_db, b"key", Some(b"val_a")).unwrap());
+    assert!(!tx.del(dup_db, b"key", Some(b"val_a")).unwrap());
+    assert_eq!(tx.get(dup_db, b"key").unwrap(), Some(b"val_b".as_slice()));
+    assert!(tx.del(dup_db, b"key", None).unwrap());
+    assert_eq!(tx.get(dup_db, b"key").unwrap(), None);
+
+    let cursor = tx.open_ro_cursor(dup_db).unwrap();
+    assert!(!cursor.first().unwrap());
+    assert!(cursor.read().unwrap().is_none());
+}
+
+#[test]
+fn test_rollback_to_savepoint_in_memory() {
+    let mut env = LmdbEnvironmentManager::create_in_memory();
+    let db = env.open_database("test", false).unwrap();
+    let dup_db = env.open_database("test_dup", true).unwrap();
+    let tx = env.create_txn().unwrap();
+    let mut tx = SharedTransaction::try_unwrap(tx).unwrap();
+
+    tx.put(db, b"key", b"val_a").unwrap();
+    tx.put(dup_db, b"key", b"val_a").unwrap();
+    let savepoint = tx.savepoint();
+    tx.put(db, b"key", b"val_b").unwrap();
+    tx.put(db, b"other", b"val_b").unwrap();
+    tx.put(dup_db, b"key", b"val_b").unwrap();
+    assert!(tx.del(dup_db, b"key", Some(b"val_a")).unwrap());
+    tx.rollback_to(savepoint).unwrap();
+
+    assert_eq!(tx.get(db, b"key").unwrap(), Some(b"val_a".as_slice()));
+    assert_eq!(tx.get(db, b"other").unwrap(), None);
+    assert_eq!(tx.get(dup_db, b"key").unwrap(), Some(b"val_a".as_slice()));
+    assert!(tx.del(dup_db, b"key", Some(b"val_a")).unwrap());
+    assert_eq!(tx.get(dup_db, b"key").unwrap(), None);
+}
+
+#[test]
+fn test_put_batch_append_in_memory() {
+    let mut env = LmdbEnvironmentManager::create_in_memory();
+    let db = env.open_database("test", false).unwrap();
+    let tx = env.create_txn().unwrap();
+    let mut tx = SharedTransaction::try_unwrap(tx).unwrap();
+
+    let items: [(&[u8], &[u8]); 2] = [(b"a", b"a"), (b"b", b"b")];
+    tx.put_batch(db, &items, PutBatchOptions { append: true })
+        .unwrap();
+
+    // Keys must be greater than the ones already in the database, and the batch is written
+    // entirely or not at all.
+    let items: [(&[u8], &[u8]); 2] = [(b"c", b"c"), (b"b", b"c")];
+    assert!(tx
+        .put_batch(db, &items, PutBatchOptions { append: true })
+        .is_err());
+    assert_eq!(tx.get(db, b"b").unwrap(), Some(b"b".as_slice()));
+    assert_eq!(tx.get(db, b"c").unwrap(), None);
+}
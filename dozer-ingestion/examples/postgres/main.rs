@@ -16,6 +16,7 @@ fn main() {
             name: "users".to_string(),
             id: 0,
             columns: None,
+            primary_key_columns: None,
         }]),
         config: tokio_postgres::Config::default()
             .host("127.0.0.1")
@@ -1,7 +1,9 @@
 use std::collections::HashMap;
 use std::{str::FromStr, sync::Arc};
 
-use crate::connectors::{Connector, ValidationResults};
+use crate::connectors::{
+    apply_primary_key_columns, Connector, ConnectorCapabilities, ValidationResults,
+};
 use crate::ingestion::Ingestor;
 use crate::{
     connectors::{ethereum::helper, TableInfo},
@@ -34,6 +36,8 @@ pub struct EthConnector {
 pub struct ContractTuple(pub Contract, pub String);
 
 pub const ETH_LOGS_TABLE: &str = "eth_logs";
+const DEFAULT_MAX_FETCH_DEPTH: usize = 100;
+const DEFAULT_MIN_BLOCK_RANGE: u64 = 1;
 impl EthConnector {
     pub fn build_filter(filter: &EthFilter) -> Filter {
         let builder = FilterBuilder::default();
@@ -126,6 +130,17 @@ impl EthConnector {
 }
 
 impl Connector for EthConnector {
+    /// Logs are fetched from the filter's starting block, then followed as blocks are mined. The
+    /// schemas are fixed, or generated from the contracts' ABIs.
+    fn capabilities(&self) -> ConnectorCapabilities {
+        ConnectorCapabilities {
+            supports_cdc: true,
+            supports_snapshot: true,
+            supports_schema_inference: true,
+            supports_resume: true,
+        }
+    }
+
     fn get_schemas(
         &self,
         tables: Option<Vec<TableInfo>>,
@@ -150,11 +165,13 @@ impl Connector for EthConnector {
         schemas.extend(event_schemas);
 
         let schemas = if let Some(tables) = tables {
-            schemas
+            let mut schemas: Vec<_> = schemas
                 .iter()
                 .filter(|(n, _, _)| tables.iter().any(|t| t.name == *n))
                 .cloned()
-                .collect()
+                .collect();
+            apply_primary_key_columns(&mut schemas, &tables)?;
+            schemas
         } else {
             schemas
         };
@@ -172,6 +189,7 @@ impl Connector for EthConnector {
                 name: name.to_string(),
                 id: id as u32,
                 columns: Some(schema.fields.iter().map(|f| f.name.to_owned()).collect()),
+                primary_key_columns: None,
             })
             .collect();
         Ok(tables)
@@ -187,7 +205,7 @@ impl Connector for EthConnector {
         Ok(())
     }
 
-    fn start(&self, _from_seq: Option<(u64, u64)>) -> Result<(), ConnectorError> {
+    fn start(&self, from_seq: Option<(u64, u64)>) -> Result<(), ConnectorError> {
         // Start a new thread that interfaces with ETH node
         let wss_url = self.config.wss_url.to_owned();
         let filter = self.config.filter.to_owned().unwrap_or_default();
@@ -206,6 +224,13 @@ impl Connector for EthConnector {
                 self.contracts.to_owned(),
                 self.tables.to_owned(),
                 self.schema_map.to_owned(),
+                self.config
+                    .max_fetch_depth
+                    .map_or(DEFAULT_MAX_FETCH_DEPTH, |depth| depth as usize),
+                self.config
+                    .min_block_range
+                    .unwrap_or(DEFAULT_MIN_BLOCK_RANGE),
+                from_seq,
             ));
             run(details).await
         })
@@ -1,13 +1,16 @@
+use dozer_types::log::warn;
+use dozer_types::rust_decimal::Decimal;
 use dozer_types::types::{
     Field, FieldDefinition, FieldType, Operation, OperationEvent, Record,
     ReplicationChangesTrackingType, Schema, SchemaIdentifier, SchemaWithChangesType,
 };
 use std::collections::HashMap;
+use std::str::FromStr;
 use std::sync::Arc;
 
-use web3::ethabi::RawLog;
+use web3::ethabi::{ParamType, RawLog, Token};
 use web3::transports::WebSocket;
-use web3::types::{Log, H256};
+use web3::types::{Log, H256, U256};
 
 use crate::connectors::TableInfo;
 
@@ -28,26 +31,18 @@ pub fn get_contract_event_schemas(
 
     for (_, contract_tuple) in contracts {
         for event in contract_tuple.0.events.values().flatten() {
-            let mut fields = vec![];
-            for input in event.inputs.iter().cloned() {
-                fields.push(FieldDefinition {
-                    name: input.name,
-                    typ: match input.kind {
-                        web3::ethabi::ParamType::Address => FieldType::String,
-                        web3::ethabi::ParamType::Bytes => FieldType::Binary,
-                        web3::ethabi::ParamType::FixedBytes(_) => FieldType::Binary,
-                        web3::ethabi::ParamType::Int(_) => FieldType::UInt,
-                        web3::ethabi::ParamType::Uint(_) => FieldType::UInt,
-                        web3::ethabi::ParamType::Bool => FieldType::Boolean,
-                        web3::ethabi::ParamType::String => FieldType::String,
-                        // TODO: These are to be mapped to appropriate types
-                        web3::ethabi::ParamType::Array(_)
-                        | web3::ethabi::ParamType::FixedArray(_, _)
-                        | web3::ethabi::ParamType::Tuple(_) => FieldType::Text,
-                    },
-                    nullable: false,
-                });
-            }
+            let fields = event
+                .inputs
+                .iter()
+                .map(|input| {
+                    let (typ, nullable) = map_param_type(&input.kind, input.indexed);
+                    FieldDefinition {
+                        name: input.name.clone(),
+                        typ,
+                        nullable,
+                    }
+                })
+                .collect();
 
             let schema_id = schema_map
                 .get(&event.signature())
@@ -78,13 +73,8 @@ pub fn decode_event(
     tables: Option<Vec<TableInfo>>,
     schema_map: HashMap<H256, usize>,
 ) -> Option<OperationEvent> {
-    // Topics 0, 1, 2 should be name, buyer, seller in most cases
-    let name = log
-        .topics
-        .get(0)
-        .expect("name is expected")
-        .to_owned()
-        .to_string();
+    // Topic 0 is the signature of the event, unless the event is anonymous
+    let signature = *log.topics.get(0)?;
 
     let address = format!("{:?}", log.address);
 
@@ -95,7 +85,7 @@ pub fn decode_event(
             .values()
             .flatten()
             .into_iter()
-            .find(|evt| evt.signature().to_string() == name);
+            .find(|evt| !evt.anonymous && evt.signature() == signature);
 
         if let Some(event) = opt_event {
             let schema_id = schema_map
@@ -109,23 +99,26 @@ pub fn decode_event(
                 tables.map_or(true, |tables| tables.iter().any(|t| t.name == table_name));
             if is_table_required {
                 // let event = contract.event(&name_str).unwrap();
-                let parsed_event = event
-                    .parse_log(RawLog {
-                        topics: log.topics,
-                        data: log.data.0,
-                    })
-                    .unwrap_or_else(|_| {
-                        panic!(
-                            "parsing event failed: block_no: {}, txn_hash: {}. Have you included the right abi to address mapping ?",
-                            log.block_number.unwrap(),
-                            log.transaction_hash.unwrap()
-                        )
-                    });
+                let parsed_event = match event.parse_log(RawLog {
+                    topics: log.topics,
+                    data: log.data.0,
+                }) {
+                    Ok(parsed_event) => parsed_event,
+                    Err(e) => {
+                        warn!(
+                            "Skipping log that failed to parse: {}, block_no: {:?}, txn_hash: {:?}. Have you included the right abi to address mapping ?",
+                            e, log.block_number, log.transaction_hash
+                        );
+                        return None;
+                    }
+                };
 
+                // Params are in the order of the inputs, whether they are indexed or not
                 let values = parsed_event
                     .params
                     .into_iter()
-                    .map(|p| map_abitype_to_field(p.value))
+                    .zip(event.inputs.iter())
+                    .map(|(p, input)| map_abitype_to_field(p.value, &input.kind))
                     .collect();
                 Some(OperationEvent {
                     seq_no,
@@ -155,19 +148,63 @@ pub fn get_table_name(contract_tuple: &ContractTuple, event_name: &str) -> Strin
     format!("{}_{}", contract_tuple.1, event_name)
 }
 
-pub fn map_abitype_to_field(f: web3::ethabi::Token) -> Field {
+/// Returns the field type and nullability of an event parameter.
+///
+/// Indexed parameters of dynamic types are stored as the keccak hash of their value in the topics,
+/// so they can only be mapped to their hash. Integers that don't fit in 64 bits are mapped to
+/// decimals, which are null when the value doesn't fit in a `Decimal` either.
+pub fn map_param_type(kind: &ParamType, indexed: bool) -> (FieldType, bool) {
+    match kind {
+        ParamType::String
+        | ParamType::Bytes
+        | ParamType::Array(_)
+        | ParamType::FixedArray(_, _)
+        | ParamType::Tuple(_)
+            if indexed =>
+        {
+            (FieldType::Binary, false)
+        }
+        ParamType::Address => (FieldType::String, false),
+        ParamType::Bytes => (FieldType::Binary, false),
+        ParamType::FixedBytes(_) => (FieldType::Binary, false),
+        ParamType::Int(size) if *size <= 64 => (FieldType::Int, false),
+        ParamType::Uint(size) if *size <= 64 => (FieldType::UInt, false),
+        ParamType::Int(size) | ParamType::Uint(size) => {
+            (FieldType::Decimal, *size > MAX_DECIMAL_BITS)
+        }
+        ParamType::Bool => (FieldType::Boolean, false),
+        ParamType::String => (FieldType::String, false),
+        // TODO: These are to be mapped to appropriate types
+        ParamType::Array(_) | ParamType::FixedArray(_, _) | ParamType::Tuple(_) => {
+            (FieldType::Text, false)
+        }
+    }
+}
+
+/// Number of bits of the mantissa of a `Decimal`
+const MAX_DECIMAL_BITS: usize = 96;
+
+pub fn map_abitype_to_field(f: Token, kind: &ParamType) -> Field {
     match f {
-        web3::ethabi::Token::Address(f) => Field::String(format!("{:?}", f)),
-        web3::ethabi::Token::FixedBytes(f) => Field::Binary(f),
-        web3::ethabi::Token::Bytes(f) => Field::Binary(f),
-        // TODO: Convert i64 appropriately
-        web3::ethabi::Token::Int(f) => Field::UInt(f.low_u64()),
-        web3::ethabi::Token::Uint(f) => Field::UInt(f.low_u64()),
-        web3::ethabi::Token::Bool(f) => Field::Boolean(f),
-        web3::ethabi::Token::String(f) => Field::String(f),
-        web3::ethabi::Token::FixedArray(f)
-        | web3::ethabi::Token::Array(f)
-        | web3::ethabi::Token::Tuple(f) => Field::Text(
+        Token::Address(f) => Field::String(format!("{:?}", f)),
+        Token::FixedBytes(f) => Field::Binary(f),
+        Token::Bytes(f) => Field::Binary(f),
+        // Signed integers are sign extended to 256 bits
+        Token::Int(f) => match kind {
+            ParamType::Int(size) if *size <= 64 => Field::Int(f.low_u64() as i64),
+            _ if f.bit(255) => {
+                let (abs, _) = (!f).overflowing_add(U256::one());
+                u256_to_decimal(abs).map_or(Field::Null, |d| Field::Decimal(-d))
+            }
+            _ => u256_to_decimal(f).map_or(Field::Null, Field::Decimal),
+        },
+        Token::Uint(f) => match kind {
+            ParamType::Uint(size) if *size <= 64 => Field::UInt(f.low_u64()),
+            _ => u256_to_decimal(f).map_or(Field::Null, Field::Decimal),
+        },
+        Token::Bool(f) => Field::Boolean(f),
+        Token::String(f) => Field::String(f),
+        Token::FixedArray(f) | Token::Array(f) | Token::Tuple(f) => Field::Text(
             f.iter()
                 .map(|f| f.to_string())
                 .collect::<Vec<String>>()
@@ -175,6 +212,11 @@ pub fn map_abitype_to_field(f: web3::ethabi::Token) -> Field {
         ),
     }
 }
+
+fn u256_to_decimal(value: U256) -> Option<Decimal> {
+    Decimal::from_str(&value.to_string()).ok()
+}
+
 pub fn map_log_to_event(log: Log, details: Arc<EthDetails>) -> Option<OperationEvent> {
     // Check if table is requested
     let is_table_required = details.tables.as_ref().map_or(true, |tables| {
@@ -200,6 +242,15 @@ pub fn map_log_to_event(log: Log, details: Arc<EthDetails>) -> Option<OperationE
     }
 }
 
+pub fn get_block_and_log_index(log: &Log) -> (u64, u64) {
+    let block_no = log
+        .block_number
+        .expect("expected for non pendning")
+        .as_u64();
+    let log_idx = log.log_index.expect("expected for non pendning").as_u64();
+    (block_no, log_idx)
+}
+
 pub fn get_id(log: &Log) -> u64 {
     let block_no = log
         .block_number
@@ -10,6 +10,7 @@ use crate::{
 use dozer_types::ingestion_types::{EthFilter, IngestionMessage};
 use dozer_types::log::{debug, trace};
 use dozer_types::parking_lot::RwLock;
+use dozer_types::types::OperationEvent;
 
 use futures::StreamExt;
 
@@ -28,9 +29,14 @@ pub struct EthDetails {
     contracts: HashMap<String, ContractTuple>,
     pub tables: Option<Vec<TableInfo>>,
     pub schema_map: HashMap<H256, usize>,
+    max_fetch_depth: usize,
+    min_block_range: u64,
+    /// Sequence number of the last forwarded message, as `(block_number, index in block)`
+    last_seq: RwLock<Option<(u64, u64)>>,
 }
 
 impl EthDetails {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         wss_url: String,
         filter: EthFilter,
@@ -38,6 +44,9 @@ impl EthDetails {
         contracts: HashMap<String, ContractTuple>,
         tables: Option<Vec<TableInfo>>,
         schema_map: HashMap<H256, usize>,
+        max_fetch_depth: usize,
+        min_block_range: u64,
+        from_seq: Option<(u64, u64)>,
     ) -> Self {
         EthDetails {
             wss_url,
@@ -46,8 +55,37 @@ impl EthDetails {
             contracts,
             tables,
             schema_map,
+            max_fetch_depth,
+            min_block_range,
+            last_seq: RwLock::new(from_seq),
         }
     }
+
+    /// Forwards `op` unless a message at or after `seq` was forwarded already, which happens for
+    /// logs that were checkpointed before a restart.
+    fn forward(&self, seq: (u64, u64), op: OperationEvent) -> Result<(), ConnectorError> {
+        let mut last_seq = self.last_seq.write();
+        if last_seq.map_or(false, |last_seq| seq <= last_seq) {
+            trace!("Skipping already forwarded : {:?}", op);
+            return Ok(());
+        }
+
+        self.ingestor
+            .write()
+            .handle_message((seq, IngestionMessage::OperationEvent(op)))
+            .map_err(ConnectorError::IngestorError)?;
+        *last_seq = Some(seq);
+        Ok(())
+    }
+}
+
+/// Returns the block to fetch the logs from: the block of the last checkpointed log when resuming,
+/// otherwise `from_block` of the filter, defaulting to the current block.
+pub fn get_start_block(last_seq: Option<(u64, u64)>, filter: &EthFilter, block_end: u64) -> u64 {
+    match last_seq {
+        Some((block_no, _)) => block_no,
+        None => filter.from_block.unwrap_or(block_end),
+    }
 }
 
 #[allow(unreachable_code)]
@@ -64,11 +102,7 @@ pub async fn run(details: Arc<EthDetails>) -> Result<(), ConnectorError> {
         .map_err(ConnectorError::EthError)?
         .as_u64();
 
-    // Default to current block if from_block is not specified
-    let block_start = match details.filter.from_block {
-        Some(block_no) => block_no,
-        None => block_end,
-    };
+    let block_start = get_start_block(*details.last_seq.read(), &details.filter, block_end);
 
     fetch_logs(details.clone(), client.clone(), block_start, block_end, 0).await?;
 
@@ -133,10 +167,11 @@ pub fn fetch_logs(
                 web3::Error::Rpc(rpc_error) => {
                     // Infura returns a RpcError if the no of records are more than 10000
                     // { code: ServerError(-32005), message: "query returned more than 10000 results", data: None }
-                    // break it down into half on each error and exit after 10 errors in a specific branch
+                    // break it down into half on each error and give up after `max_fetch_depth` splits in a specific branch.
+                    // Logs of the blocks before this range were forwarded already, so a restart resumes from them.
                     if rpc_error.code.code() == -32005 {
-                        debug!("{} More than 10000 records, block_start: {},block_end: {}, depth: {}", depth_str, block_start, block_end, depth);                
-                        if depth > 100 {
+                        debug!("{} More than 10000 records, block_start: {},block_end: {}, depth: {}", depth_str, block_start, block_end, depth);
+                        if depth >= details.max_fetch_depth || block_end.saturating_sub(block_start) < details.min_block_range {
                             Err(ConnectorError::EthTooManyRecurisions(depth))
                         } else {
                             let middle = (block_start + block_end) / 2;
@@ -153,7 +188,7 @@ pub fn fetch_logs(
                             fetch_logs(
                                 details,
                                 client.clone(),
-                                middle,
+                                middle + 1,
                                 block_end,
                                 depth + 1,
                             )
@@ -176,14 +211,12 @@ fn process_log(details: Arc<EthDetails>, msg: Log) -> Result<(), ConnectorError>
     if msg.log_index.is_none() {
         Ok(())
     } else {
+        // Each log is followed by its decoded event
+        let (block_no, log_idx) = helper::get_block_and_log_index(&msg);
         if let Some(op) = helper::map_log_to_event(msg.to_owned(), details.clone()) {
             trace!("Writing log : {:?}", op);
             // Write eth_log record
-            details
-                .ingestor
-                .write()
-                .handle_message(((0, 0), IngestionMessage::OperationEvent(op)))
-                .map_err(ConnectorError::IngestorError)?;
+            details.forward((block_no, log_idx * 2), op)?;
         } else {
             trace!("Ignoring log : {:?}", msg);
         }
@@ -198,11 +231,7 @@ fn process_log(details: Arc<EthDetails>, msg: Log) -> Result<(), ConnectorError>
         );
         if let Some(op) = op {
             trace!("Writing event : {:?}", op);
-            details
-                .ingestor
-                .write()
-                .handle_message(((0, 0), IngestionMessage::OperationEvent(op)))
-                .map_err(ConnectorError::IngestorError)?;
+            details.forward((block_no, log_idx * 2 + 1), op)?;
         } else {
             trace!("Writing event : {:?}", op);
         }
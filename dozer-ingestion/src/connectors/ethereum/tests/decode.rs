@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use dozer_types::rust_decimal::Decimal;
+use dozer_types::serde_json;
+use dozer_types::types::{Field, FieldType, Operation};
+use web3::ethabi::{encode, Contract, Token};
+use web3::signing::keccak256;
+use web3::types::{Address, Bytes, Log, H256, U256, U64};
+
+use crate::connectors::ethereum::connector::ContractTuple;
+use crate::connectors::ethereum::helper::{decode_event, get_contract_event_schemas};
+
+const ABI: &str = r#"[
+    {
+        "type": "event",
+        "name": "Transfer",
+        "anonymous": false,
+        "inputs": [
+            {"name": "from", "type": "address", "indexed": true},
+            {"name": "memo", "type": "string", "indexed": true},
+            {"name": "value", "type": "uint256", "indexed": false},
+            {"name": "delta", "type": "int256", "indexed": false},
+            {"name": "nonce", "type": "uint64", "indexed": false}
+        ]
+    }
+]"#;
+
+fn get_contracts() -> (
+    Address,
+    HashMap<String, ContractTuple>,
+    HashMap<H256, usize>,
+) {
+    let address = Address::from_low_u64_be(42);
+    let contract: Contract = serde_json::from_str(ABI).unwrap();
+    let signature = contract.event("Transfer").unwrap().signature();
+
+    let contracts = HashMap::from([(
+        format!("{:?}", address),
+        ContractTuple(contract, "token".to_string()),
+    )]);
+    (address, contracts, HashMap::from([(signature, 2)]))
+}
+
+fn get_log(address: Address, topics: Vec<H256>, data: Vec<u8>) -> Log {
+    Log {
+        address,
+        topics,
+        data: Bytes(data),
+        block_hash: None,
+        block_number: Some(U64::from(1)),
+        transaction_hash: None,
+        transaction_index: Some(U64::from(0)),
+        log_index: Some(U256::from(0)),
+        transaction_log_index: None,
+        log_type: None,
+        removed: None,
+    }
+}
+
+#[test]
+fn test_contract_event_schemas() {
+    let (_, contracts, schema_map) = get_contracts();
+
+    let schemas = get_contract_event_schemas(contracts, schema_map);
+    assert_eq!(schemas.len(), 1);
+
+    let (name, schema, _) = &schemas[0];
+    assert_eq!(name, "token_Transfer");
+    let types: Vec<(FieldType, bool)> = schema.fields.iter().map(|f| (f.typ, f.nullable)).collect();
+    assert_eq!(
+        types,
+        vec![
+            (FieldType::String, false),
+            // Indexed strings are only available as their hash
+            (FieldType::Binary, false),
+            (FieldType::Decimal, true),
+            (FieldType::Decimal, true),
+            (FieldType::UInt, false),
+        ]
+    );
+}
+
+#[test]
+fn test_decode_event() {
+    let (address, contracts, schema_map) = get_contracts();
+    let signature = *schema_map.keys().next().unwrap();
+
+    let from = Address::from_low_u64_be(7);
+    let memo_hash = keccak256(b"memo");
+    let data = encode(&[
+        Token::Uint(U256::from(1_000_000_000_000_000_000u128)),
+        // -5 as a two's complement 256 bit integer
+        Token::Int(!U256::from(4)),
+        Token::Uint(U256::from(3)),
+    ]);
+    let log = get_log(
+        address,
+        vec![signature, H256::from(from), H256::from(memo_hash)],
+        data,
+    );
+
+    let event = decode_event(log, contracts, None, schema_map).unwrap();
+    let values = if let Operation::Insert { new } = event.operation {
+        new.values
+    } else {
+        panic!("expected insert");
+    };
+    assert_eq!(
+        values,
+        vec![
+            Field::String(format!("{:?}", from)),
+            Field::Binary(memo_hash.to_vec()),
+            Field::Decimal(Decimal::from_str("1000000000000000000").unwrap()),
+            Field::Decimal(Decimal::from(-5)),
+            Field::UInt(3),
+        ]
+    );
+}
+
+#[test]
+fn test_decode_event_ignores_unknown_logs() {
+    let (address, contracts, schema_map) = get_contracts();
+
+    // Unknown event signature
+    let log = get_log(address, vec![H256::from_low_u64_be(1)], vec![]);
+    assert!(decode_event(log, contracts.clone(), None, schema_map.clone()).is_none());
+
+    // Anonymous events have no signature topic
+    let log = get_log(address, vec![], vec![]);
+    assert!(decode_event(log, contracts, None, schema_map).is_none());
+}
+
+#[test]
+fn test_decode_event_skips_unparsable_logs() {
+    let (address, contracts, schema_map) = get_contracts();
+    let signature = *schema_map.keys().next().unwrap();
+
+    // Indexed topics and data are missing
+    let log = get_log(address, vec![signature], vec![]);
+    assert!(decode_event(log, contracts, None, schema_map).is_none());
+}
@@ -69,6 +69,8 @@ pub fn get_eth_producer(
                     .to_string(),
             }],
             wss_url,
+            max_fetch_depth: None,
+            min_block_range: None,
         },
     );
 
@@ -107,9 +109,10 @@ pub fn run_eth_sample(wss_url: String, my_account: H160) -> (Contract<WebSocket>
     let mut op_index = HashSet::new();
     while let Some(msg) = iterator.write().next_timeout(Duration::from_millis(400)) {
         // Duplicates are to be expected in ethereum connector
-        let (_, IngestionOperation::OperationEvent(ev)) = msg;
-        if op_index.insert(ev.seq_no) {
-            msgs.push(ev.operation);
+        if let (_, IngestionOperation::OperationEvent(ev)) = msg {
+            if op_index.insert(ev.seq_no) {
+                msgs.push(ev.operation);
+            }
         }
     }
     (contract, msgs)
@@ -1,2 +1,4 @@
 mod connector;
+mod decode;
 pub mod helper;
+mod sender;
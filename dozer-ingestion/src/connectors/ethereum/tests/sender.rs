@@ -0,0 +1,22 @@
+use dozer_types::ingestion_types::EthFilter;
+
+use crate::connectors::ethereum::sender::get_start_block;
+
+#[test]
+fn test_get_start_block() {
+    let from_block = |from_block: Option<u64>| EthFilter {
+        from_block,
+        ..Default::default()
+    };
+
+    // Fresh start
+    assert_eq!(get_start_block(None, &from_block(Some(100)), 500), 100);
+    assert_eq!(get_start_block(None, &from_block(None), 500), 500);
+
+    // Resuming from the checkpoint, even when it's before from_block or the current block
+    assert_eq!(
+        get_start_block(Some((50, 3)), &from_block(Some(100)), 500),
+        50
+    );
+    assert_eq!(get_start_block(Some((200, 0)), &from_block(None), 500), 200);
+}
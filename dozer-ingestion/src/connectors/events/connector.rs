@@ -1,12 +1,18 @@
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use dozer_types::models::source::Source;
-use dozer_types::types::ReplicationChangesTrackingType;
+use dozer_types::types::{
+    Field, FieldDefinition, FieldType, Operation, Record, SchemaWithChangesType,
+};
 use dozer_types::{ingestion_types::IngestionMessage, parking_lot::RwLock};
 
 use crate::connectors::ValidationResults;
 use crate::{
-    connectors::{Connector, TableInfo},
+    connectors::{
+        apply_primary_key_columns, mark_keyless_tables_append_only, Connector,
+        ConnectorCapabilities, TableInfo,
+    },
     errors::ConnectorError,
     ingestion::Ingestor,
 };
@@ -14,44 +20,145 @@ use crate::{
 pub struct EventsConnector {
     pub id: u64,
     pub name: String,
+    schemas: Vec<SchemaWithChangesType>,
     ingestor: Option<Arc<RwLock<Ingestor>>>,
+    /// Sequence number of the last pushed message, every push is its own transaction
+    last_seq_no: AtomicU64,
 }
 
 impl EventsConnector {
-    pub fn new(id: u64, name: String) -> Self {
+    pub fn new(id: u64, name: String, schemas: Vec<SchemaWithChangesType>) -> Self {
         Self {
             id,
             name,
+            schemas,
             ingestor: None,
+            last_seq_no: AtomicU64::new(0),
         }
     }
 
+    /// Continues numbering pushed messages after `last_committed`, so that they are not mistaken
+    /// for already processed ones after a restart. `start` does this with its `from_seq`.
+    pub fn seed_seq(&self, last_committed: (u64, u64)) {
+        self.last_seq_no.store(last_committed.0, Ordering::SeqCst);
+    }
+
+    /// Pushes `msg` to the ingestor with the next sequence number `(n, 0)`. Records of operations
+    /// must match the registered schema with their schema identifier.
     pub fn push(&mut self, msg: IngestionMessage) -> Result<(), ConnectorError> {
+        if let IngestionMessage::OperationEvent(event) = &msg {
+            match &event.operation {
+                Operation::Insert { new } => self.validate_record(new)?,
+                Operation::Delete { old } => self.validate_record(old)?,
+                Operation::Update { old, new } => {
+                    self.validate_record(old)?;
+                    self.validate_record(new)?;
+                }
+            }
+        }
+
         let ingestor = self
             .ingestor
             .as_ref()
             .map_or(Err(ConnectorError::InitializationError), Ok)?;
 
+        let seq_no = self.last_seq_no.fetch_add(1, Ordering::SeqCst) + 1;
         ingestor
             .write()
-            .handle_message(((0, 0), msg))
+            .handle_message(((seq_no, 0), msg))
             .map_err(ConnectorError::IngestorError)
     }
+
+    fn validate_record(&self, record: &Record) -> Result<(), ConnectorError> {
+        let (table_name, schema, _) = self
+            .schemas
+            .iter()
+            .find(|(_, schema, _)| {
+                record.schema_id.is_some() && schema.identifier == record.schema_id
+            })
+            .ok_or(ConnectorError::SchemaNotRegistered(record.schema_id))?;
+
+        if record.values.len() != schema.fields.len() {
+            return Err(ConnectorError::RecordSchemaMismatch(
+                table_name.clone(),
+                format!(
+                    "expected {} fields, got {}",
+                    schema.fields.len(),
+                    record.values.len()
+                ),
+            ));
+        }
+
+        for (value, definition) in record.values.iter().zip(schema.fields.iter()) {
+            if !field_matches(value, definition) {
+                return Err(ConnectorError::RecordSchemaMismatch(
+                    table_name.clone(),
+                    format!(
+                        "field {} of type {}{} can't hold {:?}",
+                        definition.name,
+                        definition.typ,
+                        if definition.nullable {
+                            " (nullable)"
+                        } else {
+                            ""
+                        },
+                        value
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn field_matches(field: &Field, definition: &FieldDefinition) -> bool {
+    matches!(
+        (field, definition.typ),
+        (Field::UInt(_), FieldType::UInt)
+            | (Field::Int(_), FieldType::Int)
+            | (Field::Float(_), FieldType::Float)
+            | (Field::Boolean(_), FieldType::Boolean)
+            | (Field::String(_), FieldType::String)
+            | (Field::Text(_), FieldType::Text)
+            | (Field::Binary(_), FieldType::Binary)
+            | (Field::Decimal(_), FieldType::Decimal)
+            | (Field::Timestamp(_), FieldType::Timestamp)
+            | (Field::Date(_), FieldType::Date)
+            | (Field::Bson(_), FieldType::Bson)
+            | (Field::Json(_), FieldType::Json)
+    ) || (definition.nullable && field == &Field::Null)
 }
 
 impl Connector for EventsConnector {
+    /// Events are pushed by the application, with the schemas it declared.
+    fn capabilities(&self) -> ConnectorCapabilities {
+        ConnectorCapabilities {
+            supports_cdc: true,
+            supports_snapshot: false,
+            supports_schema_inference: false,
+            supports_resume: true,
+        }
+    }
+
     fn get_schemas(
         &self,
-        _table_names: Option<Vec<TableInfo>>,
-    ) -> Result<
-        Vec<(
-            String,
-            dozer_types::types::Schema,
-            ReplicationChangesTrackingType,
-        )>,
-        ConnectorError,
-    > {
-        Ok(vec![])
+        table_names: Option<Vec<TableInfo>>,
+    ) -> Result<Vec<SchemaWithChangesType>, ConnectorError> {
+        let mut schemas: Vec<SchemaWithChangesType> = self
+            .schemas
+            .iter()
+            .filter(|(name, _, _)| {
+                table_names.as_ref().map_or(true, |tables| {
+                    tables.iter().any(|table| &table.name == name)
+                })
+            })
+            .cloned()
+            .collect();
+        if let Some(tables) = &table_names {
+            apply_primary_key_columns(&mut schemas, tables)?;
+        }
+        mark_keyless_tables_append_only(&mut schemas);
+        Ok(schemas)
     }
 
     fn get_tables(&self) -> Result<Vec<TableInfo>, ConnectorError> {
@@ -73,7 +180,10 @@ impl Connector for EventsConnector {
         Ok(())
     }
 
-    fn start(&self, _from_seq: Option<(u64, u64)>) -> Result<(), ConnectorError> {
+    fn start(&self, from_seq: Option<(u64, u64)>) -> Result<(), ConnectorError> {
+        if let Some(from_seq) = from_seq {
+            self.seed_seq(from_seq);
+        }
         Ok(())
     }
 
@@ -1,2 +1,5 @@
 #[allow(dead_code)]
 pub mod connector;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,183 @@
+use crate::connectors::events::connector::EventsConnector;
+use crate::connectors::{Connector, ConnectorCapabilities, TableInfo};
+use crate::errors::ConnectorError;
+use crate::ingestion::{IngestionConfig, Ingestor};
+use dozer_types::ingestion_types::{IngestionMessage, IngestionOperation};
+use dozer_types::types::{
+    Field, FieldDefinition, FieldType, Operation, OperationEvent, Record,
+    ReplicationChangesTrackingType, Schema, SchemaIdentifier,
+};
+
+fn get_connector() -> EventsConnector {
+    let mut schema = Schema::empty();
+    schema
+        .field(
+            FieldDefinition::new("id".to_string(), FieldType::Int, false),
+            true,
+        )
+        .field(
+            FieldDefinition::new("name".to_string(), FieldType::String, true),
+            false,
+        );
+    schema.identifier = Some(SchemaIdentifier { id: 1, version: 1 });
+
+    EventsConnector::new(
+        3,
+        "events".to_string(),
+        vec![(
+            "users".to_string(),
+            schema,
+            ReplicationChangesTrackingType::FullChanges,
+        )],
+    )
+}
+
+fn insert(schema_id: u32, values: Vec<Field>) -> IngestionMessage {
+    IngestionMessage::OperationEvent(OperationEvent::new(
+        0,
+        Operation::Insert {
+            new: Record::new(
+                Some(SchemaIdentifier {
+                    id: schema_id,
+                    version: 1,
+                }),
+                values,
+                None,
+            ),
+        },
+    ))
+}
+
+#[test]
+fn test_events_get_schemas() {
+    let connector = get_connector();
+
+    let schemas = connector.get_schemas(None).unwrap();
+    assert_eq!(schemas.len(), 1);
+    assert_eq!(schemas[0].0, "users");
+
+    let tables = vec![TableInfo {
+        name: "orders".to_string(),
+        id: 0,
+        columns: None,
+        primary_key_columns: None,
+    }];
+    assert!(connector.get_schemas(Some(tables)).unwrap().is_empty());
+}
+
+#[test]
+fn test_events_push_validates_record() {
+    let mut connector = get_connector();
+    let (ingestor, iterator) = Ingestor::initialize_channel(IngestionConfig::default());
+    connector.initialize(ingestor, None).unwrap();
+
+    connector
+        .push(insert(1, vec![Field::Int(1), Field::Null]))
+        .unwrap();
+    let (_, op) = iterator.write().next().unwrap();
+    assert!(matches!(op, IngestionOperation::OperationEvent(_)));
+
+    assert!(matches!(
+        connector.push(insert(2, vec![Field::Int(1), Field::Null])),
+        Err(ConnectorError::SchemaNotRegistered(_))
+    ));
+    assert!(matches!(
+        connector.push(insert(1, vec![Field::Int(1)])),
+        Err(ConnectorError::RecordSchemaMismatch(_, _))
+    ));
+    assert!(matches!(
+        connector.push(insert(1, vec![Field::Null, Field::Null])),
+        Err(ConnectorError::RecordSchemaMismatch(_, _))
+    ));
+    assert!(matches!(
+        connector.push(insert(1, vec![Field::Int(1), Field::Int(2)])),
+        Err(ConnectorError::RecordSchemaMismatch(_, _))
+    ));
+}
+
+#[test]
+fn test_events_push_seq_no() {
+    let mut connector = get_connector();
+    let (ingestor, iterator) = Ingestor::initialize_channel(IngestionConfig::default());
+    connector.initialize(ingestor, None).unwrap();
+
+    let push_and_get_seq = |connector: &mut EventsConnector| {
+        connector
+            .push(insert(1, vec![Field::Int(1), Field::Null]))
+            .unwrap();
+        iterator.write().next().unwrap().0
+    };
+    assert_eq!(push_and_get_seq(&mut connector), (1, 0));
+    assert_eq!(push_and_get_seq(&mut connector), (2, 0));
+
+    // Restarting from a checkpoint continues after the last committed message
+    connector.start(Some((10, 0))).unwrap();
+    assert_eq!(push_and_get_seq(&mut connector), (11, 0));
+}
+
+#[test]
+fn test_events_capabilities() {
+    let connector = get_connector();
+
+    assert_eq!(
+        connector.capabilities(),
+        ConnectorCapabilities {
+            supports_cdc: true,
+            supports_snapshot: false,
+            supports_schema_inference: false,
+            supports_resume: true,
+        }
+    );
+}
+
+#[test]
+fn test_events_primary_key_columns() {
+    let mut schema = Schema::empty();
+    schema
+        .field(
+            FieldDefinition::new("id".to_string(), FieldType::Int, false),
+            false,
+        )
+        .field(
+            FieldDefinition::new("region".to_string(), FieldType::String, false),
+            false,
+        );
+    let connector = EventsConnector::new(
+        3,
+        "events".to_string(),
+        vec![(
+            "visits".to_string(),
+            schema,
+            ReplicationChangesTrackingType::FullChanges,
+        )],
+    );
+
+    let table = |primary_key_columns: &[&str]| TableInfo {
+        name: "visits".to_string(),
+        id: 0,
+        columns: None,
+        primary_key_columns: Some(primary_key_columns.iter().map(|c| c.to_string()).collect()),
+    };
+
+    // Events without a key can only be appended
+    let schemas = connector.get_schemas(None).unwrap();
+    assert!(matches!(
+        schemas[0].2,
+        ReplicationChangesTrackingType::AppendOnly
+    ));
+
+    let schemas = connector
+        .get_schemas(Some(vec![table(&["region", "id"])]))
+        .unwrap();
+    assert_eq!(schemas[0].1.primary_index, vec![1, 0]);
+    assert!(matches!(
+        schemas[0].2,
+        ReplicationChangesTrackingType::FullChanges
+    ));
+
+    assert!(matches!(
+        connector.get_schemas(Some(vec![table(&["country"])])),
+        Err(ConnectorError::PrimaryKeyColumnNotFound(column, table_name))
+            if column == "country" && table_name == "visits"
+    ));
+}
@@ -0,0 +1,188 @@
+use crate::errors::DebeziumError::{DebeziumConnectionError, InvalidSecurityConfig};
+use crate::errors::{ConnectorError, DebeziumError};
+use dozer_types::ingestion_types::KafkaConfig;
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{BaseConsumer, Consumer};
+
+/// Consumer group of the connector, whose committed offsets it resumes from.
+const GROUP_ID: &str = "dozer";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SecurityProtocol {
+    Plaintext,
+    Ssl,
+    SaslPlaintext,
+    SaslSsl,
+}
+
+impl SecurityProtocol {
+    /// The value of the `security.protocol` property of the client.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SecurityProtocol::Plaintext => "plaintext",
+            SecurityProtocol::Ssl => "ssl",
+            SecurityProtocol::SaslPlaintext => "sasl_plaintext",
+            SecurityProtocol::SaslSsl => "sasl_ssl",
+        }
+    }
+}
+
+/// Returns the security protocol of `config`, checking that the SASL settings are either all set
+/// for a `SASL_*` protocol or not set at all.
+pub fn get_security_protocol(config: &KafkaConfig) -> Result<SecurityProtocol, DebeziumError> {
+    let protocol = match config.security_protocol.as_deref() {
+        None | Some("PLAINTEXT") => SecurityProtocol::Plaintext,
+        Some("SSL") => SecurityProtocol::Ssl,
+        Some("SASL_PLAINTEXT") => SecurityProtocol::SaslPlaintext,
+        Some("SASL_SSL") => SecurityProtocol::SaslSsl,
+        Some(protocol) => {
+            return Err(InvalidSecurityConfig(format!(
+                "unknown security protocol \"{}\"",
+                protocol
+            )))
+        }
+    };
+
+    let sasl_fields = [
+        ("sasl_mechanism", &config.sasl_mechanism),
+        ("sasl_username", &config.sasl_username),
+        ("sasl_password", &config.sasl_password),
+    ];
+    let is_sasl = matches!(
+        protocol,
+        SecurityProtocol::SaslPlaintext | SecurityProtocol::SaslSsl
+    );
+    if is_sasl || sasl_fields.iter().any(|(_, value)| value.is_some()) {
+        let missing: Vec<&str> = sasl_fields
+            .iter()
+            .filter(|(_, value)| value.is_none())
+            .map(|(name, _)| *name)
+            .collect();
+        if !missing.is_empty() {
+            return Err(InvalidSecurityConfig(format!(
+                "{} required for SASL authentication",
+                missing.join(", ")
+            )));
+        }
+        if !is_sasl {
+            return Err(InvalidSecurityConfig(
+                "SASL authentication requires the SASL_PLAINTEXT or SASL_SSL security protocol"
+                    .to_string(),
+            ));
+        }
+    }
+
+    Ok(protocol)
+}
+
+/// Creates a consumer subscribed to `topic`, connected to the broker with the security protocol
+/// and the SASL credentials of `config`.
+pub fn get_consumer(config: &KafkaConfig, topic: &str) -> Result<BaseConsumer, ConnectorError> {
+    let protocol = get_security_protocol(config)?;
+
+    let mut client_config = ClientConfig::new();
+    client_config
+        .set("bootstrap.servers", &config.broker)
+        .set("group.id", GROUP_ID)
+        .set("auto.offset.reset", "earliest")
+        .set("enable.auto.commit", "false")
+        .set("security.protocol", protocol.as_str());
+    if let (Some(mechanism), Some(username), Some(password)) = (
+        &config.sasl_mechanism,
+        &config.sasl_username,
+        &config.sasl_password,
+    ) {
+        client_config
+            .set("sasl.mechanism", mechanism)
+            .set("sasl.username", username)
+            .set("sasl.password", password);
+    }
+
+    let consumer: BaseConsumer = client_config
+        .create()
+        .map_err(|e| ConnectorError::DebeziumError(DebeziumConnectionError(e)))?;
+    consumer
+        .subscribe(&[topic])
+        .map_err(|e| ConnectorError::DebeziumError(DebeziumConnectionError(e)))?;
+    Ok(consumer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_consumer, get_security_protocol, SecurityProtocol};
+    use crate::errors::DebeziumError;
+    use dozer_types::ingestion_types::KafkaConfig;
+
+    fn get_config(
+        security_protocol: Option<&str>,
+        sasl_mechanism: Option<&str>,
+        sasl_username: Option<&str>,
+        sasl_password: Option<&str>,
+    ) -> KafkaConfig {
+        KafkaConfig {
+            broker: "localhost:9092".to_string(),
+            schema_registry_url: None,
+            security_protocol: security_protocol.map(String::from),
+            sasl_mechanism: sasl_mechanism.map(String::from),
+            sasl_username: sasl_username.map(String::from),
+            sasl_password: sasl_password.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_get_security_protocol() {
+        let config = get_config(None, None, None, None);
+        assert_eq!(
+            get_security_protocol(&config).unwrap(),
+            SecurityProtocol::Plaintext
+        );
+
+        let config = get_config(Some("SSL"), None, None, None);
+        assert_eq!(
+            get_security_protocol(&config).unwrap(),
+            SecurityProtocol::Ssl
+        );
+
+        let config = get_config(Some("SASL_SSL"), Some("PLAIN"), Some("user"), Some("pass"));
+        assert_eq!(
+            get_security_protocol(&config).unwrap(),
+            SecurityProtocol::SaslSsl
+        );
+
+        let config = get_config(
+            Some("SASL_PLAINTEXT"),
+            Some("SCRAM-SHA-256"),
+            Some("user"),
+            Some("pass"),
+        );
+        assert_eq!(
+            get_security_protocol(&config).unwrap(),
+            SecurityProtocol::SaslPlaintext
+        );
+    }
+
+    #[test]
+    fn test_get_security_protocol_fails_on_invalid_config() {
+        let invalid_configs = [
+            get_config(Some("TLS"), None, None, None),
+            // Missing credentials
+            get_config(Some("SASL_SSL"), Some("PLAIN"), None, None),
+            get_config(None, Some("PLAIN"), Some("user"), None),
+            // SASL settings without a SASL protocol
+            get_config(Some("SSL"), Some("PLAIN"), Some("user"), Some("pass")),
+        ];
+        for config in invalid_configs {
+            assert!(matches!(
+                get_security_protocol(&config),
+                Err(DebeziumError::InvalidSecurityConfig(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_get_consumer_with_sasl() {
+        // The client only connects when polled, creating it checks the configuration
+        let config = get_config(Some("SASL_SSL"), Some("PLAIN"), Some("user"), Some("pass"));
+        assert!(get_consumer(&config, "products").is_ok());
+    }
+}
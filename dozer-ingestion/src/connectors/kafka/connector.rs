@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
-use crate::connectors::{Connector, ValidationResults};
+use crate::connectors::{
+    apply_primary_key_columns, Connector, ConnectorCapabilities, ValidationResults,
+};
 use crate::ingestion::Ingestor;
 use crate::{connectors::TableInfo, errors::ConnectorError};
 use dozer_types::ingestion_types::KafkaConfig;
@@ -11,13 +13,13 @@ use tokio::runtime::Runtime;
 
 use dozer_types::models::source::Source;
 use dozer_types::types::ReplicationChangesTrackingType;
-use kafka::consumer::{Consumer, FetchOffset, GroupOffsetStorage};
 
+use crate::connectors::kafka::client::{get_consumer, get_security_protocol};
 use crate::connectors::kafka::debezium::no_schema_registry::NoSchemaRegistry;
 use crate::connectors::kafka::debezium::schema_registry::SchemaRegistry;
 use crate::connectors::kafka::debezium::stream_consumer::DebeziumStreamConsumer;
 use crate::connectors::kafka::stream_consumer::StreamConsumer;
-use crate::errors::DebeziumError::{DebeziumConnectionError, TopicNotDefined};
+use crate::errors::DebeziumError::TopicNotDefined;
 
 pub struct KafkaConnector {
     pub id: u64,
@@ -38,6 +40,18 @@ impl KafkaConnector {
 }
 
 impl Connector for KafkaConnector {
+    /// Debezium topics are consumed from the consumer group's offset, which isn't tied to the
+    /// pipeline's checkpoint, and the snapshot is left to Debezium. Topics aren't listed by
+    /// `get_tables`, so they have to be named in the sources.
+    fn capabilities(&self) -> ConnectorCapabilities {
+        ConnectorCapabilities {
+            supports_cdc: true,
+            supports_snapshot: false,
+            supports_schema_inference: false,
+            supports_resume: false,
+        }
+    }
+
     fn get_schemas(
         &self,
         table_names: Option<Vec<TableInfo>>,
@@ -49,10 +63,14 @@ impl Connector for KafkaConnector {
         )>,
         ConnectorError,
     > {
-        self.config.schema_registry_url.clone().map_or(
+        let mut schemas = self.config.schema_registry_url.clone().map_or(
             NoSchemaRegistry::get_schema(table_names.clone(), self.config.clone()),
-            |_| SchemaRegistry::get_schema(table_names, self.config.clone()),
-        )
+            |_| SchemaRegistry::get_schema(table_names.clone(), self.config.clone()),
+        )?;
+        if let Some(tables) = &table_names {
+            apply_primary_key_columns(&mut schemas, tables)?;
+        }
+        Ok(schemas)
     }
 
     fn get_tables(&self) -> Result<Vec<TableInfo>, ConnectorError> {
@@ -79,7 +97,6 @@ impl Connector for KafkaConnector {
             .get(0)
             .map_or(Err(TopicNotDefined), |table| Ok(&table.name))?;
 
-        let broker = self.config.broker.to_owned();
         let ingestor = self
             .ingestor
             .as_ref()
@@ -87,7 +104,7 @@ impl Connector for KafkaConnector {
             .clone();
         Runtime::new()
             .unwrap()
-            .block_on(async { run(broker, topic, ingestor).await })
+            .block_on(async { run(&self.config, topic, ingestor).await })
     }
 
     fn stop(&self) {}
@@ -97,29 +114,30 @@ impl Connector for KafkaConnector {
     }
 
     fn validate(&self, _tables: Option<Vec<TableInfo>>) -> Result<(), ConnectorError> {
+        get_security_protocol(&self.config)?;
         Ok(())
     }
 
+    /// Every topic is consumed separately.
     fn get_connection_groups(sources: Vec<Source>) -> Vec<Vec<Source>> {
         sources.iter().map(|s| vec![s.clone()]).collect()
     }
 
+    fn ingests_sources_separately() -> bool {
+        true
+    }
+
     fn validate_schemas(&self, _tables: &[TableInfo]) -> Result<ValidationResults, ConnectorError> {
         todo!()
     }
 }
 
 async fn run(
-    broker: String,
+    config: &KafkaConfig,
     topic: &str,
     ingestor: Arc<RwLock<Ingestor>>,
 ) -> Result<(), ConnectorError> {
-    let con = Consumer::from_hosts(vec![broker])
-        .with_topic(topic.to_string())
-        .with_fallback_offset(FetchOffset::Earliest)
-        .with_offset_storage(GroupOffsetStorage::Kafka)
-        .create()
-        .map_err(DebeziumConnectionError)?;
+    let con = get_consumer(config, topic)?;
 
     let consumer = DebeziumStreamConsumer::default();
     consumer.run(con, ingestor)
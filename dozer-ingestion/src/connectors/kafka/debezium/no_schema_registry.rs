@@ -1,14 +1,18 @@
+use crate::connectors::kafka::client::get_consumer;
 use crate::connectors::kafka::debezium::schema::map_schema;
 use crate::connectors::kafka::debezium::stream_consumer::DebeziumMessage;
 use crate::connectors::TableInfo;
-use crate::errors::DebeziumError::{BytesConvertError, DebeziumConnectionError, JsonDecodeError};
+use crate::errors::DebeziumError::{BytesConvertError, JsonDecodeError};
 use crate::errors::{ConnectorError, DebeziumError, DebeziumStreamError};
 use dozer_types::ingestion_types::KafkaConfig;
 use dozer_types::serde_json;
+use rdkafka::Message;
+use std::time::Duration;
 
 use dozer_types::types::ReplicationChangesTrackingType;
-use kafka::client::{FetchOffset, GroupOffsetStorage};
-use kafka::consumer::Consumer;
+
+/// How long to wait for a message to infer the schema from.
+const POLL_TIMEOUT: Duration = Duration::from_secs(10);
 
 pub struct NoSchemaRegistry {}
 
@@ -26,45 +30,34 @@ impl NoSchemaRegistry {
     > {
         table_names.map_or(Ok(vec![]), |tables| {
             tables.get(0).map_or(Ok(vec![]), |table| {
-                let mut con = Consumer::from_hosts(vec![config.broker.clone()])
-                    .with_topic(table.name.clone())
-                    .with_fallback_offset(FetchOffset::Earliest)
-                    .with_offset_storage(GroupOffsetStorage::Kafka)
-                    .create()
-                    .map_err(DebeziumConnectionError)?;
+                let con = get_consumer(&config, &table.name)?;
 
                 let mut schemas = vec![];
-                let mss = con.poll().map_err(|e| {
-                    DebeziumError::DebeziumStreamError(DebeziumStreamError::PollingError(e))
-                })?;
-
-                if !mss.is_empty() {
-                    for ms in mss.iter() {
-                        for m in ms.messages() {
-                            let value_struct: DebeziumMessage = serde_json::from_str(
-                                std::str::from_utf8(m.value).map_err(BytesConvertError)?,
-                            )
-                            .map_err(JsonDecodeError)?;
-                            let key_struct: DebeziumMessage = serde_json::from_str(
-                                std::str::from_utf8(m.key).map_err(BytesConvertError)?,
-                            )
-                            .map_err(JsonDecodeError)?;
+                if let Some(m) = con.poll(POLL_TIMEOUT) {
+                    let m = m.map_err(|e| {
+                        DebeziumError::DebeziumStreamError(DebeziumStreamError::PollingError(e))
+                    })?;
+                    let value_struct: DebeziumMessage = serde_json::from_str(
+                        std::str::from_utf8(m.payload().unwrap_or_default())
+                            .map_err(BytesConvertError)?,
+                    )
+                    .map_err(JsonDecodeError)?;
+                    let key_struct: DebeziumMessage = serde_json::from_str(
+                        std::str::from_utf8(m.key().unwrap_or_default())
+                            .map_err(BytesConvertError)?,
+                    )
+                    .map_err(JsonDecodeError)?;
 
-                            let (mapped_schema, _fields_map) = map_schema(
-                                &value_struct.schema,
-                                &key_struct.schema,
-                            )
-                            .map_err(|e| {
-                                ConnectorError::DebeziumError(DebeziumError::DebeziumSchemaError(e))
-                            })?;
+                    let (mapped_schema, _fields_map) =
+                        map_schema(&value_struct.schema, &key_struct.schema).map_err(|e| {
+                            ConnectorError::DebeziumError(DebeziumError::DebeziumSchemaError(e))
+                        })?;
 
-                            schemas.push((
-                                table.name.clone(),
-                                mapped_schema,
-                                ReplicationChangesTrackingType::FullChanges,
-                            ));
-                        }
-                    }
+                    schemas.push((
+                        table.name.clone(),
+                        mapped_schema,
+                        ReplicationChangesTrackingType::FullChanges,
+                    ));
                 }
 
                 Ok(schemas)
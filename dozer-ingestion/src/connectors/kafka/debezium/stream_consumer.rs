@@ -10,9 +10,15 @@ use dozer_types::parking_lot::RwLock;
 use dozer_types::serde::{Deserialize, Serialize};
 use dozer_types::serde_json;
 use dozer_types::serde_json::Value;
-use dozer_types::types::{Operation, OperationEvent, Record, SchemaIdentifier};
-use kafka::consumer::Consumer;
+use dozer_types::types::{Operation, OperationEvent, Record, Schema, SchemaIdentifier};
+use rdkafka::consumer::{BaseConsumer, CommitMode, Consumer};
+use rdkafka::Message;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
+
+/// How long a poll waits for a message before polling again.
+const POLL_TIMEOUT: Duration = Duration::from_secs(1);
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(crate = "dozer_types::serde")]
@@ -79,170 +85,251 @@ pub struct DebeziumStreamConsumer {}
 
 impl DebeziumStreamConsumer {}
 
+/// Keeps the last schema seen for each table. Debezium embeds the schema in every message, so a
+/// schema that differs from the previous one means that the table was altered upstream.
+///
+/// The first schema of a table is reported too: the table may have been altered while the
+/// pipeline was stopped, so it has to be checked against the schema the DAG was built with.
+#[derive(Default)]
+struct SchemaTracker {
+    schemas: HashMap<String, Schema>,
+}
+
+impl SchemaTracker {
+    /// Returns the schema to decode the current message of `table` with, and whether it has to be
+    /// reported, as it's the first one of the table or it changed since the previous message. The
+    /// version of the identifier is bumped on every change.
+    fn track(&mut self, table: &str, mut schema: Schema) -> (Schema, bool) {
+        match self.schemas.get(table) {
+            Some(current)
+                if current.fields == schema.fields
+                    && current.primary_index == schema.primary_index =>
+            {
+                (current.clone(), false)
+            }
+            current => {
+                let version = current
+                    .and_then(|current| current.identifier)
+                    .map_or(1, |identifier| identifier.version + 1);
+                schema.identifier = Some(SchemaIdentifier {
+                    id: schema.identifier.map_or(1, |identifier| identifier.id),
+                    version,
+                });
+                self.schemas.insert(table.to_string(), schema.clone());
+                (schema, true)
+            }
+        }
+    }
+}
+
 impl StreamConsumer for DebeziumStreamConsumer {
     fn run(
         &self,
-        mut con: Consumer,
+        con: BaseConsumer,
         ingestor: Arc<RwLock<Ingestor>>,
     ) -> Result<(), ConnectorError> {
+        let mut schema_tracker = SchemaTracker::default();
         loop {
-            let mss = con.poll().map_err(|e| {
-                DebeziumError::DebeziumStreamError(DebeziumStreamError::PollingError(e))
-            })?;
-            if !mss.is_empty() {
-                for ms in mss.iter() {
-                    for m in ms.messages() {
-                        if m.value.is_empty() {
-                            continue;
-                        }
-
-                        let mut value_struct: DebeziumMessage = serde_json::from_str(
-                            std::str::from_utf8(m.value).map_err(BytesConvertError)?,
-                        )
-                        .map_err(JsonDecodeError)?;
-                        let key_struct: DebeziumMessage = serde_json::from_str(
-                            std::str::from_utf8(m.key).map_err(BytesConvertError)?,
-                        )
+            let m = match con.poll(POLL_TIMEOUT) {
+                Some(m) => m.map_err(|e| {
+                    DebeziumError::DebeziumStreamError(DebeziumStreamError::PollingError(e))
+                })?,
+                None => continue,
+            };
+            let table = m.topic();
+            // Tombstones following deletes have no value
+            if let Some(value) = m.payload() {
+                let mut value_struct: DebeziumMessage =
+                    serde_json::from_str(std::str::from_utf8(value).map_err(BytesConvertError)?)
                         .map_err(JsonDecodeError)?;
+                let key_struct: DebeziumMessage = serde_json::from_str(
+                    std::str::from_utf8(m.key().unwrap_or_default()).map_err(BytesConvertError)?,
+                )
+                .map_err(JsonDecodeError)?;
+
+                let (schema, fields_map) = map_schema(&value_struct.schema, &key_struct.schema)
+                    .map_err(|e| {
+                        ConnectorError::DebeziumError(DebeziumError::DebeziumSchemaError(e))
+                    })?;
 
-                        let (schema, fields_map) =
-                            map_schema(&value_struct.schema, &key_struct.schema).map_err(|e| {
+                // Downstream nodes are told about the schema of a table before its first
+                // operation, and about an altered table before its first operation with the new
+                // schema
+                let (schema, report) = schema_tracker.track(table, schema);
+                if report {
+                    ingestor
+                        .write()
+                        .handle_message((
+                            (0, 0),
+                            IngestionMessage::SchemaChange {
+                                table: table.to_string(),
+                                schema: schema.clone(),
+                            },
+                        ))
+                        .map_err(ConnectorError::IngestorError)?;
+                }
+                let schema_id = schema.identifier;
+
+                // When update happens before is null.
+                // If PK value changes, then debezium creates two events - delete and insert
+                if value_struct.payload.before.is_none()
+                    && value_struct.payload.op == Some("u".to_string())
+                {
+                    value_struct.payload.before = value_struct.payload.after.clone();
+                }
+
+                match (value_struct.payload.after, value_struct.payload.before) {
+                    (Some(new_payload), Some(old_payload)) => {
+                        let new = convert_value_to_schema(
+                            new_payload,
+                            schema.clone(),
+                            fields_map.clone(),
+                        )
+                        .map_err(|e| {
+                            ConnectorError::DebeziumError(DebeziumError::DebeziumSchemaError(e))
+                        })?;
+                        let old = convert_value_to_schema(old_payload, schema.clone(), fields_map)
+                            .map_err(|e| {
                                 ConnectorError::DebeziumError(DebeziumError::DebeziumSchemaError(e))
                             })?;
 
-                        // When update happens before is null.
-                        // If PK value changes, then debezium creates two events - delete and insert
-                        if value_struct.payload.before.is_none()
-                            && value_struct.payload.op == Some("u".to_string())
-                        {
-                            value_struct.payload.before = value_struct.payload.after.clone();
-                        }
-
-                        match (value_struct.payload.after, value_struct.payload.before) {
-                            (Some(new_payload), Some(old_payload)) => {
-                                let new = convert_value_to_schema(
-                                    new_payload,
-                                    schema.clone(),
-                                    fields_map.clone(),
-                                )
-                                .map_err(|e| {
-                                    ConnectorError::DebeziumError(
-                                        DebeziumError::DebeziumSchemaError(e),
-                                    )
-                                })?;
-                                let old = convert_value_to_schema(
-                                    old_payload,
-                                    schema.clone(),
-                                    fields_map,
-                                )
-                                .map_err(|e| {
-                                    ConnectorError::DebeziumError(
-                                        DebeziumError::DebeziumSchemaError(e),
-                                    )
-                                })?;
-
-                                ingestor
-                                    .write()
-                                    .handle_message((
-                                        (0, 0),
-                                        IngestionMessage::OperationEvent(OperationEvent {
-                                            seq_no: 0,
-                                            operation: Operation::Update {
-                                                old: Record {
-                                                    schema_id: Some(SchemaIdentifier {
-                                                        id: 1,
-                                                        version: 1,
-                                                    }),
-                                                    values: old,
-                                                    version: None,
-                                                },
-                                                new: Record {
-                                                    schema_id: Some(SchemaIdentifier {
-                                                        id: 1,
-                                                        version: 1,
-                                                    }),
-                                                    values: new,
-                                                    version: None,
-                                                },
-                                            },
-                                        }),
-                                    ))
-                                    .map_err(ConnectorError::IngestorError)?;
-                            }
-                            (None, Some(old_payload)) => {
-                                let old = convert_value_to_schema(old_payload, schema, fields_map)
-                                    .map_err(|e| {
-                                        ConnectorError::DebeziumError(
-                                            DebeziumError::DebeziumSchemaError(e),
-                                        )
-                                    })?;
-
-                                ingestor
-                                    .write()
-                                    .handle_message((
-                                        (0, 0),
-                                        IngestionMessage::OperationEvent(OperationEvent {
-                                            seq_no: 0,
-                                            operation: Operation::Delete {
-                                                old: Record {
-                                                    schema_id: Some(SchemaIdentifier {
-                                                        id: 1,
-                                                        version: 1,
-                                                    }),
-                                                    values: old,
-                                                    version: None,
-                                                },
-                                            },
-                                        }),
-                                    ))
-                                    .map_err(ConnectorError::IngestorError)?;
-                            }
-                            (Some(new_payload), None) => {
-                                let new = convert_value_to_schema(
-                                    new_payload,
-                                    schema.clone(),
-                                    fields_map.clone(),
-                                )
-                                .map_err(|e| {
-                                    ConnectorError::DebeziumError(
-                                        DebeziumError::DebeziumSchemaError(e),
-                                    )
-                                })?;
-
-                                ingestor
-                                    .write()
-                                    .handle_message((
-                                        (0, 0),
-                                        IngestionMessage::OperationEvent(OperationEvent {
-                                            seq_no: 0,
-                                            operation: Operation::Insert {
-                                                new: Record {
-                                                    schema_id: Some(SchemaIdentifier {
-                                                        id: 1,
-                                                        version: 1,
-                                                    }),
-                                                    values: new,
-                                                    version: None,
-                                                },
-                                            },
-                                        }),
-                                    ))
-                                    .map_err(ConnectorError::IngestorError)?;
-                            }
-                            (None, None) => {}
-                        }
+                        ingestor
+                            .write()
+                            .handle_message((
+                                (0, 0),
+                                IngestionMessage::OperationEvent(OperationEvent {
+                                    seq_no: 0,
+                                    operation: Operation::Update {
+                                        old: Record {
+                                            schema_id,
+                                            values: old,
+                                            version: None,
+                                        },
+                                        new: Record {
+                                            schema_id,
+                                            values: new,
+                                            version: None,
+                                        },
+                                    },
+                                }),
+                            ))
+                            .map_err(ConnectorError::IngestorError)?;
                     }
+                    (None, Some(old_payload)) => {
+                        let old = convert_value_to_schema(old_payload, schema, fields_map)
+                            .map_err(|e| {
+                                ConnectorError::DebeziumError(DebeziumError::DebeziumSchemaError(e))
+                            })?;
 
-                    con.consume_messageset(ms).map_err(|e| {
-                        DebeziumError::DebeziumStreamError(
-                            DebeziumStreamError::MessageConsumeError(e),
+                        ingestor
+                            .write()
+                            .handle_message((
+                                (0, 0),
+                                IngestionMessage::OperationEvent(OperationEvent {
+                                    seq_no: 0,
+                                    operation: Operation::Delete {
+                                        old: Record {
+                                            schema_id,
+                                            values: old,
+                                            version: None,
+                                        },
+                                    },
+                                }),
+                            ))
+                            .map_err(ConnectorError::IngestorError)?;
+                    }
+                    (Some(new_payload), None) => {
+                        let new = convert_value_to_schema(
+                            new_payload,
+                            schema.clone(),
+                            fields_map.clone(),
                         )
-                    })?;
+                        .map_err(|e| {
+                            ConnectorError::DebeziumError(DebeziumError::DebeziumSchemaError(e))
+                        })?;
+
+                        ingestor
+                            .write()
+                            .handle_message((
+                                (0, 0),
+                                IngestionMessage::OperationEvent(OperationEvent {
+                                    seq_no: 0,
+                                    operation: Operation::Insert {
+                                        new: Record {
+                                            schema_id,
+                                            values: new,
+                                            version: None,
+                                        },
+                                    },
+                                }),
+                            ))
+                            .map_err(ConnectorError::IngestorError)?;
+                    }
+                    (None, None) => {}
                 }
-                con.commit_consumed().map_err(|e| {
-                    DebeziumError::DebeziumStreamError(DebeziumStreamError::ConsumeCommitError(e))
-                })?;
             }
+
+            con.commit_message(&m, CommitMode::Async).map_err(|e| {
+                DebeziumError::DebeziumStreamError(DebeziumStreamError::ConsumeCommitError(e))
+            })?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SchemaTracker;
+    use dozer_types::types::{FieldDefinition, FieldType, Schema, SchemaIdentifier};
+
+    fn get_schema(field_names: &[&str]) -> Schema {
+        let mut schema = Schema::empty();
+        for (idx, name) in field_names.iter().enumerate() {
+            schema.field(
+                FieldDefinition::new(name.to_string(), FieldType::Int, false),
+                idx == 0,
+            );
         }
+        schema.identifier = Some(SchemaIdentifier { id: 1, version: 1 });
+        schema
+    }
+
+    #[test]
+    fn test_schema_tracker_detects_changes() {
+        let mut tracker = SchemaTracker::default();
+
+        // The first schema is reported, to be checked against the DAG's
+        let (schema, changed) = tracker.track("products", get_schema(&["id", "name"]));
+        assert!(changed);
+        assert_eq!(
+            schema.identifier,
+            Some(SchemaIdentifier { id: 1, version: 1 })
+        );
+
+        let (_, changed) = tracker.track("products", get_schema(&["id", "name"]));
+        assert!(!changed);
+
+        // A column was added to the table
+        let (schema, changed) = tracker.track("products", get_schema(&["id", "name", "price"]));
+        assert!(changed);
+        assert_eq!(
+            schema.identifier,
+            Some(SchemaIdentifier { id: 1, version: 2 })
+        );
+        assert_eq!(schema.fields.len(), 3);
+
+        let (schema, changed) = tracker.track("products", get_schema(&["id", "name", "price"]));
+        assert!(!changed);
+        assert_eq!(
+            schema.identifier,
+            Some(SchemaIdentifier { id: 1, version: 2 })
+        );
+
+        // Tables are tracked independently
+        let (schema, changed) = tracker.track("customers", get_schema(&["id"]));
+        assert!(changed);
+        assert_eq!(
+            schema.identifier,
+            Some(SchemaIdentifier { id: 1, version: 1 })
+        );
     }
 }
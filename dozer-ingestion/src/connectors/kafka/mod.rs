@@ -1,3 +1,4 @@
+pub mod client;
 pub mod connector;
 pub mod debezium;
 pub mod stream_consumer;
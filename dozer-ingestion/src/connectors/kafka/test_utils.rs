@@ -93,17 +93,26 @@ pub fn get_iterator_and_client(table_name: String) -> (Arc<RwLock<IngestionItera
             name: format!("dbserver1.public.{}", table_name),
             id: 0,
             columns: None,
+            primary_key_columns: None,
         }];
 
         let mut connection = config.config.connections.get(0).unwrap().clone();
         if let Some(Authentication::Kafka(KafkaConfig {
             broker,
             schema_registry_url,
+            security_protocol,
+            sasl_mechanism,
+            sasl_username,
+            sasl_password,
         })) = connection.authentication
         {
             connection.authentication = Some(Authentication::Kafka(KafkaConfig {
                 broker,
                 schema_registry_url,
+                security_protocol,
+                sasl_mechanism,
+                sasl_username,
+                sasl_password,
             }));
         };
 
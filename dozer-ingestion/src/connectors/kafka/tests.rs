@@ -2,7 +2,7 @@ use crate::connectors::kafka::connector::KafkaConnector;
 use crate::connectors::kafka::test_utils::{
     get_client_and_create_table, get_debezium_config, get_iterator_and_client,
 };
-use crate::connectors::{Connector, TableInfo};
+use crate::connectors::{Connector, ConnectorCapabilities, TableInfo};
 use dozer_types::models::connection::Authentication;
 use dozer_types::{
     ingestion_types::{IngestionOperation, KafkaConfig},
@@ -145,6 +145,10 @@ fn connector_disabled_test_e2e_connect_debezium_json_and_get_schema() {
         KafkaConfig {
             broker,
             schema_registry_url: None,
+            security_protocol: None,
+            sasl_mechanism: None,
+            sasl_username: None,
+            sasl_password: None,
         },
     );
 
@@ -154,6 +158,7 @@ fn connector_disabled_test_e2e_connect_debezium_json_and_get_schema() {
             name: topic.clone(),
             id: 0,
             columns: None,
+            primary_key_columns: None,
         }]))
         .unwrap();
 
@@ -201,6 +206,10 @@ fn connector_disabled_test_e2e_connect_debezium_avro_and_get_schema() {
         KafkaConfig {
             broker,
             schema_registry_url,
+            security_protocol: None,
+            sasl_mechanism: None,
+            sasl_username: None,
+            sasl_password: None,
         },
     );
 
@@ -210,6 +219,7 @@ fn connector_disabled_test_e2e_connect_debezium_avro_and_get_schema() {
             name: topic.clone(),
             id: 0,
             columns: None,
+            primary_key_columns: None,
         }]))
         .unwrap();
 
@@ -218,3 +228,18 @@ fn connector_disabled_test_e2e_connect_debezium_avro_and_get_schema() {
     assert_eq!(topic, schemas.get(0).unwrap().0);
     assert_eq!(4, schemas.get(0).unwrap().1.fields.len());
 }
+
+#[test]
+fn test_kafka_capabilities() {
+    let connector = KafkaConnector::new(1, KafkaConfig::default());
+
+    assert_eq!(
+        connector.capabilities(),
+        ConnectorCapabilities {
+            supports_cdc: true,
+            supports_snapshot: false,
+            supports_schema_inference: false,
+            supports_resume: false,
+        }
+    );
+}
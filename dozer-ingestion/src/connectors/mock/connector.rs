@@ -0,0 +1,185 @@
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use dozer_types::ingestion_types::IngestionMessage;
+use dozer_types::models::source::Source;
+use dozer_types::parking_lot::{Mutex, RwLock};
+use dozer_types::types::SchemaWithChangesType;
+
+use crate::connectors::{
+    apply_primary_key_columns, Connector, ConnectorCapabilities, TableInfo, ValidationResults,
+};
+use crate::errors::{ConnectorError, MockError};
+use crate::ingestion::Ingestor;
+
+/// A connector replaying a scripted list of messages, to test the ingestion pipeline without a
+/// real source.
+///
+/// The message at index `i` is ingested at position `(i + 1, 0)`, so `start` skips the messages
+/// up to and including its `from_seq`, like a real connector resuming after its last commit.
+pub struct MockConnector {
+    pub id: u64,
+    schemas: Vec<SchemaWithChangesType>,
+    messages: Vec<IngestionMessage>,
+    interval: Option<Duration>,
+    /// Indexes of the messages which make `start` fail once, before they are ingested
+    failures: Mutex<BTreeSet<usize>>,
+    ingestor: Option<Arc<RwLock<Ingestor>>>,
+    running: AtomicBool,
+}
+
+impl MockConnector {
+    pub fn new(
+        id: u64,
+        schemas: Vec<SchemaWithChangesType>,
+        messages: Vec<IngestionMessage>,
+    ) -> Self {
+        Self {
+            id,
+            schemas,
+            messages,
+            interval: None,
+            failures: Mutex::new(BTreeSet::new()),
+            ingestor: None,
+            running: AtomicBool::new(false),
+        }
+    }
+
+    /// Waits `interval` before ingesting each message.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = Some(interval);
+        self
+    }
+
+    /// Makes `start` return [`MockError::FailureInjected`] when it reaches the message at
+    /// `index`. The failure happens once: starting again from the last committed position ingests
+    /// the message.
+    pub fn with_failure_at(self, index: usize) -> Self {
+        self.failures.lock().insert(index);
+        self
+    }
+
+    /// Position at which the message at `index` is ingested.
+    pub fn position(index: usize) -> (u64, u64) {
+        (index as u64 + 1, 0)
+    }
+}
+
+impl Connector for MockConnector {
+    /// Scripted messages are replayed from the last committed position, with the schemas they
+    /// were given.
+    fn capabilities(&self) -> ConnectorCapabilities {
+        ConnectorCapabilities {
+            supports_cdc: true,
+            supports_snapshot: false,
+            supports_schema_inference: false,
+            supports_resume: true,
+        }
+    }
+
+    fn get_schemas(
+        &self,
+        table_names: Option<Vec<TableInfo>>,
+    ) -> Result<Vec<SchemaWithChangesType>, ConnectorError> {
+        let mut schemas: Vec<SchemaWithChangesType> = self
+            .schemas
+            .iter()
+            .filter(|(name, _, _)| {
+                table_names.as_ref().map_or(true, |tables| {
+                    tables.iter().any(|table| &table.name == name)
+                })
+            })
+            .cloned()
+            .collect();
+        if let Some(tables) = &table_names {
+            apply_primary_key_columns(&mut schemas, tables)?;
+        }
+        Ok(schemas)
+    }
+
+    fn get_tables(&self) -> Result<Vec<TableInfo>, ConnectorError> {
+        Ok(self
+            .schemas
+            .iter()
+            .map(|(name, schema, _)| TableInfo {
+                name: name.clone(),
+                id: schema.identifier.map_or(0, |identifier| identifier.id),
+                columns: Some(schema.fields.iter().map(|f| f.name.clone()).collect()),
+                primary_key_columns: None,
+            })
+            .collect())
+    }
+
+    fn stop(&self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+
+    fn test_connection(&self) -> Result<(), ConnectorError> {
+        Ok(())
+    }
+
+    fn initialize(
+        &mut self,
+        ingestor: Arc<RwLock<Ingestor>>,
+        _: Option<Vec<TableInfo>>,
+    ) -> Result<(), ConnectorError> {
+        self.ingestor = Some(ingestor);
+        Ok(())
+    }
+
+    fn start(&self, from_seq: Option<(u64, u64)>) -> Result<(), ConnectorError> {
+        let ingestor = self
+            .ingestor
+            .as_ref()
+            .map_or(Err(ConnectorError::InitializationError), Ok)?;
+
+        self.running.store(true, Ordering::SeqCst);
+        let skip = from_seq.map_or(0, |(lsn, _)| lsn as usize);
+        for (index, message) in self.messages.iter().enumerate().skip(skip) {
+            if !self.running.load(Ordering::SeqCst) {
+                break;
+            }
+            if self.failures.lock().remove(&index) {
+                self.running.store(false, Ordering::SeqCst);
+                return Err(MockError::FailureInjected(index).into());
+            }
+            if let Some(interval) = self.interval {
+                thread::sleep(interval);
+            }
+            ingestor
+                .write()
+                .handle_message((Self::position(index), message.clone()))
+                .map_err(ConnectorError::IngestorError)?;
+        }
+        Ok(())
+    }
+
+    fn validate(&self, _tables: Option<Vec<TableInfo>>) -> Result<(), ConnectorError> {
+        Ok(())
+    }
+
+    fn get_connection_groups(sources: Vec<Source>) -> Vec<Vec<Source>> {
+        vec![sources]
+    }
+
+    fn validate_schemas(&self, tables: &[TableInfo]) -> Result<ValidationResults, ConnectorError> {
+        Ok(tables
+            .iter()
+            .map(|table| {
+                let result = if self.schemas.iter().any(|(name, _, _)| name == &table.name) {
+                    Ok(())
+                } else {
+                    Err(ConnectorError::TableNotFound(table.name.clone()))
+                };
+                (table.name.clone(), vec![(None, result)])
+            })
+            .collect())
+    }
+
+    fn get_current_position(&self) -> Result<Option<(u64, u64)>, ConnectorError> {
+        Ok(self.messages.len().checked_sub(1).map(Self::position))
+    }
+}
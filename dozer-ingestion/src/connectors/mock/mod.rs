@@ -0,0 +1,4 @@
+pub mod connector;
+
+#[cfg(test)]
+mod tests;
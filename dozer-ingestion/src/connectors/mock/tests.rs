@@ -0,0 +1,143 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::connectors::mock::connector::MockConnector;
+use crate::connectors::{Connector, TableInfo};
+use crate::errors::{ConnectorError, MockError};
+use crate::ingestion::{IngestionConfig, IngestionIterator, Ingestor};
+use dozer_types::ingestion_types::{IngestionMessage, IngestionOperation};
+use dozer_types::parking_lot::RwLock;
+use dozer_types::types::{
+    Field, FieldDefinition, FieldType, Operation, OperationEvent, Record,
+    ReplicationChangesTrackingType, Schema, SchemaIdentifier,
+};
+
+fn get_schema() -> Schema {
+    let mut schema = Schema::empty();
+    schema.field(
+        FieldDefinition::new("id".to_string(), FieldType::Int, false),
+        true,
+    );
+    schema.identifier = Some(SchemaIdentifier { id: 1, version: 1 });
+    schema
+}
+
+fn insert(id: i64) -> IngestionMessage {
+    IngestionMessage::OperationEvent(OperationEvent::new(
+        0,
+        Operation::Insert {
+            new: Record::new(get_schema().identifier, vec![Field::Int(id)], None),
+        },
+    ))
+}
+
+fn get_connector(count: i64) -> MockConnector {
+    MockConnector::new(
+        6,
+        vec![(
+            "users".to_string(),
+            get_schema(),
+            ReplicationChangesTrackingType::FullChanges,
+        )],
+        (0..count).map(insert).collect(),
+    )
+}
+
+fn initialize(connector: &mut MockConnector) -> Arc<RwLock<IngestionIterator>> {
+    let (ingestor, iterator) = Ingestor::initialize_channel(IngestionConfig::default());
+    connector.initialize(ingestor, None).unwrap();
+    iterator
+}
+
+/// Returns the positions and ids of the `count` next ingested inserts.
+fn next_inserts(iterator: &Arc<RwLock<IngestionIterator>>, count: usize) -> Vec<((u64, u64), i64)> {
+    (0..count)
+        .map(|_| match iterator.write().next().unwrap() {
+            (
+                position,
+                IngestionOperation::OperationEvent(OperationEvent {
+                    operation: Operation::Insert { new },
+                    ..
+                }),
+            ) => (position, new.values[0].as_int().unwrap()),
+            other => panic!("Unexpected operation {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn test_mock_replays_messages() {
+    let mut connector = get_connector(3);
+    let iterator = initialize(&mut connector);
+
+    connector.start(None).unwrap();
+    assert_eq!(
+        next_inserts(&iterator, 3),
+        vec![((1, 0), 0), ((2, 0), 1), ((3, 0), 2)]
+    );
+    assert!(iterator.write().rx.is_empty());
+    assert_eq!(connector.get_current_position().unwrap(), Some((3, 0)));
+}
+
+#[test]
+fn test_mock_resumes_after_from_seq() {
+    let mut connector = get_connector(4);
+    let iterator = initialize(&mut connector);
+
+    connector.start(Some((2, 0))).unwrap();
+    assert_eq!(next_inserts(&iterator, 2), vec![((3, 0), 2), ((4, 0), 3)]);
+    assert!(iterator.write().rx.is_empty());
+}
+
+#[test]
+fn test_mock_recovers_from_injected_failure() {
+    let mut connector = get_connector(4).with_failure_at(2);
+    let iterator = initialize(&mut connector);
+
+    assert!(matches!(
+        connector.start(None),
+        Err(ConnectorError::MockError(MockError::FailureInjected(2)))
+    ));
+    assert_eq!(next_inserts(&iterator, 2), vec![((1, 0), 0), ((2, 0), 1)]);
+    assert!(iterator.write().rx.is_empty());
+
+    // Restarting from the last ingested position continues with the failed message
+    connector.start(Some((2, 0))).unwrap();
+    assert_eq!(next_inserts(&iterator, 2), vec![((3, 0), 2), ((4, 0), 3)]);
+}
+
+#[test]
+fn test_mock_paces_messages() {
+    let mut connector = get_connector(3).with_interval(Duration::from_millis(20));
+    let _iterator = initialize(&mut connector);
+
+    let start = Instant::now();
+    connector.start(None).unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(60));
+}
+
+#[test]
+fn test_mock_tables() {
+    let connector = get_connector(0);
+
+    let tables = connector.get_tables().unwrap();
+    assert_eq!(tables.len(), 1);
+    assert_eq!(tables[0].name, "users");
+    assert_eq!(tables[0].columns, Some(vec!["id".to_string()]));
+
+    let missing = TableInfo {
+        name: "orders".to_string(),
+        id: 0,
+        columns: None,
+        primary_key_columns: None,
+    };
+    let results = connector
+        .validate_schemas(&[tables[0].clone(), missing])
+        .unwrap();
+    assert!(results["users"][0].1.is_ok());
+    assert!(matches!(
+        results["orders"][0].1,
+        Err(ConnectorError::TableNotFound(_))
+    ));
+    assert_eq!(connector.get_current_position().unwrap(), None);
+}
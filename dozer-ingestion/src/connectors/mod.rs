@@ -1,6 +1,8 @@
 pub mod ethereum;
 pub mod events;
 pub mod kafka;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod mock;
 pub mod postgres;
 
 use crate::connectors::postgres::connection::helper::map_connection_config;
@@ -9,6 +11,7 @@ use std::collections::HashMap;
 use crate::connectors::kafka::connector::KafkaConnector;
 use crate::connectors::postgres::connector::{PostgresConfig, PostgresConnector};
 use crate::errors::ConnectorError;
+use crate::errors::PostgresConnectorError::LsnParseError;
 use crate::ingestion::Ingestor;
 use dozer_types::log::debug;
 use dozer_types::models::connection::Authentication;
@@ -18,7 +21,9 @@ use dozer_types::parking_lot::RwLock;
 use dozer_types::prettytable::Table;
 use dozer_types::serde;
 use dozer_types::serde::{Deserialize, Serialize};
-use dozer_types::types::SchemaWithChangesType;
+use dozer_types::types::{FieldDefinition, ReplicationChangesTrackingType, SchemaWithChangesType};
+use postgres_types::PgLsn;
+use std::str::FromStr;
 use std::sync::Arc;
 
 pub mod snowflake;
@@ -29,9 +34,37 @@ pub type ValidationResults = HashMap<String, Vec<(Option<String>, Result<(), Con
 
 // use super::{seq_no_resolver::SeqNoResolver, storage::RocksStorage};
 pub trait Connector: Send + Sync {
+    /// Splits `sources` into groups, each ingested by its own connector instance. Within a group,
+    /// operations are forwarded in the order the connector reads them from the source, so tables
+    /// of a group are consistent with each other as far as the source allows. Nothing is
+    /// guaranteed across groups: a group may be ahead of another one.
     fn get_connection_groups(sources: Vec<Source>) -> Vec<Vec<Source>>
     where
         Self: Sized;
+    /// If [`Connector::get_connection_groups`] gives every source its own group, whatever the
+    /// number of sources.
+    fn ingests_sources_separately() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+    /// Groups `sources` like [`Connector::get_connection_groups`], for queries joining the tables
+    /// given as pairs in `join_requirements`. Joined tables are only consistent with each other
+    /// when they share a group.
+    ///
+    /// By default the grouping isn't changed: connectors which read all tables in one stream, like
+    /// Postgres, already keep them in one group, and the others can't ingest several tables with
+    /// a common ordering.
+    fn recommended_grouping(
+        sources: Vec<Source>,
+        _join_requirements: &[(String, String)],
+    ) -> Vec<Vec<Source>>
+    where
+        Self: Sized,
+    {
+        Self::get_connection_groups(sources)
+    }
     fn get_schemas(
         &self,
         table_names: Option<Vec<TableInfo>>,
@@ -47,6 +80,31 @@ pub trait Connector: Send + Sync {
     fn stop(&self);
     fn validate(&self, tables: Option<Vec<TableInfo>>) -> Result<(), ConnectorError>;
     fn validate_schemas(&self, tables: &[TableInfo]) -> Result<ValidationResults, ConnectorError>;
+
+    /// Features of the source supported by the connector, so that callers can check a pipeline's
+    /// requirements before starting it.
+    fn capabilities(&self) -> ConnectorCapabilities;
+
+    /// Returns the current position of the source, in the same `(txid, seq)` terms the connector
+    /// uses for ingested messages. Data written before this call is visible once the pipeline has
+    /// committed up to the returned position. Connectors that can't tell return `None`.
+    fn get_current_position(&self) -> Result<Option<(u64, u64)>, ConnectorError> {
+        Ok(None)
+    }
+}
+
+/// What a connector can do, returned by [`Connector::capabilities`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ConnectorCapabilities {
+    /// Changes made to the source after the connector starts are ingested.
+    pub supports_cdc: bool,
+    /// The data already in the source when the connector starts is ingested.
+    pub supports_snapshot: bool,
+    /// `get_schemas` and `get_tables` describe the source, without the schemas being declared
+    /// upfront.
+    pub supports_schema_inference: bool,
+    /// `start` continues from the `from_seq` of the last commit instead of starting over.
+    pub supports_resume: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
@@ -55,17 +113,77 @@ pub struct TableInfo {
     pub name: String,
     pub id: u32,
     pub columns: Option<Vec<String>>,
+    /// Columns forming the key of the table, in order. Overrides the key found in the source, for
+    /// instance to give one to a view or a table without primary key.
+    pub primary_key_columns: Option<Vec<String>>,
+}
+
+impl TableInfo {
+    /// Positions of the `primary_key_columns` in `fields`, or `None` if the key isn't specified.
+    /// Fails with the name of the first key column which isn't in `fields`.
+    pub fn primary_index(&self, fields: &[FieldDefinition]) -> Result<Option<Vec<usize>>, String> {
+        self.primary_key_columns
+            .as_ref()
+            .map(|columns| {
+                columns
+                    .iter()
+                    .map(|column| {
+                        fields
+                            .iter()
+                            .position(|field| &field.name == column)
+                            .ok_or_else(|| column.clone())
+                    })
+                    .collect()
+            })
+            .transpose()
+    }
+}
+
+/// Replaces the primary index of `schemas` with the `primary_key_columns` of their table, for the
+/// `tables` which specify them.
+pub fn apply_primary_key_columns(
+    schemas: &mut [SchemaWithChangesType],
+    tables: &[TableInfo],
+) -> Result<(), ConnectorError> {
+    for (name, schema, _) in schemas.iter_mut() {
+        if let Some(table) = tables.iter().find(|table| &table.name == name) {
+            if let Some(primary_index) = table
+                .primary_index(&schema.fields)
+                .map_err(|column| ConnectorError::PrimaryKeyColumnNotFound(column, name.clone()))?
+            {
+                schema.primary_index = primary_index;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Updates and deletes of a table without key, or whose key isn't fully selected, can't be
+/// applied to a record, so its operations are forwarded as they come.
+pub fn mark_keyless_tables_append_only(schemas: &mut [SchemaWithChangesType]) {
+    for (_, schema, replication_type) in schemas.iter_mut() {
+        if schema.primary_index.is_empty() {
+            *replication_type = ReplicationChangesTrackingType::AppendOnly;
+        }
+    }
 }
 
 pub fn get_connector(connection: Connection) -> Result<Box<dyn Connector>, ConnectorError> {
     let authentication = connection.authentication.unwrap_or_default();
     match authentication {
-        Authentication::Postgres(_) => {
+        Authentication::Postgres(ref postgres) => {
             let config = map_connection_config(&authentication)?;
+            let start_lsn = postgres
+                .start_lsn
+                .as_deref()
+                .map(|lsn| PgLsn::from_str(lsn).map_err(|_| LsnParseError(lsn.to_string())))
+                .transpose()
+                .map_err(ConnectorError::PostgresConnectorError)?;
             let postgres_config = PostgresConfig {
                 name: connection.name,
                 tables: None,
                 config,
+                start_lsn,
             };
 
             if let Some(dbname) = postgres_config.config.get_dbname() {
@@ -74,7 +192,7 @@ pub fn get_connector(connection: Connection) -> Result<Box<dyn Connector>, Conne
             Ok(Box::new(PostgresConnector::new(1, postgres_config)))
         }
         Authentication::Ethereum(eth_config) => Ok(Box::new(EthConnector::new(2, eth_config))),
-        Authentication::Events(_) => Ok(Box::new(EventsConnector::new(3, connection.name))),
+        Authentication::Events(_) => Ok(Box::new(EventsConnector::new(3, connection.name, vec![]))),
         Authentication::Snowflake(snowflake) => {
             let snowflake_config = snowflake;
 
@@ -97,6 +215,18 @@ pub fn get_connector_outputs(connection: Connection, sources: Vec<Source>) -> Ve
     }
 }
 
+/// See [`Connector::ingests_sources_separately`].
+pub fn ingests_sources_separately(connection: &Connection) -> bool {
+    match connection.authentication {
+        Some(Authentication::Postgres { .. }) => PostgresConnector::ingests_sources_separately(),
+        Some(Authentication::Ethereum { .. }) => EthConnector::ingests_sources_separately(),
+        Some(Authentication::Events(_)) => EventsConnector::ingests_sources_separately(),
+        Some(Authentication::Snowflake { .. }) => SnowflakeConnector::ingests_sources_separately(),
+        Some(Authentication::Kafka { .. }) => KafkaConnector::ingests_sources_separately(),
+        None => false,
+    }
+}
+
 pub fn get_connector_info_table(connection: &Connection) -> Option<Table> {
     match &connection.authentication {
         Some(Authentication::Postgres(config)) => Some(config.convert_to_table()),
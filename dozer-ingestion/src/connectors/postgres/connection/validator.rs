@@ -1,12 +1,13 @@
 use crate::connectors::postgres::connector::ReplicationSlotInfo;
 
 use crate::connectors::TableInfo;
-use crate::errors::PostgresConnectorError;
 use crate::errors::PostgresConnectorError::{
     ColumnNameNotValid, ConnectionFailure, InvalidQueryError, NoAvailableSlotsError,
     ReplicationIsNotAvailableForUserError, SlotIsInUseError, SlotNotExistError,
     StartLsnIsBeforeLastFlushedLsnError, TableError, TableNameNotValid, WALLevelIsNotCorrect,
 };
+use crate::errors::PostgresSchemaError::SchemaReplicationIdentityError;
+use crate::errors::{PostgresConnectorError, PostgresSchemaError};
 use dozer_types::indicatif::ProgressStyle;
 use postgres::Client;
 use postgres_types::PgLsn;
@@ -82,7 +83,19 @@ fn validate_details(client: &mut Client) -> Result<(), PostgresConnectorError> {
     Ok(())
 }
 
+/// Role attributes of the user connecting to Postgres.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UserRoles {
+    pub can_login: bool,
+    pub is_replication_role: bool,
+    pub is_aws_replication_role: bool,
+}
+
 fn validate_user(client: &mut Client) -> Result<(), PostgresConnectorError> {
+    check_user_roles(get_user_roles(client)?)
+}
+
+pub fn get_user_roles(client: &mut Client) -> Result<UserRoles, PostgresConnectorError> {
     client
         .query_one(
             "
@@ -99,34 +112,106 @@ fn validate_user(client: &mut Client) -> Result<(), PostgresConnectorError> {
             &[],
         )
         .map_or(Err(ReplicationIsNotAvailableForUserError), |row| {
-            let can_login: bool = row.get("can_login");
-            let is_replication_role: bool = row.get("is_replication_role");
-            let is_aws_replication_role: bool = row.get("is_aws_replication_role");
-
-            if can_login && (is_replication_role || is_aws_replication_role) {
-                Ok(())
-            } else {
-                Err(ReplicationIsNotAvailableForUserError)
-            }
+            Ok(UserRoles {
+                can_login: row.get("can_login"),
+                is_replication_role: row.get("is_replication_role"),
+                is_aws_replication_role: row.get("is_aws_replication_role"),
+            })
         })
 }
 
+/// The user must be able to log in and to replicate, either with the `REPLICATION` attribute or
+/// through one of the AWS RDS replication roles.
+pub fn check_user_roles(roles: UserRoles) -> Result<(), PostgresConnectorError> {
+    if roles.can_login && (roles.is_replication_role || roles.is_aws_replication_role) {
+        Ok(())
+    } else {
+        Err(ReplicationIsNotAvailableForUserError)
+    }
+}
+
 fn validate_wal_level(client: &mut Client) -> Result<(), PostgresConnectorError> {
+    check_wal_level(&get_wal_level(client)?)
+}
+
+pub fn get_wal_level(client: &mut Client) -> Result<String, PostgresConnectorError> {
     let result = client
         .query_one("SHOW wal_level", &[])
         .map_err(|_e| WALLevelIsNotCorrect())?;
 
-    let wal_level: Result<String, _> = result.try_get(0);
-    wal_level.map_or_else(
-        |e| Err(InvalidQueryError(e)),
-        |level| {
-            if level == "logical" {
-                Ok(())
-            } else {
-                Err(WALLevelIsNotCorrect())
-            }
-        },
-    )
+    result.try_get(0).map_err(InvalidQueryError)
+}
+
+/// Logical replication requires `wal_level = logical`.
+pub fn check_wal_level(level: &str) -> Result<(), PostgresConnectorError> {
+    if level == "logical" {
+        Ok(())
+    } else {
+        Err(WALLevelIsNotCorrect())
+    }
+}
+
+/// Replica identity of a table, see `pg_class.relreplident`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableReplicaIdentity {
+    pub replica_identity: String,
+    pub has_primary_key: bool,
+    pub has_replica_identity_index: bool,
+}
+
+pub fn get_replica_identities(
+    client: &mut Client,
+    schema: &str,
+    tables: &[TableInfo],
+) -> Result<HashMap<String, TableReplicaIdentity>, PostgresConnectorError> {
+    let table_names: Vec<String> = tables.iter().map(|t| t.name.clone()).collect();
+    let rows = client
+        .query(
+            "
+                SELECT c.relname,
+                    c.relreplident::text,
+                    EXISTS(SELECT 1 FROM pg_index i WHERE i.indrelid = c.oid AND i.indisprimary),
+                    EXISTS(SELECT 1 FROM pg_index i WHERE i.indrelid = c.oid AND i.indisreplident)
+                FROM pg_class c
+                    JOIN pg_namespace n ON n.oid = c.relnamespace
+                WHERE n.nspname = $1 AND c.relname = ANY($2)
+            ",
+            &[&schema, &table_names],
+        )
+        .map_err(InvalidQueryError)?;
+
+    let mut replica_identities = HashMap::new();
+    for row in rows {
+        let table_name: String = row.try_get(0).map_err(InvalidQueryError)?;
+        replica_identities.insert(
+            table_name,
+            TableReplicaIdentity {
+                replica_identity: row.try_get(1).map_err(InvalidQueryError)?,
+                has_primary_key: row.try_get(2).map_err(InvalidQueryError)?,
+                has_replica_identity_index: row.try_get(3).map_err(InvalidQueryError)?,
+            },
+        );
+    }
+    Ok(replica_identities)
+}
+
+/// Updates and deletes can only be mapped if Postgres identifies the old rows: through the
+/// primary key with `DEFAULT`, through an index with `USING INDEX`, or with `FULL`.
+pub fn check_replica_identity(
+    table_name: &str,
+    identity: &TableReplicaIdentity,
+) -> Result<(), PostgresSchemaError> {
+    let is_identified = match identity.replica_identity.as_str() {
+        "d" => identity.has_primary_key,
+        "i" => identity.has_replica_identity_index,
+        "f" => true,
+        _ => false,
+    };
+    if is_identified {
+        Ok(())
+    } else {
+        Err(SchemaReplicationIdentityError(table_name.to_string()))
+    }
 }
 
 fn validate_tables_names(table_info: &Vec<TableInfo>) -> Result<(), PostgresConnectorError> {
@@ -188,7 +273,9 @@ fn validate_tables(
     }
 }
 
-fn validate_slot(
+/// Checks that the replication slot exists, isn't in use, and still has the changes from
+/// `replication_info.start_lsn` onwards.
+pub fn validate_slot(
     client: &mut Client,
     replication_info: &ReplicationSlotInfo,
 ) -> Result<(), PostgresConnectorError> {
@@ -205,11 +292,16 @@ fn validate_slot(
     }
 
     let flush_lsn: PgLsn = result.try_get(1).map_err(InvalidQueryError)?;
+    validate_start_lsn(replication_info.start_lsn, flush_lsn)
+}
 
-    if flush_lsn.gt(&replication_info.start_lsn) {
+/// Changes before the `confirmed_flush_lsn` of a slot aren't available anymore, so replication
+/// can't start before it.
+fn validate_start_lsn(start_lsn: PgLsn, flush_lsn: PgLsn) -> Result<(), PostgresConnectorError> {
+    if flush_lsn.gt(&start_lsn) {
         Err(StartLsnIsBeforeLastFlushedLsnError(
+            start_lsn.to_string(),
             flush_lsn.to_string(),
-            replication_info.start_lsn.to_string(),
         ))
     } else {
         Ok(())
@@ -240,7 +332,9 @@ fn validate_limit_of_replications(client: &mut Client) -> Result<(), PostgresCon
 #[cfg(test)]
 mod tests {
     use crate::connectors::postgres::connection::validator::{
-        validate_columns_names, validate_connection, validate_tables_names,
+        check_replica_identity, check_user_roles, check_wal_level, validate_columns_names,
+        validate_connection, validate_start_lsn, validate_tables_names, TableReplicaIdentity,
+        UserRoles,
     };
     use crate::connectors::postgres::connector::ReplicationSlotInfo;
 
@@ -252,7 +346,7 @@ mod tests {
     use tokio_postgres::NoTls;
 
     use crate::connectors::TableInfo;
-    use crate::errors::PostgresConnectorError;
+    use crate::errors::{PostgresConnectorError, PostgresSchemaError};
     use serial_test::serial;
 
     fn get_config() -> tokio_postgres::Config {
@@ -331,6 +425,7 @@ mod tests {
                 name: "not_existing".to_string(),
                 id: 0,
                 columns: None,
+                primary_key_columns: None,
             }];
             let result = validate_connection("pg_test_conn", config, Some(&tables), None);
 
@@ -417,6 +512,89 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_check_wal_level() {
+        assert!(check_wal_level("logical").is_ok());
+        for level in ["replica", "minimal"] {
+            assert!(matches!(
+                check_wal_level(level),
+                Err(PostgresConnectorError::WALLevelIsNotCorrect())
+            ));
+        }
+    }
+
+    #[test]
+    fn test_check_user_roles() {
+        let roles = |can_login, is_replication_role, is_aws_replication_role| UserRoles {
+            can_login,
+            is_replication_role,
+            is_aws_replication_role,
+        };
+
+        assert!(check_user_roles(roles(true, true, false)).is_ok());
+        assert!(check_user_roles(roles(true, false, true)).is_ok());
+        for roles in [
+            roles(true, false, false),
+            roles(false, true, false),
+            roles(false, false, true),
+        ] {
+            assert!(matches!(
+                check_user_roles(roles),
+                Err(PostgresConnectorError::ReplicationIsNotAvailableForUserError)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_check_replica_identity() {
+        let identity = |replica_identity: &str, has_primary_key, has_replica_identity_index| {
+            TableReplicaIdentity {
+                replica_identity: replica_identity.to_string(),
+                has_primary_key,
+                has_replica_identity_index,
+            }
+        };
+
+        for valid in [
+            identity("d", true, false),
+            identity("i", false, true),
+            identity("f", false, false),
+        ] {
+            assert!(check_replica_identity("users", &valid).is_ok());
+        }
+
+        for invalid in [
+            identity("d", false, true),
+            identity("i", true, false),
+            identity("n", true, true),
+        ] {
+            assert_eq!(
+                check_replica_identity("users", &invalid),
+                Err(PostgresSchemaError::SchemaReplicationIdentityError(
+                    "users".to_string()
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_start_lsn() {
+        let flush_lsn = PgLsn::from(0x16_B374_D848);
+
+        assert!(validate_start_lsn(flush_lsn, flush_lsn).is_ok());
+        assert!(validate_start_lsn(PgLsn::from(0x16_B374_D849), flush_lsn).is_ok());
+        assert!(validate_start_lsn(PgLsn::from(0x17_0000_0000), flush_lsn).is_ok());
+
+        match validate_start_lsn(PgLsn::from(0x16_B374_D847), flush_lsn) {
+            Err(PostgresConnectorError::StartLsnIsBeforeLastFlushedLsnError(start, flush)) => {
+                assert_eq!(start, "16/B374D847");
+                assert_eq!(flush, "16/B374D848");
+            }
+            _ => panic!("Start lsn before the flushed lsn should be rejected"),
+        }
+        assert!(validate_start_lsn(PgLsn::from(0), flush_lsn).is_err());
+    }
+
     #[test]
     #[ignore]
     #[serial]
@@ -492,6 +670,7 @@ mod tests {
                 name: table_name.to_string(),
                 id: 0,
                 columns: None,
+                primary_key_columns: None,
             }]);
 
             assert_eq!(expected_result, res.is_ok());
@@ -512,6 +691,7 @@ mod tests {
                 name: "column_test_table".to_string(),
                 id: 0,
                 columns: Some(vec![column_name.to_string()]),
+                primary_key_columns: None,
             }]);
 
             assert_eq!(expected_result, res.is_ok());
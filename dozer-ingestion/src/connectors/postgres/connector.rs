@@ -1,8 +1,9 @@
 use crate::connectors::postgres::schema_helper::SchemaHelper;
 
-use crate::connectors::postgres::connection::validator::validate_connection;
+use crate::connectors::postgres::connection::validator::{validate_connection, validate_slot};
 use crate::connectors::postgres::iterator::PostgresIterator;
-use crate::connectors::{Connector, TableInfo, ValidationResults};
+use crate::connectors::{Connector, ConnectorCapabilities, TableInfo, ValidationResults};
+use crate::errors::PostgresConnectorError::{InvalidQueryError, LsnParseError};
 use crate::errors::{ConnectorError, PostgresConnectorError};
 use crate::ingestion::Ingestor;
 use dozer_types::parking_lot::RwLock;
@@ -12,6 +13,7 @@ use postgres::Client;
 use postgres_types::PgLsn;
 
 use dozer_types::models::source::Source;
+use std::str::FromStr;
 use std::sync::Arc;
 use tokio_postgres::config::ReplicationMode;
 use tokio_postgres::Config;
@@ -23,6 +25,8 @@ pub struct PostgresConfig {
     pub name: String,
     pub tables: Option<Vec<TableInfo>>,
     pub config: Config,
+    /// LSN to start replication from instead of the pipeline's checkpoint.
+    pub start_lsn: Option<PgLsn>,
 }
 
 pub struct PostgresConnector {
@@ -33,6 +37,7 @@ pub struct PostgresConnector {
     replication_conn_config: Config,
     conn_config: Config,
     schema_helper: SchemaHelper,
+    start_lsn: Option<PgLsn>,
 }
 
 #[derive(Debug)]
@@ -59,9 +64,57 @@ impl PostgresConnector {
             tables: config.tables,
             ingestor: None,
             schema_helper: helper,
+            start_lsn: config.start_lsn,
         }
     }
 
+    /// Returns the LSN of the config to start replication from, unless the checkpoint `from_lsn`
+    /// is already past it, i.e. its window was processed before a restart.
+    fn get_explicit_start_lsn(
+        start_lsn: Option<PgLsn>,
+        from_lsn: Option<(PgLsn, u64)>,
+    ) -> Option<PgLsn> {
+        match (start_lsn, from_lsn) {
+            (Some(start_lsn), Some((from_lsn, _))) if from_lsn >= start_lsn => None,
+            (start_lsn, _) => start_lsn,
+        }
+    }
+
+    /// Returns the position to start replication from. An LSN set in the config is validated
+    /// against the replication slot, which must exist.
+    ///
+    /// Checkpoints of the pipeline aren't validated: the slot is acknowledged up to the last
+    /// commit read, which is usually past the checkpointed transaction, and the slot may still
+    /// be held by the previous run for a while after a restart.
+    fn get_start_lsn(
+        &self,
+        from_seq: Option<(u64, u64)>,
+    ) -> Result<Option<(PgLsn, u64)>, ConnectorError> {
+        let lsn = PostgresConnector::get_lsn_with_offset_from_seq(self.name.clone(), from_seq);
+        match Self::get_explicit_start_lsn(self.start_lsn, lsn) {
+            Some(start_lsn) => {
+                info!(
+                    "[{}] Starting replication from configured lsn {}",
+                    self.name, start_lsn
+                );
+                // The changes to replay must still be retained by the slot
+                let mut client = helper::connect(self.conn_config.clone())
+                    .map_err(ConnectorError::PostgresConnectorError)?;
+                validate_slot(
+                    &mut client,
+                    &ReplicationSlotInfo {
+                        name: self.get_slot_name(),
+                        start_lsn,
+                    },
+                )?;
+                Ok(Some((start_lsn, 0)))
+            }
+            None => Ok(lsn),
+        }
+    }
+
+    /// Maps the `(lsn, offset)` sequence passed to [`Connector::start`] to the LSN to resume
+    /// replication from and the offset of the last processed message of its transaction.
     fn get_lsn_with_offset_from_seq(
         conn_name: String,
         from_seq: Option<(u64, u64)>,
@@ -88,6 +141,17 @@ impl PostgresConnector {
 }
 
 impl Connector for PostgresConnector {
+    /// Tables are snapshotted, then followed through the replication slot, whose position is
+    /// kept as the resume point.
+    fn capabilities(&self) -> ConnectorCapabilities {
+        ConnectorCapabilities {
+            supports_cdc: true,
+            supports_snapshot: true,
+            supports_schema_inference: true,
+            supports_resume: true,
+        }
+    }
+
     fn get_tables(&self) -> Result<Vec<TableInfo>, ConnectorError> {
         self.schema_helper.get_tables(None)
     }
@@ -96,9 +160,7 @@ impl Connector for PostgresConnector {
         &self,
         table_names: Option<Vec<TableInfo>>,
     ) -> Result<Vec<SchemaWithChangesType>, ConnectorError> {
-        self.schema_helper
-            .get_schemas(table_names)
-            .map_err(ConnectorError::PostgresConnectorError)
+        self.schema_helper.get_schemas(table_names)
     }
 
     fn initialize(
@@ -115,8 +177,7 @@ impl Connector for PostgresConnector {
     }
 
     fn start(&self, from_seq: Option<(u64, u64)>) -> Result<(), ConnectorError> {
-        let lsn = PostgresConnector::get_lsn_with_offset_from_seq(self.name.clone(), from_seq);
-
+        let lsn = self.get_start_lsn(from_seq)?;
         let iterator = PostgresIterator::new(
             self.id,
             self.name.clone(),
@@ -153,6 +214,8 @@ impl Connector for PostgresConnector {
         Ok(())
     }
 
+    /// All tables are read from a single replication slot, in the order of their transactions,
+    /// so joined tables always share a group.
     fn get_connection_groups(sources: Vec<Source>) -> Vec<Vec<Source>> {
         vec![sources]
     }
@@ -161,6 +224,20 @@ impl Connector for PostgresConnector {
         SchemaHelper::validate(&self.schema_helper, tables)
             .map_err(ConnectorError::PostgresConnectorError)
     }
+
+    /// Operations are positioned at the commit LSN of their transaction, and heartbeats at the
+    /// server's WAL end, so the pipeline reaches the current WAL position once it has processed
+    /// every transaction committed before it.
+    fn get_current_position(&self) -> Result<Option<(u64, u64)>, ConnectorError> {
+        let mut client = helper::connect(self.conn_config.clone())
+            .map_err(ConnectorError::PostgresConnectorError)?;
+        let row = client
+            .query_one("SELECT pg_current_wal_lsn()::text", &[])
+            .map_err(InvalidQueryError)?;
+        let lsn: String = row.get(0);
+        let lsn = PgLsn::from_str(&lsn).map_err(|_| LsnParseError(lsn.to_string()))?;
+        Ok(Some((u64::from(lsn), 0)))
+    }
 }
 
 impl PostgresConnector {
@@ -200,3 +277,100 @@ impl PostgresConnector {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::connectors::postgres::connector::{PostgresConfig, PostgresConnector};
+    use crate::errors::{ConnectorError, PostgresConnectorError};
+
+    use postgres_types::PgLsn;
+    use serial_test::serial;
+    use std::ops::Deref;
+    use tokio_postgres::NoTls;
+
+    fn get_config() -> tokio_postgres::Config {
+        let mut config = tokio_postgres::Config::new();
+        config
+            .dbname("users")
+            .user("postgres")
+            .host("localhost")
+            .deref()
+            .clone()
+    }
+
+    fn get_connector(start_lsn: Option<PgLsn>) -> PostgresConnector {
+        PostgresConnector::new(
+            1,
+            PostgresConfig {
+                name: "start_lsn_test".to_string(),
+                tables: None,
+                config: get_config(),
+                start_lsn,
+            },
+        )
+    }
+
+    #[test]
+    fn test_get_explicit_start_lsn() {
+        let lsn = PgLsn::from(0x16_B374_D848);
+        let before = PgLsn::from(0x16_B374_D847);
+
+        assert_eq!(PostgresConnector::get_explicit_start_lsn(None, None), None);
+        assert_eq!(
+            PostgresConnector::get_explicit_start_lsn(None, Some((lsn, 1))),
+            None
+        );
+        assert_eq!(
+            PostgresConnector::get_explicit_start_lsn(Some(lsn), None),
+            Some(lsn)
+        );
+        assert_eq!(
+            PostgresConnector::get_explicit_start_lsn(Some(lsn), Some((before, 1))),
+            Some(lsn)
+        );
+
+        // The window was processed before a restart
+        assert_eq!(
+            PostgresConnector::get_explicit_start_lsn(Some(lsn), Some((lsn, 1))),
+            None
+        );
+    }
+
+    #[test]
+    #[ignore]
+    #[serial]
+    fn test_start_lsn_is_validated_only_when_configured() {
+        let mut client = postgres::Config::from(get_config()).connect(NoTls).unwrap();
+        let row = client
+            .query_one(
+                "SELECT lsn FROM pg_create_logical_replication_slot('dozer_slot_start_lsn_test', 'pgoutput')",
+                &[],
+            )
+            .expect("Slot creation failed");
+        let flush_lsn: PgLsn = row.get(0);
+        let before = PgLsn::from(u64::from(flush_lsn) - 1);
+
+        // A checkpoint isn't validated
+        let from_seq = Some((u64::from(before), 1));
+        let checkpoint = get_connector(None).get_start_lsn(from_seq);
+
+        let too_old = get_connector(Some(before)).get_start_lsn(None);
+        let start = get_connector(Some(flush_lsn)).get_start_lsn(from_seq);
+
+        client
+            .query(
+                "SELECT pg_drop_replication_slot('dozer_slot_start_lsn_test')",
+                &[],
+            )
+            .expect("Slot drop failed");
+
+        assert_eq!(checkpoint.unwrap(), Some((before, 1)));
+        assert!(matches!(
+            too_old,
+            Err(ConnectorError::PostgresConnectorError(
+                PostgresConnectorError::StartLsnIsBeforeLastFlushedLsnError(_, _)
+            ))
+        ));
+        assert_eq!(start.unwrap(), Some((flush_lsn, 0)));
+    }
+}
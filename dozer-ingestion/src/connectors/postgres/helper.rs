@@ -1,17 +1,23 @@
 use crate::connectors::postgres::xlog_mapper::TableColumn;
 use crate::errors::PostgresSchemaError::{
-    ColumnTypeNotFound, ColumnTypeNotSupported, CustomTypeNotSupported, ValueConversionError,
+    ColumnTypeNotFound, ColumnTypeNotSupported, CustomTypeNotSupported, InvalidColumnType,
+    ValueConversionError,
 };
-use crate::errors::{ConnectorError, PostgresSchemaError};
+use crate::errors::{ConnectorError, PostgresConnectorError, PostgresSchemaError};
 use dozer_types::bytes::Bytes;
-use dozer_types::chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, Offset, Utc};
+use dozer_types::chrono::{
+    DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Offset, Utc,
+};
 use dozer_types::ordered_float::OrderedFloat;
+use dozer_types::serde::Serialize;
+use dozer_types::serde_json::{self, Value};
 use dozer_types::{rust_decimal, types::*};
 use postgres::{Column, Row};
-use postgres_types::{Type, WasNull};
-use rust_decimal::prelude::FromPrimitive;
+use postgres_types::{FromSql, Kind, Type, WasNull};
 use rust_decimal::Decimal;
 use std::error::Error;
+use std::fmt::Display;
+use std::str::FromStr;
 use std::vec;
 
 pub fn postgres_type_to_field(
@@ -23,74 +29,199 @@ pub fn postgres_type_to_field(
             .r#type
             .clone()
             .map_or(Err(ColumnTypeNotFound), |column_type| match column_type {
-                Type::INT2 | Type::INT4 | Type::INT8 => Ok(Field::Int(
-                    String::from_utf8(v.to_vec()).unwrap().parse().unwrap(),
-                )),
-                Type::FLOAT4 | Type::FLOAT8 => Ok(Field::Float(OrderedFloat(
-                    String::from_utf8(v.to_vec())
-                        .unwrap()
-                        .parse::<f64>()
-                        .unwrap(),
-                ))),
-                Type::TEXT | Type::VARCHAR | Type::CHAR => {
-                    Ok(Field::String(String::from_utf8(v.to_vec()).unwrap()))
+                Type::INT2 | Type::INT4 | Type::INT8 => Ok(Field::Int(parse_value(v)?)),
+                Type::FLOAT4 | Type::FLOAT8 => Ok(Field::Float(OrderedFloat(parse_value(v)?))),
+                Type::TEXT | Type::VARCHAR | Type::CHAR | Type::UUID => {
+                    Ok(Field::String(value_to_str(v)?.to_string()))
                 }
                 Type::BYTEA => Ok(Field::Binary(v.to_vec())),
-                Type::NUMERIC => Ok(Field::Decimal(
-                    Decimal::from_f64(
-                        String::from_utf8(v.to_vec())
-                            .unwrap()
-                            .parse::<f64>()
-                            .unwrap(),
-                    )
-                    .unwrap(),
-                )),
+                Type::NUMERIC => Ok(Field::Decimal(parse_value(v)?)),
                 Type::TIMESTAMP => {
-                    let date = NaiveDateTime::parse_from_str(
-                        String::from_utf8(v.to_vec()).unwrap().as_str(),
-                        "%Y-%m-%d %H:%M:%S",
-                    )
-                    .unwrap();
+                    let date =
+                        NaiveDateTime::parse_from_str(value_to_str(v)?, "%Y-%m-%d %H:%M:%S%.f")
+                            .map_err(|e| conversion_error(v, e))?;
                     Ok(Field::Timestamp(DateTime::from_utc(date, Utc.fix())))
                 }
                 Type::TIMESTAMPTZ => {
-                    let date: DateTime<FixedOffset> = DateTime::parse_from_str(
-                        String::from_utf8(v.to_vec()).unwrap().as_str(),
-                        "%Y-%m-%d %H:%M:%S%.f%#z",
-                    )
-                    .unwrap();
-                    Ok(Field::Timestamp(date))
+                    let date: DateTime<FixedOffset> =
+                        DateTime::parse_from_str(value_to_str(v)?, "%Y-%m-%d %H:%M:%S%.f%#z")
+                            .map_err(|e| conversion_error(v, e))?;
+                    Ok(Field::Timestamp(to_utc(date)))
                 }
                 Type::DATE => {
-                    let date: NaiveDate = NaiveDate::parse_from_str(
-                        String::from_utf8(v.to_vec()).unwrap().as_str(),
-                        DATE_FORMAT,
-                    )
-                    .unwrap();
+                    let date: NaiveDate = NaiveDate::parse_from_str(value_to_str(v)?, DATE_FORMAT)
+                        .map_err(|e| conversion_error(v, e))?;
                     Ok(Field::from(date))
                 }
-                Type::JSONB | Type::JSON => Ok(Field::Bson(v.to_vec())),
-                Type::BOOL => Ok(Field::Boolean(v.slice(0..1) == "t")),
+                Type::TIME => {
+                    let time = NaiveTime::parse_from_str(value_to_str(v)?, "%H:%M:%S%.f")
+                        .map_err(|e| conversion_error(v, e))?;
+                    Ok(Field::String(time.format(TIME_FORMAT).to_string()))
+                }
+                Type::INTERVAL => {
+                    let interval = Interval::parse(value_to_str(v)?)?;
+                    Ok(Field::String(interval.to_iso8601()))
+                }
+                Type::JSONB | Type::JSON => serde_json::from_slice(v)
+                    .map(Field::Json)
+                    .map_err(|e| conversion_error(v, e)),
+                Type::BOOL => Ok(Field::Boolean(v.starts_with(b"t"))),
+                Type::BOOL_ARRAY
+                | Type::INT2_ARRAY
+                | Type::INT4_ARRAY
+                | Type::INT8_ARRAY
+                | Type::FLOAT4_ARRAY
+                | Type::FLOAT8_ARRAY
+                | Type::TEXT_ARRAY
+                | Type::VARCHAR_ARRAY
+                | Type::BPCHAR_ARRAY
+                | Type::UUID_ARRAY => text_array_to_field(value_to_str(v)?, &column_type),
                 _ => Err(ColumnTypeNotSupported(column_type.name().to_string())),
             })
     })
 }
 
+/// Timestamps are kept in UTC whatever the offset they were written with, so that the values of
+/// every column compare and sort the same way. Naive timestamps are assumed to be in UTC already.
+fn to_utc(date: DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+    date.with_timezone(&Utc.fix())
+}
+
+fn value_to_str(value: &Bytes) -> Result<&str, PostgresSchemaError> {
+    std::str::from_utf8(value).map_err(|e| conversion_error(value, e))
+}
+
+fn parse_value<T>(value: &Bytes) -> Result<T, PostgresSchemaError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value_to_str(value)?
+        .parse()
+        .map_err(|e| conversion_error(value, e))
+}
+
+fn conversion_error(value: &Bytes, e: impl Display) -> PostgresSchemaError {
+    ValueConversionError(format!("{:?}: {}", value, e))
+}
+
 pub fn postgres_type_to_dozer_type(column_type: Type) -> Result<FieldType, PostgresSchemaError> {
     match column_type {
         Type::BOOL => Ok(FieldType::Boolean),
         Type::INT2 | Type::INT4 | Type::INT8 => Ok(FieldType::Int),
-        Type::CHAR | Type::TEXT | Type::VARCHAR => Ok(FieldType::String),
+        Type::CHAR | Type::TEXT | Type::VARCHAR | Type::UUID => Ok(FieldType::String),
         Type::FLOAT4 | Type::FLOAT8 => Ok(FieldType::Float),
         Type::BIT => Ok(FieldType::Binary),
         Type::TIMESTAMP | Type::TIMESTAMPTZ => Ok(FieldType::Timestamp),
         Type::NUMERIC => Ok(FieldType::Decimal),
-        Type::JSONB => Ok(FieldType::Bson),
+        Type::JSONB | Type::JSON => Ok(FieldType::Json),
         Type::DATE => Ok(FieldType::Date),
+        Type::TIME | Type::INTERVAL => Ok(FieldType::String),
+        // Arrays are JSON encoded until dozer has a native array type
+        Type::BOOL_ARRAY
+        | Type::INT2_ARRAY
+        | Type::INT4_ARRAY
+        | Type::INT8_ARRAY
+        | Type::FLOAT4_ARRAY
+        | Type::FLOAT8_ARRAY
+        | Type::TEXT_ARRAY
+        | Type::VARCHAR_ARRAY
+        | Type::BPCHAR_ARRAY
+        | Type::UUID_ARRAY => Ok(FieldType::Bson),
         _ => Err(ColumnTypeNotSupported(column_type.name().to_string())),
     }
 }
 
+/// Parses a one-dimensional array in the text format, such as `{1,NULL,3}`, into a JSON array
+fn text_array_to_field(text: &str, column_type: &Type) -> Result<Field, PostgresSchemaError> {
+    let element_type = match column_type.kind() {
+        Kind::Array(element_type) => element_type,
+        _ => return Err(InvalidColumnType),
+    };
+
+    let values = split_text_array(text)?
+        .into_iter()
+        .map(|value| match value {
+            Some(value) => text_to_json_value(&value, element_type),
+            None => Ok(Value::Null),
+        })
+        .collect::<Result<Vec<Value>, PostgresSchemaError>>()?;
+    array_to_field(&values)
+}
+
+fn split_text_array(text: &str) -> Result<Vec<Option<String>>, PostgresSchemaError> {
+    let invalid = || ValueConversionError(format!("Invalid array {}", text));
+    let inner = text
+        .strip_prefix('{')
+        .and_then(|t| t.strip_suffix('}'))
+        .ok_or_else(invalid)?;
+
+    let mut values = vec![];
+    if inner.is_empty() {
+        return Ok(values);
+    }
+
+    let mut chars = inner.chars();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut in_quotes = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => current.push(chars.next().ok_or_else(invalid)?),
+            '"' => {
+                in_quotes = !in_quotes;
+                quoted = true;
+            }
+            '{' | '}' if !in_quotes => {
+                return Err(ValueConversionError(format!(
+                    "Multi-dimensional array {} not supported",
+                    text
+                )))
+            }
+            ',' if !in_quotes => {
+                values.push(text_array_element(std::mem::take(&mut current), quoted));
+                quoted = false;
+            }
+            c => current.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(invalid());
+    }
+    values.push(text_array_element(current, quoted));
+    Ok(values)
+}
+
+fn text_array_element(value: String, quoted: bool) -> Option<String> {
+    // A quoted "NULL" is a string
+    if !quoted && value.eq_ignore_ascii_case("NULL") {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+fn text_to_json_value(value: &str, element_type: &Type) -> Result<Value, PostgresSchemaError> {
+    match *element_type {
+        Type::BOOL => Ok(Value::Bool(value == "t")),
+        Type::INT2 | Type::INT4 | Type::INT8 => value
+            .parse::<i64>()
+            .map(Value::from)
+            .map_err(|e| ValueConversionError(e.to_string())),
+        Type::FLOAT4 | Type::FLOAT8 => value
+            .parse::<f64>()
+            .map(Value::from)
+            .map_err(|e| ValueConversionError(e.to_string())),
+        _ => Ok(Value::String(value.to_string())),
+    }
+}
+
+fn array_to_field<T: Serialize>(values: &[T]) -> Result<Field, PostgresSchemaError> {
+    serde_json::to_vec(values)
+        .map(Field::Bson)
+        .map_err(|e| ValueConversionError(e.to_string()))
+}
+
 fn handle_error(e: postgres::error::Error) -> Result<Field, PostgresSchemaError> {
     if let Some(e) = e.source() {
         if let Some(_e) = e.downcast_ref::<WasNull>() {
@@ -103,6 +234,190 @@ fn handle_error(e: postgres::error::Error) -> Result<Field, PostgresSchemaError>
     }
 }
 
+/// Times are ingested as strings, with microsecond precision
+const TIME_FORMAT: &str = "%H:%M:%S%.6f";
+
+/// An `INTERVAL` value. Months and days are kept apart from the time part, as their length varies.
+#[derive(Debug, PartialEq, Eq)]
+struct Interval {
+    months: i32,
+    days: i32,
+    microseconds: i64,
+}
+
+impl Interval {
+    /// Parses the default `postgres` interval style, e.g. `1 year 2 mons -3 days 04:05:06.5`
+    fn parse(text: &str) -> Result<Self, PostgresSchemaError> {
+        let invalid = || ValueConversionError(format!("Invalid interval {}", text));
+        let mut interval = Interval {
+            months: 0,
+            days: 0,
+            microseconds: 0,
+        };
+
+        let mut tokens = text.split_whitespace();
+        while let Some(token) = tokens.next() {
+            if token.contains(':') {
+                interval.microseconds = parse_interval_time(token).ok_or_else(invalid)?;
+                continue;
+            }
+
+            let value: i32 = token.parse().map_err(|_| invalid())?;
+            match tokens.next() {
+                Some("year" | "years") => interval.months += value * 12,
+                Some("mon" | "mons") => interval.months += value,
+                Some("day" | "days") => interval.days += value,
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(interval)
+    }
+
+    /// Formats the interval as an ISO 8601 duration, e.g. `P1Y2M-3DT4H5M6.5S`
+    fn to_iso8601(&self) -> String {
+        let mut duration = String::from("P");
+        let (years, months) = (self.months / 12, self.months % 12);
+        for (value, unit) in [(years, 'Y'), (months, 'M'), (self.days, 'D')] {
+            if value != 0 {
+                duration.push_str(&format!("{}{}", value, unit));
+            }
+        }
+
+        if self.microseconds != 0 {
+            let sign = if self.microseconds < 0 { "-" } else { "" };
+            let micros = self.microseconds.unsigned_abs();
+            let (hours, minutes) = (micros / 3_600_000_000, micros / 60_000_000 % 60);
+            let (seconds, fraction) = (micros / 1_000_000 % 60, micros % 1_000_000);
+
+            duration.push('T');
+            if hours != 0 {
+                duration.push_str(&format!("{}{}H", sign, hours));
+            }
+            if minutes != 0 {
+                duration.push_str(&format!("{}{}M", sign, minutes));
+            }
+            if seconds != 0 || fraction != 0 {
+                duration.push_str(&format!("{}{}", sign, seconds));
+                if fraction != 0 {
+                    let fraction = format!("{:06}", fraction);
+                    duration.push('.');
+                    duration.push_str(fraction.trim_end_matches('0'));
+                }
+                duration.push('S');
+            }
+        }
+
+        if duration.len() == 1 {
+            duration.push_str("T0S");
+        }
+        duration
+    }
+}
+
+impl<'a> FromSql<'a> for Interval {
+    fn from_sql(_ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        if raw.len() != 16 {
+            return Err(Box::new(ValueConversionError(format!(
+                "INTERVAL must be 16 bytes long, got {}",
+                raw.len()
+            ))));
+        }
+        Ok(Interval {
+            microseconds: i64::from_be_bytes(raw[0..8].try_into()?),
+            days: i32::from_be_bytes(raw[8..12].try_into()?),
+            months: i32::from_be_bytes(raw[12..16].try_into()?),
+        })
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::INTERVAL
+    }
+}
+
+/// Parses the `[-]HH:MM:SS[.ffffff]` part of an interval into microseconds
+fn parse_interval_time(text: &str) -> Option<i64> {
+    let (sign, text) = match text.strip_prefix('-') {
+        Some(text) => (-1, text),
+        None => (1, text.strip_prefix('+').unwrap_or(text)),
+    };
+
+    let mut parts = text.split(':');
+    let hours: i64 = parts.next()?.parse().ok()?;
+    let minutes: i64 = parts.next()?.parse().ok()?;
+    let seconds = parts.next()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    let (seconds, fraction) = match seconds.split_once('.') {
+        Some((seconds, fraction)) if fraction.len() <= 6 => {
+            (seconds, format!("{:0<6}", fraction).parse::<i64>().ok()?)
+        }
+        Some(_) => return None,
+        None => (seconds, 0),
+    };
+    let seconds: i64 = seconds.parse().ok()?;
+
+    Some(sign * (((hours * 60 + minutes) * 60 + seconds) * 1_000_000 + fraction))
+}
+
+/// A `UUID` value, in its canonical hyphenated form
+struct UuidString(String);
+
+impl<'a> FromSql<'a> for UuidString {
+    fn from_sql(_ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Ok(UuidString(uuid_bytes_to_string(raw)?))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::UUID
+    }
+}
+
+/// JSON parsed from the binary format of `json` and `jsonb` columns.
+struct JsonField(Value);
+
+impl<'a> FromSql<'a> for JsonField {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        // jsonb is the JSON text prefixed with a version byte.
+        let text = if *ty == Type::JSONB {
+            match raw.split_first() {
+                Some((&1, text)) => text,
+                _ => {
+                    return Err(
+                        ValueConversionError("Unsupported jsonb version".to_string()).into(),
+                    )
+                }
+            }
+        } else {
+            raw
+        };
+        Ok(JsonField(serde_json::from_slice(text)?))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::JSON || *ty == Type::JSONB
+    }
+}
+
+fn uuid_bytes_to_string(raw: &[u8]) -> Result<String, PostgresSchemaError> {
+    if raw.len() != 16 {
+        return Err(ValueConversionError(format!(
+            "UUID must be 16 bytes long, got {}",
+            raw.len()
+        )));
+    }
+
+    let hex: Vec<String> = raw.iter().map(|b| format!("{:02x}", b)).collect();
+    Ok(format!(
+        "{}-{}-{}-{}-{}",
+        hex[0..4].concat(),
+        hex[4..6].concat(),
+        hex[6..8].concat(),
+        hex[8..10].concat(),
+        hex[10..16].concat()
+    ))
+}
+
 macro_rules! convert_row_value_to_field {
     ($a:ident, $b:ident, $c:ty) => {{
         let value: Result<$c, _> = $a.try_get($b);
@@ -110,6 +425,13 @@ macro_rules! convert_row_value_to_field {
     }};
 }
 
+macro_rules! convert_row_array_to_field {
+    ($a:ident, $b:ident, $c:ty) => {{
+        let value: Result<Vec<Option<$c>>, _> = $a.try_get($b);
+        value.map_or_else(handle_error, |val| array_to_field(&val))
+    }};
+}
+
 pub fn value_to_field(
     row: &Row,
     idx: usize,
@@ -126,16 +448,48 @@ pub fn value_to_field(
         &Type::FLOAT4 => convert_row_value_to_field!(row, idx, f32),
         &Type::FLOAT8 => convert_row_value_to_field!(row, idx, f64),
         &Type::TIMESTAMP => convert_row_value_to_field!(row, idx, NaiveDateTime),
-        &Type::TIMESTAMPTZ => convert_row_value_to_field!(row, idx, DateTime<FixedOffset>),
+        &Type::TIMESTAMPTZ => {
+            let value: Result<DateTime<FixedOffset>, _> = row.try_get(idx);
+            value.map_or_else(handle_error, |v| Ok(Field::Timestamp(to_utc(v))))
+        }
         &Type::NUMERIC => convert_row_value_to_field!(row, idx, Decimal),
         &Type::DATE => convert_row_value_to_field!(row, idx, NaiveDate),
+        &Type::TIME => {
+            let value: Result<NaiveTime, _> = row.try_get(idx);
+            value.map_or_else(handle_error, |v| {
+                Ok(Field::String(v.format(TIME_FORMAT).to_string()))
+            })
+        }
+        &Type::INTERVAL => {
+            let value: Result<Interval, _> = row.try_get(idx);
+            value.map_or_else(handle_error, |v| Ok(Field::String(v.to_iso8601())))
+        }
         &Type::BYTEA => {
             let value: Result<Vec<u8>, _> = row.try_get(idx);
             value.map_or_else(handle_error, |v| Ok(Field::Binary(v)))
         }
-        &Type::JSONB => {
-            let value: Result<Vec<u8>, _> = row.try_get(idx);
-            value.map_or_else(handle_error, |v| Ok(Field::Bson(v)))
+        &Type::JSONB | &Type::JSON => {
+            let value: Result<JsonField, _> = row.try_get(idx);
+            value.map_or_else(handle_error, |v| Ok(Field::Json(v.0)))
+        }
+        &Type::UUID => {
+            let value: Result<UuidString, _> = row.try_get(idx);
+            value.map_or_else(handle_error, |v| Ok(Field::String(v.0)))
+        }
+        &Type::BOOL_ARRAY => convert_row_array_to_field!(row, idx, bool),
+        &Type::INT2_ARRAY => convert_row_array_to_field!(row, idx, i16),
+        &Type::INT4_ARRAY => convert_row_array_to_field!(row, idx, i32),
+        &Type::INT8_ARRAY => convert_row_array_to_field!(row, idx, i64),
+        &Type::FLOAT4_ARRAY => convert_row_array_to_field!(row, idx, f32),
+        &Type::FLOAT8_ARRAY => convert_row_array_to_field!(row, idx, f64),
+        &Type::TEXT_ARRAY | &Type::VARCHAR_ARRAY | &Type::BPCHAR_ARRAY => {
+            convert_row_array_to_field!(row, idx, String)
+        }
+        &Type::UUID_ARRAY => {
+            let value: Result<Vec<Option<UuidString>>, _> = row.try_get(idx);
+            value.map_or_else(handle_error, |v| {
+                array_to_field(&v.into_iter().map(|v| v.map(|v| v.0)).collect::<Vec<_>>())
+            })
         }
         _ => {
             if col_type.schema() == "pg_catalog" {
@@ -177,25 +531,64 @@ pub fn map_row_to_operation_event(
     }
 }
 
-pub fn map_schema(rel_id: &u32, columns: &[Column]) -> Result<Schema, ConnectorError> {
-    let field_defs: Result<Vec<FieldDefinition>, _> =
-        columns.iter().map(convert_column_to_field).collect();
+/// Maps the columns of a snapshot query to a schema. Nullability and the primary index are
+/// taken from the `table_schema` read from the catalog. The primary index is left empty when
+/// the table has no key.
+pub fn map_schema(
+    rel_id: &u32,
+    columns: &[Column],
+    table_schema: Option<&Schema>,
+) -> Result<Schema, ConnectorError> {
+    let fields = columns
+        .iter()
+        .map(|column| convert_column_to_field(column, is_nullable(column.name(), table_schema)))
+        .collect::<Result<Vec<FieldDefinition>, _>>()
+        .map_err(PostgresConnectorError::PostgresSchemaError)?;
+    let primary_index = table_schema.map_or(vec![], |table_schema| {
+        get_primary_index(&fields, table_schema)
+    });
 
-    Ok(Schema {
-        identifier: Some(SchemaIdentifier {
-            id: *rel_id,
-            version: 1,
-        }),
-        fields: field_defs.unwrap(),
-        primary_index: vec![0],
-    })
+    let mut schema = Schema {
+        identifier: None,
+        fields,
+        primary_index,
+    };
+    schema.identifier = Some(SchemaIdentifier {
+        id: *rel_id,
+        version: schema.fields_version(),
+    });
+    Ok(schema)
+}
+
+/// Positions of the `table_schema` key columns in `fields`. If any of them is missing, the key
+/// can't be used and the index is empty.
+fn get_primary_index(fields: &[FieldDefinition], table_schema: &Schema) -> Vec<usize> {
+    table_schema
+        .primary_index
+        .iter()
+        .map(|idx| {
+            let name = &table_schema.fields[*idx].name;
+            fields.iter().position(|field| &field.name == name)
+        })
+        .collect::<Option<Vec<usize>>>()
+        .unwrap_or_default()
+}
+
+/// Columns missing from the catalog are assumed to be nullable
+fn is_nullable(name: &str, table_schema: Option<&Schema>) -> bool {
+    table_schema
+        .and_then(|table_schema| table_schema.fields.iter().find(|field| field.name == name))
+        .map_or(true, |field| field.nullable)
 }
 
-pub fn convert_column_to_field(column: &Column) -> Result<FieldDefinition, PostgresSchemaError> {
+pub fn convert_column_to_field(
+    column: &Column,
+    nullable: bool,
+) -> Result<FieldDefinition, PostgresSchemaError> {
     postgres_type_to_dozer_type(column.type_().clone()).map(|typ| FieldDefinition {
         name: column.name().to_string(),
         typ,
-        nullable: true,
+        nullable,
     })
 }
 
@@ -232,9 +625,17 @@ mod tests {
         let value: Vec<u8> = vec![98, 121, 116, 101, 97];
         test_conversion!("bytea", Type::BYTEA, Field::Binary(value));
 
-        let value = Decimal::from_f64(8.28).unwrap();
+        let value = Decimal::new(828, 2);
         test_conversion!("8.28", Type::NUMERIC, Field::Decimal(value));
 
+        // 28 significant digits, more than an f64 can hold
+        let value = Decimal::from_i128_with_scale(1234567890123456789012345678, 4);
+        test_conversion!(
+            "123456789012345678901234.5678",
+            Type::NUMERIC,
+            Field::Decimal(value)
+        );
+
         let value = DateTime::from_utc(
             NaiveDate::from_ymd(2022, 9, 16).and_hms(5, 56, 29),
             Utc.fix(),
@@ -255,12 +656,272 @@ mod tests {
             Field::Timestamp(value)
         );
 
-        // UTF-8 bytes representation of json (https://www.charset.org/utf-8)
-        let value = vec![123, 34, 97, 98, 99, 34, 58, 34, 102, 111, 111, 34, 125];
-        test_conversion!("{\"abc\":\"foo\"}", Type::JSONB, Field::Bson(value));
+        let value = DateTime::from_utc(
+            NaiveDate::from_ymd(2022, 9, 16).and_hms_milli(5, 56, 29, 120),
+            Utc.fix(),
+        );
+        test_conversion!(
+            "2022-09-16 05:56:29.12",
+            Type::TIMESTAMP,
+            Field::Timestamp(value)
+        );
+
+        let value = serde_json::json!({"abc": "foo"});
+        test_conversion!("{\"abc\":\"foo\"}", Type::JSONB, Field::Json(value.clone()));
+        test_conversion!("{\"abc\": \"foo\"}", Type::JSON, Field::Json(value));
 
         test_conversion!("t", Type::BOOL, Field::Boolean(true));
         test_conversion!("f", Type::BOOL, Field::Boolean(false));
+
+        let value = String::from("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11");
+        test_conversion!(
+            "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11",
+            Type::UUID,
+            Field::String(value)
+        );
+    }
+
+    fn convert(value: &[u8], column_type: Type) -> Result<Field, PostgresSchemaError> {
+        postgres_type_to_field(
+            Some(&Bytes::copy_from_slice(value)),
+            &TableColumn {
+                name: "column".to_string(),
+                type_id: column_type.oid() as i32,
+                flags: 0,
+                r#type: Some(column_type),
+                idx: 0,
+            },
+        )
+    }
+
+    #[test]
+    fn it_normalizes_timestamps_to_utc() {
+        let utc = convert(b"2022-09-16 03:56:30.959787", Type::TIMESTAMP).unwrap();
+        for value in [
+            "2022-09-16 10:56:30.959787+07",
+            "2022-09-16 00:26:30.959787-03:30",
+            "2022-09-16 03:56:30.959787+00",
+        ] {
+            let field = convert(value.as_bytes(), Type::TIMESTAMPTZ).unwrap();
+            let timestamp = field.as_timestamp().unwrap();
+            assert_eq!(timestamp.offset().local_minus_utc(), 0);
+            // Same instant, same representation
+            assert_eq!(field.to_string(), utc.to_string());
+        }
+    }
+
+    #[test]
+    fn it_returns_conversion_errors() {
+        // Out of the i64 range
+        let result = convert(b"9223372036854775808", Type::INT8);
+        assert!(matches!(result, Err(ValueConversionError(_))));
+
+        let result = convert(b"12a", Type::INT4);
+        assert!(matches!(result, Err(ValueConversionError(_))));
+
+        let result = convert(&[0xff, 0xfe], Type::TEXT);
+        assert!(matches!(result, Err(ValueConversionError(_))));
+
+        let result = convert(&[0x31, 0xff], Type::INT8);
+        assert!(matches!(result, Err(ValueConversionError(_))));
+
+        let result = convert(b"NaN", Type::NUMERIC);
+        assert!(matches!(result, Err(ValueConversionError(_))));
+
+        let result = convert(b"2022-13-01", Type::DATE);
+        assert!(matches!(result, Err(ValueConversionError(_))));
+
+        // The offending value is part of the error
+        let result = convert(b"12a", Type::INT4);
+        assert!(result.unwrap_err().to_string().contains("12a"));
+
+        assert_eq!(convert(b"", Type::BOOL).unwrap(), Field::Boolean(false));
+    }
+
+    #[test]
+    fn it_converts_time_and_interval() {
+        test_conversion!(
+            "13:45:07.25",
+            Type::TIME,
+            Field::String("13:45:07.250000".to_string())
+        );
+        test_conversion!(
+            "00:00:00",
+            Type::TIME,
+            Field::String("00:00:00.000000".to_string())
+        );
+
+        test_conversion!(
+            "1 year 2 mons 3 days 04:05:06.789",
+            Type::INTERVAL,
+            Field::String("P1Y2M3DT4H5M6.789S".to_string())
+        );
+        test_conversion!(
+            "-1 days +02:00:00",
+            Type::INTERVAL,
+            Field::String("P-1DT2H".to_string())
+        );
+        test_conversion!(
+            "-00:00:30",
+            Type::INTERVAL,
+            Field::String("PT-30S".to_string())
+        );
+        test_conversion!(
+            "00:00:00",
+            Type::INTERVAL,
+            Field::String("PT0S".to_string())
+        );
+    }
+
+    #[test]
+    fn it_converts_interval_bytes() {
+        // 1 mon 2 days 00:00:01.5
+        let mut bytes = 1_500_000_i64.to_be_bytes().to_vec();
+        bytes.extend_from_slice(&2_i32.to_be_bytes());
+        bytes.extend_from_slice(&1_i32.to_be_bytes());
+
+        let interval = Interval::from_sql(&Type::INTERVAL, &bytes).unwrap();
+        assert_eq!(
+            interval,
+            Interval {
+                months: 1,
+                days: 2,
+                microseconds: 1_500_000
+            }
+        );
+        assert_eq!(interval.to_iso8601(), "P1M2DT1.5S");
+        assert!(Interval::from_sql(&Type::INTERVAL, &bytes[..8]).is_err());
+    }
+
+    #[test]
+    fn it_converts_text_arrays() {
+        test_conversion!(
+            "{1,NULL,-3}",
+            Type::INT4_ARRAY,
+            Field::Bson(b"[1,null,-3]".to_vec())
+        );
+        test_conversion!(
+            "{1.5,2}",
+            Type::FLOAT8_ARRAY,
+            Field::Bson(b"[1.5,2.0]".to_vec())
+        );
+        test_conversion!(
+            "{t,f}",
+            Type::BOOL_ARRAY,
+            Field::Bson(b"[true,false]".to_vec())
+        );
+        test_conversion!(
+            r#"{plain,"with space","a,b","quote\"d",NULL,"NULL"}"#,
+            Type::TEXT_ARRAY,
+            Field::Bson(br#"["plain","with space","a,b","quote\"d",null,"NULL"]"#.to_vec())
+        );
+        test_conversion!("{}", Type::INT8_ARRAY, Field::Bson(b"[]".to_vec()));
+
+        let value = postgres_type_to_field(
+            Some(&Bytes::from("{{1,2},{3,4}}")),
+            &TableColumn {
+                name: "column".to_string(),
+                type_id: Type::INT4_ARRAY.oid() as i32,
+                flags: 0,
+                r#type: Some(Type::INT4_ARRAY),
+                idx: 0,
+            },
+        );
+        assert!(matches!(value, Err(ValueConversionError(_))));
+    }
+
+    #[test]
+    fn it_maps_arrays_to_bson() {
+        assert_eq!(
+            postgres_type_to_dozer_type(Type::TEXT_ARRAY).unwrap(),
+            FieldType::Bson
+        );
+        assert_eq!(
+            postgres_type_to_dozer_type(Type::INT8_ARRAY).unwrap(),
+            FieldType::Bson
+        );
+    }
+
+    #[test]
+    fn it_maps_json_to_json() {
+        assert_eq!(
+            postgres_type_to_dozer_type(Type::JSONB).unwrap(),
+            FieldType::Json
+        );
+        assert_eq!(
+            postgres_type_to_dozer_type(Type::JSON).unwrap(),
+            FieldType::Json
+        );
+        assert_eq!(
+            JsonField::from_sql(&Type::JSONB, b"\x01[1,true]")
+                .unwrap()
+                .0,
+            serde_json::json!([1, true])
+        );
+        assert!(JsonField::from_sql(&Type::JSONB, b"\x02[1,true]").is_err());
+    }
+
+    #[test]
+    fn it_converts_uuid_bytes() {
+        let bytes: [u8; 16] = [
+            0xa0, 0xee, 0xbc, 0x99, 0x9c, 0x0b, 0x4e, 0xf8, 0xbb, 0x6d, 0x6b, 0xb9, 0xbd, 0x38,
+            0x0a, 0x11,
+        ];
+        let value = UuidString::from_sql(&Type::UUID, &bytes).unwrap();
+        assert_eq!(value.0, "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11");
+
+        assert!(UuidString::accepts(&Type::UUID));
+        assert!(UuidString::from_sql(&Type::UUID, &bytes[..8]).is_err());
+    }
+
+    #[test]
+    fn it_maps_uuid_to_string() {
+        assert_eq!(
+            postgres_type_to_dozer_type(Type::UUID).unwrap(),
+            FieldType::String
+        );
+    }
+
+    fn field(name: &str) -> FieldDefinition {
+        FieldDefinition::new(name.to_string(), FieldType::String, true)
+    }
+
+    #[test]
+    fn test_primary_index() {
+        let table_schema = Schema {
+            identifier: None,
+            fields: vec![field("name"), field("description"), field("id")],
+            primary_index: vec![2],
+        };
+
+        // The key is the third column of the table
+        let fields = vec![field("name"), field("description"), field("id")];
+        assert_eq!(get_primary_index(&fields, &table_schema), vec![2]);
+
+        // Snapshot queries can select columns in another order
+        let fields = vec![field("id"), field("name")];
+        assert_eq!(get_primary_index(&fields, &table_schema), vec![0]);
+
+        // Without its key column, the table is append-only
+        let fields = vec![field("name"), field("description")];
+        assert!(get_primary_index(&fields, &table_schema).is_empty());
+    }
+
+    #[test]
+    fn test_nullable() {
+        let table_schema = Schema {
+            identifier: None,
+            fields: vec![
+                FieldDefinition::new("id".to_string(), FieldType::Int, false),
+                field("name"),
+            ],
+            primary_index: vec![0],
+        };
+
+        assert!(!is_nullable("id", Some(&table_schema)));
+        assert!(is_nullable("name", Some(&table_schema)));
+        assert!(is_nullable("description", Some(&table_schema)));
+        assert!(is_nullable("id", None));
     }
 
     #[test]
@@ -269,6 +269,8 @@ impl PostgresIteratorHandler {
                 last_commit_lsn: 0,
                 connector_id: self.connector_id,
                 seq_no: 0,
+                in_transaction: false,
+                last_position: (0, 0),
                 name: self.details.name.clone(),
             };
             replicator.start(tables).await
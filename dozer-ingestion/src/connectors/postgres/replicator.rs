@@ -39,6 +39,9 @@ pub struct CDCHandler {
 
     pub offset: u64,
     pub seq_no: u64,
+    pub in_transaction: bool,
+    /// Highest position sent to the ingestor, heartbeats never go back before it.
+    pub last_position: (u64, u64),
 }
 
 impl CDCHandler {
@@ -65,6 +68,7 @@ impl CDCHandler {
 
         self.offset_lsn = u64::from(lsn);
         self.last_commit_lsn = u64::from(lsn);
+        self.last_position = self.last_position.max((u64::from(lsn), 0));
 
         let copy_stream = client
             .copy_both_simple::<bytes::Bytes>(&query)
@@ -84,6 +88,15 @@ impl CDCHandler {
         loop {
             let message = stream.next().await;
             if let Some(Ok(PrimaryKeepAlive(ref k))) = message {
+                // Nothing is pending between transactions, so the pipeline can checkpoint at the server's WAL end.
+                let position = (k.wal_end(), 0);
+                if !self.in_transaction && position > self.last_position {
+                    self.ingestor
+                        .write()
+                        .handle_message((position, IngestionMessage::Heartbeat()))
+                        .map_err(ConnectorError::IngestorError)?;
+                    self.last_position = position;
+                }
                 if k.reply() == 1 {
                     // Postgres' keep alive feedback function expects time from 2000-01-01 00:00:00
                     let since_the_epoch = SystemTime::now()
@@ -116,7 +129,13 @@ impl CDCHandler {
     ) -> Result<(), ConnectorError> {
         match message {
             Some(Ok(XLogData(body))) => {
-                let lsn = body.wal_start();
+                // Operations are positioned at the commit of their transaction: transactions are
+                // sent in commit order, so positions only go forward and compare with the server's
+                // WAL positions.
+                let lsn = match body.data() {
+                    LogicalReplicationMessage::Begin(begin) => begin.final_lsn(),
+                    _ => body.wal_start(),
+                };
                 let message = mapper
                     .handle_message(body)
                     .map_err(PostgresConnectorError)?;
@@ -124,8 +143,10 @@ impl CDCHandler {
                 match message {
                     Some(IngestionMessage::Commit(commit)) => {
                         self.last_commit_lsn = commit.lsn;
+                        self.in_transaction = false;
                     }
                     Some(IngestionMessage::Begin()) => {
+                        self.in_transaction = true;
                         self.begin_lsn = lsn;
                         self.seq_no = 0;
                         if self.begin_lsn != self.offset_lsn {
@@ -139,6 +160,8 @@ impl CDCHandler {
                                 .write()
                                 .handle_message(((self.begin_lsn, self.seq_no), ingestion_message))
                                 .map_err(ConnectorError::IngestorError)?;
+                            self.last_position =
+                                self.last_position.max((self.begin_lsn, self.seq_no));
                         } else {
                             self.offset -= 1;
                         }
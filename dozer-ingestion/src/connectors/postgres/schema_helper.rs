@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::errors::{ConnectorError, PostgresConnectorError, PostgresSchemaError};
 use dozer_types::types::{
@@ -6,13 +6,18 @@ use dozer_types::types::{
     SchemaWithChangesType,
 };
 
-use crate::connectors::{TableInfo, ValidationResults};
+use crate::connectors::{
+    apply_primary_key_columns, mark_keyless_tables_append_only, TableInfo, ValidationResults,
+};
 
 use crate::connectors::postgres::connection::helper;
-use crate::connectors::postgres::helper::postgres_type_to_dozer_type;
-use crate::errors::PostgresSchemaError::{
-    InvalidColumnType, SchemaReplicationIdentityError, ValueConversionError,
+use crate::connectors::postgres::connection::validator::{
+    check_replica_identity, check_user_roles, check_wal_level, get_replica_identities,
+    get_user_roles, get_wal_level,
 };
+use crate::connectors::postgres::helper::postgres_type_to_dozer_type;
+use crate::errors::PostgresConnectorError::ReplicationValidationError;
+use crate::errors::PostgresSchemaError::{InvalidColumnType, ValueConversionError};
 
 use postgres_types::Type;
 use std::collections::hash_map::DefaultHasher;
@@ -39,6 +44,11 @@ impl SchemaHelper {
         &self,
         tables: Option<Vec<TableInfo>>,
     ) -> Result<Vec<TableInfo>, ConnectorError> {
+        let primary_key_columns: HashMap<String, Vec<String>> = tables
+            .iter()
+            .flatten()
+            .filter_map(|table| Some((table.name.clone(), table.primary_key_columns.clone()?)))
+            .collect();
         Ok(self
             .get_schemas(tables)?
             .iter()
@@ -48,6 +58,7 @@ impl SchemaHelper {
                     name: name.clone(),
                     id: schema.identifier.unwrap().id,
                     columns,
+                    primary_key_columns: primary_key_columns.get(name).cloned(),
                 }
             })
             .collect())
@@ -82,20 +93,27 @@ impl SchemaHelper {
     pub fn get_schemas(
         &self,
         tables: Option<Vec<TableInfo>>,
-    ) -> Result<Vec<SchemaWithChangesType>, PostgresConnectorError> {
+    ) -> Result<Vec<SchemaWithChangesType>, ConnectorError> {
         let (results, tables_columns_map) = self.get_columns(tables.as_deref())?;
 
         let mut columns_map: HashMap<String, (Vec<FieldDefinition>, Vec<bool>, u32, String)> =
             HashMap::new();
+        // Tables whose key isn't fully selected, the remaining key columns don't identify a row
+        let mut tables_with_unselected_key = HashSet::new();
         results
             .iter()
             .filter(|row| {
                 let table_name: String = row.get(0);
                 let column_name: String = row.get(1);
+                let is_primary_key: bool = row.get(3);
 
-                tables_columns_map
+                let is_selected = tables_columns_map
                     .get(&table_name)
-                    .map_or(true, |table_info| table_info.contains(&column_name))
+                    .map_or(true, |table_info| table_info.contains(&column_name));
+                if !is_selected && is_primary_key {
+                    tables_with_unselected_key.insert(table_name);
+                }
+                is_selected
             })
             .map(|r| self.convert_row(r))
             .try_for_each(|row| -> Result<(), PostgresSchemaError> {
@@ -119,10 +137,23 @@ impl SchemaHelper {
                 );
 
                 Ok(())
-            })?;
+            })
+            .map_err(PostgresConnectorError::PostgresSchemaError)?;
 
-        Self::map_columns_to_schemas(columns_map)
-            .map_err(PostgresConnectorError::PostgresSchemaError)
+        let mut schemas = Self::map_columns_to_schemas(columns_map)
+            .map_err(PostgresConnectorError::PostgresSchemaError)?;
+        for (table_name, schema, _) in schemas.iter_mut() {
+            if tables_with_unselected_key.contains(table_name) {
+                schema.primary_index.clear();
+            }
+        }
+        if let Some(tables) = &tables {
+            apply_primary_key_columns(&mut schemas, tables)?;
+        }
+
+        mark_keyless_tables_append_only(&mut schemas);
+
+        Ok(schemas)
     }
 
     pub fn map_columns_to_schemas(
@@ -137,14 +168,15 @@ impl SchemaHelper {
                 .map(|(idx, _)| idx)
                 .collect();
 
-            let schema = Schema {
-                identifier: Some(SchemaIdentifier {
-                    id: table_id,
-                    version: 1,
-                }),
+            let mut schema = Schema {
+                identifier: None,
                 fields: fields.clone(),
                 primary_index,
             };
+            schema.identifier = Some(SchemaIdentifier {
+                id: table_id,
+                version: schema.fields_version(),
+            });
 
             let replication_type = match replication_type.as_str() {
                 "d" => Ok(ReplicationChangesTrackingType::OnlyPK),
@@ -159,24 +191,9 @@ impl SchemaHelper {
             schemas.push((table_name, schema, replication_type));
         }
 
-        Self::validate_schema_replication_identity(&schemas)?;
-
         Ok(schemas)
     }
 
-    pub fn validate_schema_replication_identity(
-        schemas: &[SchemaWithChangesType],
-    ) -> Result<(), PostgresSchemaError> {
-        let table_without_primary_index = schemas
-            .iter()
-            .find(|(_table_name, schema, _)| schema.primary_index.is_empty());
-
-        match table_without_primary_index {
-            Some((table_name, _, _)) => Err(SchemaReplicationIdentityError(table_name.clone())),
-            None => Ok(()),
-        }
-    }
-
     pub fn validate(
         &self,
         tables: &[TableInfo],
@@ -209,34 +226,43 @@ impl SchemaHelper {
             }
         }
 
+        self.validate_replication(tables, &mut validation_result)?;
+
+        // The selected columns and the key columns must exist in the table
         for table in tables {
-            if let Some(columns) = &table.columns {
-                let mut existing_columns = HashMap::new();
-                if let Some(res) = validation_result.get(&table.name) {
-                    for (col_name, _) in res {
-                        if let Some(name) = col_name {
-                            existing_columns.insert(name.clone(), ());
-                        }
+            let mut existing_columns = HashMap::new();
+            if let Some(res) = validation_result.get(&table.name) {
+                for (col_name, _) in res {
+                    if let Some(name) = col_name {
+                        existing_columns.insert(name.clone(), ());
                     }
                 }
+            }
 
-                for column_name in columns {
-                    if existing_columns.get(column_name).is_none() {
-                        validation_result
-                            .entry(table.name.clone())
-                            .and_modify(|r| {
-                                r.push((
-                                    None,
-                                    Err(ConnectorError::PostgresConnectorError(
-                                        PostgresConnectorError::ColumnNotFound(
-                                            column_name.to_string(),
-                                            table.name.clone(),
-                                        ),
-                                    )),
-                                ))
-                            })
-                            .or_default();
-                    }
+            let columns = table.columns.iter().flatten().map(|column_name| {
+                let error = PostgresConnectorError::ColumnNotFound(
+                    column_name.to_string(),
+                    table.name.clone(),
+                );
+                (column_name, ConnectorError::PostgresConnectorError(error))
+            });
+            let key_columns = table
+                .primary_key_columns
+                .iter()
+                .flatten()
+                .map(|column_name| {
+                    let error = ConnectorError::PrimaryKeyColumnNotFound(
+                        column_name.to_string(),
+                        table.name.clone(),
+                    );
+                    (column_name, error)
+                });
+            for (column_name, error) in columns.chain(key_columns) {
+                if existing_columns.get(column_name).is_none() {
+                    validation_result
+                        .entry(table.name.clone())
+                        .and_modify(|r| r.push((None, Err(error))))
+                        .or_default();
                 }
             }
         }
@@ -244,6 +270,62 @@ impl SchemaHelper {
         Ok(validation_result)
     }
 
+    /// Checks that the tables can be replicated, reporting every problem found for each table:
+    /// the WAL level and the privileges of the user, which apply to all of them, and the replica
+    /// identity of each table.
+    /// Failures to read these settings are reported like the problems they'd reveal.
+    fn validate_replication(
+        &self,
+        tables: &[TableInfo],
+        validation_result: &mut ValidationResults,
+    ) -> Result<(), PostgresConnectorError> {
+        let mut client = helper::connect(self.conn_config.clone())?;
+        // A failed query is reported for every table, as the settings it reads apply to all of them
+        let failed = |e: PostgresConnectorError| e.to_string();
+        let wal_level = get_wal_level(&mut client).map_err(failed);
+        let user_roles = get_user_roles(&mut client).map_err(failed);
+        let replica_identities =
+            get_replica_identities(&mut client, &self.schema, tables).map_err(failed);
+
+        for table in tables {
+            let mut errors = vec![];
+            match &wal_level {
+                Ok(wal_level) => {
+                    if let Err(e) = check_wal_level(wal_level) {
+                        errors.push(e);
+                    }
+                }
+                Err(e) => errors.push(ReplicationValidationError(e.clone())),
+            }
+            match &user_roles {
+                Ok(user_roles) => {
+                    if let Err(e) = check_user_roles(*user_roles) {
+                        errors.push(e);
+                    }
+                }
+                Err(e) => errors.push(ReplicationValidationError(e.clone())),
+            }
+            match &replica_identities {
+                // Tables which don't exist are reported by the columns validation
+                Ok(replica_identities) => {
+                    if let Some(identity) = replica_identities.get(&table.name) {
+                        if let Err(e) = check_replica_identity(&table.name, identity) {
+                            errors.push(PostgresConnectorError::PostgresSchemaError(e));
+                        }
+                    }
+                }
+                Err(e) => errors.push(ReplicationValidationError(e.clone())),
+            }
+
+            let table_result = validation_result.entry(table.name.clone()).or_default();
+            for e in errors {
+                table_result.push((None, Err(ConnectorError::PostgresConnectorError(e))));
+            }
+        }
+
+        Ok(())
+    }
+
     fn convert_row(
         &self,
         row: &Row,
@@ -333,9 +415,13 @@ ORDER BY table_info.table_schema,
 mod tests {
     use crate::connectors::postgres::schema_helper::SchemaHelper;
     use crate::connectors::postgres::test_utils::get_client;
-    use crate::connectors::TableInfo;
+    use crate::connectors::{
+        apply_primary_key_columns, mark_keyless_tables_append_only, TableInfo,
+    };
+    use crate::errors::{ConnectorError, PostgresConnectorError};
+    use dozer_types::types::{FieldDefinition, FieldType, ReplicationChangesTrackingType};
     use rand::Rng;
-    use std::collections::HashSet;
+    use std::collections::{HashMap, HashSet};
     use std::hash::Hash;
 
     fn assert_vec_eq<T>(a: Vec<T>, b: Vec<T>) -> bool
@@ -399,6 +485,7 @@ mod tests {
             name: table_name.clone(),
             id: 0,
             columns: Some(vec!["name".to_string(), "id".to_string()]),
+            primary_key_columns: None,
         };
         let result = schema_helper.get_tables(Some(vec![table_info])).unwrap();
 
@@ -411,4 +498,109 @@ mod tests {
 
         client.drop_schema(&schema);
     }
+
+    #[test]
+    #[ignore]
+    // fn connector_e2e_get_schema_without_key_column() {
+    fn connector_disabled_test_e2e_get_schema_without_key_column() {
+        let mut client = get_client();
+
+        let mut rng = rand::thread_rng();
+
+        let schema = format!("schema_helper_test_{}", rng.gen::<u32>());
+        let table_name = format!("products_test_{}", rng.gen::<u32>());
+
+        client.create_schema(&schema);
+        client.create_simple_table(&schema, &table_name);
+
+        let schema_helper = SchemaHelper::new(client.postgres_config.clone(), Some(schema.clone()));
+        let table_info = TableInfo {
+            name: table_name.clone(),
+            id: 0,
+            columns: Some(vec!["name".to_string(), "weight".to_string()]),
+            primary_key_columns: None,
+        };
+        let result = schema_helper.get_schemas(Some(vec![table_info])).unwrap();
+
+        let (_, table_schema, replication_type) = result.get(0).unwrap();
+        assert!(table_schema.primary_index.is_empty());
+        assert!(matches!(
+            replication_type,
+            ReplicationChangesTrackingType::AppendOnly
+        ));
+
+        client.drop_schema(&schema);
+    }
+
+    #[test]
+    fn test_added_column_changes_schema_version() {
+        let map_schema = |columns: &[(&str, FieldType)]| {
+            let fields: Vec<FieldDefinition> = columns
+                .iter()
+                .map(|(name, typ)| FieldDefinition::new(name.to_string(), *typ, true))
+                .collect();
+            let primary_keys = (0..fields.len()).map(|idx| idx == 0).collect();
+            let map = HashMap::from([(
+                "users".to_string(),
+                (fields, primary_keys, 16384, "f".to_string()),
+            )]);
+            let (_, schema, _) = SchemaHelper::map_columns_to_schemas(map).unwrap().remove(0);
+            schema.identifier.unwrap()
+        };
+
+        let identifier = map_schema(&[("id", FieldType::Int), ("name", FieldType::String)]);
+        assert_eq!(identifier.id, 16384);
+        assert_eq!(
+            map_schema(&[("id", FieldType::Int), ("name", FieldType::String)]),
+            identifier
+        );
+
+        let new_identifier = map_schema(&[
+            ("id", FieldType::Int),
+            ("name", FieldType::String),
+            ("email", FieldType::String),
+        ]);
+        assert_eq!(new_identifier.id, identifier.id);
+        assert_ne!(new_identifier.version, identifier.version);
+    }
+
+    #[test]
+    fn test_primary_key_columns_override_catalog_key() {
+        let fields: Vec<FieldDefinition> = ["id", "region", "name"]
+            .iter()
+            .map(|name| FieldDefinition::new(name.to_string(), FieldType::String, true))
+            .collect();
+        let map = HashMap::from([(
+            "events_view".to_string(),
+            (fields, vec![false, false, false], 16384, "f".to_string()),
+        )]);
+        let mut schemas = SchemaHelper::map_columns_to_schemas(map).unwrap();
+        let mut keyless_schemas = schemas.clone();
+        mark_keyless_tables_append_only(&mut keyless_schemas);
+        assert!(matches!(
+            keyless_schemas[0].2,
+            ReplicationChangesTrackingType::AppendOnly
+        ));
+
+        let table = |primary_key_columns: &[&str]| TableInfo {
+            name: "events_view".to_string(),
+            id: 0,
+            columns: None,
+            primary_key_columns: Some(primary_key_columns.iter().map(|c| c.to_string()).collect()),
+        };
+
+        assert!(matches!(
+            apply_primary_key_columns(&mut schemas, &[table(&["id", "country"])]),
+            Err(ConnectorError::PrimaryKeyColumnNotFound(column, _)) if column == "country"
+        ));
+        assert!(schemas[0].1.primary_index.is_empty());
+
+        apply_primary_key_columns(&mut schemas, &[table(&["region", "id"])]).unwrap();
+        assert_eq!(schemas[0].1.primary_index, vec![1, 0]);
+        mark_keyless_tables_append_only(&mut schemas);
+        assert!(matches!(
+            schemas[0].2,
+            ReplicationChangesTrackingType::FullChanges
+        ));
+    }
 }
@@ -9,11 +9,13 @@ use crate::errors::PostgresConnectorError::SyncWithSnapshotError;
 use crate::errors::PostgresConnectorError::{InvalidQueryError, PostgresSchemaError};
 use dozer_types::ingestion_types::IngestionMessage;
 use dozer_types::parking_lot::RwLock;
+use dozer_types::types::Schema;
 
 use crate::errors::ConnectorError::PostgresConnectorError;
 use postgres::fallible_iterator::FallibleIterator;
 use postgres_types::PgLsn;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 // 0.4.10
@@ -57,6 +59,12 @@ impl PostgresSnapshotter {
 
         let lsn = lsn_option.map_or(0u64, |(pg_lsn, _)| u64::from(*pg_lsn));
         let tables = self.get_tables(tables)?;
+        let table_schemas: HashMap<String, Schema> =
+            SchemaHelper::new(self.conn_config.clone(), None)
+                .get_schemas(Some(tables.clone()))?
+                .into_iter()
+                .map(|(name, schema, _)| (name, schema))
+                .collect();
 
         let mut idx: u64 = 0;
         for table_info in tables.iter() {
@@ -78,7 +86,8 @@ impl PostgresSnapshotter {
             let columns = stmt.columns();
 
             // Ingest schema for every table
-            let schema = helper::map_schema(&table_info.id, columns)?;
+            let schema =
+                helper::map_schema(&table_info.id, columns, table_schemas.get(&table_info.name))?;
 
             let empty_vec: Vec<String> = Vec::new();
             for msg in client_plain
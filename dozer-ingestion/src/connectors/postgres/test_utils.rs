@@ -24,6 +24,7 @@ pub fn get_iterator(config: Connection, table_name: String) -> Arc<RwLock<Ingest
             name: table_name.clone(),
             id: 0,
             columns: None,
+            primary_key_columns: None,
         }];
 
         let mut connector = get_connector(config).unwrap();
@@ -0,0 +1,34 @@
+use crate::connectors::postgres::connector::PostgresConnector;
+use crate::connectors::Connector;
+use dozer_types::models::source::Source;
+
+fn source(name: &str) -> Source {
+    Source {
+        name: name.to_string(),
+        table_name: name.to_string(),
+        ..Default::default()
+    }
+}
+
+fn names(groups: &[Vec<Source>]) -> Vec<Vec<&str>> {
+    groups
+        .iter()
+        .map(|group| group.iter().map(|source| source.name.as_str()).collect())
+        .collect()
+}
+
+fn join(left: &str, right: &str) -> (String, String) {
+    (left.to_string(), right.to_string())
+}
+
+#[test]
+fn test_joined_tables_share_a_group() {
+    let sources = vec![source("users"), source("orders"), source("products")];
+
+    let groups =
+        PostgresConnector::recommended_grouping(sources.clone(), &[join("users", "orders")]);
+    assert_eq!(names(&groups), vec![vec!["users", "orders", "products"]]);
+
+    let groups = PostgresConnector::recommended_grouping(sources, &[]);
+    assert_eq!(names(&groups), vec![vec!["users", "orders", "products"]]);
+}
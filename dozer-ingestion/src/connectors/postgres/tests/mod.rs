@@ -1,3 +1,7 @@
 pub mod client;
 #[cfg(test)]
 mod e2e;
+#[cfg(test)]
+mod grouping_tests;
+#[cfg(test)]
+mod xlog_mapper_tests;
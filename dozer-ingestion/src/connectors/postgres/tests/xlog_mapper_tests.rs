@@ -0,0 +1,176 @@
+use crate::connectors::postgres::xlog_mapper::XlogMapper;
+use crate::errors::PostgresConnectorError;
+use dozer_types::bytes::{BufMut, Bytes, BytesMut};
+use dozer_types::ingestion_types::IngestionMessage;
+use dozer_types::types::{Field, Operation, SchemaIdentifier};
+use postgres_protocol::message::backend::{
+    LogicalReplicationMessage, ReplicationMessage, XLogDataBody,
+};
+use postgres_types::Type;
+
+const REL_ID: u32 = 16384;
+
+/// Wraps a pgoutput message in the `XLogData` message carrying it in the replication stream.
+fn xlog_data(data: BytesMut) -> XLogDataBody<LogicalReplicationMessage> {
+    let mut buf = BytesMut::new();
+    buf.put_u8(b'w');
+    buf.put_u64(0); // wal start
+    buf.put_u64(0); // wal end
+    buf.put_i64(0); // timestamp
+    buf.put(data);
+
+    match ReplicationMessage::parse(&buf.freeze()).unwrap() {
+        ReplicationMessage::XLogData(body) => body
+            .map_data(|data: Bytes| LogicalReplicationMessage::parse(&data))
+            .unwrap(),
+        _ => panic!("Expected XLogData message"),
+    }
+}
+
+fn put_str(buf: &mut BytesMut, value: &str) {
+    buf.put_slice(value.as_bytes());
+    buf.put_u8(0);
+}
+
+/// `users (id INT4 PRIMARY KEY, bio TEXT)` with the given replica identity.
+fn relation(replica_identity: u8) -> XLogDataBody<LogicalReplicationMessage> {
+    relation_with_columns(
+        replica_identity,
+        &[(1, "id", Type::INT4), (0, "bio", Type::TEXT)],
+    )
+}
+
+/// `users` with the given replica identity and `(flags, name, type)` columns.
+fn relation_with_columns(
+    replica_identity: u8,
+    columns: &[(i8, &str, Type)],
+) -> XLogDataBody<LogicalReplicationMessage> {
+    let mut buf = BytesMut::new();
+    buf.put_u8(b'R');
+    buf.put_u32(REL_ID);
+    put_str(&mut buf, "public");
+    put_str(&mut buf, "users");
+    buf.put_u8(replica_identity);
+    buf.put_i16(columns.len() as i16);
+    for (flags, name, typ) in columns {
+        buf.put_i8(*flags);
+        put_str(&mut buf, name);
+        buf.put_u32(typ.oid());
+        buf.put_i32(-1);
+    }
+    xlog_data(buf)
+}
+
+fn insert(values: &[Option<&str>]) -> XLogDataBody<LogicalReplicationMessage> {
+    let mut buf = BytesMut::new();
+    buf.put_u8(b'I');
+    buf.put_u32(REL_ID);
+    buf.put_u8(b'N');
+    put_tuple(&mut buf, values);
+    xlog_data(buf)
+}
+
+/// Appends a tuple, `None` standing for an unchanged toast value.
+fn put_tuple(buf: &mut BytesMut, values: &[Option<&str>]) {
+    buf.put_i16(values.len() as i16);
+    for value in values {
+        match value {
+            Some(value) => {
+                buf.put_u8(b't');
+                buf.put_i32(value.len() as i32);
+                buf.put_slice(value.as_bytes());
+            }
+            None => buf.put_u8(b'u'),
+        }
+    }
+}
+
+fn update(
+    old: Option<&[Option<&str>]>,
+    new: &[Option<&str>],
+) -> XLogDataBody<LogicalReplicationMessage> {
+    let mut buf = BytesMut::new();
+    buf.put_u8(b'U');
+    buf.put_u32(REL_ID);
+    if let Some(old) = old {
+        buf.put_u8(b'O');
+        put_tuple(&mut buf, old);
+    }
+    buf.put_u8(b'N');
+    put_tuple(&mut buf, new);
+    xlog_data(buf)
+}
+
+#[test]
+fn test_update_with_unchanged_toast_value() {
+    let mut mapper = XlogMapper::default();
+    mapper.handle_message(relation(b'f')).unwrap();
+
+    let bio = "a".repeat(10000);
+    let message = mapper
+        .handle_message(update(Some(&[Some("1"), Some(&bio)]), &[Some("2"), None]))
+        .unwrap();
+
+    match message {
+        Some(IngestionMessage::OperationEvent(event)) => match event.operation {
+            Operation::Update { new, .. } => {
+                assert_eq!(new.values, vec![Field::Int(2), Field::String(bio)]);
+            }
+            operation => panic!("Unexpected operation {:?}", operation),
+        },
+        _ => panic!("Expected an operation event"),
+    }
+}
+
+#[test]
+fn test_update_with_unavailable_toast_value() {
+    let mut mapper = XlogMapper::default();
+    mapper.handle_message(relation(b'd')).unwrap();
+
+    let result = mapper.handle_message(update(None, &[Some("1"), None]));
+    match result {
+        Err(PostgresConnectorError::UnchangedToastValueNotAvailable(column)) => {
+            assert_eq!(column, "bio");
+        }
+        _ => panic!("Unchanged toast value without old tuple should be rejected"),
+    }
+}
+
+fn inserted_schema_id(message: Option<IngestionMessage>) -> SchemaIdentifier {
+    match message {
+        Some(IngestionMessage::OperationEvent(event)) => match event.operation {
+            Operation::Insert { new } => new.schema_id.unwrap(),
+            operation => panic!("Unexpected operation {:?}", operation),
+        },
+        _ => panic!("Expected an operation event"),
+    }
+}
+
+#[test]
+fn test_added_column_changes_schema_version() {
+    let mut mapper = XlogMapper::default();
+    mapper.handle_message(relation(b'f')).unwrap();
+    let message = mapper.handle_message(insert(&[Some("1"), Some("bio")]));
+    let schema_id = inserted_schema_id(message.unwrap());
+    assert_eq!(schema_id.id, REL_ID);
+
+    // The same relation doesn't change the version
+    mapper.handle_message(relation(b'f')).unwrap();
+    let message = mapper.handle_message(insert(&[Some("2"), Some("bio")]));
+    assert_eq!(inserted_schema_id(message.unwrap()), schema_id);
+
+    mapper
+        .handle_message(relation_with_columns(
+            b'f',
+            &[
+                (1, "id", Type::INT4),
+                (0, "bio", Type::TEXT),
+                (0, "email", Type::TEXT),
+            ],
+        ))
+        .unwrap();
+    let message = mapper.handle_message(insert(&[Some("3"), Some("bio"), Some("a@b.c")]));
+    let new_schema_id = inserted_schema_id(message.unwrap());
+    assert_eq!(new_schema_id.id, REL_ID);
+    assert_ne!(new_schema_id.version, schema_id.version);
+}
@@ -1,7 +1,9 @@
 use crate::connectors::postgres::helper;
 use crate::errors::{PostgresConnectorError, PostgresSchemaError};
 use dozer_types::ingestion_types::IngestionMessage;
-use dozer_types::types::{Field, FieldDefinition, Operation, OperationEvent, Record, Schema};
+use dozer_types::types::{
+    Field, FieldDefinition, Operation, OperationEvent, Record, Schema, SchemaIdentifier,
+};
 use helper::postgres_type_to_dozer_type;
 use postgres_protocol::message::backend::LogicalReplicationMessage::{
     Begin, Commit, Delete, Insert, Relation, Update,
@@ -29,9 +31,20 @@ pub struct Table {
     columns: Vec<TableColumn>,
     hash: u64,
     rel_id: u32,
+    /// Version of the schema of the replicated columns, see [`Schema::fields_version`].
+    version: u16,
     replica_identity: ReplicaIdentity,
 }
 
+impl Table {
+    fn schema_identifier(&self) -> SchemaIdentifier {
+        SchemaIdentifier {
+            id: self.rel_id,
+            version: self.version,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct TableColumn {
     pub name: String,
@@ -109,18 +122,11 @@ impl XlogMapper {
                 let table = self.relations_map.get(&insert.rel_id()).unwrap();
                 let new_values = insert.tuple().tuple_data();
 
-                let values = Self::convert_values_to_fields(table, new_values, false)?;
+                let values = Self::convert_values_to_fields(table, new_values, None, false)?;
 
                 let event = OperationEvent {
                     operation: Operation::Insert {
-                        new: Record::new(
-                            Some(dozer_types::types::SchemaIdentifier {
-                                id: table.rel_id,
-                                version: table.rel_id as u16,
-                            }),
-                            values,
-                            None,
-                        ),
+                        new: Record::new(Some(table.schema_identifier()), values, None),
                     },
                     seq_no: 0,
                 };
@@ -130,28 +136,16 @@ impl XlogMapper {
             Update(update) => {
                 let table = self.relations_map.get(&update.rel_id()).unwrap();
                 let new_values = update.new_tuple().tuple_data();
+                let previous_values = update.old_tuple().map(|tuple| tuple.tuple_data());
 
-                let values = Self::convert_values_to_fields(table, new_values, false)?;
+                let values =
+                    Self::convert_values_to_fields(table, new_values, previous_values, false)?;
                 let old_values = Self::convert_old_value_to_fields(table, update)?;
 
                 let event = OperationEvent {
                     operation: Operation::Update {
-                        old: Record::new(
-                            Some(dozer_types::types::SchemaIdentifier {
-                                id: table.rel_id,
-                                version: table.rel_id as u16,
-                            }),
-                            old_values,
-                            None,
-                        ),
-                        new: Record::new(
-                            Some(dozer_types::types::SchemaIdentifier {
-                                id: table.rel_id,
-                                version: table.rel_id as u16,
-                            }),
-                            values,
-                            None,
-                        ),
+                        old: Record::new(Some(table.schema_identifier()), old_values, None),
+                        new: Record::new(Some(table.schema_identifier()), values, None),
                     },
                     seq_no: 0,
                 };
@@ -163,18 +157,11 @@ impl XlogMapper {
                 let table = self.relations_map.get(&delete.rel_id()).unwrap();
                 let key_values = delete.key_tuple().unwrap().tuple_data();
 
-                let values = Self::convert_values_to_fields(table, key_values, true)?;
+                let values = Self::convert_values_to_fields(table, key_values, None, true)?;
 
                 let event = OperationEvent {
                     operation: Operation::Delete {
-                        old: Record::new(
-                            Some(dozer_types::types::SchemaIdentifier {
-                                id: table.rel_id,
-                                version: table.rel_id as u16,
-                            }),
-                            values,
-                            None,
-                        ),
+                        old: Record::new(Some(table.schema_identifier()), values, None),
                     },
                     seq_no: 0,
                 };
@@ -227,15 +214,8 @@ impl XlogMapper {
             ReplicaIdentity::Index => ReplicaIdentity::Index,
         };
 
-        let table = Table {
-            columns,
-            hash,
-            rel_id,
-            replica_identity,
-        };
-
         let mut fields = vec![];
-        for c in &table.columns {
+        for c in &columns {
             let typ = c.r#type.clone();
             let typ = typ
                 .map_or(
@@ -251,13 +231,20 @@ impl XlogMapper {
             });
         }
 
-        let _schema = Schema {
-            identifier: Some(dozer_types::types::SchemaIdentifier {
-                id: table.rel_id,
-                version: table.rel_id as u16,
-            }),
+        // Only used to version the fields; the primary index of the table is resolved from the
+        // catalog and the table's `primary_key_columns` when the schemas are fetched.
+        let schema = Schema {
+            identifier: None,
             fields,
-            primary_index: vec![0],
+            primary_index: vec![],
+        };
+
+        let table = Table {
+            columns,
+            hash,
+            rel_id,
+            version: schema.fields_version(),
+            replica_identity,
         };
 
         self.relations_map.insert(rel_id, table);
@@ -265,22 +252,39 @@ impl XlogMapper {
         Ok(())
     }
 
+    /// Converts the tuple of a replication message to fields.
+    ///
+    /// Postgres doesn't send the values of TOASTed columns which weren't changed by an update,
+    /// only an unchanged toast marker. These values are carried forward from `previous_values`,
+    /// the old tuple of the update, which Postgres only sends for tables with
+    /// `REPLICA IDENTITY FULL`. The connector doesn't keep any state of the records, so the
+    /// update is rejected if the old tuple isn't available, instead of emitting a wrong value.
     fn convert_values_to_fields(
         table: &Table,
         new_values: &[TupleData],
+        previous_values: Option<&[TupleData]>,
         only_key: bool,
     ) -> Result<Vec<Field>, PostgresConnectorError> {
         let mut values: Vec<Field> = vec![];
 
         for column in &table.columns {
             if column.flags == 1 || !only_key {
-                let value = new_values.get(column.idx).unwrap();
+                let value = match (new_values.get(column.idx).unwrap(), previous_values) {
+                    (TupleData::UnchangedToast, Some(previous_values)) => {
+                        previous_values.get(column.idx).unwrap()
+                    }
+                    (value, _) => value,
+                };
                 match value {
                     TupleData::Null => values.push(
                         helper::postgres_type_to_field(None, column)
                             .map_err(PostgresConnectorError::PostgresSchemaError)?,
                     ),
-                    TupleData::UnchangedToast => {}
+                    TupleData::UnchangedToast => {
+                        return Err(PostgresConnectorError::UnchangedToastValueNotAvailable(
+                            column.name.clone(),
+                        ))
+                    }
                     TupleData::Text(text) => values.push(
                         helper::postgres_type_to_field(Some(text), column)
                             .map_err(PostgresConnectorError::PostgresSchemaError)?,
@@ -300,9 +304,19 @@ impl XlogMapper {
     ) -> Result<Vec<Field>, PostgresConnectorError> {
         match table.replica_identity {
             ReplicaIdentity::Default | ReplicaIdentity::Full | ReplicaIdentity::Index => {
+                let previous_values = update.old_tuple().map(|tuple| tuple.tuple_data());
                 update.key_tuple().map_or_else(
-                    || Self::convert_values_to_fields(table, update.new_tuple().tuple_data(), true),
-                    |key_tuple| Self::convert_values_to_fields(table, key_tuple.tuple_data(), true),
+                    || {
+                        Self::convert_values_to_fields(
+                            table,
+                            update.new_tuple().tuple_data(),
+                            previous_values,
+                            true,
+                        )
+                    },
+                    |key_tuple| {
+                        Self::convert_values_to_fields(table, key_tuple.tuple_data(), None, true)
+                    },
                 )
             }
             ReplicaIdentity::Nothing => Ok(vec![]),
@@ -5,7 +5,7 @@ use crate::errors::{ConnectorError, SnowflakeError, SnowflakeSchemaError};
 
 use crate::connectors::snowflake::schema_helper::SchemaHelper;
 use crate::connectors::TableInfo;
-use crate::errors::SnowflakeError::QueryError;
+use crate::errors::SnowflakeError::{ConnectionError, QueryError};
 use crate::errors::SnowflakeSchemaError::SchemaConversionError;
 use dozer_types::chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 use dozer_types::types::*;
@@ -206,7 +206,7 @@ impl Client {
         table_name: &String,
     ) -> Result<bool, SnowflakeError> {
         let query = format!(
-            "SELECT * FROM INFORMATION_SCHEMA.TABLES WHERE TABLE_NAME = '{}';",
+            "SELECT * FROM INFORMATION_SCHEMA.TABLES WHERE TABLE_NAME = UPPER('{}');",
             table_name
         );
 
@@ -282,6 +282,45 @@ impl Client {
         Ok(())
     }
 
+    pub fn test_connection(&self, conn: &Connection<AutocommitOn>) -> Result<(), SnowflakeError> {
+        let stmt = Statement::with_parent(conn).map_err(|e| ConnectionError(Box::new(e)))?;
+        stmt.exec_direct("SELECT 1")
+            .map_err(|e| ConnectionError(Box::new(e)))?;
+        Ok(())
+    }
+
+    pub fn fetch_table_names(
+        &self,
+        config: &SnowflakeConfig,
+        conn: &Connection<AutocommitOn>,
+    ) -> Result<Vec<TableInfo>, ConnectorError> {
+        // Unquoted identifiers are stored uppercase, while the config may spell them in any case
+        let query = format!(
+            "SELECT TABLE_NAME
+            FROM INFORMATION_SCHEMA.TABLES
+            WHERE UPPER(TABLE_CATALOG) = UPPER('{}') AND UPPER(TABLE_SCHEMA) = UPPER('{}')
+                AND TABLE_TYPE = 'BASE TABLE'
+            ORDER BY TABLE_NAME",
+            config.database, config.schema
+        );
+
+        Ok(self.fetch(conn, query)?.map_or(vec![], |(_, iterator)| {
+            iterator
+                .filter_map(|row| match row.into_iter().next() {
+                    Some(Some(Field::String(name))) => Some(name),
+                    _ => None,
+                })
+                .enumerate()
+                .map(|(idx, name)| TableInfo {
+                    name,
+                    id: idx as u32,
+                    columns: None,
+                    primary_key_columns: None,
+                })
+                .collect()
+        }))
+    }
+
     pub fn fetch_tables(
         &self,
         tables: Option<Vec<TableInfo>>,
@@ -4,9 +4,11 @@ use std::sync::Arc;
 #[cfg(feature = "snowflake")]
 use std::time::Duration;
 
+#[cfg(feature = "snowflake")]
+use crate::connectors::apply_primary_key_columns;
 #[cfg(feature = "snowflake")]
 use crate::connectors::snowflake::connection::client::Client;
-use crate::connectors::{Connector, ValidationResults};
+use crate::connectors::{Connector, ConnectorCapabilities, ValidationResults};
 use crate::ingestion::Ingestor;
 use crate::{connectors::TableInfo, errors::ConnectorError};
 use dozer_types::ingestion_types::SnowflakeConfig;
@@ -17,13 +19,18 @@ use crate::connectors::snowflake::snapshotter::Snapshotter;
 #[cfg(feature = "snowflake")]
 use crate::connectors::snowflake::stream_consumer::StreamConsumer;
 #[cfg(feature = "snowflake")]
-use crate::errors::SnowflakeError::ConnectionError;
+use crate::errors::SnowflakeError;
+#[cfg(feature = "snowflake")]
+use crate::errors::SnowflakeError::{ConnectionError, EnvironmentError};
 use dozer_types::models::source::Source;
 use dozer_types::types::SchemaWithChangesType;
 use tokio::runtime::Runtime;
 #[cfg(feature = "snowflake")]
 use tokio::time;
 
+#[cfg(feature = "snowflake")]
+const DEFAULT_POLL_INTERVAL_SECONDS: u32 = 5;
+
 pub struct SnowflakeConnector {
     pub id: u64,
     config: SnowflakeConfig,
@@ -43,24 +50,45 @@ impl SnowflakeConnector {
 }
 
 impl Connector for SnowflakeConnector {
+    /// Tables are snapshotted when their stream is created, then followed through the stream.
+    /// Without the `snowflake` feature the connector can't do anything.
+    fn capabilities(&self) -> ConnectorCapabilities {
+        let enabled = cfg!(feature = "snowflake");
+        ConnectorCapabilities {
+            supports_cdc: enabled,
+            supports_snapshot: enabled,
+            supports_schema_inference: enabled,
+            supports_resume: enabled,
+        }
+    }
+
+    /// Every table is ingested separately, so that its checkpoint is the offset of its stream.
     fn get_connection_groups(sources: Vec<Source>) -> Vec<Vec<Source>> {
         sources.iter().map(|s| vec![s.clone()]).collect()
     }
 
+    fn ingests_sources_separately() -> bool {
+        true
+    }
+
     #[cfg(feature = "snowflake")]
     fn get_schemas(
         &self,
         table_names: Option<Vec<TableInfo>>,
     ) -> Result<Vec<SchemaWithChangesType>, ConnectorError> {
         let client = Client::new(&self.config);
-        let env = create_environment_v3().map_err(|e| e.unwrap()).unwrap();
+        let env = create_environment_v3().map_err(|e| EnvironmentError(e.map(Box::new)))?;
         let conn = env
             .connect_with_connection_string(&client.get_conn_string())
             .map_err(|e| ConnectionError(Box::new(e)))?;
 
-        client
-            .fetch_tables(table_names, &self.config, &conn)
-            .map_err(ConnectorError::SnowflakeError)
+        let mut schemas = client
+            .fetch_tables(table_names.clone(), &self.config, &conn)
+            .map_err(ConnectorError::SnowflakeError)?;
+        if let Some(tables) = &table_names {
+            apply_primary_key_columns(&mut schemas, tables)?;
+        }
+        Ok(schemas)
     }
 
     #[cfg(not(feature = "snowflake"))]
@@ -71,10 +99,36 @@ impl Connector for SnowflakeConnector {
         todo!()
     }
 
+    #[cfg(feature = "snowflake")]
+    fn get_tables(&self) -> Result<Vec<TableInfo>, ConnectorError> {
+        let client = Client::new(&self.config);
+        let env = create_environment_v3().map_err(|e| EnvironmentError(e.map(Box::new)))?;
+        let conn = env
+            .connect_with_connection_string(&client.get_conn_string())
+            .map_err(|e| ConnectionError(Box::new(e)))?;
+
+        client.fetch_table_names(&self.config, &conn)
+    }
+
+    #[cfg(not(feature = "snowflake"))]
     fn get_tables(&self) -> Result<Vec<TableInfo>, ConnectorError> {
         todo!()
     }
 
+    #[cfg(feature = "snowflake")]
+    fn test_connection(&self) -> Result<(), ConnectorError> {
+        let client = Client::new(&self.config);
+        let env = create_environment_v3().map_err(|e| EnvironmentError(e.map(Box::new)))?;
+        let conn = env
+            .connect_with_connection_string(&client.get_conn_string())
+            .map_err(|e| ConnectionError(Box::new(e)))?;
+
+        client
+            .test_connection(&conn)
+            .map_err(ConnectorError::SnowflakeError)
+    }
+
+    #[cfg(not(feature = "snowflake"))]
     fn test_connection(&self) -> Result<(), ConnectorError> {
         todo!()
     }
@@ -89,7 +143,7 @@ impl Connector for SnowflakeConnector {
         Ok(())
     }
 
-    fn start(&self, _from_seq: Option<(u64, u64)>) -> Result<(), ConnectorError> {
+    fn start(&self, from_seq: Option<(u64, u64)>) -> Result<(), ConnectorError> {
         let _connector_id = self.id;
         let ingestor = self
             .ingestor
@@ -97,13 +151,20 @@ impl Connector for SnowflakeConnector {
             .map_or(Err(ConnectorError::InitializationError), Ok)?
             .clone();
 
-        Runtime::new()
-            .unwrap()
-            .block_on(async { run(self.config.clone(), self.tables.clone(), ingestor).await })
+        Runtime::new().unwrap().block_on(async {
+            run(self.config.clone(), self.tables.clone(), ingestor, from_seq).await
+        })
     }
 
     fn stop(&self) {}
 
+    #[cfg(feature = "snowflake")]
+    fn validate(&self, _tables: Option<Vec<TableInfo>>) -> Result<(), ConnectorError> {
+        get_poll_interval(&self.config)?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "snowflake"))]
     fn validate(&self, _tables: Option<Vec<TableInfo>>) -> Result<(), ConnectorError> {
         Ok(())
     }
@@ -113,13 +174,26 @@ impl Connector for SnowflakeConnector {
     }
 }
 
+#[cfg(feature = "snowflake")]
+fn get_poll_interval(config: &SnowflakeConfig) -> Result<Duration, SnowflakeError> {
+    match config
+        .poll_interval_seconds
+        .unwrap_or(DEFAULT_POLL_INTERVAL_SECONDS)
+    {
+        0 => Err(SnowflakeError::InvalidPollInterval),
+        seconds => Ok(Duration::from_secs(seconds as u64)),
+    }
+}
+
 #[cfg(feature = "snowflake")]
 async fn run(
     config: SnowflakeConfig,
     tables: Option<Vec<TableInfo>>,
     ingestor: Arc<RwLock<Ingestor>>,
+    from_seq: Option<(u64, u64)>,
 ) -> Result<(), ConnectorError> {
     let client = Client::new(&config);
+    let poll_interval = get_poll_interval(&config)?;
 
     // SNAPSHOT part - run it when stream table doesnt exist
     match tables {
@@ -137,12 +211,26 @@ async fn run(
 
             let stream_client = Client::new(&config);
             let ingestor_stream = Arc::clone(&ingestor);
-            let mut interval = time::interval(Duration::from_secs(5));
+            let mut interval = time::interval(poll_interval);
+
+            // `get_connection_groups` gives every table its own source, so the checkpoint is the
+            // offset of that table
+            if from_seq.is_some() && tables.len() > 1 {
+                return Err(ConnectorError::SnowflakeError(
+                    SnowflakeError::CheckpointOfSeveralTables(tables.len()),
+                ));
+            }
+            let mut offsets = vec![from_seq; tables.len()];
 
             let mut consumer = StreamConsumer::new();
             loop {
-                for table in tables.iter() {
-                    consumer.consume_stream(&stream_client, &table.name, &ingestor_stream)?;
+                for (table, offset) in tables.iter().zip(offsets.iter_mut()) {
+                    *offset = consumer.consume_stream(
+                        &stream_client,
+                        &table.name,
+                        &ingestor_stream,
+                        *offset,
+                    )?;
 
                     interval.tick().await;
                 }
@@ -158,6 +246,7 @@ async fn run(
     _config: SnowflakeConfig,
     _tables: Option<Vec<TableInfo>>,
     _ingestor: Arc<RwLock<Ingestor>>,
+    _from_seq: Option<(u64, u64)>,
 ) -> Result<(), ConnectorError> {
     Ok(())
 }
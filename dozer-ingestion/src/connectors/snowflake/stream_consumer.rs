@@ -10,6 +10,8 @@ use crate::connectors::snowflake::snapshotter::Snapshotter;
 use crate::errors::SnowflakeStreamError::{CannotDetermineAction, UnsupportedActionInStream};
 use dozer_types::types::{Field, Operation, OperationEvent, Record, SchemaIdentifier};
 use odbc::create_environment_v3;
+use odbc::odbc_safe::AutocommitOn;
+use odbc::Connection;
 use std::sync::Arc;
 
 #[derive(Default)]
@@ -24,8 +26,8 @@ impl StreamConsumer {
         format!("dozer_{}_stream", table_name)
     }
 
-    pub fn get_stream_temp_table_name(table_name: &str) -> String {
-        format!("dozer_{}_stream_temp", table_name)
+    pub fn get_stream_batch_table_name(table_name: &str, batch: u64) -> String {
+        format!("dozer_{}_stream_batch_{}", table_name, batch)
     }
 
     pub fn is_stream_created(client: &Client, table_name: String) -> Result<bool, ConnectorError> {
@@ -117,55 +119,126 @@ impl StreamConsumer {
         }
     }
 
+    /// Forwards the rows of the stream batch table `batch` that come after `after_idx` and returns
+    /// the offset of the last forwarded row.
+    fn forward_batch(
+        client: &Client,
+        conn: &Connection<AutocommitOn>,
+        table_name: &str,
+        batch: u64,
+        after_idx: Option<u64>,
+        ingestor: &Arc<RwLock<Ingestor>>,
+    ) -> Result<Option<(u64, u64)>, ConnectorError> {
+        // Deletes come before inserts, so that updates are applied in the right order
+        let query = format!(
+            "SELECT * FROM {} ORDER BY METADATA$ACTION, METADATA$ROW_ID;",
+            Self::get_stream_batch_table_name(table_name, batch)
+        );
+
+        let mut last_offset = None;
+        if let Some((schema, iterator)) = client.fetch(conn, query)? {
+            let used_columns_for_schema = schema.len() - 3;
+            let action_idx = used_columns_for_schema;
+
+            for (idx, row) in iterator.enumerate() {
+                let idx = idx as u64;
+                if after_idx.map_or(false, |after_idx| idx <= after_idx) {
+                    continue;
+                }
+
+                let ingestion_message =
+                    Self::get_ingestion_message(row, action_idx, used_columns_for_schema)?;
+                ingestor
+                    .write()
+                    .handle_message(((batch, idx), ingestion_message))
+                    .map_err(ConnectorError::IngestorError)?;
+                last_offset = Some((batch, idx));
+            }
+        }
+        Ok(last_offset)
+    }
+
+    fn drop_batch_table(
+        client: &Client,
+        conn: &Connection<AutocommitOn>,
+        table_name: &str,
+        batch: u64,
+    ) -> Result<(), ConnectorError> {
+        let query = format!(
+            "DROP TABLE IF EXISTS {};",
+            Self::get_stream_batch_table_name(table_name, batch)
+        );
+        client.exec(conn, query)?;
+        Ok(())
+    }
+
+    /// Consumes the pending changes of the stream on `table_name` and forwards the ones after
+    /// `offset`, the `(batch, row)` position of the last change that was forwarded before.
+    /// Returns the position of the last forwarded change, which should be passed to the next call.
+    ///
+    /// Changes are moved from the stream to a batch table before being forwarded, as reading a
+    /// stream doesn't advance it. A batch table is only dropped once the following batch has been
+    /// forwarded, so after a restart the changes after the checkpointed offset can be forwarded
+    /// again. Delivery is at-least-once, unless the pipeline falls more than a whole batch behind
+    /// in checkpointing: changes of a dropped batch table can't be replayed anymore.
     pub fn consume_stream(
         &mut self,
         client: &Client,
         table_name: &str,
         ingestor: &Arc<RwLock<Ingestor>>,
-    ) -> Result<(), ConnectorError> {
+        offset: Option<(u64, u64)>,
+    ) -> Result<Option<(u64, u64)>, ConnectorError> {
         let env = create_environment_v3().map_err(|e| e.unwrap()).unwrap();
         let conn = env
             .connect_with_connection_string(&client.get_conn_string())
             .unwrap();
 
-        let temp_table_name = Self::get_stream_temp_table_name(table_name);
-        let stream_name = Self::get_stream_table_name(table_name);
-        let temp_table_exist = client.table_exist(&conn, &temp_table_name)?;
+        // Batch 0 is the snapshot
+        let (current_batch, current_idx) = offset.unwrap_or((0, 0));
+        let mut offset = offset;
+
+        // Changes of the current batch that were not checkpointed before a restart
+        if current_batch > 0
+            && client.table_exist(
+                &conn,
+                &Self::get_stream_batch_table_name(table_name, current_batch),
+            )?
+        {
+            if let Some(last_offset) = Self::forward_batch(
+                client,
+                &conn,
+                table_name,
+                current_batch,
+                Some(current_idx),
+                ingestor,
+            )? {
+                offset = Some(last_offset);
+            }
+        }
 
-        if !temp_table_exist {
+        // The next batch may already exist if it was created before a restart
+        let next_batch = current_batch + 1;
+        let next_batch_table = Self::get_stream_batch_table_name(table_name, next_batch);
+        if !client.table_exist(&conn, &next_batch_table)? {
             let query = format!(
-                "CREATE OR REPLACE TEMP TABLE {} AS
-                    SELECT * FROM {} ORDER BY METADATA$ACTION;",
-                temp_table_name, stream_name
+                "CREATE TRANSIENT TABLE {} AS SELECT * FROM {};",
+                next_batch_table,
+                Self::get_stream_table_name(table_name)
             );
-
             client.exec(&conn, query)?;
         }
 
-        let result = client.fetch(&conn, format!("SELECT * FROM {};", temp_table_name))?;
-        if let Some((schema, iterator)) = result {
-            let mut truncated_schema = schema.clone();
-            truncated_schema.truncate(schema.len() - 3);
-
-            let columns_length = schema.len();
-            let used_columns_for_schema = columns_length - 3;
-            let action_idx = used_columns_for_schema;
-
-            for (idx, row) in iterator.enumerate() {
-                let ingestion_message =
-                    Self::get_ingestion_message(row, action_idx, used_columns_for_schema)?;
-                ingestor
-                    .write()
-                    .handle_message(((1, idx as u64), ingestion_message))
-                    .map_err(ConnectorError::IngestorError)?;
+        match Self::forward_batch(client, &conn, table_name, next_batch, None, ingestor)? {
+            Some(last_offset) => {
+                if current_batch > 0 {
+                    Self::drop_batch_table(client, &conn, table_name, current_batch)?;
+                }
+                Ok(Some(last_offset))
+            }
+            None => {
+                Self::drop_batch_table(client, &conn, table_name, next_batch)?;
+                Ok(offset)
             }
         }
-
-        let query = format!("DROP TABLE {};", temp_table_name);
-
-        client
-            .exec(&conn, query)
-            .map_err(ConnectorError::SnowflakeError)
-            .map(|_| ())
     }
 }
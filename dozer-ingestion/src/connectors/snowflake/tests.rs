@@ -1,8 +1,10 @@
+use crate::connectors::snowflake::connector::SnowflakeConnector;
 use crate::connectors::snowflake::stream_consumer::StreamConsumer;
 use crate::connectors::snowflake::test_utils::{get_client, remove_streams};
-use crate::connectors::{get_connector, TableInfo};
+use crate::connectors::{get_connector, Connector, TableInfo};
+use crate::errors::{ConnectorError, SnowflakeError};
 use crate::ingestion::{IngestionConfig, Ingestor};
-use dozer_types::ingestion_types::IngestionOperation;
+use dozer_types::ingestion_types::{IngestionOperation, SnowflakeConfig};
 use dozer_types::models::app_config::Config;
 
 use dozer_types::serde_yaml;
@@ -34,6 +36,7 @@ fn connector_disabled_test_e2e_connect_snowflake_and_read_from_stream() {
             name: source.table_name,
             id: 0,
             columns: None,
+            primary_key_columns: None,
         }];
 
         let mut connector = get_connector(connection).unwrap();
@@ -49,6 +52,8 @@ fn connector_disabled_test_e2e_connect_snowflake_and_read_from_stream() {
             None => {}
             Some((_, ingestion_operation)) => match ingestion_operation {
                 IngestionOperation::OperationEvent(_) => {}
+                IngestionOperation::Heartbeat() => {}
+                IngestionOperation::SchemaChange { .. } => {}
             },
         }
     }
@@ -93,14 +98,14 @@ fn connector_disabled_test_e2e_connect_snowflake_schema_changes_test() {
 
     // Create new stream
     let mut consumer = StreamConsumer::new();
-    consumer
-        .consume_stream(&client, &table_name, &ingestor)
+    let offset = consumer
+        .consume_stream(&client, &table_name, &ingestor, None)
         .unwrap();
 
     // Insert single record
     client.execute_query(&conn, &format!("INSERT INTO {} (N_NATIONKEY, N_COMMENT, N_REGIONKEY, N_NAME) VALUES (1, 'TEST Country 1', 0, 'country name 1');", table_name)).unwrap();
-    consumer
-        .consume_stream(&client, &table_name, &ingestor)
+    let offset = consumer
+        .consume_stream(&client, &table_name, &ingestor, offset)
         .unwrap();
     assert!(matches!(
         iterator.write().next().unwrap().1,
@@ -117,7 +122,7 @@ fn connector_disabled_test_e2e_connect_snowflake_schema_changes_test() {
     client.execute_query(&conn, &format!("INSERT INTO {} (N_NATIONKEY, N_COMMENT, N_REGIONKEY, N_NAME, TEST_COLUMN) VALUES (2, 'TEST Country 2', 0, 'country name 2', null);", table_name)).unwrap();
 
     consumer
-        .consume_stream(&client, &table_name, &ingestor)
+        .consume_stream(&client, &table_name, &ingestor, offset)
         .unwrap();
     assert!(matches!(
         iterator.write().next().unwrap().1,
@@ -175,6 +180,7 @@ fn connector_disabled_test_e2e_connect_snowflake_get_schemas_test() {
             name: table_name.to_string(),
             id: 0,
             columns: None,
+            primary_key_columns: None,
         }]))
         .unwrap();
 
@@ -202,3 +208,121 @@ fn connector_disabled_test_e2e_connect_snowflake_get_schemas_test() {
         .execute_query(&conn, &format!("DROP TABLE {};", table_name))
         .unwrap();
 }
+
+#[ignore]
+#[test]
+// fn connector_e2e_connect_snowflake_get_tables_test() {
+fn connector_disabled_test_e2e_connect_snowflake_get_tables_test() {
+    let config = serde_yaml::from_str::<Config>(load_config("test.snowflake.yaml")).unwrap();
+    let connection = config.connections.get(0).unwrap().clone();
+    let client = get_client(&connection);
+    let connector = get_connector(connection).unwrap();
+
+    connector.test_connection().unwrap();
+
+    let env = create_environment_v3().map_err(|e| e.unwrap()).unwrap();
+    let conn = env
+        .connect_with_connection_string(&client.get_conn_string())
+        .unwrap();
+
+    let mut rng = rand::thread_rng();
+    let table_name = format!("GET_TABLES_TEST_{}", rng.gen::<u32>());
+
+    client
+        .execute_query(&conn, &format!("CREATE TABLE {} (id integer);", table_name))
+        .unwrap();
+
+    let tables = connector.get_tables().unwrap();
+    assert!(tables.iter().any(|table| table.name == table_name));
+
+    client
+        .execute_query(&conn, &format!("DROP TABLE {};", table_name))
+        .unwrap();
+}
+
+#[test]
+fn test_snowflake_validate_poll_interval() {
+    let connector = |poll_interval_seconds| {
+        SnowflakeConnector::new(
+            4,
+            SnowflakeConfig {
+                poll_interval_seconds,
+                ..SnowflakeConfig::default()
+            },
+        )
+    };
+
+    assert!(connector(None).validate(None).is_ok());
+    assert!(connector(Some(1)).validate(None).is_ok());
+    assert!(matches!(
+        connector(Some(0)).validate(None),
+        Err(ConnectorError::SnowflakeError(
+            SnowflakeError::InvalidPollInterval
+        ))
+    ));
+}
+
+#[ignore]
+#[test]
+// fn connector_e2e_connect_snowflake_resume_stream_test() {
+fn connector_disabled_test_e2e_connect_snowflake_resume_stream_test() {
+    let config = serde_yaml::from_str::<Config>(load_config("test.snowflake.yaml")).unwrap();
+    let connection = config.connections.get(0).unwrap().clone();
+    let client = get_client(&connection);
+
+    let (ingestor, iterator) = Ingestor::initialize_channel(IngestionConfig::default());
+
+    let mut rng = rand::thread_rng();
+    let table_name = format!("resume_stream_test_{}", rng.gen::<u32>());
+    let stream_name = StreamConsumer::get_stream_table_name(&table_name);
+
+    let env = create_environment_v3().map_err(|e| e.unwrap()).unwrap();
+    let conn = env
+        .connect_with_connection_string(&client.get_conn_string())
+        .unwrap();
+
+    client
+        .execute_query(
+            &conn,
+            &format!(
+                "CREATE TABLE {} LIKE SNOWFLAKE_SAMPLE_DATA.TPCH_SF1.NATION;",
+                table_name
+            ),
+        )
+        .unwrap();
+    client
+        .execute_query(
+            &conn,
+            &format!("CREATE STREAM {} ON TABLE {}", stream_name, table_name),
+        )
+        .unwrap();
+    client.execute_query(&conn, &format!("INSERT INTO {} (N_NATIONKEY, N_COMMENT, N_REGIONKEY, N_NAME) VALUES (1, 'TEST Country 1', 0, 'country name 1'), (2, 'TEST Country 2', 0, 'country name 2');", table_name)).unwrap();
+
+    let mut consumer = StreamConsumer::new();
+    let offset = consumer
+        .consume_stream(&client, &table_name, &ingestor, None)
+        .unwrap();
+    assert_eq!(offset, Some((1, 1)));
+    assert_eq!(iterator.write().next().unwrap().0, (1, 0));
+    assert_eq!(iterator.write().next().unwrap().0, (1, 1));
+
+    // Restarting from a checkpoint in the middle of the batch forwards the rest of it again
+    let offset = consumer
+        .consume_stream(&client, &table_name, &ingestor, Some((1, 0)))
+        .unwrap();
+    assert_eq!(offset, Some((1, 1)));
+    assert_eq!(iterator.write().next().unwrap().0, (1, 1));
+
+    client
+        .execute_query(
+            &conn,
+            &format!(
+                "DROP TABLE {};",
+                StreamConsumer::get_stream_batch_table_name(&table_name, 1)
+            ),
+        )
+        .unwrap();
+    client
+        .execute_query(&conn, &format!("DROP TABLE {};", table_name))
+        .unwrap();
+}
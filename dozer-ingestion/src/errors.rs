@@ -4,6 +4,7 @@ use dozer_types::errors::internal::BoxedError;
 use dozer_types::errors::types::{SerializationError, TypeError};
 use dozer_types::ingestion_types::IngestorError;
 use dozer_types::thiserror::Error;
+use dozer_types::types::SchemaIdentifier;
 use dozer_types::{bincode, serde_json};
 use dozer_types::{rust_decimal, thiserror};
 
@@ -14,6 +15,7 @@ use std::str::Utf8Error;
 
 #[cfg(feature = "snowflake")]
 use odbc::DiagnosticRecord;
+use rdkafka::error::KafkaError;
 use schema_registry_converter::error::SRCError;
 
 #[derive(Error, Debug)]
@@ -66,6 +68,19 @@ pub enum ConnectorError {
 
     #[error("Received empty message in connector")]
     EmptyMessage,
+
+    #[error("No schema registered for identifier {0:?}")]
+    SchemaNotRegistered(Option<SchemaIdentifier>),
+
+    #[error("Record doesn't match the schema of table {0}: {1}")]
+    RecordSchemaMismatch(String, String),
+
+    #[error("Primary key column {0} not found in table {1}")]
+    PrimaryKeyColumnNotFound(String, String),
+
+    #[cfg(any(test, feature = "test-utils"))]
+    #[error(transparent)]
+    MockError(#[from] MockError),
 }
 impl ConnectorError {
     pub fn map_serialization_error(e: serde_json::Error) -> ConnectorError {
@@ -153,6 +168,9 @@ pub enum PostgresConnectorError {
     #[error("LSN not returned from replication slot creation query")]
     LsnNotReturnedFromReplicationSlot,
 
+    #[error("Unchanged TOASTed value of column {0} not available, use REPLICA IDENTITY FULL")]
+    UnchangedToastValueNotAvailable(String),
+
     #[error("Table name \"{0}\" not valid")]
     TableNameNotValid(String),
 
@@ -161,6 +179,9 @@ pub enum PostgresConnectorError {
 
     #[error("Relation not found in replication: {0}")]
     RelationNotFound(#[source] std::io::Error),
+
+    #[error("Replication settings could not be validated. Error: {0}")]
+    ReplicationValidationError(String),
 }
 
 #[derive(Error, Debug, Eq, PartialEq)]
@@ -196,12 +217,21 @@ pub enum SnowflakeError {
     #[error("Snowflake connection error")]
     ConnectionError(#[from] Box<DiagnosticRecord>),
 
+    #[error("Unable to create the ODBC environment")]
+    EnvironmentError(#[source] Option<Box<DiagnosticRecord>>),
+
     #[cfg(feature = "snowflake")]
     #[error(transparent)]
     SnowflakeSchemaError(#[from] SnowflakeSchemaError),
 
     #[error(transparent)]
     SnowflakeStreamError(#[from] SnowflakeStreamError),
+
+    #[error("Stream poll interval must be greater than zero")]
+    InvalidPollInterval,
+
+    #[error("A checkpoint can only resume a single table, got {0} tables")]
+    CheckpointOfSeveralTables(usize),
 }
 
 #[cfg(feature = "snowflake")]
@@ -232,7 +262,7 @@ pub enum DebeziumError {
     DebeziumSchemaError(#[from] DebeziumSchemaError),
 
     #[error("Connection error")]
-    DebeziumConnectionError(#[source] kafka::Error),
+    DebeziumConnectionError(#[source] KafkaError),
 
     #[error("JSON decode error")]
     JsonDecodeError(#[source] serde_json::Error),
@@ -248,18 +278,18 @@ pub enum DebeziumError {
 
     #[error("Topic not defined")]
     TopicNotDefined,
+
+    #[error("Invalid security configuration: {0}")]
+    InvalidSecurityConfig(String),
 }
 
 #[derive(Error, Debug)]
 pub enum DebeziumStreamError {
     #[error("Consume commit error")]
-    ConsumeCommitError(#[source] kafka::Error),
-
-    #[error("Message consume error")]
-    MessageConsumeError(#[source] kafka::Error),
+    ConsumeCommitError(#[source] KafkaError),
 
     #[error("Polling error")]
-    PollingError(#[source] kafka::Error),
+    PollingError(#[source] KafkaError),
 }
 
 #[derive(Error, Debug, PartialEq)]
@@ -285,3 +315,10 @@ pub enum DebeziumSchemaError {
     #[error("Decimal convert error")]
     DecimalConvertError(#[source] rust_decimal::Error),
 }
+
+#[cfg(any(test, feature = "test-utils"))]
+#[derive(Error, Debug)]
+pub enum MockError {
+    #[error("Mock connector failed before message {0}")]
+    FailureInjected(usize),
+}
@@ -1,25 +1,46 @@
-use crossbeam::channel::{unbounded, Receiver};
+use crossbeam::channel::{bounded, Receiver, SendTimeoutError};
 use dozer_types::ingestion_types::{
     IngestionMessage, IngestionOperation, IngestorError, IngestorForwarder,
 };
 use dozer_types::log::warn;
 use dozer_types::parking_lot::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
 use super::IngestionConfig;
 
+/// How often a forwarder waiting for room in a full channel checks if it was stopped.
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Forwards operations on a channel to an [`IngestionIterator`].
+///
+/// When the channel is bounded and full, `forward` blocks until the iterator catches up. This is
+/// how connectors are slowed down to the pace of the pipeline: their read loop calls `forward`, so
+/// it stops reading from the source while the pipeline is saturated, and the number of operations
+/// held in memory stays bounded by the channel's capacity. Blocking is preferred over failing with
+/// a "would block" error so that no connector has to implement its own retry loop.
+///
+/// Connectors call `forward` holding the lock of their [`Ingestor`], so the wait can't be
+/// interrupted through it: setting `stopped` makes a blocked `forward` fail with
+/// [`IngestorError::Stopped`] instead, which ends the connector's read loop.
 #[derive(Debug)]
 pub struct ChannelForwarder {
     pub sender: crossbeam::channel::Sender<((u64, u64), IngestionOperation)>,
+    pub stopped: Arc<AtomicBool>,
 }
 
 impl IngestorForwarder for ChannelForwarder {
-    fn forward(&self, event: ((u64, u64), IngestionOperation)) -> Result<(), IngestorError> {
-        let send_res = self.sender.send(event);
-        match send_res {
-            Ok(_) => Ok(()),
-            Err(e) => Err(IngestorError::ChannelError(Box::new(e))),
+    fn forward(&self, mut event: ((u64, u64), IngestionOperation)) -> Result<(), IngestorError> {
+        loop {
+            if self.stopped.load(Ordering::SeqCst) {
+                return Err(IngestorError::Stopped);
+            }
+            match self.sender.send_timeout(event, STOP_POLL_INTERVAL) {
+                Ok(_) => return Ok(()),
+                Err(SendTimeoutError::Timeout(e)) => event = e,
+                Err(e) => return Err(IngestorError::ChannelError(Box::new(e))),
+            }
         }
     }
 }
@@ -57,16 +78,20 @@ impl IngestionIterator {
 #[derive(Debug)]
 pub struct Ingestor {
     pub sender: Arc<Box<dyn IngestorForwarder>>,
+    stopped: Arc<AtomicBool>,
 }
 
 impl Ingestor {
     pub fn initialize_channel(
         config: IngestionConfig,
     ) -> (Arc<RwLock<Ingestor>>, Arc<RwLock<IngestionIterator>>) {
-        let (tx, rx) = unbounded::<((u64, u64), IngestionOperation)>();
-        let sender: Arc<Box<dyn IngestorForwarder>> =
-            Arc::new(Box::new(ChannelForwarder { sender: tx }));
-        let ingestor = Arc::new(RwLock::new(Self::new(config, sender)));
+        let (tx, rx) = bounded::<((u64, u64), IngestionOperation)>(config.forwarder_channel_cap);
+        let stopped = Arc::new(AtomicBool::new(false));
+        let sender: Arc<Box<dyn IngestorForwarder>> = Arc::new(Box::new(ChannelForwarder {
+            sender: tx,
+            stopped: stopped.clone(),
+        }));
+        let ingestor = Arc::new(RwLock::new(Self { sender, stopped }));
 
         let iterator = Arc::new(RwLock::new(IngestionIterator { rx }));
         (ingestor, iterator)
@@ -75,7 +100,17 @@ impl Ingestor {
         _config: IngestionConfig,
         sender: Arc<Box<dyn IngestorForwarder + 'static>>,
     ) -> Self {
-        Self { sender }
+        Self {
+            sender,
+            stopped: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// The flag stopping the forwarder created by [`initialize_channel`](Self::initialize_channel).
+    /// It is meant to be taken before the connector starts, as a connector blocked on a full
+    /// channel holds the ingestor's lock.
+    pub fn stopped(&self) -> Arc<AtomicBool> {
+        self.stopped.clone()
     }
 
     pub fn handle_message(
@@ -89,6 +124,16 @@ impl Ingestor {
             }
             IngestionMessage::Commit(_event) => {}
             IngestionMessage::Begin() => {}
+            IngestionMessage::Heartbeat() => {
+                self.sender
+                    .forward(((lsn, seq_no), IngestionOperation::Heartbeat()))?;
+            }
+            IngestionMessage::SchemaChange { table, schema } => {
+                self.sender.forward((
+                    (lsn, seq_no),
+                    IngestionOperation::SchemaChange { table, schema },
+                ))?;
+            }
         }
         Ok(())
     }
@@ -98,18 +143,23 @@ impl Ingestor {
 mod tests {
     use crate::ingestion::IngestionConfig;
 
-    use super::IngestionMessage::{Begin, Commit, OperationEvent};
-    use super::{ChannelForwarder, IngestionOperation, Ingestor, IngestorForwarder};
+    use super::IngestionMessage::{Begin, Commit, Heartbeat, OperationEvent, SchemaChange};
+    use super::{ChannelForwarder, IngestionOperation, Ingestor, IngestorError, IngestorForwarder};
     use crossbeam::channel::unbounded;
-    use dozer_types::types::{Operation, Record};
+    use dozer_types::types::{Operation, Record, Schema};
+    use std::sync::atomic::{AtomicU64, Ordering};
     use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
 
     #[tokio::test]
     async fn test_message_handle() {
         let config = IngestionConfig::default();
         let (tx, rx) = unbounded::<((u64, u64), IngestionOperation)>();
-        let forwarder: Arc<Box<dyn IngestorForwarder>> =
-            Arc::new(Box::new(ChannelForwarder { sender: tx }));
+        let forwarder: Arc<Box<dyn IngestorForwarder>> = Arc::new(Box::new(ChannelForwarder {
+            sender: tx,
+            stopped: Arc::default(),
+        }));
         let mut ingestor = Ingestor::new(config, forwarder);
 
         // Expected seq no - 2
@@ -156,4 +206,107 @@ mod tests {
             assert_eq!(x, msg.1);
         }
     }
+
+    #[test]
+    fn test_heartbeat_handle() {
+        let config = IngestionConfig::default();
+        let (tx, rx) = unbounded::<((u64, u64), IngestionOperation)>();
+        let forwarder: Arc<Box<dyn IngestorForwarder>> = Arc::new(Box::new(ChannelForwarder {
+            sender: tx,
+            stopped: Arc::default(),
+        }));
+        let mut ingestor = Ingestor::new(config, forwarder);
+
+        ingestor.handle_message(((5, 0), Heartbeat())).unwrap();
+
+        assert_eq!(
+            rx.recv().unwrap(),
+            ((5, 0), IngestionOperation::Heartbeat())
+        );
+    }
+
+    #[test]
+    fn test_schema_change_handle() {
+        let config = IngestionConfig::default();
+        let (tx, rx) = unbounded::<((u64, u64), IngestionOperation)>();
+        let forwarder: Arc<Box<dyn IngestorForwarder>> = Arc::new(Box::new(ChannelForwarder {
+            sender: tx,
+            stopped: Arc::default(),
+        }));
+        let mut ingestor = Ingestor::new(config, forwarder);
+
+        ingestor
+            .handle_message((
+                (5, 1),
+                SchemaChange {
+                    table: "users".to_string(),
+                    schema: Schema::empty(),
+                },
+            ))
+            .unwrap();
+
+        assert_eq!(
+            rx.recv().unwrap(),
+            (
+                (5, 1),
+                IngestionOperation::SchemaChange {
+                    table: "users".to_string(),
+                    schema: Schema::empty(),
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn test_forward_blocks_when_channel_is_full() {
+        let (ingestor, iterator) = Ingestor::initialize_channel(IngestionConfig::new(10));
+
+        let forwarded = Arc::new(AtomicU64::new(0));
+        let producer_forwarded = forwarded.clone();
+        let producer = thread::spawn(move || {
+            for seq_no in 0..100 {
+                ingestor
+                    .write()
+                    .handle_message(((1, seq_no), Heartbeat()))
+                    .unwrap();
+                producer_forwarded.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        // Without a consumer, the producer stalls once the channel is full.
+        thread::sleep(Duration::from_millis(200));
+        assert_eq!(forwarded.load(Ordering::SeqCst), 10);
+        assert_eq!(iterator.read().rx.len(), 10);
+
+        // It resumes as the consumer catches up.
+        let mut iterator = iterator.write();
+        for seq_no in 0..100 {
+            assert_eq!(
+                iterator.next(),
+                Some(((1, seq_no), IngestionOperation::Heartbeat()))
+            );
+        }
+        producer.join().unwrap();
+        assert_eq!(forwarded.load(Ordering::SeqCst), 100);
+    }
+
+    #[test]
+    fn test_stop_unblocks_forward() {
+        let (ingestor, _iterator) = Ingestor::initialize_channel(IngestionConfig::new(1));
+        let stopped = ingestor.read().stopped();
+
+        let producer = thread::spawn(move || {
+            let mut ingestor = ingestor.write();
+            ingestor.handle_message(((1, 0), Heartbeat())).unwrap();
+            ingestor.handle_message(((1, 1), Heartbeat()))
+        });
+
+        thread::sleep(Duration::from_millis(200));
+        assert!(!producer.is_finished());
+        stopped.store(true, Ordering::SeqCst);
+        assert!(matches!(
+            producer.join().unwrap(),
+            Err(IngestorError::Stopped)
+        ));
+    }
 }
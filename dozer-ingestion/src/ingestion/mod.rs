@@ -3,5 +3,23 @@ mod ingestor;
 pub use ingestor::ChannelForwarder;
 pub use ingestor::{IngestionIterator, Ingestor};
 
-#[derive(Default)]
-pub struct IngestionConfig {}
+pub struct IngestionConfig {
+    /// Number of operations the ingestion channel buffers before `forward` blocks the connector.
+    forwarder_channel_cap: usize,
+}
+
+impl IngestionConfig {
+    pub fn new(forwarder_channel_cap: usize) -> Self {
+        Self {
+            forwarder_channel_cap,
+        }
+    }
+}
+
+impl Default for IngestionConfig {
+    fn default() -> Self {
+        Self {
+            forwarder_channel_cap: 100_000,
+        }
+    }
+}
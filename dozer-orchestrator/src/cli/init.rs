@@ -70,6 +70,7 @@ fn sample_connection(connection_name: &str) -> Connection {
                 schema: "schema".to_owned(),
                 warehouse: "warehouse".to_owned(),
                 driver: Some("SnowflakeDSIIDriver".to_owned()),
+                poll_interval_seconds: None,
             };
             let connection: Connection = Connection {
                 name: "snowflake".to_owned(),
@@ -90,6 +91,8 @@ fn sample_connection(connection_name: &str) -> Connection {
                 filter: Some(eth_filter),
                 wss_url: "wss://link".to_owned(),
                 contracts: vec![],
+                max_fetch_depth: None,
+                min_block_range: None,
             };
             let connection: Connection = Connection {
                 name: "ethereum".to_owned(),
@@ -106,6 +109,7 @@ fn sample_connection(connection_name: &str) -> Connection {
                 host: "localhost".to_owned(),
                 port: 5432,
                 database: "users".to_owned(),
+                start_lsn: None,
             };
             let connection: Connection = Connection {
                 name: "postgres".to_owned(),
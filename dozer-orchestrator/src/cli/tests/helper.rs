@@ -158,6 +158,7 @@ pub fn test_connection() -> Connection {
             host: "localhost".to_owned(),
             port: 5432,
             database: "users".to_owned(),
+            start_lsn: None,
         })),
         db_type: dozer_types::models::connection::DBType::Postgres as i32,
         name: "users".to_owned(),
@@ -192,6 +193,7 @@ pub fn test_api_config() -> ApiConfig {
             port: 8080,
             host: "0.0.0.0".to_owned(),
             cors: true,
+            rate_limit: None,
         }),
         grpc: Some(ApiGrpc {
             port: 50051,
@@ -1,19 +1,28 @@
 use dozer_core::dag::channels::SourceChannelForwarder;
 use dozer_core::dag::errors::ExecutionError;
 use dozer_core::dag::errors::ExecutionError::ReplicationTypeNotFound;
-use dozer_core::dag::node::{OutputPortDef, OutputPortType, PortHandle, Source, SourceFactory};
+use dozer_core::dag::node::{
+    OutputPortDef, OutputPortType, PortHandle, Source, SourceFactory, SourceStopper,
+};
 use dozer_ingestion::connectors::{get_connector, TableInfo};
 use dozer_ingestion::errors::ConnectorError;
 use dozer_ingestion::ingestion::{IngestionIterator, Ingestor};
+use dozer_types::crossbeam::channel::RecvTimeoutError;
 use dozer_types::ingestion_types::IngestionOperation;
-use dozer_types::log::info;
+use dozer_types::log::{info, warn};
 use dozer_types::models::connection::Connection;
 use dozer_types::parking_lot::RwLock;
-use dozer_types::types::{Operation, ReplicationChangesTrackingType, Schema, SchemaIdentifier};
+use dozer_types::types::{
+    KeyMode, Operation, ReplicationChangesTrackingType, Schema, SchemaIdentifier,
+};
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread;
+use std::time::Duration;
+
+/// How often a source waiting for data checks if it was stopped.
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 #[derive(Debug)]
 pub struct ConnectorSourceFactory {
@@ -26,6 +35,8 @@ pub struct ConnectorSourceFactory {
     pub tables: Vec<TableInfo>,
     pub connection: Connection,
     pub running: Arc<AtomicBool>,
+    /// Stops the ingestor's forwarder, taken before the connector can hold the ingestor's lock.
+    ingestor_stopped: Arc<AtomicBool>,
 }
 
 fn map_replication_type_to_output_port_type(
@@ -36,13 +47,16 @@ fn map_replication_type_to_output_port_type(
             OutputPortType::StatefulWithPrimaryKeyLookup {
                 retr_old_records_for_deletes: false,
                 retr_old_records_for_updates: false,
+                key_mode: KeyMode::Position,
             }
         }
         ReplicationChangesTrackingType::OnlyPK => OutputPortType::StatefulWithPrimaryKeyLookup {
             retr_old_records_for_deletes: true,
             retr_old_records_for_updates: true,
+            key_mode: KeyMode::Position,
         },
         ReplicationChangesTrackingType::Nothing => OutputPortType::AutogenRowKeyLookup,
+        ReplicationChangesTrackingType::AppendOnly => OutputPortType::Stateless,
     }
 }
 
@@ -57,6 +71,7 @@ impl ConnectorSourceFactory {
     ) -> Self {
         let (schema_map, schema_port_map, replication_changes_type_map) =
             Self::get_schema_map(connection.clone(), tables.clone(), ports.clone());
+        let ingestor_stopped = ingestor.read().stopped();
         Self {
             ingestor,
             iterator,
@@ -67,6 +82,7 @@ impl ConnectorSourceFactory {
             tables,
             connection,
             running,
+            ingestor_stopped,
         }
     }
 
@@ -144,15 +160,20 @@ impl SourceFactory for ConnectorSourceFactory {
 
     fn build(
         &self,
-        _output_schemas: HashMap<PortHandle, Schema>,
+        output_schemas: HashMap<PortHandle, Schema>,
     ) -> Result<Box<dyn Source>, ExecutionError> {
         Ok(Box::new(ConnectorSource {
             ingestor: self.ingestor.clone(),
             iterator: self.iterator.clone(),
             schema_port_map: self.schema_port_map.clone(),
+            output_schemas,
             tables: self.tables.clone(),
             connection: self.connection.clone(),
             running: self.running.clone(),
+            stopper: Arc::new(ConnectorSourceStopper {
+                stopped: AtomicBool::new(false),
+                ingestor_stopped: self.ingestor_stopped.clone(),
+            }),
         }))
     }
 }
@@ -162,9 +183,56 @@ pub struct ConnectorSource {
     ingestor: Arc<RwLock<Ingestor>>,
     iterator: Arc<RwLock<IngestionIterator>>,
     schema_port_map: HashMap<u32, u16>,
+    output_schemas: HashMap<PortHandle, Schema>,
     tables: Vec<TableInfo>,
     connection: Connection,
     running: Arc<AtomicBool>,
+    stopper: Arc<ConnectorSourceStopper>,
+}
+
+#[derive(Debug)]
+struct ConnectorSourceStopper {
+    stopped: AtomicBool,
+    /// Makes the connector fail with `IngestorError::Stopped` when it next forwards an operation,
+    /// or right away if it is blocked on a full channel.
+    ingestor_stopped: Arc<AtomicBool>,
+}
+
+impl SourceStopper for ConnectorSourceStopper {
+    fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        self.ingestor_stopped.store(true, Ordering::SeqCst);
+    }
+}
+
+impl ConnectorSource {
+    /// Checks the schema reported for `table` against the one the DAG was built with. The schemas
+    /// of the DAG are fixed once it is built, so a table whose records no longer fit it stops the
+    /// source. On restart, `DagExecutor` validates the new schemas against the stored ones and
+    /// resets the nodes whose schemas changed.
+    fn handle_schema_change(&self, table: &str, schema: &Schema) -> Result<(), ExecutionError> {
+        let schema_id = get_schema_id(schema.identifier.as_ref())?;
+        let port = self
+            .schema_port_map
+            .get(&schema_id)
+            .map_or(Err(ExecutionError::PortNotFound(schema_id.to_string())), Ok)?;
+        let current = self
+            .output_schemas
+            .get(port)
+            .map_or(Err(ExecutionError::PortNotFoundInSource(*port)), Ok)?;
+
+        if current.fields == schema.fields && current.primary_index == schema.primary_index {
+            info!("Source: Schema of {} is unchanged", table);
+            Ok(())
+        } else if is_compatible(current, schema) {
+            info!("Source: Schema of {} changed compatibly", table);
+            Ok(())
+        } else {
+            warn!("Source: Schema of {} changed", table);
+            schema.print().printstd();
+            Err(ExecutionError::SourceSchemaChanged(table.to_string()))
+        }
+    }
 }
 
 impl Source for ConnectorSource {
@@ -184,16 +252,26 @@ impl Source for ConnectorSource {
             Ok(())
         };
         let running = self.running.clone();
+        let stopped = self.stopper.ingestor_stopped.clone();
         let t = thread::spawn(move || {
             if let Err(e) = con_fn() {
-                if running.load(Ordering::Relaxed) {
+                if running.load(Ordering::Relaxed) && !stopped.load(Ordering::SeqCst) {
                     std::panic::panic_any(e);
                 }
             }
         });
 
         loop {
-            let msg = self.iterator.write().next();
+            if self.stopper.stopped.load(Ordering::SeqCst) {
+                // The connector quits when it next forwards an operation, its thread isn't joined
+                // as it may be waiting on the source
+                return Ok(());
+            }
+            let msg = match self.iterator.write().rx.recv_timeout(STOP_POLL_INTERVAL) {
+                Ok(msg) => Some(msg),
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => None,
+            };
             if let Some(msg) = msg {
                 match msg {
                     ((lsn, seq_no), IngestionOperation::OperationEvent(op)) => {
@@ -209,6 +287,12 @@ impl Source for ConnectorSource {
                             .map_or(Err(ExecutionError::PortNotFound(schema_id.to_string())), Ok)?;
                         fw.send(lsn, seq_no, op.operation.to_owned(), port.to_owned())?
                     }
+                    ((lsn, seq_no), IngestionOperation::Heartbeat()) => {
+                        fw.heartbeat(lsn, seq_no)?
+                    }
+                    (_, IngestionOperation::SchemaChange { table, schema }) => {
+                        self.handle_schema_change(&table, &schema)?
+                    }
                 }
             } else {
                 break;
@@ -219,6 +303,26 @@ impl Source for ConnectorSource {
 
         Ok(())
     }
+
+    fn stopper(&self) -> Option<Arc<dyn SourceStopper>> {
+        Some(self.stopper.clone())
+    }
+}
+
+/// If records of the `new` schema of a table still fit its `current` schema: the fields and primary
+/// key are the same, except that fields may no longer be nullable.
+fn is_compatible(current: &Schema, new: &Schema) -> bool {
+    current.primary_index == new.primary_index
+        && current.fields.len() == new.fields.len()
+        && current
+            .fields
+            .iter()
+            .zip(&new.fields)
+            .all(|(current, new)| {
+                current.name == new.name
+                    && current.typ == new.typ
+                    && (current.nullable || !new.nullable)
+            })
 }
 
 fn get_schema_id(op_schema_id: Option<&SchemaIdentifier>) -> Result<u32, ExecutionError> {
@@ -226,3 +330,65 @@ fn get_schema_id(op_schema_id: Option<&SchemaIdentifier>) -> Result<u32, Executi
         .map_or(Err(ExecutionError::SchemaNotInitialized), Ok)?
         .id)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::is_compatible;
+    use dozer_types::types::{FieldDefinition, FieldType, Schema};
+
+    fn get_schema(fields: &[(&str, FieldType, bool)]) -> Schema {
+        let mut schema = Schema::empty();
+        for (idx, (name, typ, nullable)) in fields.iter().enumerate() {
+            schema.field(
+                FieldDefinition::new(name.to_string(), *typ, *nullable),
+                idx == 0,
+            );
+        }
+        schema
+    }
+
+    #[test]
+    fn test_is_compatible() {
+        let current = get_schema(&[
+            ("id", FieldType::Int, false),
+            ("name", FieldType::String, true),
+        ]);
+
+        assert!(is_compatible(&current, &current));
+        // Values which were nullable can't be null anymore
+        assert!(is_compatible(
+            &current,
+            &get_schema(&[
+                ("id", FieldType::Int, false),
+                ("name", FieldType::String, false),
+            ])
+        ));
+
+        for new in [
+            get_schema(&[
+                ("id", FieldType::Int, true),
+                ("name", FieldType::String, true),
+            ]),
+            get_schema(&[
+                ("id", FieldType::Int, false),
+                ("name", FieldType::Text, true),
+            ]),
+            get_schema(&[
+                ("id", FieldType::Int, false),
+                ("title", FieldType::String, true),
+            ]),
+            get_schema(&[("id", FieldType::Int, false)]),
+            get_schema(&[
+                ("id", FieldType::Int, false),
+                ("name", FieldType::String, true),
+                ("price", FieldType::Int, true),
+            ]),
+        ] {
+            assert!(!is_compatible(&current, &new));
+        }
+
+        let mut new = current.clone();
+        new.primary_index = vec![0, 1];
+        assert!(!is_compatible(&current, &new));
+    }
+}
@@ -0,0 +1,19 @@
+use dozer_core::dag::errors::ExecutionError;
+use dozer_types::{
+    record_to_map,
+    types::{Record, Schema},
+};
+use serde_json::Value;
+
+/// Returns `record` as a JSON object keyed by the field names of `schema`.
+pub fn record_to_json(record: &Record, schema: &Schema) -> Result<Value, ExecutionError> {
+    Ok(Value::Object(
+        record_to_map(record, schema)?.into_iter().collect(),
+    ))
+}
+
+pub fn internal_err<T, E: std::error::Error + Send + Sync + 'static>(
+    result: Result<T, E>,
+) -> Result<T, ExecutionError> {
+    result.map_err(|e| ExecutionError::InternalError(Box::new(e)))
+}
@@ -0,0 +1,416 @@
+use crate::pipeline::helper::{internal_err, record_to_json};
+use dozer_core::{
+    dag::{
+        dag::DEFAULT_PORT_HANDLE,
+        epoch::Epoch,
+        errors::ExecutionError,
+        node::{PortHandle, Sink, SinkFactory},
+        record_store::RecordReader,
+    },
+    storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction},
+};
+use dozer_types::{
+    log::debug,
+    types::{Operation, Schema},
+};
+use serde_json::json;
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// Writes the operations it receives to a file as newline-delimited JSON.
+///
+/// The file is rotated when writing an operation would make it bigger than `max_file_size`: it is
+/// renamed to `<path>.<n>`, with `n` the first free index starting from 1, and a new file is
+/// started at `path`.
+///
+/// Operations are written on `commit`, after which the committed source positions are saved to
+/// `<path>.position`. When the pipeline is restarted from an earlier checkpoint, the operations of
+/// an epoch that ends at or before the saved positions were already written, and are skipped.
+#[derive(Debug)]
+pub struct JsonFileSinkFactory {
+    path: PathBuf,
+    max_file_size: u64,
+}
+
+impl JsonFileSinkFactory {
+    pub fn new(path: PathBuf, max_file_size: u64) -> Self {
+        Self {
+            path,
+            max_file_size,
+        }
+    }
+}
+
+impl SinkFactory for JsonFileSinkFactory {
+    fn get_input_ports(&self) -> Vec<PortHandle> {
+        vec![DEFAULT_PORT_HANDLE]
+    }
+
+    fn set_input_schema(
+        &self,
+        _input_schemas: &HashMap<PortHandle, Schema>,
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn build(
+        &self,
+        input_schemas: HashMap<PortHandle, Schema>,
+    ) -> Result<Box<dyn Sink>, ExecutionError> {
+        Ok(Box::new(JsonFileSink::new(
+            self.path.clone(),
+            self.max_file_size,
+            input_schemas,
+        )))
+    }
+
+    fn prepare(&self, _input_schemas: HashMap<PortHandle, Schema>) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct JsonFileSink {
+    path: PathBuf,
+    max_file_size: u64,
+    input_schemas: HashMap<PortHandle, Schema>,
+    writer: Option<BufWriter<File>>,
+    /// Size of the current file
+    file_size: u64,
+    /// Lines of the operations received since the last commit
+    pending: Vec<String>,
+    /// Last committed position of every source, by node handle
+    committed: HashMap<String, (u64, u64)>,
+}
+
+impl JsonFileSink {
+    pub fn new(
+        path: PathBuf,
+        max_file_size: u64,
+        input_schemas: HashMap<PortHandle, Schema>,
+    ) -> Self {
+        Self {
+            path,
+            max_file_size,
+            input_schemas,
+            writer: None,
+            file_size: 0,
+            pending: vec![],
+            committed: HashMap::new(),
+        }
+    }
+
+    /// Whether every source of `epoch` is at or before its saved position.
+    fn is_replayed(&self, epoch: &Epoch) -> bool {
+        !epoch.details.is_empty()
+            && epoch.details.iter().all(|(handle, position)| {
+                self.committed
+                    .get(&handle.to_string())
+                    .map_or(false, |committed| position <= committed)
+            })
+    }
+
+    fn save_position(&mut self, epoch: &Epoch) -> Result<(), ExecutionError> {
+        for (handle, position) in &epoch.details {
+            let committed = self
+                .committed
+                .entry(handle.to_string())
+                .or_insert(*position);
+            *committed = (*committed).max(*position);
+        }
+
+        // Written aside then renamed, so that a crash never leaves a partial file
+        let position_path = get_position_path(&self.path);
+        let mut tmp_path = position_path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        internal_err(fs::write(
+            &tmp_path,
+            internal_err(serde_json::to_vec(&self.committed))?,
+        ))?;
+        internal_err(fs::rename(&tmp_path, position_path))
+    }
+
+    fn open(&mut self) -> Result<&mut BufWriter<File>, ExecutionError> {
+        if self.writer.is_none() {
+            let file = internal_err(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path),
+            )?;
+            self.file_size = internal_err(file.metadata())?.len();
+            self.writer = Some(BufWriter::new(file));
+        }
+        Ok(self.writer.as_mut().unwrap())
+    }
+
+    fn flush(&mut self) -> Result<(), ExecutionError> {
+        if let Some(writer) = self.writer.as_mut() {
+            internal_err(writer.flush())?;
+            internal_err(writer.get_ref().sync_all())?;
+        }
+        Ok(())
+    }
+
+    /// Moves the current file aside, the next write starts a new one.
+    fn rotate(&mut self) -> Result<(), ExecutionError> {
+        self.flush()?;
+        self.writer = None;
+        internal_err(fs::rename(&self.path, get_rotated_path(&self.path)))?;
+        self.file_size = 0;
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: String) -> Result<(), ExecutionError> {
+        self.open()?;
+        let len = line.len() as u64 + 1;
+        if self.file_size > 0 && self.file_size + len > self.max_file_size {
+            self.rotate()?;
+        }
+
+        let writer = self.open()?;
+        internal_err(writeln!(writer, "{}", line))?;
+        self.file_size += len;
+        Ok(())
+    }
+}
+
+impl Sink for JsonFileSink {
+    fn init(&mut self, _env: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+        debug!("SINK: Initialising JsonFileSink: {:?}", self.path);
+        let position_path = get_position_path(&self.path);
+        if position_path.exists() {
+            let bytes = internal_err(fs::read(position_path))?;
+            self.committed = internal_err(serde_json::from_slice(&bytes))?;
+        }
+        Ok(())
+    }
+
+    fn process(
+        &mut self,
+        from_port: PortHandle,
+        op: Operation,
+        _state: &SharedTransaction,
+        _reader: &HashMap<PortHandle, RecordReader>,
+    ) -> Result<(), ExecutionError> {
+        let schema = self
+            .input_schemas
+            .get(&from_port)
+            .ok_or(ExecutionError::InvalidPortHandle(from_port))?;
+
+        let value = match op {
+            Operation::Insert { new } => json!({
+                "op": "insert",
+                "new": record_to_json(&new, schema)?,
+            }),
+            Operation::Delete { old } => json!({
+                "op": "delete",
+                "old": record_to_json(&old, schema)?,
+            }),
+            Operation::Update { old, new } => json!({
+                "op": "update",
+                "old": record_to_json(&old, schema)?,
+                "new": record_to_json(&new, schema)?,
+            }),
+        };
+        self.pending
+            .push(internal_err(serde_json::to_string(&value))?);
+        Ok(())
+    }
+
+    fn commit(&mut self, epoch: &Epoch, _tx: &SharedTransaction) -> Result<(), ExecutionError> {
+        let lines = std::mem::take(&mut self.pending);
+        if self.is_replayed(epoch) {
+            debug!(
+                "SINK: Skipping {} replayed operations in {:?}",
+                lines.len(),
+                self.path
+            );
+            return Ok(());
+        }
+
+        for line in lines {
+            self.write_line(line)?;
+        }
+        self.flush()?;
+        self.save_position(epoch)
+    }
+}
+
+fn get_position_path(path: &Path) -> PathBuf {
+    let mut position_path = path.as_os_str().to_owned();
+    position_path.push(".position");
+    PathBuf::from(position_path)
+}
+
+fn get_rotated_path(path: &Path) -> PathBuf {
+    let mut index = 1;
+    loop {
+        let mut rotated = path.as_os_str().to_owned();
+        rotated.push(format!(".{}", index));
+        let rotated = PathBuf::from(rotated);
+        if !rotated.exists() {
+            return rotated;
+        }
+        index += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dozer_core::dag::node::NodeHandle;
+    use dozer_types::types::{Field, FieldDefinition, FieldType, Record};
+    use serde_json::Value;
+    use tempdir::TempDir;
+
+    fn get_schema() -> Schema {
+        Schema::empty()
+            .field(
+                FieldDefinition::new("id".to_string(), FieldType::Int, false),
+                true,
+            )
+            .field(
+                FieldDefinition::new("name".to_string(), FieldType::String, false),
+                false,
+            )
+            .clone()
+    }
+
+    fn record(id: i64, name: &str) -> Record {
+        Record::new(
+            None,
+            vec![Field::Int(id), Field::String(name.to_string())],
+            None,
+        )
+    }
+
+    fn read_lines(path: &Path) -> Vec<Value> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_json_file_sink() {
+        let tmp_dir = TempDir::new("json_sink").unwrap();
+        let path = tmp_dir.path().join("films.json");
+        let env = LmdbEnvironmentManager::create(tmp_dir.path(), "test").unwrap();
+        let txn = env.create_txn().unwrap();
+
+        let mut sink = JsonFileSinkFactory::new(path.clone(), 1024 * 1024)
+            .build(HashMap::from([(DEFAULT_PORT_HANDLE, get_schema())]))
+            .unwrap();
+        let ops = vec![
+            Operation::Insert {
+                new: record(1, "a"),
+            },
+            Operation::Update {
+                old: record(1, "a"),
+                new: record(1, "b"),
+            },
+            Operation::Delete {
+                old: record(1, "b"),
+            },
+        ];
+        for op in ops {
+            sink.process(DEFAULT_PORT_HANDLE, op, &txn, &HashMap::new())
+                .unwrap();
+        }
+        sink.commit(&Epoch::new(0, HashMap::new()), &txn).unwrap();
+
+        assert_eq!(
+            read_lines(&path),
+            vec![
+                json!({"op": "insert", "new": {"id": 1, "name": "a"}}),
+                json!({
+                    "op": "update",
+                    "old": {"id": 1, "name": "a"},
+                    "new": {"id": 1, "name": "b"}
+                }),
+                json!({"op": "delete", "old": {"id": 1, "name": "b"}}),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json_file_sink_rotation() {
+        let tmp_dir = TempDir::new("json_sink").unwrap();
+        let path = tmp_dir.path().join("films.json");
+        let env = LmdbEnvironmentManager::create(tmp_dir.path(), "test").unwrap();
+        let txn = env.create_txn().unwrap();
+
+        // Room for a single line per file
+        let mut sink = JsonFileSinkFactory::new(path.clone(), 50)
+            .build(HashMap::from([(DEFAULT_PORT_HANDLE, get_schema())]))
+            .unwrap();
+        for id in 1..=3 {
+            sink.process(
+                DEFAULT_PORT_HANDLE,
+                Operation::Insert {
+                    new: record(id, "a"),
+                },
+                &txn,
+                &HashMap::new(),
+            )
+            .unwrap();
+        }
+        sink.commit(&Epoch::new(0, HashMap::new()), &txn).unwrap();
+
+        let line = |id: i64| json!({"op": "insert", "new": {"id": id, "name": "a"}});
+        assert_eq!(
+            read_lines(&tmp_dir.path().join("films.json.1")),
+            vec![line(1)]
+        );
+        assert_eq!(
+            read_lines(&tmp_dir.path().join("films.json.2")),
+            vec![line(2)]
+        );
+        assert_eq!(read_lines(&path), vec![line(3)]);
+    }
+
+    #[test]
+    fn test_json_file_sink_skips_replayed_operations() {
+        let tmp_dir = TempDir::new("json_sink").unwrap();
+        let path = tmp_dir.path().join("films.json");
+        let mut env = LmdbEnvironmentManager::create(tmp_dir.path(), "test").unwrap();
+        let txn = env.create_txn().unwrap();
+
+        let source = NodeHandle::new(None, "source".to_string());
+        let epoch = |txid: u64| Epoch::from(0, source.clone(), txid, 0);
+        let insert = |id: i64| Operation::Insert {
+            new: record(id, "a"),
+        };
+        let factory = JsonFileSinkFactory::new(path.clone(), 1024 * 1024);
+
+        let mut sink = factory
+            .build(HashMap::from([(DEFAULT_PORT_HANDLE, get_schema())]))
+            .unwrap();
+        sink.init(&mut env).unwrap();
+        for id in 1..=2 {
+            sink.process(DEFAULT_PORT_HANDLE, insert(id), &txn, &HashMap::new())
+                .unwrap();
+            sink.commit(&epoch(id as u64), &txn).unwrap();
+        }
+
+        // Restarted from the checkpoint of the first operation
+        let mut sink = factory
+            .build(HashMap::from([(DEFAULT_PORT_HANDLE, get_schema())]))
+            .unwrap();
+        sink.init(&mut env).unwrap();
+        for id in 2..=3 {
+            sink.process(DEFAULT_PORT_HANDLE, insert(id), &txn, &HashMap::new())
+                .unwrap();
+            sink.commit(&epoch(id as u64), &txn).unwrap();
+        }
+
+        let line = |id: i64| json!({"op": "insert", "new": {"id": id, "name": "a"}});
+        assert_eq!(read_lines(&path), vec![line(1), line(2), line(3)]);
+    }
+}
@@ -0,0 +1,352 @@
+use crate::pipeline::helper::{internal_err, record_to_json};
+use dozer_core::{
+    dag::{
+        dag::DEFAULT_PORT_HANDLE,
+        epoch::Epoch,
+        errors::ExecutionError,
+        node::{PortHandle, Sink, SinkFactory},
+        record_store::RecordReader,
+    },
+    storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction},
+};
+use dozer_types::{
+    ingestion_types::KafkaConfig,
+    log::{debug, warn},
+    record_to_map,
+    types::{Operation, Record, Schema},
+};
+use kafka::producer::{Producer, Record as KafkaRecord, RequiredAcks};
+use serde_json::{json, Map, Value};
+use std::{collections::HashMap, fmt, time::Duration};
+
+const ACK_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_SEND_ATTEMPTS: u32 = 3;
+
+/// Publishes the operations it receives to a Kafka topic as Debezium-style change events.
+///
+/// Each message value is an envelope `{"before": .., "after": .., "op": ..}`, where `op` is `c`,
+/// `u` or `d`, and its key holds the primary key fields of the record. Like Debezium, an update
+/// that changes the primary key is published as a delete of the old key followed by a create of
+/// the new one, so that compacted topics don't keep the old key alive.
+///
+/// Messages are buffered and sent to the broker in one batch on `commit`. A failed batch is
+/// retried as a whole, so delivery is at-least-once: messages the broker had already accepted
+/// before a retry, or before the pipeline is restarted from its last checkpoint, are published
+/// again.
+#[derive(Debug)]
+pub struct KafkaSinkFactory {
+    config: KafkaConfig,
+    topic: String,
+}
+
+impl KafkaSinkFactory {
+    pub fn new(config: KafkaConfig, topic: String) -> Self {
+        Self { config, topic }
+    }
+}
+
+impl SinkFactory for KafkaSinkFactory {
+    fn get_input_ports(&self) -> Vec<PortHandle> {
+        vec![DEFAULT_PORT_HANDLE]
+    }
+
+    fn set_input_schema(
+        &self,
+        _input_schemas: &HashMap<PortHandle, Schema>,
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn build(
+        &self,
+        input_schemas: HashMap<PortHandle, Schema>,
+    ) -> Result<Box<dyn Sink>, ExecutionError> {
+        let producer = internal_err(
+            Producer::from_hosts(vec![self.config.broker.clone()])
+                .with_ack_timeout(ACK_TIMEOUT)
+                .with_required_acks(RequiredAcks::All)
+                .create(),
+        )?;
+
+        Ok(Box::new(KafkaSink {
+            producer,
+            topic: self.topic.clone(),
+            input_schemas,
+            buffer: vec![],
+        }))
+    }
+
+    fn prepare(&self, _input_schemas: HashMap<PortHandle, Schema>) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+}
+
+pub struct KafkaSink {
+    producer: Producer,
+    topic: String,
+    input_schemas: HashMap<PortHandle, Schema>,
+    /// Serialized `(key, value)` pairs waiting for the next commit
+    buffer: Vec<(String, String)>,
+}
+
+impl fmt::Debug for KafkaSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KafkaSink")
+            .field("topic", &self.topic)
+            .field("input_schemas", &self.input_schemas)
+            .field("buffered", &self.buffer.len())
+            .finish()
+    }
+}
+
+impl KafkaSink {
+    fn send_buffer(&mut self) -> Result<(), kafka::Error> {
+        let records: Vec<_> = self
+            .buffer
+            .iter()
+            .map(|(key, value)| {
+                KafkaRecord::from_key_value(&self.topic, key.as_str(), value.as_str())
+            })
+            .collect();
+
+        for confirm in self.producer.send_all(&records)? {
+            for partition_confirm in confirm.partition_confirms {
+                partition_confirm.offset.map_err(kafka::Error::Kafka)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Sink for KafkaSink {
+    fn init(&mut self, _env: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+        debug!("SINK: Initialising KafkaSink: {}", self.topic);
+        Ok(())
+    }
+
+    fn process(
+        &mut self,
+        from_port: PortHandle,
+        op: Operation,
+        _state: &SharedTransaction,
+        _reader: &HashMap<PortHandle, RecordReader>,
+    ) -> Result<(), ExecutionError> {
+        let schema = self
+            .input_schemas
+            .get(&from_port)
+            .ok_or(ExecutionError::InvalidPortHandle(from_port))?;
+
+        for (key, value) in operation_to_messages(&op, schema)? {
+            self.buffer.push((key.to_string(), value.to_string()));
+        }
+        Ok(())
+    }
+
+    fn commit(&mut self, _epoch: &Epoch, _tx: &SharedTransaction) -> Result<(), ExecutionError> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let mut attempt = 1;
+        loop {
+            match self.send_buffer() {
+                Ok(()) => break,
+                Err(e) if attempt < MAX_SEND_ATTEMPTS => {
+                    warn!(
+                        "SINK: Failed to publish to {} (attempt {}): {}",
+                        self.topic, attempt, e
+                    );
+                    attempt += 1;
+                }
+                Err(e) => return internal_err(Err(e)),
+            }
+        }
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+/// Returns the keys and the Debezium-style envelopes of the messages publishing `op`.
+fn operation_to_messages(
+    op: &Operation,
+    schema: &Schema,
+) -> Result<Vec<(Value, Value)>, ExecutionError> {
+    Ok(match op {
+        Operation::Insert { new } => vec![create_message(new, schema)?],
+        Operation::Update { old, new } => {
+            let old_key = record_key(old, schema)?;
+            let new_key = record_key(new, schema)?;
+            if old_key == new_key {
+                let envelope = json!({
+                    "before": record_to_json(old, schema)?,
+                    "after": record_to_json(new, schema)?,
+                    "op": "u",
+                });
+                vec![(new_key, envelope)]
+            } else {
+                vec![delete_message(old, schema)?, create_message(new, schema)?]
+            }
+        }
+        Operation::Delete { old } => vec![delete_message(old, schema)?],
+    })
+}
+
+fn create_message(new: &Record, schema: &Schema) -> Result<(Value, Value), ExecutionError> {
+    let envelope = json!({
+        "before": Value::Null,
+        "after": record_to_json(new, schema)?,
+        "op": "c",
+    });
+    Ok((record_key(new, schema)?, envelope))
+}
+
+fn delete_message(old: &Record, schema: &Schema) -> Result<(Value, Value), ExecutionError> {
+    let envelope = json!({
+        "before": record_to_json(old, schema)?,
+        "after": Value::Null,
+        "op": "d",
+    });
+    Ok((record_key(old, schema)?, envelope))
+}
+
+fn record_key(record: &Record, schema: &Schema) -> Result<Value, ExecutionError> {
+    let mut values = record_to_map(record, schema)?;
+    let key: Map<String, Value> = schema
+        .primary_index
+        .iter()
+        .filter_map(|idx| {
+            let name = &schema.fields.get(*idx)?.name;
+            values.remove(name).map(|value| (name.clone(), value))
+        })
+        .collect();
+    Ok(Value::Object(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dozer_types::{
+        chrono::{NaiveDate, TimeZone, Utc},
+        ordered_float::OrderedFloat,
+        rust_decimal::Decimal,
+        types::{Field, FieldDefinition, FieldType},
+    };
+
+    fn get_schema() -> Schema {
+        let mut schema = Schema::empty();
+        let fields = [
+            ("id", FieldType::Int),
+            ("uint", FieldType::UInt),
+            ("float", FieldType::Float),
+            ("bool", FieldType::Boolean),
+            ("string", FieldType::String),
+            ("text", FieldType::Text),
+            ("binary", FieldType::Binary),
+            ("decimal", FieldType::Decimal),
+            ("timestamp", FieldType::Timestamp),
+            ("date", FieldType::Date),
+            ("bson", FieldType::Bson),
+            ("null", FieldType::String),
+        ];
+        for (idx, (name, typ)) in fields.into_iter().enumerate() {
+            schema.field(FieldDefinition::new(name.to_string(), typ, true), idx == 0);
+        }
+        schema
+    }
+
+    fn get_record(id: i64) -> Record {
+        Record::new(
+            None,
+            vec![
+                Field::Int(id),
+                Field::UInt(2),
+                Field::Float(OrderedFloat(1.5)),
+                Field::Boolean(true),
+                Field::String("string".to_string()),
+                Field::Text("text".to_string()),
+                Field::Binary(vec![1, 2]),
+                Field::Decimal(Decimal::new(12345, 2)),
+                Field::Timestamp(Utc.timestamp_millis(0).into()),
+                Field::Date(NaiveDate::from_ymd(2022, 11, 24)),
+                Field::Bson(vec![3]),
+                Field::Null,
+            ],
+            None,
+        )
+    }
+
+    fn record_json(id: i64) -> Value {
+        json!({
+            "id": id,
+            "uint": 2,
+            "float": 1.5,
+            "bool": true,
+            "string": "string",
+            "text": "text",
+            "binary": [1, 2],
+            "decimal": "123.45",
+            "timestamp": "1970-01-01T00:00:00.000Z",
+            "date": "2022-11-24",
+            "bson": [3],
+            "null": null,
+        })
+    }
+
+    #[test]
+    fn test_operation_to_messages() {
+        let schema = get_schema();
+
+        let insert = Operation::Insert { new: get_record(1) };
+        assert_eq!(
+            operation_to_messages(&insert, &schema).unwrap(),
+            vec![(
+                json!({"id": 1}),
+                json!({"before": null, "after": record_json(1), "op": "c"})
+            )]
+        );
+
+        let update = Operation::Update {
+            old: get_record(1),
+            new: get_record(1),
+        };
+        assert_eq!(
+            operation_to_messages(&update, &schema).unwrap(),
+            vec![(
+                json!({"id": 1}),
+                json!({"before": record_json(1), "after": record_json(1), "op": "u"})
+            )]
+        );
+
+        let delete = Operation::Delete { old: get_record(2) };
+        assert_eq!(
+            operation_to_messages(&delete, &schema).unwrap(),
+            vec![(
+                json!({"id": 2}),
+                json!({"before": record_json(2), "after": null, "op": "d"})
+            )]
+        );
+    }
+
+    #[test]
+    fn test_primary_key_update_to_messages() {
+        let schema = get_schema();
+
+        let update = Operation::Update {
+            old: get_record(1),
+            new: get_record(2),
+        };
+        assert_eq!(
+            operation_to_messages(&update, &schema).unwrap(),
+            vec![
+                (
+                    json!({"id": 1}),
+                    json!({"before": record_json(1), "after": null, "op": "d"})
+                ),
+                (
+                    json!({"id": 2}),
+                    json!({"before": null, "after": record_json(2), "op": "c"})
+                ),
+            ]
+        );
+    }
+}
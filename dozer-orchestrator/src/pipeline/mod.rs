@@ -1,6 +1,11 @@
 pub mod connector_source;
+mod helper;
+mod json_sink;
+mod kafka_sink;
 mod sinks;
 pub mod source_builder;
 mod streaming_sink;
+pub use json_sink::{JsonFileSink, JsonFileSinkFactory};
+pub use kafka_sink::{KafkaSink, KafkaSinkFactory};
 pub use sinks::{CacheSink, CacheSinkFactory};
 pub(crate) use streaming_sink::StreamingSinkFactory;
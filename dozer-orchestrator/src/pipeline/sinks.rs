@@ -117,7 +117,7 @@ impl CacheSinkFactory {
 
         schema.identifier = Some(SchemaIdentifier {
             id: hash as u32,
-            version: 1,
+            version: schema.fields_version(),
         });
 
         // Automatically create secondary indexes
@@ -134,7 +134,8 @@ impl CacheSinkFactory {
                 | FieldType::String
                 | FieldType::Decimal
                 | FieldType::Timestamp
-                | FieldType::Date => Some(IndexDefinition::SortedInverted(vec![idx])),
+                | FieldType::Date
+                | FieldType::Json => Some(IndexDefinition::SortedInverted(vec![idx])),
 
                 // Create full text indexes for text fields
                 FieldType::Text => Some(IndexDefinition::FullText(idx)),
@@ -186,7 +187,7 @@ impl SinkFactory for CacheSinkFactory {
 
             pipeline_schema.set_identifier(Some(SchemaIdentifier {
                 id: hash as u32,
-                version: 1,
+                version: pipeline_schema.fields_version(),
             }))?;
 
             let api_index = self.api_endpoint.index.to_owned().unwrap_or_default();
@@ -286,7 +287,8 @@ fn create_secondary_indexes(schema: &Schema) -> Vec<IndexDefinition> {
             | FieldType::String
             | FieldType::Decimal
             | FieldType::Timestamp
-            | FieldType::Date => Some(IndexDefinition::SortedInverted(vec![idx])),
+            | FieldType::Date
+            | FieldType::Json => Some(IndexDefinition::SortedInverted(vec![idx])),
 
             // Create full text indexes for text fields
             FieldType::Text => Some(IndexDefinition::FullText(idx)),
@@ -398,17 +400,17 @@ impl Sink for CacheSink {
                         ExecutionError::SinkError(SinkError::CacheDeleteFailed(Box::new(e)))
                     })?;
             }
-            Operation::Insert { new } => {
-                // new.schema_id = schema.identifier.clone();
+            Operation::Insert { mut new } => {
+                new.schema_id = schema.identifier;
                 self.cache
                     .insert_with_txn(txn, &new, schema, secondary_indexes)
                     .map_err(|e| {
                         ExecutionError::SinkError(SinkError::CacheInsertFailed(Box::new(e)))
                     })?;
             }
-            Operation::Update { old, new } => {
+            Operation::Update { old, mut new } => {
                 let key = get_primary_key(&schema.primary_index, &old.values);
-                // new.schema_id = old.schema_id.clone();
+                new.schema_id = schema.identifier;
                 self.cache
                     .update_with_txn(txn, &key, &old, &new, schema, secondary_indexes)
                     .map_err(|e| {
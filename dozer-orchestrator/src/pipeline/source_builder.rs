@@ -1,8 +1,9 @@
 use crate::pipeline::connector_source::ConnectorSourceFactory;
 use crate::OrchestrationError;
 use dozer_core::dag::appsource::{AppSource, AppSourceManager};
-use dozer_ingestion::connectors::{get_connector_outputs, TableInfo};
+use dozer_ingestion::connectors::{get_connector_outputs, ingests_sources_separately, TableInfo};
 use dozer_ingestion::ingestion::{IngestionIterator, Ingestor};
+use dozer_types::models::connection::Connection;
 use dozer_types::models::source::Source;
 use dozer_types::parking_lot::RwLock;
 use std::collections::HashMap;
@@ -29,18 +30,21 @@ impl SourceBuilder {
 
             if let Some(connection) = &first_source.connection {
                 let grouped_connector_sources =
-                    get_connector_outputs(connection.clone(), sources_group.clone());
+                    Self::get_source_groups(&conn, connection, sources_group.clone());
 
-                for same_connection_sources in grouped_connector_sources {
+                for (name, same_connection_sources) in grouped_connector_sources {
                     let mut ports = HashMap::new();
                     let mut tables = vec![];
                     for source in same_connection_sources {
                         ports.insert(source.table_name.clone(), port);
 
+                        let primary_key_columns =
+                            Some(source.primary_key_columns).filter(|columns| !columns.is_empty());
                         tables.push(TableInfo {
                             name: source.table_name,
                             id: port as u32,
                             columns: Some(source.columns),
+                            primary_key_columns,
                         });
 
                         port += 1;
@@ -55,11 +59,10 @@ impl SourceBuilder {
                         running.clone(),
                     );
 
-                    asm.add(AppSource::new(
-                        conn.clone(),
-                        Arc::new(source_factory),
-                        ports,
-                    ))?;
+                    asm.add(
+                        AppSource::new(conn.clone(), Arc::new(source_factory), ports)
+                            .with_name(name),
+                    )?;
                 }
             }
         }
@@ -67,6 +70,29 @@ impl SourceBuilder {
         Ok(asm)
     }
 
+    /// Splits the sources of the connection `conn` into the groups its connector ingests
+    /// separately, each with the name of its source node. The source of a connector which ingests
+    /// every table separately is named `<conn>.<table>`, otherwise it keeps the connection's name,
+    /// so that names don't change when tables are added or removed.
+    pub fn get_source_groups(
+        conn: &str,
+        connection: &Connection,
+        sources: Vec<Source>,
+    ) -> Vec<(String, Vec<Source>)> {
+        let separately = ingests_sources_separately(connection);
+        get_connector_outputs(connection.clone(), sources)
+            .into_iter()
+            .map(|group| {
+                let name = if separately {
+                    format!("{}.{}", conn, group[0].table_name)
+                } else {
+                    conn.to_string()
+                };
+                (name, group)
+            })
+            .collect()
+    }
+
     pub fn group_connections(sources: Vec<Source>) -> HashMap<String, Vec<Source>> {
         sources
             .into_iter()
@@ -89,6 +115,7 @@ mod tests {
     use std::sync::Arc;
 
     use dozer_core::dag::appsource::{AppSourceId, AppSourceMappings};
+    use dozer_types::ingestion_types::SnowflakeConfig;
     use dozer_types::models::connection::{
         Authentication, Connection, DBType, EventsAuthentication,
     };
@@ -127,6 +154,7 @@ mod tests {
                     connection: Some(events1_conn.clone()),
                     refresh_config: None,
                     app_id: None,
+                    primary_key_columns: vec![],
                 },
                 Source {
                     id: None,
@@ -136,6 +164,7 @@ mod tests {
                     connection: Some(events1_conn.clone()),
                     refresh_config: None,
                     app_id: None,
+                    primary_key_columns: vec![],
                 },
                 Source {
                     id: None,
@@ -145,6 +174,7 @@ mod tests {
                     connection: Some(events2_conn.clone()),
                     refresh_config: None,
                     app_id: None,
+                    primary_key_columns: vec![],
                 },
                 Source {
                     id: None,
@@ -154,6 +184,7 @@ mod tests {
                     connection: Some(events2_conn),
                     refresh_config: None,
                     app_id: None,
+                    primary_key_columns: vec![],
                 },
             ],
             endpoints: vec![],
@@ -187,4 +218,38 @@ mod tests {
 
         assert_eq!(2, pg_source_mapping.get(0).unwrap().mappings.len());
     }
+
+    #[test]
+    fn split_connection_sources_get_a_node_each() {
+        let connection = Connection {
+            authentication: Some(Authentication::Snowflake(SnowflakeConfig::default())),
+            id: None,
+            app_id: None,
+            db_type: DBType::Snowflake.into(),
+            name: "snow".to_string(),
+        };
+        let source = |table_name: &str| Source {
+            id: None,
+            name: table_name.to_string(),
+            table_name: table_name.to_string(),
+            columns: vec!["id".to_string()],
+            connection: Some(connection.clone()),
+            refresh_config: None,
+            app_id: None,
+            primary_key_columns: vec![],
+        };
+
+        // Snowflake ingests every table separately, so that each has its own checkpoint
+        let groups = SourceBuilder::get_source_groups(
+            "snow",
+            &connection,
+            vec![source("prices"), source("prices_history")],
+        );
+        let names: Vec<&str> = groups.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["snow.prices", "snow.prices_history"]);
+
+        // Names don't depend on the number of tables
+        let groups = SourceBuilder::get_source_groups("snow", &connection, vec![source("prices")]);
+        assert_eq!(groups[0].0, "snow.prices");
+    }
 }
@@ -4,15 +4,18 @@ use dozer_types::indicatif::MultiProgress;
 use dozer_types::types::{Operation, SchemaWithChangesType};
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 
-use dozer_api::CacheEndpoint;
+use dozer_api::{CacheEndpoint, ReadinessGate};
 use dozer_types::models::source::Source;
 
-use crate::pipeline::{CacheSinkFactory, StreamingSinkFactory};
+use crate::pipeline::{CacheSinkFactory, JsonFileSinkFactory, StreamingSinkFactory};
 use dozer_core::dag::dag::DEFAULT_PORT_HANDLE;
 use dozer_core::dag::executor::{DagExecutor, ExecutorOptions};
+use dozer_core::dag::node::NodeHandle;
 use dozer_ingestion::connectors::{get_connector, get_connector_info_table, TableInfo};
 
 use dozer_ingestion::ingestion::{IngestionIterator, Ingestor};
@@ -38,6 +41,7 @@ pub struct Executor {
     iterator: Arc<RwLock<IngestionIterator>>,
     running: Arc<AtomicBool>,
     progress: MultiProgress,
+    json_sink: Option<(PathBuf, u64)>,
 }
 impl Executor {
     pub fn new(
@@ -56,9 +60,17 @@ impl Executor {
             iterator,
             running,
             progress: MultiProgress::new(),
+            json_sink: None,
         }
     }
 
+    /// Also writes the output of every endpoint to `<dir>/<endpoint name>.json` as
+    /// newline-delimited JSON, rotating the files once they reach `max_file_size` bytes.
+    pub fn with_json_sink(mut self, dir: PathBuf, max_file_size: u64) -> Self {
+        self.json_sink = Some((dir, max_file_size));
+        self
+    }
+
     pub fn get_connection_groups(&self) -> HashMap<String, Vec<Source>> {
         SourceBuilder::group_connections(self.sources.clone())
     }
@@ -76,6 +88,8 @@ impl Executor {
                         name: source.table_name.clone(),
                         id: 0,
                         columns: Some(source.columns.clone()),
+                        primary_key_columns: Some(source.primary_key_columns.clone())
+                            .filter(|columns| !columns.is_empty()),
                     })
                     .collect();
 
@@ -169,7 +183,7 @@ impl Executor {
         )?;
         let mut app = App::new(asm);
 
-        let mut pipeline = PipelineBuilder {}
+        let (mut pipeline, (output_node, output_port)) = PipelineBuilder {}
             .build_pipeline(&sql)
             .map_err(OrchestrationError::PipelineError)?;
         pipeline.add_sink(
@@ -178,8 +192,8 @@ impl Executor {
         );
         pipeline
             .connect_nodes(
-                "aggregation",
-                Some(DEFAULT_PORT_HANDLE),
+                &output_node,
+                Some(output_port),
                 "streaming_sink",
                 Some(DEFAULT_PORT_HANDLE),
             )
@@ -224,7 +238,7 @@ impl Executor {
             let _api_endpoint_name = api_endpoint.name.clone();
             let cache = cache_endpoint.cache;
 
-            let mut pipeline = PipelineBuilder {}
+            let (mut pipeline, (output_node, output_port)) = PipelineBuilder {}
                 .build_pipeline(&api_endpoint.sql)
                 .map_err(OrchestrationError::PipelineError)?;
 
@@ -243,13 +257,32 @@ impl Executor {
 
             pipeline
                 .connect_nodes(
-                    "aggregation",
-                    Some(DEFAULT_PORT_HANDLE),
+                    &output_node,
+                    Some(output_port),
                     cache_endpoint.endpoint.name.as_str(),
                     Some(DEFAULT_PORT_HANDLE),
                 )
                 .map_err(ExecutionError)?;
 
+            if let Some((dir, max_file_size)) = &self.json_sink {
+                let name = format!("{}_json", cache_endpoint.endpoint.name);
+                pipeline.add_sink(
+                    Arc::new(JsonFileSinkFactory::new(
+                        dir.join(format!("{}.json", cache_endpoint.endpoint.name)),
+                        *max_file_size,
+                    )),
+                    name.as_str(),
+                );
+                pipeline
+                    .connect_nodes(
+                        &output_node,
+                        Some(output_port),
+                        name.as_str(),
+                        Some(DEFAULT_PORT_HANDLE),
+                    )
+                    .map_err(ExecutionError)?;
+            }
+
             app.add_pipeline(pipeline);
         }
 
@@ -282,13 +315,40 @@ impl Executor {
         Ok(schema_map)
     }
 
+    // Positions of the sources at startup. The caches are ready once the pipeline has committed them.
+    fn get_source_positions(
+        grouped_connections: &HashMap<String, Vec<Source>>,
+    ) -> Result<HashMap<NodeHandle, (u64, u64)>, OrchestrationError> {
+        let mut positions = HashMap::new();
+        for (connection_name, sources_group) in grouped_connections {
+            let first_source = sources_group.get(0).unwrap();
+
+            if let Some(connection) = &first_source.connection {
+                let connector = get_connector(connection.to_owned())?;
+                if let Some(position) = connector.get_current_position()? {
+                    let groups = SourceBuilder::get_source_groups(
+                        connection_name,
+                        connection,
+                        sources_group.clone(),
+                    );
+                    for (name, _sources) in groups {
+                        positions.insert(NodeHandle::new(None, name), position);
+                    }
+                }
+            }
+        }
+        Ok(positions)
+    }
+
     pub fn run(
         &self,
         notifier: Option<crossbeam::channel::Sender<PipelineResponse>>,
+        readiness: ReadinessGate,
     ) -> Result<(), OrchestrationError> {
         let running_wait = self.running.clone();
 
         let parent_dag = self.build_pipeline(notifier, PathBuf::default(), None)?;
+        let source_positions = Self::get_source_positions(&self.get_connection_groups())?;
         let path = &self.pipeline_dir;
 
         if !path.exists() {
@@ -297,14 +357,35 @@ impl Executor {
             ));
         }
 
+        // Connectors send heartbeats while idle, so that the checkpoint follows their position
         let mut exec = DagExecutor::new(
             &parent_dag,
             path.as_path(),
-            ExecutorOptions::default(),
+            ExecutorOptions {
+                heartbeat_interval: Some(Duration::from_secs(1)),
+                ..Default::default()
+            },
             running_wait,
         )?;
 
         exec.start()?;
+
+        let watermark = exec.get_watermark();
+        let running = self.running.clone();
+        thread::spawn(move || {
+            while running.load(Ordering::Relaxed) {
+                if source_positions
+                    .iter()
+                    .all(|(source, position)| watermark.has_reached(source, *position))
+                {
+                    info!("[pipeline] Caught up with sources");
+                    readiness.set_ready(true);
+                    return;
+                }
+                thread::sleep(Duration::from_millis(100));
+            }
+        });
+
         exec.join().map_err(ExecutionError)
     }
 }
@@ -14,7 +14,7 @@ use dozer_api::{
         self, internal::internal_pipeline_server::start_internal_pipeline_server,
         internal_grpc::PipelineResponse,
     },
-    rest, CacheEndpoint,
+    rest, CacheEndpoint, ReadinessGate,
 };
 use dozer_cache::cache::{CacheCommonOptions, CacheOptions, CacheReadOptions, CacheWriteOptions};
 use dozer_cache::cache::{CacheOptionsKind, LmdbCache};
@@ -47,6 +47,8 @@ pub struct SimpleOrchestrator {
     pub cache_common_options: CacheCommonOptions,
     pub cache_read_options: CacheReadOptions,
     pub cache_write_options: CacheWriteOptions,
+    /// Shared between `run_apps` and `run_api` to serve the readiness route.
+    pub readiness: ReadinessGate,
 }
 
 impl SimpleOrchestrator {
@@ -115,16 +117,6 @@ impl Orchestrator for SimpleOrchestrator {
         rt.block_on(async {
             let mut futures = FuturesUnordered::new();
 
-            // Initialize API Server
-            let rest_config = get_rest_config(self.config.to_owned());
-            let security = get_api_security_config(self.config.to_owned());
-            let rest_handle = tokio::spawn(async move {
-                let api_server = rest::ApiServer::new(rest_config, security);
-                api_server
-                    .run(cache_endpoints, tx)
-                    .await
-                    .map_err(OrchestrationError::ApiServerFailed)
-            });
             // Initiate Push Events
             // create broadcast channel
             let pipeline_config = get_pipeline_config(self.config.to_owned());
@@ -145,6 +137,19 @@ impl Orchestrator for SimpleOrchestrator {
                 None
             };
 
+            // Initialize API Server
+            let rest_config = get_rest_config(self.config.to_owned());
+            let security = get_api_security_config(self.config.to_owned());
+            let readiness = self.readiness.clone();
+            let rest_event_notifier = rx1.as_ref().map(|r| r.resubscribe());
+            let rest_handle = tokio::spawn(async move {
+                let api_server = rest::ApiServer::new(rest_config, security, readiness);
+                api_server
+                    .run(cache_endpoints, tx, rest_event_notifier)
+                    .await
+                    .map_err(OrchestrationError::ApiServerFailed)
+            });
+
             // Initialize GRPC Server
 
             let api_dir = get_api_dir(self.config.to_owned());
@@ -218,7 +223,7 @@ impl Orchestrator for SimpleOrchestrator {
             running,
             pipeline_home_dir,
         );
-        executor.run(Some(sender))
+        executor.run(Some(sender), self.readiness.clone())
     }
 
     fn list_connectors(
@@ -231,8 +236,8 @@ impl Orchestrator for SimpleOrchestrator {
         if let Some(api_config) = self.config.api.to_owned() {
             if let Some(api_security) = api_config.api_security {
                 match api_security {
-                    dozer_types::models::api_security::ApiSecurity::Jwt(secret) => {
-                        let auth = Authorizer::new(&secret, None, None);
+                    dozer_types::models::api_security::ApiSecurity::Jwt(jwt) => {
+                        let auth = Authorizer::new(&jwt.secret, None, None);
                         let token = auth.generate_token(Access::All, None).map_err(|err| {
                             OrchestrationError::GenerateTokenFailed(err.to_string())
                         })?;
@@ -8,7 +8,7 @@ use std::{
     time::Duration,
 };
 
-use dozer_api::CacheEndpoint;
+use dozer_api::{CacheEndpoint, ReadinessGate};
 use dozer_cache::cache::{expression::QueryExpression, test_utils, Cache, CacheOptions, LmdbCache};
 use dozer_ingestion::ingestion::{IngestionConfig, Ingestor};
 use dozer_types::{
@@ -94,7 +94,7 @@ fn single_source_sink_impl(schema: Schema) {
             executor_running,
             tmp_path,
         );
-        match executor.run(None) {
+        match executor.run(None, ReadinessGate::default()) {
             Ok(_) => {}
             Err(e) => warn!("Exiting: {:?}", e),
         }
@@ -53,5 +53,6 @@ pub fn init_endpoint() -> ApiEndpoint {
             primary_key: vec!["film_id".to_string()],
         }),
         app_id: None,
+        json_options: None,
     }
 }
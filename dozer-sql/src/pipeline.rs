@@ -1,9 +1,15 @@
 mod aggregation;
 pub mod builder;
+mod distinct;
 pub mod errors;
 mod expression;
+mod limit;
+mod order_by;
 mod product;
 mod projection;
 mod selection;
+mod state;
 #[cfg(test)]
 mod tests;
+mod union;
+pub mod unnest;
@@ -66,7 +66,7 @@ impl Aggregator {
         agg_db: Database,
     ) -> Result<AggregationResult, PipelineError> {
         match &self {
-            Aggregator::Avg => AvgAggregator::insert(cur_state, new, return_type, txn, agg_db),
+            Aggregator::Avg => AvgAggregator::insert(cur_state, new, return_type, txn),
             Aggregator::Count => CountAggregator::insert(cur_state, new, return_type, txn),
             Aggregator::Max => MaxAggregator::insert(cur_state, new, return_type, txn, agg_db),
             Aggregator::Min => MinAggregator::insert(cur_state, new, return_type, txn, agg_db),
@@ -84,7 +84,7 @@ impl Aggregator {
         agg_db: Database,
     ) -> Result<AggregationResult, PipelineError> {
         match &self {
-            Aggregator::Avg => AvgAggregator::update(cur_state, old, new, return_type, txn, agg_db),
+            Aggregator::Avg => AvgAggregator::update(cur_state, old, new, return_type, txn),
             Aggregator::Count => CountAggregator::update(cur_state, old, new, return_type, txn),
             Aggregator::Max => MaxAggregator::update(cur_state, old, new, return_type, txn, agg_db),
             Aggregator::Min => MinAggregator::update(cur_state, old, new, return_type, txn, agg_db),
@@ -101,7 +101,7 @@ impl Aggregator {
         agg_db: Database,
     ) -> Result<AggregationResult, PipelineError> {
         match &self {
-            Aggregator::Avg => AvgAggregator::delete(cur_state, old, return_type, txn, agg_db),
+            Aggregator::Avg => AvgAggregator::delete(cur_state, old, return_type, txn),
             Aggregator::Count => CountAggregator::delete(cur_state, old, return_type, txn),
             Aggregator::Max => MaxAggregator::delete(cur_state, old, return_type, txn, agg_db),
             Aggregator::Min => MinAggregator::delete(cur_state, old, return_type, txn, agg_db),
@@ -1,20 +1,18 @@
-use crate::deserialize;
 use crate::pipeline::aggregation::aggregator::AggregationResult;
 use crate::pipeline::errors::PipelineError;
 use crate::pipeline::errors::PipelineError::InvalidOperandType;
 use crate::{
-    check_nan_f64, deserialize_u8, field_extract_decimal, field_extract_f64, field_extract_i64,
-    to_bytes, try_unwrap,
+    deserialize, deserialize_decimal, deserialize_f64, field_extract_decimal, field_extract_f64,
+    field_extract_i64,
 };
-use dozer_core::storage::common::Database;
 use dozer_core::storage::prefix_transaction::PrefixTransaction;
 use dozer_types::ordered_float::OrderedFloat;
-use dozer_types::types::Field::{Decimal, Float, Int};
+use dozer_types::rust_decimal::Decimal;
 use dozer_types::types::{Field, FieldType};
-use num_traits::Zero;
-use std::ops::Div;
-use std::string::ToString;
+use std::mem::size_of;
 
+/// The state of an average is the sum of the values followed by their count (u64 BE). The
+/// average itself is only computed when the value is emitted.
 pub struct AvgAggregator {}
 const AGGREGATOR_NAME: &str = "AVG";
 
@@ -23,9 +21,8 @@ impl AvgAggregator {
 
     pub(crate) fn get_return_type(from: FieldType) -> FieldType {
         match from {
-            FieldType::Decimal => FieldType::Decimal,
+            FieldType::Decimal | FieldType::Int => FieldType::Decimal,
             FieldType::Float => FieldType::Float,
-            FieldType::Int => FieldType::Int,
             _ => from,
         }
     }
@@ -35,264 +32,97 @@ impl AvgAggregator {
     }
 
     pub(crate) fn insert(
-        _cur_state: Option<&[u8]>,
+        cur_state: Option<&[u8]>,
         new: &Field,
         return_type: FieldType,
-        ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        _txn: &mut PrefixTransaction,
     ) -> Result<AggregationResult, PipelineError> {
-        match (return_type, new) {
-            (FieldType::Decimal, _) => {
-                // Update aggregators_db with new val and its occurrence
-                let new_val = field_extract_decimal!(&new, AGGREGATOR_NAME).serialize();
-                Self::update_aggregator_db(new_val.as_slice(), 1, false, ptx, aggregators_db);
-
-                // Calculate average
-                let avg = try_unwrap!(Self::calc_decimal_average(ptx, aggregators_db)).serialize();
-                Ok(AggregationResult::new(
-                    Self::get_value(avg.as_slice(), return_type),
-                    Some(Vec::from(avg)),
-                ))
-            }
-            (FieldType::Float, _) => {
-                // Update aggregators_db with new val and its occurrence
-                let new_val = field_extract_f64!(&new, AGGREGATOR_NAME);
-                Self::update_aggregator_db(to_bytes!(new_val), 1, false, ptx, aggregators_db);
-
-                // Calculate average
-                let avg = try_unwrap!(Self::calc_f64_average(ptx, aggregators_db)).to_be_bytes();
-                Ok(AggregationResult::new(
-                    Self::get_value(&avg, return_type),
-                    Some(Vec::from(avg)),
-                ))
-            }
-            (FieldType::Int, _) => {
-                // Update aggregators_db with new val and its occurrence
-                let new_val = field_extract_i64!(&new, AGGREGATOR_NAME);
-                Self::update_aggregator_db(to_bytes!(new_val), 1, false, ptx, aggregators_db);
-
-                // Calculate average
-                let avg = try_unwrap!(Self::calc_i64_average(ptx, aggregators_db)).to_be_bytes();
-                Ok(AggregationResult::new(
-                    Self::get_value(&avg, return_type),
-                    Some(Vec::from(avg)),
-                ))
-            }
-            _ => Err(InvalidOperandType(AGGREGATOR_NAME.to_string())),
-        }
+        Self::aggregate(cur_state, None, Some(new), return_type)
     }
 
     pub(crate) fn update(
-        _cur_state: Option<&[u8]>,
+        cur_state: Option<&[u8]>,
         old: &Field,
         new: &Field,
         return_type: FieldType,
-        ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        _txn: &mut PrefixTransaction,
     ) -> Result<AggregationResult, PipelineError> {
-        match (return_type, new) {
-            (FieldType::Decimal, _) => {
-                // Update aggregators_db with new val and its occurrence
-                let new_val = field_extract_decimal!(&new, AGGREGATOR_NAME).serialize();
-                Self::update_aggregator_db(new_val.as_slice(), 1, false, ptx, aggregators_db);
-
-                // Update aggregators_db with new val and its occurrence
-                let old_val = field_extract_decimal!(&old, AGGREGATOR_NAME).serialize();
-                Self::update_aggregator_db(old_val.as_slice(), 1, true, ptx, aggregators_db);
-
-                // Calculate average
-                let avg = try_unwrap!(Self::calc_decimal_average(ptx, aggregators_db)).serialize();
-                Ok(AggregationResult::new(
-                    Self::get_value(avg.as_slice(), return_type),
-                    Some(Vec::from(avg)),
-                ))
-            }
-            (FieldType::Float, _) => {
-                // Update aggregators_db with new val and its occurrence
-                let new_val = field_extract_f64!(&new, AGGREGATOR_NAME);
-                Self::update_aggregator_db(to_bytes!(new_val), 1, false, ptx, aggregators_db);
-                let old_val = field_extract_f64!(&old, AGGREGATOR_NAME);
-                Self::update_aggregator_db(to_bytes!(old_val), 1, true, ptx, aggregators_db);
-
-                // Calculate average
-                let avg = try_unwrap!(Self::calc_f64_average(ptx, aggregators_db)).to_be_bytes();
-                Ok(AggregationResult::new(
-                    Self::get_value(&avg, return_type),
-                    Some(Vec::from(avg)),
-                ))
-            }
-            (FieldType::Int, _) => {
-                // Update aggregators_db with new val and its occurrence
-                let new_val = field_extract_i64!(&new, AGGREGATOR_NAME);
-                Self::update_aggregator_db(to_bytes!(new_val), 1, false, ptx, aggregators_db);
-                let old_val = field_extract_i64!(&old, AGGREGATOR_NAME);
-                Self::update_aggregator_db(to_bytes!(old_val), 1, true, ptx, aggregators_db);
-
-                // Calculate average
-                let avg = (try_unwrap!(Self::calc_i64_average(ptx, aggregators_db))).to_be_bytes();
-                Ok(AggregationResult::new(
-                    Self::get_value(&avg, return_type),
-                    Some(Vec::from(avg)),
-                ))
-            }
-            _ => Err(InvalidOperandType(AGGREGATOR_NAME.to_string())),
-        }
+        Self::aggregate(cur_state, Some(old), Some(new), return_type)
     }
 
     pub(crate) fn delete(
-        _cur_state: Option<&[u8]>,
+        cur_state: Option<&[u8]>,
         old: &Field,
         return_type: FieldType,
-        ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        _txn: &mut PrefixTransaction,
     ) -> Result<AggregationResult, PipelineError> {
-        match (return_type, old) {
-            (FieldType::Decimal, _) => {
-                // Update aggregators_db with new val and its occurrence
-                let old_val = field_extract_decimal!(&old, AGGREGATOR_NAME).serialize();
-                Self::update_aggregator_db(old_val.as_slice(), 1, true, ptx, aggregators_db);
+        Self::aggregate(cur_state, Some(old), None, return_type)
+    }
 
-                // Calculate average
-                let avg = try_unwrap!(Self::calc_decimal_average(ptx, aggregators_db)).serialize();
-                Ok(AggregationResult::new(
-                    Self::get_value(avg.as_slice(), return_type),
-                    Some(Vec::from(avg)),
-                ))
+    /// Removes `old` from and adds `new` to the sum and count held by `cur_state`.
+    fn aggregate(
+        cur_state: Option<&[u8]>,
+        old: Option<&Field>,
+        new: Option<&Field>,
+        return_type: FieldType,
+    ) -> Result<AggregationResult, PipelineError> {
+        let (sum_state, count) = match cur_state {
+            Some(state) => {
+                let (sum, count) = state.split_at(state.len() - size_of::<u64>());
+                (Some(sum), u64::from_be_bytes(deserialize!(count)))
             }
-            (FieldType::Float, _) => {
-                // Update aggregators_db with new val and its occurrence
-                let old_val = field_extract_f64!(&old, AGGREGATOR_NAME);
-                Self::update_aggregator_db(to_bytes!(old_val), 1, true, ptx, aggregators_db);
-
-                // Calculate average
-                let avg = try_unwrap!(Self::calc_f64_average(ptx, aggregators_db)).to_be_bytes();
+            None => (None, 0_u64),
+        };
+        let count = (count + u64::from(new.is_some())).saturating_sub(u64::from(old.is_some()));
+
+        match return_type {
+            FieldType::Decimal | FieldType::Int => {
+                let mut sum = deserialize_decimal!(sum_state);
+                if let Some(old) = old {
+                    sum -= Self::extract_decimal(old, return_type)?;
+                }
+                if let Some(new) = new {
+                    sum += Self::extract_decimal(new, return_type)?;
+                }
+
+                // An empty group is retracted, its average is never emitted
+                let value = if count == 0 {
+                    Field::Null
+                } else {
+                    Field::Decimal(sum / Decimal::from(count))
+                };
                 Ok(AggregationResult::new(
-                    Self::get_value(&avg, return_type),
-                    Some(Vec::from(avg)),
+                    value,
+                    Some([&sum.serialize()[..], &count.to_be_bytes()[..]].concat()),
                 ))
             }
-            (FieldType::Int, _) => {
-                // Update aggregators_db with new val and its occurrence
-                let old_val = field_extract_i64!(&old, AGGREGATOR_NAME);
-                Self::update_aggregator_db(to_bytes!(old_val), 1, true, ptx, aggregators_db);
-
-                // Calculate average
-                let avg = try_unwrap!(Self::calc_i64_average(ptx, aggregators_db)).to_be_bytes();
+            FieldType::Float => {
+                let mut sum = deserialize_f64!(sum_state);
+                if let Some(old) = old {
+                    sum -= field_extract_f64!(old, AGGREGATOR_NAME).0;
+                }
+                if let Some(new) = new {
+                    sum += field_extract_f64!(new, AGGREGATOR_NAME).0;
+                }
+
+                let value = if count == 0 {
+                    Field::Null
+                } else {
+                    Field::Float(OrderedFloat(sum / count as f64))
+                };
                 Ok(AggregationResult::new(
-                    Self::get_value(&avg, return_type),
-                    Some(Vec::from(avg)),
+                    value,
+                    Some([sum.to_be_bytes(), count.to_be_bytes()].concat()),
                 ))
             }
             _ => Err(InvalidOperandType(AGGREGATOR_NAME.to_string())),
         }
     }
 
-    pub(crate) fn get_value(f: &[u8], from: FieldType) -> Field {
+    fn extract_decimal(field: &Field, from: FieldType) -> Result<Decimal, PipelineError> {
         match from {
-            FieldType::Decimal => Decimal(dozer_types::rust_decimal::Decimal::deserialize(
-                deserialize!(f),
-            )),
-            FieldType::Float => Float(OrderedFloat(f64::from_be_bytes(deserialize!(f)))),
-            FieldType::Int => Int(i64::from_be_bytes(deserialize!(f))),
-            _ => Field::Null,
-        }
-    }
-
-    fn update_aggregator_db(
-        key: &[u8],
-        val_delta: u8,
-        decr: bool,
-        ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
-    ) {
-        let get_prev_count = try_unwrap!(ptx.get(aggregators_db, key));
-        let prev_count = deserialize_u8!(get_prev_count);
-        let mut new_count = prev_count;
-        if decr {
-            new_count = new_count.wrapping_sub(val_delta);
-        } else {
-            new_count = new_count.wrapping_add(val_delta);
-        }
-        if new_count < 1 {
-            try_unwrap!(ptx.del(aggregators_db, key, Option::from(to_bytes!(prev_count))));
-        } else {
-            try_unwrap!(ptx.put(aggregators_db, key, to_bytes!(new_count)));
-        }
-    }
-
-    fn calc_f64_average(
-        ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
-    ) -> Result<f64, PipelineError> {
-        let ptx_cur = ptx.open_cursor(aggregators_db)?;
-        let mut total_count = 0_u8;
-        let mut total_sum = 0_f64;
-        let mut exist = ptx_cur.first()?;
-
-        // Loop through aggregators_db to calculate average
-        while exist {
-            let cur = try_unwrap!(ptx_cur.read()).unwrap();
-            let val = f64::from_be_bytes(deserialize!(cur.0));
-            let get_count = ptx.get(aggregators_db, cur.0);
-            if get_count.is_ok() {
-                let count = deserialize_u8!(try_unwrap!(get_count));
-                total_count += count;
-                total_sum += val * f64::from(count);
-            }
-            exist = ptx_cur.next()?;
-        }
-        Ok(check_nan_f64!(total_sum / f64::from(total_count)))
-    }
-
-    fn calc_decimal_average(
-        ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
-    ) -> Result<dozer_types::rust_decimal::Decimal, PipelineError> {
-        let ptx_cur = ptx.open_cursor(aggregators_db)?;
-        let mut total_count = 0_u8;
-        let mut total_sum = dozer_types::rust_decimal::Decimal::zero();
-        let mut exist = ptx_cur.first()?;
-
-        // Loop through aggregators_db to calculate average
-        while exist {
-            let cur = try_unwrap!(ptx_cur.read()).unwrap();
-            let val = dozer_types::rust_decimal::Decimal::deserialize(deserialize!(cur.0));
-            let get_count = ptx.get(aggregators_db, cur.0);
-            if get_count.is_ok() {
-                let count = deserialize_u8!(try_unwrap!(get_count));
-                total_count += count;
-                total_sum += val * dozer_types::rust_decimal::Decimal::from(count);
-            }
-            exist = ptx_cur.next()?;
-        }
-        if total_count.is_zero() {
-            Ok(dozer_types::rust_decimal::Decimal::zero())
-        } else {
-            Ok(total_sum.div(dozer_types::rust_decimal::Decimal::from(total_count)))
-        }
-    }
-
-    fn calc_i64_average(
-        ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
-    ) -> Result<i64, PipelineError> {
-        let ptx_cur = ptx.open_cursor(aggregators_db)?;
-        let mut total_count = 0_u8;
-        let mut total_sum = 0_i64;
-        let mut exist = ptx_cur.first()?;
-
-        // Loop through aggregators_db to calculate average
-        while exist {
-            let cur = try_unwrap!(ptx_cur.read()).unwrap();
-            let val = i64::from_be_bytes(deserialize!(cur.0));
-            let get_count = ptx.get(aggregators_db, cur.0);
-            if get_count.is_ok() {
-                let count = deserialize_u8!(try_unwrap!(get_count));
-                total_count += count;
-                total_sum += val * i64::from(count);
-            }
-            exist = ptx_cur.next()?;
+            FieldType::Int => Ok(Decimal::from(*field_extract_i64!(field, AGGREGATOR_NAME))),
+            _ => Ok(field_extract_decimal!(field, AGGREGATOR_NAME)),
         }
-        Ok(check_nan_f64!(total_sum as f64 / total_count as f64) as i64)
     }
 }
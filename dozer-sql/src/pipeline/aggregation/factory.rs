@@ -6,33 +6,65 @@ use dozer_core::dag::{
     node::{OutputPortDef, OutputPortType, PortHandle, Processor, ProcessorFactory},
 };
 use dozer_types::types::{FieldDefinition, Schema};
-use sqlparser::ast::{Expr as SqlExpr, Expr, SelectItem};
+use sqlparser::ast::{Expr as SqlExpr, Expr, Ident, SelectItem};
 
 use crate::pipeline::{
     errors::PipelineError,
     expression::{
         aggregate::AggregateFunctionType,
-        builder::{BuilderExpressionType, ExpressionBuilder},
+        builder::{check_unique_field_names, BuilderExpressionType, ExpressionBuilder},
         execution::{Expression, ExpressionExecutor},
     },
-    projection::{factory::parse_sql_select_item, processor::ProjectionProcessor},
+    projection::{
+        factory::{expand_select_items, parse_sql_select_item},
+        processor::ProjectionProcessor,
+    },
 };
 
 use super::{
     aggregator::Aggregator,
-    processor::{AggregationProcessor, FieldRule},
+    processor::{AggregationProcessor, FieldRule, HavingFilter},
 };
 
 #[derive(Debug)]
 pub struct AggregationProcessorFactory {
     select: Vec<SelectItem>,
     groupby: Vec<SqlExpr>,
+    having: Option<SqlExpr>,
 }
 
 impl AggregationProcessorFactory {
     /// Creates a new [`AggregationProcessorFactory`].
-    pub fn new(select: Vec<SelectItem>, groupby: Vec<SqlExpr>) -> Self {
-        Self { select, groupby }
+    pub fn new(select: Vec<SelectItem>, groupby: Vec<SqlExpr>, having: Option<SqlExpr>) -> Self {
+        Self {
+            select,
+            groupby,
+            having,
+        }
+    }
+
+    /// Returns the rules of the aggregated records and the `HAVING` clause rewritten over them.
+    /// Aggregate functions of the clause that aren't part of the projection are appended to the
+    /// rules as measures only computed for the clause.
+    fn get_rules(
+        &self,
+        select: &[SelectItem],
+        input_schema: &Schema,
+    ) -> Result<(Vec<FieldRule>, Option<SqlExpr>), PipelineError> {
+        let mut select = select.to_vec();
+        let having = self.having.as_ref().map(|having| {
+            let mut measures = vec![];
+            let having = split_having(having, &mut measures);
+            for measure in measures {
+                if !select.iter().any(|item| is_select_item(item, &measure)) {
+                    select.push(SelectItem::UnnamedExpr(measure));
+                }
+            }
+            having
+        });
+
+        let rules = get_aggregation_rules(&select, &self.groupby, input_schema)?;
+        Ok((rules, having))
     }
 }
 
@@ -56,14 +88,25 @@ impl ProcessorFactory for AggregationProcessorFactory {
         let input_schema = input_schemas
             .get(&DEFAULT_PORT_HANDLE)
             .ok_or(ExecutionError::InvalidPortHandle(DEFAULT_PORT_HANDLE))?;
-        let output_field_rules =
-            get_aggregation_rules(&self.select, &self.groupby, input_schema).unwrap();
-
-        if is_aggregation(&self.groupby, &output_field_rules) {
-            return build_output_schema(input_schema, output_field_rules);
-        }
-
-        build_projection_schema(input_schema, &self.select)
+        let select = expand_select_items(&self.select, input_schema)
+            .map_err(|e| ExecutionError::InternalStringError(e.to_string()))?;
+        let (output_field_rules, having) = self
+            .get_rules(&select, input_schema)
+            .map_err(|e| ExecutionError::InternalStringError(e.to_string()))?;
+
+        let output_schema =
+            if having.is_some() || is_aggregation(&self.groupby, &output_field_rules) {
+                let mut output_schema = build_output_schema(input_schema, &output_field_rules)?;
+                // Measures only computed for the HAVING clause aren't part of the output
+                output_schema.fields.truncate(select.len());
+                output_schema
+            } else {
+                build_projection_schema(input_schema, &select)?
+            };
+
+        check_unique_field_names(&output_schema)
+            .map_err(|e| ExecutionError::InternalStringError(e.to_string()))?;
+        Ok(output_schema)
     }
 
     fn build(
@@ -74,19 +117,32 @@ impl ProcessorFactory for AggregationProcessorFactory {
         let input_schema = input_schemas
             .get(&DEFAULT_PORT_HANDLE)
             .ok_or(ExecutionError::InvalidPortHandle(DEFAULT_PORT_HANDLE))?;
-        let output_field_rules =
-            get_aggregation_rules(&self.select, &self.groupby, input_schema).unwrap();
-
-        if is_aggregation(&self.groupby, &output_field_rules) {
+        let select = expand_select_items(&self.select, input_schema)
+            .map_err(|e| ExecutionError::InternalStringError(e.to_string()))?;
+        let (output_field_rules, having) = self
+            .get_rules(&select, input_schema)
+            .map_err(|e| ExecutionError::InternalStringError(e.to_string()))?;
+
+        if having.is_some() || is_aggregation(&self.groupby, &output_field_rules) {
+            let having = match having {
+                Some(having) => {
+                    let schema = build_output_schema(input_schema, &output_field_rules)?;
+                    let expression = ExpressionBuilder {}
+                        .build(&BuilderExpressionType::FullExpression, &having, &schema)
+                        .map_err(|e| ExecutionError::InternalStringError(e.to_string()))?;
+                    Some(HavingFilter::new(expression, schema, select.len()))
+                }
+                None => None,
+            };
             return Ok(Box::new(AggregationProcessor::new(
                 output_field_rules,
                 input_schema.clone(),
+                having,
             )));
         }
 
         // Build a Projection
-        match self
-            .select
+        match select
             .iter()
             .map(|item| parse_sql_select_item(item, input_schema))
             .collect::<Result<Vec<(String, Expression)>, PipelineError>>()
@@ -126,7 +182,6 @@ pub(crate) fn get_aggregation_rules(
     let mut select_rules = select
         .iter()
         .map(|item| parse_sql_aggregate_item(item, schema))
-        .filter(|e| e.is_ok())
         .collect::<Result<Vec<FieldRule>, PipelineError>>()?;
 
     let mut groupby_rules = groupby
@@ -223,7 +278,7 @@ fn get_aggregator(
 
 fn build_output_schema(
     input_schema: &Schema,
-    output_field_rules: Vec<FieldRule>,
+    output_field_rules: &[FieldRule],
 ) -> Result<Schema, ExecutionError> {
     let mut output_schema = Schema::empty();
 
@@ -260,6 +315,57 @@ fn build_output_schema(
     Ok(output_schema)
 }
 
+/// Replaces the aggregate functions of a `HAVING` clause with references to the fields holding
+/// their values, and collects them into `measures`.
+fn split_having(expr: &SqlExpr, measures: &mut Vec<SqlExpr>) -> SqlExpr {
+    match expr {
+        SqlExpr::Function(function)
+            if AggregateFunctionType::new(&function.name.to_string().to_lowercase()).is_ok() =>
+        {
+            if !measures.contains(expr) {
+                measures.push(expr.clone());
+            }
+            SqlExpr::Identifier(Ident::new(expr.to_string()))
+        }
+        SqlExpr::BinaryOp { left, op, right } => SqlExpr::BinaryOp {
+            left: Box::new(split_having(left, measures)),
+            op: op.clone(),
+            right: Box::new(split_having(right, measures)),
+        },
+        SqlExpr::UnaryOp { op, expr } => SqlExpr::UnaryOp {
+            op: op.clone(),
+            expr: Box::new(split_having(expr, measures)),
+        },
+        SqlExpr::Nested(expr) => SqlExpr::Nested(Box::new(split_having(expr, measures))),
+        SqlExpr::Between {
+            expr,
+            negated,
+            low,
+            high,
+        } => SqlExpr::Between {
+            expr: Box::new(split_having(expr, measures)),
+            negated: *negated,
+            low: Box::new(split_having(low, measures)),
+            high: Box::new(split_having(high, measures)),
+        },
+        SqlExpr::InList {
+            expr,
+            list,
+            negated,
+        } => SqlExpr::InList {
+            expr: Box::new(split_having(expr, measures)),
+            list: list.clone(),
+            negated: *negated,
+        },
+        _ => expr.clone(),
+    }
+}
+
+/// Returns `true` if `item` projects `expr` under its own name.
+fn is_select_item(item: &SelectItem, expr: &SqlExpr) -> bool {
+    matches!(item, SelectItem::UnnamedExpr(e) if e == expr)
+}
+
 fn build_projection_schema(
     input_schema: &Schema,
     select: &[SelectItem],
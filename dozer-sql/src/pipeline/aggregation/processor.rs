@@ -2,6 +2,8 @@
 use crate::deserialize;
 use crate::pipeline::errors::PipelineError;
 use crate::pipeline::expression::execution::ExpressionExecutor;
+use crate::pipeline::expression::scalar::datetime::current_time;
+use crate::pipeline::product::join::get_composite_key;
 use crate::pipeline::{aggregation::aggregator::Aggregator, expression::execution::Expression};
 use dozer_core::dag::channels::ProcessorChannelForwarder;
 use dozer_core::dag::dag::DEFAULT_PORT_HANDLE;
@@ -63,14 +65,81 @@ impl<'a> AggregationData<'a> {
     }
 }
 
+/// `HAVING` clause, applied to the aggregated records
+#[derive(Debug)]
+pub struct HavingFilter {
+    expression: Box<Expression>,
+    /// Schema of the aggregated records, including the measures only computed for the clause
+    schema: Schema,
+    /// Number of fields of the aggregated records that are part of the output
+    output_len: usize,
+}
+
+impl HavingFilter {
+    pub fn new(expression: Box<Expression>, schema: Schema, output_len: usize) -> Self {
+        Self {
+            expression,
+            schema,
+            output_len,
+        }
+    }
+
+    fn is_selected(&self, record: &Record) -> Result<bool, PipelineError> {
+        Ok(self.expression.evaluate(record, &self.schema)? == Field::Boolean(true))
+    }
+
+    fn strip(&self, mut record: Record) -> Record {
+        record.values.truncate(self.output_len);
+        record
+    }
+
+    /// Filters an operation on the aggregated records. A group that stops or starts fulfilling
+    /// the clause is retracted or inserted.
+    fn apply(&self, op: Operation) -> Result<Option<Operation>, PipelineError> {
+        let op = match op {
+            Operation::Insert { new } => match self.is_selected(&new)? {
+                true => Some(Operation::Insert {
+                    new: self.strip(new),
+                }),
+                false => None,
+            },
+            Operation::Delete { old } => match self.is_selected(&old)? {
+                true => Some(Operation::Delete {
+                    old: self.strip(old),
+                }),
+                false => None,
+            },
+            Operation::Update { old, new } => {
+                match (self.is_selected(&old)?, self.is_selected(&new)?) {
+                    (true, true) => Some(Operation::Update {
+                        old: self.strip(old),
+                        new: self.strip(new),
+                    }),
+                    (true, false) => Some(Operation::Delete {
+                        old: self.strip(old),
+                    }),
+                    (false, true) => Some(Operation::Insert {
+                        new: self.strip(new),
+                    }),
+                    (false, false) => None,
+                }
+            }
+        };
+        Ok(op)
+    }
+}
+
 #[derive(Debug)]
 pub struct AggregationProcessor {
+    /// Expressions identifying the group of a record, whether or not they are part of the output
+    group_by: Vec<Box<Expression>>,
     out_dimensions: Vec<(Box<Expression>, usize)>,
     out_measures: Vec<(Box<Expression>, Box<Aggregator>, usize)>,
     pub db: Option<Database>,
     meta_db: Option<Database>,
     aggregators_db: Option<Database>,
     input_schema: Schema,
+    having: Option<HavingFilter>,
 }
 
 enum AggregatorOperation {
@@ -85,15 +154,37 @@ const AGG_COUNT_DATASET_ID: u16 = 0x0001_u16;
 const AGG_DEFAULT_DIMENSION_ID: u8 = 0xFF_u8;
 
 impl AggregationProcessor {
-    pub fn new(output_field_rules: Vec<FieldRule>, input_schema: Schema) -> Self {
-        let (out_measures, out_dimensions) = populate_rules(&output_field_rules).unwrap();
+    pub fn new(
+        output_field_rules: Vec<FieldRule>,
+        input_schema: Schema,
+        having: Option<HavingFilter>,
+    ) -> Self {
+        let (out_measures, out_dimensions, group_by) = populate_rules(&output_field_rules).unwrap();
         Self {
+            group_by,
             out_dimensions,
             out_measures,
             db: None,
             meta_db: None,
             aggregators_db: None,
             input_schema,
+            having,
+        }
+    }
+
+    fn set_now(&mut self) {
+        let now = current_time();
+        let dimensions = self.out_dimensions.iter_mut().map(|(e, _)| e);
+        let measures = self.out_measures.iter_mut().map(|(e, _, _)| e);
+        let having = self.having.iter_mut().map(|having| &mut having.expression);
+        for expression in self
+            .group_by
+            .iter_mut()
+            .chain(dimensions)
+            .chain(measures)
+            .chain(having)
+        {
+            expression.set_now(&now);
         }
     }
 
@@ -346,9 +437,8 @@ impl AggregationProcessor {
         let mut out_rec_insert = Record::nulls(None, size, None);
         let mut out_rec_delete = Record::nulls(None, size, None);
 
-        let record_hash = if !self.out_dimensions.is_empty() {
-            get_key(&self.input_schema, old, &self.out_dimensions)?
-            //old.get_key(&self.out_dimensions.iter().map(|i| i.0).collect())
+        let record_hash = if !self.group_by.is_empty() {
+            get_key(&self.input_schema, old, &self.group_by)?
         } else {
             vec![AGG_DEFAULT_DIMENSION_ID]
         };
@@ -383,7 +473,8 @@ impl AggregationProcessor {
             }
         };
 
-        if prev_count > 0 {
+        // The state of an emptied group is dropped, so that it starts over if records come back
+        if prev_count > 1 {
             txn.put(db, record_key.as_slice(), new_state.as_slice())?;
         } else {
             let _ = txn.del(db, record_key.as_slice(), None)?;
@@ -401,9 +492,8 @@ impl AggregationProcessor {
         let mut out_rec_insert = Record::nulls(None, size, None);
         let mut out_rec_delete = Record::nulls(None, size, None);
 
-        let record_hash = if !self.out_dimensions.is_empty() {
-            get_key(&self.input_schema, new, &self.out_dimensions)?
-            //new.get_key(&self.out_dimensions.iter().map(|i| i.0).collect())
+        let record_hash = if !self.group_by.is_empty() {
+            get_key(&self.input_schema, new, &self.group_by)?
         } else {
             vec![AGG_DEFAULT_DIMENSION_ID]
         };
@@ -486,64 +576,75 @@ impl AggregationProcessor {
         db: Database,
         op: Operation,
     ) -> Result<Vec<Operation>, PipelineError> {
-        match op {
-            Operation::Insert { ref new } => Ok(vec![self.agg_insert(txn, db, new)?]),
-            Operation::Delete { ref old } => Ok(vec![self.agg_delete(txn, db, old)?]),
+        let ops = match op {
+            Operation::Insert { ref new } => vec![self.agg_insert(txn, db, new)?],
+            Operation::Delete { ref old } => vec![self.agg_delete(txn, db, old)?],
             Operation::Update { ref old, ref new } => {
-                let (old_record_hash, new_record_hash) = if self.out_dimensions.is_empty() {
+                let (old_record_hash, new_record_hash) = if self.group_by.is_empty() {
                     (
                         vec![AGG_DEFAULT_DIMENSION_ID],
                         vec![AGG_DEFAULT_DIMENSION_ID],
                     )
                 } else {
                     (
-                        get_key(&self.input_schema, old, &self.out_dimensions)?,
-                        get_key(&self.input_schema, new, &self.out_dimensions)?,
+                        get_key(&self.input_schema, old, &self.group_by)?,
+                        get_key(&self.input_schema, new, &self.group_by)?,
                     )
-                    //let record_keys: Vec<usize> = self.out_dimensions.iter().map(|i| i.0).collect();
-                    //(old.get_key(&record_keys), new.get_key(&record_keys))
                 };
 
                 if old_record_hash == new_record_hash {
-                    Ok(vec![self.agg_update(txn, db, old, new, old_record_hash)?])
+                    vec![self.agg_update(txn, db, old, new, old_record_hash)?]
                 } else {
-                    Ok(vec![
+                    vec![
                         self.agg_delete(txn, db, old)?,
                         self.agg_insert(txn, db, new)?,
-                    ])
+                    ]
                 }
             }
+        };
+
+        match &self.having {
+            Some(having) => ops
+                .into_iter()
+                .filter_map(|op| having.apply(op).transpose())
+                .collect(),
+            None => Ok(ops),
         }
     }
 }
 
+/// Returns the key of the group `record` belongs to.
 fn get_key(
     schema: &Schema,
     record: &Record,
-    out_dimensions: &[(Box<Expression>, usize)],
+    group_by: &[Box<Expression>],
 ) -> Result<Vec<u8>, PipelineError> {
-    let mut tot_size = 0_usize;
-    let mut buffers = Vec::<Vec<u8>>::with_capacity(out_dimensions.len());
-
-    for dimension in out_dimensions.iter() {
-        let value = dimension.0.evaluate(record, schema)?;
-        let bytes = value.encode();
-        tot_size += bytes.len();
-        buffers.push(bytes);
-    }
-
-    let mut res_buffer = Vec::<u8>::with_capacity(tot_size);
-    for i in buffers {
-        res_buffer.extend(i);
-    }
-    Ok(res_buffer)
+    let values = group_by
+        .iter()
+        .map(|expression| expression.evaluate(record, schema))
+        .collect::<Result<Vec<Field>, PipelineError>>()?;
+    let key_indexes = (0..values.len()).collect::<Vec<usize>>();
+    Ok(get_composite_key(
+        &Record::new(None, values, None),
+        &key_indexes,
+    )?)
 }
 
 impl Processor for AggregationProcessor {
     fn init(&mut self, state: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+        self.set_now();
         internal_err!(self.init_store(state))
     }
 
+    fn flush(
+        &mut self,
+        _fw: &mut dyn ProcessorChannelForwarder,
+        _tx: &SharedTransaction,
+    ) -> Result<(), ExecutionError> {
+        self.set_now();
+        Ok(())
+    }
+
     fn commit(&self, _epoch: &Epoch, _tx: &SharedTransaction) -> Result<(), ExecutionError> {
         Ok(())
     }
@@ -558,7 +659,7 @@ impl Processor for AggregationProcessor {
     ) -> Result<(), ExecutionError> {
         match self.db {
             Some(d) => {
-                let ops = internal_err!(self.aggregate(&mut txn.write(), d, op))?;
+                let ops = self.aggregate(&mut txn.write(), d, op)?;
                 for fop in ops {
                     fw.send(fop, DEFAULT_PORT_HANDLE)?;
                 }
@@ -572,11 +673,13 @@ impl Processor for AggregationProcessor {
 type OutputRules = (
     Vec<(Box<Expression>, Box<Aggregator>, usize)>,
     Vec<(Box<Expression>, usize)>,
+    Vec<Box<Expression>>,
 );
 
 fn populate_rules(output_field_rules: &[FieldRule]) -> Result<OutputRules, PipelineError> {
     let mut out_measures: Vec<(Box<Expression>, Box<Aggregator>, usize)> = Vec::new();
     let mut out_dimensions: Vec<(Box<Expression>, usize)> = Vec::new();
+    let mut group_by: Vec<Box<Expression>> = Vec::new();
 
     for rule in output_field_rules.iter().enumerate() {
         match rule.1 {
@@ -587,9 +690,10 @@ fn populate_rules(output_field_rules: &[FieldRule]) -> Result<OutputRules, Pipel
                 if *is_value {
                     out_dimensions.push((expression.clone(), rule.0));
                 }
+                group_by.push(expression.clone());
             }
         }
     }
 
-    Ok((out_measures, out_dimensions))
+    Ok((out_measures, out_dimensions, group_by))
 }
@@ -3,6 +3,10 @@ mod aggregation_avg_tests;
 #[cfg(test)]
 mod aggregation_count_tests;
 #[cfg(test)]
+mod aggregation_group_by_tests;
+#[cfg(test)]
+mod aggregation_having_tests;
+#[cfg(test)]
 mod aggregation_max_tests;
 #[cfg(test)]
 mod aggregation_min_tests;
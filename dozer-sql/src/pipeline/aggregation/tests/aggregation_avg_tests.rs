@@ -1,12 +1,15 @@
 use crate::output;
+use crate::pipeline::aggregation::factory::AggregationProcessorFactory;
 use crate::pipeline::aggregation::tests::aggregation_tests_utils::{
     delete_exp, delete_field, get_decimal_div_field, get_decimal_field, init_input_schema,
-    init_processor, insert_exp, insert_field, update_exp, update_field, FIELD_0_FLOAT, FIELD_0_INT,
+    init_processor, insert_exp, insert_field, update_exp, update_field, FIELD_0_FLOAT,
     FIELD_100_FLOAT, FIELD_100_INT, FIELD_200_FLOAT, FIELD_200_INT, FIELD_250_DIV_3_FLOAT,
-    FIELD_250_DIV_3_INT, FIELD_350_DIV_3_FLOAT, FIELD_350_DIV_3_INT, FIELD_50_FLOAT, FIELD_50_INT,
-    FIELD_75_FLOAT, FIELD_75_INT, FIELD_NULL, ITALY, SINGAPORE,
+    FIELD_350_DIV_3_FLOAT, FIELD_50_FLOAT, FIELD_50_INT, FIELD_75_FLOAT, FIELD_NULL, ITALY,
+    SINGAPORE,
 };
+use crate::pipeline::builder::get_select;
 use dozer_core::dag::dag::DEFAULT_PORT_HANDLE;
+use dozer_core::dag::node::ProcessorFactory;
 use dozer_types::types::FieldType::{Decimal, Float, Int};
 use std::collections::HashMap;
 
@@ -152,7 +155,7 @@ fn test_avg_aggregation_int() {
     */
     let mut inp = insert_field(ITALY, FIELD_100_INT);
     let mut out = output!(processor, inp, tx);
-    let mut exp = vec![insert_exp(ITALY, FIELD_100_INT)];
+    let mut exp = vec![insert_exp(ITALY, &get_decimal_field(100))];
     assert_eq!(out, exp);
 
     // Insert another 100 for segment Italy
@@ -164,7 +167,12 @@ fn test_avg_aggregation_int() {
     */
     inp = insert_field(ITALY, FIELD_100_INT);
     out = output!(processor, inp, tx);
-    exp = vec![update_exp(ITALY, ITALY, FIELD_100_INT, FIELD_100_INT)];
+    exp = vec![update_exp(
+        ITALY,
+        ITALY,
+        &get_decimal_field(100),
+        &get_decimal_field(100),
+    )];
     assert_eq!(out, exp);
 
     // Insert 50 for segment Singapore
@@ -180,7 +188,7 @@ fn test_avg_aggregation_int() {
     */
     inp = insert_field(SINGAPORE, FIELD_50_INT);
     out = output!(processor, inp, tx);
-    exp = vec![insert_exp(SINGAPORE, FIELD_50_INT)];
+    exp = vec![insert_exp(SINGAPORE, &get_decimal_field(50))];
     assert_eq!(out, exp);
 
     // Update Singapore segment to Italy
@@ -194,8 +202,13 @@ fn test_avg_aggregation_int() {
     inp = update_field(SINGAPORE, ITALY, FIELD_50_INT, FIELD_50_INT);
     out = output!(processor, inp, tx);
     exp = vec![
-        delete_exp(SINGAPORE, FIELD_50_INT),
-        update_exp(ITALY, ITALY, FIELD_100_INT, FIELD_250_DIV_3_INT),
+        delete_exp(SINGAPORE, &get_decimal_field(50)),
+        update_exp(
+            ITALY,
+            ITALY,
+            &get_decimal_field(100),
+            &get_decimal_div_field(250, 3),
+        ),
     ];
     assert_eq!(out, exp);
 
@@ -212,8 +225,8 @@ fn test_avg_aggregation_int() {
     exp = vec![update_exp(
         ITALY,
         ITALY,
-        FIELD_250_DIV_3_INT,
-        FIELD_350_DIV_3_INT,
+        &get_decimal_div_field(250, 3),
+        &get_decimal_div_field(350, 3),
     )];
     assert_eq!(out, exp);
 
@@ -226,7 +239,12 @@ fn test_avg_aggregation_int() {
     */
     inp = delete_field(ITALY, FIELD_200_INT);
     out = output!(processor, inp, tx);
-    exp = vec![update_exp(ITALY, ITALY, FIELD_350_DIV_3_INT, FIELD_75_INT)];
+    exp = vec![update_exp(
+        ITALY,
+        ITALY,
+        &get_decimal_div_field(350, 3),
+        &get_decimal_field(75),
+    )];
     assert_eq!(out, exp);
 
     // Delete another record (50)
@@ -237,7 +255,12 @@ fn test_avg_aggregation_int() {
     */
     inp = delete_field(ITALY, FIELD_50_INT);
     out = output!(processor, inp, tx);
-    exp = vec![update_exp(ITALY, ITALY, FIELD_75_INT, FIELD_100_INT)];
+    exp = vec![update_exp(
+        ITALY,
+        ITALY,
+        &get_decimal_field(75),
+        &get_decimal_field(100),
+    )];
     assert_eq!(out, exp);
 
     // Delete last record
@@ -247,7 +270,7 @@ fn test_avg_aggregation_int() {
     */
     inp = delete_field(ITALY, FIELD_100_INT);
     out = output!(processor, inp, tx);
-    exp = vec![delete_exp(ITALY, FIELD_100_INT)];
+    exp = vec![delete_exp(ITALY, &get_decimal_field(100))];
     assert_eq!(out, exp);
 }
 
@@ -418,7 +441,7 @@ fn test_avg_aggregation_int_null() {
     */
     let mut inp = insert_field(ITALY, FIELD_NULL);
     let mut out = output!(processor, inp, tx);
-    let mut exp = vec![insert_exp(ITALY, FIELD_0_INT)];
+    let mut exp = vec![insert_exp(ITALY, &get_decimal_field(0))];
     assert_eq!(out, exp);
 
     // Insert 100 for segment Italy
@@ -430,7 +453,12 @@ fn test_avg_aggregation_int_null() {
     */
     inp = insert_field(ITALY, FIELD_100_INT);
     out = output!(processor, inp, tx);
-    exp = vec![update_exp(ITALY, ITALY, FIELD_0_INT, FIELD_50_INT)];
+    exp = vec![update_exp(
+        ITALY,
+        ITALY,
+        &get_decimal_field(0),
+        &get_decimal_field(50),
+    )];
     assert_eq!(out, exp);
 
     // Update 100 for segment Italy to NULL
@@ -442,7 +470,12 @@ fn test_avg_aggregation_int_null() {
     */
     inp = update_field(ITALY, ITALY, FIELD_100_INT, FIELD_NULL);
     out = output!(processor, inp, tx);
-    exp = vec![update_exp(ITALY, ITALY, FIELD_50_INT, FIELD_0_INT)];
+    exp = vec![update_exp(
+        ITALY,
+        ITALY,
+        &get_decimal_field(50),
+        &get_decimal_field(0),
+    )];
     assert_eq!(out, exp);
 
     // Delete a record
@@ -453,7 +486,12 @@ fn test_avg_aggregation_int_null() {
     */
     inp = delete_field(ITALY, FIELD_NULL);
     out = output!(processor, inp, tx);
-    exp = vec![update_exp(ITALY, ITALY, FIELD_0_INT, FIELD_0_INT)];
+    exp = vec![update_exp(
+        ITALY,
+        ITALY,
+        &get_decimal_field(0),
+        &get_decimal_field(0),
+    )];
     assert_eq!(out, exp);
 
     // Delete last record
@@ -463,7 +501,7 @@ fn test_avg_aggregation_int_null() {
     */
     inp = delete_field(ITALY, FIELD_NULL);
     out = output!(processor, inp, tx);
-    exp = vec![delete_exp(ITALY, FIELD_0_INT)];
+    exp = vec![delete_exp(ITALY, &get_decimal_field(0))];
     assert_eq!(out, exp);
 }
 
@@ -617,3 +655,56 @@ fn test_avg_aggregation_decimal_null() {
     exp = vec![delete_exp(ITALY, &get_decimal_field(0))];
     assert_eq!(out, exp);
 }
+
+#[test]
+fn test_avg_aggregation_empty_group() {
+    let schema = init_input_schema(Int, "AVG");
+    let select = get_select("SELECT Country, AVG(Salary) FROM Users GROUP BY Country").unwrap();
+    let out_schema = AggregationProcessorFactory::new(select.projection, select.group_by, None)
+        .get_output_schema(
+            &DEFAULT_PORT_HANDLE,
+            &[(DEFAULT_PORT_HANDLE, schema.clone())]
+                .into_iter()
+                .collect(),
+        )
+        .unwrap();
+    assert_eq!(out_schema.fields[1].typ, Decimal);
+
+    let (processor, tx) = init_processor(
+        "SELECT Country, AVG(Salary) FROM Users GROUP BY Country",
+        HashMap::from([(DEFAULT_PORT_HANDLE, schema)]),
+    )
+    .unwrap();
+
+    let mut inp = insert_field(ITALY, FIELD_100_INT);
+    let mut out = output!(processor, inp, tx);
+    let mut exp = vec![insert_exp(ITALY, &get_decimal_field(100))];
+    assert_eq!(out, exp);
+
+    // Deleting the last record retracts the group
+    inp = delete_field(ITALY, FIELD_100_INT);
+    out = output!(processor, inp, tx);
+    exp = vec![delete_exp(ITALY, &get_decimal_field(100))];
+    assert_eq!(out, exp);
+
+    // The group starts over
+    inp = insert_field(ITALY, FIELD_50_INT);
+    out = output!(processor, inp, tx);
+    exp = vec![insert_exp(ITALY, &get_decimal_field(50))];
+    assert_eq!(out, exp);
+
+    inp = update_field(ITALY, ITALY, FIELD_50_INT, FIELD_200_INT);
+    out = output!(processor, inp, tx);
+    exp = vec![update_exp(
+        ITALY,
+        ITALY,
+        &get_decimal_field(50),
+        &get_decimal_field(200),
+    )];
+    assert_eq!(out, exp);
+
+    inp = delete_field(ITALY, FIELD_200_INT);
+    out = output!(processor, inp, tx);
+    exp = vec![delete_exp(ITALY, &get_decimal_field(200))];
+    assert_eq!(out, exp);
+}
@@ -0,0 +1,201 @@
+use crate::output;
+use crate::pipeline::aggregation::tests::aggregation_tests_utils::{
+    init_processor, FIELD_100_INT, FIELD_150_INT, FIELD_1_INT, FIELD_200_INT, FIELD_250_INT,
+    FIELD_2_INT, FIELD_50_INT, ITALY, SINGAPORE,
+};
+use dozer_core::dag::dag::DEFAULT_PORT_HANDLE;
+use dozer_types::types::{Field, FieldDefinition, FieldType, Operation, Record, Schema};
+use std::collections::HashMap;
+
+fn get_schema() -> Schema {
+    Schema::empty()
+        .field(
+            FieldDefinition::new(String::from("ID"), FieldType::Int, false),
+            false,
+        )
+        .field(
+            FieldDefinition::new(String::from("Country"), FieldType::String, false),
+            false,
+        )
+        .field(
+            FieldDefinition::new(String::from("Salary"), FieldType::Int, false),
+            false,
+        )
+        .clone()
+}
+
+fn user(id: i64, country: &str, salary: &Field) -> Record {
+    Record::new(
+        None,
+        vec![
+            Field::Int(id),
+            Field::String(country.to_string()),
+            salary.clone(),
+        ],
+        None,
+    )
+}
+
+fn group(country: &str, measures: &[&Field]) -> Record {
+    let mut values = measures
+        .iter()
+        .map(|f| (*f).clone())
+        .collect::<Vec<Field>>();
+    values.push(Field::String(country.to_string()));
+    Record::new(None, values, None)
+}
+
+#[test]
+fn test_multiple_aggregates() {
+    let (processor, tx) = init_processor(
+        "SELECT COUNT(Salary), SUM(Salary), MIN(Salary), MAX(Salary), Country \
+        FROM Users GROUP BY Country",
+        HashMap::from([(DEFAULT_PORT_HANDLE, get_schema())]),
+    )
+    .unwrap();
+
+    let inp = Operation::Insert {
+        new: user(1, ITALY, FIELD_100_INT),
+    };
+    assert_eq!(
+        output!(processor, inp, tx),
+        vec![Operation::Insert {
+            new: group(
+                ITALY,
+                &[FIELD_1_INT, FIELD_100_INT, FIELD_100_INT, FIELD_100_INT]
+            ),
+        }]
+    );
+
+    let inp = Operation::Insert {
+        new: user(2, ITALY, FIELD_50_INT),
+    };
+    assert_eq!(
+        output!(processor, inp, tx),
+        vec![Operation::Update {
+            old: group(
+                ITALY,
+                &[FIELD_1_INT, FIELD_100_INT, FIELD_100_INT, FIELD_100_INT]
+            ),
+            new: group(
+                ITALY,
+                &[FIELD_2_INT, FIELD_150_INT, FIELD_50_INT, FIELD_100_INT]
+            ),
+        }]
+    );
+
+    let inp = Operation::Update {
+        old: user(1, ITALY, FIELD_100_INT),
+        new: user(1, ITALY, FIELD_200_INT),
+    };
+    assert_eq!(
+        output!(processor, inp, tx),
+        vec![Operation::Update {
+            old: group(
+                ITALY,
+                &[FIELD_2_INT, FIELD_150_INT, FIELD_50_INT, FIELD_100_INT]
+            ),
+            new: group(
+                ITALY,
+                &[FIELD_2_INT, FIELD_250_INT, FIELD_50_INT, FIELD_200_INT]
+            ),
+        }]
+    );
+
+    // Moving a record to another group updates both
+    let inp = Operation::Update {
+        old: user(2, ITALY, FIELD_50_INT),
+        new: user(2, SINGAPORE, FIELD_50_INT),
+    };
+    assert_eq!(
+        output!(processor, inp, tx),
+        vec![
+            Operation::Update {
+                old: group(
+                    ITALY,
+                    &[FIELD_2_INT, FIELD_250_INT, FIELD_50_INT, FIELD_200_INT]
+                ),
+                new: group(
+                    ITALY,
+                    &[FIELD_1_INT, FIELD_200_INT, FIELD_200_INT, FIELD_200_INT]
+                ),
+            },
+            Operation::Insert {
+                new: group(
+                    SINGAPORE,
+                    &[FIELD_1_INT, FIELD_50_INT, FIELD_50_INT, FIELD_50_INT]
+                ),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_group_by_column_not_selected() {
+    let (processor, tx) = init_processor(
+        "SELECT SUM(Salary) FROM Users GROUP BY Country",
+        HashMap::from([(DEFAULT_PORT_HANDLE, get_schema())]),
+    )
+    .unwrap();
+
+    let sum = |salary: &Field| Record::new(None, vec![salary.clone()], None);
+
+    let inp = Operation::Insert {
+        new: user(1, ITALY, FIELD_100_INT),
+    };
+    assert_eq!(
+        output!(processor, inp, tx),
+        vec![Operation::Insert {
+            new: sum(FIELD_100_INT)
+        }]
+    );
+
+    // Singapore is a group of its own, even though Country isn't in the output
+    let inp = Operation::Insert {
+        new: user(2, SINGAPORE, FIELD_50_INT),
+    };
+    assert_eq!(
+        output!(processor, inp, tx),
+        vec![Operation::Insert {
+            new: sum(FIELD_50_INT)
+        }]
+    );
+}
+
+#[test]
+fn test_group_by_composite_key() {
+    let (processor, tx) = init_processor(
+        "SELECT Country, SUM(Salary) FROM Users GROUP BY Country, ID",
+        HashMap::from([(DEFAULT_PORT_HANDLE, get_schema())]),
+    )
+    .unwrap();
+
+    let inp = Operation::Insert {
+        new: user(1, ITALY, FIELD_100_INT),
+    };
+    assert_eq!(
+        output!(processor, inp, tx),
+        vec![Operation::Insert {
+            new: Record::new(
+                None,
+                vec![Field::String(ITALY.to_string()), FIELD_100_INT.clone()],
+                None
+            ),
+        }]
+    );
+
+    // Same country, different ID
+    let inp = Operation::Insert {
+        new: user(2, ITALY, FIELD_50_INT),
+    };
+    assert_eq!(
+        output!(processor, inp, tx),
+        vec![Operation::Insert {
+            new: Record::new(
+                None,
+                vec![Field::String(ITALY.to_string()), FIELD_50_INT.clone()],
+                None
+            ),
+        }]
+    );
+}
@@ -0,0 +1,197 @@
+use crate::pipeline::aggregation::factory::AggregationProcessorFactory;
+use crate::pipeline::aggregation::tests::aggregation_tests_utils::{
+    FIELD_100_INT, FIELD_150_INT, FIELD_200_INT, FIELD_2_INT, FIELD_350_INT, FIELD_3_INT,
+    FIELD_50_INT, ITALY,
+};
+use crate::pipeline::builder::get_select;
+use dozer_core::dag::channels::ProcessorChannelForwarder;
+use dozer_core::dag::dag::DEFAULT_PORT_HANDLE;
+use dozer_core::dag::errors::ExecutionError;
+use dozer_core::dag::node::{PortHandle, ProcessorFactory};
+use dozer_core::storage::lmdb_storage::LmdbEnvironmentManager;
+use dozer_types::types::{Field, FieldDefinition, FieldType, Operation, Record, Schema};
+use std::collections::HashMap;
+use tempdir::TempDir;
+
+struct TestChannelForwarder {
+    operations: Vec<Operation>,
+}
+
+impl ProcessorChannelForwarder for TestChannelForwarder {
+    fn send(&mut self, op: Operation, _port: PortHandle) -> Result<(), ExecutionError> {
+        self.operations.push(op);
+        Ok(())
+    }
+}
+
+fn get_schema() -> Schema {
+    Schema::empty()
+        .field(
+            FieldDefinition::new(String::from("ID"), FieldType::Int, false),
+            false,
+        )
+        .field(
+            FieldDefinition::new(String::from("Country"), FieldType::String, false),
+            false,
+        )
+        .field(
+            FieldDefinition::new(String::from("Salary"), FieldType::Int, false),
+            false,
+        )
+        .clone()
+}
+
+fn get_factory(sql: &str) -> AggregationProcessorFactory {
+    let select = get_select(sql).unwrap_or_else(|e| panic!("{}", e.to_string()));
+    AggregationProcessorFactory::new(select.projection, select.group_by, select.having)
+}
+
+fn user(id: i64, salary: &Field) -> Record {
+    Record::new(
+        None,
+        vec![
+            Field::Int(id),
+            Field::String(ITALY.to_string()),
+            salary.clone(),
+        ],
+        None,
+    )
+}
+
+fn group(value: &Field) -> Record {
+    Record::new(
+        None,
+        vec![Field::String(ITALY.to_string()), value.clone()],
+        None,
+    )
+}
+
+fn run_aggregation(sql: &str, ops: Vec<Operation>) -> Vec<Operation> {
+    let mut processor = get_factory(sql)
+        .build(
+            HashMap::from([(DEFAULT_PORT_HANDLE, get_schema())]),
+            HashMap::new(),
+        )
+        .unwrap_or_else(|e| panic!("{}", e.to_string()));
+
+    let tmp_dir = TempDir::new("test").unwrap();
+    let mut storage = LmdbEnvironmentManager::create(tmp_dir.path(), "having_test").unwrap();
+    processor.init(&mut storage).unwrap();
+
+    let tx = storage.create_txn().unwrap();
+    let mut fw = TestChannelForwarder { operations: vec![] };
+    for op in ops {
+        processor
+            .process(DEFAULT_PORT_HANDLE, op, &mut fw, &tx, &HashMap::new())
+            .unwrap();
+    }
+    fw.operations
+}
+
+#[test]
+fn test_having_output_schema() {
+    let out_schema =
+        get_factory("SELECT Country, SUM(Salary) FROM Users GROUP BY Country HAVING COUNT(ID) > 1")
+            .get_output_schema(
+                &DEFAULT_PORT_HANDLE,
+                &[(DEFAULT_PORT_HANDLE, get_schema())].into_iter().collect(),
+            )
+            .unwrap();
+
+    // COUNT(ID) is only computed for the HAVING clause
+    let field_names: Vec<String> = out_schema
+        .fields
+        .into_iter()
+        .map(|field| field.name)
+        .collect();
+    assert_eq!(field_names, vec!["Country", "SUM(Salary)"]);
+}
+
+#[test]
+fn test_having_threshold() {
+    let ops = vec![
+        Operation::Insert {
+            new: user(1, FIELD_100_INT),
+        },
+        // COUNT goes 1 -> 2, the group starts fulfilling the clause
+        Operation::Insert {
+            new: user(2, FIELD_50_INT),
+        },
+        Operation::Insert {
+            new: user(3, FIELD_200_INT),
+        },
+        Operation::Delete {
+            old: user(3, FIELD_200_INT),
+        },
+        // COUNT goes 2 -> 1, the group is retracted
+        Operation::Delete {
+            old: user(2, FIELD_50_INT),
+        },
+        Operation::Delete {
+            old: user(1, FIELD_100_INT),
+        },
+    ];
+
+    assert_eq!(
+        run_aggregation(
+            "SELECT Country, SUM(Salary) FROM Users GROUP BY Country HAVING COUNT(ID) > 1",
+            ops
+        ),
+        vec![
+            Operation::Insert {
+                new: group(FIELD_150_INT),
+            },
+            Operation::Update {
+                old: group(FIELD_150_INT),
+                new: group(FIELD_350_INT),
+            },
+            Operation::Update {
+                old: group(FIELD_350_INT),
+                new: group(FIELD_150_INT),
+            },
+            Operation::Delete {
+                old: group(FIELD_150_INT),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_having_projected_aggregate() {
+    let ops = vec![
+        Operation::Insert {
+            new: user(1, FIELD_100_INT),
+        },
+        Operation::Insert {
+            new: user(2, FIELD_100_INT),
+        },
+        Operation::Insert {
+            new: user(3, FIELD_100_INT),
+        },
+        // Updates can retract the group as well
+        Operation::Update {
+            old: user(3, FIELD_100_INT),
+            new: user(3, FIELD_350_INT),
+        },
+    ];
+
+    assert_eq!(
+        run_aggregation(
+            "SELECT Country, COUNT(ID) FROM Users GROUP BY Country \
+            HAVING COUNT(ID) > 1 AND MAX(Salary) < 200",
+            ops
+        ),
+        vec![
+            Operation::Insert {
+                new: group(FIELD_2_INT),
+            },
+            Operation::Update {
+                old: group(FIELD_2_INT),
+                new: group(FIELD_3_INT),
+            },
+            Operation::Delete {
+                old: group(FIELD_3_INT),
+            },
+        ]
+    );
+}
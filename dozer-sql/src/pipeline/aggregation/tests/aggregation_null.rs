@@ -61,7 +61,7 @@ fn test_aggregation_alias() {
 
     let select = get_select("SELECT ID, SUM(Salary) as Salaries FROM Users GROUP BY ID").unwrap();
 
-    let factory = AggregationProcessorFactory::new(select.projection, select.group_by);
+    let factory = AggregationProcessorFactory::new(select.projection, select.group_by, None);
     let out_schema = factory
         .get_output_schema(
             &DEFAULT_PORT_HANDLE,
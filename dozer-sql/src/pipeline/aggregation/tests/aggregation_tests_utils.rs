@@ -38,7 +38,7 @@ pub(crate) fn init_processor(
         input_schema,
     )?;
 
-    let mut processor = AggregationProcessor::new(output_field_rules, input_schema.clone());
+    let mut processor = AggregationProcessor::new(output_field_rules, input_schema.clone(), None);
 
     let mut storage = LmdbEnvironmentManager::create(Path::new("/tmp"), "aggregation_test")
         .unwrap_or_else(|e| panic!("{}", e.to_string()));
@@ -227,7 +227,4 @@ pub const FIELD_150_INT: &Field = &Field::Int(150);
 pub const FIELD_200_INT: &Field = &Field::Int(200);
 pub const FIELD_250_INT: &Field = &Field::Int(250);
 pub const FIELD_350_INT: &Field = &Field::Int(350);
-pub const FIELD_75_INT: &Field = &Field::Int(75);
 pub const FIELD_50_INT: &Field = &Field::Int(50);
-pub const FIELD_250_DIV_3_INT: &Field = &Field::Int(250 / 3);
-pub const FIELD_350_DIV_3_INT: &Field = &Field::Int(350 / 3);
@@ -1,52 +1,215 @@
 use super::aggregation::factory::AggregationProcessorFactory;
-use super::product::factory::get_input_tables;
+use super::distinct::factory::DistinctProcessorFactory;
+use super::expression::builder::normalize_ident;
+use super::limit::factory::LimitProcessorFactory;
+use super::order_by::factory::OrderByProcessorFactory;
+use super::product::factory::get_input_table_names;
 use super::product::factory::ProductProcessorFactory;
 use super::selection::factory::SelectionProcessorFactory;
+use super::union::factory::{UnionProcessorFactory, UNION_LEFT_PORT, UNION_RIGHT_PORT};
 use crate::pipeline::errors::PipelineError;
 use crate::pipeline::errors::PipelineError::InvalidQuery;
 use dozer_core::dag::app::AppPipeline;
 use dozer_core::dag::app::PipelineEntryPoint;
 use dozer_core::dag::dag::DEFAULT_PORT_HANDLE;
 use dozer_core::dag::node::PortHandle;
-use sqlparser::ast::{Query, Select, SetExpr, Statement};
+use sqlparser::ast::{Expr, Query, Select, SetExpr, SetOperator, Statement, Value};
 use sqlparser::dialect::AnsiDialect;
 use sqlparser::parser::Parser;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use dozer_core::dag::appsource::AppSourceId;
 
 pub struct PipelineBuilder {}
 
+/// Name and port of the node producing the query output, which sinks should be connected to
+pub type OutputNode = (String, PortHandle);
+
+/// Maps the name of each CTE declared in a `WITH` clause to the node producing its output
+type CteNodes = HashMap<String, String>;
+
 impl PipelineBuilder {
-    pub fn build_pipeline(&self, sql: &str) -> Result<AppPipeline, PipelineError> {
+    pub fn build_pipeline(&self, sql: &str) -> Result<(AppPipeline, OutputNode), PipelineError> {
         let statement = get_statement(sql)?;
-        let query = get_query(statement)?;
-        self.select_to_pipeline(*query)
+        self.statement_to_pipeline(statement)
     }
     pub fn statement_to_pipeline(
         &self,
         statement: Statement,
-    ) -> Result<AppPipeline, PipelineError> {
+    ) -> Result<(AppPipeline, OutputNode), PipelineError> {
         match statement {
             Statement::Query(query) => self.query_to_pipeline(*query),
             _ => Err(InvalidQuery(statement.to_string())),
         }
     }
 
-    pub fn query_to_pipeline(&self, query: Query) -> Result<AppPipeline, PipelineError> {
-        self.set_expr_to_pipeline(*query.body)
+    pub fn query_to_pipeline(
+        &self,
+        query: Query,
+    ) -> Result<(AppPipeline, OutputNode), PipelineError> {
+        let mut pipeline = AppPipeline::new();
+        let output_node = self.query_to_nodes(&mut pipeline, query, "", &CteNodes::new())?;
+        Ok((pipeline, (output_node, DEFAULT_PORT_HANDLE)))
+    }
+
+    /// Adds the nodes of `query` to the pipeline, prefixing their names with `prefix`.
+    /// Returns the name of the node producing the query output.
+    fn query_to_nodes(
+        &self,
+        pipeline: &mut AppPipeline,
+        query: Query,
+        prefix: &str,
+        cte_nodes: &CteNodes,
+    ) -> Result<String, PipelineError> {
+        let mut cte_nodes = cte_nodes.clone();
+
+        if let Some(with) = query.with {
+            if with.recursive {
+                return Err(InvalidQuery("Recursive CTEs are not supported".to_string()));
+            }
+
+            let mut declared = vec![];
+            for cte in with.cte_tables {
+                let name = normalize_ident(&cte.alias.name);
+                if declared.contains(&name) {
+                    return Err(InvalidQuery(format!(
+                        "CTE {} is defined more than once",
+                        name
+                    )));
+                }
+                if !cte.alias.columns.is_empty() {
+                    return Err(InvalidQuery(format!(
+                        "Column aliases are not supported for CTE {}",
+                        name
+                    )));
+                }
+
+                // Each CTE is built as a sub-pipeline which can reference the CTEs declared before it
+                let cte_prefix = format!("{}{}_", prefix, name);
+                let output_node =
+                    self.query_to_nodes(pipeline, cte.query, &cte_prefix, &cte_nodes)?;
+                cte_nodes.insert(name.clone(), output_node);
+                declared.push(name);
+            }
+        }
+
+        let mut output_node = self.set_expr_to_nodes(pipeline, *query.body, prefix, &cte_nodes)?;
+
+        // ORDER BY clause
+        if !query.order_by.is_empty() {
+            let order_by_name = format!("{}order_by", prefix);
+            let order_by = OrderByProcessorFactory::new(query.order_by);
+            pipeline.add_processor(Arc::new(order_by), &order_by_name, vec![]);
+            pipeline.connect_nodes(
+                &output_node,
+                Some(DEFAULT_PORT_HANDLE),
+                &order_by_name,
+                Some(DEFAULT_PORT_HANDLE),
+            )?;
+            output_node = order_by_name;
+        }
+
+        // LIMIT and OFFSET clauses
+        if query.limit.is_some() || query.offset.is_some() {
+            let limit = query.limit.as_ref().map(get_limit_value).transpose()?;
+            let offset = match &query.offset {
+                Some(offset) => get_limit_value(&offset.value)?,
+                None => 0,
+            };
+            let limit_name = format!("{}limit", prefix);
+            let limit = LimitProcessorFactory::new(limit, offset);
+            pipeline.add_processor(Arc::new(limit), &limit_name, vec![]);
+            pipeline.connect_nodes(
+                &output_node,
+                Some(DEFAULT_PORT_HANDLE),
+                &limit_name,
+                Some(DEFAULT_PORT_HANDLE),
+            )?;
+            output_node = limit_name;
+        }
+
+        Ok(output_node)
     }
 
-    fn set_expr_to_pipeline(&self, set_expr: SetExpr) -> Result<AppPipeline, PipelineError> {
+    fn set_expr_to_nodes(
+        &self,
+        pipeline: &mut AppPipeline,
+        set_expr: SetExpr,
+        prefix: &str,
+        cte_nodes: &CteNodes,
+    ) -> Result<String, PipelineError> {
         match set_expr {
-            SetExpr::Select(s) => self.select_to_pipeline(*s),
-            SetExpr::Query(q) => self.query_to_pipeline(*q),
+            SetExpr::Select(s) => self.select_to_nodes(pipeline, *s, prefix, cte_nodes),
+            SetExpr::Query(q) => self.query_to_nodes(pipeline, *q, prefix, cte_nodes),
+            SetExpr::SetOperation {
+                op: SetOperator::Union,
+                all,
+                left,
+                right,
+            } => self.union_to_nodes(pipeline, *left, *right, all, prefix, cte_nodes),
             _ => Err(InvalidQuery(set_expr.to_string())),
         }
     }
 
-    fn select_to_pipeline(&self, select: Select) -> Result<AppPipeline, PipelineError> {
-        let mut pipeline = AppPipeline::new();
+    /// Both sides are built as sub-pipelines feeding a single union node. A `UNION` without
+    /// `ALL` is a `UNION ALL` followed by a distinct node.
+    fn union_to_nodes(
+        &self,
+        pipeline: &mut AppPipeline,
+        left: SetExpr,
+        right: SetExpr,
+        all: bool,
+        prefix: &str,
+        cte_nodes: &CteNodes,
+    ) -> Result<String, PipelineError> {
+        let left_node =
+            self.set_expr_to_nodes(pipeline, left, &format!("{}left_", prefix), cte_nodes)?;
+        let right_node =
+            self.set_expr_to_nodes(pipeline, right, &format!("{}right_", prefix), cte_nodes)?;
+
+        let union_name = format!("{}union", prefix);
+        let union = UnionProcessorFactory::new();
+        pipeline.add_processor(Arc::new(union), &union_name, vec![]);
+        pipeline.connect_nodes(
+            &left_node,
+            Some(DEFAULT_PORT_HANDLE),
+            &union_name,
+            Some(UNION_LEFT_PORT),
+        )?;
+        pipeline.connect_nodes(
+            &right_node,
+            Some(DEFAULT_PORT_HANDLE),
+            &union_name,
+            Some(UNION_RIGHT_PORT),
+        )?;
+
+        if all {
+            return Ok(union_name);
+        }
+
+        let distinct_name = format!("{}distinct", prefix);
+        let distinct = DistinctProcessorFactory::new();
+        pipeline.add_processor(Arc::new(distinct), &distinct_name, vec![]);
+        pipeline.connect_nodes(
+            &union_name,
+            Some(DEFAULT_PORT_HANDLE),
+            &distinct_name,
+            Some(DEFAULT_PORT_HANDLE),
+        )?;
+        Ok(distinct_name)
+    }
+
+    fn select_to_nodes(
+        &self,
+        pipeline: &mut AppPipeline,
+        select: Select,
+        prefix: &str,
+        cte_nodes: &CteNodes,
+    ) -> Result<String, PipelineError> {
+        let product_name = format!("{}product", prefix);
+        let selection_name = format!("{}selection", prefix);
+        let aggregation_name = format!("{}aggregation", prefix);
 
         // FROM clause
         if select.from.len() != 1 {
@@ -56,63 +219,97 @@ impl PipelineBuilder {
         }
 
         let product = ProductProcessorFactory::new(select.from[0].clone());
-        let input_tables = get_input_tables(&select.from[0])?;
-        let input_endpoints = self.get_input_endpoints(&input_tables)?;
+        let input_tables = get_input_table_names(&select.from[0])?;
 
-        pipeline.add_processor(Arc::new(product), "product", input_endpoints);
+        // Relations referencing a CTE are fed by the CTE output node, the others by the sources
+        let mut input_endpoints = vec![];
+        let mut cte_inputs = vec![];
+        for (input_port, table) in input_tables.iter().enumerate() {
+            match cte_nodes.get(table) {
+                Some(cte_node) => cte_inputs.push((cte_node.clone(), input_port as PortHandle)),
+                None => input_endpoints.push(PipelineEntryPoint::new(
+                    AppSourceId::new(table.clone(), None),
+                    input_port as PortHandle,
+                )),
+            }
+        }
+
+        pipeline.add_processor(Arc::new(product), &product_name, input_endpoints);
+
+        for (cte_node, input_port) in cte_inputs {
+            pipeline.connect_nodes(
+                &cte_node,
+                Some(DEFAULT_PORT_HANDLE),
+                &product_name,
+                Some(input_port),
+            )?;
+        }
 
-        let aggregation =
-            AggregationProcessorFactory::new(select.projection.clone(), select.group_by);
+        let aggregation = AggregationProcessorFactory::new(
+            select.projection.clone(),
+            select.group_by,
+            select.having,
+        );
 
-        pipeline.add_processor(Arc::new(aggregation), "aggregation", vec![]);
+        pipeline.add_processor(Arc::new(aggregation), &aggregation_name, vec![]);
 
         // Where clause
         if let Some(selection) = select.selection {
             let selection = SelectionProcessorFactory::new(selection);
-            // first_node_name = String::from("selection");
 
-            pipeline.add_processor(Arc::new(selection), "selection", vec![]);
+            pipeline.add_processor(Arc::new(selection), &selection_name, vec![]);
 
             pipeline.connect_nodes(
-                "product",
+                &product_name,
                 Some(DEFAULT_PORT_HANDLE),
-                "selection",
+                &selection_name,
                 Some(DEFAULT_PORT_HANDLE),
             )?;
 
             pipeline.connect_nodes(
-                "selection",
+                &selection_name,
                 Some(DEFAULT_PORT_HANDLE),
-                "aggregation",
+                &aggregation_name,
                 Some(DEFAULT_PORT_HANDLE),
             )?;
         } else {
             pipeline.connect_nodes(
-                "product",
+                &product_name,
                 Some(DEFAULT_PORT_HANDLE),
-                "aggregation",
+                &aggregation_name,
                 Some(DEFAULT_PORT_HANDLE),
             )?;
         }
 
-        Ok(pipeline)
-    }
-
-    fn get_input_endpoints(
-        &self,
-        input_tables: &[String],
-    ) -> Result<Vec<PipelineEntryPoint>, PipelineError> {
-        let mut endpoints = vec![];
-
-        for (input_port, table) in input_tables.iter().enumerate() {
-            endpoints.push(PipelineEntryPoint::new(
-                AppSourceId::new(table.clone(), None),
-                input_port as PortHandle,
-            ));
+        // DISTINCT applies to the projected records
+        if select.distinct {
+            let distinct_name = format!("{}distinct", prefix);
+            let distinct = DistinctProcessorFactory::new();
+            pipeline.add_processor(Arc::new(distinct), &distinct_name, vec![]);
+            pipeline.connect_nodes(
+                &aggregation_name,
+                Some(DEFAULT_PORT_HANDLE),
+                &distinct_name,
+                Some(DEFAULT_PORT_HANDLE),
+            )?;
+            return Ok(distinct_name);
         }
 
-        Ok(endpoints)
+        Ok(aggregation_name)
+    }
+}
+
+fn get_limit_value(expr: &Expr) -> Result<u64, PipelineError> {
+    match expr {
+        Expr::Value(Value::Number(n, _)) => n.parse::<u64>().ok(),
+        _ => None,
     }
+    .ok_or_else(|| {
+        InvalidQuery(format!(
+            "LIMIT and OFFSET must be non-negative integers: {}",
+            expr
+        ))
+    })
 }
 
 pub fn get_select(sql: &str) -> Result<Box<Select>, PipelineError> {
@@ -0,0 +1,126 @@
+use crate::pipeline::errors::PipelineError;
+use crate::pipeline::state::{decode_count, get_values_key};
+use dozer_core::dag::channels::ProcessorChannelForwarder;
+use dozer_core::dag::dag::DEFAULT_PORT_HANDLE;
+use dozer_core::dag::epoch::Epoch;
+use dozer_core::dag::errors::ExecutionError;
+use dozer_core::dag::errors::ExecutionError::InternalError;
+use dozer_core::dag::node::{PortHandle, Processor};
+use dozer_core::dag::record_store::RecordReader;
+use dozer_core::storage::common::Database;
+use dozer_core::storage::errors::StorageError::InvalidDatabase;
+use dozer_core::storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
+use dozer_core::storage::prefix_transaction::PrefixTransaction;
+use dozer_types::log::info;
+use dozer_types::types::{Operation, Record};
+use std::collections::HashMap;
+
+const DISTINCT_COUNT_DATASET_ID: u32 = 0x0000_0001_u32;
+
+/// Removes duplicate records from a stream.
+///
+/// Every distinct record is reference counted: it is inserted downstream when its first copy
+/// arrives and retracted when its last copy is deleted. Copies in between are absorbed.
+#[derive(Debug)]
+pub struct DistinctProcessor {
+    /// Number of copies of each record, keyed by its serialized values
+    db: Option<Database>,
+}
+
+impl DistinctProcessor {
+    pub fn new() -> Self {
+        Self { db: None }
+    }
+
+    fn init_store(&mut self, env: &mut LmdbEnvironmentManager) -> Result<(), PipelineError> {
+        self.db = Some(env.open_database("distinct", false)?);
+        Ok(())
+    }
+
+    /// Returns `true` if this is the first copy of `record`.
+    fn increment(&self, record: &Record, tx: &SharedTransaction) -> Result<bool, ExecutionError> {
+        let db = self
+            .db
+            .ok_or(ExecutionError::InternalDatabaseError(InvalidDatabase))?;
+        let mut tx = tx.write();
+        let mut ptx = PrefixTransaction::new(&mut tx, DISTINCT_COUNT_DATASET_ID);
+
+        let key = get_values_key(record)?;
+        let count = decode_count(ptx.get(db, &key)?)?;
+        ptx.put(db, &key, &(count + 1).to_be_bytes())?;
+        Ok(count == 0)
+    }
+
+    /// Returns `true` if this was the last copy of `record`.
+    fn decrement(&self, record: &Record, tx: &SharedTransaction) -> Result<bool, ExecutionError> {
+        let db = self
+            .db
+            .ok_or(ExecutionError::InternalDatabaseError(InvalidDatabase))?;
+        let mut tx = tx.write();
+        let mut ptx = PrefixTransaction::new(&mut tx, DISTINCT_COUNT_DATASET_ID);
+
+        let key = get_values_key(record)?;
+        match decode_count(ptx.get(db, &key)?)? {
+            0 => Ok(false),
+            1 => {
+                ptx.del(db, &key, None)?;
+                Ok(true)
+            }
+            count => {
+                ptx.put(db, &key, &(count - 1).to_be_bytes())?;
+                Ok(false)
+            }
+        }
+    }
+}
+
+impl Default for DistinctProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Processor for DistinctProcessor {
+    fn init(&mut self, env: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+        info!("{:?}", "Initialising Distinct Processor");
+        self.init_store(env).map_err(|e| InternalError(Box::new(e)))
+    }
+
+    fn commit(&self, _epoch: &Epoch, _tx: &SharedTransaction) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn process(
+        &mut self,
+        _from_port: PortHandle,
+        op: Operation,
+        fw: &mut dyn ProcessorChannelForwarder,
+        tx: &SharedTransaction,
+        _reader: &HashMap<PortHandle, RecordReader>,
+    ) -> Result<(), ExecutionError> {
+        let op = match op {
+            Operation::Insert { new } => match self.increment(&new, tx)? {
+                true => Some(Operation::Insert { new }),
+                false => None,
+            },
+            Operation::Delete { old } => match self.decrement(&old, tx)? {
+                true => Some(Operation::Delete { old }),
+                false => None,
+            },
+            Operation::Update { old, new } => {
+                let removed = self.decrement(&old, tx)?;
+                let added = self.increment(&new, tx)?;
+                match (removed, added) {
+                    (true, true) => Some(Operation::Update { old, new }),
+                    (true, false) => Some(Operation::Delete { old }),
+                    (false, true) => Some(Operation::Insert { new }),
+                    (false, false) => None,
+                }
+            }
+        };
+        if let Some(op) = op {
+            fw.send(op, DEFAULT_PORT_HANDLE)?;
+        }
+        Ok(())
+    }
+}
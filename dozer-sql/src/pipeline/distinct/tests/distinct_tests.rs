@@ -0,0 +1,125 @@
+use crate::pipeline::distinct::factory::DistinctProcessorFactory;
+use dozer_core::dag::channels::ProcessorChannelForwarder;
+use dozer_core::dag::dag::DEFAULT_PORT_HANDLE;
+use dozer_core::dag::errors::ExecutionError;
+use dozer_core::dag::node::{PortHandle, ProcessorFactory};
+use dozer_core::storage::lmdb_storage::LmdbEnvironmentManager;
+use dozer_types::types::{Field, Operation, Record};
+use std::collections::HashMap;
+use tempdir::TempDir;
+
+struct TestChannelForwarder {
+    operations: Vec<Operation>,
+}
+
+impl ProcessorChannelForwarder for TestChannelForwarder {
+    fn send(&mut self, op: Operation, _port: PortHandle) -> Result<(), ExecutionError> {
+        self.operations.push(op);
+        Ok(())
+    }
+}
+
+fn last_name(last_name: &str) -> Record {
+    Record::new(None, vec![Field::String(last_name.to_string())], None)
+}
+
+fn insert(name: &str) -> Operation {
+    Operation::Insert {
+        new: last_name(name),
+    }
+}
+
+fn delete(name: &str) -> Operation {
+    Operation::Delete {
+        old: last_name(name),
+    }
+}
+
+fn update(old: &str, new: &str) -> Operation {
+    Operation::Update {
+        old: last_name(old),
+        new: last_name(new),
+    }
+}
+
+fn run_distinct(ops: Vec<Operation>) -> Vec<Operation> {
+    let factory = DistinctProcessorFactory::new();
+    let mut processor = factory.build(HashMap::new(), HashMap::new()).unwrap();
+
+    let tmp_dir = TempDir::new("test").unwrap();
+    let mut storage = LmdbEnvironmentManager::create(tmp_dir.path(), "distinct_test").unwrap();
+    processor.init(&mut storage).unwrap();
+
+    let tx = storage.create_txn().unwrap();
+    let mut fw = TestChannelForwarder { operations: vec![] };
+    for op in ops {
+        processor
+            .process(DEFAULT_PORT_HANDLE, op, &mut fw, &tx, &HashMap::new())
+            .unwrap();
+    }
+    fw.operations
+}
+
+#[test]
+fn test_distinct_duplicates() {
+    let ops = vec![
+        insert("GUINESS"),
+        insert("WAHLBERG"),
+        insert("GUINESS"),
+        insert("GUINESS"),
+        // Copies of GUINESS are left
+        delete("GUINESS"),
+        delete("WAHLBERG"),
+    ];
+
+    assert_eq!(
+        run_distinct(ops),
+        vec![insert("GUINESS"), insert("WAHLBERG"), delete("WAHLBERG")]
+    );
+}
+
+#[test]
+fn test_distinct_last_copy_retracted() {
+    let ops = vec![
+        insert("GUINESS"),
+        insert("GUINESS"),
+        delete("GUINESS"),
+        delete("GUINESS"),
+        // Unknown records are ignored
+        delete("CHASE"),
+        insert("GUINESS"),
+    ];
+
+    assert_eq!(
+        run_distinct(ops),
+        vec![insert("GUINESS"), delete("GUINESS"), insert("GUINESS")]
+    );
+}
+
+#[test]
+fn test_distinct_updates() {
+    let ops = vec![
+        insert("GUINESS"),
+        insert("GUINESS"),
+        insert("WAHLBERG"),
+        // GUINESS is still there, CHASE is new
+        update("GUINESS", "CHASE"),
+        // WAHLBERG is gone, CHASE was already there
+        update("WAHLBERG", "CHASE"),
+        // Last GUINESS replaced by a new name
+        update("GUINESS", "DAVIS"),
+        // Same value on both sides
+        update("CHASE", "CHASE"),
+    ];
+
+    assert_eq!(
+        run_distinct(ops),
+        vec![
+            insert("GUINESS"),
+            insert("WAHLBERG"),
+            insert("CHASE"),
+            delete("WAHLBERG"),
+            update("GUINESS", "DAVIS"),
+        ]
+    );
+}
@@ -31,6 +31,8 @@ impl Display for FieldTypes {
 pub enum PipelineError {
     #[error("Invalid operand type for function: {0}()")]
     InvalidOperandType(String),
+    #[error("Arithmetic overflow in operator: {0}")]
+    ArithmeticOverflow(String),
     #[error("Invalid input type. Reason: {0}")]
     InvalidInputType(String),
     #[error("Invalid function: {0}")]
@@ -47,6 +49,12 @@ pub enum PipelineError {
     InvalidQuery(String),
     #[error("Invalid relation")]
     InvalidRelation,
+    #[error("Column not found: {0}")]
+    ColumnNotFound(String),
+    #[error("Ambiguous column: {0}, qualify it with the name or alias of its table")]
+    AmbiguousColumn(String),
+    #[error("Duplicate column: {0}, give the columns distinct names with an alias")]
+    DuplicateColumn(String),
     #[error("Invalid relation")]
     DataTypeMismatch,
     #[error("Invalid argument for function {0}(): argument: {1}, index: {2}")]
@@ -57,6 +65,8 @@ pub enum PipelineError {
         "Invalid argument type for function {0}(): type: {1}, expected types: {2}, index: {3}"
     )]
     InvalidFunctionArgumentType(String, FieldType, FieldTypes, usize),
+    #[error("Invalid cast: from: {0}, to: {1}")]
+    InvalidCast(Field, FieldType),
 
     // Error forwarding
     #[error(transparent)]
@@ -68,3 +78,17 @@ pub enum PipelineError {
     #[error(transparent)]
     InternalError(#[from] BoxedError),
 }
+
+/// Keeps the forwarded errors as they are, so that storage errors stay fatal, and reports every
+/// other error as a failure to process the record, which the pipeline can skip.
+impl From<PipelineError> for ExecutionError {
+    fn from(e: PipelineError) -> Self {
+        match e {
+            PipelineError::InternalStorageError(e) => ExecutionError::InternalDatabaseError(e),
+            PipelineError::InternalTypeError(e) => ExecutionError::InternalTypeError(e),
+            PipelineError::InternalExecutionError(e) => e,
+            PipelineError::InternalError(e) => ExecutionError::InternalError(e),
+            e => ExecutionError::RecordProcessingError(Box::new(e)),
+        }
+    }
+}
@@ -1,8 +1,11 @@
 pub mod aggregate;
 mod arg_utils;
 pub mod builder;
+pub mod case;
+pub mod cast;
 pub mod comparison;
 pub mod execution;
+pub mod in_list;
 pub mod logical;
 pub mod mathematical;
 pub mod operator;
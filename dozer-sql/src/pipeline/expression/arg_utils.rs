@@ -1,14 +1,14 @@
 use crate::pipeline::errors::PipelineError::InvalidFunctionArgumentType;
 use crate::pipeline::errors::{FieldTypes, PipelineError};
 use crate::pipeline::expression::execution::{Expression, ExpressionExecutor, ExpressionType};
-use crate::pipeline::expression::scalar::common::ScalarFunctionType;
 use dozer_types::types::{FieldType, Schema};
+use std::fmt::Display;
 
 pub(crate) fn validate_arg_type(
     arg: &Expression,
     expected: Vec<FieldType>,
     schema: &Schema,
-    fct: ScalarFunctionType,
+    fct: impl Display,
     idx: usize,
 ) -> Result<ExpressionType, PipelineError> {
     let arg_t = arg.get_type(schema)?;
@@ -1,13 +1,15 @@
 use std::cmp;
+use std::collections::HashSet;
 
 use dozer_types::{
     ordered_float::OrderedFloat,
-    types::{Field, Schema},
+    types::{Field, FieldType, Record, Schema},
 };
 
 use sqlparser::ast::{
-    BinaryOperator as SqlBinaryOperator, Expr as SqlExpr, Expr, Function, FunctionArg,
-    FunctionArgExpr, Ident, TrimWhereField, UnaryOperator as SqlUnaryOperator, Value as SqlValue,
+    BinaryOperator as SqlBinaryOperator, DataType, DateTimeField, Expr as SqlExpr, Expr, Function,
+    FunctionArg, FunctionArgExpr, Ident, TrimWhereField, UnaryOperator as SqlUnaryOperator,
+    Value as SqlValue,
 };
 
 use crate::pipeline::errors::PipelineError;
@@ -16,11 +18,14 @@ use crate::pipeline::expression::builder::PipelineError::InvalidArgument;
 use crate::pipeline::expression::builder::PipelineError::InvalidExpression;
 use crate::pipeline::expression::builder::PipelineError::InvalidOperator;
 use crate::pipeline::expression::builder::PipelineError::InvalidValue;
-use crate::pipeline::expression::execution::Expression;
+use crate::pipeline::expression::case::get_case_type;
+use crate::pipeline::expression::cast::get_cast_target_type;
 use crate::pipeline::expression::execution::Expression::ScalarFunction;
+use crate::pipeline::expression::execution::{get_field_type, Expression, ExpressionExecutor};
 use crate::pipeline::expression::operator::{BinaryOperatorType, UnaryOperatorType};
-use crate::pipeline::expression::scalar::common::ScalarFunctionType;
-use crate::pipeline::expression::scalar::string::TrimType;
+use crate::pipeline::expression::scalar::common::{get_scalar_function_type, ScalarFunctionType};
+use crate::pipeline::expression::scalar::conditional::validate_coalesce;
+use crate::pipeline::expression::scalar::string::{validate_concat, LikePattern, TrimType};
 
 pub type Bypass = bool;
 
@@ -104,6 +109,47 @@ impl ExpressionBuilder {
                 escape_char,
                 schema,
             ),
+            SqlExpr::Between {
+                expr,
+                negated,
+                low,
+                high,
+            } => self.parse_sql_between_operator(expression_type, negated, expr, low, high, schema),
+            SqlExpr::InList {
+                expr,
+                list,
+                negated,
+            } => self.parse_sql_in_list_operator(expression_type, negated, expr, list, schema),
+            SqlExpr::Substring {
+                expr,
+                substring_from,
+                substring_for,
+            } => self.parse_sql_substring_function(
+                expression_type,
+                expr,
+                substring_from,
+                substring_for,
+                schema,
+            ),
+            SqlExpr::Cast { expr, data_type } => {
+                self.parse_sql_cast_operator(expression_type, expr, data_type, schema)
+            }
+            SqlExpr::Extract { field, expr } => {
+                self.parse_sql_extract_function(expression_type, field, expr, schema)
+            }
+            SqlExpr::Case {
+                operand,
+                conditions,
+                results,
+                else_result,
+            } => self.parse_sql_case_operator(
+                expression_type,
+                operand,
+                conditions,
+                results,
+                else_result,
+                schema,
+            ),
             _ => Err(InvalidExpression(format!("{:?}", expression))),
         }
     }
@@ -151,7 +197,7 @@ impl ExpressionBuilder {
         expression: &SqlExpr,
     ) -> Result<(Box<Expression>, bool), PipelineError> {
         let name = sql_function.name.to_string().to_lowercase();
-        if let Ok(function) = ScalarFunctionType::new(&name) {
+        if is_scalar_function(&name) {
             let mut arg_exprs = vec![];
             for arg in &sql_function.args {
                 let r = self.parse_sql_function_arg(expression_type, arg, schema);
@@ -170,10 +216,7 @@ impl ExpressionBuilder {
             }
 
             return Ok((
-                Box::new(ScalarFunction {
-                    fun: function,
-                    args: arg_exprs,
-                }),
+                Box::new(build_scalar_function(&name, arg_exprs, schema)?),
                 false,
             ));
         };
@@ -194,7 +237,7 @@ impl ExpressionBuilder {
     ) -> Result<(Box<Expression>, bool), PipelineError> {
         let name = sql_function.name.to_string().to_lowercase();
 
-        if let Ok(function) = ScalarFunctionType::new(&name) {
+        if is_scalar_function(&name) {
             let mut arg_exprs = vec![];
             for arg in &sql_function.args {
                 let r = self.parse_sql_function_arg(expression_type, arg, schema);
@@ -213,10 +256,7 @@ impl ExpressionBuilder {
             }
 
             return Ok((
-                Box::new(ScalarFunction {
-                    fun: function,
-                    args: arg_exprs,
-                }),
+                Box::new(build_scalar_function(&name, arg_exprs, schema)?),
                 false,
             ));
         };
@@ -237,7 +277,7 @@ impl ExpressionBuilder {
     ) -> Result<(Box<Expression>, bool), PipelineError> {
         let name = sql_function.name.to_string().to_lowercase();
 
-        if let Ok(function) = ScalarFunctionType::new(&name) {
+        if is_scalar_function(&name) {
             let mut arg_exprs = vec![];
             for arg in &sql_function.args {
                 let r = self.parse_sql_function_arg(expression_type, arg, schema);
@@ -256,10 +296,7 @@ impl ExpressionBuilder {
             }
 
             return Ok((
-                Box::new(ScalarFunction {
-                    fun: function,
-                    args: arg_exprs,
-                }),
+                Box::new(build_scalar_function(&name, arg_exprs, schema)?),
                 false,
             ));
         };
@@ -341,7 +378,10 @@ impl ExpressionBuilder {
             _ => return Err(InvalidOperator(format!("{:?}", op))),
         };
 
-        Ok((Box::new(Expression::UnaryOperator { operator, arg }), false))
+        Ok((
+            fold_constants(Box::new(Expression::UnaryOperator { operator, arg })),
+            false,
+        ))
     }
 
     fn parse_sql_binary_op(
@@ -361,6 +401,21 @@ impl ExpressionBuilder {
             return Ok((right_op, bypass_right));
         }
 
+        if let SqlBinaryOperator::StringConcat = op {
+            let mut args = vec![];
+            for operand in [left_op, right_op] {
+                match *operand {
+                    Expression::Concat {
+                        args: operands,
+                        skip_nulls: false,
+                        ..
+                    } => args.extend(operands),
+                    operand => args.push(operand),
+                }
+            }
+            return Ok((Box::new(build_concat(args, false, schema)?), false));
+        }
+
         let operator = match op {
             SqlBinaryOperator::Gt => BinaryOperatorType::Gt,
             SqlBinaryOperator::GtEq => BinaryOperatorType::Gte,
@@ -380,16 +435,15 @@ impl ExpressionBuilder {
 
             // BinaryOperator::BitwiseAnd => ...
             // BinaryOperator::BitwiseOr => ...
-            // BinaryOperator::StringConcat => ...
             _ => return Err(InvalidOperator(format!("{:?}", op))),
         };
 
         Ok((
-            Box::new(Expression::BinaryOperator {
+            fold_constants(Box::new(Expression::BinaryOperator {
                 left: left_op,
                 operator,
                 right: right_op,
-            }),
+            })),
             false,
         ))
     }
@@ -416,24 +470,257 @@ impl ExpressionBuilder {
         escape_char: &Option<char>,
         schema: &Schema,
     ) -> Result<(Box<Expression>, bool), PipelineError> {
-        let arg = self.parse_sql_expression(expression_type, expr, schema)?;
-        let pattern = self.parse_sql_expression(expression_type, pattern, schema)?;
-        let like_expression = Box::new(Expression::Like {
-            arg: arg.0,
-            pattern: pattern.0,
-            escape: *escape_char,
+        let (arg, bypass) = self.parse_sql_expression(expression_type, expr, schema)?;
+        if bypass {
+            return Ok((arg, bypass));
+        }
+        let pattern = match *self
+            .parse_sql_expression(expression_type, pattern, schema)?
+            .0
+        {
+            Expression::Literal(Field::String(pattern) | Field::Text(pattern)) => {
+                LikePattern::new(&pattern, *escape_char)?
+            }
+            _ => {
+                return Err(InvalidExpression(format!(
+                    "LIKE pattern must be a string literal: {:?}",
+                    pattern
+                )))
+            }
+        };
+        Ok((
+            Box::new(Expression::Like {
+                arg,
+                pattern,
+                negated: *negated,
+            }),
+            false,
+        ))
+    }
+
+    fn parse_sql_between_operator(
+        &self,
+        expression_type: &BuilderExpressionType,
+        negated: &bool,
+        expr: &Expr,
+        low: &Expr,
+        high: &Expr,
+        schema: &Schema,
+    ) -> Result<(Box<Expression>, bool), PipelineError> {
+        let (arg, bypass) = self.parse_sql_expression(expression_type, expr, schema)?;
+        if bypass {
+            return Ok((arg, bypass));
+        }
+        let (low, bypass) = self.parse_sql_expression(expression_type, low, schema)?;
+        if bypass {
+            return Ok((low, bypass));
+        }
+        let (high, bypass) = self.parse_sql_expression(expression_type, high, schema)?;
+        if bypass {
+            return Ok((high, bypass));
+        }
+
+        // expr BETWEEN low AND high is equivalent to low <= expr AND expr <= high
+        let between_expression = Box::new(Expression::BinaryOperator {
+            left: Box::new(Expression::BinaryOperator {
+                left: arg.clone(),
+                operator: BinaryOperatorType::Gte,
+                right: low,
+            }),
+            operator: BinaryOperatorType::And,
+            right: Box::new(Expression::BinaryOperator {
+                left: arg,
+                operator: BinaryOperatorType::Lte,
+                right: high,
+            }),
         });
         if *negated {
             Ok((
                 Box::new(Expression::UnaryOperator {
                     operator: UnaryOperatorType::Not,
-                    arg: like_expression,
+                    arg: between_expression,
                 }),
-                arg.1,
+                false,
             ))
         } else {
-            Ok((like_expression, arg.1))
+            Ok((between_expression, false))
+        }
+    }
+
+    fn parse_sql_substring_function(
+        &self,
+        expression_type: &BuilderExpressionType,
+        expr: &Expr,
+        substring_from: &Option<Box<Expr>>,
+        substring_for: &Option<Box<Expr>>,
+        schema: &Schema,
+    ) -> Result<(Box<Expression>, bool), PipelineError> {
+        let (arg, bypass) = self.parse_sql_expression(expression_type, expr, schema)?;
+        if bypass {
+            return Ok((arg, bypass));
         }
+        let from = match substring_from {
+            Some(from) => self.parse_sql_expression(expression_type, from, schema)?.0,
+            None => Box::new(Expression::Literal(Field::Int(1))),
+        };
+        let mut args = vec![*arg, *from];
+        if let Some(length) = substring_for {
+            args.push(
+                *self
+                    .parse_sql_expression(expression_type, length, schema)?
+                    .0,
+            );
+        }
+        Ok((
+            Box::new(ScalarFunction {
+                fun: ScalarFunctionType::Substring,
+                args,
+            }),
+            false,
+        ))
+    }
+
+    fn parse_sql_in_list_operator(
+        &self,
+        expression_type: &BuilderExpressionType,
+        negated: &bool,
+        expr: &Expr,
+        list: &[Expr],
+        schema: &Schema,
+    ) -> Result<(Box<Expression>, bool), PipelineError> {
+        let (arg, bypass) = self.parse_sql_expression(expression_type, expr, schema)?;
+        if bypass {
+            return Ok((arg, bypass));
+        }
+        let mut values = vec![];
+        for item in list {
+            match *self.parse_sql_expression(expression_type, item, schema)?.0 {
+                Expression::Literal(value) => values.push(value),
+                _ => {
+                    return Err(InvalidExpression(format!(
+                        "IN list only supports literal values: {:?}",
+                        item
+                    )))
+                }
+            }
+        }
+        Ok((
+            Box::new(Expression::InList {
+                arg,
+                list: values,
+                negated: *negated,
+            }),
+            false,
+        ))
+    }
+
+    fn parse_sql_cast_operator(
+        &self,
+        expression_type: &BuilderExpressionType,
+        expr: &Expr,
+        data_type: &DataType,
+        schema: &Schema,
+    ) -> Result<(Box<Expression>, bool), PipelineError> {
+        let (arg, bypass) = self.parse_sql_expression(expression_type, expr, schema)?;
+        if bypass {
+            return Ok((arg, bypass));
+        }
+        let typ = get_cast_target_type(data_type)?;
+        Ok((Box::new(Expression::Cast { arg, typ }), false))
+    }
+
+    fn parse_sql_extract_function(
+        &self,
+        expression_type: &BuilderExpressionType,
+        field: &DateTimeField,
+        expr: &Expr,
+        schema: &Schema,
+    ) -> Result<(Box<Expression>, bool), PipelineError> {
+        let (arg, bypass) = self.parse_sql_expression(expression_type, expr, schema)?;
+        if bypass {
+            return Ok((arg, bypass));
+        }
+        Ok((
+            Box::new(ScalarFunction {
+                fun: ScalarFunctionType::Extract,
+                args: vec![
+                    Expression::Literal(Field::String(field.to_string().to_lowercase())),
+                    *arg,
+                ],
+            }),
+            false,
+        ))
+    }
+
+    fn parse_sql_case_operator(
+        &self,
+        expression_type: &BuilderExpressionType,
+        operand: &Option<Box<Expr>>,
+        sql_conditions: &[Expr],
+        sql_results: &[Expr],
+        else_result: &Option<Box<Expr>>,
+        schema: &Schema,
+    ) -> Result<(Box<Expression>, bool), PipelineError> {
+        let operand = match operand {
+            Some(operand) => {
+                let (operand, bypass) =
+                    self.parse_sql_expression(expression_type, operand, schema)?;
+                if bypass {
+                    return Ok((operand, bypass));
+                }
+                Some(operand)
+            }
+            None => None,
+        };
+
+        let mut conditions = vec![];
+        let mut results = vec![];
+        for (sql_condition, sql_result) in sql_conditions.iter().zip(sql_results) {
+            let (condition, bypass) =
+                self.parse_sql_expression(expression_type, sql_condition, schema)?;
+            if bypass {
+                return Ok((condition, bypass));
+            }
+            conditions.push(*condition);
+            let (result, bypass) =
+                self.parse_sql_expression(expression_type, sql_result, schema)?;
+            if bypass {
+                return Ok((result, bypass));
+            }
+            results.push(*result);
+        }
+        let else_result = match else_result {
+            Some(else_result) => {
+                let (else_result, bypass) =
+                    self.parse_sql_expression(expression_type, else_result, schema)?;
+                if bypass {
+                    return Ok((else_result, bypass));
+                }
+                Some(else_result)
+            }
+            None => None,
+        };
+
+        // Results of other types are cast to the type of the CASE
+        let typ = get_case_type(&results, &else_result, schema)?.return_type;
+        let results = results
+            .into_iter()
+            .map(|result| cast_to(result, typ, schema))
+            .collect::<Result<Vec<_>, _>>()?;
+        let else_result = match else_result {
+            Some(else_result) => Some(Box::new(cast_to(*else_result, typ, schema)?)),
+            None => None,
+        };
+
+        Ok((
+            Box::new(Expression::Case {
+                operand,
+                conditions,
+                results,
+                else_result,
+            }),
+            false,
+        ))
     }
 }
 
@@ -453,23 +740,13 @@ pub fn get_field_index(ident: &[Ident], schema: &Schema) -> Result<usize, Pipeli
     for (index, field) in schema.fields.iter().enumerate() {
         if compare_name(field.name.clone(), full_ident.clone()) {
             if field_index.is_some() {
-                return Err(PipelineError::InvalidQuery(format!(
-                    "Ambiguous Field {}",
-                    full_ident
-                )));
+                return Err(PipelineError::AmbiguousColumn(full_ident));
             } else {
                 field_index = Some(index);
             }
         }
     }
-    if let Some(index) = field_index {
-        Ok(index)
-    } else {
-        Err(PipelineError::InvalidQuery(format!(
-            "Field {} not found",
-            full_ident
-        )))
-    }
+    field_index.ok_or(PipelineError::ColumnNotFound(full_ident))
 }
 
 pub(crate) fn compare_name(name: String, ident: String) -> bool {
@@ -493,6 +770,109 @@ pub(crate) fn compare_name(name: String, ident: String) -> bool {
     is_equal
 }
 
+/// Checks that no two fields of `schema` share a name, as the cache and the APIs address fields
+/// by name.
+pub(crate) fn check_unique_field_names(schema: &Schema) -> Result<(), PipelineError> {
+    let mut names = HashSet::new();
+    for field in schema.fields.iter() {
+        if !names.insert(field.name.as_str()) {
+            return Err(PipelineError::DuplicateColumn(field.name.clone()));
+        }
+    }
+    Ok(())
+}
+
+/// Replaces an operator whose operands are all literals with the literal it evaluates to, so that
+/// it's evaluated once instead of once per record. Operands are folded before the operators
+/// using them, which collapses every constant sub-expression. Operators which fail or evaluate to
+/// `NULL` are kept as they are, to behave the same at runtime.
+fn fold_constants(expression: Box<Expression>) -> Box<Expression> {
+    let is_literal = |e: &Expression| matches!(e, Expression::Literal(_));
+    let is_constant = match expression.as_ref() {
+        Expression::UnaryOperator { arg, .. } => is_literal(arg),
+        Expression::BinaryOperator { left, right, .. } => is_literal(left) && is_literal(right),
+        _ => false,
+    };
+    if !is_constant {
+        return expression;
+    }
+
+    let schema = Schema::empty();
+    let record = Record::new(None, vec![], None);
+    match (
+        expression.get_type(&schema),
+        expression.evaluate(&record, &schema),
+    ) {
+        (Ok(typ), Ok(value)) if get_field_type(&value) == Some(typ.return_type) => {
+            Box::new(Expression::Literal(value))
+        }
+        _ => expression,
+    }
+}
+
+/// Casts `expression` to `typ`, unless it's already of that type or a NULL literal.
+fn cast_to(
+    expression: Expression,
+    typ: FieldType,
+    schema: &Schema,
+) -> Result<Expression, PipelineError> {
+    if expression == Expression::Literal(Field::Null)
+        || expression.get_type(schema)?.return_type == typ
+    {
+        Ok(expression)
+    } else {
+        Ok(Expression::Cast {
+            arg: Box::new(expression),
+            typ,
+        })
+    }
+}
+
+fn is_scalar_function(name: &str) -> bool {
+    name == "concat" || ScalarFunctionType::new(name).is_ok()
+}
+
+/// Builds the scalar function `name`, checking the types of its arguments once here rather than
+/// for every record.
+fn build_scalar_function(
+    name: &str,
+    args: Vec<Expression>,
+    schema: &Schema,
+) -> Result<Expression, PipelineError> {
+    if name == "concat" {
+        return build_concat(args, true, schema);
+    }
+    let fun = ScalarFunctionType::new(name)?;
+    let args = match fun {
+        ScalarFunctionType::Coalesce => {
+            // The arguments are cast to their common type, like the results of a CASE
+            let typ = validate_coalesce(&args, schema)?.return_type;
+            args.into_iter()
+                .map(|arg| cast_to(arg, typ, schema))
+                .collect::<Result<Vec<_>, _>>()?
+        }
+        ScalarFunctionType::NullIf => {
+            get_scalar_function_type(&fun, &args, schema)?;
+            args
+        }
+        _ => args,
+    };
+    Ok(ScalarFunction { fun, args })
+}
+
+fn build_concat(
+    args: Vec<Expression>,
+    skip_nulls: bool,
+    schema: &Schema,
+) -> Result<Expression, PipelineError> {
+    let typ = validate_concat(&args, schema)?.return_type;
+    Ok(Expression::Concat {
+        args,
+        skip_nulls,
+        typ,
+    })
+}
+
 fn parse_sql_string(s: &str) -> Result<(Box<Expression>, bool), PipelineError> {
     Ok((
         Box::new(Expression::Literal(Field::String(s.to_owned()))),
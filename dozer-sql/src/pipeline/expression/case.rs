@@ -0,0 +1,89 @@
+use dozer_types::types::{Field, FieldType, Record, Schema};
+
+use crate::pipeline::errors::PipelineError;
+
+use super::execution::{Expression, ExpressionExecutor, ExpressionType};
+use super::in_list::is_equal;
+use super::scalar::conditional::get_common_type;
+
+/// Returns the common type of the results of a `CASE`, the `ELSE` included.
+///
+/// `NULL` results take the type of the other results. The expression is nullable if a result is,
+/// or if there's no `ELSE`.
+pub(crate) fn get_case_type(
+    results: &[Expression],
+    else_result: &Option<Box<Expression>>,
+    schema: &Schema,
+) -> Result<ExpressionType, PipelineError> {
+    let mut return_type: Option<FieldType> = None;
+    let mut nullable = else_result.is_none();
+    for result in results.iter().chain(else_result.as_deref()) {
+        if *result == Expression::Literal(Field::Null) {
+            nullable = true;
+            continue;
+        }
+        let result_type = result.get_type(schema)?;
+        nullable |= result_type.nullable;
+        return_type = match return_type {
+            None => Some(result_type.return_type),
+            Some(typ) => Some(
+                get_common_type(typ, result_type.return_type).ok_or_else(|| {
+                    PipelineError::InvalidExpression(format!(
+                        "CASE results have incompatible types {:?} and {:?}",
+                        typ, result_type.return_type
+                    ))
+                })?,
+            ),
+        };
+    }
+
+    let return_type = return_type.ok_or_else(|| {
+        PipelineError::InvalidExpression("CASE must have a result which is not NULL".to_string())
+    })?;
+    Ok(ExpressionType::new(return_type, nullable))
+}
+
+/// Returns the result of the first branch whose condition holds, or the `ELSE` result. Without
+/// `ELSE`, the result is `NULL`.
+///
+/// With an `operand`, a condition holds if it equals the operand. Otherwise it must evaluate to a
+/// boolean, and holds if it's `true`. A `NULL` condition never holds.
+pub(crate) fn evaluate_case(
+    schema: &Schema,
+    operand: &Option<Box<Expression>>,
+    conditions: &[Expression],
+    results: &[Expression],
+    else_result: &Option<Box<Expression>>,
+    record: &Record,
+) -> Result<Field, PipelineError> {
+    let operand = match operand {
+        Some(operand) => Some(operand.evaluate(record, schema)?),
+        None => None,
+    };
+
+    for (condition, result) in conditions.iter().zip(results) {
+        let condition = condition.evaluate(record, schema)?;
+        let holds = match &operand {
+            Some(Field::Null) => false,
+            Some(operand) => condition != Field::Null && is_equal(operand, &condition),
+            None => match condition {
+                Field::Boolean(value) => value,
+                Field::Null => false,
+                _ => {
+                    return Err(PipelineError::InvalidExpression(format!(
+                        "CASE condition must be a boolean, got {}",
+                        condition
+                    )))
+                }
+            },
+        };
+        if holds {
+            return result.evaluate(record, schema);
+        }
+    }
+
+    match else_result {
+        Some(else_result) => else_result.evaluate(record, schema),
+        None => Ok(Field::Null),
+    }
+}
@@ -0,0 +1,100 @@
+use dozer_types::{
+    ordered_float::OrderedFloat,
+    rust_decimal::prelude::{FromPrimitive, ToPrimitive},
+    rust_decimal::Decimal,
+    types::{Field, FieldType, Record, Schema},
+};
+use sqlparser::ast::DataType;
+
+use crate::pipeline::errors::PipelineError;
+
+use super::execution::{Expression, ExpressionExecutor, ExpressionType};
+
+pub(crate) fn get_cast_target_type(data_type: &DataType) -> Result<FieldType, PipelineError> {
+    match data_type {
+        DataType::TinyInt(_) | DataType::SmallInt(_) | DataType::Int(_) | DataType::BigInt(_) => {
+            Ok(FieldType::Int)
+        }
+        DataType::Float(_) | DataType::Real | DataType::Double => Ok(FieldType::Float),
+        DataType::Decimal(_, _) => Ok(FieldType::Decimal),
+        DataType::Char(_) | DataType::Varchar(_) | DataType::String => Ok(FieldType::String),
+        DataType::Text => Ok(FieldType::Text),
+        DataType::Boolean => Ok(FieldType::Boolean),
+        DataType::Timestamp => Ok(FieldType::Timestamp),
+        DataType::Date => Ok(FieldType::Date),
+        _ => Err(PipelineError::InvalidExpression(format!(
+            "CAST to {} is not supported",
+            data_type
+        ))),
+    }
+}
+
+pub(crate) fn get_cast_type(
+    arg: &Expression,
+    typ: &FieldType,
+    schema: &Schema,
+) -> Result<ExpressionType, PipelineError> {
+    let arg_type = arg.get_type(schema)?;
+    Ok(ExpressionType::new(*typ, arg_type.nullable))
+}
+
+pub(crate) fn evaluate_cast(
+    schema: &Schema,
+    arg: &Expression,
+    typ: &FieldType,
+    record: &Record,
+) -> Result<Field, PipelineError> {
+    let value = arg.evaluate(record, schema)?;
+    if value == Field::Null {
+        return Ok(Field::Null);
+    }
+
+    let result = match typ {
+        FieldType::Int => match &value {
+            Field::Float(f) => f.0.trunc().to_i64(),
+            Field::Decimal(d) => d.trunc().to_i64(),
+            Field::Boolean(b) => Some(i64::from(*b)),
+            Field::Text(t) => t.trim().parse::<i64>().ok(),
+            Field::String(s) => s.trim().parse::<i64>().ok(),
+            _ => value.to_int(),
+        }
+        .map(Field::Int),
+        FieldType::UInt => value.to_uint().map(Field::UInt),
+        FieldType::Float => match &value {
+            Field::Text(t) => t.trim().parse::<f64>().ok(),
+            Field::String(s) => s.trim().parse::<f64>().ok(),
+            _ => value.to_float(),
+        }
+        .map(|f| Field::Float(OrderedFloat(f))),
+        FieldType::Decimal => match &value {
+            Field::Float(f) => Decimal::from_f64(f.0),
+            Field::Text(t) => Decimal::from_str_exact(t.trim()).ok(),
+            Field::String(s) => Decimal::from_str_exact(s.trim()).ok(),
+            _ => value.to_decimal(),
+        }
+        .map(Field::Decimal),
+        FieldType::Boolean => match &value {
+            Field::Boolean(_) | Field::Int(_) | Field::UInt(_) => value.to_boolean(),
+            Field::String(s) | Field::Text(s) => s.trim().to_lowercase().parse::<bool>().ok(),
+            _ => None,
+        }
+        .map(Field::Boolean),
+        FieldType::String => value.to_string().map(Field::String),
+        FieldType::Text => value.to_text().map(Field::Text),
+        FieldType::Timestamp => match &value {
+            Field::Text(t) => Field::String(t.to_owned()).to_timestamp(),
+            _ => value.to_timestamp(),
+        }
+        .map(Field::Timestamp),
+        FieldType::Date => match &value {
+            Field::Timestamp(t) => Some(t.naive_local().date()),
+            Field::Text(t) => Field::String(t.to_owned()).to_date(),
+            _ => value.to_date(),
+        }
+        .map(Field::Date),
+        FieldType::Json => value.to_json().map(Field::Json),
+        FieldType::Binary | FieldType::Bson => None,
+    };
+
+    result.ok_or_else(|| PipelineError::InvalidCast(value.clone(), *typ))
+}
@@ -7,7 +7,12 @@ use crate::pipeline::expression::scalar::string::{evaluate_trim, validate_trim,
 use dozer_types::types::{Field, FieldType, Record, Schema};
 
 use super::aggregate::AggregateFunctionType;
-use super::scalar::string::{evaluate_like, get_like_operator_type};
+use super::case::{evaluate_case, get_case_type};
+use super::cast::{evaluate_cast, get_cast_type};
+use super::in_list::{evaluate_in_list, get_in_list_type};
+use super::scalar::string::{
+    evaluate_concat, evaluate_like, get_like_operator_type, validate_concat, LikePattern,
+};
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Expression {
@@ -39,8 +44,32 @@ pub enum Expression {
     },
     Like {
         arg: Box<Expression>,
-        pattern: Box<Expression>,
-        escape: Option<char>,
+        pattern: LikePattern,
+        negated: bool,
+    },
+    Cast {
+        arg: Box<Expression>,
+        typ: FieldType,
+    },
+    InList {
+        arg: Box<Expression>,
+        list: Vec<Field>,
+        negated: bool,
+    },
+    /// `||`, flattened over all the operands of a chain, or `CONCAT()`, which skips NULL
+    /// arguments. `typ` is resolved when the expression is built.
+    Concat {
+        args: Vec<Expression>,
+        skip_nulls: bool,
+        typ: FieldType,
+    },
+    /// Searched `CASE WHEN condition THEN result` if there's no `operand`, simple
+    /// `CASE operand WHEN value THEN result` otherwise.
+    Case {
+        operand: Option<Box<Expression>>,
+        conditions: Vec<Expression>,
+        results: Vec<Expression>,
+        else_result: Option<Box<Expression>>,
     },
 }
 
@@ -58,7 +87,53 @@ impl ExpressionType {
     }
 }
 
-impl Expression {}
+impl Expression {
+    /// Fixes the value of every `NOW()` in the expression to `now`, until it's fixed again.
+    /// Processors fix it once per epoch, so that all the records of an epoch see the same time.
+    pub fn set_now(&mut self, now: &Field) {
+        match self {
+            Expression::Column { .. } | Expression::Literal(_) => {}
+            Expression::ScalarFunction {
+                fun: ScalarFunctionType::Now,
+                args,
+            } => *args = vec![Expression::Literal(now.clone())],
+            Expression::ScalarFunction { args, .. }
+            | Expression::AggregateFunction { args, .. }
+            | Expression::Concat { args, .. } => {
+                args.iter_mut().for_each(|arg| arg.set_now(now));
+            }
+            Expression::UnaryOperator { arg, .. }
+            | Expression::Like { arg, .. }
+            | Expression::Cast { arg, .. }
+            | Expression::InList { arg, .. } => arg.set_now(now),
+            Expression::BinaryOperator { left, right, .. } => {
+                left.set_now(now);
+                right.set_now(now);
+            }
+            Expression::Trim { arg, what, .. } => {
+                arg.set_now(now);
+                if let Some(what) = what {
+                    what.set_now(now);
+                }
+            }
+            Expression::Case {
+                operand,
+                conditions,
+                results,
+                else_result,
+            } => {
+                operand
+                    .iter_mut()
+                    .chain(else_result.iter_mut())
+                    .for_each(|e| e.set_now(now));
+                conditions
+                    .iter_mut()
+                    .chain(results.iter_mut())
+                    .for_each(|e| e.set_now(now));
+            }
+        }
+    }
+}
 
 pub trait ExpressionExecutor: Send + Sync {
     fn evaluate(&self, record: &Record, schema: &Schema) -> Result<Field, PipelineError>;
@@ -92,8 +167,23 @@ impl ExpressionExecutor for Expression {
             Expression::Like {
                 arg,
                 pattern,
-                escape,
-            } => evaluate_like(schema, arg, pattern, *escape, record),
+                negated,
+            } => evaluate_like(schema, arg, pattern, *negated, record),
+            Expression::Cast { arg, typ } => evaluate_cast(schema, arg, typ, record),
+            Expression::InList { arg, list, negated } => {
+                evaluate_in_list(schema, arg, list, *negated, record)
+            }
+            Expression::Concat {
+                args,
+                skip_nulls,
+                typ,
+            } => evaluate_concat(schema, args, *skip_nulls, *typ, record),
+            Expression::Case {
+                operand,
+                conditions,
+                results,
+                else_result,
+            } => evaluate_case(schema, operand, conditions, results, else_result, record),
         }
     }
 
@@ -127,14 +217,35 @@ impl ExpressionExecutor for Expression {
             } => validate_trim(arg, schema),
             Expression::Like {
                 arg,
-                pattern,
-                escape: _,
-            } => get_like_operator_type(arg, pattern, schema),
+                pattern: _,
+                negated: _,
+            } => get_like_operator_type(arg, schema),
+            Expression::Cast { arg, typ } => get_cast_type(arg, typ, schema),
+            Expression::InList {
+                arg,
+                list,
+                negated: _,
+            } => get_in_list_type(arg, list, schema),
+            Expression::Concat {
+                args,
+                skip_nulls,
+                typ,
+            } => {
+                // NULL arguments are skipped by `CONCAT()`, so its result is never NULL
+                let nullable = !*skip_nulls && validate_concat(args, schema)?.nullable;
+                Ok(ExpressionType::new(*typ, nullable))
+            }
+            Expression::Case {
+                operand: _,
+                conditions: _,
+                results,
+                else_result,
+            } => get_case_type(results, else_result, schema),
         }
     }
 }
 
-fn get_field_type(field: &Field) -> Option<FieldType> {
+pub(crate) fn get_field_type(field: &Field) -> Option<FieldType> {
     match field {
         Field::Int(_) => Some(FieldType::Int),
         Field::Float(_) => Some(FieldType::Float),
@@ -144,6 +255,7 @@ fn get_field_type(field: &Field) -> Option<FieldType> {
         Field::Decimal(_) => Some(FieldType::Decimal),
         Field::Timestamp(_) => Some(FieldType::Timestamp),
         Field::Bson(_) => Some(FieldType::Bson),
+        Field::Json(_) => Some(FieldType::Json),
         Field::Null => None,
         Field::UInt(_) => Some(FieldType::UInt),
         Field::Text(_) => Some(FieldType::Text),
@@ -205,40 +317,54 @@ fn get_binary_operator_type(
             }
         }
 
-        BinaryOperatorType::Add | BinaryOperatorType::Sub | BinaryOperatorType::Mul => {
-            match (left_field_type.return_type, right_field_type.return_type) {
-                (FieldType::Int, FieldType::Int) => Ok(ExpressionType::new(FieldType::Int, false)),
-                (FieldType::Int, FieldType::Float)
-                | (FieldType::Float, FieldType::Int)
-                | (FieldType::Float, FieldType::Float) => {
-                    Ok(ExpressionType::new(FieldType::Float, false))
-                }
-                (left_field_type, right_field_type) => {
-                    Err(PipelineError::InvalidExpression(format!(
-                        "cannot apply {:?} to {:?} and {:?}",
-                        operator, left_field_type, right_field_type
-                    )))
-                }
-            }
-        }
-        BinaryOperatorType::Div | BinaryOperatorType::Mod => {
-            match (left_field_type.return_type, right_field_type.return_type) {
-                (FieldType::Int, FieldType::Float)
-                | (FieldType::Float, FieldType::Int)
-                | (FieldType::Float, FieldType::Float) => {
-                    Ok(ExpressionType::new(FieldType::Float, false))
-                }
-                (left_field_type, right_field_type) => {
-                    Err(PipelineError::InvalidExpression(format!(
-                        "cannot apply {:?} to {:?} and {:?}",
-                        operator, left_field_type, right_field_type
-                    )))
-                }
-            }
+        BinaryOperatorType::Add
+        | BinaryOperatorType::Sub
+        | BinaryOperatorType::Mul
+        | BinaryOperatorType::Div
+        | BinaryOperatorType::Mod => {
+            let return_type = get_arithmetic_type(
+                operator,
+                left_field_type.return_type,
+                right_field_type.return_type,
+            )
+            .ok_or_else(|| {
+                PipelineError::InvalidExpression(format!(
+                    "cannot apply {:?} to {:?} and {:?}",
+                    operator, left_field_type.return_type, right_field_type.return_type
+                ))
+            })?;
+            // Dividing by zero evaluates to NULL
+            let nullable = left_field_type.nullable
+                || right_field_type.nullable
+                || matches!(operator, BinaryOperatorType::Div | BinaryOperatorType::Mod);
+            Ok(ExpressionType::new(return_type, nullable))
         }
     }
 }
 
+/// Returns the type of an arithmetic operation, or `None` if an operand isn't a number.
+///
+/// Operands are promoted to the wider type: `Int` to `Float` and both to `Decimal`, which is exact.
+/// Dividing two `Int`s gives a `Float`.
+fn get_arithmetic_type(
+    operator: &BinaryOperatorType,
+    left: FieldType,
+    right: FieldType,
+) -> Option<FieldType> {
+    match (left, right) {
+        (FieldType::Int, FieldType::Int) => match operator {
+            BinaryOperatorType::Div => Some(FieldType::Float),
+            _ => Some(FieldType::Int),
+        },
+        (FieldType::Int, FieldType::Float)
+        | (FieldType::Float, FieldType::Int)
+        | (FieldType::Float, FieldType::Float) => Some(FieldType::Float),
+        (FieldType::Int | FieldType::Float | FieldType::Decimal, FieldType::Decimal)
+        | (FieldType::Decimal, FieldType::Int | FieldType::Float) => Some(FieldType::Decimal),
+        _ => None,
+    }
+}
+
 fn get_aggregate_function_type(
     function: &AggregateFunctionType,
     args: &[Expression],
@@ -0,0 +1,52 @@
+use dozer_types::{
+    ordered_float::OrderedFloat,
+    types::{Field, FieldType, Record, Schema},
+};
+
+use crate::pipeline::errors::PipelineError;
+
+use super::execution::{Expression, ExpressionExecutor, ExpressionType};
+
+pub(crate) fn get_in_list_type(
+    arg: &Expression,
+    list: &[Field],
+    schema: &Schema,
+) -> Result<ExpressionType, PipelineError> {
+    let arg_type = arg.get_type(schema)?;
+    Ok(ExpressionType::new(
+        FieldType::Boolean,
+        arg_type.nullable || list.contains(&Field::Null),
+    ))
+}
+
+/// Follows SQL semantics: a `NULL` value, or a value missing from a list containing `NULL`, gives `NULL`.
+pub(crate) fn evaluate_in_list(
+    schema: &Schema,
+    arg: &Expression,
+    list: &[Field],
+    negated: bool,
+    record: &Record,
+) -> Result<Field, PipelineError> {
+    let value = arg.evaluate(record, schema)?;
+    if value == Field::Null {
+        return Ok(Field::Null);
+    }
+
+    if list.iter().any(|item| is_equal(&value, item)) {
+        Ok(Field::Boolean(!negated))
+    } else if list.contains(&Field::Null) {
+        Ok(Field::Null)
+    } else {
+        Ok(Field::Boolean(negated))
+    }
+}
+
+/// Equality that also matches numerically equal `Int` and `Float` values.
+pub(crate) fn is_equal(left: &Field, right: &Field) -> bool {
+    match (left, right) {
+        (Field::Int(i), Field::Float(f)) | (Field::Float(f), Field::Int(i)) => {
+            OrderedFloat(*i as f64) == *f
+        }
+        _ => left == right,
+    }
+}
@@ -2,6 +2,8 @@ use crate::pipeline::errors::PipelineError;
 use crate::pipeline::expression::execution::{Expression, ExpressionExecutor};
 use dozer_types::types::{Field, Record, Schema};
 
+/// Evaluates `left AND right` with SQL three-valued logic: `false` if either operand is `false`,
+/// otherwise `NULL` if either operand is `NULL`. `right` isn't evaluated if `left` is `false`.
 pub fn evaluate_and(
     schema: &Schema,
     left: &Expression,
@@ -9,18 +11,19 @@ pub fn evaluate_and(
     record: &Record,
 ) -> Result<Field, PipelineError> {
     match left.evaluate(record, schema)? {
-        Field::Boolean(true) => match right.evaluate(record, schema)? {
-            Field::Boolean(true) => Ok(Field::Boolean(true)),
+        Field::Boolean(false) => Ok(Field::Boolean(false)),
+        left @ (Field::Boolean(true) | Field::Null) => match right.evaluate(record, schema)? {
             Field::Boolean(false) => Ok(Field::Boolean(false)),
-            Field::Null => Ok(Field::Boolean(false)),
+            Field::Boolean(true) => Ok(left),
+            Field::Null => Ok(Field::Null),
             _ => Err(PipelineError::InvalidOperandType("AND".to_string())),
         },
-        Field::Boolean(false) => Ok(Field::Boolean(false)),
-        Field::Null => Ok(Field::Boolean(false)),
         _ => Err(PipelineError::InvalidOperandType("AND".to_string())),
     }
 }
 
+/// Evaluates `left OR right` with SQL three-valued logic: `true` if either operand is `true`,
+/// otherwise `NULL` if either operand is `NULL`. `right` isn't evaluated if `left` is `true`.
 pub fn evaluate_or(
     schema: &Schema,
     left: &Expression,
@@ -29,10 +32,10 @@ pub fn evaluate_or(
 ) -> Result<Field, PipelineError> {
     match left.evaluate(record, schema)? {
         Field::Boolean(true) => Ok(Field::Boolean(true)),
-        Field::Boolean(false) | Field::Null => match right.evaluate(record, schema)? {
-            Field::Boolean(false) => Ok(Field::Boolean(false)),
-            Field::Null => Ok(Field::Boolean(false)),
+        left @ (Field::Boolean(false) | Field::Null) => match right.evaluate(record, schema)? {
             Field::Boolean(true) => Ok(Field::Boolean(true)),
+            Field::Boolean(false) => Ok(left),
+            Field::Null => Ok(Field::Null),
             _ => Err(PipelineError::InvalidOperandType("OR".to_string())),
         },
         _ => Err(PipelineError::InvalidOperandType("OR".to_string())),
@@ -55,6 +58,8 @@ pub fn evaluate_not(
 
 #[cfg(test)]
 use crate::pipeline::expression::execution::Expression::Literal;
+#[cfg(test)]
+use crate::pipeline::expression::operator::BinaryOperatorType;
 
 #[test]
 fn test_bool_bool_and() {
@@ -76,7 +81,7 @@ fn test_bool_null_and() {
     assert!(matches!(
         evaluate_and(&Schema::empty(), &l, &r, &row)
             .unwrap_or_else(|e| panic!("{}", e.to_string())),
-        Field::Boolean(false)
+        Field::Null
     ));
 }
 
@@ -88,7 +93,7 @@ fn test_null_bool_and() {
     assert!(matches!(
         evaluate_and(&Schema::empty(), &l, &r, &row)
             .unwrap_or_else(|e| panic!("{}", e.to_string())),
-        Field::Boolean(false)
+        Field::Null
     ));
 }
 
@@ -125,6 +130,87 @@ fn test_bool_null_or() {
     ));
 }
 
+#[cfg(test)]
+const TRUTH_VALUES: [Field; 3] = [Field::Boolean(true), Field::Boolean(false), Field::Null];
+
+#[cfg(test)]
+fn truth_value(field: &Field) -> Option<bool> {
+    match field {
+        Field::Boolean(value) => Some(*value),
+        Field::Null => None,
+        _ => panic!("Not a truth value: {}", field),
+    }
+}
+
+#[test]
+fn test_and_truth_table() {
+    let row = Record::new(None, vec![], None);
+    for left in &TRUTH_VALUES {
+        for right in &TRUTH_VALUES {
+            let expected = match (truth_value(left), truth_value(right)) {
+                (Some(false), _) | (_, Some(false)) => Field::Boolean(false),
+                (Some(true), Some(true)) => Field::Boolean(true),
+                _ => Field::Null,
+            };
+            let l = Box::new(Literal(left.clone()));
+            let r = Box::new(Literal(right.clone()));
+            assert_eq!(
+                evaluate_and(&Schema::empty(), &l, &r, &row).unwrap(),
+                expected,
+                "{} AND {}",
+                left,
+                right
+            );
+        }
+    }
+}
+
+#[test]
+fn test_or_truth_table() {
+    let row = Record::new(None, vec![], None);
+    for left in &TRUTH_VALUES {
+        for right in &TRUTH_VALUES {
+            let expected = match (truth_value(left), truth_value(right)) {
+                (Some(true), _) | (_, Some(true)) => Field::Boolean(true),
+                (Some(false), Some(false)) => Field::Boolean(false),
+                _ => Field::Null,
+            };
+            let l = Box::new(Literal(left.clone()));
+            let r = Box::new(Literal(right.clone()));
+            assert_eq!(
+                evaluate_or(&Schema::empty(), &l, &r, &row).unwrap(),
+                expected,
+                "{} OR {}",
+                left,
+                right
+            );
+        }
+    }
+}
+
+#[test]
+fn test_nested_logical_with_null() {
+    // (NULL AND true) OR true
+    let row = Record::new(None, vec![], None);
+    let and = Box::new(Expression::BinaryOperator {
+        left: Box::new(Literal(Field::Null)),
+        operator: BinaryOperatorType::And,
+        right: Box::new(Literal(Field::Boolean(true))),
+    });
+    let r = Box::new(Literal(Field::Boolean(true)));
+    assert_eq!(
+        evaluate_or(&Schema::empty(), &and, &r, &row).unwrap(),
+        Field::Boolean(true)
+    );
+
+    // (NULL AND true) OR false
+    let r = Box::new(Literal(Field::Boolean(false)));
+    assert_eq!(
+        evaluate_or(&Schema::empty(), &and, &r, &row).unwrap(),
+        Field::Null
+    );
+}
+
 #[test]
 fn test_bool_not() {
     let row = Record::new(None, vec![], None);
@@ -3,12 +3,16 @@ use crate::pipeline::expression::execution::{Expression, ExpressionExecutor};
 use dozer_types::types::Schema;
 use dozer_types::{
     ordered_float::OrderedFloat,
-    types::{Field, Record},
+    rust_decimal::Decimal,
+    types::{Field, FieldType, Record},
 };
 use num_traits::cast::*;
 
+/// Arithmetic operators promote their operands to the wider type: `Int` to `Float`, and both to
+/// `Decimal`. An operation with a `NULL` operand evaluates to `NULL`. An `Int` or `Decimal` result
+/// that does not fit its type is an error.
 macro_rules! define_math_operator {
-    ($id:ident, $op:expr, $fct:expr, $t: expr) => {
+    ($id:ident, $op:expr, $int_fct:expr, $float_fct:expr, $decimal_fct:expr, $t: expr, $null_on_zero: expr) => {
         pub fn $id(
             schema: &Schema,
             left: &Expression,
@@ -18,42 +22,118 @@ macro_rules! define_math_operator {
             let left_p = left.evaluate(&record, schema)?;
             let right_p = right.evaluate(&record, schema)?;
 
-            match left_p {
-                Field::Float(left_v) => match right_p {
-                    Field::Int(right_v) => Ok(Field::Float($fct(
-                        left_v,
-                        OrderedFloat::<f64>::from_i64(right_v).unwrap(),
-                    ))),
-                    Field::Float(right_v) => Ok(Field::Float($fct(left_v, right_v))),
-                    _ => Err(PipelineError::InvalidOperandType($op.to_string())),
-                },
-                Field::Int(left_v) => match right_p {
-                    Field::Int(right_v) => {
-                        return match ($t) {
-                            1 => Ok(Field::Float($fct(
-                                OrderedFloat::<f64>::from_i64(left_v).unwrap(),
-                                OrderedFloat::<f64>::from_i64(right_v).unwrap(),
-                            ))),
-                            _ => Ok(Field::Int($fct(left_v, right_v))),
-                        };
-                    }
-                    Field::Float(right_v) => Ok(Field::Float($fct(
+            if !is_number_or_null(&left_p) || !is_number_or_null(&right_p) {
+                return Err(PipelineError::InvalidOperandType($op.to_string()));
+            }
+            if $null_on_zero && is_zero(&right_p) {
+                return Ok(Field::Null);
+            }
+
+            match (left_p, right_p) {
+                (Field::Null, _) | (_, Field::Null) => Ok(Field::Null),
+                (Field::Int(left_v), Field::Int(right_v)) => match ($t) {
+                    1 => Ok(Field::Float($float_fct(
                         OrderedFloat::<f64>::from_i64(left_v).unwrap(),
-                        right_v,
+                        OrderedFloat::<f64>::from_i64(right_v).unwrap(),
                     ))),
-                    _ => Err(PipelineError::InvalidOperandType($op.to_string())),
+                    _ => $int_fct(left_v, right_v)
+                        .map(Field::Int)
+                        .ok_or_else(|| PipelineError::ArithmeticOverflow($op.to_string())),
                 },
-                _ => Err(PipelineError::InvalidOperandType($op.to_string())),
+                (Field::Int(left_v), Field::Float(right_v)) => Ok(Field::Float($float_fct(
+                    OrderedFloat::<f64>::from_i64(left_v).unwrap(),
+                    right_v,
+                ))),
+                (Field::Float(left_v), Field::Int(right_v)) => Ok(Field::Float($float_fct(
+                    left_v,
+                    OrderedFloat::<f64>::from_i64(right_v).unwrap(),
+                ))),
+                (Field::Float(left_v), Field::Float(right_v)) => {
+                    Ok(Field::Float($float_fct(left_v, right_v)))
+                }
+                (left_p, right_p) => $decimal_fct(to_decimal(left_p)?, to_decimal(right_p)?)
+                    .map(Field::Decimal)
+                    .ok_or_else(|| PipelineError::ArithmeticOverflow($op.to_string())),
             }
         }
     };
 }
 
-define_math_operator!(evaluate_add, "+", |a, b| { a + b }, 0);
-define_math_operator!(evaluate_sub, "-", |a, b| { a - b }, 0);
-define_math_operator!(evaluate_mul, "*", |a, b| { a * b }, 0);
-define_math_operator!(evaluate_div, "/", |a, b| { a / b }, 1);
-define_math_operator!(evaluate_mod, "%", |a, b| { a % b }, 0);
+define_math_operator!(
+    evaluate_add,
+    "+",
+    i64::checked_add,
+    |a, b| { a + b },
+    Decimal::checked_add,
+    0,
+    false
+);
+define_math_operator!(
+    evaluate_sub,
+    "-",
+    i64::checked_sub,
+    |a, b| { a - b },
+    Decimal::checked_sub,
+    0,
+    false
+);
+define_math_operator!(
+    evaluate_mul,
+    "*",
+    i64::checked_mul,
+    |a, b| { a * b },
+    Decimal::checked_mul,
+    0,
+    false
+);
+// Unlike Postgres, which raises an error, dividing by zero evaluates to NULL. An error would
+// stop the whole pipeline because of a single record.
+define_math_operator!(
+    evaluate_div,
+    "/",
+    i64::checked_div,
+    |a, b| { a / b },
+    Decimal::checked_div,
+    1,
+    true
+);
+// `i64::MIN % -1` is 0, but `checked_rem` reports it as an overflow of the implied division.
+define_math_operator!(
+    evaluate_mod,
+    "%",
+    |a: i64, b: i64| Some(a.wrapping_rem(b)),
+    |a, b| { a % b },
+    Decimal::checked_rem,
+    0,
+    true
+);
+
+fn is_number_or_null(field: &Field) -> bool {
+    matches!(
+        field,
+        Field::Int(_) | Field::Float(_) | Field::Decimal(_) | Field::Null
+    )
+}
+
+fn is_zero(field: &Field) -> bool {
+    match field {
+        Field::Int(v) => *v == 0,
+        Field::Float(v) => v.0 == 0.0,
+        Field::Decimal(v) => v.is_zero(),
+        _ => false,
+    }
+}
+
+fn to_decimal(field: Field) -> Result<Decimal, PipelineError> {
+    match field {
+        Field::Int(v) => Ok(Decimal::from(v)),
+        Field::Float(v) => {
+            Decimal::from_f64(v.0).ok_or(PipelineError::InvalidCast(field, FieldType::Decimal))
+        }
+        Field::Decimal(v) => Ok(v),
+        _ => Err(PipelineError::InvalidCast(field, FieldType::Decimal)),
+    }
+}
 
 pub fn evaluate_plus(
     schema: &Schema,
@@ -75,7 +155,10 @@ pub fn evaluate_minus(
 ) -> Result<Field, PipelineError> {
     let expression_result = expression.evaluate(record, schema)?;
     match expression_result {
-        Field::Int(v) => Ok(Field::Int(-v)),
+        Field::Int(v) => v
+            .checked_neg()
+            .map(Field::Int)
+            .ok_or_else(|| PipelineError::ArithmeticOverflow("-".to_string())),
         Field::Float(v) => Ok(Field::Float(-v)),
         _ => Err(PipelineError::InvalidOperandType("-".to_string())),
     }
@@ -1,4 +1,6 @@
 pub mod common;
+pub mod conditional;
+pub mod datetime;
 pub mod number;
 pub mod string;
 
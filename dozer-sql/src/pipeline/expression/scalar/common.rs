@@ -1,9 +1,16 @@
 use crate::argv;
 use crate::pipeline::errors::PipelineError;
 use crate::pipeline::expression::execution::{Expression, ExpressionExecutor, ExpressionType};
+use crate::pipeline::expression::scalar::conditional::{
+    evaluate_coalesce, evaluate_nullif, validate_coalesce, validate_nullif,
+};
+use crate::pipeline::expression::scalar::datetime::{
+    evaluate_date_trunc, evaluate_extract, evaluate_now, validate_date_trunc, validate_extract,
+};
 use crate::pipeline::expression::scalar::number::{evaluate_abs, evaluate_round};
 use crate::pipeline::expression::scalar::string::{
-    evaluate_concat, evaluate_length, evaluate_ucase, validate_concat, validate_ucase,
+    evaluate_lcase, evaluate_length, evaluate_substring, evaluate_ucase, validate_lcase,
+    validate_length, validate_substring, validate_ucase,
 };
 
 use dozer_types::types::{Field, FieldType, Record, Schema};
@@ -15,8 +22,14 @@ pub enum ScalarFunctionType {
     Abs,
     Round,
     Ucase,
-    Concat,
+    Lcase,
     Length,
+    Substring,
+    Coalesce,
+    NullIf,
+    Extract,
+    DateTrunc,
+    Now,
 }
 
 impl Display for ScalarFunctionType {
@@ -25,8 +38,14 @@ impl Display for ScalarFunctionType {
             ScalarFunctionType::Abs => f.write_str("ABS"),
             ScalarFunctionType::Round => f.write_str("ROUND"),
             ScalarFunctionType::Ucase => f.write_str("UCASE"),
-            ScalarFunctionType::Concat => f.write_str("CONCAT"),
+            ScalarFunctionType::Lcase => f.write_str("LCASE"),
             ScalarFunctionType::Length => f.write_str("LENGTH"),
+            ScalarFunctionType::Substring => f.write_str("SUBSTRING"),
+            ScalarFunctionType::Coalesce => f.write_str("COALESCE"),
+            ScalarFunctionType::NullIf => f.write_str("NULLIF"),
+            ScalarFunctionType::Extract => f.write_str("EXTRACT"),
+            ScalarFunctionType::DateTrunc => f.write_str("DATE_TRUNC"),
+            ScalarFunctionType::Now => f.write_str("NOW"),
         }
     }
 }
@@ -42,12 +61,35 @@ pub(crate) fn get_scalar_function_type(
         ScalarFunctionType::Ucase => {
             validate_ucase(argv!(args, 0, ScalarFunctionType::Ucase)?, schema)
         }
-        ScalarFunctionType::Concat => validate_concat(
-            argv!(args, 0, ScalarFunctionType::Concat)?,
-            argv!(args, 1, ScalarFunctionType::Concat)?,
+        ScalarFunctionType::Lcase => {
+            validate_lcase(argv!(args, 0, ScalarFunctionType::Lcase)?, schema)
+        }
+        ScalarFunctionType::Length => {
+            validate_length(argv!(args, 0, ScalarFunctionType::Length)?, schema)
+        }
+        ScalarFunctionType::Substring => validate_substring(
+            argv!(args, 0, ScalarFunctionType::Substring)?,
+            argv!(args, 1, ScalarFunctionType::Substring)?,
+            args.get(2),
             schema,
         ),
-        ScalarFunctionType::Length => Ok(ExpressionType::new(FieldType::UInt, false)),
+        ScalarFunctionType::Coalesce => validate_coalesce(args, schema),
+        ScalarFunctionType::NullIf => validate_nullif(
+            argv!(args, 0, ScalarFunctionType::NullIf)?,
+            argv!(args, 1, ScalarFunctionType::NullIf)?,
+            schema,
+        ),
+        ScalarFunctionType::Extract => validate_extract(
+            argv!(args, 0, ScalarFunctionType::Extract)?,
+            argv!(args, 1, ScalarFunctionType::Extract)?,
+            schema,
+        ),
+        ScalarFunctionType::DateTrunc => validate_date_trunc(
+            argv!(args, 0, ScalarFunctionType::DateTrunc)?,
+            argv!(args, 1, ScalarFunctionType::DateTrunc)?,
+            schema,
+        ),
+        ScalarFunctionType::Now => Ok(ExpressionType::new(FieldType::Timestamp, false)),
     }
 }
 
@@ -56,9 +98,15 @@ impl ScalarFunctionType {
         match name {
             "abs" => Ok(ScalarFunctionType::Abs),
             "round" => Ok(ScalarFunctionType::Round),
-            "ucase" => Ok(ScalarFunctionType::Ucase),
-            "concat" => Ok(ScalarFunctionType::Concat),
+            "ucase" | "upper" => Ok(ScalarFunctionType::Ucase),
+            "lcase" | "lower" => Ok(ScalarFunctionType::Lcase),
             "length" => Ok(ScalarFunctionType::Length),
+            "substring" => Ok(ScalarFunctionType::Substring),
+            "coalesce" => Ok(ScalarFunctionType::Coalesce),
+            "nullif" => Ok(ScalarFunctionType::NullIf),
+            "extract" => Ok(ScalarFunctionType::Extract),
+            "date_trunc" => Ok(ScalarFunctionType::DateTrunc),
+            "now" => Ok(ScalarFunctionType::Now),
             _ => Err(PipelineError::InvalidFunction(name.to_string())),
         }
     }
@@ -82,15 +130,39 @@ impl ScalarFunctionType {
             ScalarFunctionType::Ucase => {
                 evaluate_ucase(schema, argv!(args, 0, ScalarFunctionType::Ucase)?, record)
             }
-            ScalarFunctionType::Concat => evaluate_concat(
-                schema,
-                argv!(args, 0, ScalarFunctionType::Concat)?,
-                argv!(args, 1, ScalarFunctionType::Concat)?,
-                record,
-            ),
             ScalarFunctionType::Length => {
                 evaluate_length(schema, argv!(args, 0, ScalarFunctionType::Length)?, record)
             }
+            ScalarFunctionType::Lcase => {
+                evaluate_lcase(schema, argv!(args, 0, ScalarFunctionType::Lcase)?, record)
+            }
+            ScalarFunctionType::Substring => evaluate_substring(
+                schema,
+                argv!(args, 0, ScalarFunctionType::Substring)?,
+                argv!(args, 1, ScalarFunctionType::Substring)?,
+                args.get(2),
+                record,
+            ),
+            ScalarFunctionType::Coalesce => evaluate_coalesce(schema, args, record),
+            ScalarFunctionType::NullIf => evaluate_nullif(
+                schema,
+                argv!(args, 0, ScalarFunctionType::NullIf)?,
+                argv!(args, 1, ScalarFunctionType::NullIf)?,
+                record,
+            ),
+            ScalarFunctionType::Extract => evaluate_extract(
+                schema,
+                argv!(args, 0, ScalarFunctionType::Extract)?,
+                argv!(args, 1, ScalarFunctionType::Extract)?,
+                record,
+            ),
+            ScalarFunctionType::DateTrunc => evaluate_date_trunc(
+                schema,
+                argv!(args, 0, ScalarFunctionType::DateTrunc)?,
+                argv!(args, 1, ScalarFunctionType::DateTrunc)?,
+                record,
+            ),
+            ScalarFunctionType::Now => evaluate_now(args),
         }
     }
 }
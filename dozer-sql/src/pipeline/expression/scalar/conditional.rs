@@ -0,0 +1,100 @@
+use crate::pipeline::errors::{FieldTypes, PipelineError};
+use crate::pipeline::expression::execution::{Expression, ExpressionExecutor, ExpressionType};
+use crate::pipeline::expression::in_list::is_equal;
+use crate::pipeline::expression::scalar::common::ScalarFunctionType;
+use dozer_types::types::{Field, FieldType, Record, Schema};
+
+/// Type both `left` and `right` can be converted to without losing values.
+pub(crate) fn get_common_type(left: FieldType, right: FieldType) -> Option<FieldType> {
+    match (left, right) {
+        (left, right) if left == right => Some(left),
+        (FieldType::Int | FieldType::UInt, FieldType::Float)
+        | (FieldType::Float, FieldType::Int | FieldType::UInt) => Some(FieldType::Float),
+        (FieldType::Int | FieldType::UInt, FieldType::Decimal)
+        | (FieldType::Decimal, FieldType::Int | FieldType::UInt) => Some(FieldType::Decimal),
+        (FieldType::Int, FieldType::UInt) | (FieldType::UInt, FieldType::Int) => {
+            Some(FieldType::Int)
+        }
+        (FieldType::String, FieldType::Text) | (FieldType::Text, FieldType::String) => {
+            Some(FieldType::Text)
+        }
+        _ => None,
+    }
+}
+
+pub(crate) fn validate_coalesce(
+    args: &[Expression],
+    schema: &Schema,
+) -> Result<ExpressionType, PipelineError> {
+    let mut result: Option<ExpressionType> = None;
+    for (idx, arg) in args.iter().enumerate() {
+        // A NULL literal has no type of its own
+        if arg == &Expression::Literal(Field::Null) {
+            continue;
+        }
+        let arg_t = arg.get_type(schema)?;
+        result = Some(match result {
+            None => arg_t,
+            Some(current) => ExpressionType::new(
+                get_common_type(current.return_type, arg_t.return_type).ok_or_else(|| {
+                    PipelineError::InvalidFunctionArgumentType(
+                        ScalarFunctionType::Coalesce.to_string(),
+                        arg_t.return_type,
+                        FieldTypes::new(vec![current.return_type]),
+                        idx,
+                    )
+                })?,
+                current.nullable && arg_t.nullable,
+            ),
+        });
+    }
+    result
+        .ok_or_else(|| PipelineError::NotEnoughArguments(ScalarFunctionType::Coalesce.to_string()))
+}
+
+/// The arguments are cast to their common type when the expression is built.
+pub(crate) fn evaluate_coalesce(
+    schema: &Schema,
+    args: &[Expression],
+    record: &Record,
+) -> Result<Field, PipelineError> {
+    for arg in args {
+        let value = arg.evaluate(record, schema)?;
+        if value != Field::Null {
+            return Ok(value);
+        }
+    }
+    Ok(Field::Null)
+}
+
+pub(crate) fn validate_nullif(
+    arg0: &Expression,
+    arg1: &Expression,
+    schema: &Schema,
+) -> Result<ExpressionType, PipelineError> {
+    let arg0_t = arg0.get_type(schema)?;
+    let arg1_t = arg1.get_type(schema)?;
+    if get_common_type(arg0_t.return_type, arg1_t.return_type).is_none() {
+        return Err(PipelineError::InvalidFunctionArgumentType(
+            ScalarFunctionType::NullIf.to_string(),
+            arg1_t.return_type,
+            FieldTypes::new(vec![arg0_t.return_type]),
+            1,
+        ));
+    }
+    Ok(ExpressionType::new(arg0_t.return_type, true))
+}
+
+pub(crate) fn evaluate_nullif(
+    schema: &Schema,
+    arg0: &Expression,
+    arg1: &Expression,
+    record: &Record,
+) -> Result<Field, PipelineError> {
+    let value = arg0.evaluate(record, schema)?;
+    if is_equal(&value, &arg1.evaluate(record, schema)?) {
+        Ok(Field::Null)
+    } else {
+        Ok(value)
+    }
+}
@@ -0,0 +1,256 @@
+use crate::pipeline::errors::{FieldTypes, PipelineError};
+use crate::pipeline::expression::execution::{Expression, ExpressionExecutor, ExpressionType};
+use crate::pipeline::expression::scalar::common::ScalarFunctionType;
+use dozer_types::chrono::{
+    DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc,
+};
+use dozer_types::types::{Field, FieldType, Record, Schema};
+
+/// A part of a date or time, as in `EXTRACT(part FROM value)` or `DATE_TRUNC('part', value)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateTimePart {
+    Year,
+    Quarter,
+    Month,
+    Week,
+    Day,
+    DayOfWeek,
+    DayOfYear,
+    Hour,
+    Minute,
+    Second,
+    Millisecond,
+    Microsecond,
+    Epoch,
+}
+
+impl DateTimePart {
+    fn new(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "year" | "years" => Some(DateTimePart::Year),
+            "quarter" => Some(DateTimePart::Quarter),
+            "month" | "months" => Some(DateTimePart::Month),
+            "week" | "weeks" => Some(DateTimePart::Week),
+            "day" | "days" => Some(DateTimePart::Day),
+            "dow" => Some(DateTimePart::DayOfWeek),
+            "doy" => Some(DateTimePart::DayOfYear),
+            "hour" | "hours" => Some(DateTimePart::Hour),
+            "minute" | "minutes" => Some(DateTimePart::Minute),
+            "second" | "seconds" => Some(DateTimePart::Second),
+            "millisecond" | "milliseconds" => Some(DateTimePart::Millisecond),
+            "microsecond" | "microseconds" => Some(DateTimePart::Microsecond),
+            "epoch" => Some(DateTimePart::Epoch),
+            _ => None,
+        }
+    }
+
+    fn can_truncate(&self) -> bool {
+        !matches!(
+            self,
+            DateTimePart::DayOfWeek | DateTimePart::DayOfYear | DateTimePart::Epoch
+        )
+    }
+}
+
+fn validate_part(
+    function: ScalarFunctionType,
+    part: &Expression,
+) -> Result<DateTimePart, PipelineError> {
+    let part = match part {
+        Expression::Literal(field) => field,
+        _ => {
+            return Err(PipelineError::InvalidExpression(format!(
+                "{}() only supports a literal date or time part",
+                function
+            )))
+        }
+    };
+    get_part(function, part.clone())
+}
+
+fn get_part(function: ScalarFunctionType, part: Field) -> Result<DateTimePart, PipelineError> {
+    let parsed = match &part {
+        Field::String(s) | Field::Text(s) => DateTimePart::new(s),
+        _ => None,
+    };
+    match parsed {
+        Some(part) if function != ScalarFunctionType::DateTrunc || part.can_truncate() => Ok(part),
+        _ => Err(PipelineError::InvalidFunctionArgument(
+            function.to_string(),
+            part,
+            0,
+        )),
+    }
+}
+
+fn validate_temporal_arg(
+    function: ScalarFunctionType,
+    arg: &Expression,
+    schema: &Schema,
+) -> Result<ExpressionType, PipelineError> {
+    let arg_type = arg.get_type(schema)?;
+    match arg_type.return_type {
+        FieldType::Timestamp | FieldType::Date => Ok(arg_type),
+        return_type => Err(PipelineError::InvalidFunctionArgumentType(
+            function.to_string(),
+            return_type,
+            FieldTypes::new(vec![FieldType::Timestamp, FieldType::Date]),
+            1,
+        )),
+    }
+}
+
+/// Evaluates `arg`, converting dates to timestamps at midnight UTC. `None` means `NULL`.
+fn evaluate_temporal_arg(
+    function: ScalarFunctionType,
+    arg: &Expression,
+    schema: &Schema,
+    record: &Record,
+) -> Result<Option<DateTime<FixedOffset>>, PipelineError> {
+    match arg.evaluate(record, schema)? {
+        Field::Timestamp(timestamp) => Ok(Some(timestamp)),
+        Field::Date(date) => Ok(date
+            .and_hms_opt(0, 0, 0)
+            .map(|datetime| DateTime::<Utc>::from_utc(datetime, Utc).into())),
+        Field::Null => Ok(None),
+        value => Err(PipelineError::InvalidFunctionArgument(
+            function.to_string(),
+            value,
+            1,
+        )),
+    }
+}
+
+pub(crate) fn validate_extract(
+    part: &Expression,
+    arg: &Expression,
+    schema: &Schema,
+) -> Result<ExpressionType, PipelineError> {
+    validate_part(ScalarFunctionType::Extract, part)?;
+    let arg_type = validate_temporal_arg(ScalarFunctionType::Extract, arg, schema)?;
+    Ok(ExpressionType::new(FieldType::Int, arg_type.nullable))
+}
+
+/// Returns the `part` of a timestamp or date, in the time zone of the timestamp.
+///
+/// `DOW` counts days from Sunday (0), `WEEK` is the ISO week and `EPOCH` the number of seconds
+/// since 1970-01-01 00:00:00 UTC. `MILLISECOND` and `MICROSECOND` include the seconds.
+pub(crate) fn evaluate_extract(
+    schema: &Schema,
+    part: &Expression,
+    arg: &Expression,
+    record: &Record,
+) -> Result<Field, PipelineError> {
+    let part = get_part(ScalarFunctionType::Extract, part.evaluate(record, schema)?)?;
+    let t = match evaluate_temporal_arg(ScalarFunctionType::Extract, arg, schema, record)? {
+        Some(t) => t,
+        None => return Ok(Field::Null),
+    };
+
+    let value = match part {
+        DateTimePart::Year => t.year() as i64,
+        DateTimePart::Quarter => (t.month0() / 3 + 1) as i64,
+        DateTimePart::Month => t.month() as i64,
+        DateTimePart::Week => t.iso_week().week() as i64,
+        DateTimePart::Day => t.day() as i64,
+        DateTimePart::DayOfWeek => t.weekday().num_days_from_sunday() as i64,
+        DateTimePart::DayOfYear => t.ordinal() as i64,
+        DateTimePart::Hour => t.hour() as i64,
+        DateTimePart::Minute => t.minute() as i64,
+        DateTimePart::Second => t.second() as i64,
+        DateTimePart::Millisecond => t.second() as i64 * 1_000 + t.nanosecond() as i64 / 1_000_000,
+        DateTimePart::Microsecond => t.second() as i64 * 1_000_000 + t.nanosecond() as i64 / 1_000,
+        DateTimePart::Epoch => t.timestamp(),
+    };
+    Ok(Field::Int(value))
+}
+
+pub(crate) fn validate_date_trunc(
+    part: &Expression,
+    arg: &Expression,
+    schema: &Schema,
+) -> Result<ExpressionType, PipelineError> {
+    validate_part(ScalarFunctionType::DateTrunc, part)?;
+    let arg_type = validate_temporal_arg(ScalarFunctionType::DateTrunc, arg, schema)?;
+    Ok(ExpressionType::new(FieldType::Timestamp, arg_type.nullable))
+}
+
+/// Truncates a timestamp or date to the `part`, in the time zone of the timestamp. Weeks start on
+/// Monday. Dates are truncated as timestamps at midnight UTC.
+pub(crate) fn evaluate_date_trunc(
+    schema: &Schema,
+    part: &Expression,
+    arg: &Expression,
+    record: &Record,
+) -> Result<Field, PipelineError> {
+    let part = get_part(
+        ScalarFunctionType::DateTrunc,
+        part.evaluate(record, schema)?,
+    )?;
+    let t = match evaluate_temporal_arg(ScalarFunctionType::DateTrunc, arg, schema, record)? {
+        Some(t) => t,
+        None => return Ok(Field::Null),
+    };
+
+    let local = t.naive_local();
+    let date = local.date();
+    let truncated = match part {
+        DateTimePart::Year => NaiveDate::from_ymd_opt(date.year(), 1, 1).map(midnight),
+        DateTimePart::Quarter => {
+            NaiveDate::from_ymd_opt(date.year(), date.month0() / 3 * 3 + 1, 1).map(midnight)
+        }
+        DateTimePart::Month => NaiveDate::from_ymd_opt(date.year(), date.month(), 1).map(midnight),
+        DateTimePart::Week => Some(midnight(
+            date - Duration::days(date.weekday().num_days_from_monday() as i64),
+        )),
+        DateTimePart::Day => Some(midnight(date)),
+        DateTimePart::Hour => date.and_hms_opt(local.hour(), 0, 0),
+        DateTimePart::Minute => date.and_hms_opt(local.hour(), local.minute(), 0),
+        DateTimePart::Second => date.and_hms_opt(local.hour(), local.minute(), local.second()),
+        DateTimePart::Millisecond => date.and_hms_milli_opt(
+            local.hour(),
+            local.minute(),
+            local.second(),
+            local.nanosecond() / 1_000_000,
+        ),
+        DateTimePart::Microsecond => date.and_hms_micro_opt(
+            local.hour(),
+            local.minute(),
+            local.second(),
+            local.nanosecond() / 1_000,
+        ),
+        DateTimePart::DayOfWeek | DateTimePart::DayOfYear | DateTimePart::Epoch => None,
+    };
+
+    truncated
+        .and_then(|truncated| t.offset().from_local_datetime(&truncated).single())
+        .map(Field::Timestamp)
+        .ok_or(PipelineError::InvalidFunctionArgument(
+            ScalarFunctionType::DateTrunc.to_string(),
+            Field::Timestamp(t),
+            1,
+        ))
+}
+
+fn midnight(date: NaiveDate) -> NaiveDateTime {
+    date.and_hms_opt(0, 0, 0)
+        .expect("Midnight is always a valid time")
+}
+
+/// Returns the time `NOW()` was fixed at by [`Expression::set_now`], or the current UTC time if
+/// it isn't fixed.
+///
+/// Processors fix `NOW()` to the UTC time at the start of every epoch, so it's the same for all
+/// the records of an epoch. It isn't stored though: reprocessing a record in another epoch, or
+/// processing a retraction of it, gives a different value.
+pub(crate) fn evaluate_now(args: &[Expression]) -> Result<Field, PipelineError> {
+    match args.first() {
+        Some(Expression::Literal(now)) => Ok(now.clone()),
+        _ => Ok(current_time()),
+    }
+}
+
+/// Returns the current UTC time, as `NOW()` does.
+pub(crate) fn current_time() -> Field {
+    Field::Timestamp(Utc::now().into())
+}
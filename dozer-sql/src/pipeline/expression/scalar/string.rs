@@ -1,13 +1,12 @@
-use crate::arg_str;
+use crate::{arg_int, arg_str};
 
-use crate::pipeline::errors::PipelineError;
+use crate::pipeline::errors::{FieldTypes, PipelineError};
 
 use crate::pipeline::expression::execution::{Expression, ExpressionExecutor, ExpressionType};
 
 use crate::pipeline::expression::arg_utils::validate_arg_type;
 use crate::pipeline::expression::scalar::common::ScalarFunctionType;
 use dozer_types::types::{Field, FieldType, Record, Schema};
-use like::{Escape, Like};
 
 pub(crate) fn validate_ucase(
     arg: &Expression,
@@ -28,6 +27,9 @@ pub(crate) fn evaluate_ucase(
     record: &Record,
 ) -> Result<Field, PipelineError> {
     let f = arg.evaluate(record, schema)?;
+    if f == Field::Null {
+        return Ok(Field::Null);
+    }
     let v = arg_str!(f, ScalarFunctionType::Ucase, 0)?;
     let ret = v.to_uppercase();
 
@@ -37,70 +39,210 @@ pub(crate) fn evaluate_ucase(
     })
 }
 
-pub(crate) fn validate_concat(
-    arg0: &Expression,
-    arg1: &Expression,
+pub(crate) fn validate_lcase(
+    arg: &Expression,
     schema: &Schema,
 ) -> Result<ExpressionType, PipelineError> {
-    let arg0 = validate_arg_type(
-        arg0,
+    validate_arg_type(
+        arg,
         vec![FieldType::String, FieldType::Text],
         schema,
-        ScalarFunctionType::Concat,
+        ScalarFunctionType::Lcase,
         0,
-    )?;
-    let arg1 = validate_arg_type(
-        arg1,
-        vec![FieldType::String, FieldType::Text],
-        schema,
-        ScalarFunctionType::Concat,
-        1,
-    )?;
+    )
+}
 
-    Ok(ExpressionType::new(
-        match (arg0.return_type, arg1.return_type) {
-            (FieldType::String, FieldType::String) => FieldType::String,
-            _ => FieldType::Text,
-        },
-        false,
-    ))
+pub(crate) fn evaluate_lcase(
+    schema: &Schema,
+    arg: &Expression,
+    record: &Record,
+) -> Result<Field, PipelineError> {
+    let f = arg.evaluate(record, schema)?;
+    if f == Field::Null {
+        return Ok(Field::Null);
+    }
+    let v = arg_str!(f, ScalarFunctionType::Lcase, 0)?;
+    let ret = v.to_lowercase();
+
+    Ok(match arg.get_type(schema)?.return_type {
+        FieldType::String => Field::String(ret),
+        _ => Field::Text(ret),
+    })
 }
 
+pub(crate) fn validate_concat(
+    args: &[Expression],
+    schema: &Schema,
+) -> Result<ExpressionType, PipelineError> {
+    let mut return_type = FieldType::String;
+    let mut nullable = false;
+    for (idx, arg) in args.iter().enumerate() {
+        // A NULL literal has no type of its own
+        if *arg == Expression::Literal(Field::Null) {
+            nullable = true;
+            continue;
+        }
+        let arg_t = arg.get_type(schema)?;
+        match arg_t.return_type {
+            FieldType::Binary | FieldType::Bson => {
+                return Err(PipelineError::InvalidFunctionArgumentType(
+                    "CONCAT".to_string(),
+                    arg_t.return_type,
+                    FieldTypes::new(vec![
+                        FieldType::String,
+                        FieldType::Text,
+                        FieldType::Int,
+                        FieldType::UInt,
+                        FieldType::Float,
+                        FieldType::Decimal,
+                        FieldType::Boolean,
+                        FieldType::Timestamp,
+                        FieldType::Date,
+                    ]),
+                    idx,
+                ))
+            }
+            FieldType::Text => return_type = FieldType::Text,
+            _ => {}
+        }
+        nullable |= arg_t.nullable;
+    }
+    Ok(ExpressionType::new(return_type, nullable))
+}
+
+/// Shared by `||` and `CONCAT()`. Non-string arguments are converted to strings, and `typ` is
+/// the type [`validate_concat`] returned when the expression was built.
+///
+/// Like Postgres, `||` returns NULL if any argument is NULL while `CONCAT()` skips NULL arguments.
 pub(crate) fn evaluate_concat(
     schema: &Schema,
-    arg0: &Expression,
-    arg1: &Expression,
+    args: &[Expression],
+    skip_nulls: bool,
+    typ: FieldType,
     record: &Record,
 ) -> Result<Field, PipelineError> {
-    let (f0, f1) = (
-        arg0.evaluate(record, schema)?,
-        arg1.evaluate(record, schema)?,
-    );
-    let (v0, v1) = (
-        arg_str!(f0, ScalarFunctionType::Concat, 0)?,
-        arg_str!(f1, ScalarFunctionType::Concat, 1)?,
-    );
-    let ret_val = v0 + v1.as_str();
-
-    Ok(
-        match (
-            arg0.get_type(schema)?.return_type,
-            arg1.get_type(schema)?.return_type,
-        ) {
-            (FieldType::String, FieldType::String) => Field::String(ret_val),
-            _ => Field::Text(ret_val),
-        },
-    )
+    let mut ret_val = String::new();
+    for (idx, arg) in args.iter().enumerate() {
+        let f = arg.evaluate(record, schema)?;
+        if f == Field::Null {
+            if skip_nulls {
+                continue;
+            }
+            return Ok(Field::Null);
+        }
+        ret_val.push_str(&arg_str!(f, "CONCAT", idx)?);
+    }
+
+    Ok(match typ {
+        FieldType::String => Field::String(ret_val),
+        _ => Field::Text(ret_val),
+    })
+}
+
+pub(crate) fn validate_length(
+    arg: &Expression,
+    schema: &Schema,
+) -> Result<ExpressionType, PipelineError> {
+    let arg_t = validate_arg_type(
+        arg,
+        vec![FieldType::String, FieldType::Text],
+        schema,
+        ScalarFunctionType::Length,
+        0,
+    )?;
+    Ok(ExpressionType::new(FieldType::UInt, arg_t.nullable))
 }
 
+/// Counts characters, not bytes.
 pub(crate) fn evaluate_length(
     schema: &Schema,
     arg0: &Expression,
     record: &Record,
 ) -> Result<Field, PipelineError> {
     let f0 = arg0.evaluate(record, schema)?;
-    let v0 = arg_str!(f0, ScalarFunctionType::Concat, 0)?;
-    Ok(Field::UInt(v0.len() as u64))
+    if f0 == Field::Null {
+        return Ok(Field::Null);
+    }
+    let v0 = arg_str!(f0, ScalarFunctionType::Length, 0)?;
+    Ok(Field::UInt(v0.chars().count() as u64))
+}
+
+pub(crate) fn validate_substring(
+    arg: &Expression,
+    from: &Expression,
+    length: Option<&Expression>,
+    schema: &Schema,
+) -> Result<ExpressionType, PipelineError> {
+    let arg_t = validate_arg_type(
+        arg,
+        vec![FieldType::String, FieldType::Text],
+        schema,
+        ScalarFunctionType::Substring,
+        0,
+    )?;
+    let mut nullable = arg_t.nullable;
+    for (idx, e) in std::iter::once(from).chain(length).enumerate() {
+        nullable |= validate_arg_type(
+            e,
+            vec![FieldType::Int, FieldType::UInt],
+            schema,
+            ScalarFunctionType::Substring,
+            idx + 1,
+        )?
+        .nullable;
+    }
+    Ok(ExpressionType::new(arg_t.return_type, nullable))
+}
+
+/// Follows Postgres: `from` is 1-based and the range is clamped to the string, so
+/// `SUBSTRING('dozer' FROM 0 FOR 3)` returns `do`.
+pub(crate) fn evaluate_substring(
+    schema: &Schema,
+    arg: &Expression,
+    from: &Expression,
+    length: Option<&Expression>,
+    record: &Record,
+) -> Result<Field, PipelineError> {
+    let arg_field = arg.evaluate(record, schema)?;
+    let from_field = from.evaluate(record, schema)?;
+    let length_field = match length {
+        Some(e) => Some(e.evaluate(record, schema)?),
+        None => None,
+    };
+    if arg_field == Field::Null || from_field == Field::Null || length_field == Some(Field::Null) {
+        return Ok(Field::Null);
+    }
+
+    let value = arg_str!(arg_field, ScalarFunctionType::Substring, 0)?;
+    let start = arg_int!(from_field, ScalarFunctionType::Substring, 1)?;
+    let end = match length_field {
+        Some(f) => {
+            let length = arg_int!(f, ScalarFunctionType::Substring, 2)?;
+            if length < 0 {
+                return Err(PipelineError::InvalidFunctionArgument(
+                    ScalarFunctionType::Substring.to_string(),
+                    Field::Int(length),
+                    2,
+                ));
+            }
+            start.saturating_add(length)
+        }
+        None => i64::MAX,
+    };
+
+    // Convert to 0-based character offsets
+    let skip = start.max(1) - 1;
+    let take = end.saturating_sub(start.max(1)).max(0);
+    let ret: String = value
+        .chars()
+        .skip(skip.try_into().unwrap_or(usize::MAX))
+        .take(take.try_into().unwrap_or(usize::MAX))
+        .collect();
+
+    Ok(match arg.get_type(schema)?.return_type {
+        FieldType::String => Field::String(ret),
+        _ => Field::Text(ret),
+    })
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -118,7 +260,7 @@ pub(crate) fn validate_trim(
         arg,
         vec![FieldType::String, FieldType::Text],
         schema,
-        ScalarFunctionType::Concat,
+        "TRIM",
         0,
     )
 }
@@ -154,55 +296,120 @@ pub(crate) fn evaluate_trim(
     })
 }
 
+/// A `LIKE` pattern, compiled once when the expression is built.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LikePattern {
+    tokens: Vec<LikeToken>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum LikeToken {
+    Char(char),
+    /// `_`
+    AnyChar,
+    /// `%`
+    AnyString,
+}
+
+impl LikePattern {
+    /// `escape` defaults to `\`.
+    pub fn new(pattern: &str, escape: Option<char>) -> Result<Self, PipelineError> {
+        let escape = escape.unwrap_or('\\');
+        let mut tokens = vec![];
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            let token = if c == escape {
+                match chars.next() {
+                    Some(escaped) => LikeToken::Char(escaped),
+                    None => {
+                        return Err(PipelineError::InvalidArgument(format!(
+                            "LIKE pattern must not end with escape character: {}",
+                            pattern
+                        )))
+                    }
+                }
+            } else if c == '%' {
+                if tokens.last() == Some(&LikeToken::AnyString) {
+                    continue;
+                }
+                LikeToken::AnyString
+            } else if c == '_' {
+                LikeToken::AnyChar
+            } else {
+                LikeToken::Char(c)
+            };
+            tokens.push(token);
+        }
+        Ok(Self { tokens })
+    }
+
+    pub fn matches(&self, value: &str) -> bool {
+        let chars: Vec<char> = value.chars().collect();
+        let (mut t, mut c) = (0, 0);
+        // Position of the last `%` and of the first character it has not consumed yet
+        let mut backtrack = None;
+        while c < chars.len() {
+            match self.tokens.get(t) {
+                Some(LikeToken::AnyString) => {
+                    backtrack = Some((t, c));
+                    t += 1;
+                    continue;
+                }
+                Some(LikeToken::AnyChar) => {
+                    t += 1;
+                    c += 1;
+                    continue;
+                }
+                Some(LikeToken::Char(p)) if *p == chars[c] => {
+                    t += 1;
+                    c += 1;
+                    continue;
+                }
+                _ => {}
+            }
+            match backtrack {
+                Some((bt, bc)) => {
+                    backtrack = Some((bt, bc + 1));
+                    t = bt + 1;
+                    c = bc + 1;
+                }
+                None => return false,
+            }
+        }
+        self.tokens[t..]
+            .iter()
+            .all(|token| *token == LikeToken::AnyString)
+    }
+}
+
 pub(crate) fn get_like_operator_type(
     arg: &Expression,
-    pattern: &Expression,
     schema: &Schema,
 ) -> Result<ExpressionType, PipelineError> {
-    validate_arg_type(
-        pattern,
-        vec![FieldType::String, FieldType::Text],
-        schema,
-        ScalarFunctionType::Concat,
-        0,
-    )?;
-
-    validate_arg_type(
-        arg,
-        vec![FieldType::String, FieldType::Text],
-        schema,
-        ScalarFunctionType::Concat,
-        0,
-    )
+    let arg_t = arg.get_type(schema)?;
+    let expected = vec![FieldType::String, FieldType::Text];
+    if !expected.contains(&arg_t.return_type) {
+        return Err(PipelineError::InvalidFunctionArgumentType(
+            "LIKE".to_string(),
+            arg_t.return_type,
+            FieldTypes::new(expected),
+            0,
+        ));
+    }
+    Ok(ExpressionType::new(FieldType::Boolean, arg_t.nullable))
 }
 
 pub(crate) fn evaluate_like(
     schema: &Schema,
     arg: &Expression,
-    pattern: &Expression,
-    escape: Option<char>,
+    pattern: &LikePattern,
+    negated: bool,
     record: &Record,
 ) -> Result<Field, PipelineError> {
     let arg_field = arg.evaluate(record, schema)?;
-    let arg_value = arg_str!(arg_field, "LIKE", 0)?;
-    let arg_string = arg_value.as_str();
-
-    let pattern_field = pattern.evaluate(record, schema)?;
-    let pattern_value = arg_str!(pattern_field, "LIKE", 1)?;
-    let pattern_string = pattern_value.as_str();
-
-    if let Some(escape_char) = escape {
-        let arg_escape = &arg_string
-            .escape(&escape_char.to_string())
-            .map_err(|e| PipelineError::InvalidArgument(e.to_string()))?;
-        let result = Like::<false>::like(arg_escape.as_str(), pattern_string)
-            .map(Field::Boolean)
-            .map_err(|e| PipelineError::InvalidArgument(e.to_string()))?;
-        return Ok(result);
+    if arg_field == Field::Null {
+        return Ok(Field::Null);
     }
-
-    let result = Like::<false>::like(arg_string, pattern_string)
-        .map(Field::Boolean)
-        .map_err(|e| PipelineError::InvalidArgument(e.to_string()))?;
-    Ok(result)
+    let arg_value = arg_str!(arg_field, "LIKE", 0)?;
+    Ok(Field::Boolean(pattern.matches(&arg_value) != negated))
 }
@@ -1,4 +1,8 @@
 #[cfg(test)]
+mod conditional;
+#[cfg(test)]
+mod datetime;
+#[cfg(test)]
 mod number;
 #[cfg(test)]
 mod scalar_common;
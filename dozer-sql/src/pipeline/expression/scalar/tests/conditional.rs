@@ -0,0 +1,185 @@
+use crate::pipeline::builder::get_select;
+use crate::pipeline::errors::PipelineError;
+use crate::pipeline::expression::builder::{BuilderExpressionType, ExpressionBuilder};
+use crate::pipeline::expression::execution::Expression;
+use crate::pipeline::expression::scalar::common::ScalarFunctionType;
+use crate::pipeline::expression::scalar::tests::scalar_common::run_scalar_fct;
+use crate::pipeline::projection::factory::ProjectionProcessorFactory;
+use dozer_core::dag::dag::DEFAULT_PORT_HANDLE;
+use dozer_core::dag::errors::ExecutionError;
+use dozer_core::dag::node::ProcessorFactory;
+use dozer_types::ordered_float::OrderedFloat;
+use dozer_types::types::{Field, FieldDefinition, FieldType, Schema};
+use sqlparser::ast::SelectItem;
+
+fn actor_schema() -> Schema {
+    Schema::empty()
+        .field(
+            FieldDefinition::new(String::from("actor_id"), FieldType::Int, false),
+            false,
+        )
+        .field(
+            FieldDefinition::new(String::from("first_name"), FieldType::String, false),
+            false,
+        )
+        .field(
+            FieldDefinition::new(String::from("last_name"), FieldType::String, true),
+            false,
+        )
+        .field(
+            FieldDefinition::new(String::from("rating"), FieldType::Float, true),
+            false,
+        )
+        .clone()
+}
+
+fn build(sql: &str) -> Result<Box<Expression>, PipelineError> {
+    let select = get_select(sql).unwrap();
+    let expr = match &select.projection[0] {
+        SelectItem::UnnamedExpr(expr) => expr.clone(),
+        item => panic!("Unexpected projection {:?}", item),
+    };
+    ExpressionBuilder.build(
+        &BuilderExpressionType::FullExpression,
+        &expr,
+        &actor_schema(),
+    )
+}
+
+fn get_output_schema(sql: &str) -> Result<Schema, ExecutionError> {
+    let select = get_select(sql).unwrap();
+    ProjectionProcessorFactory::_new(select.projection).get_output_schema(
+        &DEFAULT_PORT_HANDLE,
+        &[(DEFAULT_PORT_HANDLE, actor_schema())]
+            .into_iter()
+            .collect(),
+    )
+}
+
+#[test]
+fn test_coalesce() {
+    let sql = "SELECT COALESCE(last_name, 'unknown') FROM actor";
+    let f = run_scalar_fct(
+        sql,
+        actor_schema(),
+        vec![
+            Field::Int(1),
+            Field::String("PENELOPE".to_string()),
+            Field::Null,
+            Field::Null,
+        ],
+    );
+    assert_eq!(f, Field::String("unknown".to_string()));
+
+    let f = run_scalar_fct(
+        sql,
+        actor_schema(),
+        vec![
+            Field::Int(1),
+            Field::String("PENELOPE".to_string()),
+            Field::String("GUINESS".to_string()),
+            Field::Null,
+        ],
+    );
+    assert_eq!(f, Field::String("GUINESS".to_string()));
+}
+
+#[test]
+fn test_coalesce_common_type() {
+    let f = run_scalar_fct(
+        "SELECT COALESCE(rating, NULL, actor_id) FROM actor",
+        actor_schema(),
+        vec![
+            Field::Int(1),
+            Field::String("PENELOPE".to_string()),
+            Field::Null,
+            Field::Null,
+        ],
+    );
+    assert_eq!(f, Field::Float(OrderedFloat(1.0)));
+
+    let schema = get_output_schema("SELECT COALESCE(rating, actor_id) AS rating FROM actor");
+    assert_eq!(
+        schema.unwrap().fields[0],
+        FieldDefinition::new(String::from("rating"), FieldType::Float, false)
+    );
+
+    let schema = get_output_schema("SELECT COALESCE(last_name, NULL) AS last_name FROM actor");
+    assert_eq!(
+        schema.unwrap().fields[0],
+        FieldDefinition::new(String::from("last_name"), FieldType::String, true)
+    );
+
+    assert!(matches!(
+        get_output_schema("SELECT COALESCE(last_name, actor_id) FROM actor"),
+        Err(ExecutionError::InternalError(_))
+    ));
+}
+
+#[test]
+fn test_nullif() {
+    let sql = "SELECT NULLIF(first_name, 'PENELOPE') FROM actor";
+    let f = run_scalar_fct(
+        sql,
+        actor_schema(),
+        vec![
+            Field::Int(1),
+            Field::String("PENELOPE".to_string()),
+            Field::Null,
+            Field::Null,
+        ],
+    );
+    assert_eq!(f, Field::Null);
+
+    let f = run_scalar_fct(
+        sql,
+        actor_schema(),
+        vec![
+            Field::Int(2),
+            Field::String("NICK".to_string()),
+            Field::Null,
+            Field::Null,
+        ],
+    );
+    assert_eq!(f, Field::String("NICK".to_string()));
+
+    let f = run_scalar_fct(
+        "SELECT NULLIF(last_name, first_name) FROM actor",
+        actor_schema(),
+        vec![
+            Field::Int(2),
+            Field::String("NICK".to_string()),
+            Field::Null,
+            Field::Null,
+        ],
+    );
+    assert_eq!(f, Field::Null);
+}
+
+#[test]
+fn test_arguments_checked_when_built() {
+    // Arguments are cast to the common type once, rather than for every record
+    assert_eq!(
+        *build("SELECT COALESCE(rating, NULL, actor_id) FROM actor").unwrap(),
+        Expression::ScalarFunction {
+            fun: ScalarFunctionType::Coalesce,
+            args: vec![
+                Expression::Column { index: 3 },
+                Expression::Literal(Field::Null),
+                Expression::Cast {
+                    arg: Box::new(Expression::Column { index: 0 }),
+                    typ: FieldType::Float,
+                },
+            ],
+        }
+    );
+
+    assert!(matches!(
+        build("SELECT COALESCE(last_name, actor_id) FROM actor"),
+        Err(PipelineError::InvalidFunctionArgumentType(..))
+    ));
+    assert!(matches!(
+        build("SELECT NULLIF(actor_id, last_name) FROM actor"),
+        Err(PipelineError::InvalidFunctionArgumentType(..))
+    ));
+}
@@ -0,0 +1,245 @@
+use crate::pipeline::builder::get_select;
+use crate::pipeline::expression::scalar::tests::scalar_common::{
+    run_scalar_fct, TestChannelForwarder,
+};
+use crate::pipeline::projection::factory::ProjectionProcessorFactory;
+use dozer_core::dag::dag::DEFAULT_PORT_HANDLE;
+use dozer_core::dag::errors::ExecutionError;
+use dozer_core::dag::node::{Processor, ProcessorFactory};
+use dozer_core::storage::lmdb_storage::LmdbEnvironmentManager;
+use dozer_types::chrono::{DateTime, FixedOffset, NaiveDate, Offset, TimeZone, Utc};
+use dozer_types::types::{Field, FieldDefinition, FieldType, Operation, Record, Schema};
+use std::collections::HashMap;
+use std::time::Duration;
+use tempdir::TempDir;
+
+fn rental_schema() -> Schema {
+    Schema::empty()
+        .field(
+            FieldDefinition::new(String::from("rental_id"), FieldType::Int, false),
+            false,
+        )
+        .field(
+            FieldDefinition::new(String::from("last_update"), FieldType::Timestamp, true),
+            false,
+        )
+        .field(
+            FieldDefinition::new(String::from("return_date"), FieldType::Date, false),
+            false,
+        )
+        .clone()
+}
+
+fn last_update() -> DateTime<FixedOffset> {
+    DateTime::from_utc(
+        NaiveDate::from_ymd(2022, 9, 16).and_hms_micro(5, 56, 29, 959787),
+        Utc.fix(),
+    )
+}
+
+fn rental(last_update: Field) -> Vec<Field> {
+    vec![
+        Field::Int(1),
+        last_update,
+        Field::Date(NaiveDate::from_ymd(2022, 9, 20)),
+    ]
+}
+
+fn run(sql: &str) -> Field {
+    run_scalar_fct(
+        sql,
+        rental_schema(),
+        rental(Field::Timestamp(last_update())),
+    )
+}
+
+fn get_output_schema(sql: &str) -> Result<Schema, ExecutionError> {
+    let select = get_select(sql).unwrap();
+    ProjectionProcessorFactory::_new(select.projection).get_output_schema(
+        &DEFAULT_PORT_HANDLE,
+        &[(DEFAULT_PORT_HANDLE, rental_schema())]
+            .into_iter()
+            .collect(),
+    )
+}
+
+#[test]
+fn test_extract() {
+    let cases = [
+        ("YEAR", 2022),
+        ("QUARTER", 3),
+        ("MONTH", 9),
+        ("WEEK", 37),
+        ("DAY", 16),
+        ("DOW", 5),
+        ("DOY", 259),
+        ("HOUR", 5),
+        ("MINUTE", 56),
+        ("SECOND", 29),
+        ("MILLISECONDS", 29959),
+        ("MICROSECONDS", 29959787),
+        ("EPOCH", 1663307789),
+    ];
+    for (part, expected) in cases {
+        let sql = format!("SELECT EXTRACT({} FROM last_update) FROM rental", part);
+        assert_eq!(run(&sql), Field::Int(expected), "{}", part);
+    }
+
+    assert_eq!(
+        run("SELECT EXTRACT(DAY FROM return_date) FROM rental"),
+        Field::Int(20)
+    );
+    assert_eq!(
+        run_scalar_fct(
+            "SELECT EXTRACT(YEAR FROM last_update) FROM rental",
+            rental_schema(),
+            rental(Field::Null),
+        ),
+        Field::Null
+    );
+}
+
+#[test]
+fn test_extract_in_time_zone() {
+    // 2022-09-16 22:30:00 UTC is already the next day at +07
+    let offset = FixedOffset::east(7 * 3600);
+    let timestamp = offset.from_utc_datetime(&NaiveDate::from_ymd(2022, 9, 16).and_hms(22, 30, 0));
+    let f = run_scalar_fct(
+        "SELECT EXTRACT(DAY FROM last_update) FROM rental",
+        rental_schema(),
+        rental(Field::Timestamp(timestamp)),
+    );
+    assert_eq!(f, Field::Int(17));
+}
+
+#[test]
+fn test_date_trunc() {
+    let cases = [
+        ("year", NaiveDate::from_ymd(2022, 1, 1).and_hms(0, 0, 0)),
+        ("quarter", NaiveDate::from_ymd(2022, 7, 1).and_hms(0, 0, 0)),
+        ("month", NaiveDate::from_ymd(2022, 9, 1).and_hms(0, 0, 0)),
+        ("week", NaiveDate::from_ymd(2022, 9, 12).and_hms(0, 0, 0)),
+        ("day", NaiveDate::from_ymd(2022, 9, 16).and_hms(0, 0, 0)),
+        ("hour", NaiveDate::from_ymd(2022, 9, 16).and_hms(5, 0, 0)),
+        ("minute", NaiveDate::from_ymd(2022, 9, 16).and_hms(5, 56, 0)),
+        (
+            "second",
+            NaiveDate::from_ymd(2022, 9, 16).and_hms(5, 56, 29),
+        ),
+        (
+            "milliseconds",
+            NaiveDate::from_ymd(2022, 9, 16).and_hms_milli(5, 56, 29, 959),
+        ),
+    ];
+    for (part, expected) in cases {
+        let sql = format!("SELECT DATE_TRUNC('{}', last_update) FROM rental", part);
+        assert_eq!(
+            run(&sql),
+            Field::Timestamp(DateTime::from_utc(expected, Utc.fix())),
+            "{}",
+            part
+        );
+    }
+
+    assert_eq!(
+        run("SELECT DATE_TRUNC('month', return_date) FROM rental"),
+        Field::Timestamp(DateTime::from_utc(
+            NaiveDate::from_ymd(2022, 9, 1).and_hms(0, 0, 0),
+            Utc.fix()
+        ))
+    );
+}
+
+#[test]
+fn test_date_trunc_keeps_time_zone() {
+    let offset = FixedOffset::east(7 * 3600);
+    let timestamp = offset.from_utc_datetime(&NaiveDate::from_ymd(2022, 9, 16).and_hms(22, 30, 0));
+    let f = run_scalar_fct(
+        "SELECT DATE_TRUNC('day', last_update) FROM rental",
+        rental_schema(),
+        rental(Field::Timestamp(timestamp)),
+    );
+    assert_eq!(
+        f,
+        Field::Timestamp(offset.ymd(2022, 9, 17).and_hms(0, 0, 0))
+    );
+}
+
+#[test]
+fn test_now() {
+    let before = Utc::now();
+    let f = run("SELECT NOW() FROM rental");
+    let after = Utc::now();
+    match f {
+        Field::Timestamp(now) => assert!(before <= now && now <= after),
+        _ => panic!("NOW() should return a timestamp, got {}", f),
+    }
+}
+
+#[test]
+fn test_now_is_fixed_per_epoch() {
+    let select = get_select("SELECT NOW() FROM rental").unwrap();
+    let mut processor = ProjectionProcessorFactory::_new(select.projection)
+        .build(
+            HashMap::from([(DEFAULT_PORT_HANDLE, rental_schema())]),
+            HashMap::new(),
+        )
+        .unwrap();
+    let tmp_dir = TempDir::new("test").unwrap();
+    let mut storage = LmdbEnvironmentManager::create(tmp_dir.path(), "now_test").unwrap();
+    processor.init(&mut storage).unwrap();
+    let tx = storage.create_txn().unwrap();
+    let mut fw = TestChannelForwarder { operations: vec![] };
+
+    let mut now = |processor: &mut Box<dyn Processor>| {
+        let op = Operation::Insert {
+            new: Record::new(None, rental(Field::Null), None),
+        };
+        processor
+            .process(DEFAULT_PORT_HANDLE, op, &mut fw, &tx, &HashMap::new())
+            .unwrap();
+        match fw.operations.pop() {
+            Some(Operation::Insert { new }) => new.values[0].clone(),
+            op => panic!("Unexpected operation {:?}", op),
+        }
+    };
+    let first = now(&mut processor);
+    std::thread::sleep(Duration::from_millis(2));
+    assert_eq!(now(&mut processor), first);
+
+    // The next epoch gets a new time
+    processor
+        .flush(&mut TestChannelForwarder { operations: vec![] }, &tx)
+        .unwrap();
+    assert!(now(&mut processor) > first);
+}
+
+#[test]
+fn test_datetime_types() {
+    let schema = get_output_schema(
+        "SELECT EXTRACT(YEAR FROM last_update) AS year, \
+         DATE_TRUNC('day', return_date) AS day, NOW() AS now FROM rental",
+    )
+    .unwrap();
+    assert_eq!(
+        schema.fields,
+        vec![
+            FieldDefinition::new(String::from("year"), FieldType::Int, true),
+            FieldDefinition::new(String::from("day"), FieldType::Timestamp, false),
+            FieldDefinition::new(String::from("now"), FieldType::Timestamp, false),
+        ]
+    );
+
+    assert!(matches!(
+        get_output_schema("SELECT EXTRACT(YEAR FROM rental_id) FROM rental"),
+        Err(ExecutionError::InternalError(_))
+    ));
+    assert!(matches!(
+        get_output_schema("SELECT DATE_TRUNC('fortnight', last_update) FROM rental"),
+        Err(ExecutionError::InternalError(_))
+    ));
+    assert!(matches!(
+        get_output_schema("SELECT DATE_TRUNC('dow', last_update) FROM rental"),
+        Err(ExecutionError::InternalError(_))
+    ));
+}
@@ -9,8 +9,8 @@ use dozer_types::types::{Field, Operation, Record, Schema};
 use std::collections::HashMap;
 use tempdir::TempDir;
 
-struct TestChannelForwarder {
-    operations: Vec<Operation>,
+pub(crate) struct TestChannelForwarder {
+    pub operations: Vec<Operation>,
 }
 
 impl ProcessorChannelForwarder for TestChannelForwarder {
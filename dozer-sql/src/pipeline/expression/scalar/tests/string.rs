@@ -1,7 +1,9 @@
 use crate::pipeline::expression::execution::Expression::Literal;
 use crate::pipeline::expression::scalar::{
-    string::evaluate_like, tests::scalar_common::run_scalar_fct,
+    string::{evaluate_like, LikePattern},
+    tests::scalar_common::run_scalar_fct,
 };
+use dozer_types::ordered_float::OrderedFloat;
 use dozer_types::types::{Field, FieldDefinition, FieldType, Record, Schema};
 
 #[test]
@@ -59,15 +61,86 @@ fn test_concat_wrong_schema() {
                 false,
             )
             .field(
-                FieldDefinition::new(String::from("ln"), FieldType::Int, false),
+                FieldDefinition::new(String::from("ln"), FieldType::Binary, false),
                 false,
             )
             .clone(),
-        vec![Field::String("John".to_string()), Field::Int(0)],
+        vec![Field::String("John".to_string()), Field::Binary(vec![0])],
     );
     assert_eq!(f, Field::String("JohnDoe".to_string()));
 }
 
+fn run_concat(sql: &str, last_name: Field) -> Field {
+    run_scalar_fct(
+        sql,
+        Schema::empty()
+            .field(
+                FieldDefinition::new(String::from("first_name"), FieldType::String, false),
+                false,
+            )
+            .field(
+                FieldDefinition::new(String::from("last_name"), FieldType::String, true),
+                false,
+            )
+            .field(
+                FieldDefinition::new(String::from("actor_id"), FieldType::Int, false),
+                false,
+            )
+            .field(
+                FieldDefinition::new(String::from("rating"), FieldType::Float, false),
+                false,
+            )
+            .clone(),
+        vec![
+            Field::String("PENELOPE".to_string()),
+            last_name,
+            Field::Int(1),
+            Field::Float(OrderedFloat(4.5)),
+        ],
+    )
+}
+
+#[test]
+fn test_concat_operator() {
+    assert_eq!(
+        run_concat(
+            "SELECT first_name || ' ' || last_name FROM actor",
+            Field::String("GUINESS".to_string())
+        ),
+        Field::String("PENELOPE GUINESS".to_string())
+    );
+    assert_eq!(
+        run_concat(
+            "SELECT first_name || ' ' || last_name FROM actor",
+            Field::Null
+        ),
+        Field::Null
+    );
+    assert_eq!(
+        run_concat("SELECT actor_id || ':' || rating FROM actor", Field::Null),
+        Field::String("1:4.5".to_string())
+    );
+}
+
+#[test]
+fn test_concat_variadic() {
+    assert_eq!(
+        run_concat(
+            "SELECT CONCAT(actor_id, '-', first_name, '-', last_name) FROM actor",
+            Field::String("GUINESS".to_string())
+        ),
+        Field::String("1-PENELOPE-GUINESS".to_string())
+    );
+    // NULL arguments are skipped
+    assert_eq!(
+        run_concat(
+            "SELECT CONCAT(first_name, last_name, NULL, rating) FROM actor",
+            Field::Null
+        ),
+        Field::String("PENELOPE4.5".to_string())
+    );
+}
+
 #[test]
 fn test_ucase() {
     let f = run_scalar_fct(
@@ -113,6 +186,78 @@ fn test_length() {
     assert_eq!(f, Field::UInt(4));
 }
 
+fn run_string_fct(sql: &str, value: Field) -> Field {
+    run_scalar_fct(
+        sql,
+        Schema::empty()
+            .field(
+                FieldDefinition::new(String::from("fn"), FieldType::String, true),
+                false,
+            )
+            .clone(),
+        vec![value],
+    )
+}
+
+#[test]
+fn test_upper_lower() {
+    let value = || Field::String("Zoë".to_string());
+    assert_eq!(
+        run_string_fct("SELECT UPPER(fn) FROM USERS", value()),
+        Field::String("ZOË".to_string())
+    );
+    assert_eq!(
+        run_string_fct("SELECT LOWER(fn) FROM USERS", value()),
+        Field::String("zoë".to_string())
+    );
+    assert_eq!(
+        run_string_fct("SELECT LOWER(fn) FROM USERS", Field::Null),
+        Field::Null
+    );
+}
+
+#[test]
+fn test_length_multibyte() {
+    assert_eq!(
+        run_string_fct(
+            "SELECT LENGTH(fn) FROM USERS",
+            Field::String("Zoë 日本".to_string())
+        ),
+        Field::UInt(6)
+    );
+    assert_eq!(
+        run_string_fct("SELECT LENGTH(fn) FROM USERS", Field::Null),
+        Field::Null
+    );
+}
+
+#[test]
+fn test_substring() {
+    let value = || Field::String("Zoë 日本語".to_string());
+    let cases = [
+        ("SELECT SUBSTRING(fn FROM 3 FOR 4) FROM USERS", "ë 日本"),
+        ("SELECT SUBSTRING(fn FROM 5) FROM USERS", "日本語"),
+        ("SELECT SUBSTRING(fn FOR 2) FROM USERS", "Zo"),
+        ("SELECT SUBSTRING(fn, 6, 1) FROM USERS", "本"),
+        // Out of range offsets are clamped
+        ("SELECT SUBSTRING(fn FROM -1 FOR 4) FROM USERS", "Zo"),
+        ("SELECT SUBSTRING(fn FROM 6 FOR 100) FROM USERS", "本語"),
+        ("SELECT SUBSTRING(fn FROM 100) FROM USERS", ""),
+    ];
+    for (sql, expected) in cases {
+        assert_eq!(
+            run_string_fct(sql, value()),
+            Field::String(expected.to_string()),
+            "{}",
+            sql
+        );
+    }
+    assert_eq!(
+        run_string_fct("SELECT SUBSTRING(fn FROM 2) FROM USERS", Field::Null),
+        Field::Null
+    );
+}
+
 #[test]
 fn test_trim() {
     let f = run_scalar_fct(
@@ -218,50 +363,101 @@ fn test_ttrim_value() {
     assert_eq!(f, Field::String("___John".to_string()));
 }
 
+fn like(value: Field, pattern: &str, escape: Option<char>, negated: bool) -> Field {
+    let pattern = LikePattern::new(pattern, escape).unwrap();
+    evaluate_like(
+        &Schema::empty(),
+        &Literal(value),
+        &pattern,
+        negated,
+        &Record::new(None, vec![], None),
+    )
+    .unwrap()
+}
+
 #[test]
 fn test_like() {
     let row = Record::new(None, vec![], None);
 
     let value = Box::new(Literal(Field::String("Hello, World!".to_owned())));
-    let pattern = Box::new(Literal(Field::String("Hello%".to_owned())));
+    let pattern = LikePattern::new("Hello%", None).unwrap();
 
     assert_eq!(
-        evaluate_like(&Schema::empty(), &value, &pattern, None, &row).unwrap(),
+        evaluate_like(&Schema::empty(), &value, &pattern, false, &row).unwrap(),
         Field::Boolean(true)
     );
 
     let value = Box::new(Literal(Field::String("Hello, World!".to_owned())));
-    let pattern = Box::new(Literal(Field::String("Hello, _orld!".to_owned())));
+    let pattern = LikePattern::new("Hello, _orld!", None).unwrap();
 
     assert_eq!(
-        evaluate_like(&Schema::empty(), &value, &pattern, None, &row).unwrap(),
+        evaluate_like(&Schema::empty(), &value, &pattern, false, &row).unwrap(),
         Field::Boolean(true)
     );
 
     let value = Box::new(Literal(Field::String("Bye, World!".to_owned())));
-    let pattern = Box::new(Literal(Field::String("Hello%".to_owned())));
+    let pattern = LikePattern::new("Hello%", None).unwrap();
 
     assert_eq!(
-        evaluate_like(&Schema::empty(), &value, &pattern, None, &row).unwrap(),
+        evaluate_like(&Schema::empty(), &value, &pattern, false, &row).unwrap(),
         Field::Boolean(false)
     );
 
     let value = Box::new(Literal(Field::String("Hello, World!".to_owned())));
-    let pattern = Box::new(Literal(Field::String("Hello, _!".to_owned())));
+    let pattern = LikePattern::new("Hello, _!", None).unwrap();
 
     assert_eq!(
-        evaluate_like(&Schema::empty(), &value, &pattern, None, &row).unwrap(),
+        evaluate_like(&Schema::empty(), &value, &pattern, false, &row).unwrap(),
         Field::Boolean(false)
     );
 
     let value = Box::new(Literal(Field::String("Hello, $%".to_owned())));
-    let pattern = Box::new(Literal(Field::String("Hello, %".to_owned())));
-    let escape = Some('$');
+    let pattern = LikePattern::new("Hello, %", Some('$')).unwrap();
+
+    assert_eq!(
+        evaluate_like(&Schema::empty(), &value, &pattern, false, &row).unwrap(),
+        Field::Boolean(true)
+    );
+}
 
+#[test]
+fn test_like_pattern() {
+    let value = || Field::String("Hello, World!".to_owned());
+
+    assert_eq!(like(value(), "%o%o%!", None, false), Field::Boolean(true));
+    assert_eq!(like(value(), "%World", None, false), Field::Boolean(false));
+    assert_eq!(like(value(), "Hello%", None, true), Field::Boolean(false));
+    assert_eq!(like(Field::Null, "Hello%", None, false), Field::Null);
+}
+
+#[test]
+fn test_like_escape_pattern() {
+    let value = Field::String("100%".to_owned());
     assert_eq!(
-        evaluate_like(&Schema::empty(), &value, &pattern, escape, &row).unwrap(),
+        like(value.clone(), "100$%", Some('$'), false),
         Field::Boolean(true)
     );
+    assert_eq!(
+        like(value.clone(), "10$_%", Some('$'), false),
+        Field::Boolean(false)
+    );
+    assert_eq!(like(value, "100\\%", None, false), Field::Boolean(true));
+    assert!(LikePattern::new("100$", Some('$')).is_err());
+}
+
+#[test]
+fn test_like_projection() {
+    let f = run_scalar_fct(
+        "SELECT first_name LIKE 'GUI%' FROM users",
+        Schema::empty()
+            .field(
+                FieldDefinition::new(String::from("first_name"), FieldType::String, false),
+                false,
+            )
+            .clone(),
+        vec![Field::String("GUINESS".to_string())],
+    );
+    assert_eq!(f, Field::Boolean(true));
 }
 
 #[test]
@@ -1,4 +1,14 @@
 #[cfg(test)]
 mod builder_test;
 #[cfg(test)]
+mod case;
+#[cfg(test)]
+mod cast;
+#[cfg(test)]
 mod execution;
+#[cfg(test)]
+mod folding;
+#[cfg(test)]
+mod in_list;
+#[cfg(test)]
+mod mathematical;
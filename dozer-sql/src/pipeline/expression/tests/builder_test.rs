@@ -1,6 +1,7 @@
 use dozer_types::types::{FieldDefinition, FieldType, Schema};
 use sqlparser::ast::Ident;
 
+use crate::pipeline::errors::PipelineError;
 use crate::pipeline::expression::builder::{compare_name, get_field_index};
 
 #[test]
@@ -92,5 +93,12 @@ fn test_get_field_index() {
             false,
         )
         .clone();
-    assert!(get_field_index(&[Ident::new("name")], &schema).is_err());
+    assert!(matches!(
+        get_field_index(&[Ident::new("name")], &schema),
+        Err(PipelineError::AmbiguousColumn(_))
+    ));
+    assert!(matches!(
+        get_field_index(&[Ident::new("salary")], &schema),
+        Err(PipelineError::ColumnNotFound(_))
+    ));
 }
@@ -0,0 +1,120 @@
+use crate::pipeline::builder::get_select;
+use crate::pipeline::errors::PipelineError;
+use crate::pipeline::expression::builder::{BuilderExpressionType, ExpressionBuilder};
+use crate::pipeline::expression::execution::{Expression, ExpressionExecutor};
+use dozer_types::ordered_float::OrderedFloat;
+use dozer_types::types::{Field, FieldDefinition, FieldType, Record, Schema};
+use sqlparser::ast::SelectItem;
+
+fn get_schema() -> Schema {
+    Schema::empty()
+        .field(
+            FieldDefinition::new(String::from("salary"), FieldType::Int, true),
+            false,
+        )
+        .field(
+            FieldDefinition::new(String::from("grade"), FieldType::String, false),
+            false,
+        )
+        .clone()
+}
+
+fn build(sql: &str) -> Result<Box<Expression>, PipelineError> {
+    let select = get_select(sql).unwrap();
+    let expr = match &select.projection[0] {
+        SelectItem::UnnamedExpr(expr) => expr.clone(),
+        item => panic!("Unexpected projection {:?}", item),
+    };
+    ExpressionBuilder.build(&BuilderExpressionType::FullExpression, &expr, &get_schema())
+}
+
+fn evaluate(expression: &Expression, salary: Field, grade: &str) -> Field {
+    let record = Record::new(None, vec![salary, Field::String(grade.to_string())], None);
+    expression.evaluate(&record, &get_schema()).unwrap()
+}
+
+#[test]
+fn test_searched_case() {
+    let e = build(
+        "SELECT CASE WHEN salary > 5000 THEN 'very high' WHEN salary > 1000 THEN 'high' \
+         ELSE 'low' END FROM employees",
+    )
+    .unwrap();
+
+    let high = Field::String("high".to_string());
+    let low = Field::String("low".to_string());
+    assert_eq!(
+        evaluate(&e, Field::Int(9000), "a"),
+        Field::String("very high".to_string())
+    );
+    assert_eq!(evaluate(&e, Field::Int(2000), "a"), high);
+    assert_eq!(evaluate(&e, Field::Int(10), "a"), low);
+    // A NULL condition doesn't hold
+    assert_eq!(evaluate(&e, Field::Null, "a"), low);
+
+    let typ = e.get_type(&get_schema()).unwrap();
+    assert_eq!(typ.return_type, FieldType::String);
+    assert!(!typ.nullable);
+}
+
+#[test]
+fn test_simple_case() {
+    let e =
+        build("SELECT CASE grade WHEN 'a' THEN 3 WHEN 'b' THEN 2 ELSE salary END FROM employees")
+            .unwrap();
+
+    assert_eq!(evaluate(&e, Field::Int(0), "a"), Field::Int(3));
+    assert_eq!(evaluate(&e, Field::Int(0), "b"), Field::Int(2));
+    assert_eq!(evaluate(&e, Field::Int(7), "c"), Field::Int(7));
+
+    let typ = e.get_type(&get_schema()).unwrap();
+    assert_eq!(typ.return_type, FieldType::Int);
+    // The ELSE result is nullable
+    assert!(typ.nullable);
+}
+
+#[test]
+fn test_case_without_else() {
+    let e = build("SELECT CASE WHEN salary > 1000 THEN salary END FROM employees").unwrap();
+
+    assert_eq!(evaluate(&e, Field::Int(2000), "a"), Field::Int(2000));
+    assert_eq!(evaluate(&e, Field::Int(10), "a"), Field::Null);
+    assert!(e.get_type(&get_schema()).unwrap().nullable);
+}
+
+#[test]
+fn test_case_common_type() {
+    let e = build("SELECT CASE WHEN salary > 1000 THEN 1 ELSE 0.5 END FROM employees").unwrap();
+    assert_eq!(
+        e.get_type(&get_schema()).unwrap().return_type,
+        FieldType::Float
+    );
+    // The Int result is cast to Float
+    assert_eq!(
+        evaluate(&e, Field::Int(2000), "a"),
+        Field::Float(OrderedFloat(1.0))
+    );
+    assert_eq!(
+        evaluate(&e, Field::Int(10), "a"),
+        Field::Float(OrderedFloat(0.5))
+    );
+
+    let e =
+        build("SELECT CASE WHEN salary > 1000 THEN NULL ELSE grade END FROM employees").unwrap();
+    let typ = e.get_type(&get_schema()).unwrap();
+    assert_eq!(typ.return_type, FieldType::String);
+    assert!(typ.nullable);
+    assert_eq!(evaluate(&e, Field::Int(2000), "a"), Field::Null);
+}
+
+#[test]
+fn test_case_incompatible_types() {
+    assert!(matches!(
+        build("SELECT CASE WHEN salary > 1000 THEN salary ELSE grade END FROM employees"),
+        Err(PipelineError::InvalidExpression(_))
+    ));
+    assert!(matches!(
+        build("SELECT CASE WHEN salary > 1000 THEN NULL END FROM employees"),
+        Err(PipelineError::InvalidExpression(_))
+    ));
+}
@@ -0,0 +1,106 @@
+use crate::pipeline::builder::get_select;
+use crate::pipeline::errors::PipelineError;
+use crate::pipeline::expression::execution::{Expression, ExpressionExecutor};
+use crate::pipeline::projection::factory::ProjectionProcessorFactory;
+use dozer_core::dag::dag::DEFAULT_PORT_HANDLE;
+use dozer_core::dag::node::ProcessorFactory;
+use dozer_types::chrono::DateTime;
+use dozer_types::ordered_float::OrderedFloat;
+use dozer_types::rust_decimal::Decimal;
+use dozer_types::types::{Field, FieldDefinition, FieldType, Record, Schema};
+
+fn cast(value: Field, typ: FieldType) -> Result<Field, PipelineError> {
+    let e = Expression::Cast {
+        arg: Box::new(Expression::Literal(value)),
+        typ,
+    };
+    e.evaluate(&Record::new(None, vec![], None), &Schema::empty())
+}
+
+#[test]
+fn test_cast_numbers() {
+    assert_eq!(
+        cast(Field::Float(OrderedFloat(10.7)), FieldType::Int).unwrap(),
+        Field::Int(10)
+    );
+    assert_eq!(
+        cast(Field::Decimal(Decimal::new(-1234, 2)), FieldType::Int).unwrap(),
+        Field::Int(-12)
+    );
+    assert_eq!(
+        cast(Field::Int(5), FieldType::Float).unwrap(),
+        Field::Float(OrderedFloat(5.0))
+    );
+    assert_eq!(
+        cast(Field::String("12.50".to_string()), FieldType::Decimal).unwrap(),
+        Field::Decimal(Decimal::new(1250, 2))
+    );
+    assert_eq!(
+        cast(Field::Int(42), FieldType::String).unwrap(),
+        Field::String("42".to_string())
+    );
+    assert_eq!(cast(Field::Null, FieldType::Int).unwrap(), Field::Null);
+    assert!(matches!(
+        cast(Field::String("abc".to_string()), FieldType::Int),
+        Err(PipelineError::InvalidCast(_, FieldType::Int))
+    ));
+}
+
+#[test]
+fn test_cast_timestamp() {
+    assert_eq!(
+        cast(
+            Field::String("2022-11-30T10:15:00+00:00".to_string()),
+            FieldType::Timestamp
+        )
+        .unwrap(),
+        Field::Timestamp(DateTime::parse_from_rfc3339("2022-11-30T10:15:00+00:00").unwrap())
+    );
+    assert!(matches!(
+        cast(
+            Field::String("30/11/2022".to_string()),
+            FieldType::Timestamp
+        ),
+        Err(PipelineError::InvalidCast(_, FieldType::Timestamp))
+    ));
+}
+
+#[test]
+fn test_cast_type() {
+    let schema = Schema::empty()
+        .field(
+            FieldDefinition::new(String::from("salary"), FieldType::Float, true),
+            false,
+        )
+        .field(
+            FieldDefinition::new(String::from("last_update"), FieldType::String, false),
+            false,
+        )
+        .clone();
+
+    let select = get_select(
+        "SELECT CAST(salary AS BIGINT) AS salary, CAST(last_update AS TIMESTAMP) AS last_update FROM t1",
+    )
+    .unwrap();
+    let processor_factory = ProjectionProcessorFactory::_new(select.projection);
+    let r = processor_factory
+        .get_output_schema(
+            &DEFAULT_PORT_HANDLE,
+            &[(DEFAULT_PORT_HANDLE, schema)].into_iter().collect(),
+        )
+        .unwrap();
+
+    assert_eq!(
+        r,
+        Schema::empty()
+            .field(
+                FieldDefinition::new(String::from("salary"), FieldType::Int, true),
+                false,
+            )
+            .field(
+                FieldDefinition::new(String::from("last_update"), FieldType::Timestamp, false),
+                false,
+            )
+            .clone()
+    );
+}
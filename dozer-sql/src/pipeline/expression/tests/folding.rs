@@ -0,0 +1,160 @@
+use crate::pipeline::builder::get_select;
+use crate::pipeline::expression::builder::{BuilderExpressionType, ExpressionBuilder};
+use crate::pipeline::expression::execution::{Expression, ExpressionExecutor};
+use crate::pipeline::expression::operator::{BinaryOperatorType, UnaryOperatorType};
+use dozer_types::ordered_float::OrderedFloat;
+use dozer_types::types::{Field, FieldDefinition, FieldType, Record, Schema};
+use sqlparser::ast::SelectItem;
+
+fn get_schema() -> Schema {
+    Schema::empty()
+        .field(
+            FieldDefinition::new(String::from("salary"), FieldType::Int, true),
+            false,
+        )
+        .clone()
+}
+
+fn build(sql: &str) -> Box<Expression> {
+    let select = get_select(sql).unwrap();
+    let expr = match &select.projection[0] {
+        SelectItem::UnnamedExpr(expr) => expr.clone(),
+        item => panic!("Unexpected projection {:?}", item),
+    };
+    ExpressionBuilder
+        .build(&BuilderExpressionType::FullExpression, &expr, &get_schema())
+        .unwrap()
+}
+
+fn evaluate(expression: &Expression, salary: Field) -> Field {
+    let record = Record::new(None, vec![salary], None);
+    expression.evaluate(&record, &get_schema()).unwrap()
+}
+
+fn literal(field: Field) -> Box<Expression> {
+    Box::new(Expression::Literal(field))
+}
+
+fn binary(
+    left: Box<Expression>,
+    operator: BinaryOperatorType,
+    right: Box<Expression>,
+) -> Box<Expression> {
+    Box::new(Expression::BinaryOperator {
+        left,
+        operator,
+        right,
+    })
+}
+
+#[test]
+fn test_fold_arithmetic() {
+    let folded = build("SELECT salary * (2 + 3) FROM users");
+    let salary = Box::new(Expression::Column { index: 0 });
+    assert_eq!(
+        folded,
+        binary(
+            salary.clone(),
+            BinaryOperatorType::Mul,
+            literal(Field::Int(5))
+        )
+    );
+
+    let unfolded = binary(
+        salary,
+        BinaryOperatorType::Mul,
+        binary(
+            literal(Field::Int(2)),
+            BinaryOperatorType::Add,
+            literal(Field::Int(3)),
+        ),
+    );
+    for salary in [Field::Int(1000), Field::Int(-7), Field::Null] {
+        assert_eq!(
+            evaluate(&folded, salary.clone()),
+            evaluate(&unfolded, salary)
+        );
+    }
+
+    assert_eq!(
+        build("SELECT -(1 + 2) * 4 / 2 FROM users"),
+        literal(Field::Float(OrderedFloat(-6.0)))
+    );
+}
+
+#[test]
+fn test_fold_comparison_and_logical() {
+    assert_eq!(
+        build("SELECT 1 < 2 AND NOT 3 = 4 FROM users"),
+        literal(Field::Boolean(true))
+    );
+
+    let folded = build("SELECT salary > 10 OR 1 > 2 FROM users");
+    let unfolded = binary(
+        binary(
+            Box::new(Expression::Column { index: 0 }),
+            BinaryOperatorType::Gt,
+            literal(Field::Int(10)),
+        ),
+        BinaryOperatorType::Or,
+        binary(
+            literal(Field::Int(1)),
+            BinaryOperatorType::Gt,
+            literal(Field::Int(2)),
+        ),
+    );
+    assert_eq!(
+        folded,
+        binary(
+            binary(
+                Box::new(Expression::Column { index: 0 }),
+                BinaryOperatorType::Gt,
+                literal(Field::Int(10)),
+            ),
+            BinaryOperatorType::Or,
+            literal(Field::Boolean(false)),
+        )
+    );
+    for salary in [Field::Int(100), Field::Int(1), Field::Null] {
+        assert_eq!(
+            evaluate(&folded, salary.clone()),
+            evaluate(&unfolded, salary)
+        );
+    }
+}
+
+#[test]
+fn test_fold_keeps_null_results() {
+    // Division by zero evaluates to NULL, which isn't typed as a literal
+    assert_eq!(
+        build("SELECT 1 / 0 FROM users"),
+        binary(
+            literal(Field::Int(1)),
+            BinaryOperatorType::Div,
+            literal(Field::Int(0)),
+        )
+    );
+
+    assert_eq!(
+        build("SELECT -salary FROM users"),
+        Box::new(Expression::UnaryOperator {
+            operator: UnaryOperatorType::Minus,
+            arg: Box::new(Expression::Column { index: 0 }),
+        })
+    );
+}
+
+#[test]
+fn test_fold_keeps_overflowing_operators() {
+    let expression = build("SELECT 9223372036854775807 + 1 FROM users");
+    assert_eq!(
+        expression,
+        binary(
+            literal(Field::Int(i64::MAX)),
+            BinaryOperatorType::Add,
+            literal(Field::Int(1)),
+        )
+    );
+    let record = Record::new(None, vec![Field::Null], None);
+    assert!(expression.evaluate(&record, &get_schema()).is_err());
+}
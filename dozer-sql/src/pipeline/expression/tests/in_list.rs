@@ -0,0 +1,89 @@
+use crate::pipeline::builder::get_select;
+use crate::pipeline::errors::PipelineError;
+use crate::pipeline::expression::builder::{BuilderExpressionType, ExpressionBuilder};
+use crate::pipeline::expression::execution::{Expression, ExpressionExecutor};
+use dozer_types::ordered_float::OrderedFloat;
+use dozer_types::types::{Field, FieldDefinition, FieldType, Record, Schema};
+
+fn in_list(value: Field, list: Vec<Field>, negated: bool) -> Field {
+    let e = Expression::InList {
+        arg: Box::new(Expression::Literal(value)),
+        list,
+        negated,
+    };
+    e.evaluate(&Record::new(None, vec![], None), &Schema::empty())
+        .unwrap()
+}
+
+#[test]
+fn test_in_list() {
+    let list = vec![Field::Int(1), Field::Int(5)];
+    assert_eq!(
+        in_list(Field::Int(5), list.clone(), false),
+        Field::Boolean(true)
+    );
+    assert_eq!(
+        in_list(Field::Int(2), list.clone(), false),
+        Field::Boolean(false)
+    );
+    assert_eq!(
+        in_list(Field::Int(5), list.clone(), true),
+        Field::Boolean(false)
+    );
+    assert_eq!(
+        in_list(Field::Int(2), list.clone(), true),
+        Field::Boolean(true)
+    );
+    assert_eq!(
+        in_list(Field::Float(OrderedFloat(1.0)), list, false),
+        Field::Boolean(true)
+    );
+}
+
+#[test]
+fn test_in_list_null() {
+    let list = vec![Field::Int(1), Field::Null];
+    assert_eq!(
+        in_list(Field::Int(1), list.clone(), false),
+        Field::Boolean(true)
+    );
+    assert_eq!(in_list(Field::Int(2), list.clone(), false), Field::Null);
+    assert_eq!(in_list(Field::Int(2), list, true), Field::Null);
+    assert_eq!(
+        in_list(Field::Null, vec![Field::Int(1)], false),
+        Field::Null
+    );
+}
+
+#[test]
+fn test_in_list_builder() {
+    let schema = Schema::empty()
+        .field(
+            FieldDefinition::new(String::from("actor_id"), FieldType::Int, false),
+            false,
+        )
+        .clone();
+
+    let build = |sql: &str| {
+        let select = get_select(sql).unwrap();
+        let builder = ExpressionBuilder;
+        builder.build(
+            &BuilderExpressionType::FullExpression,
+            &select.selection.unwrap(),
+            &schema,
+        )
+    };
+
+    assert_eq!(
+        *build("SELECT actor_id FROM actor WHERE actor_id NOT IN (1, 5)").unwrap(),
+        Expression::InList {
+            arg: Box::new(Expression::Column { index: 0 }),
+            list: vec![Field::Int(1), Field::Int(5)],
+            negated: true,
+        }
+    );
+    assert!(matches!(
+        build("SELECT actor_id FROM actor WHERE actor_id IN (1, actor_id)"),
+        Err(PipelineError::InvalidExpression(_))
+    ));
+}
@@ -0,0 +1,310 @@
+use crate::pipeline::expression::execution::{Expression, ExpressionExecutor};
+use crate::pipeline::expression::operator::{BinaryOperatorType, UnaryOperatorType};
+use dozer_types::ordered_float::OrderedFloat;
+use dozer_types::rust_decimal::Decimal;
+use dozer_types::types::{Field, FieldDefinition, FieldType, Record, Schema};
+
+fn get_schema(typ: FieldType) -> Schema {
+    Schema::empty()
+        .field(FieldDefinition::new("a".to_string(), typ, false), false)
+        .field(FieldDefinition::new("b".to_string(), typ, false), false)
+        .clone()
+}
+
+fn binary(operator: BinaryOperatorType) -> Expression {
+    Expression::BinaryOperator {
+        left: Box::new(Expression::Column { index: 0 }),
+        operator,
+        right: Box::new(Expression::Column { index: 1 }),
+    }
+}
+
+fn evaluate(operator: BinaryOperatorType, typ: FieldType, a: Field, b: Field) -> Field {
+    let record = Record::new(None, vec![a, b], None);
+    binary(operator)
+        .evaluate(&record, &get_schema(typ))
+        .unwrap_or_else(|e| panic!("{}", e.to_string()))
+}
+
+#[test]
+fn test_div_mod_by_zero_column() {
+    let cases = [
+        (FieldType::Int, Field::Int(7), Field::Int(0)),
+        (
+            FieldType::Float,
+            Field::Float(OrderedFloat(7.5)),
+            Field::Float(OrderedFloat(0.0)),
+        ),
+        (
+            FieldType::Decimal,
+            Field::Decimal(Decimal::new(75, 1)),
+            Field::Decimal(Decimal::ZERO),
+        ),
+    ];
+    for (typ, a, zero) in cases {
+        for operator in [BinaryOperatorType::Div, BinaryOperatorType::Mod] {
+            assert_eq!(
+                evaluate(operator.clone(), typ, a.clone(), zero.clone()),
+                Field::Null,
+                "{} {:?} {}",
+                a,
+                operator,
+                zero
+            );
+        }
+    }
+}
+
+#[test]
+fn test_div_mod_by_non_zero_column() {
+    assert_eq!(
+        evaluate(
+            BinaryOperatorType::Div,
+            FieldType::Int,
+            Field::Int(7),
+            Field::Int(2)
+        ),
+        Field::Float(OrderedFloat(3.5))
+    );
+    assert_eq!(
+        evaluate(
+            BinaryOperatorType::Mod,
+            FieldType::Int,
+            Field::Int(7),
+            Field::Int(2)
+        ),
+        Field::Int(1)
+    );
+    assert_eq!(
+        evaluate(
+            BinaryOperatorType::Div,
+            FieldType::Decimal,
+            Field::Decimal(Decimal::new(75, 1)),
+            Field::Decimal(Decimal::new(25, 1))
+        ),
+        Field::Decimal(Decimal::new(3, 0))
+    );
+}
+
+#[test]
+fn test_div_mod_type_is_nullable() {
+    for typ in [FieldType::Int, FieldType::Float, FieldType::Decimal] {
+        for operator in [BinaryOperatorType::Div, BinaryOperatorType::Mod] {
+            let expression_type = binary(operator).get_type(&get_schema(typ)).unwrap();
+            assert!(expression_type.nullable);
+        }
+    }
+}
+
+fn field_type(field: &Field) -> Option<FieldType> {
+    match field {
+        Field::Int(_) => Some(FieldType::Int),
+        Field::Float(_) => Some(FieldType::Float),
+        Field::Decimal(_) => Some(FieldType::Decimal),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_arithmetic_type_promotion() {
+    let values = [
+        Field::Int(6),
+        Field::Float(OrderedFloat(1.5)),
+        Field::Decimal(Decimal::new(25, 1)),
+        Field::Null,
+    ];
+    let operators = [
+        BinaryOperatorType::Add,
+        BinaryOperatorType::Sub,
+        BinaryOperatorType::Mul,
+        BinaryOperatorType::Div,
+        BinaryOperatorType::Mod,
+    ];
+    for operator in operators {
+        for a in &values {
+            for b in &values {
+                let schema = Schema::empty()
+                    .field(
+                        FieldDefinition::new(
+                            "a".to_string(),
+                            field_type(a).unwrap_or(FieldType::Int),
+                            true,
+                        ),
+                        false,
+                    )
+                    .field(
+                        FieldDefinition::new(
+                            "b".to_string(),
+                            field_type(b).unwrap_or(FieldType::Int),
+                            true,
+                        ),
+                        false,
+                    )
+                    .clone();
+                let record = Record::new(None, vec![a.clone(), b.clone()], None);
+                let result = binary(operator.clone())
+                    .evaluate(&record, &schema)
+                    .unwrap_or_else(|e| panic!("{}", e.to_string()));
+
+                match (field_type(a), field_type(b)) {
+                    (Some(_), Some(_)) => {
+                        let expression_type = binary(operator.clone()).get_type(&schema).unwrap();
+                        assert_eq!(
+                            field_type(&result),
+                            Some(expression_type.return_type),
+                            "{} {:?} {}",
+                            a,
+                            operator,
+                            b
+                        );
+                    }
+                    _ => assert_eq!(result, Field::Null, "{} {:?} {}", a, operator, b),
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_arithmetic_promoted_values() {
+    assert_eq!(
+        evaluate(
+            BinaryOperatorType::Mul,
+            FieldType::Int,
+            Field::Int(1000),
+            Field::Float(OrderedFloat(1.5))
+        ),
+        Field::Float(OrderedFloat(1500.0))
+    );
+    assert_eq!(
+        evaluate(
+            BinaryOperatorType::Add,
+            FieldType::Int,
+            Field::Int(1),
+            Field::Decimal(Decimal::new(25, 1))
+        ),
+        Field::Decimal(Decimal::new(35, 1))
+    );
+    assert_eq!(
+        evaluate(
+            BinaryOperatorType::Sub,
+            FieldType::Float,
+            Field::Float(OrderedFloat(0.5)),
+            Field::Decimal(Decimal::new(25, 1))
+        ),
+        Field::Decimal(Decimal::new(-2, 0))
+    );
+}
+
+#[test]
+fn test_arithmetic_non_numeric_operands() {
+    let schema = get_schema(FieldType::Int);
+    for (a, b) in [
+        (Field::String("a".to_string()), Field::Int(1)),
+        (Field::Null, Field::Boolean(true)),
+    ] {
+        let record = Record::new(None, vec![a, b], None);
+        assert!(binary(BinaryOperatorType::Add)
+            .evaluate(&record, &schema)
+            .is_err());
+    }
+
+    let schema = Schema::empty()
+        .field(
+            FieldDefinition::new("a".to_string(), FieldType::String, false),
+            false,
+        )
+        .field(
+            FieldDefinition::new("b".to_string(), FieldType::Int, false),
+            false,
+        )
+        .clone();
+    assert!(binary(BinaryOperatorType::Mul).get_type(&schema).is_err());
+}
+
+#[test]
+fn test_arithmetic_overflow() {
+    let cases = [
+        (
+            BinaryOperatorType::Add,
+            FieldType::Int,
+            Field::Int(i64::MAX),
+            Field::Int(1),
+        ),
+        (
+            BinaryOperatorType::Sub,
+            FieldType::Int,
+            Field::Int(i64::MIN),
+            Field::Int(1),
+        ),
+        (
+            BinaryOperatorType::Mul,
+            FieldType::Int,
+            Field::Int(i64::MAX),
+            Field::Int(2),
+        ),
+        (
+            BinaryOperatorType::Add,
+            FieldType::Decimal,
+            Field::Decimal(Decimal::MAX),
+            Field::Decimal(Decimal::new(1, 0)),
+        ),
+        (
+            BinaryOperatorType::Mul,
+            FieldType::Decimal,
+            Field::Decimal(Decimal::MAX),
+            Field::Decimal(Decimal::new(2, 0)),
+        ),
+    ];
+    for (operator, typ, a, b) in cases {
+        let record = Record::new(None, vec![a, b], None);
+        assert!(binary(operator)
+            .evaluate(&record, &get_schema(typ))
+            .is_err());
+    }
+}
+
+#[test]
+fn test_mod_min_by_minus_one() {
+    assert_eq!(
+        evaluate(
+            BinaryOperatorType::Mod,
+            FieldType::Int,
+            Field::Int(i64::MIN),
+            Field::Int(-1)
+        ),
+        Field::Int(0)
+    );
+}
+
+#[test]
+fn test_promoted_arithmetic_overflow() {
+    let schema = Schema::empty()
+        .field(
+            FieldDefinition::new("a".to_string(), FieldType::Int, false),
+            false,
+        )
+        .field(
+            FieldDefinition::new("b".to_string(), FieldType::Decimal, false),
+            false,
+        )
+        .clone();
+    for (a, b) in [
+        (Field::Int(i64::MAX), Field::Decimal(Decimal::MAX)),
+        (Field::Int(2), Field::Decimal(Decimal::MIN)),
+    ] {
+        let record = Record::new(None, vec![a, b], None);
+        assert!(binary(BinaryOperatorType::Mul)
+            .evaluate(&record, &schema)
+            .is_err());
+    }
+
+    let record = Record::new(None, vec![Field::Int(i64::MIN)], None);
+    let negation = Expression::UnaryOperator {
+        operator: UnaryOperatorType::Minus,
+        arg: Box::new(Expression::Column { index: 0 }),
+    };
+    assert!(negation
+        .evaluate(&record, &get_schema(FieldType::Int))
+        .is_err());
+}
@@ -0,0 +1,151 @@
+use crate::pipeline::errors::PipelineError;
+use crate::pipeline::state::{decode_count, get_values_key};
+use dozer_core::dag::channels::ProcessorChannelForwarder;
+use dozer_core::dag::dag::DEFAULT_PORT_HANDLE;
+use dozer_core::dag::epoch::Epoch;
+use dozer_core::dag::errors::ExecutionError;
+use dozer_core::dag::errors::ExecutionError::InternalError;
+use dozer_core::dag::node::{PortHandle, Processor};
+use dozer_core::dag::record_store::RecordReader;
+use dozer_core::storage::common::Database;
+use dozer_core::storage::errors::StorageError::InvalidDatabase;
+use dozer_core::storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
+use dozer_types::log::info;
+use dozer_types::types::{Operation, Record};
+use std::collections::HashMap;
+
+const SKIPPED_KEY: u8 = 1_u8;
+const EMITTED_KEY: u8 = 2_u8;
+
+/// Applies `LIMIT` and `OFFSET` to a stream.
+///
+/// Over an unbounded stream the output is a prefix: the first `offset` records received are
+/// skipped and the next `limit` are forwarded, in arrival order rather than in `ORDER BY` order.
+/// Retracting a forwarded record frees a slot for the next insert, but records that were
+/// skipped or dropped earlier are never brought back. Updates of forwarded records are
+/// forwarded, the others are dropped.
+#[derive(Debug)]
+pub struct LimitProcessor {
+    limit: Option<u64>,
+    offset: u64,
+    /// Number of copies of each forwarded record, keyed by its serialized values
+    db: Option<Database>,
+    meta_db: Option<Database>,
+}
+
+impl LimitProcessor {
+    pub fn new(limit: Option<u64>, offset: u64) -> Self {
+        Self {
+            limit,
+            offset,
+            db: None,
+            meta_db: None,
+        }
+    }
+
+    fn init_store(&mut self, env: &mut LmdbEnvironmentManager) -> Result<(), PipelineError> {
+        self.db = Some(env.open_database("limit", false)?);
+        self.meta_db = Some(env.open_database("limit_meta", false)?);
+        Ok(())
+    }
+
+    fn get_databases(&self) -> Result<(Database, Database), ExecutionError> {
+        match (self.db, self.meta_db) {
+            (Some(db), Some(meta_db)) => Ok((db, meta_db)),
+            _ => Err(ExecutionError::InternalDatabaseError(InvalidDatabase)),
+        }
+    }
+
+    /// Returns `true` if `record` is forwarded.
+    fn insert(&self, record: &Record, tx: &SharedTransaction) -> Result<bool, ExecutionError> {
+        let (db, meta_db) = self.get_databases()?;
+        let mut tx = tx.write();
+
+        let skipped = decode_count(tx.get(meta_db, &SKIPPED_KEY.to_be_bytes())?)?;
+        if skipped < self.offset {
+            tx.put(
+                meta_db,
+                &SKIPPED_KEY.to_be_bytes(),
+                &(skipped + 1).to_be_bytes(),
+            )?;
+            return Ok(false);
+        }
+
+        let emitted = decode_count(tx.get(meta_db, &EMITTED_KEY.to_be_bytes())?)?;
+        if self.limit.map_or(false, |limit| emitted >= limit) {
+            return Ok(false);
+        }
+        tx.put(
+            meta_db,
+            &EMITTED_KEY.to_be_bytes(),
+            &(emitted + 1).to_be_bytes(),
+        )?;
+
+        let key = get_values_key(record)?;
+        let copies = decode_count(tx.get(db, &key)?)?;
+        tx.put(db, &key, &(copies + 1).to_be_bytes())?;
+        Ok(true)
+    }
+
+    /// Returns `true` if `record` had been forwarded, and so must be retracted downstream.
+    fn delete(&self, record: &Record, tx: &SharedTransaction) -> Result<bool, ExecutionError> {
+        let (db, meta_db) = self.get_databases()?;
+        let mut tx = tx.write();
+
+        let key = get_values_key(record)?;
+        let copies = decode_count(tx.get(db, &key)?)?;
+        if copies == 0 {
+            return Ok(false);
+        }
+        if copies == 1 {
+            tx.del(db, &key, None)?;
+        } else {
+            tx.put(db, &key, &(copies - 1).to_be_bytes())?;
+        }
+
+        let emitted = decode_count(tx.get(meta_db, &EMITTED_KEY.to_be_bytes())?)?;
+        tx.put(
+            meta_db,
+            &EMITTED_KEY.to_be_bytes(),
+            &emitted.saturating_sub(1).to_be_bytes(),
+        )?;
+        Ok(true)
+    }
+}
+
+impl Processor for LimitProcessor {
+    fn init(&mut self, env: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+        info!("{:?}", "Initialising Limit Processor");
+        self.init_store(env).map_err(|e| InternalError(Box::new(e)))
+    }
+
+    fn commit(&self, _epoch: &Epoch, _tx: &SharedTransaction) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn process(
+        &mut self,
+        _from_port: PortHandle,
+        op: Operation,
+        fw: &mut dyn ProcessorChannelForwarder,
+        tx: &SharedTransaction,
+        _reader: &HashMap<PortHandle, RecordReader>,
+    ) -> Result<(), ExecutionError> {
+        let forward = match &op {
+            Operation::Insert { new } => self.insert(new, tx)?,
+            Operation::Delete { old } => self.delete(old, tx)?,
+            Operation::Update { old, new } => {
+                if self.delete(old, tx)? {
+                    // The freed slot is taken back by the new version of the record
+                    self.insert(new, tx)?
+                } else {
+                    false
+                }
+            }
+        };
+        if forward {
+            fw.send(op, DEFAULT_PORT_HANDLE)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use crate::pipeline::limit::factory::LimitProcessorFactory;
+use dozer_core::dag::channels::ProcessorChannelForwarder;
+use dozer_core::dag::dag::DEFAULT_PORT_HANDLE;
+use dozer_core::dag::errors::ExecutionError;
+use dozer_core::dag::node::{PortHandle, ProcessorFactory};
+use dozer_core::storage::lmdb_storage::LmdbEnvironmentManager;
+use dozer_types::types::{Field, Operation, Record};
+use std::collections::HashMap;
+use tempdir::TempDir;
+
+struct TestChannelForwarder {
+    operations: Vec<Operation>,
+}
+
+impl ProcessorChannelForwarder for TestChannelForwarder {
+    fn send(&mut self, op: Operation, _port: PortHandle) -> Result<(), ExecutionError> {
+        self.operations.push(op);
+        Ok(())
+    }
+}
+
+fn actor(id: i64, first_name: &str) -> Record {
+    Record::new(
+        None,
+        vec![Field::Int(id), Field::String(first_name.to_string())],
+        None,
+    )
+}
+
+fn insert(id: i64, first_name: &str) -> Operation {
+    Operation::Insert {
+        new: actor(id, first_name),
+    }
+}
+
+fn run_limit(limit: Option<u64>, offset: u64, ops: Vec<Operation>) -> Vec<Operation> {
+    let factory = LimitProcessorFactory::new(limit, offset);
+    let mut processor = factory.build(HashMap::new(), HashMap::new()).unwrap();
+
+    let tmp_dir = TempDir::new("test").unwrap();
+    let mut storage = LmdbEnvironmentManager::create(tmp_dir.path(), "limit_test").unwrap();
+    processor.init(&mut storage).unwrap();
+
+    let tx = storage.create_txn().unwrap();
+    let mut fw = TestChannelForwarder { operations: vec![] };
+    for op in ops {
+        processor
+            .process(DEFAULT_PORT_HANDLE, op, &mut fw, &tx, &HashMap::new())
+            .unwrap();
+    }
+    fw.operations
+}
+
+#[test]
+fn test_limit_offset() {
+    let ops = vec![
+        insert(1, "PENELOPE"),
+        insert(2, "NICK"),
+        insert(3, "ED"),
+        insert(4, "JENNIFER"),
+        insert(5, "JOHNNY"),
+    ];
+
+    assert_eq!(
+        run_limit(Some(2), 0, ops.clone()),
+        vec![insert(1, "PENELOPE"), insert(2, "NICK")]
+    );
+    assert_eq!(
+        run_limit(Some(2), 1, ops.clone()),
+        vec![insert(2, "NICK"), insert(3, "ED")]
+    );
+    assert_eq!(
+        run_limit(None, 3, ops.clone()),
+        vec![insert(4, "JENNIFER"), insert(5, "JOHNNY")]
+    );
+    assert_eq!(run_limit(Some(0), 0, ops), vec![]);
+}
+
+#[test]
+fn test_limit_retractions() {
+    let delete_nick = Operation::Delete {
+        old: actor(2, "NICK"),
+    };
+    let update_penelope = Operation::Update {
+        old: actor(1, "PENELOPE"),
+        new: actor(1, "GUINESS"),
+    };
+    let ops = vec![
+        insert(1, "PENELOPE"),
+        insert(2, "NICK"),
+        // Beyond the limit
+        insert(3, "ED"),
+        Operation::Update {
+            old: actor(3, "ED"),
+            new: actor(3, "CHASE"),
+        },
+        Operation::Delete {
+            old: actor(3, "CHASE"),
+        },
+        // Forwarded records
+        update_penelope.clone(),
+        delete_nick.clone(),
+        // Takes the slot freed by NICK
+        insert(4, "JENNIFER"),
+        insert(5, "JOHNNY"),
+    ];
+
+    assert_eq!(
+        run_limit(Some(2), 0, ops),
+        vec![
+            insert(1, "PENELOPE"),
+            insert(2, "NICK"),
+            update_penelope,
+            delete_nick,
+            insert(4, "JENNIFER"),
+        ]
+    );
+}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use dozer_core::dag::{
+    dag::DEFAULT_PORT_HANDLE,
+    errors::ExecutionError,
+    node::{OutputPortDef, OutputPortType, PortHandle, Processor, ProcessorFactory},
+};
+use dozer_types::types::Schema;
+use sqlparser::ast::OrderByExpr;
+
+use crate::pipeline::errors::PipelineError;
+use crate::pipeline::expression::builder::{BuilderExpressionType, ExpressionBuilder};
+
+use super::processor::{OrderByProcessor, SortColumn};
+
+/// Builds an [`OrderByProcessor`], which forwards the net change of each epoch to the sorted view
+/// rather than snapshots of it.
+#[derive(Debug)]
+pub struct OrderByProcessorFactory {
+    order_by: Vec<OrderByExpr>,
+}
+
+impl OrderByProcessorFactory {
+    /// Creates a new [`OrderByProcessorFactory`] from the `ORDER BY` clause of a query.
+    pub fn new(order_by: Vec<OrderByExpr>) -> Self {
+        Self { order_by }
+    }
+
+    fn build_sort_columns(&self, schema: &Schema) -> Result<Vec<SortColumn>, PipelineError> {
+        let builder = ExpressionBuilder {};
+        self.order_by
+            .iter()
+            .map(|order_by| {
+                let expression = builder.build(
+                    &BuilderExpressionType::FullExpression,
+                    &order_by.expr,
+                    schema,
+                )?;
+                let asc = order_by.asc.unwrap_or(true);
+                // Same defaults as Postgres: NULLs sort as if larger than any value
+                let nulls_first = order_by.nulls_first.unwrap_or(!asc);
+                Ok(SortColumn::new(expression, asc, nulls_first))
+            })
+            .collect()
+    }
+}
+
+impl ProcessorFactory for OrderByProcessorFactory {
+    fn get_input_ports(&self) -> Vec<PortHandle> {
+        vec![DEFAULT_PORT_HANDLE]
+    }
+
+    fn get_output_ports(&self) -> Vec<OutputPortDef> {
+        vec![OutputPortDef::new(
+            DEFAULT_PORT_HANDLE,
+            OutputPortType::Stateless,
+        )]
+    }
+
+    fn get_output_schema(
+        &self,
+        _output_port: &PortHandle,
+        input_schemas: &HashMap<PortHandle, Schema>,
+    ) -> Result<Schema, ExecutionError> {
+        let schema = input_schemas
+            .get(&DEFAULT_PORT_HANDLE)
+            .ok_or(ExecutionError::InvalidPortHandle(DEFAULT_PORT_HANDLE))?;
+        // Fail early if a sort column is missing from the input
+        self.build_sort_columns(schema)
+            .map_err(|e| ExecutionError::InternalStringError(e.to_string()))?;
+        Ok(schema.clone())
+    }
+
+    fn build(
+        &self,
+        input_schemas: HashMap<PortHandle, Schema>,
+        _output_schemas: HashMap<PortHandle, Schema>,
+    ) -> Result<Box<dyn Processor>, ExecutionError> {
+        let schema = input_schemas
+            .get(&DEFAULT_PORT_HANDLE)
+            .ok_or(ExecutionError::InvalidPortHandle(DEFAULT_PORT_HANDLE))?;
+
+        match self.build_sort_columns(schema) {
+            Ok(sort_columns) => Ok(Box::new(OrderByProcessor::new(
+                schema.clone(),
+                sort_columns,
+            ))),
+            Err(e) => Err(ExecutionError::InternalStringError(e.to_string())),
+        }
+    }
+
+    fn prepare(
+        &self,
+        _input_schemas: HashMap<PortHandle, Schema>,
+        _output_schemas: HashMap<PortHandle, Schema>,
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,378 @@
+use crate::pipeline::errors::PipelineError;
+use crate::pipeline::expression::execution::{Expression, ExpressionExecutor};
+use crate::pipeline::expression::scalar::datetime::current_time;
+use crate::pipeline::state::{decode_count, get_values_key};
+use dozer_core::dag::channels::ProcessorChannelForwarder;
+use dozer_core::dag::dag::DEFAULT_PORT_HANDLE;
+use dozer_core::dag::epoch::Epoch;
+use dozer_core::dag::errors::ExecutionError;
+use dozer_core::dag::errors::ExecutionError::InternalError;
+use dozer_core::dag::node::{PortHandle, Processor};
+use dozer_core::dag::record_store::RecordReader;
+use dozer_core::storage::common::{Database, Seek};
+use dozer_core::storage::errors::StorageError;
+use dozer_core::storage::errors::StorageError::{InvalidDatabase, SerializationError};
+use dozer_core::storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
+use dozer_types::bincode;
+use dozer_types::chrono::Datelike;
+use dozer_types::log::info;
+use dozer_types::rust_decimal::Decimal;
+use dozer_types::types::{encode_json, Field, Operation, Record, Schema};
+use std::collections::{BTreeMap, HashMap};
+
+const COUNTER_KEY: u8 = 1_u8;
+
+/// Size of the sequence number appended to the sort key, to tell apart records sorting equal.
+const SEQ_LEN: usize = 8;
+
+const NULL_FIRST_TAG: u8 = 0x00;
+const VALUE_TAG: u8 = 0x01;
+const NULL_LAST_TAG: u8 = 0x02;
+
+/// One expression of the `ORDER BY` clause.
+#[derive(Debug, Clone)]
+pub struct SortColumn {
+    expression: Box<Expression>,
+    asc: bool,
+    nulls_first: bool,
+}
+
+impl SortColumn {
+    pub fn new(expression: Box<Expression>, asc: bool, nulls_first: bool) -> Self {
+        Self {
+            expression,
+            asc,
+            nulls_first,
+        }
+    }
+}
+
+/// Maintains the records it receives sorted in LMDB, keyed by [`get_sort_key`].
+///
+/// The processor doesn't forward snapshots of the sorted view. Operations are held back until the
+/// end of the epoch, and only the net change of the epoch is then forwarded: first the deletes of
+/// the records which left the view, then the inserts of the records which entered it, each group
+/// in `ORDER BY` order. An update is a delete of the old record and an insert of the new one, so
+/// deletes come first for a sink keyed by primary key to not drop the new record. Changes which
+/// cancel out within the epoch are not forwarded. The whole view, in order, is read with
+/// [`get_ordered_records`](Self::get_ordered_records).
+#[derive(Debug)]
+pub struct OrderByProcessor {
+    sort_columns: Vec<SortColumn>,
+    input_schema: Schema,
+    db: Option<Database>,
+    meta_db: Option<Database>,
+    /// Net number of copies of each record added to the view during the epoch, keyed by sort key
+    /// and then by encoded values.
+    changes: BTreeMap<(Vec<u8>, Vec<u8>), (Record, i64)>,
+}
+
+impl OrderByProcessor {
+    pub fn new(input_schema: Schema, sort_columns: Vec<SortColumn>) -> Self {
+        Self {
+            sort_columns,
+            input_schema,
+            db: None,
+            meta_db: None,
+            changes: BTreeMap::new(),
+        }
+    }
+
+    fn set_now(&mut self) {
+        let now = current_time();
+        for column in &mut self.sort_columns {
+            column.expression.set_now(&now);
+        }
+    }
+
+    fn init_store(&mut self, env: &mut LmdbEnvironmentManager) -> Result<(), PipelineError> {
+        self.db = Some(env.open_database("order_by", false)?);
+        self.meta_db = Some(env.open_database("order_by_meta", false)?);
+        Ok(())
+    }
+
+    fn get_databases(&self) -> Result<(Database, Database), ExecutionError> {
+        match (self.db, self.meta_db) {
+            (Some(db), Some(meta_db)) => Ok((db, meta_db)),
+            _ => Err(ExecutionError::InternalDatabaseError(InvalidDatabase)),
+        }
+    }
+
+    fn get_sort_key(&self, record: &Record) -> Result<Vec<u8>, ExecutionError> {
+        get_sort_key(record, &self.sort_columns, &self.input_schema).map_err(ExecutionError::from)
+    }
+
+    /// Stores `record` and returns its sort key.
+    fn insert(&self, record: &Record, tx: &SharedTransaction) -> Result<Vec<u8>, ExecutionError> {
+        let (db, meta_db) = self.get_databases()?;
+        let sort_key = self.get_sort_key(record)?;
+        let mut key = sort_key.clone();
+        let value = bincode::serialize(record).map_err(|e| SerializationError {
+            typ: "Record".to_string(),
+            reason: Box::new(e),
+        })?;
+
+        let mut tx = tx.write();
+        let seq = decode_count(tx.get(meta_db, &COUNTER_KEY.to_be_bytes())?)?;
+        tx.put(
+            meta_db,
+            &COUNTER_KEY.to_be_bytes(),
+            &(seq + 1).to_be_bytes(),
+        )?;
+
+        key.extend(seq.to_be_bytes());
+        tx.put(db, &key, &value)?;
+        Ok(sort_key)
+    }
+
+    /// Deletes one of the stored records equal to `record` and returns its sort key.
+    fn delete(&self, record: &Record, tx: &SharedTransaction) -> Result<Vec<u8>, ExecutionError> {
+        let (db, _) = self.get_databases()?;
+        let sort_key = self.get_sort_key(record)?;
+
+        let mut tx = tx.write();
+        let mut found = None;
+        {
+            let cursor = tx.open_ro_cursor(db)?;
+            let mut exists = cursor.seek_gte(&sort_key)?;
+            while exists {
+                let (key, value) = cursor.read()?.ok_or(StorageError::InvalidRecord)?;
+                if key.len() != sort_key.len() + SEQ_LEN || !key.starts_with(&sort_key) {
+                    break;
+                }
+                let stored: Record =
+                    bincode::deserialize(value).map_err(|e| SerializationError {
+                        typ: "Record".to_string(),
+                        reason: Box::new(e),
+                    })?;
+                if stored.values == record.values {
+                    found = Some(key.to_vec());
+                    break;
+                }
+                exists = cursor.next()?;
+            }
+        }
+
+        match found {
+            Some(key) => {
+                tx.del(db, &key, None)?;
+                Ok(sort_key)
+            }
+            None => Err(ExecutionError::InternalDatabaseError(
+                StorageError::InvalidKey(format!("{:x?}", sort_key)),
+            )),
+        }
+    }
+
+    fn get_change_key(
+        sort_key: Vec<u8>,
+        record: &Record,
+    ) -> Result<(Vec<u8>, Vec<u8>), ExecutionError> {
+        Ok((sort_key, get_values_key(record)?))
+    }
+
+    fn record_change(&mut self, key: (Vec<u8>, Vec<u8>), record: &Record, count: i64) {
+        let (_, net_count) = self
+            .changes
+            .entry(key)
+            .or_insert_with(|| (record.clone(), 0));
+        *net_count += count;
+    }
+
+    /// Returns the records currently in the view, in `ORDER BY` order.
+    pub fn get_ordered_records(
+        &self,
+        tx: &SharedTransaction,
+    ) -> Result<Vec<Record>, ExecutionError> {
+        let (db, _) = self.get_databases()?;
+        let tx = tx.read();
+        let cursor = tx.open_ro_cursor(db)?;
+
+        let mut records = vec![];
+        let mut exists = cursor.first()?;
+        while exists {
+            let (_, value) = cursor.read()?.ok_or(StorageError::InvalidRecord)?;
+            records.push(bincode::deserialize(value).map_err(|e| SerializationError {
+                typ: "Record".to_string(),
+                reason: Box::new(e),
+            })?);
+            exists = cursor.next()?;
+        }
+        Ok(records)
+    }
+}
+
+impl Processor for OrderByProcessor {
+    fn init(&mut self, env: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+        info!("{:?}", "Initialising Order By Processor");
+        self.set_now();
+        self.init_store(env).map_err(|e| InternalError(Box::new(e)))
+    }
+
+    /// Forwards the net change of the epoch, as described on [`OrderByProcessor`].
+    fn flush(
+        &mut self,
+        fw: &mut dyn ProcessorChannelForwarder,
+        _tx: &SharedTransaction,
+    ) -> Result<(), ExecutionError> {
+        self.set_now();
+        let changes = std::mem::take(&mut self.changes);
+        for (record, count) in changes.values() {
+            for _ in *count..0 {
+                fw.send(
+                    Operation::Delete {
+                        old: record.clone(),
+                    },
+                    DEFAULT_PORT_HANDLE,
+                )?;
+            }
+        }
+        for (record, count) in changes.into_values() {
+            for _ in 0..count {
+                fw.send(
+                    Operation::Insert {
+                        new: record.clone(),
+                    },
+                    DEFAULT_PORT_HANDLE,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn commit(&self, _epoch: &Epoch, _tx: &SharedTransaction) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn process(
+        &mut self,
+        _from_port: PortHandle,
+        op: Operation,
+        _fw: &mut dyn ProcessorChannelForwarder,
+        tx: &SharedTransaction,
+        _reader: &HashMap<PortHandle, RecordReader>,
+    ) -> Result<(), ExecutionError> {
+        // Changes are only recorded once the operation succeeded, so that a failed operation
+        // leaves nothing to forward.
+        match &op {
+            Operation::Delete { old } => {
+                let key = Self::get_change_key(self.delete(old, tx)?, old)?;
+                self.record_change(key, old, -1);
+            }
+            Operation::Insert { new } => {
+                let key = Self::get_change_key(self.insert(new, tx)?, new)?;
+                self.record_change(key, new, 1);
+            }
+            Operation::Update { old, new } => {
+                let old_key = Self::get_change_key(self.delete(old, tx)?, old)?;
+                let new_key = Self::get_change_key(self.insert(new, tx)?, new)?;
+                self.record_change(old_key, old, -1);
+                self.record_change(new_key, new, 1);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns a key whose byte order is the `ORDER BY` order of `record`.
+/// Like `get_composite_key`, the fields are concatenated, but each one is encoded so that comparing
+/// the keys byte by byte compares the values.
+pub fn get_sort_key(
+    record: &Record,
+    sort_columns: &[SortColumn],
+    schema: &Schema,
+) -> Result<Vec<u8>, PipelineError> {
+    let mut sort_key = Vec::with_capacity(64);
+
+    for column in sort_columns {
+        let value = column.expression.evaluate(record, schema)?;
+        if value == Field::Null {
+            sort_key.push(if column.nulls_first {
+                NULL_FIRST_TAG
+            } else {
+                NULL_LAST_TAG
+            });
+            continue;
+        }
+
+        sort_key.push(VALUE_TAG);
+        let start = sort_key.len();
+        encode_sortable(&value, &mut sort_key);
+        if !column.asc {
+            for byte in &mut sort_key[start..] {
+                *byte = !*byte;
+            }
+        }
+    }
+
+    Ok(sort_key)
+}
+
+fn encode_sortable(value: &Field, buf: &mut Vec<u8>) {
+    match value {
+        Field::UInt(u) => buf.extend(u.to_be_bytes()),
+        Field::Int(i) => buf.extend(flip_sign_bit(*i).to_be_bytes()),
+        Field::Float(f) => buf.extend(sortable_f64(f.0).to_be_bytes()),
+        Field::Decimal(d) => encode_sortable_decimal(d, buf),
+        Field::Boolean(b) => buf.push(u8::from(*b)),
+        Field::String(s) | Field::Text(s) => encode_sortable_bytes(s.as_bytes(), buf),
+        Field::Binary(b) | Field::Bson(b) => encode_sortable_bytes(b, buf),
+        // JSON values are ordered by their canonical encoding, like in `Field`'s `Ord`
+        Field::Json(j) => encode_sortable_bytes(encode_json(j).as_bytes(), buf),
+        Field::Timestamp(t) => {
+            buf.extend(flip_sign_bit(t.timestamp()).to_be_bytes());
+            buf.extend(t.timestamp_subsec_nanos().to_be_bytes());
+        }
+        Field::Date(d) => buf.extend(flip_sign_bit(i64::from(d.num_days_from_ce())).to_be_bytes()),
+        Field::Null => {}
+    }
+}
+
+/// Encodes a sign byte, then the decimal exponent and the significant digits of `d`, all inverted
+/// if `d` is negative. Digits are terminated by `0x00`, so that `0.12` sorts before `0.123`.
+fn encode_sortable_decimal(d: &Decimal, buf: &mut Vec<u8>) {
+    if d.is_zero() {
+        buf.push(1);
+        return;
+    }
+
+    let d = d.normalize();
+    let digits = d.mantissa().unsigned_abs().to_string();
+    // `d` is `0.{digits} * 10^exponent`, at most 29 digits with a scale of at most 28.
+    let exponent = digits.len() as i32 - d.scale() as i32;
+    let start = buf.len() + 1;
+    buf.push(if d.is_sign_negative() { 0 } else { 2 });
+    buf.push((exponent + 128) as u8);
+    buf.extend(digits.trim_end_matches('0').bytes());
+    buf.push(0);
+    if d.is_sign_negative() {
+        for byte in &mut buf[start..] {
+            *byte = !*byte;
+        }
+    }
+}
+
+fn flip_sign_bit(i: i64) -> u64 {
+    (i as u64) ^ (1 << 63)
+}
+
+fn sortable_f64(f: f64) -> u64 {
+    let bits = f.to_bits();
+    if bits >> 63 == 1 {
+        !bits
+    } else {
+        bits | (1 << 63)
+    }
+}
+
+/// Escapes `0x00` as `0x00 0xFF` and terminates with `0x00 0x00`, so that a value sorts before
+/// any longer value it is a prefix of.
+fn encode_sortable_bytes(bytes: &[u8], buf: &mut Vec<u8>) {
+    for byte in bytes {
+        buf.push(*byte);
+        if *byte == 0 {
+            buf.push(0xFF);
+        }
+    }
+    buf.extend([0, 0]);
+}
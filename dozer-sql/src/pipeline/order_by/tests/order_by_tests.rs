@@ -0,0 +1,332 @@
+use crate::pipeline::expression::execution::Expression;
+use crate::pipeline::order_by::factory::OrderByProcessorFactory;
+use crate::pipeline::order_by::processor::{get_sort_key, OrderByProcessor, SortColumn};
+use dozer_core::dag::channels::ProcessorChannelForwarder;
+use dozer_core::dag::dag::DEFAULT_PORT_HANDLE;
+use dozer_core::dag::errors::ExecutionError;
+use dozer_core::dag::node::{PortHandle, Processor, ProcessorFactory};
+use dozer_core::storage::lmdb_storage::LmdbEnvironmentManager;
+use dozer_types::ordered_float::OrderedFloat;
+use dozer_types::rust_decimal::Decimal;
+use dozer_types::types::{Field, FieldDefinition, FieldType, Operation, Record, Schema};
+use sqlparser::ast::Statement;
+use sqlparser::dialect::AnsiDialect;
+use sqlparser::parser::Parser;
+use std::collections::HashMap;
+use std::str::FromStr;
+use tempdir::TempDir;
+
+struct TestChannelForwarder {
+    operations: Vec<Operation>,
+}
+
+impl ProcessorChannelForwarder for TestChannelForwarder {
+    fn send(&mut self, op: Operation, _port: PortHandle) -> Result<(), ExecutionError> {
+        self.operations.push(op);
+        Ok(())
+    }
+}
+
+fn get_schema() -> Schema {
+    Schema::empty()
+        .field(
+            FieldDefinition::new(String::from("actor_id"), FieldType::Int, false),
+            false,
+        )
+        .field(
+            FieldDefinition::new(String::from("last_name"), FieldType::String, true),
+            false,
+        )
+        .clone()
+}
+
+fn actor(id: i64, last_name: Option<&str>) -> Record {
+    Record::new(
+        None,
+        vec![
+            Field::Int(id),
+            last_name.map_or(Field::Null, |s| Field::String(s.to_string())),
+        ],
+        None,
+    )
+}
+
+fn column(index: usize, asc: bool, nulls_first: bool) -> SortColumn {
+    SortColumn::new(Box::new(Expression::Column { index }), asc, nulls_first)
+}
+
+/// Sorts `values` by their single field sort key and returns them in that order.
+fn sort_by_key(values: Vec<Field>, asc: bool, nulls_first: bool) -> Vec<Field> {
+    let schema = Schema::empty()
+        .field(
+            FieldDefinition::new(String::from("value"), FieldType::Int, true),
+            false,
+        )
+        .clone();
+    let sort_columns = vec![column(0, asc, nulls_first)];
+    let mut keyed: Vec<(Vec<u8>, Field)> = values
+        .into_iter()
+        .map(|value| {
+            let record = Record::new(None, vec![value.clone()], None);
+            (
+                get_sort_key(&record, &sort_columns, &schema).unwrap(),
+                value,
+            )
+        })
+        .collect();
+    keyed.sort();
+    keyed.into_iter().map(|(_, value)| value).collect()
+}
+
+#[test]
+fn test_sort_key_order() {
+    assert_eq!(
+        sort_by_key(
+            vec![Field::Int(3), Field::Null, Field::Int(-5), Field::Int(0)],
+            true,
+            false
+        ),
+        vec![Field::Int(-5), Field::Int(0), Field::Int(3), Field::Null]
+    );
+    assert_eq!(
+        sort_by_key(
+            vec![Field::Int(3), Field::Null, Field::Int(-5), Field::Int(0)],
+            false,
+            true
+        ),
+        vec![Field::Null, Field::Int(3), Field::Int(0), Field::Int(-5)]
+    );
+    assert_eq!(
+        sort_by_key(
+            vec![
+                Field::Float(OrderedFloat(1.5)),
+                Field::Float(OrderedFloat(-0.5)),
+                Field::Float(OrderedFloat(-10.0)),
+            ],
+            true,
+            false
+        ),
+        vec![
+            Field::Float(OrderedFloat(-10.0)),
+            Field::Float(OrderedFloat(-0.5)),
+            Field::Float(OrderedFloat(1.5)),
+        ]
+    );
+    // A string sorts before the longer strings it is a prefix of, in both directions
+    let strings = || {
+        vec![
+            Field::String("ab".to_string()),
+            Field::String("b".to_string()),
+            Field::String("a".to_string()),
+            Field::String("a\0".to_string()),
+        ]
+    };
+    assert_eq!(
+        sort_by_key(strings(), true, false),
+        vec![
+            Field::String("a".to_string()),
+            Field::String("a\0".to_string()),
+            Field::String("ab".to_string()),
+            Field::String("b".to_string()),
+        ]
+    );
+    assert_eq!(
+        sort_by_key(strings(), false, true),
+        vec![
+            Field::String("b".to_string()),
+            Field::String("ab".to_string()),
+            Field::String("a\0".to_string()),
+            Field::String("a".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_sort_key_decimal_order() {
+    // Beyond the precision of an f64
+    let values = [
+        "-100",
+        "-12.5",
+        "-12.34",
+        "-0.0001",
+        "0",
+        "0.000",
+        "0.0000000000000000000000000001",
+        "0.12",
+        "0.123",
+        "1",
+        "1.00",
+        "9.999999999999999999",
+        "10",
+        "10000000000000000000000000000",
+        "79228162514264337593543950335",
+    ]
+    .map(|value| Field::Decimal(Decimal::from_str(value).unwrap()));
+
+    let mut reversed = values.to_vec();
+    reversed.reverse();
+    assert_eq!(sort_by_key(reversed, true, false), values);
+
+    let mut descending = sort_by_key(values.to_vec(), false, false);
+    descending.reverse();
+    assert_eq!(descending, values);
+}
+
+#[test]
+fn test_order_by_processor() {
+    // ORDER BY last_name, actor_id DESC
+    let mut processor = OrderByProcessor::new(
+        get_schema(),
+        vec![column(1, true, false), column(0, false, true)],
+    );
+
+    let tmp_dir = TempDir::new("test").unwrap();
+    let mut storage = LmdbEnvironmentManager::create(tmp_dir.path(), "order_by_test").unwrap();
+    processor.init(&mut storage).unwrap();
+
+    let tx = storage.create_txn().unwrap();
+    let mut fw = TestChannelForwarder { operations: vec![] };
+    let ops = vec![
+        Operation::Insert {
+            new: actor(1, Some("GUINESS")),
+        },
+        Operation::Insert {
+            new: actor(2, None),
+        },
+        Operation::Insert {
+            new: actor(3, Some("CHASE")),
+        },
+        Operation::Insert {
+            new: actor(4, Some("GUINESS")),
+        },
+        // Same sort key as actor 4
+        Operation::Insert {
+            new: actor(4, Some("GUINESS")),
+        },
+        Operation::Insert {
+            new: actor(5, Some("DAVIS")),
+        },
+        Operation::Delete {
+            old: actor(4, Some("GUINESS")),
+        },
+        Operation::Update {
+            old: actor(5, Some("DAVIS")),
+            new: actor(5, Some("AKROYD")),
+        },
+    ];
+    for op in ops {
+        processor
+            .process(DEFAULT_PORT_HANDLE, op, &mut fw, &tx, &HashMap::new())
+            .unwrap();
+    }
+
+    // Nothing is forwarded before the end of the epoch
+    assert!(fw.operations.is_empty());
+    assert_eq!(
+        processor.get_ordered_records(&tx).unwrap(),
+        vec![
+            actor(5, Some("AKROYD")),
+            actor(3, Some("CHASE")),
+            actor(4, Some("GUINESS")),
+            actor(1, Some("GUINESS")),
+            actor(2, None),
+        ]
+    );
+
+    // The net change of the epoch is forwarded in order
+    processor.flush(&mut fw, &tx).unwrap();
+    assert_eq!(
+        fw.operations,
+        vec![
+            Operation::Insert {
+                new: actor(5, Some("AKROYD")),
+            },
+            Operation::Insert {
+                new: actor(3, Some("CHASE")),
+            },
+            Operation::Insert {
+                new: actor(4, Some("GUINESS")),
+            },
+            Operation::Insert {
+                new: actor(1, Some("GUINESS")),
+            },
+            Operation::Insert {
+                new: actor(2, None),
+            },
+        ]
+    );
+
+    // Deletes of the records which left the view come first, and changes which cancel out are
+    // not forwarded
+    fw.operations.clear();
+    for op in [
+        Operation::Update {
+            old: actor(3, Some("CHASE")),
+            new: actor(3, Some("BALL")),
+        },
+        Operation::Insert {
+            new: actor(6, Some("ALLEN")),
+        },
+        Operation::Delete {
+            old: actor(1, Some("GUINESS")),
+        },
+        Operation::Delete {
+            old: actor(6, Some("ALLEN")),
+        },
+    ] {
+        processor
+            .process(DEFAULT_PORT_HANDLE, op, &mut fw, &tx, &HashMap::new())
+            .unwrap();
+    }
+    processor.flush(&mut fw, &tx).unwrap();
+    assert_eq!(
+        fw.operations,
+        vec![
+            Operation::Delete {
+                old: actor(3, Some("CHASE")),
+            },
+            Operation::Delete {
+                old: actor(1, Some("GUINESS")),
+            },
+            Operation::Insert {
+                new: actor(3, Some("BALL")),
+            },
+        ]
+    );
+    processor.flush(&mut fw, &tx).unwrap();
+    assert_eq!(fw.operations.len(), 3);
+
+    // Deleting a record that was never inserted is an error
+    assert!(processor
+        .process(
+            DEFAULT_PORT_HANDLE,
+            Operation::Delete {
+                old: actor(6, Some("CHASE")),
+            },
+            &mut fw,
+            &tx,
+            &HashMap::new(),
+        )
+        .is_err());
+}
+
+#[test]
+fn test_order_by_factory() {
+    let get_output_schema = |sql: &str| {
+        let query = match Parser::parse_sql(&AnsiDialect {}, sql).unwrap().remove(0) {
+            Statement::Query(query) => query,
+            _ => panic!("{} is not a query", sql),
+        };
+        OrderByProcessorFactory::new(query.order_by).get_output_schema(
+            &DEFAULT_PORT_HANDLE,
+            &[(DEFAULT_PORT_HANDLE, get_schema())].into_iter().collect(),
+        )
+    };
+
+    assert_eq!(
+        get_output_schema("SELECT * FROM actor ORDER BY last_name DESC NULLS LAST, actor_id")
+            .unwrap(),
+        get_schema()
+    );
+    assert!(get_output_schema("SELECT * FROM actor ORDER BY first_name").is_err());
+}
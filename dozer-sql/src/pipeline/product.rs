@@ -1,4 +1,4 @@
 pub mod factory;
-mod join;
+pub(crate) mod join;
 mod processor;
 mod tests;
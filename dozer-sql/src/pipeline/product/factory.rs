@@ -12,7 +12,10 @@ use sqlparser::ast::{
 
 use crate::pipeline::{
     errors::PipelineError,
-    expression::builder::{fullname_from_ident, get_field_index, normalize_ident},
+    expression::builder::{
+        check_unique_field_names, compare_name, fullname_from_ident, get_field_index,
+        normalize_ident,
+    },
 };
 
 use super::{
@@ -65,6 +68,9 @@ impl ProcessorFactory for ProductProcessorFactory {
             }
         }
 
+        // Tables joined with themselves need distinct aliases
+        check_unique_field_names(&output_schema)
+            .map_err(|e| ExecutionError::InternalStringError(e.to_string()))?;
         Ok(output_schema)
     }
 
@@ -86,6 +92,11 @@ impl ProcessorFactory for ProductProcessorFactory {
     ) -> Result<(), ExecutionError> {
         Ok(())
     }
+
+    fn requires_record_lookup(&self, _port: &PortHandle) -> bool {
+        // Joined records are looked up on the other side of the join
+        !self.from.joins.is_empty()
+    }
 }
 
 /// Returns a vector of input port handles and relative table name
@@ -106,28 +117,49 @@ pub fn get_input_tables(from: &TableWithJoins) -> Result<Vec<String>, ExecutionE
     Ok(input_tables)
 }
 
-/// Returns the table name
+/// Returns a vector with the names of the input tables, ignoring their aliases
+///
+/// # Errors
+///
+/// This function will return an error if it's not possible to get an input name.
+pub fn get_input_table_names(from: &TableWithJoins) -> Result<Vec<String>, ExecutionError> {
+    let mut input_tables = vec![get_table_name(&from.relation)?];
+
+    for join in from.joins.iter() {
+        input_tables.push(get_table_name(&join.relation)?);
+    }
+
+    Ok(input_tables)
+}
+
+/// Returns the table name, or its alias if defined
 ///
 /// # Errors
 ///
 /// This function will return an error if the input argument is not a Table.
 pub fn get_input_name(relation: &TableFactor) -> Result<String, ExecutionError> {
     match relation {
-        TableFactor::Table { name, alias, .. } => {
-            if let Some(alias_ident) = alias {
-                let alias_name = fullname_from_ident(&[alias_ident.name.clone()]);
-                Ok(alias_name)
-            } else {
-                let input_name = name
-                    .0
-                    .iter()
-                    .map(normalize_ident)
-                    .collect::<Vec<String>>()
-                    .join(".");
-
-                Ok(input_name)
-            }
-        }
+        TableFactor::Table {
+            alias: Some(alias_ident),
+            ..
+        } => Ok(fullname_from_ident(&[alias_ident.name.clone()])),
+        _ => get_table_name(relation),
+    }
+}
+
+/// Returns the table name
+///
+/// # Errors
+///
+/// This function will return an error if the input argument is not a Table.
+pub fn get_table_name(relation: &TableFactor) -> Result<String, ExecutionError> {
+    match relation {
+        TableFactor::Table { name, .. } => Ok(name
+            .0
+            .iter()
+            .map(normalize_ident)
+            .collect::<Vec<String>>()
+            .join(".")),
         _ => Err(ExecutionError::InternalStringError(
             "Invalid Input table".to_string(),
         )),
@@ -311,9 +343,26 @@ fn parse_join_constraint(
     }
 }
 
-fn from_table(ident: &[Ident], left_join_table: &JoinTable) -> bool {
-    let full_ident = fullname_from_ident(ident);
-    full_ident.starts_with(&left_join_table.name)
+/// Returns the index of the column in `join_table`, or `None` if the identifier is qualified
+/// with another table or the column doesn't belong to it.
+fn get_join_table_column(
+    ident: &[Ident],
+    join_table: &JoinTable,
+) -> Result<Option<usize>, PipelineError> {
+    if let Some((column, qualifier)) = ident.split_last() {
+        if !qualifier.is_empty()
+            && !compare_name(join_table.name.clone(), fullname_from_ident(qualifier))
+        {
+            return Ok(None);
+        }
+        match get_field_index(&[column.clone()], &join_table.schema) {
+            Ok(index) => Ok(Some(index)),
+            Err(PipelineError::ColumnNotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    } else {
+        Ok(None)
+    }
 }
 
 fn parse_compound_identifier(
@@ -321,17 +370,13 @@ fn parse_compound_identifier(
     left_join_table: &JoinTable,
     right_join_table: &JoinTable,
 ) -> Result<(Option<usize>, Option<usize>), PipelineError> {
-    if from_table(ident, left_join_table) {
-        Ok((Some(get_field_index(ident, &left_join_table.schema)?), None))
-    } else if from_table(ident, right_join_table) {
-        Ok((
-            None,
-            Some(get_field_index(ident, &right_join_table.schema)?),
-        ))
-    } else {
-        Err(PipelineError::InvalidExpression(
-            "Invalid Field in the Join Constraint".to_string(),
-        ))
+    match (
+        get_join_table_column(ident, left_join_table)?,
+        get_join_table_column(ident, right_join_table)?,
+    ) {
+        (Some(_), Some(_)) => Err(PipelineError::AmbiguousColumn(fullname_from_ident(ident))),
+        (None, None) => Err(PipelineError::ColumnNotFound(fullname_from_ident(ident))),
+        keys => Ok(keys),
     }
 }
 
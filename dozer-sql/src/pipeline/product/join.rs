@@ -8,7 +8,7 @@ use dozer_core::storage::lmdb_storage::SharedTransaction;
 use dozer_core::{dag::errors::ExecutionError, storage::prefix_transaction::PrefixTransaction};
 use dozer_types::bincode;
 use dozer_types::errors::types::TypeError;
-use dozer_types::types::{Record, Schema};
+use dozer_types::types::{KeyMode, Record, Schema};
 use sqlparser::ast::TableFactor;
 
 use crate::pipeline::product::join::StorageError::SerializationError;
@@ -148,6 +148,15 @@ impl JoinOperator {
         get_composite_key(record, self.right_join_key_indexes.as_slice())
     }
 
+    /// Returns the left join key of a joined record, where the fields of the left table start at `offset`
+    fn get_left_record_join_key_at(
+        &self,
+        record: &Record,
+        offset: usize,
+    ) -> Result<Vec<u8>, TypeError> {
+        get_composite_key(record, &rebase_indexes(&self.left_join_key_indexes, offset))
+    }
+
     fn get_right_join_keys(
         &self,
         join_key: &[u8],
@@ -238,6 +247,9 @@ impl JoinOperator {
 }
 
 impl JoinExecutor for JoinOperator {
+    /// Joins `records` with the matching records of the table on the right side of the join.
+    /// If that table is itself joined with a further table on its right, the joined records
+    /// are passed on to the next join, until the end of the chain is reached.
     fn execute_right(
         &self,
         mut records: Vec<Record>,
@@ -245,17 +257,21 @@ impl JoinExecutor for JoinOperator {
         db: &Database,
         transaction: &SharedTransaction,
         readers: &HashMap<PortHandle, RecordReader>,
-        _join_tables: &HashMap<PortHandle, JoinTable>,
+        join_tables: &HashMap<PortHandle, JoinTable>,
     ) -> Result<Vec<Record>, ExecutionError> {
         let mut result_records = vec![];
         let reader = readers
             .get(&self.right_table)
             .ok_or(ExecutionError::InvalidPortHandle(self.right_table))?;
 
-        for record in records.iter_mut() {
-            // retrieve the lookup keys for the table on the right side of the join
-            let right_keys = self.get_right_join_keys(join_key, db, transaction)?;
+        let right_table = join_tables
+            .get(&self.right_table)
+            .ok_or(ExecutionError::InvalidPortHandle(self.right_table))?;
 
+        // retrieve the lookup keys for the table on the right side of the join
+        let right_keys = self.get_right_join_keys(join_key, db, transaction)?;
+
+        for record in records.iter_mut() {
             // retrieve records for the table on the right side of the join
             for right_lookup_key in right_keys.iter() {
                 if let Some(record_bytes) = reader.get(right_lookup_key)? {
@@ -264,32 +280,36 @@ impl JoinExecutor for JoinOperator {
                             typ: "Record".to_string(),
                             reason: Box::new(e),
                         })?;
+
+                    // the right record starts after the fields of the records joined so far
+                    let offset = record.values.len();
                     let join_record = join_records(&mut record.clone(), &mut right_record.clone());
-                    result_records.push(join_record);
+
+                    if let Some(next_join) = &right_table.right {
+                        let next_join_key =
+                            next_join.get_left_record_join_key_at(&join_record, offset)?;
+                        let mut next_join_records = next_join.execute_right(
+                            vec![join_record],
+                            &next_join_key,
+                            db,
+                            transaction,
+                            readers,
+                            join_tables,
+                        )?;
+                        result_records.append(&mut next_join_records);
+                    } else {
+                        result_records.push(join_record);
+                    }
                 }
             }
-
-            // let join_schema = Schema::empty();
-
-            // let right_table = join_tables.get(&(self.right_table as PortHandle)).ok_or(
-            //     ExecutionError::InternalDatabaseError(StorageError::InvalidRecord),
-            // )?;
-
-            // if let Some(next_join) = &right_table.right {
-            //     let next_join_records = next_join.execute_right(
-            //         result_records,
-            //         &join_schema,
-            //         db,
-            //         transaction,
-            //         readers,
-            //         join_tables,
-            //     );
-            // }
         }
 
         Ok(result_records)
     }
 
+    /// Joins `records` with the matching records of the table on the left side of the join.
+    /// If that table is itself joined with a further table on its left, the joined records
+    /// are passed on to the next join, until the start of the chain is reached.
     fn execute_left(
         &self,
         mut records: Vec<Record>,
@@ -297,18 +317,22 @@ impl JoinExecutor for JoinOperator {
         db: &Database,
         transaction: &SharedTransaction,
         readers: &HashMap<PortHandle, RecordReader>,
-        _join_tables: &HashMap<PortHandle, JoinTable>,
+        join_tables: &HashMap<PortHandle, JoinTable>,
     ) -> Result<Vec<Record>, ExecutionError> {
         let mut result_records = vec![];
         let reader = readers
             .get(&self.left_table)
             .ok_or(ExecutionError::InvalidPortHandle(self.left_table))?;
 
-        for record in records.iter_mut() {
-            // retrieve the lookup keys for the table on the right side of the join
-            let left_keys = self.get_left_join_keys(join_key, db, transaction)?;
+        let left_table = join_tables
+            .get(&self.left_table)
+            .ok_or(ExecutionError::InvalidPortHandle(self.left_table))?;
 
-            // retrieve records for the table on the right side of the join
+        // retrieve the lookup keys for the table on the left side of the join
+        let left_keys = self.get_left_join_keys(join_key, db, transaction)?;
+
+        for record in records.iter_mut() {
+            // retrieve records for the table on the left side of the join
             for left_lookup_key in left_keys.iter() {
                 if let Some(record_bytes) = reader.get(left_lookup_key)? {
                     let left_record: Record =
@@ -316,27 +340,26 @@ impl JoinExecutor for JoinOperator {
                             typ: "Record".to_string(),
                             reason: Box::new(e),
                         })?;
+
+                    // the left record is placed at the start of the joined record
                     let join_record = join_records(&mut left_record.clone(), &mut record.clone());
-                    result_records.push(join_record);
+
+                    if let Some(next_join) = &left_table.left {
+                        let next_join_key = next_join.get_right_record_join_key(&join_record)?;
+                        let mut next_join_records = next_join.execute_left(
+                            vec![join_record],
+                            &next_join_key,
+                            db,
+                            transaction,
+                            readers,
+                            join_tables,
+                        )?;
+                        result_records.append(&mut next_join_records);
+                    } else {
+                        result_records.push(join_record);
+                    }
                 }
             }
-
-            // let join_schema = Schema::empty();
-
-            // let right_table = join_tables.get(&(self.right_table as PortHandle)).ok_or(
-            //     ExecutionError::InternalDatabaseError(StorageError::InvalidRecord),
-            // )?;
-
-            // if let Some(next_join) = &right_table.right {
-            //     let next_join_records = next_join.execute_right(
-            //         result_records,
-            //         &join_schema,
-            //         db,
-            //         transaction,
-            //         readers,
-            //         join_tables,
-            //     );
-            // }
         }
 
         Ok(result_records)
@@ -411,23 +434,42 @@ impl JoinExecutor for JoinOperator {
     }
 }
 
+fn rebase_indexes(indexes: &[usize], offset: usize) -> Vec<usize> {
+    indexes.iter().map(|index| index + offset).collect()
+}
+
 fn join_records(left_record: &mut Record, right_record: &mut Record) -> Record {
     left_record.values.append(&mut right_record.values);
     Record::new(None, left_record.values.clone(), None)
 }
 
+/// Returns the key made of the fields at `key_indexes`.
+/// Each encoded field is prefixed with its length (u32 LE), so that different splits of the same bytes produce different keys.
 pub fn get_composite_key(record: &Record, key_indexes: &[usize]) -> Result<Vec<u8>, TypeError> {
     let mut join_key = Vec::with_capacity(64);
 
     for key_index in key_indexes.iter() {
         let key_value = record.get_value(*key_index)?;
         let key_bytes = key_value.encode();
+        join_key.extend((key_bytes.len() as u32).to_le_bytes());
         join_key.extend(key_bytes.iter());
     }
 
     Ok(join_key)
 }
 
-pub fn get_lookup_key(record: &Record, schema: &Schema) -> Result<Vec<u8>, TypeError> {
-    get_composite_key(record, schema.primary_index.as_slice())
+/// Returns the primary key of the record, encoded the same way as the keys of the record store.
+pub fn get_lookup_key(
+    record: &Record,
+    schema: &Schema,
+    key_mode: KeyMode,
+) -> Result<Vec<u8>, TypeError> {
+    let mut lookup_key = Vec::with_capacity(64);
+
+    for key_index in schema.get_primary_key_indexes(key_mode).iter() {
+        let key_value = record.get_value(*key_index)?;
+        lookup_key.extend(key_value.encode());
+    }
+
+    Ok(lookup_key)
 }
@@ -8,7 +8,7 @@ use dozer_core::dag::record_store::RecordReader;
 use dozer_core::storage::common::Database;
 use dozer_core::storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
 use dozer_types::internal_err;
-use dozer_types::types::{Operation, Record};
+use dozer_types::types::{KeyMode, Operation, Record};
 use std::collections::HashMap;
 
 use dozer_core::dag::errors::ExecutionError::InternalError;
@@ -52,20 +52,17 @@ impl ProductProcessor {
             let mut records = vec![record.clone()];
 
             let database = &self.db.ok_or(ExecutionError::InvalidDatabase)?;
+            let key_mode = get_key_mode(reader, from_port);
 
-            let mut input_left_join = &input_table.left;
-
-            if let Some(left_join) = input_left_join {
+            // the join executor follows the chain of joins on the left
+            if let Some(left_join) = &input_table.left {
                 // generate the key with the fields of the left table used in the join contstraint
                 let join_key: Vec<u8> = left_join.get_right_record_join_key(record)?;
                 // generate the key with theprimary key fields of the left table
-                let lookup_key: Vec<u8> = get_lookup_key(record, &input_table.schema)?;
+                let lookup_key: Vec<u8> = get_lookup_key(record, &input_table.schema, key_mode)?;
                 // Update the Join index
                 left_join.delete_right_index(&join_key, &lookup_key, database, transaction)?;
-            }
 
-            while let Some(left_join) = input_left_join {
-                let join_key: Vec<u8> = left_join.get_right_record_join_key(record)?;
                 records = left_join.execute_left(
                     records,
                     &join_key,
@@ -74,21 +71,14 @@ impl ProductProcessor {
                     reader,
                     &self.join_tables,
                 )?;
-
-                let next_table = self
-                    .join_tables
-                    .get(&left_join.left_table)
-                    .ok_or(ExecutionError::InvalidPortHandle(left_join.left_table))?;
-                input_left_join = &next_table.left;
             }
 
-            let mut input_right_join = &input_table.right;
-
-            while let Some(right_join) = input_right_join {
+            // the join executor follows the chain of joins on the right
+            if let Some(right_join) = &input_table.right {
                 // generate the key with the fields of the left table used in the join contstraint
                 let join_key: Vec<u8> = right_join.get_left_record_join_key(record)?;
                 // generate the key with theprimary key fields of the left table
-                let lookup_key: Vec<u8> = get_lookup_key(record, &input_table.schema)?;
+                let lookup_key: Vec<u8> = get_lookup_key(record, &input_table.schema, key_mode)?;
                 // Update the Join index
                 right_join.delete_left_index(&join_key, &lookup_key, database, transaction)?;
 
@@ -100,12 +90,6 @@ impl ProductProcessor {
                     reader,
                     &self.join_tables,
                 )?;
-
-                let next_table = self
-                    .join_tables
-                    .get(&right_join.right_table)
-                    .ok_or(ExecutionError::InvalidPortHandle(right_join.left_table))?;
-                input_right_join = &next_table.right;
             }
 
             return Ok(records);
@@ -126,20 +110,17 @@ impl ProductProcessor {
             let mut records = vec![record.clone()];
 
             let database = &self.db.ok_or(ExecutionError::InvalidDatabase)?;
+            let key_mode = get_key_mode(reader, from_port);
 
-            let mut input_left_join = &input_table.left;
-
-            if let Some(left_join) = input_left_join {
+            // the join executor follows the chain of joins on the left
+            if let Some(left_join) = &input_table.left {
                 // generate the key with the fields of the left table used in the join contstraint
                 let join_key: Vec<u8> = left_join.get_right_record_join_key(record)?;
                 // generate the key with theprimary key fields of the left table
-                let lookup_key: Vec<u8> = get_lookup_key(record, &input_table.schema)?;
+                let lookup_key: Vec<u8> = get_lookup_key(record, &input_table.schema, key_mode)?;
                 // Update the Join index
                 left_join.insert_right_index(&join_key, &lookup_key, database, transaction)?;
-            }
 
-            while let Some(left_join) = input_left_join {
-                let join_key: Vec<u8> = left_join.get_right_record_join_key(record)?;
                 records = left_join.execute_left(
                     records,
                     &join_key,
@@ -148,21 +129,14 @@ impl ProductProcessor {
                     reader,
                     &self.join_tables,
                 )?;
-
-                let next_table = self
-                    .join_tables
-                    .get(&left_join.left_table)
-                    .ok_or(ExecutionError::InvalidPortHandle(left_join.left_table))?;
-                input_left_join = &next_table.left;
             }
 
-            let mut input_right_join = &input_table.right;
-
-            while let Some(right_join) = input_right_join {
+            // the join executor follows the chain of joins on the right
+            if let Some(right_join) = &input_table.right {
                 // generate the key with the fields of the left table used in the join contstraint
                 let join_key: Vec<u8> = right_join.get_left_record_join_key(record)?;
                 // generate the key with theprimary key fields of the left table
-                let lookup_key: Vec<u8> = get_lookup_key(record, &input_table.schema)?;
+                let lookup_key: Vec<u8> = get_lookup_key(record, &input_table.schema, key_mode)?;
                 // Update the Join index
                 right_join.insert_left_index(&join_key, &lookup_key, database, transaction)?;
 
@@ -174,12 +148,6 @@ impl ProductProcessor {
                     reader,
                     &self.join_tables,
                 )?;
-
-                let next_table = self
-                    .join_tables
-                    .get(&right_join.right_table)
-                    .ok_or(ExecutionError::InvalidPortHandle(right_join.left_table))?;
-                input_right_join = &next_table.right;
             }
 
             return Ok(records);
@@ -207,6 +175,13 @@ impl ProductProcessor {
     // }
 }
 
+/// Returns how the record store of `port` keys its records, which the lookup keys must match.
+fn get_key_mode(reader: &HashMap<PortHandle, RecordReader>, port: PortHandle) -> KeyMode {
+    reader
+        .get(&port)
+        .map_or(KeyMode::Position, |reader| reader.key_mode())
+}
+
 impl Processor for ProductProcessor {
     fn init(&mut self, state: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
         internal_err!(self.init_store(state))
@@ -1,4 +1,6 @@
 #[cfg(test)]
 mod factory_tests;
 #[cfg(test)]
+mod join_tests;
+#[cfg(test)]
 mod pipeline_test;
@@ -1,11 +1,17 @@
 use std::collections::HashMap;
 
+use dozer_core::dag::dag::DEFAULT_PORT_HANDLE;
 use dozer_core::dag::node::{PortHandle, ProcessorFactory};
-use dozer_types::types::{FieldDefinition, FieldType, Schema};
+use dozer_types::ordered_float::OrderedFloat;
+use dozer_types::types::{Field, FieldDefinition, FieldType, Record, Schema};
+use sqlparser::ast::Ident;
 
 use crate::pipeline::{
     builder::get_select,
+    errors::PipelineError,
+    expression::builder::get_field_index,
     product::factory::{build_join_chain, ProductProcessorFactory},
+    projection::factory::ProjectionProcessorFactory,
 };
 
 #[test]
@@ -118,3 +124,226 @@ fn test_join_tables_three() {
 
     //assert_eq!(join_tables.)
 }
+
+#[test]
+fn test_join_tables_four() {
+    let statement = get_select(
+        "SELECT r.name, c.name, d.name, AVG(salary) \
+    FROM Region r JOIN Country c ON r.id = c.region_id \
+    JOIN Department d ON c.id = d.country_id JOIN Users u ON d.id = u.department_id \
+    WHERE salary >= 1000 GROUP BY r.name",
+    )
+    .unwrap_or_else(|e| panic!("{}", e.to_string()));
+
+    let id_name_schema = |extra: &str| {
+        Schema::empty()
+            .field(
+                FieldDefinition::new(String::from("id"), FieldType::Int, false),
+                false,
+            )
+            .field(
+                FieldDefinition::new(String::from("name"), FieldType::String, false),
+                false,
+            )
+            .field(
+                FieldDefinition::new(String::from(extra), FieldType::Int, false),
+                false,
+            )
+            .clone()
+    };
+
+    let input_schemas = HashMap::from([
+        (0 as PortHandle, id_name_schema("population")),
+        (1 as PortHandle, id_name_schema("region_id")),
+        (2 as PortHandle, id_name_schema("country_id")),
+        (3 as PortHandle, id_name_schema("department_id")),
+    ]);
+
+    let product = ProductProcessorFactory::new(statement.from[0].clone());
+    let output_schema = product
+        .get_output_schema(&DEFAULT_PORT_HANDLE, &input_schemas)
+        .unwrap_or_else(|e| panic!("{}", e.to_string()));
+    assert_eq!(output_schema.fields.len(), 12);
+
+    let join_tables = build_join_chain(&statement.from[0], input_schemas)
+        .unwrap_or_else(|e| panic!("{}", e.to_string()));
+
+    for port in 0..4 as PortHandle {
+        let join_table = join_tables.get(&port).unwrap();
+        assert_eq!(join_table.left.is_some(), port > 0);
+        assert_eq!(join_table.right.is_some(), port < 3);
+        if let Some(right_join) = &join_table.right {
+            assert_eq!(right_join.left_table, port);
+            assert_eq!(right_join.right_table, port + 1);
+        }
+    }
+}
+
+fn get_users_and_departments_schemas() -> HashMap<PortHandle, Schema> {
+    let user_schema = Schema::empty()
+        .field(
+            FieldDefinition::new(String::from("id"), FieldType::Int, false),
+            false,
+        )
+        .field(
+            FieldDefinition::new(String::from("name"), FieldType::String, false),
+            false,
+        )
+        .field(
+            FieldDefinition::new(String::from("salary"), FieldType::Float, false),
+            false,
+        )
+        .field(
+            FieldDefinition::new(String::from("department_id"), FieldType::Int, false),
+            false,
+        )
+        .clone();
+
+    let department_schema = Schema::empty()
+        .field(
+            FieldDefinition::new(String::from("id"), FieldType::Int, false),
+            false,
+        )
+        .field(
+            FieldDefinition::new(String::from("name"), FieldType::String, false),
+            false,
+        )
+        .clone();
+
+    HashMap::from([
+        (0 as PortHandle, user_schema),
+        (1 as PortHandle, department_schema),
+    ])
+}
+
+#[test]
+fn test_join_qualified_columns() {
+    let statement = get_select(
+        "SELECT u.name, d.name, salary \
+    FROM Users u JOIN Department d ON u.department_id = d.id",
+    )
+    .unwrap_or_else(|e| panic!("{}", e.to_string()));
+
+    let product = ProductProcessorFactory::new(statement.from[0].clone());
+    let output_schema = product
+        .get_output_schema(&DEFAULT_PORT_HANDLE, &get_users_and_departments_schemas())
+        .unwrap_or_else(|e| panic!("{}", e.to_string()));
+
+    let index = |ident: &[&str]| {
+        let ident: Vec<Ident> = ident.iter().map(|part| Ident::new(*part)).collect();
+        get_field_index(&ident, &output_schema)
+    };
+
+    assert_eq!(index(&["u", "name"]).unwrap(), 1);
+    assert_eq!(index(&["d", "name"]).unwrap(), 5);
+    assert_eq!(index(&["d", "id"]).unwrap(), 4);
+    assert_eq!(index(&["salary"]).unwrap(), 2);
+    assert!(matches!(
+        index(&["name"]),
+        Err(PipelineError::AmbiguousColumn(_))
+    ));
+    assert!(matches!(
+        index(&["c", "name"]),
+        Err(PipelineError::ColumnNotFound(_))
+    ));
+}
+
+#[test]
+fn test_join_constraint_columns() {
+    let user = Record::new(
+        None,
+        vec![
+            Field::Int(1),
+            Field::String("Alice".to_string()),
+            Field::Float(OrderedFloat(1000.0)),
+            Field::Int(10),
+        ],
+        None,
+    );
+    let department = Record::new(
+        None,
+        vec![Field::Int(10), Field::String("Engineering".to_string())],
+        None,
+    );
+
+    for sql in [
+        "SELECT salary FROM Users u JOIN Department d ON u.department_id = d.id",
+        "SELECT salary FROM Users u JOIN Department d ON department_id = d.id",
+        "SELECT salary FROM Users u JOIN Department d ON d.id = department_id",
+        "SELECT salary FROM Users JOIN Department ON Users.department_id = Department.id",
+    ] {
+        let statement = get_select(sql).unwrap_or_else(|e| panic!("{}", e.to_string()));
+        let join_tables = build_join_chain(&statement.from[0], get_users_and_departments_schemas())
+            .unwrap_or_else(|e| panic!("{}", e.to_string()));
+
+        let join = join_tables.get(&0).unwrap().right.as_ref().unwrap();
+        assert_eq!(
+            join.get_left_record_join_key(&user).unwrap(),
+            join.get_right_record_join_key(&department).unwrap()
+        );
+    }
+
+    let statement =
+        get_select("SELECT salary FROM Users u JOIN Department d ON id = d.id").unwrap();
+    assert!(matches!(
+        build_join_chain(&statement.from[0], get_users_and_departments_schemas()),
+        Err(PipelineError::AmbiguousColumn(_))
+    ));
+
+    let statement =
+        get_select("SELECT salary FROM Users u JOIN Department d ON u.id = d.department_id")
+            .unwrap();
+    assert!(matches!(
+        build_join_chain(&statement.from[0], get_users_and_departments_schemas()),
+        Err(PipelineError::ColumnNotFound(_))
+    ));
+}
+
+#[test]
+fn test_join_duplicate_columns() {
+    let get_output_schema = |sql: &str| {
+        let statement = get_select(sql).unwrap_or_else(|e| panic!("{}", e.to_string()));
+        let product_schema = ProductProcessorFactory::new(statement.from[0].clone())
+            .get_output_schema(&DEFAULT_PORT_HANDLE, &get_users_and_departments_schemas())?;
+        ProjectionProcessorFactory::_new(statement.projection).get_output_schema(
+            &DEFAULT_PORT_HANDLE,
+            &HashMap::from([(DEFAULT_PORT_HANDLE, product_schema)]),
+        )
+    };
+
+    let output_schema = get_output_schema(
+        "SELECT u.name, d.name FROM Users u JOIN Department d ON u.department_id = d.id",
+    )
+    .unwrap_or_else(|e| panic!("{}", e.to_string()));
+    let names: Vec<&str> = output_schema
+        .fields
+        .iter()
+        .map(|field| field.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["u.name", "d.name"]);
+
+    let output_schema = get_output_schema(
+        "SELECT u.name AS user_name, d.name AS department_name \
+        FROM Users u JOIN Department d ON u.department_id = d.id",
+    )
+    .unwrap_or_else(|e| panic!("{}", e.to_string()));
+    let names: Vec<&str> = output_schema
+        .fields
+        .iter()
+        .map(|field| field.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["user_name", "department_name"]);
+
+    let error = get_output_schema(
+        "SELECT u.name AS name, d.name AS name \
+        FROM Users u JOIN Department d ON u.department_id = d.id",
+    )
+    .unwrap_err();
+    assert!(error.to_string().contains("Duplicate column: name"));
+
+    // A table joined with itself without aliases
+    let error =
+        get_output_schema("SELECT salary FROM Users JOIN Users ON Users.id = Users.department_id")
+            .unwrap_err();
+    assert!(error.to_string().contains("Duplicate column: Users.id"));
+}
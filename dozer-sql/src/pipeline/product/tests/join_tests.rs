@@ -0,0 +1,30 @@
+use dozer_types::types::{Field, Record};
+
+use crate::pipeline::product::join::get_composite_key;
+
+#[test]
+fn test_composite_key_field_boundaries() {
+    let first = Record::new(
+        None,
+        vec![
+            Field::String("ab".to_string()),
+            Field::String("c".to_string()),
+        ],
+        None,
+    );
+    let second = Record::new(
+        None,
+        vec![
+            Field::String("a".to_string()),
+            Field::String("bc".to_string()),
+        ],
+        None,
+    );
+
+    let first_key =
+        get_composite_key(&first, &[0, 1]).unwrap_or_else(|e| panic!("{}", e.to_string()));
+    let second_key =
+        get_composite_key(&second, &[0, 1]).unwrap_or_else(|e| panic!("{}", e.to_string()));
+
+    assert_ne!(first_key, second_key);
+}
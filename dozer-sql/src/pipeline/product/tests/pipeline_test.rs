@@ -11,7 +11,7 @@ use dozer_core::dag::node::{
 use dozer_core::dag::record_store::RecordReader;
 use dozer_core::storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
 use dozer_types::ordered_float::OrderedFloat;
-use dozer_types::types::{Field, FieldDefinition, FieldType, Operation, Record, Schema};
+use dozer_types::types::{Field, FieldDefinition, FieldType, KeyMode, Operation, Record, Schema};
 #[cfg(not(test))]
 use log::debug; // Use log crate when building application
 
@@ -48,6 +48,7 @@ impl SourceFactory for TestSourceFactory {
                 OutputPortType::StatefulWithPrimaryKeyLookup {
                     retr_old_records_for_updates: true,
                     retr_old_records_for_deletes: true,
+                    key_mode: KeyMode::Position,
                 },
             ),
             OutputPortDef::new(
@@ -55,6 +56,7 @@ impl SourceFactory for TestSourceFactory {
                 OutputPortType::StatefulWithPrimaryKeyLookup {
                     retr_old_records_for_updates: true,
                     retr_old_records_for_deletes: true,
+                    key_mode: KeyMode::Position,
                 },
             ),
         ])
@@ -357,7 +359,7 @@ impl Sink for TestSink {
 #[test]
 #[ignore]
 fn test_pipeline_builder() {
-    let mut pipeline = PipelineBuilder {}
+    let (mut pipeline, (output_node, output_port)) = PipelineBuilder {}
         .build_pipeline(
             "SELECT user.name, department.name \
                 FROM user JOIN department ON user.department_id = department.id \
@@ -386,8 +388,8 @@ fn test_pipeline_builder() {
     pipeline.add_sink(Arc::new(TestSinkFactory::new(7, latch)), "sink");
     pipeline
         .connect_nodes(
-            "aggregation",
-            Some(DEFAULT_PORT_HANDLE),
+            &output_node,
+            Some(output_port),
             "sink",
             Some(DEFAULT_PORT_HANDLE),
         )
@@ -6,12 +6,15 @@ use dozer_core::dag::{
     node::{OutputPortDef, OutputPortType, PortHandle, Processor, ProcessorFactory},
 };
 use dozer_types::types::{FieldDefinition, Schema};
-use sqlparser::ast::SelectItem;
+use sqlparser::ast::{Expr as SqlExpr, Ident, SelectItem};
 
 use crate::pipeline::{
     errors::PipelineError,
     expression::{
-        builder::{BuilderExpressionType, ExpressionBuilder},
+        builder::{
+            check_unique_field_names, compare_name, fullname_from_ident, BuilderExpressionType,
+            ExpressionBuilder,
+        },
         execution::Expression,
         execution::ExpressionExecutor,
     },
@@ -49,12 +52,12 @@ impl ProcessorFactory for ProjectionProcessorFactory {
         input_schemas: &HashMap<PortHandle, Schema>,
     ) -> Result<Schema, ExecutionError> {
         let input_schema = input_schemas.get(&DEFAULT_PORT_HANDLE).unwrap();
-        match self
-            .select
-            .iter()
-            .map(|item| parse_sql_select_item(item, input_schema))
-            .collect::<Result<Vec<(String, Expression)>, PipelineError>>()
-        {
+        match expand_select_items(&self.select, input_schema).and_then(|select| {
+            select
+                .iter()
+                .map(|item| parse_sql_select_item(item, input_schema))
+                .collect::<Result<Vec<(String, Expression)>, PipelineError>>()
+        }) {
             Ok(expressions) => {
                 let mut output_schema = Schema::empty();
 
@@ -70,6 +73,8 @@ impl ProcessorFactory for ProjectionProcessorFactory {
                     ));
                 }
 
+                check_unique_field_names(&output_schema)
+                    .map_err(|e| ExecutionError::InternalStringError(e.to_string()))?;
                 Ok(output_schema)
             }
             Err(error) => Err(ExecutionError::InternalStringError(error.to_string())),
@@ -88,12 +93,12 @@ impl ProcessorFactory for ProjectionProcessorFactory {
             )),
         }?;
 
-        match self
-            .select
-            .iter()
-            .map(|item| parse_sql_select_item(item, schema))
-            .collect::<Result<Vec<(String, Expression)>, PipelineError>>()
-        {
+        match expand_select_items(&self.select, schema).and_then(|select| {
+            select
+                .iter()
+                .map(|item| parse_sql_select_item(item, schema))
+                .collect::<Result<Vec<(String, Expression)>, PipelineError>>()
+        }) {
             Ok(expressions) => Ok(Box::new(ProjectionProcessor::new(
                 schema.clone(),
                 expressions,
@@ -111,6 +116,50 @@ impl ProcessorFactory for ProjectionProcessorFactory {
     }
 }
 
+/// Expands `*` into the columns of `schema` and `table.*` into the columns of `schema` coming
+/// from `table`, in order. Each column is projected under its name in `schema`.
+pub(crate) fn expand_select_items(
+    select: &[SelectItem],
+    schema: &Schema,
+) -> Result<Vec<SelectItem>, PipelineError> {
+    let mut items = vec![];
+    for item in select {
+        match item {
+            SelectItem::Wildcard => {
+                items.extend(schema.fields.iter().map(|field| select_column(&field.name)));
+            }
+            SelectItem::QualifiedWildcard(object_name) => {
+                let table = fullname_from_ident(&object_name.0);
+                let columns = schema
+                    .fields
+                    .iter()
+                    .filter(|field| match field.name.rsplit_once('.') {
+                        Some((qualifier, _)) => compare_name(qualifier.to_string(), table.clone()),
+                        None => false,
+                    })
+                    .map(|field| select_column(&field.name))
+                    .collect::<Vec<SelectItem>>();
+                if columns.is_empty() {
+                    return Err(PipelineError::InvalidQuery(format!(
+                        "{}.* doesn't match any table",
+                        table
+                    )));
+                }
+                items.extend(columns);
+            }
+            _ => items.push(item.clone()),
+        }
+    }
+    Ok(items)
+}
+
+fn select_column(name: &str) -> SelectItem {
+    SelectItem::ExprWithAlias {
+        expr: SqlExpr::Identifier(Ident::new(name)),
+        alias: Ident::new(name),
+    }
+}
+
 pub(crate) fn parse_sql_select_item(
     sql: &SelectItem,
     schema: &Schema,
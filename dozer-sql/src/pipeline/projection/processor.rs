@@ -1,10 +1,10 @@
 use crate::pipeline::expression::execution::{Expression, ExpressionExecutor};
+use crate::pipeline::expression::scalar::datetime::current_time;
 
 use dozer_core::dag::channels::ProcessorChannelForwarder;
 use dozer_core::dag::dag::DEFAULT_PORT_HANDLE;
 use dozer_core::dag::epoch::Epoch;
 use dozer_core::dag::errors::ExecutionError;
-use dozer_core::dag::errors::ExecutionError::InternalError;
 use dozer_core::dag::node::{PortHandle, Processor};
 use dozer_core::dag::record_store::RecordReader;
 use dozer_core::storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
@@ -25,15 +25,18 @@ impl ProjectionProcessor {
         }
     }
 
+    fn set_now(&mut self) {
+        let now = current_time();
+        for (_, expression) in &mut self.expressions {
+            expression.set_now(&now);
+        }
+    }
+
     fn delete(&mut self, record: &Record) -> Result<Operation, ExecutionError> {
         let mut results = vec![];
 
         for expr in &self.expressions {
-            results.push(
-                expr.1
-                    .evaluate(record, &self.input_schema)
-                    .map_err(|e| InternalError(Box::new(e)))?,
-            );
+            results.push(expr.1.evaluate(record, &self.input_schema)?);
         }
         Ok(Operation::Delete {
             old: Record::new(None, results, None),
@@ -44,11 +47,7 @@ impl ProjectionProcessor {
         let mut results = vec![];
 
         for expr in self.expressions.clone() {
-            results.push(
-                expr.1
-                    .evaluate(record, &self.input_schema)
-                    .map_err(|e| InternalError(Box::new(e)))?,
-            );
+            results.push(expr.1.evaluate(record, &self.input_schema)?);
         }
         Ok(Operation::Insert {
             new: Record::new(None, results, None),
@@ -60,16 +59,8 @@ impl ProjectionProcessor {
         let mut new_results = vec![];
 
         for expr in &self.expressions {
-            old_results.push(
-                expr.1
-                    .evaluate(old, &self.input_schema)
-                    .map_err(|e| InternalError(Box::new(e)))?,
-            );
-            new_results.push(
-                expr.1
-                    .evaluate(new, &self.input_schema)
-                    .map_err(|e| InternalError(Box::new(e)))?,
-            );
+            old_results.push(expr.1.evaluate(old, &self.input_schema)?);
+            new_results.push(expr.1.evaluate(new, &self.input_schema)?);
         }
 
         Ok(Operation::Update {
@@ -81,6 +72,16 @@ impl ProjectionProcessor {
 
 impl Processor for ProjectionProcessor {
     fn init(&mut self, _env: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+        self.set_now();
+        Ok(())
+    }
+
+    fn flush(
+        &mut self,
+        _fw: &mut dyn ProcessorChannelForwarder,
+        _tx: &SharedTransaction,
+    ) -> Result<(), ExecutionError> {
+        self.set_now();
         Ok(())
     }
 
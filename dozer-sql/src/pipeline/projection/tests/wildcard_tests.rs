@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use dozer_core::dag::dag::DEFAULT_PORT_HANDLE;
+use dozer_core::dag::errors::ExecutionError;
+use dozer_core::dag::node::{PortHandle, ProcessorFactory};
+use dozer_types::types::{FieldDefinition, FieldType, Schema};
+
+use crate::pipeline::aggregation::factory::AggregationProcessorFactory;
+use crate::pipeline::builder::get_select;
+use crate::pipeline::projection::factory::ProjectionProcessorFactory;
+
+fn get_input_schemas(schema: Schema) -> HashMap<PortHandle, Schema> {
+    HashMap::from([(DEFAULT_PORT_HANDLE, schema)])
+}
+
+fn get_projection_schema(sql: &str, schema: Schema) -> Result<Schema, ExecutionError> {
+    let select = get_select(sql).unwrap_or_else(|e| panic!("{}", e.to_string()));
+    ProjectionProcessorFactory::_new(select.projection)
+        .get_output_schema(&DEFAULT_PORT_HANDLE, &get_input_schemas(schema))
+}
+
+fn get_users_schema() -> Schema {
+    Schema::empty()
+        .field(
+            FieldDefinition::new(String::from("id"), FieldType::Int, false),
+            false,
+        )
+        .field(
+            FieldDefinition::new(String::from("name"), FieldType::String, true),
+            false,
+        )
+        .field(
+            FieldDefinition::new(String::from("salary"), FieldType::Float, false),
+            false,
+        )
+        .clone()
+}
+
+fn get_join_schema() -> Schema {
+    Schema::empty()
+        .field(
+            FieldDefinition::new(String::from("u.id"), FieldType::Int, false),
+            false,
+        )
+        .field(
+            FieldDefinition::new(String::from("u.name"), FieldType::String, false),
+            false,
+        )
+        .field(
+            FieldDefinition::new(String::from("d.id"), FieldType::Int, false),
+            false,
+        )
+        .field(
+            FieldDefinition::new(String::from("d.name"), FieldType::String, true),
+            false,
+        )
+        .clone()
+}
+
+#[test]
+fn test_wildcard() {
+    let output_schema = get_projection_schema("SELECT * FROM users", get_users_schema())
+        .unwrap_or_else(|e| panic!("{}", e.to_string()));
+    assert_eq!(output_schema, get_users_schema());
+
+    let output_schema = get_projection_schema(
+        "SELECT * FROM users u JOIN departments d ON u.id = d.id",
+        get_join_schema(),
+    )
+    .unwrap_or_else(|e| panic!("{}", e.to_string()));
+    assert_eq!(output_schema, get_join_schema());
+}
+
+#[test]
+fn test_wildcard_with_columns() {
+    let output_schema = get_projection_schema(
+        "SELECT salary * 2 AS bonus, * FROM users",
+        get_users_schema(),
+    )
+    .unwrap_or_else(|e| panic!("{}", e.to_string()));
+
+    let names: Vec<&str> = output_schema
+        .fields
+        .iter()
+        .map(|field| field.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["bonus", "id", "name", "salary"]);
+}
+
+#[test]
+fn test_qualified_wildcard() {
+    let output_schema = get_projection_schema(
+        "SELECT d.*, u.name FROM users u JOIN departments d ON u.id = d.id",
+        get_join_schema(),
+    )
+    .unwrap_or_else(|e| panic!("{}", e.to_string()));
+
+    let join_schema = get_join_schema();
+    assert_eq!(
+        output_schema.fields,
+        vec![
+            join_schema.fields[2].clone(),
+            join_schema.fields[3].clone(),
+            FieldDefinition::new(String::from("u.name"), FieldType::String, false),
+        ]
+    );
+
+    assert!(get_projection_schema(
+        "SELECT c.* FROM users u JOIN departments d ON u.id = d.id",
+        get_join_schema(),
+    )
+    .is_err());
+}
+
+#[test]
+fn test_wildcard_in_aggregation() {
+    let select = get_select("SELECT * FROM users").unwrap_or_else(|e| panic!("{}", e.to_string()));
+    let output_schema = AggregationProcessorFactory::new(select.projection, vec![], None)
+        .get_output_schema(&DEFAULT_PORT_HANDLE, &get_input_schemas(get_users_schema()))
+        .unwrap_or_else(|e| panic!("{}", e.to_string()));
+    assert_eq!(output_schema, get_users_schema());
+
+    let select = get_select("SELECT * FROM users GROUP BY id, name, salary")
+        .unwrap_or_else(|e| panic!("{}", e.to_string()));
+    let output_schema = AggregationProcessorFactory::new(select.projection, select.group_by, None)
+        .get_output_schema(&DEFAULT_PORT_HANDLE, &get_input_schemas(get_users_schema()))
+        .unwrap_or_else(|e| panic!("{}", e.to_string()));
+    let names: Vec<&str> = output_schema
+        .fields
+        .iter()
+        .map(|field| field.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["id", "name", "salary"]);
+}
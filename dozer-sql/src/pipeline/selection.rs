@@ -1,2 +1,3 @@
 pub mod factory;
 pub mod processor;
+mod tests;
@@ -1,9 +1,9 @@
 use crate::pipeline::expression::execution::{Expression, ExpressionExecutor};
+use crate::pipeline::expression::scalar::datetime::current_time;
 use dozer_core::dag::channels::ProcessorChannelForwarder;
 use dozer_core::dag::dag::DEFAULT_PORT_HANDLE;
 use dozer_core::dag::epoch::Epoch;
 use dozer_core::dag::errors::ExecutionError;
-use dozer_core::dag::errors::ExecutionError::InternalError;
 use dozer_core::dag::node::{PortHandle, Processor};
 use dozer_core::dag::record_store::RecordReader;
 use dozer_core::storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
@@ -25,6 +25,10 @@ impl SelectionProcessor {
         }
     }
 
+    fn set_now(&mut self) {
+        self.expression.set_now(&current_time());
+    }
+
     fn delete(&self, record: &dozer_types::types::Record) -> Operation {
         Operation::Delete {
             old: record.clone(),
@@ -36,11 +40,30 @@ impl SelectionProcessor {
             new: record.clone(),
         }
     }
+
+    /// Evaluates the WHERE condition, following SQL three-valued logic.
+    fn is_selected(&self, record: &dozer_types::types::Record) -> Result<bool, ExecutionError> {
+        match self.expression.evaluate(record, &self.input_schema)? {
+            Field::Boolean(true) => Ok(true),
+            // FALSE and NULL (unknown) both drop the record
+            _ => Ok(false),
+        }
+    }
 }
 
 impl Processor for SelectionProcessor {
     fn init(&mut self, _env: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
         info!("{:?}", "Initialising Selection Processor");
+        self.set_now();
+        Ok(())
+    }
+
+    fn flush(
+        &mut self,
+        _fw: &mut dyn ProcessorChannelForwarder,
+        _tx: &SharedTransaction,
+    ) -> Result<(), ExecutionError> {
+        self.set_now();
         Ok(())
     }
 
@@ -58,36 +81,18 @@ impl Processor for SelectionProcessor {
     ) -> Result<(), ExecutionError> {
         match op {
             Operation::Delete { ref old } => {
-                if self
-                    .expression
-                    .evaluate(old, &self.input_schema)
-                    .map_err(|e| InternalError(Box::new(e)))?
-                    == Field::Boolean(true)
-                {
+                if self.is_selected(old)? {
                     let _ = fw.send(op, DEFAULT_PORT_HANDLE);
                 }
             }
             Operation::Insert { ref new } => {
-                if self
-                    .expression
-                    .evaluate(new, &self.input_schema)
-                    .map_err(|e| InternalError(Box::new(e)))?
-                    == Field::Boolean(true)
-                {
+                if self.is_selected(new)? {
                     let _ = fw.send(op, DEFAULT_PORT_HANDLE);
                 }
             }
             Operation::Update { ref old, ref new } => {
-                let old_fulfilled = self
-                    .expression
-                    .evaluate(old, &self.input_schema)
-                    .map_err(|e| InternalError(Box::new(e)))?
-                    == Field::Boolean(true);
-                let new_fulfilled = self
-                    .expression
-                    .evaluate(new, &self.input_schema)
-                    .map_err(|e| InternalError(Box::new(e)))?
-                    == Field::Boolean(true);
+                let old_fulfilled = self.is_selected(old)?;
+                let new_fulfilled = self.is_selected(new)?;
                 match (old_fulfilled, new_fulfilled) {
                     (true, true) => {
                         // both records fulfills the WHERE condition, forward the operation
@@ -0,0 +1,143 @@
+use crate::pipeline::builder::get_select;
+use crate::pipeline::selection::factory::SelectionProcessorFactory;
+use dozer_core::dag::channels::ProcessorChannelForwarder;
+use dozer_core::dag::dag::DEFAULT_PORT_HANDLE;
+use dozer_core::dag::errors::ExecutionError;
+use dozer_core::dag::node::{PortHandle, ProcessorFactory};
+use dozer_core::storage::lmdb_storage::LmdbEnvironmentManager;
+use dozer_types::types::{Field, FieldDefinition, FieldType, Operation, Record, Schema};
+use std::collections::HashMap;
+use tempdir::TempDir;
+
+struct TestChannelForwarder {
+    operations: Vec<Operation>,
+}
+
+impl ProcessorChannelForwarder for TestChannelForwarder {
+    fn send(&mut self, op: Operation, _port: PortHandle) -> Result<(), ExecutionError> {
+        self.operations.push(op);
+        Ok(())
+    }
+}
+
+fn run_selection(sql: &str, schema: Schema, ops: Vec<Operation>) -> Vec<Operation> {
+    let select = get_select(sql).unwrap();
+    let factory = SelectionProcessorFactory::new(select.selection.unwrap());
+    let mut processor = factory
+        .build(
+            HashMap::from([(DEFAULT_PORT_HANDLE, schema)]),
+            HashMap::new(),
+        )
+        .unwrap();
+
+    let tmp_dir = TempDir::new("test").unwrap();
+    let mut storage = LmdbEnvironmentManager::create(tmp_dir.path(), "selection_test").unwrap();
+    processor.init(&mut storage).unwrap();
+
+    let tx = storage.create_txn().unwrap();
+    let mut fw = TestChannelForwarder { operations: vec![] };
+    for op in ops {
+        processor
+            .process(DEFAULT_PORT_HANDLE, op, &mut fw, &tx, &HashMap::new())
+            .unwrap();
+    }
+    fw.operations
+}
+
+fn insert(values: Vec<Field>) -> Operation {
+    Operation::Insert {
+        new: Record::new(None, values, None),
+    }
+}
+
+#[test]
+fn test_selection_filters_records() {
+    let schema = Schema::empty()
+        .field(
+            FieldDefinition::new(String::from("salary"), FieldType::Int, true),
+            false,
+        )
+        .clone();
+
+    let out = run_selection(
+        "SELECT salary FROM users WHERE salary >= 1000",
+        schema,
+        vec![
+            insert(vec![Field::Int(1500)]),
+            insert(vec![Field::Int(500)]),
+            insert(vec![Field::Null]),
+        ],
+    );
+
+    assert_eq!(out, vec![insert(vec![Field::Int(1500)])]);
+}
+
+#[test]
+fn test_selection_null_condition() {
+    let schema = Schema::empty()
+        .field(
+            FieldDefinition::new(String::from("active"), FieldType::Boolean, true),
+            false,
+        )
+        .clone();
+
+    // NOT NULL evaluates to NULL, which must not select the record
+    let out = run_selection(
+        "SELECT active FROM users WHERE NOT active",
+        schema,
+        vec![
+            insert(vec![Field::Null]),
+            insert(vec![Field::Boolean(false)]),
+        ],
+    );
+
+    assert_eq!(out, vec![insert(vec![Field::Boolean(false)])]);
+}
+
+#[test]
+fn test_selection_update() {
+    let schema = Schema::empty()
+        .field(
+            FieldDefinition::new(String::from("salary"), FieldType::Int, true),
+            false,
+        )
+        .clone();
+
+    let old = Record::new(None, vec![Field::Int(1500)], None);
+    let new = Record::new(None, vec![Field::Null], None);
+    let out = run_selection(
+        "SELECT salary FROM users WHERE salary >= 1000",
+        schema,
+        vec![Operation::Update {
+            old: old.clone(),
+            new,
+        }],
+    );
+
+    assert_eq!(out, vec![Operation::Delete { old }]);
+}
+
+#[test]
+fn test_selection_between() {
+    let schema = Schema::empty()
+        .field(
+            FieldDefinition::new(String::from("actor_id"), FieldType::Int, false),
+            false,
+        )
+        .clone();
+    let ops: Vec<Operation> = (1..=6).map(|id| insert(vec![Field::Int(id)])).collect();
+
+    let out = run_selection(
+        "SELECT actor_id FROM actor WHERE actor_id BETWEEN 2 AND 5",
+        schema.clone(),
+        ops.clone(),
+    );
+    assert_eq!(out, ops[1..5].to_vec());
+
+    let out = run_selection(
+        "SELECT actor_id FROM actor WHERE actor_id NOT BETWEEN 2 AND 5",
+        schema,
+        ops.clone(),
+    );
+    assert_eq!(out, vec![ops[0].clone(), ops[5].clone()]);
+}
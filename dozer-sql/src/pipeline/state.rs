@@ -0,0 +1,22 @@
+use dozer_core::storage::errors::StorageError;
+use dozer_core::storage::errors::StorageError::SerializationError;
+use dozer_types::bincode;
+use dozer_types::types::Record;
+
+/// Returns the serialized values of `record`, which equal records share, to key their state.
+pub(crate) fn get_values_key(record: &Record) -> Result<Vec<u8>, StorageError> {
+    bincode::serialize(&record.values).map_err(|e| SerializationError {
+        typ: "Record".to_string(),
+        reason: Box::new(e),
+    })
+}
+
+/// Decodes a count stored as a big endian `u64`. A missing count is 0.
+pub(crate) fn decode_count(value: Option<&[u8]>) -> Result<u64, StorageError> {
+    match value {
+        Some(v) => Ok(u64::from_be_bytes(
+            v.try_into().map_err(|_| StorageError::InvalidRecord)?,
+        )),
+        None => Ok(0),
+    }
+}
@@ -1,12 +1,14 @@
-use crate::pipeline::builder::PipelineBuilder;
-use dozer_core::dag::app::App;
+use crate::pipeline::builder::{OutputNode, PipelineBuilder};
+use crate::pipeline::errors::PipelineError;
+use dozer_core::dag::app::{App, AppPipeline};
 use dozer_core::dag::appsource::{AppSource, AppSourceManager};
 use dozer_core::dag::channels::SourceChannelForwarder;
-use dozer_core::dag::dag::DEFAULT_PORT_HANDLE;
+use dozer_core::dag::dag::{Dag, DEFAULT_PORT_HANDLE};
+use dozer_core::dag::dag_schemas::DagSchemaManager;
 use dozer_core::dag::errors::ExecutionError;
 use dozer_core::dag::executor::{DagExecutor, ExecutorOptions};
 use dozer_core::dag::node::{
-    OutputPortDef, OutputPortType, PortHandle, Sink, SinkFactory, Source, SourceFactory,
+    NodeHandle, OutputPortDef, OutputPortType, PortHandle, Sink, SinkFactory, Source, SourceFactory,
 };
 use dozer_core::dag::record_store::RecordReader;
 use dozer_core::storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
@@ -166,7 +168,7 @@ impl Sink for TestSink {
 
 #[test]
 fn test_pipeline_builder() {
-    let mut pipeline = PipelineBuilder {}
+    let (mut pipeline, (output_node, output_port)) = PipelineBuilder {}
         .build_pipeline(
             "SELECT COUNT(Spending), users.Country \
                 FROM users \
@@ -190,8 +192,8 @@ fn test_pipeline_builder() {
     );
     pipeline
         .connect_nodes(
-            "aggregation",
-            Some(DEFAULT_PORT_HANDLE),
+            &output_node,
+            Some(output_port),
             "sink",
             Some(DEFAULT_PORT_HANDLE),
         )
@@ -228,3 +230,241 @@ fn test_pipeline_builder() {
     let elapsed = now.elapsed();
     debug!("Elapsed: {:.2?}", elapsed);
 }
+
+fn build_dag((mut pipeline, (output_node, output_port)): (AppPipeline, OutputNode)) -> Dag {
+    let mut asm = AppSourceManager::new();
+    asm.add(AppSource::new(
+        "mem".to_string(),
+        Arc::new(TestSourceFactory::new(vec![DEFAULT_PORT_HANDLE])),
+        vec![("users".to_string(), DEFAULT_PORT_HANDLE)]
+            .into_iter()
+            .collect(),
+    ))
+    .unwrap();
+
+    pipeline.add_sink(
+        Arc::new(TestSinkFactory::new(vec![DEFAULT_PORT_HANDLE])),
+        "sink",
+    );
+    pipeline
+        .connect_nodes(
+            &output_node,
+            Some(output_port),
+            "sink",
+            Some(DEFAULT_PORT_HANDLE),
+        )
+        .unwrap();
+
+    let mut app = App::new(asm);
+    app.add_pipeline(pipeline);
+
+    app.get_dag().unwrap()
+}
+
+fn run_pipeline(pipeline: (AppPipeline, OutputNode)) {
+    let dag = build_dag(pipeline);
+
+    let tmp_dir = TempDir::new("test").unwrap();
+    let mut executor = DagExecutor::new(
+        &dag,
+        tmp_dir.path(),
+        ExecutorOptions::default(),
+        Arc::new(AtomicBool::new(true)),
+    )
+    .unwrap();
+
+    executor
+        .start()
+        .unwrap_or_else(|e| panic!("Unable to start the Executor: {}", e));
+    assert!(executor.join().is_ok());
+}
+
+#[test]
+fn test_pipeline_builder_projection() {
+    let pipeline = PipelineBuilder {}
+        .build_pipeline("SELECT CustomerID, Spending FROM users WHERE Spending >= 1")
+        .unwrap_or_else(|e| panic!("Unable to build the pipeline: {}", e));
+
+    let dag = build_dag(pipeline);
+    let schema_manager = DagSchemaManager::new(&dag).unwrap();
+    let output_schema = schema_manager
+        .get_node_output_schemas(&NodeHandle::new(Some(1), "aggregation".to_string()))
+        .unwrap()
+        .get(&DEFAULT_PORT_HANDLE)
+        .unwrap()
+        .clone();
+
+    let field_names: Vec<String> = output_schema
+        .fields
+        .into_iter()
+        .map(|field| field.name)
+        .collect();
+    assert_eq!(field_names, vec!["CustomerID", "Spending"]);
+}
+
+#[test]
+fn test_pipeline_builder_group_by() {
+    let sql = "SELECT Country, COUNT(Spending), SUM(Spending), MIN(Spending), MAX(Spending) \
+        FROM users GROUP BY Country";
+    let build = || {
+        PipelineBuilder {}
+            .build_pipeline(sql)
+            .unwrap_or_else(|e| panic!("Unable to build the pipeline: {}", e))
+    };
+
+    let dag = build_dag(build());
+    let schema_manager = DagSchemaManager::new(&dag).unwrap();
+    let output_schema = schema_manager
+        .get_node_output_schemas(&NodeHandle::new(Some(1), "aggregation".to_string()))
+        .unwrap()
+        .get(&DEFAULT_PORT_HANDLE)
+        .unwrap()
+        .clone();
+
+    let field_names: Vec<String> = output_schema
+        .fields
+        .into_iter()
+        .map(|field| field.name)
+        .collect();
+    assert_eq!(
+        field_names,
+        vec![
+            "Country",
+            "COUNT(Spending)",
+            "SUM(Spending)",
+            "MIN(Spending)",
+            "MAX(Spending)"
+        ]
+    );
+
+    run_pipeline(build());
+}
+
+#[test]
+fn test_pipeline_builder_having() {
+    let pipeline = PipelineBuilder {}
+        .build_pipeline(
+            "SELECT Country, SUM(Spending) FROM users \
+                GROUP BY Country HAVING COUNT(CustomerID) > 1",
+        )
+        .unwrap_or_else(|e| panic!("Unable to build the pipeline: {}", e));
+
+    run_pipeline(pipeline);
+}
+
+#[test]
+fn test_pipeline_builder_with_cte() {
+    let pipeline = PipelineBuilder {}
+        .build_pipeline(
+            "WITH italians AS ( \
+                SELECT CustomerID, Country, Spending FROM users WHERE Country = 'Italy' \
+            ) \
+            SELECT COUNT(Spending), italians.Country \
+            FROM italians \
+            GROUP BY italians.Country",
+        )
+        .unwrap_or_else(|e| panic!("Unable to build the pipeline: {}", e));
+
+    run_pipeline(pipeline);
+}
+
+#[test]
+fn test_pipeline_builder_with_chained_cte() {
+    let pipeline = PipelineBuilder {}
+        .build_pipeline(
+            "WITH italians AS (SELECT CustomerID, Spending FROM users WHERE Country = 'Italy'), \
+            spenders AS (SELECT CustomerID, Spending FROM italians WHERE Spending >= 1) \
+            SELECT COUNT(Spending) FROM spenders",
+        )
+        .unwrap_or_else(|e| panic!("Unable to build the pipeline: {}", e));
+
+    run_pipeline(pipeline);
+}
+
+#[test]
+fn test_pipeline_builder_recursive_cte() {
+    let result = PipelineBuilder {}.build_pipeline(
+        "WITH RECURSIVE italians AS (SELECT CustomerID FROM users) \
+        SELECT CustomerID FROM italians",
+    );
+    assert!(matches!(result, Err(PipelineError::InvalidQuery(_))));
+}
+
+#[test]
+fn test_pipeline_builder_order_by() {
+    let pipeline = PipelineBuilder {}
+        .build_pipeline("SELECT CustomerID, Spending FROM users ORDER BY Spending DESC, CustomerID")
+        .unwrap_or_else(|e| panic!("Unable to build the pipeline: {}", e));
+    assert_eq!(pipeline.1, ("order_by".to_string(), DEFAULT_PORT_HANDLE));
+
+    run_pipeline(pipeline);
+}
+
+#[test]
+fn test_pipeline_builder_order_by_unknown_column() {
+    // Sort columns must be part of the query output
+    let pipeline = PipelineBuilder {}
+        .build_pipeline("SELECT CustomerID FROM users ORDER BY Spending")
+        .unwrap_or_else(|e| panic!("Unable to build the pipeline: {}", e));
+
+    let dag = build_dag(pipeline);
+    assert!(DagSchemaManager::new(&dag).is_err());
+}
+
+#[test]
+fn test_pipeline_builder_limit() {
+    let pipeline = PipelineBuilder {}
+        .build_pipeline(
+            "SELECT CustomerID, Spending FROM users ORDER BY CustomerID LIMIT 10 OFFSET 5",
+        )
+        .unwrap_or_else(|e| panic!("Unable to build the pipeline: {}", e));
+    assert_eq!(pipeline.1, ("limit".to_string(), DEFAULT_PORT_HANDLE));
+
+    run_pipeline(pipeline);
+
+    let result = PipelineBuilder {}.build_pipeline("SELECT CustomerID FROM users LIMIT -1");
+    assert!(matches!(result, Err(PipelineError::InvalidQuery(_))));
+}
+
+#[test]
+fn test_pipeline_builder_distinct() {
+    let pipeline = PipelineBuilder {}
+        .build_pipeline("SELECT DISTINCT Country FROM users")
+        .unwrap_or_else(|e| panic!("Unable to build the pipeline: {}", e));
+    assert_eq!(pipeline.1, ("distinct".to_string(), DEFAULT_PORT_HANDLE));
+
+    run_pipeline(pipeline);
+}
+
+#[test]
+fn test_pipeline_builder_union() {
+    let pipeline = PipelineBuilder {}
+        .build_pipeline(
+            "SELECT CustomerID, Country FROM users WHERE Spending >= 1 \
+            UNION ALL SELECT CustomerID, Country FROM users WHERE Spending < 1",
+        )
+        .unwrap_or_else(|e| panic!("Unable to build the pipeline: {}", e));
+    assert_eq!(pipeline.1, ("union".to_string(), DEFAULT_PORT_HANDLE));
+
+    run_pipeline(pipeline);
+
+    // A deduplicating UNION goes through a distinct node
+    let pipeline = PipelineBuilder {}
+        .build_pipeline("SELECT Country FROM users UNION SELECT Country FROM users")
+        .unwrap_or_else(|e| panic!("Unable to build the pipeline: {}", e));
+    assert_eq!(pipeline.1, ("distinct".to_string(), DEFAULT_PORT_HANDLE));
+}
+
+#[test]
+fn test_pipeline_builder_union_incompatible() {
+    let pipeline = PipelineBuilder {}
+        .build_pipeline("SELECT CustomerID FROM users UNION ALL SELECT Country FROM users")
+        .unwrap_or_else(|e| panic!("Unable to build the pipeline: {}", e));
+
+    let dag = build_dag(pipeline);
+    assert!(DagSchemaManager::new(&dag).is_err());
+
+    let result = PipelineBuilder {}
+        .build_pipeline("SELECT Country FROM users EXCEPT SELECT Country FROM users");
+    assert!(matches!(result, Err(PipelineError::InvalidQuery(_))));
+}
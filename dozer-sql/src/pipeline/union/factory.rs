@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use dozer_core::dag::{
+    dag::DEFAULT_PORT_HANDLE,
+    errors::ExecutionError,
+    node::{OutputPortDef, OutputPortType, PortHandle, Processor, ProcessorFactory},
+};
+use dozer_types::types::{FieldDefinition, Schema};
+
+use crate::pipeline::errors::PipelineError;
+
+use super::processor::UnionProcessor;
+
+pub const UNION_LEFT_PORT: PortHandle = 0;
+pub const UNION_RIGHT_PORT: PortHandle = 1;
+
+/// Merges the outputs of the two sides of a `UNION ALL`.
+#[derive(Debug, Default)]
+pub struct UnionProcessorFactory {}
+
+impl UnionProcessorFactory {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ProcessorFactory for UnionProcessorFactory {
+    fn get_input_ports(&self) -> Vec<PortHandle> {
+        vec![UNION_LEFT_PORT, UNION_RIGHT_PORT]
+    }
+
+    fn get_output_ports(&self) -> Vec<OutputPortDef> {
+        vec![OutputPortDef::new(
+            DEFAULT_PORT_HANDLE,
+            OutputPortType::Stateless,
+        )]
+    }
+
+    fn get_output_schema(
+        &self,
+        _output_port: &PortHandle,
+        input_schemas: &HashMap<PortHandle, Schema>,
+    ) -> Result<Schema, ExecutionError> {
+        let left = input_schemas
+            .get(&UNION_LEFT_PORT)
+            .ok_or(ExecutionError::InvalidPortHandle(UNION_LEFT_PORT))?;
+        let right = input_schemas
+            .get(&UNION_RIGHT_PORT)
+            .ok_or(ExecutionError::InvalidPortHandle(UNION_RIGHT_PORT))?;
+        get_union_schema(left, right)
+            .map_err(|e| ExecutionError::InternalStringError(e.to_string()))
+    }
+
+    fn build(
+        &self,
+        _input_schemas: HashMap<PortHandle, Schema>,
+        _output_schemas: HashMap<PortHandle, Schema>,
+    ) -> Result<Box<dyn Processor>, ExecutionError> {
+        Ok(Box::new(UnionProcessor::new()))
+    }
+
+    fn prepare(
+        &self,
+        _input_schemas: HashMap<PortHandle, Schema>,
+        _output_schemas: HashMap<PortHandle, Schema>,
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+}
+
+/// Both sides must have the same number of fields, with the same types. Fields are named after
+/// the left side, and are nullable if they are nullable on either side.
+pub(crate) fn get_union_schema(left: &Schema, right: &Schema) -> Result<Schema, PipelineError> {
+    if left.fields.len() != right.fields.len() {
+        return Err(PipelineError::InvalidQuery(format!(
+            "Each UNION query must have the same number of columns: {} and {}",
+            left.fields.len(),
+            right.fields.len()
+        )));
+    }
+
+    let mut schema = Schema::empty();
+    for (l, r) in left.fields.iter().zip(right.fields.iter()) {
+        if l.typ != r.typ {
+            return Err(PipelineError::InvalidQuery(format!(
+                "UNION types {} and {} of column {} don't match",
+                l.typ, r.typ, l.name
+            )));
+        }
+        // Records of both sides can share a key, so the union has no primary key
+        schema.field(
+            FieldDefinition::new(l.name.clone(), l.typ, l.nullable || r.nullable),
+            false,
+        );
+    }
+    Ok(schema)
+}
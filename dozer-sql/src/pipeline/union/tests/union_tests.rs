@@ -0,0 +1,126 @@
+use crate::pipeline::errors::PipelineError;
+use crate::pipeline::union::factory::{
+    get_union_schema, UnionProcessorFactory, UNION_LEFT_PORT, UNION_RIGHT_PORT,
+};
+use dozer_core::dag::channels::ProcessorChannelForwarder;
+use dozer_core::dag::errors::ExecutionError;
+use dozer_core::dag::node::{PortHandle, ProcessorFactory};
+use dozer_core::storage::lmdb_storage::LmdbEnvironmentManager;
+use dozer_types::types::{Field, FieldDefinition, FieldType, Operation, Record, Schema};
+use std::collections::HashMap;
+use tempdir::TempDir;
+
+struct TestChannelForwarder {
+    operations: Vec<Operation>,
+}
+
+impl ProcessorChannelForwarder for TestChannelForwarder {
+    fn send(&mut self, op: Operation, _port: PortHandle) -> Result<(), ExecutionError> {
+        self.operations.push(op);
+        Ok(())
+    }
+}
+
+fn get_schema(name: &str, typ: FieldType, nullable: bool) -> Schema {
+    Schema::empty()
+        .field(
+            FieldDefinition::new(String::from("ID"), FieldType::Int, false),
+            true,
+        )
+        .field(FieldDefinition::new(name.to_string(), typ, nullable), false)
+        .clone()
+}
+
+fn actor(id: i64, name: &str) -> Record {
+    Record::new(
+        None,
+        vec![Field::Int(id), Field::String(name.to_string())],
+        None,
+    )
+}
+
+#[test]
+fn test_union_schema() {
+    let schema = get_union_schema(
+        &get_schema("FirstName", FieldType::String, false),
+        &get_schema("LastName", FieldType::String, true),
+    )
+    .unwrap();
+
+    // Names come from the left side, nullability from both
+    assert_eq!(
+        schema.fields,
+        vec![
+            FieldDefinition::new(String::from("ID"), FieldType::Int, false),
+            FieldDefinition::new(String::from("FirstName"), FieldType::String, true),
+        ]
+    );
+    assert!(schema.primary_index.is_empty());
+}
+
+#[test]
+fn test_union_schema_mismatch() {
+    let left = get_schema("FirstName", FieldType::String, false);
+
+    let result = get_union_schema(&left, &get_schema("Salary", FieldType::Int, false));
+    assert!(matches!(result, Err(PipelineError::InvalidQuery(_))));
+
+    let mut right = left.clone();
+    right.field(
+        FieldDefinition::new(String::from("LastName"), FieldType::String, false),
+        false,
+    );
+    let result = get_union_schema(&left, &right);
+    assert!(matches!(result, Err(PipelineError::InvalidQuery(_))));
+}
+
+#[test]
+fn test_union_forwards_both_inputs() {
+    let factory = UnionProcessorFactory::new();
+    let mut processor = factory.build(HashMap::new(), HashMap::new()).unwrap();
+
+    let tmp_dir = TempDir::new("test").unwrap();
+    let mut storage = LmdbEnvironmentManager::create(tmp_dir.path(), "union_test").unwrap();
+    processor.init(&mut storage).unwrap();
+
+    let tx = storage.create_txn().unwrap();
+    let mut fw = TestChannelForwarder { operations: vec![] };
+    let ops = vec![
+        (
+            UNION_LEFT_PORT,
+            Operation::Insert {
+                new: actor(1, "PENELOPE"),
+            },
+        ),
+        // Duplicates are kept
+        (
+            UNION_RIGHT_PORT,
+            Operation::Insert {
+                new: actor(1, "PENELOPE"),
+            },
+        ),
+        (
+            UNION_RIGHT_PORT,
+            Operation::Update {
+                old: actor(1, "PENELOPE"),
+                new: actor(1, "NICK"),
+            },
+        ),
+        (
+            UNION_LEFT_PORT,
+            Operation::Delete {
+                old: actor(1, "PENELOPE"),
+            },
+        ),
+    ];
+    for (port, op) in ops.clone() {
+        processor
+            .process(port, op, &mut fw, &tx, &HashMap::new())
+            .unwrap();
+    }
+
+    assert_eq!(
+        fw.operations,
+        ops.into_iter().map(|(_, op)| op).collect::<Vec<_>>()
+    );
+}
@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+
+use dozer_core::dag::{
+    dag::DEFAULT_PORT_HANDLE,
+    errors::ExecutionError,
+    node::{OutputPortDef, OutputPortType, PortHandle, Processor, ProcessorFactory},
+};
+use dozer_types::types::{FieldType, Schema};
+
+use super::processor::UnnestProcessor;
+
+#[derive(Debug)]
+pub struct UnnestProcessorFactory {
+    column: String,
+}
+
+impl UnnestProcessorFactory {
+    /// Creates a new [`UnnestProcessorFactory`] exploding the array in `column`.
+    pub fn new(column: String) -> Self {
+        Self { column }
+    }
+
+    fn get_input_schema<'a>(
+        &self,
+        input_schemas: &'a HashMap<PortHandle, Schema>,
+    ) -> Result<&'a Schema, ExecutionError> {
+        input_schemas
+            .get(&DEFAULT_PORT_HANDLE)
+            .ok_or(ExecutionError::InvalidPortHandle(DEFAULT_PORT_HANDLE))
+    }
+
+    fn get_column_index(&self, schema: &Schema) -> Result<usize, ExecutionError> {
+        schema
+            .get_field_index(&self.column)
+            .map(|(index, _)| index)
+            .map_err(|_| ExecutionError::FieldNotFound(self.column.clone()))
+    }
+}
+
+impl ProcessorFactory for UnnestProcessorFactory {
+    fn get_input_ports(&self) -> Vec<PortHandle> {
+        vec![DEFAULT_PORT_HANDLE]
+    }
+
+    fn get_output_ports(&self) -> Vec<OutputPortDef> {
+        vec![OutputPortDef::new(
+            DEFAULT_PORT_HANDLE,
+            OutputPortType::Stateless,
+        )]
+    }
+
+    fn get_output_schema(
+        &self,
+        _output_port: &PortHandle,
+        input_schemas: &HashMap<PortHandle, Schema>,
+    ) -> Result<Schema, ExecutionError> {
+        let mut schema = self.get_input_schema(input_schemas)?.clone();
+        let index = self.get_column_index(&schema)?;
+        schema.fields[index].typ = FieldType::Json;
+        // The records exploded from the same input record share its primary key
+        schema.primary_index.clear();
+        Ok(schema)
+    }
+
+    fn build(
+        &self,
+        input_schemas: HashMap<PortHandle, Schema>,
+        _output_schemas: HashMap<PortHandle, Schema>,
+    ) -> Result<Box<dyn Processor>, ExecutionError> {
+        let schema = self.get_input_schema(&input_schemas)?;
+        let index = self.get_column_index(schema)?;
+        Ok(Box::new(UnnestProcessor::new(index)))
+    }
+
+    fn prepare(
+        &self,
+        _input_schemas: HashMap<PortHandle, Schema>,
+        _output_schemas: HashMap<PortHandle, Schema>,
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+use crate::pipeline::errors::PipelineError;
+use dozer_core::dag::channels::ProcessorChannelForwarder;
+use dozer_core::dag::dag::DEFAULT_PORT_HANDLE;
+use dozer_core::dag::epoch::Epoch;
+use dozer_core::dag::errors::ExecutionError;
+use dozer_core::dag::node::{PortHandle, Processor};
+use dozer_core::dag::record_store::RecordReader;
+use dozer_core::storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
+use dozer_types::log::info;
+use dozer_types::serde_json::Value as JsonValue;
+use dozer_types::types::{Field, Operation, Record};
+use std::collections::HashMap;
+
+/// Explodes a JSON array column, forwarding one record per element of the array.
+///
+/// Each output record is a copy of the input record with the array replaced by one of its
+/// elements, as a [`Field::Json`]. The column can hold a [`Field::Json`] or a [`Field::Bson`]
+/// array. A `NULL` or empty array produces no records.
+///
+/// Every operation received is turned into operations on all its elements: an insert into N
+/// inserts, a delete into N deletes and an update into the deletes of the old elements followed
+/// by the inserts of the new ones. Since the old record travels with deletes and updates, no
+/// state is kept.
+#[derive(Debug)]
+pub struct UnnestProcessor {
+    column: usize,
+}
+
+impl UnnestProcessor {
+    pub fn new(column: usize) -> Self {
+        Self { column }
+    }
+
+    fn unnest(&self, record: &Record) -> Result<Vec<Record>, PipelineError> {
+        let field = record
+            .values
+            .get(self.column)
+            .ok_or_else(|| PipelineError::InvalidValue(format!("column {}", self.column)))?;
+        let elements = match field {
+            Field::Null => vec![],
+            Field::Json(JsonValue::Array(elements)) => elements.clone(),
+            Field::Bson(_) => match field.to_json() {
+                Some(JsonValue::Array(elements)) => elements,
+                _ => return Err(invalid_array(field)),
+            },
+            _ => return Err(invalid_array(field)),
+        };
+
+        Ok(elements
+            .into_iter()
+            .map(|element| {
+                let mut exploded = record.clone();
+                exploded.values[self.column] = Field::Json(element);
+                exploded
+            })
+            .collect())
+    }
+}
+
+fn invalid_array(field: &Field) -> PipelineError {
+    PipelineError::InvalidFunctionArgument("unnest".to_string(), field.clone(), 0)
+}
+
+impl Processor for UnnestProcessor {
+    fn init(&mut self, _env: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+        info!("{:?}", "Initialising Unnest Processor");
+        Ok(())
+    }
+
+    fn commit(&self, _epoch: &Epoch, _tx: &SharedTransaction) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn process(
+        &mut self,
+        _from_port: PortHandle,
+        op: Operation,
+        fw: &mut dyn ProcessorChannelForwarder,
+        _tx: &SharedTransaction,
+        _reader: &HashMap<PortHandle, RecordReader>,
+    ) -> Result<(), ExecutionError> {
+        let unnest = |record: &Record| self.unnest(record).map_err(ExecutionError::from);
+        let (old, new) = match &op {
+            Operation::Insert { new } => (vec![], unnest(new)?),
+            Operation::Delete { old } => (unnest(old)?, vec![]),
+            Operation::Update { old, new } => (unnest(old)?, unnest(new)?),
+        };
+
+        // Unnesting can fail, so every record is exploded before the first one is forwarded
+        for old in old {
+            fw.send(Operation::Delete { old }, DEFAULT_PORT_HANDLE)?;
+        }
+        for new in new {
+            fw.send(Operation::Insert { new }, DEFAULT_PORT_HANDLE)?;
+        }
+        Ok(())
+    }
+}
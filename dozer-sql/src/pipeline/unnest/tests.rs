@@ -0,0 +1,2 @@
+#[cfg(test)]
+mod unnest_tests;
@@ -0,0 +1,161 @@
+use crate::pipeline::unnest::factory::UnnestProcessorFactory;
+use dozer_core::dag::channels::ProcessorChannelForwarder;
+use dozer_core::dag::dag::DEFAULT_PORT_HANDLE;
+use dozer_core::dag::errors::ExecutionError;
+use dozer_core::dag::node::{PortHandle, ProcessorFactory};
+use dozer_core::storage::lmdb_storage::LmdbEnvironmentManager;
+use dozer_types::serde_json::{json, Value as JsonValue};
+use dozer_types::types::{Field, FieldDefinition, FieldType, Operation, Record, Schema};
+use std::collections::HashMap;
+use tempdir::TempDir;
+
+struct TestChannelForwarder {
+    operations: Vec<Operation>,
+}
+
+impl ProcessorChannelForwarder for TestChannelForwarder {
+    fn send(&mut self, op: Operation, _port: PortHandle) -> Result<(), ExecutionError> {
+        self.operations.push(op);
+        Ok(())
+    }
+}
+
+fn get_schema() -> Schema {
+    Schema::empty()
+        .field(
+            FieldDefinition::new(String::from("id"), FieldType::Int, false),
+            true,
+        )
+        .field(
+            FieldDefinition::new(String::from("tags"), FieldType::Json, true),
+            false,
+        )
+        .clone()
+}
+
+fn order(id: i64, tags: Field) -> Record {
+    Record::new(None, vec![Field::Int(id), tags], None)
+}
+
+fn tags(id: i64, tags: JsonValue) -> Record {
+    order(id, Field::Json(tags))
+}
+
+fn run_unnest(ops: Vec<Operation>) -> Result<Vec<Operation>, ExecutionError> {
+    let factory = UnnestProcessorFactory::new(String::from("tags"));
+    let input_schemas = HashMap::from([(DEFAULT_PORT_HANDLE, get_schema())]);
+    let mut processor = factory.build(input_schemas, HashMap::new())?;
+
+    let tmp_dir = TempDir::new("test").unwrap();
+    let mut storage = LmdbEnvironmentManager::create(tmp_dir.path(), "unnest_test").unwrap();
+    processor.init(&mut storage)?;
+
+    let tx = storage.create_txn().unwrap();
+    let mut fw = TestChannelForwarder { operations: vec![] };
+    for op in ops {
+        processor.process(DEFAULT_PORT_HANDLE, op, &mut fw, &tx, &HashMap::new())?;
+    }
+    Ok(fw.operations)
+}
+
+#[test]
+fn test_unnest_output_schema() {
+    let factory = UnnestProcessorFactory::new(String::from("tags"));
+    let input_schemas = HashMap::from([(DEFAULT_PORT_HANDLE, get_schema())]);
+    let schema = factory
+        .get_output_schema(&DEFAULT_PORT_HANDLE, &input_schemas)
+        .unwrap();
+    assert_eq!(schema.fields[1].typ, FieldType::Json);
+    assert!(schema.primary_index.is_empty());
+
+    let factory = UnnestProcessorFactory::new(String::from("missing"));
+    assert!(matches!(
+        factory.get_output_schema(&DEFAULT_PORT_HANDLE, &input_schemas),
+        Err(ExecutionError::FieldNotFound(_))
+    ));
+}
+
+#[test]
+fn test_unnest_insert_fan_out() {
+    let ops = vec![
+        Operation::Insert {
+            new: tags(1, json!(["a", "b", "c"])),
+        },
+        Operation::Insert {
+            new: tags(2, json!([])),
+        },
+        Operation::Insert {
+            new: order(3, Field::Null),
+        },
+        Operation::Insert {
+            new: order(4, Field::Bson(b"[1, {\"x\": 2}]".to_vec())),
+        },
+    ];
+
+    assert_eq!(
+        run_unnest(ops).unwrap(),
+        vec![
+            Operation::Insert {
+                new: tags(1, json!("a")),
+            },
+            Operation::Insert {
+                new: tags(1, json!("b")),
+            },
+            Operation::Insert {
+                new: tags(1, json!("c")),
+            },
+            Operation::Insert {
+                new: tags(4, json!(1)),
+            },
+            Operation::Insert {
+                new: tags(4, json!({"x": 2})),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_unnest_retractions_fan_out() {
+    let ops = vec![
+        Operation::Update {
+            old: tags(1, json!(["a", "b"])),
+            new: tags(1, json!(["a", "c", "d"])),
+        },
+        Operation::Delete {
+            old: tags(2, json!(["e", "f"])),
+        },
+    ];
+
+    let output = run_unnest(ops).unwrap();
+    let count = |f: fn(&Operation) -> bool| output.iter().filter(|op| f(op)).count();
+    assert_eq!(count(|op| matches!(op, Operation::Delete { .. })), 4);
+    assert_eq!(count(|op| matches!(op, Operation::Insert { .. })), 3);
+    assert_eq!(count(|op| matches!(op, Operation::Update { .. })), 0);
+    assert_eq!(
+        output[..3],
+        [
+            Operation::Delete {
+                old: tags(1, json!("a")),
+            },
+            Operation::Delete {
+                old: tags(1, json!("b")),
+            },
+            Operation::Insert {
+                new: tags(1, json!("a")),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_unnest_rejects_non_arrays() {
+    let ops = vec![Operation::Insert {
+        new: tags(1, json!({"a": 1})),
+    }];
+    assert!(run_unnest(ops).is_err());
+
+    let ops = vec![Operation::Insert {
+        new: order(1, Field::String(String::from("[1, 2]"))),
+    }];
+    assert!(run_unnest(ops).is_err());
+}
@@ -248,20 +248,21 @@ impl Client {
                     endpoint, field.name, rest_path
                 )
             });
-            let oapi_type = match &schema.schema_kind {
-                SchemaKind::Type(oapi_type) => oapi_type,
+            match &schema.schema_kind {
+                SchemaKind::Type(oapi_type) => assert!(
+                    oapi_type_matches(oapi_type, field.typ),
+                    "Check REST schema failed for endpoint {}, expected field type {}, got {:?}",
+                    endpoint,
+                    field.typ,
+                    oapi_type
+                ),
+                // JSON fields accept any value.
+                SchemaKind::Any(_) if field.typ == FieldType::Json => {}
                 _ => panic!(
                     "Expecting type schema for endpoint {}, field {} in oapi response, path is {}",
                     endpoint, field.name, rest_path
                 ),
-            };
-            assert!(
-                oapi_type_matches(oapi_type, field.typ),
-                "Check REST schema failed for endpoint {}, expected field type {}, got {:?}",
-                endpoint,
-                field.typ,
-                oapi_type
-            );
+            }
             if field.nullable {
                 assert!(!required.contains(&field.name), "Check REST schema failed for endpoint {}, field {} is nullable, but it is required", endpoint, field.name);
             } else {
@@ -352,6 +353,7 @@ fn grpc_type_matches(grpc_type: i32, field_type: FieldType) -> bool {
         FieldType::Timestamp => grpc_type == Type::Timestamp as i32,
         FieldType::Date => grpc_type == Type::Date as i32,
         FieldType::Bson => grpc_type == Type::Bson as i32,
+        FieldType::Json => grpc_type == Type::Json as i32,
     }
 }
 
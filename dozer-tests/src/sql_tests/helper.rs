@@ -149,7 +149,7 @@ pub fn map_sqlite_to_record(
                 Field::Decimal(Decimal::from_str(&val).expect("decimal parse error"))
             },
             FieldType::Date =>  convert_type!(Field::String, f, row, idx),
-            dozer_types::types::FieldType::Bson => {
+            dozer_types::types::FieldType::Bson | dozer_types::types::FieldType::Json => {
                 panic!("type not supported : {:?}", f.typ.to_owned())
             }
         };
@@ -223,7 +223,7 @@ pub fn map_field_to_string(f: &Field) -> String {
         Field::Text(i) => i.to_string(),
         Field::Timestamp(i) => i.to_string(),
         Field::Date(i) => i.to_string(),
-        Field::Binary(_) | Field::Bson(_) => panic!("not supported {:?}", f),
+        Field::Binary(_) | Field::Bson(_) | Field::Json(_) => panic!("not supported {:?}", f),
         Field::Decimal(i) => i.to_string(),
         Field::Null => "null".to_string(),
     }
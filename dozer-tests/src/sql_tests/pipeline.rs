@@ -244,7 +244,8 @@ impl TestPipeline {
         }
     }
     pub fn run(&mut self) -> Result<Schema, ExecutionError> {
-        let mut pipeline = PipelineBuilder {}.build_pipeline(&self.sql).unwrap();
+        let (mut pipeline, (output_node, output_port)) =
+            PipelineBuilder {}.build_pipeline(&self.sql).unwrap();
 
         let schema_holder: Arc<RwLock<SchemaHolder>> =
             Arc::new(RwLock::new(SchemaHolder { schema: None }));
@@ -278,8 +279,8 @@ impl TestPipeline {
 
         pipeline
             .connect_nodes(
-                "aggregation",
-                Some(DEFAULT_PORT_HANDLE),
+                &output_node,
+                Some(output_port),
                 "sink",
                 Some(DEFAULT_PORT_HANDLE),
             )
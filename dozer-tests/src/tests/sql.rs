@@ -59,13 +59,24 @@ fn get_queries() -> Vec<&'static str> {
         "select actor_id, first_name as fn, last_name as ln,last_update from actor where last_name = 'PIPPO'",
      //   "select actor_id, first_name, last_name,last_update from actor where last_name IS NULL",
         "select count(actor_id) from actor",
-         // "select actor_id, first_name, last_name,last_update from actor where actor_id in (1,5)",
+         "select actor_id, first_name, last_name,last_update from actor where actor_id in (1,5)",
+         "select actor_id, first_name, last_name,last_update from actor where actor_id not in (1,5)",
          "select actor_id, first_name, last_name,last_update from actor where first_name='GUINESS'",
+         "select actor_id, first_name || ' ' || last_name as full_name from actor",
+         "select actor_id, first_name, last_name,last_update from actor where first_name LIKE 'GUI%'",
+         "select actor_id, first_name, last_name,last_update from actor where last_name NOT LIKE '_A%'",
          "select actor_id, first_name, last_name,last_update from actor where actor_id<5 and actor_id>2",
          "select actor_id, first_name, last_name,last_update from actor where (actor_id<5 and actor_id>2) or (actor_id>50)",
+         "select actor_id, first_name, last_name,last_update from actor where actor_id between 2 and 5",
+         "select actor_id, first_name, last_name,last_update from actor where actor_id not between 2 and 5",
          "select actor_id from actor order by actor_id",
+         "select distinct last_name from actor",
+         "select actor_id, first_name, last_name,last_update from actor limit 10 offset 5",
          "select actor_id, count(actor_id) from actor group by actor_id",
          "select actor_id, count(actor_id) as counts from actor group by actor_id",
+         "select last_name, count(actor_id), sum(actor_id), min(actor_id), max(actor_id) from actor group by last_name",
+         "select last_name, count(actor_id) from actor group by last_name having count(actor_id) > 1",
+         "select actor_id, first_name from actor where actor_id < 5 union all select actor_id, first_name from actor where actor_id > 195",
     ]
 }
 
@@ -42,6 +42,8 @@ pub enum DeserializationError {
     UnrecognisedFieldType(u8),
     #[error("Bad data length")]
     BadDataLength,
+    #[error("Bad decimal encoding")]
+    BadDecimalEncoding,
     #[error(transparent)]
     BadDateFormat(#[from] chrono::ParseError),
     #[error(transparent)]
@@ -1,20 +1,31 @@
 use crate::errors::types::{DeserializationError, TypeError};
+use crate::models::api_endpoint::ApiJsonOptions;
 use crate::types::DATE_FORMAT;
 use crate::types::{Field, FieldType, Record, Schema};
 use chrono::{DateTime, NaiveDate, SecondsFormat};
 use indexmap::IndexMap;
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use serde_json::Value;
 use std::str::FromStr;
 use std::string::FromUtf8Error;
 /// Used in REST APIs for converting to JSON
 pub fn record_to_map(rec: &Record, schema: &Schema) -> Result<IndexMap<String, Value>, TypeError> {
+    record_to_map_with_options(rec, schema, &ApiJsonOptions::default())
+}
+
+/// Like [`record_to_map`], with the representation of decimals and integers set by `options`.
+pub fn record_to_map_with_options(
+    rec: &Record,
+    schema: &Schema,
+    options: &ApiJsonOptions,
+) -> Result<IndexMap<String, Value>, TypeError> {
     let mut map = IndexMap::new();
 
     for (idx, field_def) in schema.fields.iter().enumerate() {
         if rec.values.len() > idx {
             let field = rec.values[idx].clone();
-            let val = field_to_json_value(field)
+            let val = field_to_json_value(field, options)
                 .map_err(|_| TypeError::InvalidFieldValue("Bson field is not valid utf8".into()))?;
             map.insert(field_def.name.clone(), val);
         }
@@ -26,8 +37,13 @@ pub fn record_to_map(rec: &Record, schema: &Schema) -> Result<IndexMap<String, V
 /// Used in REST APIs for converting raw value back and forth.
 ///
 /// Should be consistent with `convert_cache_type_to_schema_type`.
-fn field_to_json_value(field: Field) -> Result<Value, FromUtf8Error> {
+fn field_to_json_value(field: Field, options: &ApiJsonOptions) -> Result<Value, FromUtf8Error> {
     match field {
+        Field::UInt(n) if options.ints_as_strings => Ok(Value::String(n.to_string())),
+        Field::Int(n) if options.ints_as_strings => Ok(Value::String(n.to_string())),
+        Field::Decimal(n) if options.decimals_as_numbers => Ok(n
+            .to_f64()
+            .map_or_else(|| Value::String(n.to_string()), Value::from)),
         Field::UInt(n) => Ok(Value::from(n)),
         Field::Int(n) => Ok(Value::from(n)),
         Field::Float(n) => Ok(Value::from(n.0)),
@@ -41,6 +57,7 @@ fn field_to_json_value(field: Field) -> Result<Value, FromUtf8Error> {
         )),
         Field::Date(n) => Ok(Value::String(n.format(DATE_FORMAT).to_string())),
         Field::Bson(b) => Ok(Value::from(b)),
+        Field::Json(j) => Ok(j),
         Field::Null => Ok(Value::Null),
     }
 }
@@ -58,6 +75,12 @@ pub fn json_value_to_field(
     }
 
     match (typ, &value) {
+        (FieldType::UInt, Value::String(str)) => u64::from_str(str)
+            .map_err(|e| DeserializationError::Custom(Box::new(e)))
+            .map(Field::UInt),
+        (FieldType::Int, Value::String(str)) => i64::from_str(str)
+            .map_err(|e| DeserializationError::Custom(Box::new(e)))
+            .map(Field::Int),
         (FieldType::UInt, _) => serde_json::from_value(value)
             .map_err(DeserializationError::Json)
             .map(Field::UInt),
@@ -82,6 +105,10 @@ pub fn json_value_to_field(
         (FieldType::Decimal, Value::String(str)) => Decimal::from_str(str)
             .map_err(|e| DeserializationError::Custom(Box::new(e)))
             .map(Field::Decimal),
+        (FieldType::Decimal, Value::Number(number)) => Decimal::from_str(&number.to_string())
+            .or_else(|_| Decimal::from_scientific(&number.to_string()))
+            .map_err(|e| DeserializationError::Custom(Box::new(e)))
+            .map(Field::Decimal),
         (FieldType::Timestamp, Value::String(str)) => DateTime::parse_from_rfc3339(str)
             .map_err(|e| DeserializationError::Custom(Box::new(e)))
             .map(Field::Timestamp),
@@ -91,6 +118,7 @@ pub fn json_value_to_field(
         (FieldType::Bson, _) => serde_json::from_value(value)
             .map_err(DeserializationError::Json)
             .map(Field::Bson),
+        (FieldType::Json, _) => Ok(Field::Json(value)),
         _ => Err(DeserializationError::Custom(
             "Json value type does not match field type"
                 .to_string()
@@ -111,14 +139,18 @@ mod tests {
     use crate::{
         helper::{field_to_json_value, json_value_to_field},
         json_str_to_field,
+        models::api_endpoint::ApiJsonOptions,
         types::{Field, FieldType},
     };
     use chrono::{NaiveDate, Offset, TimeZone, Utc};
     use ordered_float::OrderedFloat;
     use rust_decimal::Decimal;
+    use serde_json::Value;
+    use std::str::FromStr;
+
     fn test_field_conversion(field_type: FieldType, field: Field) {
         // Convert the field to a JSON value.
-        let value = field_to_json_value(field.clone()).unwrap();
+        let value = field_to_json_value(field.clone(), &ApiJsonOptions::default()).unwrap();
 
         // Convert the JSON value back to a Field.
         let deserialized = json_value_to_field(value, field_type, true).unwrap();
@@ -152,6 +184,10 @@ mod tests {
                 ]),
             ),
             (FieldType::Text, Field::Text("lorem ipsum".to_string())),
+            (
+                FieldType::Json,
+                Field::Json(serde_json::json!({"abc": ["foo", 1, true]})),
+            ),
         ];
         for (field_type, field) in fields {
             test_field_conversion(field_type, field);
@@ -166,4 +202,43 @@ mod tests {
         );
         assert!(json_str_to_field("null", FieldType::Int, false).is_err());
     }
+
+    #[test]
+    fn test_json_options_conversion() {
+        let decimal = Field::Decimal(Decimal::from_str("12345678901234567.123456789").unwrap());
+
+        // Decimals are strings by default, which keeps every digit
+        let value = field_to_json_value(decimal.clone(), &ApiJsonOptions::default()).unwrap();
+        assert_eq!(
+            value,
+            Value::String("12345678901234567.123456789".to_string())
+        );
+        assert_eq!(
+            json_value_to_field(value, FieldType::Decimal, false).unwrap(),
+            decimal
+        );
+
+        let options = ApiJsonOptions {
+            decimals_as_numbers: true,
+            ints_as_strings: true,
+        };
+        let value = field_to_json_value(Field::Decimal(Decimal::new(202, 2)), &options).unwrap();
+        assert_eq!(value, serde_json::json!(2.02));
+        assert_eq!(
+            json_value_to_field(value, FieldType::Decimal, false).unwrap(),
+            Field::Decimal(Decimal::new(202, 2))
+        );
+
+        for (field_type, field) in [
+            (FieldType::Int, Field::Int(i64::MIN)),
+            (FieldType::UInt, Field::UInt(u64::MAX)),
+        ] {
+            let value = field_to_json_value(field.clone(), &options).unwrap();
+            assert!(value.is_string());
+            assert_eq!(
+                json_value_to_field(value, field_type, false).unwrap(),
+                field
+            );
+        }
+    }
 }
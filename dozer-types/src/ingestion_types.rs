@@ -6,12 +6,19 @@ use thiserror::Error;
 
 use crate::{
     errors::internal::BoxedError,
-    types::{Commit, OperationEvent},
+    types::{Commit, OperationEvent, Schema},
 };
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum IngestionOperation {
     OperationEvent(OperationEvent),
+    /// The source has no pending changes up to the attached position.
+    Heartbeat(),
+    /// The schema of `table` changed, see [`IngestionMessage::SchemaChange`].
+    SchemaChange {
+        table: String,
+        schema: Schema,
+    },
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -19,12 +26,22 @@ pub enum IngestionMessage {
     Begin(),
     OperationEvent(OperationEvent),
     Commit(Commit),
+    /// The source has no pending changes up to the attached position.
+    Heartbeat(),
+    /// The schema of `table` changed. The following operations on `table` use `schema`, whose
+    /// identifier has a bumped version.
+    SchemaChange {
+        table: String,
+        schema: Schema,
+    },
 }
 
 #[derive(Error, Debug)]
 pub enum IngestorError {
     #[error("Failed to send message on channel")]
     ChannelError(#[from] BoxedError),
+    #[error("Ingestion was stopped")]
+    Stopped,
 }
 
 pub trait IngestorForwarder: Send + Sync + Debug {
@@ -55,6 +72,12 @@ pub struct EthConfig {
     #[prost(message, repeated, tag = "3")]
     #[serde(default)]
     pub contracts: Vec<EthContract>,
+    /// How many times a block range is split in halves when the node refuses to return its logs
+    #[prost(uint32, optional, tag = "5")]
+    pub max_fetch_depth: Option<u32>,
+    /// Block ranges of at most this many blocks are not split any further
+    #[prost(uint64, optional, tag = "6")]
+    pub min_block_range: Option<u64>,
 }
 
 impl EthConfig {
@@ -91,6 +114,13 @@ impl EthConfig {
             table.add_row(row!["filter", filter_table]);
         }
 
+        if let Some(max_fetch_depth) = self.max_fetch_depth {
+            table.add_row(row!["max_fetch_depth", max_fetch_depth]);
+        }
+        if let Some(min_block_range) = self.min_block_range {
+            table.add_row(row!["min_block_range", min_block_range]);
+        }
+
         table
     }
 }
@@ -111,11 +141,20 @@ pub struct KafkaConfig {
     pub broker: String,
     #[prost(string, optional, tag = "3")]
     pub schema_registry_url: Option<String>,
+    /// One of `PLAINTEXT` (default), `SSL`, `SASL_PLAINTEXT` or `SASL_SSL`
+    #[prost(string, optional, tag = "4")]
+    pub security_protocol: Option<String>,
+    #[prost(string, optional, tag = "5")]
+    pub sasl_mechanism: Option<String>,
+    #[prost(string, optional, tag = "6")]
+    pub sasl_username: Option<String>,
+    #[prost(string, optional, tag = "7")]
+    pub sasl_password: Option<String>,
 }
 
 impl KafkaConfig {
     pub fn convert_to_table(&self) -> Table {
-        table!(
+        let mut table = table!(
             ["broker", self.broker],
             [
                 "schema registry url",
@@ -123,7 +162,20 @@ impl KafkaConfig {
                     .as_ref()
                     .map_or("--------", |url| url)
             ]
-        )
+        );
+        if let Some(security_protocol) = &self.security_protocol {
+            table.add_row(row!["security protocol", security_protocol]);
+        }
+        if let Some(sasl_mechanism) = &self.sasl_mechanism {
+            table.add_row(row!["sasl mechanism", sasl_mechanism]);
+        }
+        if let Some(sasl_username) = &self.sasl_username {
+            table.add_row(row!["sasl username", sasl_username]);
+        }
+        if self.sasl_password.is_some() {
+            table.add_row(row!["sasl password", "************"]);
+        }
+        table
     }
 }
 
@@ -145,6 +197,8 @@ pub struct SnowflakeConfig {
     pub warehouse: String,
     #[prost(string, optional, tag = "8")]
     pub driver: Option<String>,
+    #[prost(uint32, optional, tag = "9")]
+    pub poll_interval_seconds: Option<u32>,
 }
 
 impl SnowflakeConfig {
@@ -157,7 +211,12 @@ impl SnowflakeConfig {
             ["database", self.database],
             ["schema", self.schema],
             ["warehouse", self.warehouse],
-            ["driver", self.driver.as_ref().map_or("default", |d| d)]
+            ["driver", self.driver.as_ref().map_or("default", |d| d)],
+            [
+                "poll interval",
+                self.poll_interval_seconds
+                    .map_or("default".to_string(), |s| format!("{}s", s))
+            ]
         )
     }
 }
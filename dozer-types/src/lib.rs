@@ -8,7 +8,9 @@ pub mod models;
 mod tests;
 pub mod types;
 
-pub use helper::{json_str_to_field, json_value_to_field, record_to_map};
+pub use helper::{
+    json_str_to_field, json_value_to_field, record_to_map, record_to_map_with_options,
+};
 
 // Re-exports
 pub use bincode;
@@ -43,6 +43,24 @@ pub struct ApiRest {
     #[prost(bool, tag = "3")]
     #[serde(default = "default_cors")]
     pub cors: bool,
+    #[prost(message, tag = "4")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Request rate limit applied to every endpoint; Default: None
+    pub rate_limit: Option<ApiRateLimit>,
+}
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, prost::Message)]
+pub struct ApiRateLimit {
+    #[prost(uint32, tag = "1")]
+    /// Number of requests a client can make to an endpoint within a window
+    pub max_requests: u32,
+    #[prost(uint64, tag = "2")]
+    #[serde(default = "default_rate_limit_window_secs")]
+    /// Length of the window in seconds; Default: 60
+    pub window_secs: u64,
+    #[prost(bool, tag = "3")]
+    #[serde(default)]
+    /// Count requests per token subject instead of per client IP when the request is authenticated
+    pub per_token: bool,
 }
 #[derive(Serialize, Deserialize, Eq, PartialEq, Clone, prost::Message)]
 pub struct ApiGrpc {
@@ -109,6 +127,7 @@ pub(crate) fn default_api_rest() -> Option<ApiRest> {
         port: default_rest_port(),
         host: default_host(),
         cors: default_cors(),
+        rate_limit: None,
     })
 }
 pub(crate) fn default_api_grpc() -> Option<ApiGrpc> {
@@ -125,6 +144,9 @@ fn default_grpc_port() -> u32 {
 fn default_rest_port() -> u32 {
     8080
 }
+fn default_rate_limit_window_secs() -> u64 {
+    60
+}
 fn default_enable_web() -> bool {
     true
 }
@@ -23,4 +23,22 @@ pub struct ApiEndpoint {
     pub sql: String,
     #[prost(message, tag = "6")]
     pub index: Option<ApiIndex>,
+    #[prost(message, tag = "7")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// how the records of the endpoint are serialized to JSON; Default: decimals as strings
+    pub json_options: Option<ApiJsonOptions>,
+}
+
+/// JSON numbers are read as doubles by most clients, which can't represent every decimal or 64 bit
+/// integer. By default decimals are sent as strings and integers as numbers.
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, ::prost::Message)]
+pub struct ApiJsonOptions {
+    #[prost(bool, tag = "1")]
+    #[serde(default)]
+    /// Send decimals as JSON numbers instead of strings; Default: false
+    pub decimals_as_numbers: bool,
+    #[prost(bool, tag = "2")]
+    #[serde(default)]
+    /// Send integers as strings instead of JSON numbers; Default: false
+    pub ints_as_strings: bool,
 }
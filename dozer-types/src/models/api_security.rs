@@ -3,6 +3,57 @@ use serde::{Deserialize, Serialize};
 #[derive(Serialize, Deserialize, Eq, PartialEq, Clone, ::prost::Oneof, Hash)]
 pub enum ApiSecurity {
     /// Initialize with a JWT_SECRET
+    #[prost(message, tag = "1")]
+    Jwt(JwtSecurity),
+}
+
+/// The secrets used to sign and validate JWTs.
+///
+/// Can be written as just the secret, or as a map that also lists `previous_secrets`.
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, ::prost::Message, Hash)]
+#[serde(from = "JwtSecurityConfig")]
+pub struct JwtSecurity {
+    /// The secret new tokens are signed with
     #[prost(string, tag = "1")]
-    Jwt(String),
+    pub secret: String,
+    /// Secrets that tokens are still accepted with, so that `secret` can be rotated without
+    /// invalidating the tokens signed before
+    #[prost(string, repeated, tag = "2")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub previous_secrets: Vec<String>,
+}
+
+impl JwtSecurity {
+    pub fn new(secret: String) -> Self {
+        Self {
+            secret,
+            previous_secrets: vec![],
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JwtSecurityConfig {
+    Secret(String),
+    Secrets {
+        secret: String,
+        #[serde(default)]
+        previous_secrets: Vec<String>,
+    },
+}
+
+impl From<JwtSecurityConfig> for JwtSecurity {
+    fn from(config: JwtSecurityConfig) -> Self {
+        match config {
+            JwtSecurityConfig::Secret(secret) => JwtSecurity::new(secret),
+            JwtSecurityConfig::Secrets {
+                secret,
+                previous_secrets,
+            } => JwtSecurity {
+                secret,
+                previous_secrets,
+            },
+        }
+    }
 }
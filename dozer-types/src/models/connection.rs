@@ -95,6 +95,11 @@ pub struct PostgresAuthentication {
     pub port: u32,
     #[prost(string, tag = "5")]
     pub database: String,
+    /// LSN to start replication from instead of the pipeline's checkpoint, e.g. `16/B374D848`, to
+    /// reprocess a known window. It can't be before the replication slot's confirmed flush LSN.
+    #[prost(string, optional, tag = "6")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_lsn: Option<String>,
 }
 
 impl PostgresAuthentication {
@@ -26,6 +26,10 @@ pub struct Source {
     #[serde(default = "default_refresh_config")]
     /// setting for how to refresh the data; Default: RealTime
     pub refresh_config: Option<RefreshConfig>,
+    #[prost(string, repeated, tag = "8")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    /// columns identifying a row, instead of the primary key of the source table, if any; Type: String[]
+    pub primary_key_columns: Vec<String>,
 }
 fn default_refresh_config() -> Option<RefreshConfig> {
     Some(RefreshConfig::default())
@@ -35,7 +39,7 @@ impl Serialize for Source {
     where
         S: serde::Serializer,
     {
-        let mut state = serializer.serialize_struct("Source", 5)?;
+        let mut state = serializer.serialize_struct("Source", 6)?;
         state.serialize_field("name", &self.name)?;
         state.serialize_field("table_name", &self.table_name)?;
         state.serialize_field("columns", &self.columns)?;
@@ -44,6 +48,9 @@ impl Serialize for Source {
             &Value::Ref(self.connection.to_owned().unwrap_or_default().name),
         )?;
         state.serialize_field("refresh_config", &self.refresh_config)?;
+        if !self.primary_key_columns.is_empty() {
+            state.serialize_field("primary_key_columns", &self.primary_key_columns)?;
+        }
         state.end()
     }
 }
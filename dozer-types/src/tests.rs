@@ -8,3 +8,5 @@ mod eth_yaml_deserialize;
 mod field_serialize_test;
 #[cfg(test)]
 mod postgres_yaml_deserialize;
+#[cfg(test)]
+mod schema_version_test;
@@ -1,6 +1,6 @@
 use crate::models::{
     api_config::{default_api_grpc, default_api_rest, default_pipeline_internal, ApiGrpc, ApiRest},
-    api_security::ApiSecurity,
+    api_security::{ApiSecurity, JwtSecurity},
     app_config::Config,
 };
 
@@ -25,6 +25,7 @@ fn override_rest_port() {
         port: 9876,
         host: default_api_rest.host,
         cors: default_api_rest.cors,
+        rate_limit: None,
     };
     assert_eq!(api_config.rest.unwrap(), expected_rest_config);
     assert_eq!(api_config.grpc.unwrap(), default_api_grpc);
@@ -50,6 +51,7 @@ fn override_rest_host() {
         port: default_api_rest.port,
         host: "localhost".to_owned(),
         cors: default_api_rest.cors,
+        rate_limit: None,
     };
     assert_eq!(api_config.rest.unwrap(), expected_rest_config);
     assert_eq!(api_config.grpc.unwrap(), default_api_grpc);
@@ -110,6 +112,7 @@ fn override_grpc_and_rest_port() {
         port: 3324,
         host: default_api_rest.host,
         cors: default_api_rest.cors,
+        rate_limit: None,
     };
     assert_eq!(api_config.rest.unwrap(), expected_rest_config);
     assert_eq!(api_config.grpc.unwrap(), expected_grpc_config);
@@ -146,13 +149,14 @@ fn override_grpc_and_rest_port_jwt() {
         port: 3324,
         host: default_api_rest.host,
         cors: default_api_rest.cors,
+        rate_limit: None,
     };
     assert_eq!(api_config.rest.unwrap(), expected_rest_config);
     assert_eq!(api_config.grpc.unwrap(), expected_grpc_config);
     let api_security = api_config.api_security;
     assert!(api_security.is_some());
     let api_security = api_security.unwrap();
-    let expected_api_security = ApiSecurity::Jwt("Vv44T1GugX".to_owned());
+    let expected_api_security = ApiSecurity::Jwt(JwtSecurity::new("Vv44T1GugX".to_owned()));
     assert_eq!(api_security, expected_api_security);
 }
 #[test]
@@ -191,13 +195,14 @@ fn override_grpc_and_rest_port_jwt_pipeline_home_dir() {
         port: 3324,
         host: default_api_rest.host,
         cors: default_api_rest.cors,
+        rate_limit: None,
     };
     assert_eq!(api_config.rest.unwrap(), expected_rest_config);
     assert_eq!(api_config.grpc.unwrap(), expected_grpc_config);
     let api_security = api_config.api_security;
     assert!(api_security.is_some());
     let api_security = api_security.unwrap();
-    let expected_api_security = ApiSecurity::Jwt("Vv44T1GugX".to_owned());
+    let expected_api_security = ApiSecurity::Jwt(JwtSecurity::new("Vv44T1GugX".to_owned()));
     assert_eq!(api_security, expected_api_security);
 
     let pipeline_internal = api_config.pipeline_internal;
@@ -208,3 +213,37 @@ fn override_grpc_and_rest_port_jwt_pipeline_home_dir() {
     assert_eq!(pipeline_internal.port, 3993);
     assert_eq!(pipeline_internal.host, default_pipeline_internal.host);
 }
+
+#[test]
+fn jwt_with_previous_secrets() {
+    let input_config = r#"
+  app_name: working_app
+  api:
+    api_security: !Jwt
+      secret: Vv44T1GugX
+      previous_secrets:
+        - DXkzrlnTy6
+  home_dir: './.dozer'
+"#;
+
+    let config = serde_yaml::from_str::<Config>(input_config).unwrap();
+    let expected_api_security = ApiSecurity::Jwt(JwtSecurity {
+        secret: "Vv44T1GugX".to_owned(),
+        previous_secrets: vec!["DXkzrlnTy6".to_owned()],
+    });
+    assert_eq!(
+        config.api.unwrap().api_security,
+        Some(expected_api_security.clone())
+    );
+
+    // Round trips through the format stored by dozer-admin.
+    let json = serde_json::to_string(&expected_api_security).unwrap();
+    assert_eq!(
+        serde_json::from_str::<ApiSecurity>(&json).unwrap(),
+        expected_api_security
+    );
+    assert_eq!(
+        serde_json::from_str::<ApiSecurity>(r#"{"Jwt":"Vv44T1GugX"}"#).unwrap(),
+        ApiSecurity::Jwt(JwtSecurity::new("Vv44T1GugX".to_owned()))
+    );
+}
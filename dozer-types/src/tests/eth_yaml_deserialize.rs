@@ -24,6 +24,8 @@ fn standard() {
         filter: Some(expected_eth_filter),
         wss_url: "wss://link".to_owned(),
         contracts: vec![],
+        max_fetch_depth: None,
+        min_block_range: None,
     };
     let expected = Authentication::Ethereum(expected_eth_config);
     assert_eq!(expected, deserializer_result);
@@ -48,6 +50,28 @@ fn config_without_empty_array() {
         filter: Some(expected_eth_filter),
         wss_url: "wss://link".to_owned(),
         contracts: vec![],
+        max_fetch_depth: None,
+        min_block_range: None,
+    };
+    let expected = Authentication::Ethereum(expected_eth_config);
+    assert_eq!(expected, deserializer_result);
+}
+
+#[test]
+fn config_with_fetch_limits() {
+    let eth_config = r#"
+  !Ethereum
+  wss_url: wss://link
+  max_fetch_depth: 10
+  min_block_range: 100
+  "#;
+    let deserializer_result = serde_yaml::from_str::<Authentication>(eth_config).unwrap();
+    let expected_eth_config = EthConfig {
+        filter: None,
+        wss_url: "wss://link".to_owned(),
+        contracts: vec![],
+        max_fetch_depth: Some(10),
+        min_block_range: Some(100),
     };
     let expected = Authentication::Ethereum(expected_eth_config);
     assert_eq!(expected, deserializer_result);
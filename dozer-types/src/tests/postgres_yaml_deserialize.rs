@@ -16,6 +16,7 @@ fn standard() {
         host: "localhost".to_owned(),
         port: 5432,
         database: "users".to_owned(),
+        start_lsn: None,
     };
     let expected = Authentication::Postgres(postgres_auth);
     assert_eq!(expected, deserializer_result);
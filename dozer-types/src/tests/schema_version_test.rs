@@ -0,0 +1,52 @@
+use crate::types::{FieldDefinition, FieldType, Schema};
+
+fn schema(fields: &[(&str, FieldType, bool)]) -> Schema {
+    let mut schema = Schema::empty();
+    for (name, typ, nullable) in fields {
+        schema.field(
+            FieldDefinition::new(name.to_string(), *typ, *nullable),
+            false,
+        );
+    }
+    schema
+}
+
+#[test]
+fn test_fields_version() {
+    let version = schema(&[
+        ("id", FieldType::Int, false),
+        ("name", FieldType::String, true),
+    ])
+    .fields_version();
+
+    // Same fields, same version
+    let mut same = schema(&[
+        ("id", FieldType::Int, true),
+        ("name", FieldType::String, false),
+    ]);
+    same.primary_index = vec![0];
+    assert_eq!(same.fields_version(), version);
+
+    for changed in [
+        schema(&[("id", FieldType::Int, false)]),
+        schema(&[
+            ("id", FieldType::Int, false),
+            ("name", FieldType::String, true),
+            ("email", FieldType::String, true),
+        ]),
+        schema(&[
+            ("id", FieldType::Int, false),
+            ("title", FieldType::String, true),
+        ]),
+        schema(&[
+            ("id", FieldType::UInt, false),
+            ("name", FieldType::String, true),
+        ]),
+        schema(&[
+            ("name", FieldType::String, true),
+            ("id", FieldType::Int, false),
+        ]),
+    ] {
+        assert_ne!(changed.fields_version(), version);
+    }
+}
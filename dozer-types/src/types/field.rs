@@ -4,11 +4,17 @@ use ordered_float::OrderedFloat;
 use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 use rust_decimal::Decimal;
 use serde::{self, Deserialize, Serialize};
+use serde_json::Value as JsonValue;
 use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::fmt::{Display, Formatter};
 
 pub const DATE_FORMAT: &str = "%Y-%m-%d";
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, PartialOrd, Ord)]
+/// Version of the byte encoding of [`Field::encode`], bumped whenever the encoding of a type
+/// changes. Stores keyed or sorted by encoded fields record it, and don't read data written with
+/// another version. Stores without a version were written with version 1.
+pub const FIELD_ENCODING_VERSION: u32 = 2;
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Field {
     UInt(u64),
     Int(i64),
@@ -23,9 +29,13 @@ pub enum Field {
     Date(NaiveDate),
     Bson(Vec<u8>),
     Null,
+    /// Appended after `Null` so that the type prefixes and bincode indexes of the other variants
+    /// stay those of already stored data.
+    #[serde(with = "json_as_string")]
+    Json(JsonValue),
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, PartialOrd, Ord)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FieldBorrow<'a> {
     UInt(u64),
     Int(i64),
@@ -40,6 +50,9 @@ pub enum FieldBorrow<'a> {
     Date(NaiveDate),
     Bson(&'a [u8]),
     Null,
+    /// Decoding a JSON field parses it, so it can't be borrowed.
+    #[serde(with = "json_as_string")]
+    Json(JsonValue),
 }
 
 impl Field {
@@ -52,10 +65,11 @@ impl Field {
             Field::String(s) => s.len(),
             Field::Text(s) => s.len(),
             Field::Binary(b) => b.len(),
-            Field::Decimal(_) => 16,
+            Field::Decimal(d) => encode_decimal(d).len(),
             Field::Timestamp(_) => 8,
             Field::Date(_) => 10,
             Field::Bson(b) => b.len(),
+            Field::Json(j) => encode_json(j).len(),
             Field::Null => 0,
         }
     }
@@ -63,16 +77,17 @@ impl Field {
     fn encode_data(&self) -> Cow<[u8]> {
         match self {
             Field::UInt(i) => Cow::Owned(i.to_be_bytes().into()),
-            Field::Int(i) => Cow::Owned(i.to_be_bytes().into()),
-            Field::Float(f) => Cow::Owned(f.to_be_bytes().into()),
+            Field::Int(i) => Cow::Owned(encode_i64(*i).into()),
+            Field::Float(f) => Cow::Owned(encode_f64(*f).into()),
             Field::Boolean(b) => Cow::Owned(if *b { [1] } else { [0] }.into()),
             Field::String(s) => Cow::Borrowed(s.as_bytes()),
             Field::Text(s) => Cow::Borrowed(s.as_bytes()),
             Field::Binary(b) => Cow::Borrowed(b.as_slice()),
-            Field::Decimal(d) => Cow::Owned(d.serialize().into()),
-            Field::Timestamp(t) => Cow::Owned(t.timestamp_millis().to_be_bytes().into()),
+            Field::Decimal(d) => Cow::Owned(encode_decimal(d)),
+            Field::Timestamp(t) => Cow::Owned(encode_i64(t.timestamp_millis()).into()),
             Field::Date(t) => Cow::Owned(t.to_string().into()),
             Field::Bson(b) => Cow::Borrowed(b),
+            Field::Json(j) => Cow::Owned(encode_json(j).into_bytes()),
             Field::Null => Cow::Owned([].into()),
         }
     }
@@ -107,6 +122,7 @@ impl Field {
             Field::Timestamp(t) => FieldBorrow::Timestamp(*t),
             Field::Date(t) => FieldBorrow::Date(*t),
             Field::Bson(b) => FieldBorrow::Bson(b),
+            Field::Json(j) => FieldBorrow::Json(j.clone()),
             Field::Null => FieldBorrow::Null,
         }
     }
@@ -123,24 +139,21 @@ impl Field {
                 val.try_into()
                     .map_err(|_| DeserializationError::BadDataLength)?,
             ))),
-            1 => Ok(FieldBorrow::Int(i64::from_be_bytes(
+            1 => Ok(FieldBorrow::Int(decode_i64(
                 val.try_into()
                     .map_err(|_| DeserializationError::BadDataLength)?,
             ))),
-            2 => Ok(FieldBorrow::Float(OrderedFloat(f64::from_be_bytes(
+            2 => Ok(FieldBorrow::Float(decode_f64(
                 val.try_into()
                     .map_err(|_| DeserializationError::BadDataLength)?,
-            )))),
+            ))),
             3 => Ok(FieldBorrow::Boolean(val[0] == 1)),
             4 => Ok(FieldBorrow::String(std::str::from_utf8(val)?)),
             5 => Ok(FieldBorrow::Text(std::str::from_utf8(val)?)),
             6 => Ok(FieldBorrow::Binary(val)),
-            7 => Ok(FieldBorrow::Decimal(Decimal::deserialize(
-                val.try_into()
-                    .map_err(|_| DeserializationError::BadDataLength)?,
-            ))),
+            7 => Ok(FieldBorrow::Decimal(decode_decimal(val)?)),
             8 => Ok(FieldBorrow::Timestamp(DateTime::from(
-                Utc.timestamp_millis(i64::from_be_bytes(
+                Utc.timestamp_millis(decode_i64(
                     val.try_into()
                         .map_err(|_| DeserializationError::BadDataLength)?,
                 )),
@@ -151,6 +164,7 @@ impl Field {
             )?)),
             10 => Ok(FieldBorrow::Bson(val)),
             11 => Ok(FieldBorrow::Null),
+            12 => Ok(FieldBorrow::Json(serde_json::from_slice(val)?)),
             other => Err(DeserializationError::UnrecognisedFieldType(other)),
         }
     }
@@ -169,6 +183,7 @@ impl Field {
             Field::Date(_) => 9,
             Field::Bson(_) => 10,
             Field::Null => 11,
+            Field::Json(_) => 12,
         }
     }
 
@@ -249,6 +264,13 @@ impl Field {
         }
     }
 
+    pub fn as_json(&self) -> Option<&JsonValue> {
+        match self {
+            Field::Json(j) => Some(j),
+            _ => None,
+        }
+    }
+
     pub fn as_null(&self) -> Option<()> {
         match self {
             Field::Null => Some(()),
@@ -316,6 +338,7 @@ impl Field {
             Field::Date(d) => Some(d.format("%Y-%m-%d").to_string()),
             Field::Timestamp(t) => Some(t.to_rfc3339()),
             Field::Binary(b) => Some(format!("{:X?}", b)),
+            Field::Json(j) => Some(encode_json(j)),
             Field::Null => Some("".to_string()),
             _ => None,
         }
@@ -337,6 +360,7 @@ impl Field {
             Field::Date(d) => Some(d.format("%Y-%m-%d").to_string()),
             Field::Timestamp(t) => Some(t.to_rfc3339()),
             Field::Binary(b) => Some(format!("{:X?}", b)),
+            Field::Json(j) => Some(encode_json(j)),
             Field::Null => Some("".to_string()),
             _ => None,
         }
@@ -384,6 +408,15 @@ impl Field {
         }
     }
 
+    pub fn to_json(&self) -> Option<JsonValue> {
+        match self {
+            Field::Json(j) => Some(j.clone()),
+            Field::String(s) | Field::Text(s) => serde_json::from_str(s).ok(),
+            Field::Bson(b) => serde_json::from_slice(b).ok(),
+            _ => None,
+        }
+    }
+
     pub fn to_null(&self) -> Option<()> {
         match self {
             Field::Null => Some(()),
@@ -406,11 +439,42 @@ impl Display for Field {
             Field::Timestamp(v) => f.write_str(&format!("{}", v)),
             Field::Date(v) => f.write_str(&format!("{}", v)),
             Field::Bson(v) => f.write_str(&format!("{:x?}", v)),
+            Field::Json(v) => f.write_str(&encode_json(v)),
             Field::Null => f.write_str("NULL"),
         }
     }
 }
 
+/// Fields of different types are ordered by their type prefix, so that the order agrees with
+/// the byte order of the encoded fields. JSON values are ordered by their canonical encoding
+/// for the same reason.
+impl Ord for Field {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Field::UInt(a), Field::UInt(b)) => a.cmp(b),
+            (Field::Int(a), Field::Int(b)) => a.cmp(b),
+            (Field::Float(a), Field::Float(b)) => a.cmp(b),
+            (Field::Boolean(a), Field::Boolean(b)) => a.cmp(b),
+            (Field::String(a), Field::String(b)) => a.cmp(b),
+            (Field::Text(a), Field::Text(b)) => a.cmp(b),
+            (Field::Binary(a), Field::Binary(b)) => a.cmp(b),
+            (Field::Decimal(a), Field::Decimal(b)) => a.cmp(b),
+            (Field::Timestamp(a), Field::Timestamp(b)) => a.cmp(b),
+            (Field::Date(a), Field::Date(b)) => a.cmp(b),
+            (Field::Bson(a), Field::Bson(b)) => a.cmp(b),
+            (Field::Json(a), Field::Json(b)) => cmp_json(a, b),
+            (Field::Null, Field::Null) => Ordering::Equal,
+            (a, b) => a.get_type_prefix().cmp(&b.get_type_prefix()),
+        }
+    }
+}
+
+impl PartialOrd for Field {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl<'a> FieldBorrow<'a> {
     pub fn to_owned(self) -> Field {
         match self {
@@ -425,9 +489,239 @@ impl<'a> FieldBorrow<'a> {
             FieldBorrow::Timestamp(t) => Field::Timestamp(t),
             FieldBorrow::Date(d) => Field::Date(d),
             FieldBorrow::Bson(b) => Field::Bson(b.to_owned()),
+            FieldBorrow::Json(j) => Field::Json(j),
             FieldBorrow::Null => Field::Null,
         }
     }
+
+    fn get_type_prefix(&self) -> u8 {
+        match self {
+            FieldBorrow::UInt(_) => 0,
+            FieldBorrow::Int(_) => 1,
+            FieldBorrow::Float(_) => 2,
+            FieldBorrow::Boolean(_) => 3,
+            FieldBorrow::String(_) => 4,
+            FieldBorrow::Text(_) => 5,
+            FieldBorrow::Binary(_) => 6,
+            FieldBorrow::Decimal(_) => 7,
+            FieldBorrow::Timestamp(_) => 8,
+            FieldBorrow::Date(_) => 9,
+            FieldBorrow::Bson(_) => 10,
+            FieldBorrow::Null => 11,
+            FieldBorrow::Json(_) => 12,
+        }
+    }
+}
+
+/// Must agree with the order of `Field`.
+impl<'a> Ord for FieldBorrow<'a> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (FieldBorrow::UInt(a), FieldBorrow::UInt(b)) => a.cmp(b),
+            (FieldBorrow::Int(a), FieldBorrow::Int(b)) => a.cmp(b),
+            (FieldBorrow::Float(a), FieldBorrow::Float(b)) => a.cmp(b),
+            (FieldBorrow::Boolean(a), FieldBorrow::Boolean(b)) => a.cmp(b),
+            (FieldBorrow::String(a), FieldBorrow::String(b)) => a.cmp(b),
+            (FieldBorrow::Text(a), FieldBorrow::Text(b)) => a.cmp(b),
+            (FieldBorrow::Binary(a), FieldBorrow::Binary(b)) => a.cmp(b),
+            (FieldBorrow::Decimal(a), FieldBorrow::Decimal(b)) => a.cmp(b),
+            (FieldBorrow::Timestamp(a), FieldBorrow::Timestamp(b)) => a.cmp(b),
+            (FieldBorrow::Date(a), FieldBorrow::Date(b)) => a.cmp(b),
+            (FieldBorrow::Bson(a), FieldBorrow::Bson(b)) => a.cmp(b),
+            (FieldBorrow::Json(a), FieldBorrow::Json(b)) => cmp_json(a, b),
+            (FieldBorrow::Null, FieldBorrow::Null) => Ordering::Equal,
+            (a, b) => a.get_type_prefix().cmp(&b.get_type_prefix()),
+        }
+    }
+}
+
+impl<'a> PartialOrd for FieldBorrow<'a> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+const SIGN_BIT: u64 = 1 << 63;
+
+/// Offset binary: flipping the sign bit makes the big endian bytes sort in numeric order.
+fn encode_i64(i: i64) -> [u8; 8] {
+    ((i as u64) ^ SIGN_BIT).to_be_bytes()
+}
+
+fn decode_i64(bytes: [u8; 8]) -> i64 {
+    (u64::from_be_bytes(bytes) ^ SIGN_BIT) as i64
+}
+
+/// Sets the sign bit of positive numbers and flips all bits of negative numbers, so that the big
+/// endian bytes sort in numeric order.
+///
+/// `OrderedFloat` considers `-0.0` equal to `0.0`, and all NaNs equal to each other and greater
+/// than everything else, so those are encoded as `0.0` and a positive NaN respectively.
+fn encode_f64(f: OrderedFloat<f64>) -> [u8; 8] {
+    let f = if f.is_nan() {
+        f64::NAN
+    } else if f.0 == 0.0 {
+        0.0
+    } else {
+        f.0
+    };
+    let bits = f.to_bits();
+    let bits = if bits & SIGN_BIT == 0 {
+        bits | SIGN_BIT
+    } else {
+        !bits
+    };
+    bits.to_be_bytes()
+}
+
+fn decode_f64(bytes: [u8; 8]) -> OrderedFloat<f64> {
+    let bits = u64::from_be_bytes(bytes);
+    let bits = if bits & SIGN_BIT == 0 {
+        !bits
+    } else {
+        bits ^ SIGN_BIT
+    };
+    OrderedFloat(f64::from_bits(bits))
+}
+
+const DECIMAL_NEGATIVE: u8 = 0;
+const DECIMAL_ZERO: u8 = 1;
+const DECIMAL_POSITIVE: u8 = 2;
+const DECIMAL_EXPONENT_BIAS: i32 = 128;
+/// Ends the digits of negative decimals, so that shorter digits sort after longer ones.
+const DECIMAL_NEGATIVE_TERMINATOR: u8 = 10;
+
+/// Encodes a decimal as its sign, the position of its most significant digit relative to the
+/// decimal point, and its significant digits, one per byte. For negative decimals the position and
+/// the digits are inverted, and the digits are terminated, so that the bytes sort in numeric order.
+fn encode_decimal(d: &Decimal) -> Vec<u8> {
+    if d.is_zero() {
+        return vec![DECIMAL_ZERO];
+    }
+    let d = d.normalize();
+    let digits = d.mantissa().unsigned_abs().to_string();
+    let exponent = digits.len() as i32 - d.scale() as i32;
+    let digits = digits.trim_end_matches('0');
+
+    let mut result = Vec::with_capacity(digits.len() + 3);
+    if d.is_sign_negative() {
+        result.push(DECIMAL_NEGATIVE);
+        result.push((DECIMAL_EXPONENT_BIAS - exponent) as u8);
+        result.extend(digits.bytes().map(|digit| b'9' - digit));
+        result.push(DECIMAL_NEGATIVE_TERMINATOR);
+    } else {
+        result.push(DECIMAL_POSITIVE);
+        result.push((DECIMAL_EXPONENT_BIAS + exponent) as u8);
+        result.extend(digits.bytes().map(|digit| digit - b'0'));
+    }
+    result
+}
+
+fn decode_decimal(buf: &[u8]) -> Result<Decimal, DeserializationError> {
+    let (sign, rest) = buf
+        .split_first()
+        .ok_or(DeserializationError::BadDataLength)?;
+    if *sign == DECIMAL_ZERO {
+        return Ok(Decimal::ZERO);
+    }
+    let (exponent, digits) = rest
+        .split_first()
+        .ok_or(DeserializationError::BadDataLength)?;
+
+    let (negative, exponent, digits) = match *sign {
+        DECIMAL_POSITIVE => (false, *exponent as i32 - DECIMAL_EXPONENT_BIAS, digits),
+        DECIMAL_NEGATIVE => match digits.split_last() {
+            Some((&DECIMAL_NEGATIVE_TERMINATOR, digits)) => {
+                (true, DECIMAL_EXPONENT_BIAS - *exponent as i32, digits)
+            }
+            _ => return Err(DeserializationError::BadDecimalEncoding),
+        },
+        _ => return Err(DeserializationError::BadDecimalEncoding),
+    };
+
+    let mut mantissa: i128 = 0;
+    for digit in digits {
+        let digit = if negative {
+            9_u8.checked_sub(*digit)
+        } else {
+            Some(*digit).filter(|digit| *digit <= 9)
+        }
+        .ok_or(DeserializationError::BadDecimalEncoding)?;
+        mantissa = mantissa
+            .checked_mul(10)
+            .and_then(|mantissa| mantissa.checked_add(digit as i128))
+            .ok_or(DeserializationError::BadDecimalEncoding)?;
+    }
+    let mut scale = digits.len() as i32 - exponent;
+    while scale < 0 {
+        mantissa = mantissa
+            .checked_mul(10)
+            .ok_or(DeserializationError::BadDecimalEncoding)?;
+        scale += 1;
+    }
+    if negative {
+        mantissa = -mantissa;
+    }
+    Decimal::try_from_i128_with_scale(mantissa, scale as u32)
+        .map_err(|_| DeserializationError::BadDecimalEncoding)
+}
+
+/// Returns the canonical encoding of a JSON value: compact, with the keys of every object sorted,
+/// so that equal values always encode to the same bytes.
+pub fn encode_json(value: &JsonValue) -> String {
+    let mut result = String::new();
+    write_canonical_json(value, &mut result);
+    result
+}
+
+fn write_canonical_json(value: &JsonValue, result: &mut String) {
+    match value {
+        JsonValue::Array(values) => {
+            result.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    result.push(',');
+                }
+                write_canonical_json(value, result);
+            }
+            result.push(']');
+        }
+        JsonValue::Object(map) => {
+            let mut entries = map.iter().collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            result.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    result.push(',');
+                }
+                result.push_str(&JsonValue::String(key.clone()).to_string());
+                result.push(':');
+                write_canonical_json(value, result);
+            }
+            result.push('}');
+        }
+        scalar => result.push_str(&scalar.to_string()),
+    }
+}
+
+fn cmp_json(a: &JsonValue, b: &JsonValue) -> Ordering {
+    encode_json(a).cmp(&encode_json(b))
+}
+
+/// `serde_json::Value` can only be deserialized from self describing formats, so JSON fields are
+/// serialized as their canonical encoding, which bincode can handle too.
+mod json_as_string {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use serde_json::Value as JsonValue;
+
+    pub fn serialize<S: Serializer>(value: &JsonValue, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::encode_json(value))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<JsonValue, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        serde_json::from_str(&string).map_err(D::Error::custom)
+    }
 }
 
 #[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
@@ -443,6 +737,7 @@ pub enum FieldType {
     Timestamp,
     Date,
     Bson,
+    Json,
 }
 
 impl Display for FieldType {
@@ -459,6 +754,7 @@ impl Display for FieldType {
             FieldType::Timestamp => f.write_str("timestamp"),
             FieldType::Date => f.write_str("date"),
             FieldType::Bson => f.write_str("bson"),
+            FieldType::Json => f.write_str("json"),
         }
     }
 }
@@ -467,10 +763,12 @@ impl Display for FieldType {
 /// and we need this function in `dozer-cache`.
 pub fn field_test_cases() -> impl Iterator<Item = Field> {
     [
+        Field::Int(-1_i64),
         Field::Int(0_i64),
         Field::Int(1_i64),
         Field::UInt(0_u64),
         Field::UInt(1_u64),
+        Field::Float(OrderedFloat::from(-1_f64)),
         Field::Float(OrderedFloat::from(0_f64)),
         Field::Float(OrderedFloat::from(1_f64)),
         Field::Boolean(true),
@@ -481,8 +779,10 @@ pub fn field_test_cases() -> impl Iterator<Item = Field> {
         Field::Text("1".to_string()),
         Field::Binary(vec![]),
         Field::Binary(vec![1]),
+        Field::Decimal(Decimal::new(-1, 0)),
         Field::Decimal(Decimal::new(0, 0)),
         Field::Decimal(Decimal::new(1, 0)),
+        Field::Timestamp(DateTime::from(Utc.timestamp_millis(-1))),
         Field::Timestamp(DateTime::from(Utc.timestamp_millis(0))),
         Field::Timestamp(DateTime::parse_from_rfc3339("2020-01-01T00:00:00Z").unwrap()),
         Field::Date(NaiveDate::from_ymd(1970, 1, 1)),
@@ -492,6 +792,8 @@ pub fn field_test_cases() -> impl Iterator<Item = Field> {
             123, 34, 97, 98, 99, 34, 58, 34, 102, 111, 111, 34, 125,
         ]),
         Field::Null,
+        Field::Json(JsonValue::Null),
+        Field::Json(serde_json::json!({"abc": ["foo", 1, true]})),
     ]
     .into_iter()
 }
@@ -500,6 +802,128 @@ pub fn field_test_cases() -> impl Iterator<Item = Field> {
 pub mod tests {
     use super::*;
 
+    #[test]
+    fn null_encoding_is_unchanged_by_json() {
+        assert_eq!(Field::Null.encode(), vec![11]);
+        assert_eq!(bincode::serialize(&Field::Null).unwrap(), vec![11, 0, 0, 0]);
+        assert_eq!(Field::Json(JsonValue::Null).encode()[0], 12);
+    }
+
+    #[test]
+    fn json_encoding_is_canonical() {
+        let a = Field::Json(serde_json::json!({"b": 1, "a": [true, null]}));
+        let b = Field::Json(serde_json::from_str(r#"{ "a": [true, null], "b": 1 }"#).unwrap());
+        assert_eq!(a.encode(), b.encode());
+        assert_eq!(a.encode()[1..], *br#"{"a":[true,null],"b":1}"#);
+    }
+
+    #[test]
+    fn order_must_agree_with_encoding() {
+        let fields = field_test_cases().collect::<Vec<_>>();
+        for a in &fields {
+            for b in &fields {
+                assert_eq!(a.cmp(b), a.encode().cmp(&b.encode()), "{:?} and {:?}", a, b);
+                assert_eq!(a.borrow().cmp(&b.borrow()), a.cmp(b));
+            }
+        }
+    }
+
+    /// Deterministic xorshift sequence, so that the encoding tests cover values across the range.
+    fn pseudo_random_u64s(count: usize) -> impl Iterator<Item = u64> {
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        (0..count).map(move |_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        })
+    }
+
+    fn check_encoding_preserves_order(fields: &[Field]) {
+        for a in fields {
+            let encoded = a.encode();
+            assert_eq!(&Field::decode(&encoded).unwrap(), a);
+            for b in fields {
+                assert_eq!(a.cmp(b), encoded.cmp(&b.encode()), "{:?} and {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn int_encoding_preserves_order() {
+        let fields = [
+            i64::MIN,
+            i64::MIN + 1,
+            -256,
+            -1,
+            0,
+            1,
+            256,
+            i64::MAX - 1,
+            i64::MAX,
+        ]
+        .into_iter()
+        .chain(pseudo_random_u64s(200).map(|n| n as i64))
+        .map(Field::Int)
+        .collect::<Vec<_>>();
+        check_encoding_preserves_order(&fields);
+    }
+
+    #[test]
+    fn float_encoding_preserves_order() {
+        let fields = [
+            f64::NEG_INFINITY,
+            f64::MIN,
+            -1.5,
+            -f64::MIN_POSITIVE,
+            -0.0,
+            0.0,
+            f64::MIN_POSITIVE,
+            1.5,
+            f64::MAX,
+            f64::INFINITY,
+            f64::NAN,
+            -f64::NAN,
+        ]
+        .into_iter()
+        .chain(pseudo_random_u64s(200).map(f64::from_bits))
+        .map(|f| Field::Float(OrderedFloat(f)))
+        .collect::<Vec<_>>();
+        check_encoding_preserves_order(&fields);
+    }
+
+    #[test]
+    fn decimal_encoding_preserves_order() {
+        let mut random = pseudo_random_u64s(400);
+        let random_decimals = std::iter::from_fn(|| {
+            let high = random.next()?;
+            let low = random.next()?;
+            // An arithmetic shift keeps the mantissa within 96 bits.
+            let mantissa = (((high as i128) << 64) | low as i128) >> 32;
+            Some(Decimal::from_i128_with_scale(mantissa, (low % 29) as u32))
+        });
+        let fields = [
+            Decimal::MIN,
+            Decimal::new(-12, 0),
+            Decimal::new(-123, 2),
+            Decimal::new(-12, 1),
+            Decimal::new(-1200, 3),
+            Decimal::new(-1, 28),
+            Decimal::ZERO,
+            Decimal::new(0, 5),
+            Decimal::new(1, 28),
+            Decimal::new(12, 1),
+            Decimal::new(123, 2),
+            Decimal::new(100, 0),
+            Decimal::MAX,
+        ]
+        .into_iter()
+        .chain(random_decimals)
+        .map(Field::Decimal)
+        .collect::<Vec<_>>();
+        check_encoding_preserves_order(&fields);
+    }
+
     #[test]
     fn data_encoding_len_must_agree_with_encode() {
         for field in field_test_cases() {
@@ -6,7 +6,10 @@ use serde::{self, Deserialize, Serialize};
 
 mod field;
 
-pub use field::{field_test_cases, Field, FieldBorrow, FieldType, DATE_FORMAT};
+pub use field::{
+    encode_json, field_test_cases, Field, FieldBorrow, FieldType, DATE_FORMAT,
+    FIELD_ENCODING_VERSION,
+};
 
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub struct FieldDefinition {
@@ -49,11 +52,24 @@ pub struct Schema {
     pub primary_index: Vec<usize>,
 }
 
+/// How the primary key fields of a record are ordered in its key.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum KeyMode {
+    /// In the order of `Schema::primary_index`.
+    Position,
+    /// In the order of the field names, so that keys stay the same when the schema's columns are
+    /// reordered.
+    FieldName,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub enum ReplicationChangesTrackingType {
     FullChanges,
     OnlyPK,
     Nothing,
+    /// Operations are forwarded as they come, for tables which only receive inserts or whose
+    /// records have no key, so old records are never looked up and no state is kept.
+    AppendOnly,
 }
 
 pub type SchemaWithChangesType = (String, Schema, ReplicationChangesTrackingType);
@@ -87,6 +103,36 @@ impl Schema {
         }
     }
 
+    /// Returns the indexes of the primary key fields, in the order `mode` puts them in keys.
+    pub fn get_primary_key_indexes(&self, mode: KeyMode) -> Vec<usize> {
+        let mut indexes = self.primary_index.clone();
+        if mode == KeyMode::FieldName {
+            indexes.sort_by(|a, b| self.fields[*a].name.cmp(&self.fields[*b].name));
+        }
+        indexes
+    }
+
+    /// Version of the schema derived from the names and types of its fields, so that it changes
+    /// when a field is added, removed, renamed or retyped. Nullability and the primary index don't
+    /// change what a record holds, and aren't part of it.
+    ///
+    /// The hash is computed by hand (FNV-1a) rather than with `DefaultHasher`, whose output isn't
+    /// guaranteed to be the same across Rust releases, as versions are persisted in caches.
+    pub fn fields_version(&self) -> u16 {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+        const FNV_PRIME: u64 = 0x100000001b3;
+
+        let mut hash = FNV_OFFSET_BASIS;
+        for field in &self.fields {
+            let typ = field.typ.to_string();
+            for byte in field.name.bytes().chain([0]).chain(typ.bytes()).chain([0]) {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        (hash ^ (hash >> 16) ^ (hash >> 32) ^ (hash >> 48)) as u16
+    }
+
     pub fn print(&self) -> Table {
         let mut table = Table::new();
         table.add_row(row!["Field", "Type", "Nullable"]);